@@ -148,9 +148,11 @@ pub mod vault {
 
 #[cfg(feature = "ockam_transport_tcp")]
 pub use ockam_transport_tcp::{
-    TcpConnectionOptions, TcpInletOptions, TcpListenerOptions, TcpOutletOptions, TcpTransport,
-    TcpTransportExtension,
+    TcpConnectionOptions, TcpInletOptions, TcpKeepaliveOptions, TcpListenerOptions,
+    TcpOutletOptions, TcpTransport, TcpTransportExtension,
 };
+#[cfg(feature = "tls")]
+pub use ockam_transport_tcp::{TcpTlsClientOptions, TcpTlsServerOptions};
 
 /// List of all top-level services
 pub mod node;
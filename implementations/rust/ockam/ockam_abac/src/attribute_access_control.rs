@@ -11,7 +11,7 @@ use tracing as log;
 
 use crate::expr::str;
 use crate::Expr::*;
-use crate::{eval, Env, Expr};
+use crate::{evaluate, AccessDecision, DenyReason, Env, Expr, Namespace};
 use ockam_core::compat::format;
 use ockam_core::compat::string::ToString;
 use ockam_core::compat::sync::Arc;
@@ -69,10 +69,26 @@ where {
 impl AbacAccessControl {
     /// Returns true if the identity is authorized
     pub async fn is_identity_authorized(&self, id: IdentityIdentifier) -> Result<bool> {
+        Ok(self.decide_for_identity(id).await?.allowed)
+    }
+
+    /// Return the environment used to evaluate the policy expression for the identity:
+    /// the environment of this access control together with the identity attributes.
+    ///
+    /// The attributes of an entry attested by an authority are bound in the
+    /// [`Namespace::Credential`] namespace, the other ones in the [`Namespace::Local`]
+    /// namespace, and all of them as `subject.<name>` following the precedence of the
+    /// namespaces. The connection attributes are bound by [`AbacAccessControl::decide`].
+    pub async fn environment_for_identity(&self, id: &IdentityIdentifier) -> Result<Env> {
         let mut environment = self.environment.clone();
 
         // Get identity attributes and populate the environment:
-        if let Some(attrs) = self.repository.get_attributes(&id).await? {
+        if let Some(attrs) = self.repository.get_attributes(id).await? {
+            let namespace = if attrs.attested_by().is_some() {
+                Namespace::Credential
+            } else {
+                Namespace::Local
+            };
             for (key, value) in attrs.attrs() {
                 if key.find(|c: char| c.is_whitespace()).is_some() {
                     log::warn! {
@@ -84,7 +100,7 @@ impl AbacAccessControl {
                 }
                 match str::from_utf8(value) {
                     Ok(s) => {
-                        if environment.contains(key) {
+                        if environment.contains(&namespace.ident(key)) {
                             log::debug! {
                                 policy = %self.expression,
                                 id     = %id,
@@ -92,7 +108,7 @@ impl AbacAccessControl {
                                 "attribute already present"
                             }
                         } else {
-                            environment.put(format!("subject.{key}"), str(s.to_string()));
+                            environment.put_attribute(namespace, key, str(s.to_string()));
                         }
                     }
                     Err(e) => {
@@ -110,44 +126,49 @@ impl AbacAccessControl {
 
         // add the identifier itself as a subject parameter
         environment.put("subject.identifier", str(id.to_string()));
+        Ok(environment)
+    }
 
-        // Finally, evaluate the expression and return the result:
-        match eval(&self.expression, &environment) {
-            Ok(Expr::Bool(b)) => {
-                log::debug! {
-                    policy        = %self.expression,
-                    id            = %id,
-                    is_authorized = %b,
-                    "policy evaluated"
-                }
-                Ok(b)
-            }
-            Ok(x) => {
-                log::warn! {
-                    policy = %self.expression,
-                    id     = %id,
-                    expr   = %x,
-                    "evaluation did not yield a boolean result"
-                }
-                Ok(false)
-            }
-            Err(e) => {
+    /// Evaluate the policy expression for the identity and return the decision
+    /// together with the reason for a denial
+    pub async fn decide_for_identity(&self, id: IdentityIdentifier) -> Result<AccessDecision> {
+        let environment = self.environment_for_identity(&id).await?;
+        self.decide_in(&id, &environment)
+    }
+
+    /// Evaluate the policy expression in the environment of an identity
+    fn decide_in(&self, id: &IdentityIdentifier, environment: &Env) -> Result<AccessDecision> {
+        let decision = evaluate(&self.expression, environment)?;
+        match &decision.reason {
+            Some(DenyReason::EvaluationError(e)) => {
                 log::warn! {
                     policy = %self.expression,
                     id     = %id,
                     err    = %e,
                     "policy evaluation failed"
                 }
-                Ok(false)
+            }
+            _ => {
+                log::debug! {
+                    policy        = %self.expression,
+                    id            = %id,
+                    is_authorized = %decision.allowed,
+                    "policy evaluated"
+                }
             }
         }
+        Ok(decision)
     }
 }
 
-#[async_trait]
-impl IncomingAccessControl for AbacAccessControl {
-    /// Returns true if the sender of the message is validated by the expression stored in AbacAccessControl
-    async fn is_authorized(&self, msg: &RelayMessage) -> Result<bool> {
+impl AbacAccessControl {
+    /// Evaluate the policy expression for the sender of the message.
+    ///
+    /// Besides the attributes of the sender identity, the attributes of the connection which
+    /// delivered the message are bound in the [`Namespace::Connection`] namespace:
+    /// `conn.channel`, the address of the secure channel, and `conn.route`, the route back
+    /// to the sender
+    pub async fn decide(&self, msg: &RelayMessage) -> Result<AccessDecision> {
         // Get identity identifier from message metadata:
         let id = if let Ok(info) = IdentitySecureChannelLocalInfo::find_info(msg.local_message()) {
             info.their_identity_id()
@@ -156,9 +177,95 @@ impl IncomingAccessControl for AbacAccessControl {
                 policy = %self.expression,
                 "identity identifier not found; access denied"
             }
-            return Ok(false);
+            return Ok(AccessDecision::deny(DenyReason::EvaluationError(
+                "identity identifier not found".to_string(),
+            )));
         };
 
-        self.is_identity_authorized(id).await
+        let mut environment = self.environment_for_identity(&id).await?;
+        environment
+            .put_attribute(
+                Namespace::Connection,
+                "channel",
+                str(msg.source().to_string()),
+            )
+            .put_attribute(
+                Namespace::Connection,
+                "route",
+                str(msg.return_route().to_string()),
+            );
+        self.decide_in(&id, &environment)
+    }
+}
+
+#[async_trait]
+impl IncomingAccessControl for AbacAccessControl {
+    /// Returns true if the sender of the message is validated by the expression stored in AbacAccessControl
+    async fn is_authorized(&self, msg: &RelayMessage) -> Result<bool> {
+        let decision = match self.decide(msg).await {
+            Ok(decision) => decision,
+            Err(e) => {
+                log::warn! {
+                    policy      = %self.expression,
+                    destination = %msg.destination(),
+                    err         = %e,
+                    "failed to evaluate the policy {} for a message to {}: {e}",
+                    self.expression, msg.destination()
+                }
+                return Err(e);
+            }
+        };
+        if let Some(reason) = &decision.reason {
+            log::debug! {
+                policy      = %self.expression,
+                destination = %msg.destination(),
+                reason      = %reason.code(),
+                "the policy {} denied a message to {}: {reason}",
+                self.expression, msg.destination()
+            }
+        }
+        Ok(decision.allowed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use ockam_core::compat::vec::Vec;
+    use ockam_core::{route, Address, LocalMessage, TransportMessage};
+    use ockam_identity::{IdentitiesStorage, IdentityAttributesWriter};
+
+    #[tokio::test]
+    async fn the_connection_attributes_are_bound_when_deciding_for_a_message() -> Result<()> {
+        let repository = IdentitiesStorage::create();
+        let id = IdentityIdentifier::try_from(
+            "P6474cfdbf547240b6d716bff89c976810859bc3f47be8ea620df12a392ea6cb7",
+        )?;
+        repository.put_attribute_value(&id, "role", "admin").await?;
+
+        let message = |channel: &str| -> Result<RelayMessage> {
+            let local_info = IdentitySecureChannelLocalInfo::mark(Vec::new(), id.clone())?;
+            let transport =
+                TransportMessage::v1(route!["outlet"], route![channel, "inlet"], vec![]);
+            Ok(RelayMessage::new(
+                Address::from_string(channel),
+                Address::from_string("outlet"),
+                LocalMessage::new(transport, local_info),
+            ))
+        };
+
+        let expression = parse(
+            r#"(and (= subject.role "admin") (= conn.channel "channel") (= conn.route "0#channel => 0#inlet"))"#,
+        )
+        .unwrap()
+        .unwrap();
+        let access_control = AbacAccessControl::new(repository, expression, Env::new());
+        assert!(access_control.decide(&message("channel")?).await?.allowed);
+        assert!(access_control.is_authorized(&message("channel")?).await?);
+
+        let decision = access_control.decide(&message("other")?).await?;
+        assert_eq!(decision, AccessDecision::deny(DenyReason::PolicyFalse));
+        Ok(())
     }
 }
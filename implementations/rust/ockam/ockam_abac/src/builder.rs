@@ -0,0 +1,270 @@
+use crate::expr::{ident, seq, str, subject_attr, when, Expr};
+use core::fmt;
+use core::ops::Not;
+use ockam_core::compat::string::String;
+use ockam_core::compat::vec::Vec;
+
+/// Builder of policy expressions, combining conditions instead of writing the text of a policy.
+///
+/// Each operator of the policy language has a constructor, and the conditions are combined
+/// with [`Policy::and`], [`Policy::or`] and `!`:
+///
+/// ```
+/// use ockam_abac::expr::{int, subject_attr};
+/// use ockam_abac::Policy;
+///
+/// let policy = Policy::require_attr("role", "admin")
+///     .and(Policy::gt(subject_attr("age"), int(17)))
+///     .or(!Policy::has_attr("guest"));
+/// assert_eq!(
+///     policy.to_string(),
+///     r#"(or (and (= subject.role "admin") (> subject.age 17)) (not (exists? subject.guest)))"#
+/// );
+/// ```
+///
+/// The operands are expressions, built with the functions of [`crate::expr`], and the
+/// attributes of the subject are referenced with [`subject_attr`].
+#[derive(Debug, Clone)]
+pub struct Policy(Expr);
+
+impl Policy {
+    /// A policy which is always true
+    pub fn allow_all() -> Self {
+        Self(Expr::Bool(true))
+    }
+
+    /// A policy which is always false
+    pub fn deny_all() -> Self {
+        Self(Expr::Bool(false))
+    }
+
+    /// Require the subject to have an attribute with a given value: `(= subject.<name> "<value>")`
+    pub fn require_attr(name: &str, value: impl Into<String>) -> Self {
+        Self::eq(subject_attr(name), str(value))
+    }
+
+    /// Require the subject to have an attribute, whatever its value: `(exists? subject.<name>)`
+    pub fn has_attr(name: &str) -> Self {
+        Self::exists([subject_attr(name)])
+    }
+
+    /// Require some identifiers to be defined: `(exists? <ident> ...)`
+    pub fn exists(idents: impl IntoIterator<Item = Expr>) -> Self {
+        Self::op("exists?", idents)
+    }
+
+    /// `(= <a> <b>)`
+    pub fn eq(a: impl Into<Expr>, b: impl Into<Expr>) -> Self {
+        Self::op("=", [a.into(), b.into()])
+    }
+
+    /// `(!= <a> <b>)`
+    pub fn ne(a: impl Into<Expr>, b: impl Into<Expr>) -> Self {
+        Self::op("!=", [a.into(), b.into()])
+    }
+
+    /// `(< <a> <b>)`
+    pub fn lt(a: impl Into<Expr>, b: impl Into<Expr>) -> Self {
+        Self::op("<", [a.into(), b.into()])
+    }
+
+    /// `(> <a> <b>)`
+    pub fn gt(a: impl Into<Expr>, b: impl Into<Expr>) -> Self {
+        Self::op(">", [a.into(), b.into()])
+    }
+
+    /// Require a value to be one of some values: `(member? <value> [<values> ...])`
+    pub fn member(value: impl Into<Expr>, values: impl IntoIterator<Item = Expr>) -> Self {
+        Self::op("member?", [value.into(), seq(values)])
+    }
+
+    /// Require a sequence, for example an attribute, to contain a value:
+    /// `(contains <values> <value>)`
+    pub fn contains(values: impl Into<Expr>, value: impl Into<Expr>) -> Self {
+        Self::op("contains", [values.into(), value.into()])
+    }
+
+    /// Require all the values of a sequence to be in another one: `(subset <values> <of>)`
+    pub fn subset(values: impl Into<Expr>, of: impl Into<Expr>) -> Self {
+        Self::op("subset", [values.into(), of.into()])
+    }
+
+    /// Evaluate one of two policies depending on a test: `(if <test> <then> <orelse>)`
+    pub fn when(test: Policy, then: Policy, orelse: Policy) -> Self {
+        Self(when(test.0, then.0, orelse.0))
+    }
+
+    /// Require all the policies to be true. An empty list of policies is true
+    pub fn all(policies: impl IntoIterator<Item = Policy>) -> Self {
+        Self::op("and", policies.into_iter().map(|p| p.0))
+    }
+
+    /// Require one of the policies to be true. An empty list of policies is false
+    pub fn any(policies: impl IntoIterator<Item = Policy>) -> Self {
+        Self::op("or", policies.into_iter().map(|p| p.0))
+    }
+
+    /// Require both policies to be true. Successive conjunctions are flattened
+    pub fn and(self, other: Policy) -> Self {
+        self.combine("and", other)
+    }
+
+    /// Require one of the policies to be true. Successive disjunctions are flattened
+    pub fn or(self, other: Policy) -> Self {
+        self.combine("or", other)
+    }
+
+    /// Return the expression of the policy
+    pub fn build(self) -> Expr {
+        self.0
+    }
+
+    fn op(op: &str, args: impl IntoIterator<Item = Expr>) -> Self {
+        let mut xs = Vec::from_iter([ident(op)]);
+        xs.extend(args);
+        Self(Expr::List(xs))
+    }
+
+    fn combine(self, op: &str, other: Policy) -> Self {
+        match self.0 {
+            Expr::List(mut xs) if matches!(xs.first(), Some(Expr::Ident(o)) if o == op) => {
+                xs.push(other.0);
+                Self(Expr::List(xs))
+            }
+            expr => Self::op(op, [expr, other.0]),
+        }
+    }
+}
+
+impl Not for Policy {
+    type Output = Policy;
+
+    /// `(not <policy>)`
+    fn not(self) -> Self::Output {
+        Self::op("not", [self.0])
+    }
+}
+
+impl From<Expr> for Policy {
+    fn from(expr: Expr) -> Self {
+        Self(expr)
+    }
+}
+
+impl From<Policy> for Expr {
+    fn from(policy: Policy) -> Self {
+        policy.0
+    }
+}
+
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Policy;
+    use crate::expr::{int, seq, str, subject_attr};
+    use crate::{eval, parser::parse, Env};
+
+    #[test]
+    fn a_built_policy_is_evaluated_like_its_text() {
+        let policy = Policy::require_attr("role", "admin")
+            .and(Policy::gt(subject_attr("age"), int(17)))
+            .and(Policy::lt(subject_attr("age"), int(65)))
+            .or(Policy::all([
+                Policy::has_attr("guest"),
+                Policy::lt(subject_attr("level"), int(3)),
+                Policy::gt(subject_attr("level"), int(0)),
+                !Policy::eq(subject_attr("name"), str("mallory")),
+                Policy::ne(subject_attr("name"), str("eve")),
+            ]))
+            .or(Policy::member(
+                subject_attr("team"),
+                [str("blue"), str("red")],
+            ))
+            .or(Policy::contains(subject_attr("groups"), str("ops"))
+                .and(Policy::subset(seq([str("ops")]), subject_attr("groups"))))
+            .or(Policy::when(
+                Policy::exists([subject_attr("trusted")]),
+                Policy::allow_all(),
+                Policy::any([Policy::deny_all()]),
+            ));
+        let text = r#"(or
+              (and (= subject.role "admin") (> subject.age 17) (< subject.age 65))
+              (and (exists? subject.guest) (< subject.level 3) (> subject.level 0)
+                   (not (= subject.name "mallory")) (!= subject.name "eve"))
+              (member? subject.team ["blue" "red"])
+              (and (contains subject.groups "ops") (subset ["ops"] subject.groups))
+              (if (exists? subject.trusted) true (or false)))"#;
+        let parsed = parse(text).unwrap().unwrap();
+        let built = policy.build();
+        assert_eq!(built.to_string(), parsed.to_string());
+
+        let envs = [
+            Env::new()
+                .with("subject.role", str("admin"))
+                .with("subject.age", int(30)),
+            Env::new()
+                .with("subject.role", str("admin"))
+                .with("subject.age", int(70))
+                .with("subject.team", str("green"))
+                .with("subject.groups", seq([str("dev")])),
+            Env::new()
+                .with("subject.role", str("dev"))
+                .with("subject.guest", str("yes"))
+                .with("subject.level", int(1))
+                .with("subject.name", str("alice")),
+            Env::new()
+                .with("subject.role", str("dev"))
+                .with("subject.guest", str("yes"))
+                .with("subject.level", int(1))
+                .with("subject.name", str("mallory"))
+                .with("subject.team", str("red")),
+            Env::new()
+                .with("subject.role", str("dev"))
+                .with("subject.team", str("green"))
+                .with("subject.groups", seq([str("dev"), str("ops")])),
+            Env::new()
+                .with("subject.role", str("dev"))
+                .with("subject.team", str("green"))
+                .with("subject.groups", seq([str("dev")]))
+                .with("subject.trusted", str("yes")),
+            Env::new()
+                .with("subject.role", str("dev"))
+                .with("subject.team", str("green"))
+                .with("subject.groups", seq([str("dev")])),
+        ];
+        let results: Vec<bool> = envs
+            .iter()
+            .map(|env| {
+                let expected = eval(&parsed, env).unwrap();
+                let actual = eval(&built, env).unwrap();
+                assert!(actual.equals(&expected).unwrap(), "{env:?}");
+                actual.is_true()
+            })
+            .collect();
+        // the environments cover both outcomes of the policy
+        assert_eq!(results, vec![true, false, true, true, true, true, false]);
+    }
+
+    #[test]
+    fn the_conditions_are_flattened() {
+        let policy = Policy::has_attr("a")
+            .and(Policy::has_attr("b"))
+            .and(Policy::has_attr("c"));
+        assert_eq!(
+            policy.to_string(),
+            "(and (exists? subject.a) (exists? subject.b) (exists? subject.c))"
+        );
+        let policy = Policy::has_attr("a")
+            .or(Policy::has_attr("b"))
+            .and(Policy::has_attr("c"));
+        assert_eq!(
+            policy.to_string(),
+            "(and (or (exists? subject.a) (exists? subject.b)) (exists? subject.c))"
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use core::fmt;
+use ockam_core::compat::string::String;
+
+/// The outcome of evaluating a policy for an access request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessDecision {
+    pub allowed: bool,
+    /// Set when access is denied. Always `None` if `allowed` is true.
+    pub reason: Option<DenyReason>,
+}
+
+/// Machine-readable reason explaining why access was denied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DenyReason {
+    /// No policy exists for the resource and action.
+    NoPolicy,
+    /// A policy exists and it evaluated to `false`.
+    PolicyFalse,
+    /// The policy could not be evaluated to a boolean.
+    EvaluationError(String),
+}
+
+impl AccessDecision {
+    pub fn allow() -> Self {
+        Self {
+            allowed: true,
+            reason: None,
+        }
+    }
+
+    pub fn deny(reason: DenyReason) -> Self {
+        Self {
+            allowed: false,
+            reason: Some(reason),
+        }
+    }
+
+    pub fn is_allowed(&self) -> bool {
+        self.allowed
+    }
+}
+
+impl From<bool> for AccessDecision {
+    fn from(b: bool) -> Self {
+        if b {
+            AccessDecision::allow()
+        } else {
+            AccessDecision::deny(DenyReason::PolicyFalse)
+        }
+    }
+}
+
+impl DenyReason {
+    /// A short, stable code for this reason, suitable for logs and metrics.
+    pub fn code(&self) -> &'static str {
+        match self {
+            DenyReason::NoPolicy => "no_policy",
+            DenyReason::PolicyFalse => "policy_false",
+            DenyReason::EvaluationError(_) => "evaluation_error",
+        }
+    }
+}
+
+impl fmt::Display for DenyReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DenyReason::NoPolicy => f.write_str("no policy found"),
+            DenyReason::PolicyFalse => f.write_str("policy evaluated to false"),
+            DenyReason::EvaluationError(e) => write!(f, "policy evaluation failed: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccessDecision, DenyReason};
+
+    #[test]
+    fn from_bool() {
+        assert_eq!(AccessDecision::from(true), AccessDecision::allow());
+        let d = AccessDecision::from(false);
+        assert!(!d.is_allowed());
+        assert_eq!(Some(DenyReason::PolicyFalse), d.reason)
+    }
+}
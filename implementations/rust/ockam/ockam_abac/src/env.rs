@@ -1,19 +1,125 @@
 use crate::error::{EvalError, MergeError};
 use crate::expr::Expr;
 use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::format;
 use ockam_core::compat::string::{String, ToString};
 
+/// Source of the attributes of a subject.
+///
+/// The attributes of each source are bound as `<namespace>.<name>`, for example
+/// `credential.role` or `conn.ip`, so that a policy can tell apart attributes with the
+/// same name coming from different sources.
+///
+/// For compatibility with the existing policies, an attribute is also bound unqualified,
+/// as `subject.<name>`, to its value in the namespace with the highest precedence. The
+/// namespaces are declared by decreasing precedence: credential, local, then connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Namespace {
+    /// Attributes attested by an authority, with a credential
+    Credential,
+    /// Attributes stored on the node without an attester
+    Local,
+    /// Attributes of the connection used by the subject, see [`crate::AbacAccessControl::decide`]
+    Connection,
+}
+
+impl Namespace {
+    /// All the namespaces, by decreasing precedence
+    pub const PRECEDENCE: [Namespace; 3] = [
+        Namespace::Credential,
+        Namespace::Local,
+        Namespace::Connection,
+    ];
+
+    /// Prefix of the identifiers of the namespace
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Namespace::Credential => "credential",
+            Namespace::Local => "local",
+            Namespace::Connection => "conn",
+        }
+    }
+
+    /// Identifier of an attribute in the namespace: `<namespace>.<name>`
+    pub fn ident(&self, name: &str) -> String {
+        format!("{}.{name}", self.prefix())
+    }
+
+    /// Identifier of an attribute bound to its value in the namespace with the highest
+    /// precedence: `subject.<name>`
+    pub fn subject_ident(name: &str) -> String {
+        format!("subject.{name}")
+    }
+
+    /// Split a namespaced identifier into its namespace and the name of the attribute
+    pub fn split(ident: &str) -> Option<(Namespace, &str)> {
+        let (prefix, name) = ident.split_once('.')?;
+        Namespace::PRECEDENCE
+            .into_iter()
+            .find(|ns| ns.prefix() == prefix)
+            .map(|ns| (ns, name))
+    }
+}
+
+/// The values of the identifiers used by a policy expression.
+///
+/// The access controls of a node bind the attributes of the sender identity as
+/// `subject.<name>`, and the resource and the action as `resource.id` and `action.id`.
+/// Attributes added with [`Env::put_attribute`] are also bound in their [`Namespace`].
+/// An environment can also be built directly, to evaluate an expression with
+/// [`crate::evaluate`]:
+///
+/// ```
+/// use ockam_abac::expr::{int, seq, str};
+/// use ockam_abac::Env;
+///
+/// let env = Env::new()
+///     .with("subject.name", str("alice"))
+///     .with("subject.roles", seq([str("admin"), str("dev")]))
+///     .with("subject.age", int(42));
+/// assert!(env.contains("subject.roles"));
+/// ```
 #[derive(Debug, Clone, Default)]
 pub struct Env(BTreeMap<String, Expr>);
 
 impl Env {
+    /// Create an empty environment
     pub fn new() -> Self {
         Env(BTreeMap::new())
     }
+
+    /// Create an environment with the given bindings
     pub fn new_from(v: BTreeMap<&str, Expr>) -> Self {
         Env(v.into_iter().map(|(k, e)| (k.to_string(), e)).collect())
     }
 
+    /// Bind an identifier to a value and return the environment, replacing the
+    /// previous value of the identifier if there is one
+    pub fn with<K: Into<String>, E: Into<Expr>>(mut self, k: K, v: E) -> Self {
+        self.put(k, v);
+        self
+    }
+
+    /// Bind an attribute of a namespace and return the environment, see [`Env::put_attribute`]
+    pub fn with_attribute<E: Into<Expr>>(mut self, ns: Namespace, name: &str, v: E) -> Self {
+        self.put_attribute(ns, name, v);
+        self
+    }
+
+    /// Bind an attribute as `<namespace>.<name>`, and as `subject.<name>` unless a
+    /// namespace with a higher precedence already binds an attribute with the same name
+    pub fn put_attribute<E: Into<Expr>>(&mut self, ns: Namespace, name: &str, v: E) -> &mut Self {
+        let v = v.into();
+        let shadowed = Namespace::PRECEDENCE
+            .into_iter()
+            .take_while(|other| *other < ns)
+            .any(|other| self.contains(&other.ident(name)));
+        if !shadowed {
+            self.put(Namespace::subject_ident(name), v.clone());
+        }
+        self.put(ns.ident(name), v)
+    }
+
     pub fn get(&self, k: &str) -> Result<&Expr, EvalError> {
         self.0
             .get(k)
@@ -24,6 +130,8 @@ impl Env {
         self.0.contains_key(k)
     }
 
+    /// Bind an identifier to a value, replacing the previous value of the identifier
+    /// if there is one
     pub fn put<K: Into<String>, E: Into<Expr>>(&mut self, k: K, v: E) -> &mut Self {
         self.0.insert(k.into(), v.into());
         self
@@ -65,3 +173,60 @@ impl Env {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluate;
+    use crate::expr::{attribute, eq, ident, str, subject_attr};
+
+    #[test]
+    fn same_named_attributes_of_different_namespaces() {
+        let env = Env::new()
+            .with_attribute(Namespace::Connection, "role", str("guest"))
+            .with_attribute(Namespace::Local, "role", str("member"))
+            .with_attribute(Namespace::Credential, "role", str("admin"));
+
+        for (ns, value) in [
+            (Namespace::Credential, "admin"),
+            (Namespace::Local, "member"),
+            (Namespace::Connection, "guest"),
+        ] {
+            let expr = eq([attribute(ns, "role"), str(value)]);
+            assert!(evaluate(&expr, &env).unwrap().is_allowed(), "{ns:?}");
+        }
+        let expr = eq([ident("local.role"), str("admin")]);
+        assert!(!evaluate(&expr, &env).unwrap().is_allowed());
+        let expr = eq([subject_attr("role"), str("admin")]);
+        assert!(evaluate(&expr, &env).unwrap().is_allowed());
+    }
+
+    #[test]
+    fn unqualified_attributes_follow_the_precedence_of_the_namespaces() {
+        // the order of insertion doesn't matter
+        let env = Env::new()
+            .with_attribute(Namespace::Local, "role", str("member"))
+            .with_attribute(Namespace::Credential, "role", str("admin"))
+            .with_attribute(Namespace::Connection, "role", str("guest"))
+            .with_attribute(Namespace::Connection, "ip", str("10.0.0.1"))
+            .with_attribute(Namespace::Connection, "team", str("ops"))
+            .with_attribute(Namespace::Local, "team", str("dev"));
+        assert_eq!(env.get("subject.role").unwrap().to_string(), r#""admin""#);
+        assert_eq!(env.get("subject.team").unwrap().to_string(), r#""dev""#);
+        assert_eq!(env.get("subject.ip").unwrap().to_string(), r#""10.0.0.1""#);
+    }
+
+    #[test]
+    fn split_a_namespaced_identifier() {
+        assert_eq!(
+            Namespace::split("credential.role"),
+            Some((Namespace::Credential, "role"))
+        );
+        assert_eq!(
+            Namespace::split("conn.ip"),
+            Some((Namespace::Connection, "ip"))
+        );
+        assert_eq!(Namespace::split("subject.role"), None);
+        assert_eq!(Namespace::split("role"), None);
+    }
+}
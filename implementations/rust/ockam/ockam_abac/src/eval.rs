@@ -1,10 +1,43 @@
 use core::cmp::Ordering;
 
+use crate::decision::{AccessDecision, DenyReason};
 use crate::env::Env;
 use crate::error::EvalError;
 use crate::expr::{unit, Expr};
+use ockam_core::compat::format;
 use ockam_core::compat::string::ToString;
 use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+
+/// Evaluate a policy expression in an environment and return the access decision.
+///
+/// This is the decision taken by the access controls of a node, without a node: the
+/// environment contains the attributes which would otherwise come from the identities
+/// repository, for example `subject.role`, and those of the resource and the action.
+/// An expression which fails to evaluate, or which does not evaluate to a boolean,
+/// denies the access with [`DenyReason::EvaluationError`].
+///
+/// ```
+/// use ockam_abac::expr::str;
+/// use ockam_abac::{evaluate, parse, Env};
+///
+/// let expr = parse(r#"(and (= subject.role "admin") (= resource.id "db"))"#)?.unwrap();
+/// let env = Env::new()
+///     .with("subject.role", str("admin"))
+///     .with("resource.id", str("db"));
+/// assert!(evaluate(&expr, &env)?.is_allowed());
+/// # Ok::<(), ockam_core::Error>(())
+/// ```
+pub fn evaluate(expr: &Expr, env: &Env) -> Result<AccessDecision> {
+    let decision = match eval(expr, env) {
+        Ok(Expr::Bool(b)) => AccessDecision::from(b),
+        Ok(x) => AccessDecision::deny(DenyReason::EvaluationError(format!(
+            "expected a boolean, got {x}"
+        ))),
+        Err(e) => AccessDecision::deny(DenyReason::EvaluationError(e.to_string())),
+    };
+    Ok(decision)
+}
 
 #[rustfmt::skip]
 pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
@@ -23,6 +56,11 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
         Gt(usize),
         Lt(usize),
         Member,
+        Contains,
+        Subset,
+        Int,
+        Float,
+        Bool,
         Seq(usize),
     }
 
@@ -132,6 +170,31 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
                             }
                             ctrl.push(Op::Member)
                         }
+                        "contains" => {
+                            if nargs != 2 {
+                                let msg = "'contains' requires two arguments";
+                                return Err(EvalError::malformed(msg))
+                            }
+                            ctrl.push(Op::Contains)
+                        }
+                        "subset" => {
+                            if nargs != 2 {
+                                let msg = "'subset' requires two arguments";
+                                return Err(EvalError::malformed(msg))
+                            }
+                            ctrl.push(Op::Subset)
+                        }
+                        "int" | "float" | "bool" => {
+                            if nargs != 1 {
+                                let msg = "conversions require one argument";
+                                return Err(EvalError::malformed(msg))
+                            }
+                            ctrl.push(match id.as_str() {
+                                "int"   => Op::Int,
+                                "float" => Op::Float,
+                                _       => Op::Bool,
+                            })
+                        }
                         "exists?" => {
                             let mut b = true;
                             for x in &xs[1 ..] {
@@ -239,22 +302,55 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
                 let s = pop(&mut args);
                 let y = pop(&mut args);
                 match s {
-                    Expr::Seq(xs) => {
-                        let mut b = false;
-                        for x in &xs {
-                            if y.equals(x)? {
-                                b = true;
+                    Expr::Seq(xs) => args.push(Expr::Bool(is_member(&y, &xs)?)),
+                    other => {
+                        let msg = "'member?' expects sequence as second argument";
+                        return Err(EvalError::InvalidType(other, msg))
+                    }
+                }
+            }
+            Op::Contains => {
+                let y = pop(&mut args);
+                match pop(&mut args) {
+                    Expr::Seq(xs) => args.push(Expr::Bool(is_member(&y, &xs)?)),
+                    other => {
+                        let msg = "'contains' expects sequence as first argument";
+                        return Err(EvalError::InvalidType(other, msg))
+                    }
+                }
+            }
+            Op::Subset => {
+                let s = pop(&mut args);
+                let r = pop(&mut args);
+                match (r, s) {
+                    (Expr::Seq(required), Expr::Seq(xs)) => {
+                        let mut b = true;
+                        for y in &required {
+                            if !is_member(y, &xs)? {
+                                b = false;
                                 break
                             }
                         }
                         args.push(Expr::Bool(b))
                     }
-                    other => {
-                        let msg = "'member?' expects sequence as second argument";
+                    (Expr::Seq(_), other) | (other, _) => {
+                        let msg = "'subset' expects sequences as arguments";
                         return Err(EvalError::InvalidType(other, msg))
                     }
                 }
             }
+            Op::Int => {
+                let x = pop(&mut args).into_int()?;
+                args.push(x)
+            }
+            Op::Float => {
+                let x = pop(&mut args).into_float()?;
+                args.push(x)
+            }
+            Op::Bool => {
+                let x = pop(&mut args).into_bool()?;
+                args.push(x)
+            }
             Op::Seq(n) => {
                 let s = args.split_off(args.len() - n);
                 args.push(Expr::Seq(s))
@@ -275,6 +371,16 @@ fn pop<T>(s: &mut Vec<T>) -> T {
     s.pop().expect("stack is not empty")
 }
 
+/// Check if `y` is equal to one of the elements of `xs`.
+fn is_member(y: &Expr, xs: &[Expr]) -> Result<bool, EvalError> {
+    for x in xs {
+        if y.equals(x)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
 /// Evaluate a predicate against the `n` topmost arguments.
 fn eval_predicate<F>(n: usize, args: &mut Vec<Expr>, f: F) -> Result<(), EvalError>
 where
@@ -292,3 +398,251 @@ where
     args.push(Expr::Bool(b));
     Ok(())
 }
+
+/// A step of a traced evaluation, see [`trace`].
+#[derive(Debug)]
+pub struct TraceStep {
+    /// Nesting level of the expression, 0 being the toplevel expression.
+    pub depth: usize,
+    /// The evaluated expression.
+    pub expr: Expr,
+    /// The value of the expression.
+    pub result: Result<Expr, EvalError>,
+}
+
+/// Evaluate an expression and record the value of each evaluated identifier
+/// and sub-expression.
+///
+/// Steps are listed in evaluation order, i.e. the steps of the arguments of an
+/// expression come before the step of the expression itself. Like in [`eval`],
+/// the arguments of `and`, `or` and `if` which do not need to be evaluated are
+/// skipped.
+pub fn trace(expr: &Expr, env: &Env) -> Vec<TraceStep> {
+    let mut steps = Vec::new();
+    trace_expr(expr, env, 0, &mut steps);
+    steps
+}
+
+/// Trace `expr` and its arguments and return its value if it is a boolean.
+fn trace_expr(expr: &Expr, env: &Env, depth: usize, steps: &mut Vec<TraceStep>) -> Option<bool> {
+    match expr {
+        Expr::Ident(_) => {}
+        Expr::List(xs) => {
+            if let [Expr::Ident(id), args @ ..] = &xs[..] {
+                match (id.as_str(), args) {
+                    ("and", _) | ("or", _) => {
+                        let stop = id == "or";
+                        for x in args {
+                            if trace_expr(x, env, depth + 1, steps) == Some(stop) {
+                                break;
+                            }
+                        }
+                    }
+                    ("if", [test, then, orelse]) => match trace_expr(test, env, depth + 1, steps) {
+                        Some(true) => {
+                            trace_expr(then, env, depth + 1, steps);
+                        }
+                        Some(false) => {
+                            trace_expr(orelse, env, depth + 1, steps);
+                        }
+                        None => {}
+                    },
+                    _ => {
+                        for x in args {
+                            trace_expr(x, env, depth + 1, steps);
+                        }
+                    }
+                }
+            }
+        }
+        // Literals evaluate to themselves
+        _ => return None,
+    }
+    let result = eval(expr, env);
+    let value = match &result {
+        Ok(Expr::Bool(b)) => Some(*b),
+        _ => None,
+    };
+    steps.push(TraceStep {
+        depth,
+        expr: expr.clone(),
+        result,
+    });
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{eval, evaluate, trace};
+    use crate::decision::{AccessDecision, DenyReason};
+    use crate::env::Env;
+    use crate::error::EvalError;
+    use crate::expr::Expr;
+    use crate::expr::{int, seq, str};
+    use crate::parser::parse;
+
+    fn env() -> Env {
+        let mut e = Env::new();
+        e.put("subject.roles", seq([str("admin"), str("dev"), str("ops")]))
+            .put("subject.name", str("John"));
+        e
+    }
+
+    fn run(s: &str) -> Result<bool, EvalError> {
+        let expr = parse(s).unwrap().unwrap();
+        eval(&expr, &env()).map(|x| x.is_true())
+    }
+
+    #[test]
+    fn subset() {
+        // proper subset
+        assert!(run(r#"(subset ["dev" "admin"] subject.roles)"#).unwrap());
+        // equal sets
+        assert!(run(r#"(subset ["ops" "dev" "admin"] subject.roles)"#).unwrap());
+        // the empty set is a subset of any set
+        assert!(run(r#"(subset [] subject.roles)"#).unwrap());
+        assert!(!run(r#"(subset ["dev" "root"] subject.roles)"#).unwrap());
+    }
+
+    #[test]
+    fn contains() {
+        assert!(run(r#"(contains subject.roles "ops")"#).unwrap());
+        assert!(!run(r#"(contains subject.roles "root")"#).unwrap());
+    }
+
+    #[test]
+    fn evaluate_with_an_ad_hoc_environment() {
+        let env = env()
+            .with("subject.age", int(42))
+            .with("resource.id", str("db"));
+        let decide = |s: &str| evaluate(&parse(s).unwrap().unwrap(), &env).unwrap();
+
+        for allowed in [
+            r#"(and (= subject.name "John") (= resource.id "db"))"#,
+            r#"(or (= subject.name "Jane") (!= subject.name "Jane"))"#,
+            r#"(not (< subject.age 18))"#,
+            r#"(> subject.age 18 17)"#,
+            r#"(if (exists? subject.age) (member? "dev" subject.roles) false)"#,
+            r#"(contains subject.roles "ops")"#,
+            r#"(subset ["ops"] subject.roles)"#,
+            r#"(= subject.roles ["admin" "dev" "ops"])"#,
+        ] {
+            assert_eq!(decide(allowed), AccessDecision::allow(), "{allowed}");
+        }
+        assert_eq!(
+            decide(r#"(= subject.name "Jane")"#),
+            AccessDecision::deny(DenyReason::PolicyFalse)
+        );
+        assert_eq!(
+            decide(r#"(= subject.team "ops")"#),
+            AccessDecision::deny(DenyReason::EvaluationError(
+                EvalError::Unbound("subject.team".to_string()).to_string()
+            ))
+        );
+        assert_eq!(
+            decide("subject.name"),
+            AccessDecision::deny(DenyReason::EvaluationError(
+                r#"expected a boolean, got "John""#.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn missing_or_invalid_attributes() {
+        assert!(matches!(
+            run(r#"(contains subject.groups "ops")"#),
+            Err(EvalError::Unbound(_))
+        ));
+        assert!(matches!(
+            run(r#"(subset ["dev"] subject.groups)"#),
+            Err(EvalError::Unbound(_))
+        ));
+        assert!(matches!(
+            run(r#"(contains subject.name "John")"#),
+            Err(EvalError::InvalidType(..))
+        ));
+        assert!(matches!(
+            run(r#"(subset ["John"] subject.name)"#),
+            Err(EvalError::InvalidType(..))
+        ));
+        assert!(matches!(
+            run(r#"(subset subject.name subject.roles)"#),
+            Err(EvalError::InvalidType(..))
+        ));
+        assert!(matches!(
+            run(r#"(subset subject.roles)"#),
+            Err(EvalError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn convert_string_attributes_explicitly() {
+        let decide = |age: &str, s: &str| {
+            let env = env()
+                .with("subject.age", str(age))
+                .with("subject.admin", str("true"));
+            eval(&parse(s).unwrap().unwrap(), &env).map(|x| x.is_true())
+        };
+        // the converted attribute is compared numerically with an integer
+        assert!(decide("20", "(> (int subject.age) 18)").unwrap());
+        assert!(!decide("9", "(> (int subject.age) 18)").unwrap());
+        assert!(decide("18", "(= (int subject.age) 18)").unwrap());
+        assert!(decide("9", "(< 1.5 (float subject.age) 18.5)").unwrap());
+        assert!(decide("9", "(= (bool subject.admin) true)").unwrap());
+        assert!(run("(= (float 18) 18.0)").unwrap());
+        // without a conversion two strings are compared lexically
+        assert!(decide("9", r#"(> subject.age "18")"#).unwrap());
+        // and a string is never compared with a value of another type
+        for invalid in ["(> subject.age 18)", "(= subject.admin true)"] {
+            assert!(
+                matches!(decide("9", invalid), Err(EvalError::TypeMismatch(..))),
+                "{invalid}"
+            );
+        }
+        // the values which can't be converted are invalid
+        for invalid in ["(int subject.name)", "(bool subject.name)", "(int 18.5)"] {
+            assert!(
+                matches!(run(invalid), Err(EvalError::InvalidType(..))),
+                "{invalid}"
+            );
+        }
+        assert!(matches!(
+            decide("018", "(= (int subject.age) 18)"),
+            Err(EvalError::InvalidType(..))
+        ));
+        assert!(matches!(run("(int)"), Err(EvalError::Malformed(_))));
+    }
+
+    #[test]
+    fn trace_steps() {
+        let expr = parse(
+            r#"(or (= subject.name "Jane") (member? "ops" subject.roles) (= subject.age 25))"#,
+        )
+        .unwrap()
+        .unwrap();
+        let steps = trace(&expr, &env());
+        let steps: Vec<(usize, String, Option<bool>)> = steps
+            .iter()
+            .map(|s| {
+                let value = s.result.as_ref().ok().map(|x| x.is_true());
+                (s.depth, s.expr.to_string(), value)
+            })
+            .collect();
+        // The last argument of `or` is never evaluated
+        assert_eq!(
+            steps,
+            vec![
+                (2, "subject.name".to_string(), Some(false)),
+                (1, r#"(= subject.name "Jane")"#.to_string(), Some(false)),
+                (2, "subject.roles".to_string(), Some(false)),
+                (
+                    1,
+                    r#"(member? "ops" subject.roles)"#.to_string(),
+                    Some(true)
+                ),
+                (0, expr.to_string(), Some(true)),
+            ]
+        );
+        assert!(matches!(trace(&Expr::Bool(true), &env())[..], []));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::env::Namespace;
 use crate::EvalError;
 #[cfg(feature = "std")]
 use crate::ParseError;
@@ -7,8 +8,10 @@ use core::str::FromStr;
 use core::cmp::Ordering;
 use core::fmt;
 use minicbor::{Decode, Encode};
-use ockam_core::compat::string::String;
+use ockam_core::compat::format;
+use ockam_core::compat::string::{String, ToString};
 use ockam_core::compat::vec::{vec, Vec};
+use ockam_core::errcode::{Kind, Origin};
 
 #[derive(Debug, Clone, Encode, Decode)]
 #[rustfmt::skip]
@@ -145,6 +148,152 @@ impl Expr {
 
         Ok(result)
     }
+
+    /// Convert a value to an integer, this is the `int` operator.
+    ///
+    /// The attributes of an identity are bound as strings in the evaluation environment, so
+    /// they must be converted explicitly to be compared with numbers, for example
+    /// `(> (int subject.age) 18)`. A string is converted if it is the decimal representation
+    /// of an integer, without a sign for positive integers nor leading zeros, for example
+    /// `"18"` or `"-3"`.
+    pub fn into_int(self) -> Result<Expr, EvalError> {
+        match self {
+            Expr::Int(_) => Ok(self),
+            Expr::Str(ref s) => match s.parse::<i64>() {
+                Ok(i) if i.to_string() == *s => Ok(Expr::Int(i)),
+                _ => Err(EvalError::InvalidType(self, "'int' expects an integer")),
+            },
+            other => Err(EvalError::InvalidType(other, "'int' expects an integer")),
+        }
+    }
+
+    /// Convert a value to a float, this is the `float` operator, see [`Expr::into_int`].
+    /// An integer or a string which can be parsed as a float are converted, for example
+    /// `18` or `"1.5"`.
+    pub fn into_float(self) -> Result<Expr, EvalError> {
+        match self {
+            Expr::Float(_) => Ok(self),
+            Expr::Int(i) => Ok(Expr::Float(i as f64)),
+            Expr::Str(ref s) => match s.parse::<f64>() {
+                Ok(x) if !x.is_nan() => Ok(Expr::Float(x)),
+                _ => Err(EvalError::InvalidType(self, "'float' expects a number")),
+            },
+            other => Err(EvalError::InvalidType(other, "'float' expects a number")),
+        }
+    }
+
+    /// Convert a value to a boolean, this is the `bool` operator, see [`Expr::into_int`].
+    /// The only strings which are converted are `"true"` and `"false"`.
+    pub fn into_bool(self) -> Result<Expr, EvalError> {
+        match self {
+            Expr::Bool(_) => Ok(self),
+            Expr::Str(ref s) if s == "true" => Ok(Expr::Bool(true)),
+            Expr::Str(ref s) if s == "false" => Ok(Expr::Bool(false)),
+            other => Err(EvalError::InvalidType(other, "'bool' expects a boolean")),
+        }
+    }
+}
+
+/// Latest version of the binary encoding of the expressions, see [`Expr::to_bytes`]
+pub const EXPR_FORMAT_VERSION: u8 = 2;
+
+/// Operators of the policy language, with the version of the binary encoding which
+/// introduced them
+const OPERATOR_VERSIONS: &[(&str, u8)] = &[
+    ("and", 1),
+    ("or", 1),
+    ("not", 1),
+    ("if", 1),
+    ("<", 1),
+    (">", 1),
+    ("=", 1),
+    ("!=", 1),
+    ("member?", 1),
+    ("contains", 1),
+    ("subset", 1),
+    ("exists?", 1),
+    ("int", 2),
+    ("float", 2),
+    ("bool", 2),
+];
+
+impl Expr {
+    /// Encode the expression, for example to persist it: one byte for the version of the
+    /// encoding followed by the CBOR encoding of the expression.
+    ///
+    /// The version is the lowest one supporting all the operators of the expression, so an
+    /// expression without newer operators can still be read by the nodes which don't know
+    /// them. The same expression is always encoded with the same bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.format_version()];
+        minicbor::encode(self, &mut bytes).expect("an expression can be encoded");
+        bytes
+    }
+
+    /// Decode an expression encoded with [`Expr::to_bytes`].
+    ///
+    /// This fails if the version of the encoding is unknown, for example if the expression
+    /// was encoded by a newer node, or if the expression uses operators which were introduced
+    /// after its version
+    pub fn from_bytes(bytes: &[u8]) -> ockam_core::Result<Expr> {
+        let (version, cbor) = bytes
+            .split_first()
+            .ok_or_else(|| format_error(Kind::Serialization, "the expression is empty"))?;
+        if *version == 0 || *version > EXPR_FORMAT_VERSION {
+            return Err(format_error(
+                Kind::Unsupported,
+                format!(
+                    "unsupported expression format version {version}, \
+                     the supported versions are 1 to {EXPR_FORMAT_VERSION}"
+                ),
+            ));
+        }
+        let expr: Expr = minicbor::decode(cbor).map_err(|e| {
+            format_error(
+                Kind::Serialization,
+                format!("invalid expression of format version {version}: {e}"),
+            )
+        })?;
+        // an operator unknown to the version of the expression can't be interpreted as it was
+        // by the node which encoded it
+        let required = expr.format_version();
+        if required > *version {
+            return Err(format_error(
+                Kind::Invalid,
+                format!(
+                    "the expression {expr} of format version {version} \
+                     uses operators of format version {required}"
+                ),
+            ));
+        }
+        Ok(expr)
+    }
+
+    /// Return the lowest version of the binary encoding supporting all the operators of
+    /// the expression
+    fn format_version(&self) -> u8 {
+        let mut version = 1;
+        let mut exprs = vec![self];
+        while let Some(expr) = exprs.pop() {
+            match expr {
+                Expr::List(xs) => {
+                    if let Some(Expr::Ident(op)) = xs.first() {
+                        if let Some((_, v)) = OPERATOR_VERSIONS.iter().find(|(o, _)| o == op) {
+                            version = version.max(*v)
+                        }
+                    }
+                    exprs.extend(xs)
+                }
+                Expr::Seq(xs) => exprs.extend(xs),
+                _ => {}
+            }
+        }
+        version
+    }
+}
+
+fn format_error(kind: Kind, message: impl Into<String>) -> ockam_core::Error {
+    ockam_core::Error::new(Origin::Application, kind, message.into())
 }
 
 impl From<bool> for Expr {
@@ -189,6 +338,17 @@ pub fn ident<S: Into<String>>(s: S) -> Expr {
     Expr::Ident(s.into())
 }
 
+/// Reference to an attribute of a namespace, for example `credential.role`
+pub fn attribute(ns: Namespace, name: &str) -> Expr {
+    ident(ns.ident(name))
+}
+
+/// Reference to an attribute of the subject, for example `subject.role`, whatever its
+/// namespace, see [`Namespace::subject_ident`]
+pub fn subject_attr(name: &str) -> Expr {
+    ident(Namespace::subject_ident(name))
+}
+
 pub fn seq<T: IntoIterator<Item = Expr>>(xs: T) -> Expr {
     Expr::Seq(xs.into_iter().collect())
 }
@@ -337,7 +497,8 @@ impl FromStr for Expr {
 
 #[cfg(test)]
 mod tests {
-    use super::Expr;
+    use super::{Expr, EXPR_FORMAT_VERSION};
+    use crate::expr::str;
     use crate::{eval, parser::parse, Env};
     use core::cmp::Ordering;
     use ockam_core::compat::string::ToString;
@@ -578,4 +739,56 @@ mod tests {
             .min_tests_passed(1000)
             .quickcheck(property as fn(_))
     }
+
+    #[test]
+    fn encode_decode() {
+        fn property(e: Expr) -> bool {
+            let bytes = e.to_bytes();
+            let x = Expr::from_bytes(&bytes).unwrap();
+            e.equals(&x).unwrap() && x.to_bytes() == bytes
+        }
+        QuickCheck::new()
+            .gen(Gen::new(4))
+            .tests(1000)
+            .min_tests_passed(1000)
+            .quickcheck(property as fn(_) -> bool)
+    }
+
+    #[test]
+    fn a_v1_payload_is_decoded_by_a_newer_version() {
+        // (= subject.role "admin") encoded by version 1, before 'int', 'float' and 'bool' were added
+        let v1 = b"\x01\x82\x07\x81\x83\x82\x05\x81\x61=\x82\x05\x81\x6csubject.role\x82\x01\x81\x65admin";
+        let expr = Expr::from_bytes(v1).unwrap();
+        assert_eq!(expr.to_string(), r#"(= subject.role "admin")"#);
+        let env = Env::new().with("subject.role", str("admin"));
+        assert!(eval(&expr, &env).unwrap().is_true());
+        // an expression without newer operators is still encoded with version 1
+        assert_eq!(expr.to_bytes(), v1);
+
+        let expr = parse("(and (> (int subject.age) 17) (= subject.role \"admin\"))")
+            .unwrap()
+            .unwrap();
+        assert_eq!(expr.to_bytes()[0], 2);
+    }
+
+    #[test]
+    fn an_unknown_format_version_is_rejected() {
+        let mut bytes = parse("(> (int subject.age) 17)")
+            .unwrap()
+            .unwrap()
+            .to_bytes();
+        assert_eq!(bytes[0], EXPR_FORMAT_VERSION);
+
+        bytes[0] = EXPR_FORMAT_VERSION + 1;
+        let error = Expr::from_bytes(&bytes).unwrap_err();
+        assert!(error.to_string().contains(&format!(
+            "unsupported expression format version {}",
+            bytes[0]
+        )));
+        assert!(Expr::from_bytes(&[]).is_err());
+
+        // the operators of version 2 are rejected in an expression of version 1
+        bytes[0] = 1;
+        assert!(Expr::from_bytes(&bytes).is_err());
+    }
 }
@@ -6,10 +6,13 @@
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+mod builder;
+mod decision;
 mod env;
 mod error;
 mod eval;
 mod policy;
+mod snapshot;
 mod traits;
 mod types;
 
@@ -22,12 +25,15 @@ pub mod mem;
 mod storage;
 
 pub use attribute_access_control::AbacAccessControl;
-pub use env::Env;
+pub use builder::Policy;
+pub use decision::{AccessDecision, DenyReason};
+pub use env::{Env, Namespace};
 pub use error::{EvalError, ParseError};
-pub use eval::eval;
+pub use eval::{eval, evaluate, trace, TraceStep};
 pub use expr::Expr;
-pub use policy::PolicyAccessControl;
-pub use traits::PolicyStorage;
+pub use policy::{evaluate_batch, PolicyAccessControl};
+pub use snapshot::{PolicyChange, PolicyDiff, PolicySnapshot};
+pub use traits::{check_resource_parent, check_resource_parent_with, PolicyStorage};
 pub use types::{Action, Resource, Subject};
 
 #[cfg(feature = "std")]
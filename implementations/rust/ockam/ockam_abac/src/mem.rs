@@ -1,10 +1,12 @@
 use crate::expr::Expr;
-use crate::traits::PolicyStorage;
+use crate::snapshot::PolicySnapshot;
+use crate::traits::{check_resource_parent_with, PolicyStorage};
 use crate::types::{Action, Resource};
 use core::fmt;
 use ockam_core::async_trait;
 use ockam_core::compat::boxed::Box;
 use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::string::String;
 use ockam_core::compat::sync::{Arc, RwLock};
 use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
@@ -30,7 +32,8 @@ impl Memory {
 
 #[derive(Default)]
 pub struct Inner {
-    policies: BTreeMap<Resource, BTreeMap<Action, Expr>>,
+    policies: BTreeMap<Resource, BTreeMap<Action, (Expr, Option<String>)>>,
+    parents: BTreeMap<Resource, Resource>,
 }
 
 impl Inner {
@@ -48,25 +51,70 @@ impl Inner {
     }
 
     fn get_policy(&self, r: &Resource, a: &Action) -> Option<Expr> {
-        self.policies.get(r).and_then(|p| p.get(a).cloned())
+        self.policies
+            .get(r)
+            .and_then(|p| p.get(a).map(|(expr, _)| expr.clone()))
+    }
+
+    fn get_policy_meta(&self, r: &Resource, a: &Action) -> Option<String> {
+        self.policies
+            .get(r)
+            .and_then(|p| p.get(a).and_then(|(_, meta)| meta.clone()))
     }
 
     fn set_policy(&mut self, r: &Resource, a: &Action, p: &Expr) {
+        self.set_policy_with_meta(r, a, p, None)
+    }
+
+    fn set_policy_with_meta(&mut self, r: &Resource, a: &Action, p: &Expr, meta: Option<&str>) {
         self.policies
             .entry(r.clone())
             .or_insert_with(BTreeMap::new)
-            .insert(a.clone(), p.clone());
+            .insert(a.clone(), (p.clone(), meta.map(String::from)));
     }
 
     fn policies(&self, r: &Resource) -> Vec<(Action, Expr)> {
         if let Some(p) = self.policies.get(r) {
             p.iter()
-                .map(|(k, v)| (k.clone(), v.clone()))
+                .map(|(k, (v, _))| (k.clone(), v.clone()))
                 .collect::<Vec<_>>()
         } else {
             Vec::new()
         }
     }
+
+    fn resources(&self) -> Vec<Resource> {
+        self.policies.keys().cloned().collect()
+    }
+
+    fn count(&self) -> usize {
+        self.policies.values().map(|p| p.len()).sum()
+    }
+
+    fn snapshot(&self) -> PolicySnapshot {
+        let mut snapshot = PolicySnapshot::new();
+        for (r, p) in self.policies.iter() {
+            for (a, (expr, meta)) in p.iter() {
+                snapshot.insert_with_meta(r.clone(), a.clone(), expr.clone(), meta.clone());
+            }
+        }
+        snapshot
+    }
+
+    fn replace_all(&mut self, snapshot: PolicySnapshot) {
+        self.policies.clear();
+        for (r, a, expr, meta) in snapshot.iter() {
+            self.set_policy_with_meta(r, a, expr, meta);
+        }
+    }
+
+    fn get_resource_parent(&self, r: &Resource) -> Option<Resource> {
+        self.parents.get(r).cloned()
+    }
+
+    fn set_resource_parent(&mut self, child: &Resource, parent: &Resource) {
+        self.parents.insert(child.clone(), parent.clone());
+    }
 }
 
 #[async_trait]
@@ -85,9 +133,57 @@ impl PolicyStorage for Memory {
         Ok(())
     }
 
+    async fn set_policy_with_meta(
+        &self,
+        r: &Resource,
+        a: &Action,
+        p: &Expr,
+        meta: Option<&str>,
+    ) -> Result<()> {
+        self.inner
+            .write()
+            .unwrap()
+            .set_policy_with_meta(r, a, p, meta);
+        Ok(())
+    }
+
+    async fn get_policy_meta(&self, r: &Resource, a: &Action) -> Result<Option<String>> {
+        Ok(self.inner.read().unwrap().get_policy_meta(r, a))
+    }
+
     async fn policies(&self, r: &Resource) -> Result<Vec<(Action, Expr)>> {
         Ok(self.inner.write().unwrap().policies(r))
     }
+
+    async fn resources(&self) -> Result<Vec<Resource>> {
+        Ok(self.inner.read().unwrap().resources())
+    }
+
+    async fn count(&self) -> Result<usize> {
+        Ok(self.inner.read().unwrap().count())
+    }
+
+    /// The policies are read under a single lock
+    async fn snapshot(&self) -> Result<PolicySnapshot> {
+        Ok(self.inner.read().unwrap().snapshot())
+    }
+
+    /// The snapshot is applied atomically
+    async fn replace_all(&self, snapshot: PolicySnapshot) -> Result<()> {
+        self.inner.write().unwrap().replace_all(snapshot);
+        Ok(())
+    }
+
+    async fn get_resource_parent(&self, r: &Resource) -> Result<Option<Resource>> {
+        Ok(self.inner.read().unwrap().get_resource_parent(r))
+    }
+
+    async fn set_resource_parent(&self, child: &Resource, parent: &Resource) -> Result<()> {
+        let mut inner = self.inner.write().unwrap();
+        check_resource_parent_with(child, parent, |r| Ok(inner.get_resource_parent(r)))?;
+        inner.set_resource_parent(child, parent);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -97,7 +193,10 @@ mod tests {
     use crate::expr::{int, seq, str};
     use crate::mem::Memory;
     use crate::parser::parse;
+    use crate::snapshot::PolicySnapshot;
+    use crate::traits::PolicyStorage;
     use crate::types::{Action, Resource};
+    use crate::Expr;
 
     #[test]
     fn example1() {
@@ -131,4 +230,177 @@ mod tests {
             .unwrap();
         assert!(eval(&policy, &e).unwrap().is_true())
     }
+
+    #[tokio::test]
+    async fn inherited_policies() {
+        let store = Memory::new();
+        let action = Action::new("handle_message");
+        let root = Resource::new("root");
+        let child = Resource::new("child");
+        let grandchild = Resource::new("grandchild");
+        store.set_resource_parent(&child, &root).await.unwrap();
+        store
+            .set_resource_parent(&grandchild, &child)
+            .await
+            .unwrap();
+
+        // No policy anywhere in the chain
+        let policy = store.get_effective_policy(&grandchild, &action).await;
+        assert!(policy.unwrap().is_none());
+
+        // The policy of the root is inherited by all its descendants
+        store
+            .set_policy(&root, &action, &Expr::Bool(false))
+            .await
+            .unwrap();
+        for r in [&root, &child, &grandchild] {
+            let policy = store.get_effective_policy(r, &action).await.unwrap();
+            assert!(matches!(policy, Some(Expr::Bool(false))));
+        }
+
+        // The closest policy wins
+        store
+            .set_policy(&child, &action, &Expr::Bool(true))
+            .await
+            .unwrap();
+        let policy = store.get_effective_policy(&grandchild, &action).await;
+        assert!(matches!(policy.unwrap(), Some(Expr::Bool(true))));
+        let policy = store.get_effective_policy(&root, &action).await;
+        assert!(matches!(policy.unwrap(), Some(Expr::Bool(false))));
+
+        // The resource defining the effective policy is returned with it
+        let found = store.find_effective_policy(&grandchild, &action).await;
+        assert!(matches!(found.unwrap(), Some((r, Expr::Bool(true))) if r == child));
+        let found = store.find_effective_policy(&root, &action).await;
+        assert!(matches!(found.unwrap(), Some((r, Expr::Bool(false))) if r == root));
+
+        // Policies are not inherited for other actions
+        let other = Action::new("other");
+        let policy = store.get_effective_policy(&grandchild, &other).await;
+        assert!(policy.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn count_policies() {
+        let store = Memory::new();
+        assert_eq!(store.count().await.unwrap(), 0);
+
+        let db = Resource::new("db");
+        let web = Resource::new("web");
+        let read = Action::new("read");
+        let write = Action::new("write");
+        for (r, a) in [(&db, &read), (&db, &write), (&web, &read)] {
+            store.set_policy(r, a, &Expr::Bool(true)).await.unwrap();
+        }
+        // replacing a policy doesn't change the count
+        store
+            .set_policy(&db, &read, &Expr::Bool(false))
+            .await
+            .unwrap();
+        assert_eq!(store.count().await.unwrap(), 3);
+        assert_eq!(store.resources().await.unwrap(), vec![db.clone(), web]);
+
+        store.del_policy(&db, &write).await.unwrap();
+        assert_eq!(store.count().await.unwrap(), 2);
+
+        // the count is the same as the one of the default implementation
+        let mut count = 0;
+        for r in store.resources().await.unwrap() {
+            count += store.policies(&r).await.unwrap().len();
+        }
+        assert_eq!(count, 2);
+    }
+
+    #[tokio::test]
+    async fn replace_all_policies() {
+        let store = Memory::new();
+        let db = Resource::new("db");
+        let web = Resource::new("web");
+        let read = Action::new("read");
+        let write = Action::new("write");
+        store
+            .set_policy(&db, &read, &Expr::Bool(true))
+            .await
+            .unwrap();
+        store
+            .set_policy(&db, &write, &Expr::Bool(true))
+            .await
+            .unwrap();
+        store.set_resource_parent(&db, &web).await.unwrap();
+
+        let snapshot = PolicySnapshot::new()
+            .with_policy(db.clone(), read.clone(), Expr::Bool(false))
+            .with_policy_meta(
+                web.clone(),
+                write.clone(),
+                Expr::Bool(true),
+                "the web can write",
+            );
+        store.replace_all(snapshot).await.unwrap();
+
+        let policy = store.get_policy(&db, &read).await.unwrap();
+        assert!(matches!(policy, Some(Expr::Bool(false))));
+        assert!(store.get_policy(&db, &write).await.unwrap().is_none());
+        let policy = store.get_policy(&web, &write).await.unwrap();
+        assert!(matches!(policy, Some(Expr::Bool(true))));
+        assert_eq!(store.count().await.unwrap(), 2);
+        // the resource parents are not replaced
+        assert_eq!(
+            store.get_resource_parent(&db).await.unwrap(),
+            Some(web.clone())
+        );
+
+        let taken = PolicySnapshot::take(&store).await.unwrap();
+        assert_eq!(taken.len(), 2);
+        assert!(matches!(taken.get(&db, &read), Some(Expr::Bool(false))));
+        assert_eq!(taken.get_meta(&web, &write), Some("the web can write"));
+
+        store.replace_all(PolicySnapshot::new()).await.unwrap();
+        assert_eq!(store.count().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn policy_descriptions() {
+        let store = Memory::new();
+        let r = Resource::new("db");
+        let a = Action::new("handle_message");
+        let description = "only the web server can access the database";
+        store
+            .set_policy_with_meta(&r, &a, &Expr::Bool(true), Some(description))
+            .await
+            .unwrap();
+        let meta = store.get_policy_meta(&r, &a).await.unwrap();
+        assert_eq!(meta.as_deref(), Some(description));
+        let policy = store.get_policy(&r, &a).await.unwrap();
+        assert!(matches!(policy, Some(Expr::Bool(true))));
+
+        // the description is removed with the policy, or when the policy is set without one
+        store.set_policy(&r, &a, &Expr::Bool(false)).await.unwrap();
+        assert_eq!(store.get_policy_meta(&r, &a).await.unwrap(), None);
+        store
+            .set_policy_with_meta(&r, &a, &Expr::Bool(true), Some(description))
+            .await
+            .unwrap();
+        store.del_policy(&r, &a).await.unwrap();
+        assert_eq!(store.get_policy_meta(&r, &a).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn resource_parent_cycles_are_rejected() {
+        let store = Memory::new();
+        let a = Resource::new("a");
+        let b = Resource::new("b");
+        let c = Resource::new("c");
+        store.set_resource_parent(&b, &a).await.unwrap();
+        store.set_resource_parent(&c, &b).await.unwrap();
+
+        assert!(store.set_resource_parent(&a, &a).await.is_err());
+        assert!(store.set_resource_parent(&a, &c).await.is_err());
+        assert!(store.set_resource_parent(&b, &c).await.is_err());
+        assert_eq!(store.get_resource_parent(&a).await.unwrap(), None);
+
+        // Re-parenting without creating a cycle is allowed
+        store.set_resource_parent(&c, &a).await.unwrap();
+        assert_eq!(store.get_resource_parent(&c).await.unwrap(), Some(a));
+    }
 }
@@ -1,12 +1,14 @@
+use crate::expr::str;
 use crate::traits::PolicyStorage;
 use crate::types::{Action, Resource};
-use crate::AbacAccessControl;
+use crate::{evaluate, AbacAccessControl, AccessDecision, DenyReason};
 use crate::{Env, Expr};
 use core::fmt;
 use core::fmt::{Debug, Formatter};
 use ockam_core::compat::boxed::Box;
 use ockam_core::compat::format;
 use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
 use ockam_core::{async_trait, RelayMessage};
 use ockam_core::{IncomingAccessControl, Result};
 use ockam_identity::IdentitiesRepository;
@@ -59,34 +61,147 @@ impl PolicyAccessControl {
     }
 }
 
-#[async_trait]
-impl IncomingAccessControl for PolicyAccessControl {
-    async fn is_authorized(&self, msg: &RelayMessage) -> Result<bool> {
-        // Load the policy expression for resource and action:
+impl PolicyAccessControl {
+    /// Evaluate the policy stored for the resource and action against the
+    /// sender of the message and return the decision
+    pub async fn decide(&self, msg: &RelayMessage) -> Result<AccessDecision> {
+        // Load the policy expression for resource and action,
+        // possibly inherited from a parent resource:
         let expr = if let Some(expr) = self
             .policies
-            .get_policy(&self.resource, &self.action)
+            .get_effective_policy(&self.resource, &self.action)
             .await?
         {
             if let Expr::Bool(b) = expr {
                 // If the policy is a constant there is no need to populate
                 // the environment or look for message metadata.
-                return Ok(b);
+                return Ok(AccessDecision::from(b));
             } else {
                 expr
             }
         } else {
             // If no policy exists for this resource and action access is denied:
-            log::debug! {
-                resource = %self.resource,
-                action   = %self.action,
-                "no policy found; access denied"
-            }
-            return Ok(false);
+            return Ok(AccessDecision::deny(DenyReason::NoPolicy));
         };
 
         AbacAccessControl::new(self.repository.clone(), expr, self.environment.clone())
-            .is_authorized(msg)
+            .decide(msg)
             .await
     }
 }
+
+#[async_trait]
+impl IncomingAccessControl for PolicyAccessControl {
+    async fn is_authorized(&self, msg: &RelayMessage) -> Result<bool> {
+        let decision = match self.decide(msg).await {
+            Ok(decision) => decision,
+            Err(e) => {
+                log::warn! {
+                    resource    = %self.resource,
+                    action      = %self.action,
+                    destination = %msg.destination(),
+                    err         = %e,
+                    "failed to evaluate the policy of {}/{} for a message to {}: {e}",
+                    self.resource, self.action, msg.destination()
+                }
+                return Err(e);
+            }
+        };
+        if let Some(reason) = &decision.reason {
+            log::debug! {
+                resource    = %self.resource,
+                action      = %self.action,
+                destination = %msg.destination(),
+                reason      = %reason.code(),
+                "the policy of {}/{} denied a message to {}: {reason}",
+                self.resource, self.action, msg.destination()
+            }
+        }
+        Ok(decision.allowed)
+    }
+}
+
+/// Evaluate the effective policies of a resource for several actions and return one result
+/// per action, in the order of the actions.
+///
+/// The subject attributes are assembled once, for example with
+/// [`AbacAccessControl::environment_for_identity`]. Each action is then evaluated in its own
+/// copy of this environment, where `resource.id` and `action.id` are bound to the resource and
+/// to that action. Like for a [`PolicyAccessControl`], an action without policy is denied with
+/// [`DenyReason::NoPolicy`], and a policy which can't be loaded only fails its own action.
+pub async fn evaluate_batch<S: PolicyStorage + ?Sized>(
+    policies: &S,
+    env: &Env,
+    resource: &Resource,
+    actions: &[Action],
+) -> Vec<Result<AccessDecision>> {
+    let mut decisions = Vec::with_capacity(actions.len());
+    for action in actions {
+        let decision = match policies.get_effective_policy(resource, action).await {
+            Ok(Some(Expr::Bool(b))) => Ok(AccessDecision::from(b)),
+            Ok(Some(expr)) => {
+                let env = env
+                    .clone()
+                    .with("resource.id", str(resource.as_str()))
+                    .with("action.id", str(action.as_str()));
+                evaluate(&expr, &env)
+            }
+            Ok(None) => Ok(AccessDecision::deny(DenyReason::NoPolicy)),
+            Err(e) => Err(e),
+        };
+        decisions.push(decision);
+    }
+    decisions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::Memory;
+    use crate::parser::parse;
+
+    #[tokio::test]
+    async fn evaluate_several_actions_at_once() -> Result<()> {
+        let store = Memory::new();
+        let resource = Resource::new("db");
+        let (read, write, delete, admin, list) = (
+            Action::new("read"),
+            Action::new("write"),
+            Action::new("delete"),
+            Action::new("admin"),
+            Action::new("list"),
+        );
+        let policy = |s: &str| parse(s).unwrap().unwrap();
+        store
+            .set_policy(&resource, &read, &policy(r#"(= subject.role "dev")"#))
+            .await?;
+        store
+            .set_policy(&resource, &write, &policy(r#"(= subject.role "ops")"#))
+            .await?;
+        store
+            .set_policy(&resource, &admin, &Expr::Bool(false))
+            .await?;
+        // each action is evaluated with its own action.id
+        let own_action = policy(r#"(and (= action.id "list") (= resource.id "db"))"#);
+        store.set_policy(&resource, &list, &own_action).await?;
+        store.set_policy(&resource, &delete, &own_action).await?;
+
+        let env = Env::new().with("subject.role", str("dev"));
+        let actions = [write.clone(), read.clone(), delete, admin, read, list];
+        let decisions = evaluate_batch(&store, &env, &resource, &actions).await;
+        let decisions = decisions.into_iter().collect::<Result<Vec<_>>>()?;
+        assert_eq!(
+            decisions,
+            vec![
+                AccessDecision::deny(DenyReason::PolicyFalse),
+                AccessDecision::allow(),
+                AccessDecision::deny(DenyReason::PolicyFalse),
+                AccessDecision::deny(DenyReason::PolicyFalse),
+                AccessDecision::allow(),
+                AccessDecision::allow(),
+            ]
+        );
+        assert!(env.get("action.id").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,291 @@
+use crate::{Action, Expr, PolicyStorage, Resource};
+use core::fmt;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+
+/// A set of policies, with at most one policy per resource and action.
+/// Each policy can have a description, see [`PolicyStorage::set_policy_with_meta`].
+///
+/// The resource parents are not part of a snapshot.
+#[derive(Debug, Clone, Default)]
+pub struct PolicySnapshot {
+    policies: BTreeMap<(Resource, Action), (Expr, Option<String>)>,
+}
+
+impl PolicySnapshot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return all the policies of a storage, see [`PolicyStorage::snapshot`].
+    pub async fn take<S: PolicyStorage + ?Sized>(storage: &S) -> Result<Self> {
+        storage.snapshot().await
+    }
+
+    /// Add a policy, replacing the previous policy of the resource for the action.
+    pub fn insert(&mut self, r: Resource, a: Action, expr: Expr) -> Option<Expr> {
+        self.insert_with_meta(r, a, expr, None)
+    }
+
+    /// Add a policy with a description, replacing the previous policy of the resource
+    /// for the action.
+    pub fn insert_with_meta(
+        &mut self,
+        r: Resource,
+        a: Action,
+        expr: Expr,
+        meta: Option<String>,
+    ) -> Option<Expr> {
+        self.policies
+            .insert((r, a), (expr, meta))
+            .map(|(expr, _)| expr)
+    }
+
+    pub fn with_policy(mut self, r: Resource, a: Action, expr: Expr) -> Self {
+        self.insert(r, a, expr);
+        self
+    }
+
+    pub fn with_policy_meta(mut self, r: Resource, a: Action, expr: Expr, meta: &str) -> Self {
+        self.insert_with_meta(r, a, expr, Some(meta.into()));
+        self
+    }
+
+    pub fn get(&self, r: &Resource, a: &Action) -> Option<&Expr> {
+        self.policies
+            .get(&(r.clone(), a.clone()))
+            .map(|(expr, _)| expr)
+    }
+
+    pub fn get_meta(&self, r: &Resource, a: &Action) -> Option<&str> {
+        self.policies
+            .get(&(r.clone(), a.clone()))
+            .and_then(|(_, meta)| meta.as_deref())
+    }
+
+    /// Iterate over the policies and their descriptions, ordered by resource and action.
+    pub fn iter(&self) -> impl Iterator<Item = (&Resource, &Action, &Expr, Option<&str>)> {
+        self.policies
+            .iter()
+            .map(|((r, a), (expr, meta))| (r, a, expr, meta.as_deref()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.policies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
+
+    /// Return the policies added, removed or changed in `other` compared to this snapshot,
+    /// ordered by resource and action.
+    ///
+    /// Only the expressions are compared, a policy whose description changed is not
+    /// reported.
+    pub fn diff(&self, other: &PolicySnapshot) -> PolicyDiff {
+        let mut changes = Vec::new();
+        for ((r, a), (old, _)) in self.policies.iter() {
+            match other.policies.get(&(r.clone(), a.clone())) {
+                None => changes.push(PolicyChange::Removed(r.clone(), a.clone(), old.clone())),
+                // `Expr` is not `PartialEq`, its rendering is used to compare expressions
+                Some((new, _)) if old.to_string() != new.to_string() => changes.push(
+                    PolicyChange::Changed(r.clone(), a.clone(), old.clone(), new.clone()),
+                ),
+                Some(_) => {}
+            }
+        }
+        for ((r, a), (new, _)) in other.policies.iter() {
+            if !self.policies.contains_key(&(r.clone(), a.clone())) {
+                changes.push(PolicyChange::Added(r.clone(), a.clone(), new.clone()))
+            }
+        }
+        changes.sort_by(|c1, c2| c1.key().cmp(&c2.key()));
+        PolicyDiff { changes }
+    }
+}
+
+/// The differences between two [`PolicySnapshot`]s, see [`PolicySnapshot::diff`].
+#[derive(Debug, Clone, Default)]
+pub struct PolicyDiff {
+    changes: Vec<PolicyChange>,
+}
+
+/// A policy which differs between two [`PolicySnapshot`]s.
+#[derive(Debug, Clone)]
+pub enum PolicyChange {
+    /// The policy only exists in the new snapshot.
+    Added(Resource, Action, Expr),
+    /// The policy only exists in the old snapshot.
+    Removed(Resource, Action, Expr),
+    /// The policy exists in both snapshots with different expressions: old, then new.
+    Changed(Resource, Action, Expr, Expr),
+}
+
+impl PolicyChange {
+    fn key(&self) -> (&Resource, &Action) {
+        match self {
+            PolicyChange::Added(r, a, _)
+            | PolicyChange::Removed(r, a, _)
+            | PolicyChange::Changed(r, a, _, _) => (r, a),
+        }
+    }
+}
+
+impl PolicyDiff {
+    /// Iterate over the changes, ordered by resource and action.
+    pub fn iter(&self) -> impl Iterator<Item = &PolicyChange> {
+        self.changes.iter()
+    }
+
+    pub fn added(&self) -> impl Iterator<Item = (&Resource, &Action, &Expr)> {
+        self.changes.iter().filter_map(|c| match c {
+            PolicyChange::Added(r, a, expr) => Some((r, a, expr)),
+            _ => None,
+        })
+    }
+
+    pub fn removed(&self) -> impl Iterator<Item = (&Resource, &Action, &Expr)> {
+        self.changes.iter().filter_map(|c| match c {
+            PolicyChange::Removed(r, a, expr) => Some((r, a, expr)),
+            _ => None,
+        })
+    }
+
+    pub fn changed(&self) -> impl Iterator<Item = (&Resource, &Action, &Expr, &Expr)> {
+        self.changes.iter().filter_map(|c| match c {
+            PolicyChange::Changed(r, a, old, new) => Some((r, a, old, new)),
+            _ => None,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for PolicyChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PolicyChange::Added(r, a, expr) => write!(f, "+ {r} {a}: {expr}"),
+            PolicyChange::Removed(r, a, expr) => write!(f, "- {r} {a}: {expr}"),
+            PolicyChange::Changed(r, a, old, new) => {
+                write!(f, "- {r} {a}: {old}\n+ {r} {a}: {new}")
+            }
+        }
+    }
+}
+
+impl fmt::Display for PolicyDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PolicySnapshot;
+    use crate::expr::{int, str};
+    use crate::types::{Action, Resource};
+    use crate::Expr;
+    use ockam_core::compat::string::ToString;
+
+    fn snapshot() -> PolicySnapshot {
+        PolicySnapshot::new()
+            .with_policy(Resource::new("db"), Action::new("read"), Expr::Bool(true))
+            .with_policy(Resource::new("db"), Action::new("write"), int(1))
+            .with_policy_meta(Resource::new("web"), Action::new("read"), str("a"), "web")
+    }
+
+    #[test]
+    fn identical_snapshots() {
+        let diff = snapshot().diff(&snapshot());
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "");
+    }
+
+    #[test]
+    fn a_changed_description_is_not_a_change() {
+        let other = snapshot().with_policy_meta(
+            Resource::new("web"),
+            Action::new("read"),
+            str("a"),
+            "the web can read",
+        );
+        assert!(snapshot().diff(&other).is_empty())
+    }
+
+    #[test]
+    fn added_policies() {
+        let other = snapshot().with_policy(Resource::new("api"), Action::new("read"), int(2));
+        let diff = snapshot().diff(&other);
+        let added: Vec<_> = diff.added().collect();
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].0, &Resource::new("api"));
+        assert_eq!(added[0].1, &Action::new("read"));
+        assert_eq!(diff.removed().count(), 0);
+        assert_eq!(diff.changed().count(), 0);
+        assert_eq!(diff.to_string(), "+ api read: 2\n");
+    }
+
+    #[test]
+    fn removed_policies() {
+        let diff = snapshot().diff(&PolicySnapshot::new());
+        assert_eq!(diff.removed().count(), 3);
+        assert_eq!(diff.added().count(), 0);
+        assert_eq!(diff.changed().count(), 0);
+        assert_eq!(
+            diff.to_string(),
+            "- db read: true\n- db write: 1\n- web read: \"a\"\n"
+        );
+    }
+
+    #[test]
+    fn changed_policies() {
+        let other = snapshot().with_policy(Resource::new("db"), Action::new("write"), int(2));
+        let diff = snapshot().diff(&other);
+        let changed: Vec<_> = diff.changed().collect();
+        assert_eq!(changed.len(), 1);
+        let (r, a, old, new) = changed[0];
+        assert_eq!(r, &Resource::new("db"));
+        assert_eq!(a, &Action::new("write"));
+        assert!(matches!(old, Expr::Int(1)));
+        assert!(matches!(new, Expr::Int(2)));
+        assert_eq!(diff.to_string(), "- db write: 1\n+ db write: 2\n");
+    }
+
+    #[test]
+    fn the_changes_are_ordered_by_resource_and_action() {
+        let mut other = snapshot().with_policy(Resource::new("api"), Action::new("read"), int(2));
+        other.insert(Resource::new("db"), Action::new("read"), Expr::Bool(false));
+        let other = other.with_policy(Resource::new("zone"), Action::new("read"), int(3));
+        let old = snapshot().with_policy(Resource::new("db"), Action::new("delete"), int(0));
+
+        let diff = old.diff(&other);
+        let keys: Vec<_> = diff
+            .iter()
+            .map(|c| {
+                let (r, a) = c.key();
+                (r.as_str(), a.as_str())
+            })
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                ("api", "read"),
+                ("db", "delete"),
+                ("db", "read"),
+                ("zone", "read")
+            ]
+        );
+    }
+}
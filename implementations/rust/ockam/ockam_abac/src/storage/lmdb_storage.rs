@@ -1,30 +1,33 @@
 use crate::tokio::task::{spawn_blocking, JoinError};
-use crate::{Action, Expr, PolicyStorage, Resource};
+use crate::{check_resource_parent_with, Action, Expr, PolicySnapshot, PolicyStorage, Resource};
 use core::str;
 use lmdb::{Cursor, Transaction};
 use ockam_core::async_trait;
 use ockam_core::compat::boxed::Box;
+use ockam_core::compat::string::String;
 use ockam_core::compat::vec::Vec;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
 use ockam_identity::LmdbStorage;
-use std::borrow::Cow;
 use tracing as log;
 
 use super::PolicyEntry;
 
+/// Prefix of the keys storing the parent of a resource.
+/// It cannot clash with the `{resource}:{action}` policy keys since a parent key
+/// has no action.
+const PARENT_KEY_PREFIX: &str = "~parent:";
+
 #[async_trait]
 impl PolicyStorage for LmdbStorage {
     async fn get_policy(&self, r: &Resource, a: &Action) -> Result<Option<Expr>> {
         let d = self.clone();
         let k = format!("{r}:{a}");
         let t = move || {
+            let _guard = d.transaction_guard();
             let r = d.env.begin_ro_txn().map_err(map_lmdb_err)?;
             match r.get(d.map, &k) {
-                Ok(value) => {
-                    let e: PolicyEntry = minicbor::decode(value)?;
-                    Ok(Some(e.expr.into_owned()))
-                }
+                Ok(value) => Ok(Some(PolicyEntry::decode(value)?.expr()?)),
                 Err(lmdb::Error::NotFound) => Ok(None),
                 Err(e) => Err(map_lmdb_err(e)),
             }
@@ -33,12 +36,35 @@ impl PolicyStorage for LmdbStorage {
     }
 
     async fn set_policy(&self, r: &Resource, a: &Action, c: &Expr) -> Result<()> {
-        let v = minicbor::to_vec(PolicyEntry {
-            expr: Cow::Borrowed(c),
-        })?;
+        self.set_policy_with_meta(r, a, c, None).await
+    }
+
+    async fn set_policy_with_meta(
+        &self,
+        r: &Resource,
+        a: &Action,
+        c: &Expr,
+        meta: Option<&str>,
+    ) -> Result<()> {
+        let v = PolicyEntry::encode(c, meta)?;
         self.write(format!("{r}:{a}"), v).await
     }
 
+    async fn get_policy_meta(&self, r: &Resource, a: &Action) -> Result<Option<String>> {
+        let d = self.clone();
+        let k = format!("{r}:{a}");
+        let t = move || {
+            let _guard = d.transaction_guard();
+            let r = d.env.begin_ro_txn().map_err(map_lmdb_err)?;
+            match r.get(d.map, &k) {
+                Ok(value) => Ok(PolicyEntry::decode(value)?.meta()),
+                Err(lmdb::Error::NotFound) => Ok(None),
+                Err(e) => Err(map_lmdb_err(e)),
+            }
+        };
+        spawn_blocking(t).await.map_err(map_join_err)?
+    }
+
     async fn del_policy(&self, r: &Resource, a: &Action) -> Result<()> {
         self.delete(format!("{r}:{a}")).await
     }
@@ -47,18 +73,21 @@ impl PolicyStorage for LmdbStorage {
         let d = self.clone();
         let r = r.clone();
         let t = move || {
+            let _guard = d.transaction_guard();
             let tx = d.env.begin_ro_txn().map_err(map_lmdb_err)?;
             let mut c = tx.open_ro_cursor(d.map).map_err(map_lmdb_err)?;
             let mut xs = Vec::new();
-            for entry in c.iter_from(r.as_str()) {
+            // start at the first key of the resource, not at the resources it is a prefix of,
+            // like `db-inlet` for `db`, which are sorted before it
+            for entry in c.iter_from(format!("{r}:")) {
                 let (k, v) = entry.map_err(map_lmdb_err)?;
                 let ks = str::from_utf8(k).map_err(from_utf8_err)?;
                 if let Some((prefix, a)) = ks.split_once(':') {
                     if prefix != r.as_str() {
                         break;
                     }
-                    let x: PolicyEntry = minicbor::decode(v)?;
-                    xs.push((Action::new(a), x.expr.into_owned()))
+                    let x = PolicyEntry::decode(v)?;
+                    xs.push((Action::new(a), x.expr()?))
                 } else {
                     log::warn!(key = %ks, "malformed key in policy database")
                 }
@@ -67,6 +96,163 @@ impl PolicyStorage for LmdbStorage {
         };
         spawn_blocking(t).await.map_err(map_join_err)?
     }
+
+    async fn resources(&self) -> Result<Vec<Resource>> {
+        let d = self.clone();
+        let t = move || {
+            let _guard = d.transaction_guard();
+            let tx = d.env.begin_ro_txn().map_err(map_lmdb_err)?;
+            let mut c = tx.open_ro_cursor(d.map).map_err(map_lmdb_err)?;
+            let mut rs: Vec<Resource> = Vec::new();
+            for entry in c.iter_start() {
+                let (k, _) = entry.map_err(map_lmdb_err)?;
+                if let Some((r, _)) = policy_key(k)? {
+                    // the keys are sorted, the policies of a resource are contiguous
+                    if rs.last().map(|last| last.as_str()) != Some(r) {
+                        rs.push(Resource::new(r))
+                    }
+                }
+            }
+            Ok(rs)
+        };
+        spawn_blocking(t).await.map_err(map_join_err)?
+    }
+
+    async fn count(&self) -> Result<usize> {
+        let d = self.clone();
+        let t = move || {
+            let _guard = d.transaction_guard();
+            let tx = d.env.begin_ro_txn().map_err(map_lmdb_err)?;
+            let mut c = tx.open_ro_cursor(d.map).map_err(map_lmdb_err)?;
+            let mut count = 0;
+            for entry in c.iter_start() {
+                let (k, _) = entry.map_err(map_lmdb_err)?;
+                if policy_key(k)?.is_some() {
+                    count += 1
+                }
+            }
+            Ok(count)
+        };
+        spawn_blocking(t).await.map_err(map_join_err)?
+    }
+
+    /// The policies are read in a single transaction
+    async fn snapshot(&self) -> Result<PolicySnapshot> {
+        let d = self.clone();
+        let t = move || {
+            let _guard = d.transaction_guard();
+            let tx = d.env.begin_ro_txn().map_err(map_lmdb_err)?;
+            let mut c = tx.open_ro_cursor(d.map).map_err(map_lmdb_err)?;
+            let mut snapshot = PolicySnapshot::new();
+            for entry in c.iter_start() {
+                let (k, v) = entry.map_err(map_lmdb_err)?;
+                if let Some((r, a)) = policy_key(k)? {
+                    let x = PolicyEntry::decode(v)?;
+                    snapshot.insert_with_meta(
+                        Resource::new(r),
+                        Action::new(a),
+                        x.expr()?,
+                        x.meta(),
+                    );
+                }
+            }
+            Ok(snapshot)
+        };
+        spawn_blocking(t).await.map_err(map_join_err)?
+    }
+
+    /// The snapshot is applied in a single transaction
+    async fn replace_all(&self, snapshot: PolicySnapshot) -> Result<()> {
+        let mut entries = Vec::new();
+        for (r, a, expr, meta) in snapshot.iter() {
+            let v = PolicyEntry::encode(expr, meta)?;
+            entries.push((format!("{r}:{a}"), v));
+        }
+        let d = self.clone();
+        let t = move || {
+            let _guard = d.transaction_guard();
+            let mut w = d.env.begin_rw_txn().map_err(map_lmdb_err)?;
+            let mut keys = Vec::new();
+            {
+                let mut c = w.open_ro_cursor(d.map).map_err(map_lmdb_err)?;
+                for entry in c.iter_start() {
+                    let (k, _) = entry.map_err(map_lmdb_err)?;
+                    if policy_key(k)?.is_some() {
+                        keys.push(k.to_vec())
+                    }
+                }
+            }
+            for k in keys {
+                w.del(d.map, &k, None).map_err(map_lmdb_err)?;
+            }
+            for (k, v) in entries {
+                w.put(d.map, &k, &v, lmdb::WriteFlags::empty())
+                    .map_err(map_lmdb_err)?;
+            }
+            w.commit().map_err(map_lmdb_err)
+        };
+        spawn_blocking(t).await.map_err(map_join_err)?
+    }
+
+    async fn get_resource_parent(&self, r: &Resource) -> Result<Option<Resource>> {
+        let d = self.clone();
+        let k = format!("{PARENT_KEY_PREFIX}{r}");
+        let t = move || {
+            let _guard = d.transaction_guard();
+            let r = d.env.begin_ro_txn().map_err(map_lmdb_err)?;
+            match r.get(d.map, &k) {
+                Ok(value) => {
+                    let parent = str::from_utf8(value).map_err(from_utf8_err)?;
+                    Ok(Some(Resource::new(parent)))
+                }
+                Err(lmdb::Error::NotFound) => Ok(None),
+                Err(e) => Err(map_lmdb_err(e)),
+            }
+        };
+        spawn_blocking(t).await.map_err(map_join_err)?
+    }
+
+    async fn set_resource_parent(&self, child: &Resource, parent: &Resource) -> Result<()> {
+        let d = self.clone();
+        let child = child.clone();
+        let parent = parent.clone();
+        let t = move || {
+            let _guard = d.transaction_guard();
+            let mut w = d.env.begin_rw_txn().map_err(map_lmdb_err)?;
+            check_resource_parent_with(&child, &parent, |r| {
+                match w.get(d.map, &format!("{PARENT_KEY_PREFIX}{r}")) {
+                    Ok(value) => {
+                        let parent = str::from_utf8(value).map_err(from_utf8_err)?;
+                        Ok(Some(Resource::new(parent)))
+                    }
+                    Err(lmdb::Error::NotFound) => Ok(None),
+                    Err(e) => Err(map_lmdb_err(e)),
+                }
+            })?;
+            w.put(
+                d.map,
+                &format!("{PARENT_KEY_PREFIX}{child}"),
+                &parent.as_str(),
+                lmdb::WriteFlags::empty(),
+            )
+            .map_err(map_lmdb_err)?;
+            w.commit().map_err(map_lmdb_err)
+        };
+        spawn_blocking(t).await.map_err(map_join_err)?
+    }
+}
+
+/// Split a `{resource}:{action}` policy key, return `None` for the other keys
+fn policy_key(k: &[u8]) -> Result<Option<(&str, &str)>> {
+    let ks = str::from_utf8(k).map_err(from_utf8_err)?;
+    if ks.starts_with(PARENT_KEY_PREFIX) {
+        return Ok(None);
+    }
+    let key = ks.split_once(':');
+    if key.is_none() {
+        log::warn!(key = %ks, "malformed key in policy database")
+    }
+    Ok(key)
 }
 
 fn map_join_err(err: JoinError) -> Error {
@@ -80,3 +266,84 @@ fn map_lmdb_err(err: lmdb::Error) -> Error {
 fn from_utf8_err(err: str::Utf8Error) -> Error {
     Error::new(Origin::Other, Kind::Invalid, err)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[tokio::test]
+    async fn replace_all_policies() -> Result<()> {
+        let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+        let db = LmdbStorage::new(temp_path.to_path_buf()).await?;
+
+        let r = Resource::new("db");
+        let parent = Resource::new("parent");
+        let read = Action::new("read");
+        let write = Action::new("write");
+        db.set_policy(&r, &read, &Expr::Bool(true)).await?;
+        db.set_policy(&parent, &write, &Expr::Bool(true)).await?;
+        db.set_resource_parent(&r, &parent).await?;
+        assert_eq!(db.count().await?, 2);
+        assert_eq!(db.resources().await?, vec![r.clone(), parent.clone()]);
+
+        let snapshot = PolicySnapshot::new().with_policy_meta(
+            r.clone(),
+            write.clone(),
+            Expr::Bool(false),
+            "deny",
+        );
+        db.replace_all(snapshot).await?;
+        assert!(db.get_policy(&r, &read).await?.is_none());
+        assert!(db.get_policy(&parent, &write).await?.is_none());
+        assert!(matches!(
+            db.get_policy(&r, &write).await?,
+            Some(Expr::Bool(false))
+        ));
+        assert_eq!(db.count().await?, 1);
+        assert_eq!(
+            db.get_policy_meta(&r, &write).await?.as_deref(),
+            Some("deny")
+        );
+        // the resource parents are not replaced, nor part of a snapshot
+        assert_eq!(db.get_resource_parent(&r).await?, Some(parent));
+        let snapshot = db.snapshot().await?;
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot.get_meta(&r, &write), Some("deny"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn list_the_policies_of_a_resource_prefixing_another_one() -> Result<()> {
+        let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+        let db = LmdbStorage::new(temp_path.to_path_buf()).await?;
+
+        let action = Action::new("handle_message");
+        db.set_policy(&Resource::new("db"), &action, &Expr::Bool(true))
+            .await?;
+        db.set_policy(&Resource::new("db-inlet"), &action, &Expr::Bool(false))
+            .await?;
+        let policies = db.policies(&Resource::new("db")).await?;
+        assert_eq!(policies.len(), 1);
+        assert!(matches!(policies[0].1, Expr::Bool(true)));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn concurrent_resource_parents_cannot_create_a_cycle() -> Result<()> {
+        let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+        let db = LmdbStorage::new(temp_path.to_path_buf()).await?;
+
+        for i in 0..20 {
+            let a = Resource::new(format!("a{i}"));
+            let b = Resource::new(format!("b{i}"));
+            let (r1, r2) = tokio::join!(
+                db.set_resource_parent(&a, &b),
+                db.set_resource_parent(&b, &a)
+            );
+            // the second write sees the first one and is rejected
+            assert!(r1.is_ok() != r2.is_ok());
+        }
+        Ok(())
+    }
+}
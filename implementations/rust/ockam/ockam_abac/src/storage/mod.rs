@@ -12,6 +12,10 @@ pub use sqlite_storage::*;
 
 use minicbor::{Decode, Encode};
 use ockam_core::compat::borrow::Cow;
+use ockam_core::compat::string::String;
+use ockam_core::compat::vec::Vec;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{CowBytes, Error, Result};
 
 use crate::Expr;
 
@@ -19,8 +23,79 @@ use crate::Expr;
 ///
 /// Used instead of storing plain `Expr` values to allow for additional
 /// metadata, versioning, etc.
+///
+/// The expression is stored with its versioned encoding, see [`Expr::to_bytes`].
+/// The entries stored before, with an unversioned expression, are still read and
+/// are written with the versioned encoding the next time their policy is set.
 #[derive(Debug, Encode, Decode)]
 #[rustfmt::skip]
 struct PolicyEntry<'a> {
-    #[b(0)] expr: Cow<'a, Expr>,
+    /// Expression of an entry stored without a version
+    #[b(0)] unversioned_expr: Option<Cow<'a, Expr>>,
+    /// Description of the policy, it is not used for the evaluation
+    #[b(1)] meta: Option<Cow<'a, str>>,
+    #[b(2)] expr: Option<CowBytes<'a>>,
+}
+
+impl<'a> PolicyEntry<'a> {
+    /// Encode an entry with the versioned encoding of its expression
+    fn encode(expr: &Expr, meta: Option<&str>) -> Result<Vec<u8>> {
+        Ok(minicbor::to_vec(PolicyEntry {
+            unversioned_expr: None,
+            meta: meta.map(Cow::Borrowed),
+            expr: Some(CowBytes::from(expr.to_bytes())),
+        })?)
+    }
+
+    fn decode(bytes: &'a [u8]) -> Result<Self> {
+        Ok(minicbor::decode(bytes)?)
+    }
+
+    fn expr(&self) -> Result<Expr> {
+        match (&self.expr, &self.unversioned_expr) {
+            (Some(bytes), _) => Expr::from_bytes(bytes),
+            (None, Some(expr)) => Ok(expr.clone().into_owned()),
+            (None, None) => Err(Error::new(
+                Origin::Application,
+                Kind::Serialization,
+                "the policy entry has no expression",
+            )),
+        }
+    }
+
+    fn meta(self) -> Option<String> {
+        self.meta.map(|meta| meta.into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::{eq, ident, str};
+
+    #[test]
+    fn an_unversioned_entry_is_still_read() {
+        let expr = eq([ident("subject.role"), str("admin")]);
+        // an entry written before the expressions had a versioned encoding
+        #[derive(Encode)]
+        #[rustfmt::skip]
+        struct UnversionedEntry<'a> {
+            #[b(0)] expr: &'a Expr,
+            #[b(1)] meta: Option<&'a str>,
+        }
+        let unversioned = minicbor::to_vec(UnversionedEntry {
+            expr: &expr,
+            meta: Some("admins only"),
+        })
+        .unwrap();
+        let entry = PolicyEntry::decode(&unversioned).unwrap();
+        assert!(entry.expr().unwrap().equals(&expr).unwrap());
+        assert_eq!(entry.meta().as_deref(), Some("admins only"));
+
+        let versioned = PolicyEntry::encode(&expr, None).unwrap();
+        let entry = PolicyEntry::decode(&versioned).unwrap();
+        assert_eq!(entry.expr.as_deref(), Some(expr.to_bytes().as_slice()));
+        assert!(entry.expr().unwrap().equals(&expr).unwrap());
+        assert_eq!(entry.meta(), None);
+    }
 }
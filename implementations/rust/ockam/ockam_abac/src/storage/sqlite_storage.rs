@@ -1,13 +1,12 @@
 use crate::tokio::task::{spawn_blocking, JoinError};
-use crate::{Action, Expr, PolicyStorage, Resource};
+use crate::{check_resource_parent_with, Action, Expr, PolicySnapshot, PolicyStorage, Resource};
 use ockam_core::async_trait;
 use ockam_core::compat::boxed::Box;
 use ockam_core::compat::vec::Vec;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
 use ockam_identity::SqliteStorage;
-use rusqlite::{params, ToSql};
-use std::borrow::Cow;
+use rusqlite::{params, OptionalExtension, ToSql};
 
 use super::PolicyEntry;
 
@@ -31,30 +30,37 @@ impl PolicyStorage for SqliteStorage {
         let a = a.clone();
         let t = move || {
             let conn = conn.lock().unwrap();
-            let result = conn
-                .query_row::<Option<Expr>, _, _>(
+            let value = conn
+                .query_row(
                     "SELECT value FROM policy WHERE resource = ?1 AND action = ?2;",
                     params![r, a],
-                    |row| {
-                        row.get::<_, Vec<u8>>(0).map(|value| {
-                            let e: PolicyEntry = minicbor::decode(&value).unwrap();
-                            Some(e.expr.into_owned())
-                        })
-                    },
+                    |row| row.get::<_, Vec<u8>>(0),
                 )
-                .map_err(map_sqlite_err);
-            result
+                .optional()
+                .map_err(map_sqlite_err)?;
+            match value {
+                Some(value) => Ok(Some(PolicyEntry::decode(&value)?.expr()?)),
+                None => Ok(None),
+            }
         };
         spawn_blocking(t).await.map_err(map_join_err)?
     }
 
     async fn set_policy(&self, r: &Resource, a: &Action, c: &Expr) -> Result<()> {
+        self.set_policy_with_meta(r, a, c, None).await
+    }
+
+    async fn set_policy_with_meta(
+        &self,
+        r: &Resource,
+        a: &Action,
+        c: &Expr,
+        meta: Option<&str>,
+    ) -> Result<()> {
         let conn = self.conn();
         let r = r.clone();
         let a = a.clone();
-        let v = minicbor::to_vec(PolicyEntry {
-            expr: Cow::Borrowed(c),
-        })?;
+        let v = PolicyEntry::encode(c, meta)?;
         let t = move || {
             let conn = conn.lock().unwrap();
             conn.execute(
@@ -67,6 +73,28 @@ impl PolicyStorage for SqliteStorage {
         spawn_blocking(t).await.map_err(map_join_err)?
     }
 
+    async fn get_policy_meta(&self, r: &Resource, a: &Action) -> Result<Option<String>> {
+        let conn = self.conn();
+        let r = r.clone();
+        let a = a.clone();
+        let t = move || {
+            let conn = conn.lock().unwrap();
+            let value = conn
+                .query_row(
+                    "SELECT value FROM policy WHERE resource = ?1 AND action = ?2;",
+                    params![r, a],
+                    |row| row.get::<_, Vec<u8>>(0),
+                )
+                .optional()
+                .map_err(map_sqlite_err)?;
+            match value {
+                Some(value) => Ok(PolicyEntry::decode(&value)?.meta()),
+                None => Ok(None),
+            }
+        };
+        spawn_blocking(t).await.map_err(map_join_err)?
+    }
+
     async fn del_policy(&self, r: &Resource, a: &Action) -> Result<()> {
         let conn = self.conn();
         let r = r.clone();
@@ -106,15 +134,138 @@ impl PolicyStorage for SqliteStorage {
                 .collect::<Result<Vec<(Action, Vec<u8>)>, Error>>()?;
             let decoded_result = result
                 .iter()
-                .map(|(action, value)| {
-                    let e: PolicyEntry = minicbor::decode(value).map_err(map_decode_err)?;
-                    Ok((action.to_owned(), e.expr.into_owned()))
-                })
+                .map(|(action, value)| Ok((action.to_owned(), PolicyEntry::decode(value)?.expr()?)))
                 .collect();
             decoded_result
         };
         spawn_blocking(t).await.map_err(map_join_err)?
     }
+
+    async fn resources(&self) -> Result<Vec<Resource>> {
+        let conn = self.conn();
+        let t = move || {
+            let conn = conn.lock().unwrap();
+            let mut stmt = conn
+                .prepare("SELECT DISTINCT resource FROM policy;")
+                .map_err(map_sqlite_err)?;
+            let result = stmt
+                .query_map([], |row| row.get::<_, String>(0).map(Resource::from))
+                .map_err(map_sqlite_err)?
+                .collect::<rusqlite::Result<Vec<Resource>>>()
+                .map_err(map_sqlite_err);
+            result
+        };
+        spawn_blocking(t).await.map_err(map_join_err)?
+    }
+
+    async fn count(&self) -> Result<usize> {
+        let conn = self.conn();
+        let t = move || {
+            let conn = conn.lock().unwrap();
+            conn.query_row("SELECT COUNT(*) FROM policy;", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map(|count| count as usize)
+            .map_err(map_sqlite_err)
+        };
+        spawn_blocking(t).await.map_err(map_join_err)?
+    }
+
+    /// The policies are read with a single query
+    async fn snapshot(&self) -> Result<PolicySnapshot> {
+        let conn = self.conn();
+        let t = move || {
+            let conn = conn.lock().unwrap();
+            let mut stmt = conn
+                .prepare("SELECT resource, action, value FROM policy;")
+                .map_err(map_sqlite_err)?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((
+                        Resource::from(row.get::<_, String>(0)?),
+                        Action::from(row.get::<_, String>(1)?),
+                        row.get::<_, Vec<u8>>(2)?,
+                    ))
+                })
+                .map_err(map_sqlite_err)?
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .map_err(map_sqlite_err)?;
+            let mut snapshot = PolicySnapshot::new();
+            for (r, a, value) in rows {
+                let x = PolicyEntry::decode(&value)?;
+                snapshot.insert_with_meta(r, a, x.expr()?, x.meta());
+            }
+            Ok(snapshot)
+        };
+        spawn_blocking(t).await.map_err(map_join_err)?
+    }
+
+    /// The snapshot is applied in a single transaction
+    async fn replace_all(&self, snapshot: PolicySnapshot) -> Result<()> {
+        let conn = self.conn();
+        let mut entries = Vec::new();
+        for (r, a, expr, meta) in snapshot.iter() {
+            let v = PolicyEntry::encode(expr, meta)?;
+            entries.push((r.clone(), a.clone(), v));
+        }
+        let t = move || {
+            let mut conn = conn.lock().unwrap();
+            let tx = conn.transaction().map_err(map_sqlite_err)?;
+            tx.execute("DELETE FROM policy;", [])
+                .map_err(map_sqlite_err)?;
+            for (r, a, v) in entries {
+                tx.execute(
+                    "INSERT INTO policy (resource, action, value) VALUES (?1, ?2, ?3)",
+                    params![r, a, v],
+                )
+                .map_err(map_sqlite_err)?;
+            }
+            tx.commit().map_err(map_sqlite_err)
+        };
+        spawn_blocking(t).await.map_err(map_join_err)?
+    }
+
+    async fn get_resource_parent(&self, r: &Resource) -> Result<Option<Resource>> {
+        let conn = self.conn();
+        let r = r.clone();
+        let t = move || {
+            let conn = conn.lock().unwrap();
+            conn.query_row(
+                "SELECT parent FROM resource_parent WHERE resource = ?1;",
+                params![r],
+                |row| row.get::<_, String>(0).map(Resource::from),
+            )
+            .optional()
+            .map_err(map_sqlite_err)
+        };
+        spawn_blocking(t).await.map_err(map_join_err)?
+    }
+
+    async fn set_resource_parent(&self, child: &Resource, parent: &Resource) -> Result<()> {
+        let conn = self.conn();
+        let child = child.clone();
+        let parent = parent.clone();
+        let t = move || {
+            let mut conn = conn.lock().unwrap();
+            let tx = conn.transaction().map_err(map_sqlite_err)?;
+            check_resource_parent_with(&child, &parent, |r| {
+                tx.query_row(
+                    "SELECT parent FROM resource_parent WHERE resource = ?1;",
+                    params![r],
+                    |row| row.get::<_, String>(0).map(Resource::from),
+                )
+                .optional()
+                .map_err(map_sqlite_err)
+            })?;
+            tx.execute(
+                "INSERT OR REPLACE INTO resource_parent (resource, parent) VALUES (?1, ?2)",
+                params![child, parent],
+            )
+            .map_err(map_sqlite_err)?;
+            tx.commit().map_err(map_sqlite_err)
+        };
+        spawn_blocking(t).await.map_err(map_join_err)?
+    }
 }
 
 fn map_join_err(err: JoinError) -> Error {
@@ -125,10 +276,6 @@ fn map_sqlite_err(err: rusqlite::Error) -> Error {
     Error::new(Origin::Application, Kind::Io, err)
 }
 
-fn map_decode_err(err: minicbor::decode::Error) -> Error {
-    Error::new(Origin::Application, Kind::Io, err)
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
@@ -161,6 +308,37 @@ mod test {
         let policies = db.policies(&r).await?;
         assert_eq!(policies.len(), 1);
 
+        let other = Resource::from("4");
+        db.set_policy(&other, &a, &e).await?;
+        db.set_policy(&other, &a, &e).await?;
+        assert_eq!(db.count().await?, 2);
+        assert_eq!(db.resources().await?, vec![r, other]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resource_parent() -> Result<()> {
+        let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+        let db = SqliteStorage::new(temp_path.to_path_buf()).await?;
+
+        let parent = Resource::from("parent");
+        let child = Resource::from("child");
+        let a = Action::from("handle_message");
+        let e = Expr::from_str("true")?;
+        assert_eq!(db.get_resource_parent(&child).await?, None);
+
+        db.set_resource_parent(&child, &parent).await?;
+        assert_eq!(db.get_resource_parent(&child).await?, Some(parent.clone()));
+        assert!(db.set_resource_parent(&parent, &child).await.is_err());
+
+        db.set_policy(&parent, &a, &e).await?;
+        assert!(db
+            .get_effective_policy(&child, &a)
+            .await?
+            .unwrap()
+            .equals(&e)?);
+
         Ok(())
     }
 }
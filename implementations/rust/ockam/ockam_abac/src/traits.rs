@@ -1,13 +1,174 @@
-use crate::{Action, Expr, Resource};
+use crate::{Action, Expr, PolicySnapshot, Resource};
 use ockam_core::async_trait;
 use ockam_core::compat::boxed::Box;
+use ockam_core::compat::collections::BTreeSet;
+use ockam_core::compat::format;
+use ockam_core::compat::string::String;
 use ockam_core::compat::vec::Vec;
-use ockam_core::Result;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
 
 #[async_trait]
 pub trait PolicyStorage: Send + Sync + 'static {
     async fn get_policy(&self, r: &Resource, a: &Action) -> Result<Option<Expr>>;
     async fn set_policy(&self, r: &Resource, a: &Action, c: &Expr) -> Result<()>;
+
+    /// Set the policy of a resource for an action, with a description of the policy.
+    ///
+    /// The description documents the policy, it is never used to evaluate it.
+    /// Setting a policy with `set_policy` removes its previous description.
+    async fn set_policy_with_meta(
+        &self,
+        r: &Resource,
+        a: &Action,
+        c: &Expr,
+        meta: Option<&str>,
+    ) -> Result<()>;
+
+    /// Return the description of a policy, if the policy exists and has one.
+    async fn get_policy_meta(&self, r: &Resource, a: &Action) -> Result<Option<String>>;
+
     async fn del_policy(&self, r: &Resource, a: &Action) -> Result<()>;
     async fn policies(&self, r: &Resource) -> Result<Vec<(Action, Expr)>>;
+
+    /// Return the resources having at least one policy.
+    async fn resources(&self) -> Result<Vec<Resource>>;
+
+    /// Return the number of policies, i.e. of resource and action pairs having a policy.
+    ///
+    /// The default implementation loads all the policies, storages should override it
+    /// with a more efficient count.
+    async fn count(&self) -> Result<usize> {
+        let mut count = 0;
+        for r in self.resources().await? {
+            count += self.policies(&r).await?.len();
+        }
+        Ok(count)
+    }
+
+    /// Return all the policies with their descriptions.
+    ///
+    /// Transactional storages read the policies in a single transaction, so that the snapshot
+    /// is consistent even if the storage is modified concurrently.
+    /// The default implementation reads the policies one resource at a time.
+    async fn snapshot(&self) -> Result<PolicySnapshot> {
+        let mut snapshot = PolicySnapshot::new();
+        for r in self.resources().await? {
+            for (a, expr) in self.policies(&r).await? {
+                let meta = self.get_policy_meta(&r, &a).await?;
+                snapshot.insert_with_meta(r.clone(), a, expr, meta);
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Replace all the policies with the policies of a snapshot.
+    /// The resource parents are left untouched.
+    ///
+    /// Transactional storages apply the snapshot atomically: `get_policy` either returns
+    /// the previous policies or the policies of the snapshot, never a mix of both.
+    /// The default implementation is best-effort: it deletes the previous policies then
+    /// sets the new ones, and might leave a partial set of policies if it fails.
+    async fn replace_all(&self, snapshot: PolicySnapshot) -> Result<()> {
+        for r in self.resources().await? {
+            for (a, _) in self.policies(&r).await? {
+                self.del_policy(&r, &a).await?;
+            }
+        }
+        for (r, a, expr, meta) in snapshot.iter() {
+            self.set_policy_with_meta(r, a, expr, meta).await?;
+        }
+        Ok(())
+    }
+
+    /// Return the parent of a resource, if it has one.
+    async fn get_resource_parent(&self, r: &Resource) -> Result<Option<Resource>>;
+
+    /// Make `parent` the parent of `child`, replacing any previous parent.
+    ///
+    /// Implementations must reject a parent which would create a cycle,
+    /// see [`check_resource_parent`], and check it in the same transaction as the write.
+    async fn set_resource_parent(&self, child: &Resource, parent: &Resource) -> Result<()>;
+
+    /// Return the policy of a resource for an action or, if the resource has none,
+    /// the policy of its closest ancestor.
+    async fn get_effective_policy(&self, r: &Resource, a: &Action) -> Result<Option<Expr>> {
+        Ok(self
+            .find_effective_policy(r, a)
+            .await?
+            .map(|(_, expr)| expr))
+    }
+
+    /// Return the effective policy of a resource for an action, see [`Self::get_effective_policy`],
+    /// with the resource defining it: either `r` itself or one of its ancestors.
+    async fn find_effective_policy(
+        &self,
+        r: &Resource,
+        a: &Action,
+    ) -> Result<Option<(Resource, Expr)>> {
+        let mut visited = BTreeSet::new();
+        let mut current = r.clone();
+        loop {
+            if let Some(expr) = self.get_policy(&current, a).await? {
+                return Ok(Some((current, expr)));
+            }
+            visited.insert(current.clone());
+            match self.get_resource_parent(&current).await? {
+                Some(parent) if visited.contains(&parent) => {
+                    return Err(cycle_error(r, &parent));
+                }
+                Some(parent) => current = parent,
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Check that making `parent` the parent of `child` would not create a cycle,
+/// i.e. that `child` is not `parent` itself or one of its ancestors.
+pub async fn check_resource_parent<S: PolicyStorage + ?Sized>(
+    storage: &S,
+    child: &Resource,
+    parent: &Resource,
+) -> Result<()> {
+    let mut visited = BTreeSet::new();
+    let mut current = Some(parent.clone());
+    while let Some(r) = current {
+        if &r == child || !visited.insert(r.clone()) {
+            return Err(cycle_error(child, parent));
+        }
+        current = storage.get_resource_parent(&r).await?;
+    }
+    Ok(())
+}
+
+/// Same check as [`check_resource_parent`] where the parents are read with `get_parent`.
+///
+/// The storages use it to read the parents in the transaction writing the new parent,
+/// so that no other parent can be set between the check and the write.
+pub fn check_resource_parent_with<F>(
+    child: &Resource,
+    parent: &Resource,
+    mut get_parent: F,
+) -> Result<()>
+where
+    F: FnMut(&Resource) -> Result<Option<Resource>>,
+{
+    let mut visited = BTreeSet::new();
+    let mut current = Some(parent.clone());
+    while let Some(r) = current {
+        if &r == child || !visited.insert(r.clone()) {
+            return Err(cycle_error(child, parent));
+        }
+        current = get_parent(&r)?;
+    }
+    Ok(())
+}
+
+fn cycle_error(child: &Resource, parent: &Resource) -> Error {
+    Error::new(
+        Origin::Application,
+        Kind::Conflict,
+        format!("the parent {parent} of the resource {child} creates a cycle"),
+    )
 }
@@ -20,7 +20,8 @@ use types::AddMember;
 
 use crate::authenticator::direct::types::CreateToken;
 
-const MAX_TOKEN_DURATION: Duration = Duration::from_secs(600);
+/// Maximum duration during which an enrollment token can be presented to the authority
+pub const MAX_TOKEN_DURATION: Duration = Duration::from_secs(600);
 
 /// Schema identifier for a project membership credential.
 ///
@@ -0,0 +1,183 @@
+//! Retries of the operations which can fail temporarily.
+
+use core::future::Future;
+use core::time::Duration;
+
+use ockam_node::compat::tokio::time::sleep;
+use rand::Rng;
+
+/// Delays between the attempts of an operation which can fail temporarily.
+///
+/// The first retry is made after `initial`, each following delay is multiplied by `multiplier`
+/// without exceeding `max`. A `jitter` between 0 and 1 randomly shortens each delay by up to
+/// that proportion, so that several clients don't retry at the same time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BackoffPolicy {
+    pub initial: Duration,
+    pub max: Duration,
+    pub multiplier: f64,
+    pub jitter: f64,
+    /// Maximum number of attempts, including the first one
+    pub max_attempts: usize,
+}
+
+impl Default for BackoffPolicy {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.0,
+            max_attempts: 5,
+        }
+    }
+}
+
+impl BackoffPolicy {
+    /// Exponential backoff starting at `initial`, doubling each delay
+    pub fn new(initial: Duration, max_attempts: usize) -> Self {
+        Self {
+            initial,
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    /// The same delay between all the attempts
+    pub fn fixed(interval: Duration, max_attempts: usize) -> Self {
+        Self {
+            initial: interval,
+            max: interval,
+            multiplier: 1.0,
+            jitter: 0.0,
+            max_attempts,
+        }
+    }
+
+    pub fn with_max(mut self, max: Duration) -> Self {
+        self.max = max;
+        self
+    }
+
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Delay before the retry following the failed attempt `attempt`, starting at 1, without jitter
+    pub fn delay(&self, attempt: usize) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as usize) as i32;
+        let delay = self.initial.as_secs_f64() * self.multiplier.powi(exponent);
+        if delay.is_finite() && delay < self.max.as_secs_f64() {
+            Duration::from_secs_f64(delay)
+        } else {
+            self.max
+        }
+    }
+
+    /// Delay before the retry following the failed attempt `attempt`, with jitter
+    fn jittered_delay(&self, attempt: usize) -> Duration {
+        let delay = self.delay(attempt);
+        if self.jitter > 0.0 {
+            let reduction = rand::thread_rng().gen_range(0.0..=self.jitter);
+            delay.mul_f64(1.0 - reduction)
+        } else {
+            delay
+        }
+    }
+}
+
+/// Run an operation until it succeeds, waiting between the attempts as specified by the policy.
+/// The error of the last attempt is returned if all the attempts fail.
+///
+/// The operation is run at least once, even if the policy allows no attempt.
+pub async fn retry<T, E, F, Fut>(policy: BackoffPolicy, mut operation: F) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 1;
+    loop {
+        match operation().await {
+            Ok(result) => return Ok(result),
+            Err(e) if attempt >= policy.max_attempts => return Err(e),
+            Err(_) => {
+                let delay = policy.jittered_delay(attempt);
+                debug!(%attempt, ?delay, "operation failed, retrying");
+                sleep(delay).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn delays_grow_until_the_maximum() {
+        let policy = BackoffPolicy::new(Duration::from_millis(100), 10)
+            .with_multiplier(3.0)
+            .with_max(Duration::from_secs(1));
+        let delays: Vec<u128> = (1..=4).map(|a| policy.delay(a).as_millis()).collect();
+        assert_eq!(delays, vec![100, 300, 900, 1000]);
+        assert_eq!(policy.delay(usize::MAX), Duration::from_secs(1));
+
+        let policy = BackoffPolicy::fixed(Duration::from_secs(5), 3);
+        assert_eq!(policy.delay(1), policy.delay(3));
+    }
+
+    #[test]
+    fn jitter_shortens_the_delays() {
+        let policy = BackoffPolicy::fixed(Duration::from_millis(100), 3).with_jitter(0.5);
+        for _ in 0..100 {
+            let delay = policy.jittered_delay(1);
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(100));
+        }
+    }
+
+    #[tokio::test]
+    async fn the_last_error_is_returned_when_the_attempts_are_exhausted() {
+        let attempts = AtomicUsize::new(0);
+        let policy = BackoffPolicy::new(Duration::from_millis(1), 3);
+        let result: Result<(), usize> = retry(policy, || async {
+            Err(attempts.fetch_add(1, Ordering::SeqCst) + 1)
+        })
+        .await;
+        assert_eq!(result, Err(3));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn the_final_allowed_attempt_can_succeed() {
+        let attempts = AtomicUsize::new(0);
+        let policy = BackoffPolicy::fixed(Duration::from_millis(1), 4);
+        let result: Result<&str, &str> = retry(policy, || async {
+            if attempts.fetch_add(1, Ordering::SeqCst) + 1 < 4 {
+                Err("not yet")
+            } else {
+                Ok("done")
+            }
+        })
+        .await;
+        assert_eq!(result, Ok("done"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+
+        // the operation is run once even if no attempt is allowed
+        let result: Result<(), &str> =
+            retry(policy.with_max_attempts(0), || async { Err("failed") }).await;
+        assert_eq!(result, Err("failed"));
+    }
+}
@@ -1,12 +1,15 @@
+pub mod backup;
 pub mod credentials;
 pub mod identities;
 pub mod nodes;
 pub mod projects;
+pub mod snapshot;
 pub mod spaces;
 pub mod traits;
 pub mod trust_contexts;
 pub mod vaults;
 
+pub use crate::cli_state::backup::*;
 pub use crate::cli_state::credentials::*;
 pub use crate::cli_state::identities::*;
 pub use crate::cli_state::nodes::*;
@@ -17,7 +20,7 @@ pub use crate::cli_state::trust_contexts::*;
 pub use crate::cli_state::vaults::*;
 use crate::config::cli::LegacyCliConfig;
 use miette::Diagnostic;
-use ockam::identity::Identities;
+use ockam::identity::{Identities, Identity};
 use ockam_core::compat::sync::Arc;
 use ockam_core::env::get_env_with_default;
 use ockam_identity::IdentityIdentifier;
@@ -104,17 +107,21 @@ impl CliState {
     /// There should only be one call to this function since it also performs a migration
     /// of configuration files if necessary
     pub fn initialize() -> Result<Self> {
-        let dir = Self::default_dir()?;
+        Self::initialize_in(Self::default_dir()?)
+    }
+
+    /// Return a CliState initialized in a given directory instead of the default one.
+    /// Like [`CliState::initialize`], it performs a migration of configuration files if necessary
+    pub fn initialize_in(dir: PathBuf) -> Result<Self> {
         std::fs::create_dir_all(dir.join("defaults"))?;
-        Executor::execute_future(Self::initialize_cli_state())?
+        Executor::execute_future(Self::initialize_cli_state(dir))?
     }
 
     /// Create a new CliState by initializing all of its components
     /// The calls to 'init(dir)' are loading each piece of configuration and possibly doing some
     /// configuration migration if necessary
-    async fn initialize_cli_state() -> Result<CliState> {
-        let default = Self::default_dir()?;
-        let dir = default.as_path();
+    async fn initialize_cli_state(dir: PathBuf) -> Result<CliState> {
+        let dir = dir.as_path();
         let state = Self {
             vaults: VaultsState::init(dir).await?,
             identities: IdentitiesState::init(dir).await?,
@@ -132,7 +139,7 @@ impl CliState {
     /// Reset all directories and return a new CliState
     pub async fn reset(&self) -> Result<CliState> {
         self.delete(true)?;
-        Self::initialize_cli_state().await
+        Self::initialize_cli_state(self.dir.clone()).await
     }
 
     fn migrate(&self) -> Result<()> {
@@ -276,6 +283,36 @@ impl CliState {
             .with_identities_repository(self.identities.identities_repository().await?)
             .build())
     }
+
+    /// Return the identity of the authority of a project, or of the default project if no name
+    /// is given. Return `None` if the project has no authority
+    pub async fn project_authority(&self, project_name: Option<&str>) -> Result<Option<Identity>> {
+        let project = match project_name {
+            Some(name) => self.projects.get(name)?,
+            None if self.projects.default_path()?.exists() => self.projects.default()?,
+            None => {
+                return Err(CliStateError::ResourceNotFound {
+                    resource: ProjectsState::default_filename().to_string(),
+                    name: "default".to_string(),
+                })
+            }
+        };
+        let authority_identity = match project.config().authority_identity.as_deref() {
+            Some(identity) if !identity.is_empty() => identity,
+            _ => return Ok(None),
+        };
+        let authority_identity = hex::decode(authority_identity).map_err(|_| {
+            CliStateError::InvalidOperation(format!(
+                "The authority identity of the project {} is invalid",
+                project.name()
+            ))
+        })?;
+        let identity = ockam::identity::identities()
+            .identities_creation()
+            .decode_identity(&authority_identity)
+            .await?;
+        Ok(Some(identity))
+    }
 }
 
 /// Test support
@@ -458,6 +495,166 @@ mod tests {
         assert!(!test_dir.join("config.json").exists());
     }
 
+    #[tokio::test]
+    async fn get_the_authority_of_a_project() {
+        let state = CliState::test().unwrap();
+        let error = state.project_authority(None).await.unwrap_err();
+        assert!(matches!(error, CliStateError::ResourceNotFound { .. }));
+
+        let authority = ockam::identity::identities()
+            .identities_creation()
+            .create_identity()
+            .await
+            .unwrap();
+        let project = ProjectConfig {
+            name: "p1".to_string(),
+            authority_identity: Some(authority.export_hex().unwrap()),
+            ..Default::default()
+        };
+        state.projects.create("p1", project).unwrap();
+        state
+            .projects
+            .create("p2", ProjectConfig::default())
+            .unwrap();
+
+        // the first project is the default one
+        let identity = state.project_authority(None).await.unwrap().unwrap();
+        assert_eq!(identity.identifier(), authority.identifier());
+        assert!(state.project_authority(Some("p1")).await.unwrap().is_some());
+        // a project without authority is not an error
+        assert!(state.project_authority(Some("p2")).await.unwrap().is_none());
+        let error = state.project_authority(Some("p3")).await.unwrap_err();
+        assert!(matches!(
+            error,
+            CliStateError::ResourceNotFound { ref name, .. } if name == "p3"
+        ));
+    }
+
+    #[tokio::test]
+    async fn export_and_import_state() {
+        let source = CliState::test().unwrap();
+        let vault_name = random_name();
+        source
+            .vaults
+            .create_async(&vault_name, VaultConfig::default())
+            .await
+            .unwrap();
+        let project = ProjectConfig {
+            name: "p2".to_string(),
+            ..Default::default()
+        };
+        source
+            .projects
+            .create("p1", ProjectConfig::default())
+            .unwrap();
+        source.projects.create("p2", project).unwrap();
+        source.projects.set_default("p2").unwrap();
+
+        // Without secrets, the vaults data is not exported
+        let backup = source.dir.join("backup.tar.gz");
+        let manifest = source.export(&backup, false).unwrap();
+        assert_eq!(manifest.version, BACKUP_VERSION);
+        assert_eq!(manifest.defaults.get("projects"), Some(&"p2".to_string()));
+
+        let target = CliState::test().unwrap();
+        assert_eq!(target.import(&backup).unwrap(), manifest);
+        assert_eq!(
+            target.projects.default().unwrap().config(),
+            source.projects.default().unwrap().config()
+        );
+        assert!(target.projects.get("p1").is_ok());
+        assert!(target.vaults.is_default(&vault_name).unwrap());
+        assert!(!target
+            .vaults
+            .get(&vault_name)
+            .unwrap()
+            .vault_file_path()
+            .exists());
+
+        // With secrets, the vaults data is restored as well
+        source.export(&backup, true).unwrap();
+        let target = CliState::test().unwrap();
+        target.import(&backup).unwrap();
+        assert!(target
+            .vaults
+            .get(&vault_name)
+            .unwrap()
+            .vault_file_path()
+            .exists());
+    }
+
+    #[tokio::test]
+    async fn mutations_of_a_snapshot_dont_affect_the_original_state() {
+        let original = CliState::test().unwrap();
+        let vault_name = random_name();
+        original
+            .vaults
+            .create_async(&vault_name, VaultConfig::default())
+            .await
+            .unwrap();
+        original
+            .projects
+            .create("p1", ProjectConfig::default())
+            .unwrap();
+        original.projects.set_default("p1").unwrap();
+
+        let snapshot = original
+            .snapshot_to(&CliState::test_dir().unwrap())
+            .unwrap();
+        assert_ne!(snapshot.dir, original.dir);
+        assert!(snapshot.vaults.is_default(&vault_name).unwrap());
+        assert_eq!(snapshot.projects.default().unwrap().name(), "p1");
+        // the default item of the snapshot is the copy of the original default item
+        let default = std::fs::canonicalize(snapshot.projects.default_path().unwrap()).unwrap();
+        assert!(default.starts_with(std::fs::canonicalize(&snapshot.dir).unwrap()));
+
+        snapshot
+            .projects
+            .create("p2", ProjectConfig::default())
+            .unwrap();
+        snapshot.projects.set_default("p2").unwrap();
+        snapshot.projects.delete("p1").unwrap();
+        assert_eq!(original.projects.default().unwrap().name(), "p1");
+        assert!(original.projects.get("p2").is_err());
+
+        snapshot.delete(true).unwrap();
+        assert!(original.vaults.get(&vault_name).is_ok());
+        assert!(original.projects.get("p1").is_ok());
+
+        // a snapshot is not written over an existing state
+        assert!(original.snapshot_to(&original.dir).is_err());
+    }
+
+    #[tokio::test]
+    async fn import_rejects_newer_backup_versions() {
+        let state = CliState::test().unwrap();
+        let manifest = BackupManifest {
+            version: BACKUP_VERSION + 1,
+            include_secrets: false,
+            defaults: Default::default(),
+        };
+        let contents = serde_json::to_vec(&manifest).unwrap();
+        let backup = state.dir.join("backup.tar.gz");
+        let file = std::fs::File::create(&backup).unwrap();
+        let mut archive = tar::Builder::new(flate2::write::GzEncoder::new(
+            file,
+            flate2::Compression::default(),
+        ));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        archive
+            .append_data(&mut header, "manifest.json", contents.as_slice())
+            .unwrap();
+        archive.into_inner().unwrap().finish().unwrap();
+
+        assert!(matches!(
+            state.import(&backup),
+            Err(CliStateError::InvalidVersion(_))
+        ));
+    }
+
     #[ockam_macros::test(crate = "ockam")]
     async fn integration(ctx: &mut ockam::Context) -> ockam::Result<()> {
         let sut = CliState::test()?;
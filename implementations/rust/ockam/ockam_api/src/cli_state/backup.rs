@@ -0,0 +1,195 @@
+use crate::cli_state::traits::{StateDirTrait, StateItemTrait};
+use crate::cli_state::{
+    file_stem, CliState, CliStateError, CredentialsState, IdentitiesState, ProjectsState,
+    SpacesState, TrustContextsState, VaultsState, DATA_DIR_NAME,
+};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Component, Path};
+
+use super::Result;
+
+/// Version of the backup format produced by [`CliState::export`].
+/// It must be incremented whenever the layout of a backup changes in an incompatible way.
+pub const BACKUP_VERSION: u32 = 1;
+
+/// Name of the manifest entry, always stored first in a backup archive
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Header of a backup archive, describing how it was produced
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BackupManifest {
+    /// Version of the backup format
+    pub version: u32,
+    /// True if the vaults secret material is part of the backup
+    pub include_secrets: bool,
+    /// Name of the default item for each state directory, indexed by directory name
+    #[serde(default)]
+    pub defaults: BTreeMap<String, String>,
+}
+
+/// State directories which are part of a backup.
+/// Nodes are not exported since they are bound to the local machine.
+const BACKUP_DIRS: [&str; 6] = [
+    VaultsState::DIR_NAME,
+    IdentitiesState::DIR_NAME,
+    SpacesState::DIR_NAME,
+    ProjectsState::DIR_NAME,
+    CredentialsState::DIR_NAME,
+    TrustContextsState::DIR_NAME,
+];
+
+impl CliState {
+    /// Export the vaults, identities, spaces, projects, credentials and trust contexts
+    /// to a gzipped tar archive at the given path.
+    ///
+    /// The secret material stored in the vaults is only exported if `include_secrets` is true.
+    pub fn export(&self, path: &Path, include_secrets: bool) -> Result<BackupManifest> {
+        let manifest = BackupManifest {
+            version: BACKUP_VERSION,
+            include_secrets,
+            defaults: self.default_names()?,
+        };
+
+        let file = File::create(path)?;
+        let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        archive.follow_symlinks(false);
+
+        let contents = serde_json::to_vec_pretty(&manifest)?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        archive.append_data(&mut header, MANIFEST_FILE_NAME, contents.as_slice())?;
+
+        for dir_name in BACKUP_DIRS {
+            let skip_data = dir_name == VaultsState::DIR_NAME && !include_secrets;
+            append_dir(&mut archive, &self.dir, Path::new(dir_name), skip_data)?;
+        }
+        archive.into_inner()?.finish()?;
+        Ok(manifest)
+    }
+
+    /// Import a backup created with [`CliState::export`] into this state.
+    ///
+    /// Items with the same name are overwritten and the default items of the backup are
+    /// restored.
+    pub fn import(&self, path: &Path) -> Result<BackupManifest> {
+        let file = File::open(path)?;
+        let mut archive = tar::Archive::new(GzDecoder::new(file));
+        let mut entries = archive.entries()?;
+
+        // The manifest must be read first to make sure that the backup can be restored
+        let manifest: BackupManifest = match entries.next() {
+            Some(entry) => {
+                let entry = entry?;
+                if entry.path()?.as_ref() != Path::new(MANIFEST_FILE_NAME) {
+                    return Err(CliStateError::InvalidOperation(
+                        "The backup manifest is missing".to_string(),
+                    ));
+                }
+                serde_json::from_reader(entry)?
+            }
+            None => {
+                return Err(CliStateError::InvalidOperation(
+                    "The backup is empty".to_string(),
+                ))
+            }
+        };
+        if manifest.version > BACKUP_VERSION {
+            return Err(CliStateError::InvalidVersion(manifest.version.to_string()));
+        }
+
+        for entry in entries {
+            let mut entry = entry?;
+            let entry_path = entry.path()?.to_path_buf();
+            let is_valid = match entry_path.components().next() {
+                Some(Component::Normal(first)) => BACKUP_DIRS.iter().any(|d| first == *d),
+                _ => false,
+            };
+            if !is_valid {
+                return Err(CliStateError::InvalidPath(
+                    entry_path.to_string_lossy().to_string(),
+                ));
+            }
+            entry.unpack_in(&self.dir)?;
+        }
+
+        for (dir_name, name) in &manifest.defaults {
+            match dir_name.as_str() {
+                VaultsState::DIR_NAME => self.vaults.set_default(name)?,
+                IdentitiesState::DIR_NAME => self.identities.set_default(name)?,
+                SpacesState::DIR_NAME => self.spaces.set_default(name)?,
+                ProjectsState::DIR_NAME => self.projects.set_default(name)?,
+                CredentialsState::DIR_NAME => self.credentials.set_default(name)?,
+                TrustContextsState::DIR_NAME => self.trust_contexts.set_default(name)?,
+                _ => {}
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Return the name of the default item of each exported state directory
+    fn default_names(&self) -> Result<BTreeMap<String, String>> {
+        let defaults = [
+            (VaultsState::DIR_NAME, default_name(&self.vaults)?),
+            (IdentitiesState::DIR_NAME, default_name(&self.identities)?),
+            (SpacesState::DIR_NAME, default_name(&self.spaces)?),
+            (ProjectsState::DIR_NAME, default_name(&self.projects)?),
+            (CredentialsState::DIR_NAME, default_name(&self.credentials)?),
+            (
+                TrustContextsState::DIR_NAME,
+                default_name(&self.trust_contexts)?,
+            ),
+        ];
+        Ok(defaults
+            .into_iter()
+            .filter_map(|(dir_name, name)| name.map(|n| (dir_name.to_string(), n)))
+            .collect())
+    }
+}
+
+/// Return the name of the default item of a state directory, if there is one
+fn default_name<S: StateDirTrait>(state: &S) -> Result<Option<String>> {
+    match state.default() {
+        Ok(item) => Ok(Some(file_stem(item.path())?)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Recursively append the files of `root/relative` to the archive.
+/// Symlinks and lock files are skipped, as well as the data directory if `skip_data` is true.
+fn append_dir<W: std::io::Write>(
+    archive: &mut tar::Builder<W>,
+    root: &Path,
+    relative: &Path,
+    skip_data: bool,
+) -> Result<()> {
+    let dir = root.join(relative);
+    if !dir.exists() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(&dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let file_name = entry.file_name();
+        let entry_relative = relative.join(&file_name);
+        if file_type.is_dir() {
+            if skip_data && file_name == DATA_DIR_NAME {
+                continue;
+            }
+            append_dir(archive, root, &entry_relative, false)?;
+        } else if file_type.is_file() {
+            let name = file_name.to_string_lossy();
+            if name.ends_with(".lock") || name.ends_with("-lock") {
+                continue;
+            }
+            archive.append_path_with_name(entry.path(), &entry_relative)?;
+        }
+    }
+    Ok(())
+}
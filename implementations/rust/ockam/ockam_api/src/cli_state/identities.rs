@@ -1,15 +1,20 @@
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
+use std::fs::File;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::SystemTime;
 
+use fs2::FileExt;
 use serde::{Deserialize, Serialize};
 use time::format_description::well_known::Iso8601;
 use time::OffsetDateTime;
 
+use ockam_core::async_trait;
+use ockam_core::errcode::{Kind, Origin};
 use ockam_identity::{
-    IdentitiesRepository, IdentitiesStorage, Identity, IdentityChangeHistory, IdentityIdentifier,
-    LmdbStorage,
+    AttributesEntry, IdentitiesRepository, IdentitiesStorage, Identity, IdentityAttributesReader,
+    IdentityChangeHistory, IdentityIdentifier, LmdbStorage, Timestamp,
 };
 
 use crate::cli_state::traits::{StateDirTrait, StateItemTrait};
@@ -48,6 +53,21 @@ impl IdentitiesState {
         ))))
     }
 
+    /// Return the local attributes of the identities
+    pub fn attributes_reader(&self) -> Arc<dyn IdentityAttributesReader> {
+        Arc::new(LocalAttributesReader {
+            identities: self.clone(),
+        })
+    }
+
+    /// Remove the expired local attributes from the identities files
+    pub fn prune_expired_attributes(&self) -> Result<()> {
+        for mut identity in self.list()? {
+            identity.prune_expired_attributes()?;
+        }
+        Ok(())
+    }
+
     pub fn identities_repository_path(&self) -> Result<PathBuf> {
         let lmdb_path = self
             .dir
@@ -57,6 +77,38 @@ impl IdentitiesState {
     }
 }
 
+/// The local attributes of the identities, set with `ockam identity set-attribute`.
+///
+/// They are read from the identities files each time they are requested, so that the
+/// attributes set after a node is started are taken into account by its policies.
+struct LocalAttributesReader {
+    identities: IdentitiesState,
+}
+
+#[async_trait]
+impl IdentityAttributesReader for LocalAttributesReader {
+    async fn get_attributes(
+        &self,
+        identity_id: &IdentityIdentifier,
+    ) -> ockam_core::Result<Option<AttributesEntry>> {
+        match self.identities.get_by_identifier(identity_id) {
+            Ok(state) => Ok(state.attributes_entry()?),
+            Err(CliStateError::ResourceNotFound { .. }) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn list(&self) -> ockam_core::Result<Vec<(IdentityIdentifier, AttributesEntry)>> {
+        let mut entries = vec![];
+        for state in self.identities.list()? {
+            if let Some(entry) = state.attributes_entry()? {
+                entries.push((state.identifier(), entry));
+            }
+        }
+        Ok(entries)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct IdentityState {
     name: String,
@@ -76,6 +128,105 @@ impl IdentityState {
         self.persist()
     }
 
+    /// Set a local attribute of the identity, replacing its previous value and expiration
+    pub fn set_attribute(&mut self, name: &str, value: &str) -> Result<()> {
+        self.set_attribute_with_expiration(name, value, None)
+    }
+
+    /// Set a local attribute of the identity, replacing its previous value.
+    ///
+    /// When an expiration is given, the attribute is not used to evaluate the policies after
+    /// that time, and it is removed by [`IdentityState::prune_expired_attributes`]
+    pub fn set_attribute_with_expiration(
+        &mut self,
+        name: &str,
+        value: &str,
+        expires_at: Option<SystemTime>,
+    ) -> Result<()> {
+        self.modify_config(|config| {
+            config
+                .attributes
+                .insert(name.to_string(), value.to_string());
+            match expires_at {
+                Some(expires_at) => config
+                    .attributes_expiration
+                    .insert(name.to_string(), expires_at),
+                None => config.attributes_expiration.remove(name),
+            };
+            true
+        })
+    }
+
+    /// Return all the local attributes of the identity, including the expired ones
+    pub fn attributes(&self) -> &BTreeMap<String, String> {
+        &self.config.attributes
+    }
+
+    /// Return the expiration of a local attribute, if it has one
+    pub fn attribute_expiration(&self, name: &str) -> Option<SystemTime> {
+        self.config.attributes_expiration.get(name).copied()
+    }
+
+    /// Remove the expired local attributes and persist the identity if some were removed
+    pub fn prune_expired_attributes(&mut self) -> Result<()> {
+        let now = SystemTime::now();
+        self.modify_config(|config| {
+            let expired: Vec<String> = config
+                .attributes
+                .keys()
+                .filter(|name| config.is_expired(name, now))
+                .cloned()
+                .collect();
+            for name in &expired {
+                config.attributes.remove(name);
+                config.attributes_expiration.remove(name);
+            }
+            !expired.is_empty()
+        })
+    }
+
+    /// Reload the configuration of the identity, modify it and persist it if `modify` returns
+    /// true, while holding the lock of the identity file. The commands and the nodes modifying
+    /// the same identity concurrently don't lose each other's modifications
+    fn modify_config(&mut self, modify: impl FnOnce(&mut IdentityConfig) -> bool) -> Result<()> {
+        let lock_file = File::create(self.path.with_extension("json.lock"))?;
+        lock_file.lock_exclusive()?;
+        let result = self.reload().and_then(|_| {
+            if modify(&mut self.config) {
+                self.persist()
+            } else {
+                Ok(())
+            }
+        });
+        // the lock is released anyway when the file is closed
+        let _ = lock_file.unlock();
+        result
+    }
+
+    fn reload(&mut self) -> Result<()> {
+        let contents = std::fs::read_to_string(&self.path)?;
+        self.config = serde_json::from_str(&contents)?;
+        Ok(())
+    }
+
+    fn attributes_entry(&self) -> ockam_core::Result<Option<AttributesEntry>> {
+        let now = SystemTime::now();
+        let attrs: BTreeMap<String, Vec<u8>> = self
+            .config
+            .attributes
+            .iter()
+            .filter(|(k, _)| !self.config.is_expired(k, now))
+            .map(|(k, v)| (k.clone(), v.as_bytes().to_vec()))
+            .collect();
+        if attrs.is_empty() {
+            return Ok(None);
+        }
+        let now = Timestamp::now().ok_or_else(|| {
+            ockam_core::Error::new(Origin::Core, Kind::Internal, "invalid system time")
+        })?;
+        Ok(Some(AttributesEntry::new(attrs, now, None, None)))
+    }
+
     fn build_data_path(path: &Path) -> PathBuf {
         path.parent()
             .expect("Should have parent")
@@ -105,6 +256,17 @@ impl Display for IdentityState {
             }
             None => (),
         }
+        if !self.config.attributes.is_empty() {
+            writeln!(f, "Attributes:")?;
+            for (name, value) in &self.config.attributes {
+                match self.attribute_expiration(name).map(format_time) {
+                    Some(expires_at) => {
+                        writeln!(f, "{:2}{}: {} (expires at {})", "", name, value, expires_at)?
+                    }
+                    None => writeln!(f, "{:2}{}: {}", "", name, value)?,
+                }
+            }
+        }
         Ok(())
     }
 }
@@ -113,6 +275,13 @@ impl Display for IdentityState {
 pub struct IdentityConfig {
     pub identifier: IdentityIdentifier,
     pub enrollment_status: Option<EnrollmentStatus>,
+    /// Attributes of the identity which are only known locally.
+    /// The attributes attested by a credential take precedence over them
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, String>,
+    /// Expiration of the local attributes which are only valid for a limited time
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes_expiration: BTreeMap<String, SystemTime>,
 }
 
 impl PartialEq for IdentityConfig {
@@ -128,12 +297,21 @@ impl IdentityConfig {
         Self {
             identifier: identifier.clone(),
             enrollment_status: None,
+            attributes: BTreeMap::new(),
+            attributes_expiration: BTreeMap::new(),
         }
     }
 
     pub fn identifier(&self) -> IdentityIdentifier {
         self.identifier.clone()
     }
+
+    /// Return true if the local attribute has expired at the given time
+    fn is_expired(&self, name: &str, now: SystemTime) -> bool {
+        self.attributes_expiration
+            .get(name)
+            .map_or(false, |expires_at| *expires_at <= now)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -172,6 +350,13 @@ impl Display for EnrollmentStatus {
     }
 }
 
+/// Format a time as an Iso8601 string
+fn format_time(time: SystemTime) -> String {
+    OffsetDateTime::from(time)
+        .format(&Iso8601::DEFAULT)
+        .unwrap_or_else(|_| format!("{time:?}"))
+}
+
 #[derive(Deserialize, Debug, Clone)]
 struct IdentityConfigV1 {
     identifier: IdentityIdentifier,
@@ -231,8 +416,9 @@ mod traits {
                     let _ = std::fs::remove_file(self.default_path()?);
                 }
             }
-            // Remove identity file
+            // Remove identity file and its lock file
             identity.delete()?;
+            let _ = std::fs::remove_file(identity.path.with_extension("json.lock"));
             Ok(())
         }
 
@@ -249,6 +435,8 @@ mod traits {
                     let new_config = IdentityConfig {
                         identifier: identifier.clone(),
                         enrollment_status: config.enrollment_status,
+                        attributes: BTreeMap::new(),
+                        attributes_expiration: BTreeMap::new(),
                     };
                     let identity = Identity::new(identifier, config.change_history);
                     self.identities_repository()
@@ -261,6 +449,8 @@ mod traits {
                     let new_config = IdentityConfig {
                         identifier: config.identity.identifier(),
                         enrollment_status: config.enrollment_status,
+                        attributes: BTreeMap::new(),
+                        attributes_expiration: BTreeMap::new(),
                     };
                     self.identities_repository()
                         .await?
@@ -342,6 +532,28 @@ mod tests {
         assert_eq!(actual, expected)
     }
 
+    #[test]
+    fn concurrent_modifications_of_an_identity_are_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("identity.json");
+        let mut first = IdentityState::new(path.clone(), create_identity_config()).unwrap();
+        let mut second = IdentityState::load(path.clone()).unwrap();
+
+        let expires_at = SystemTime::now() + std::time::Duration::from_secs(3600);
+        first
+            .set_attribute_with_expiration("role", "admin", None)
+            .unwrap();
+        second
+            .set_attribute_with_expiration("team", "ops", Some(expires_at))
+            .unwrap();
+        second.prune_expired_attributes().unwrap();
+
+        let config = IdentityState::load(path).unwrap().config;
+        assert_eq!(config.attributes.get("role"), Some(&"admin".to_string()));
+        assert_eq!(config.attributes.get("team"), Some(&"ops".to_string()));
+        assert!(config.attributes_expiration.contains_key("team"));
+    }
+
     fn create_identity_config() -> IdentityConfig {
         let data = hex::decode("0144c7eb72dd1e633f38e0d0521e9d5eb5072f6418176529eb1b00189e4d69ad2e000547c93239ba3d818ec26c9cdadd2a35cbdf1fa3b6d1a731e06164b1079fb7b8084f434b414d5f524b03012000000020c6c52380125d42b0b4da922b1cff8503a258c3497ec8ac0b4a3baa0d9ca7b3780301014075064b902bda9d16db81ab5f38fbcf226a0e904e517a8c087d379ea139df1f2d7fee484ac7e1c2b7ab2da75f85adef6af7ddb05e7fa8faf180820cb9e86def02").unwrap();
         let identity = Identity::new(
@@ -356,6 +568,8 @@ mod tests {
                 is_enrolled: true,
                 created_at: SystemTime::from(OffsetDateTime::from_unix_timestamp(0).unwrap()),
             }),
+            attributes: BTreeMap::new(),
+            attributes_expiration: BTreeMap::new(),
         }
     }
 
@@ -160,6 +160,17 @@ impl NodeState {
         Ok(LmdbStorage::new(self.paths.policies_storage()).await?)
     }
 
+    /// Path of the optional file declaring the portals, policies and trust contexts
+    /// which are created when the node starts
+    pub fn startup_config_path(&self) -> PathBuf {
+        self.paths.startup_config()
+    }
+
+    /// Path of the file listing the identities revoked by the node
+    pub fn revocation_list_path(&self) -> PathBuf {
+        self.paths.revocation_list()
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -308,6 +319,11 @@ pub struct NodeSetupConfig {
     pub authority_node: Option<bool>,
     pub project: Option<ProjectLookup>,
     pub api_transport: Option<CreateTransportJson>,
+
+    /// True if the node applies the edits of its startup configuration while it is running,
+    /// including when it is restarted.
+    /// The field might be missing in previous configuration files, hence it is an Option
+    pub watch_startup_config: Option<bool>,
 }
 
 impl NodeSetupConfig {
@@ -331,6 +347,15 @@ impl NodeSetupConfig {
         self
     }
 
+    pub fn set_watch_startup_config(mut self, watch: bool) -> Self {
+        self.watch_startup_config = Some(watch);
+        self
+    }
+
+    pub fn watch_startup_config(&self) -> bool {
+        self.watch_startup_config.unwrap_or(false)
+    }
+
     pub fn api_transport(&self) -> Result<&CreateTransportJson> {
         self.api_transport.as_ref().ok_or_else(|| {
             CliStateError::InvalidOperation(
@@ -383,6 +408,14 @@ impl NodePaths {
     fn policies_storage(&self) -> PathBuf {
         self.path.join("policies_storage.lmdb")
     }
+
+    fn startup_config(&self) -> PathBuf {
+        self.path.join("startup.yaml")
+    }
+
+    fn revocation_list(&self) -> PathBuf {
+        self.path.join("revoked_identities.json")
+    }
 }
 
 mod backwards_compatibility {
@@ -489,6 +522,7 @@ mod traits {
                         authority_node: setup.authority_node,
                         project: setup.project,
                         api_transport: None,
+                        watch_startup_config: None,
                     };
                     if let Some(t) = setup
                         .transports
@@ -611,6 +645,24 @@ mod tests {
         assert_eq!(config.transports.len(), 1);
     }
 
+    #[test]
+    fn the_watch_of_the_startup_config_is_kept() {
+        // the flag is missing in the previous configuration files
+        let config_json = r#"{
+            "verbose": 0,
+            "authority_node": null,
+            "project": null,
+            "api_transport": {"tt":"Tcp","tm":"Listen","addr":{"V4":"127.0.0.1:1020"}}
+        }"#;
+        let config = serde_json::from_str::<NodeSetupConfig>(config_json).unwrap();
+        assert!(!config.watch_startup_config());
+
+        let config = config.set_watch_startup_config(true);
+        let contents = serde_json::to_string(&config).unwrap();
+        let config = serde_json::from_str::<NodeSetupConfig>(&contents).unwrap();
+        assert!(config.watch_startup_config());
+    }
+
     #[tokio::test]
     async fn migrate_node_config_from_v1_to_v2() {
         // Create a v1 setup.json file
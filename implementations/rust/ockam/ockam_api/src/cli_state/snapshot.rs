@@ -0,0 +1,82 @@
+use crate::cli_state::traits::StateDirTrait;
+use crate::cli_state::{CliState, CliStateError, NodesState};
+use std::path::{Path, PathBuf};
+
+use super::Result;
+
+impl CliState {
+    /// Copy the state to `dir` and return a CliState rooted there, so that destructive
+    /// operations, like deleting nodes or resetting the state, can be run on the copy without
+    /// modifying this state.
+    ///
+    /// The links to the default items are changed to point to the items of the copy, and the
+    /// nodes of the copy are not running: their process ids are not copied. The directory
+    /// must not exist or be empty
+    pub fn snapshot_to(&self, dir: &Path) -> Result<CliState> {
+        if dir.exists() && std::fs::read_dir(dir)?.next().is_some() {
+            return Err(CliStateError::InvalidOperation(format!(
+                "The directory {} is not empty",
+                dir.display()
+            )));
+        }
+        std::fs::create_dir_all(dir)?;
+        let copy = StateCopy {
+            source: std::fs::canonicalize(&self.dir)?,
+            original_source: self.dir.clone(),
+            target: std::fs::canonicalize(dir)?,
+        };
+        copy.dir(&copy.source, &copy.target)?;
+        CliState::new(&copy.target)
+    }
+}
+
+/// Recursive copy of a state directory
+struct StateCopy {
+    /// Canonical path of the copied directory
+    source: PathBuf,
+    /// Path of the copied directory, as used by the links of the state
+    original_source: PathBuf,
+    /// Canonical path of the copy
+    target: PathBuf,
+}
+
+impl StateCopy {
+    fn dir(&self, from: &Path, to: &Path) -> Result<()> {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            let path = entry.path();
+            // the copy can be inside the copied directory, for example for the test states
+            if path == self.target || self.is_node_pid(&path) {
+                continue;
+            }
+            let destination = to.join(entry.file_name());
+            let file_type = entry.file_type()?;
+            if file_type.is_symlink() {
+                let link = std::fs::read_link(&path)?;
+                std::os::unix::fs::symlink(self.relocate(&link), &destination)?;
+            } else if file_type.is_dir() {
+                self.dir(&path, &destination)?;
+            } else {
+                std::fs::copy(&path, &destination)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the path of a link target in the copy, if it is in the copied directory
+    fn relocate(&self, link: &Path) -> PathBuf {
+        [&self.source, &self.original_source]
+            .into_iter()
+            .find_map(|root| link.strip_prefix(root).ok())
+            .map(|relative| self.target.join(relative))
+            .unwrap_or_else(|| link.to_path_buf())
+    }
+
+    /// Return true if the path is the process id file of a node: `nodes/<name>/pid`
+    fn is_node_pid(&self, path: &Path) -> bool {
+        path.file_name().map_or(false, |name| name == "pid")
+            && path.parent().and_then(Path::parent)
+                == Some(self.source.join(NodesState::DIR_NAME).as_path())
+    }
+}
@@ -0,0 +1,258 @@
+//! Circuit breaker for the connections to the controller.
+//!
+//! When the controller can't be reached, each request would otherwise go through a full
+//! secure channel handshake before failing. After [`FAILURE_THRESHOLD`] consecutive failures
+//! to connect to an address, the circuit of that address is opened and the next requests
+//! fail right away. Once the cooldown has elapsed, the circuit is half-open: a single request
+//! is let through to probe the address, and its outcome closes or re-opens the circuit.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::Result;
+use serde::Serialize;
+
+/// Number of consecutive failures after which the circuit of an address is opened
+pub const FAILURE_THRESHOLD: usize = 3;
+
+/// Time during which the requests to an address fail right away once its circuit is opened
+pub const COOLDOWN: Duration = Duration::from_secs(30);
+
+/// State of the circuit of an address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CircuitState {
+    /// The requests are sent to the address
+    Closed,
+    /// The requests fail right away until the cooldown has elapsed
+    Open,
+    /// The cooldown has elapsed, the next request probes the address
+    HalfOpen,
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitState::Closed => write!(f, "closed"),
+            CircuitState::Open => write!(f, "open"),
+            CircuitState::HalfOpen => write!(f, "half-open"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Circuit {
+    failures: usize,
+    opened_at: Option<Instant>,
+    /// Time at which the request probing a half-open circuit was let through
+    probing_since: Option<Instant>,
+}
+
+/// Circuit breaker keeping a circuit per address
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: usize,
+    cooldown: Duration,
+    circuits: Mutex<HashMap<String, Circuit>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: usize, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold,
+            cooldown,
+            circuits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Return the circuit breaker shared by all the requests to the controller sent by the node
+    /// managers of this process. Its state is not shared with other processes, for example
+    /// with the commands using an embedded node
+    pub fn global() -> &'static CircuitBreaker {
+        static BREAKER: OnceLock<CircuitBreaker> = OnceLock::new();
+        BREAKER.get_or_init(|| CircuitBreaker::new(FAILURE_THRESHOLD, COOLDOWN))
+    }
+
+    /// Return the state of the circuit of an address
+    pub fn state(&self, address: &str) -> CircuitState {
+        self.state_at(address, Instant::now())
+    }
+
+    /// Check if a request can be sent to an address.
+    ///
+    /// Return an error if the circuit is open, or if it is half-open and another request is
+    /// already probing the address. The outcome of the request must then be reported with
+    /// [`CircuitBreaker::record_success`] or [`CircuitBreaker::record_failure`]
+    pub fn acquire(&self, address: &str) -> Result<()> {
+        self.acquire_at(address, Instant::now())
+    }
+
+    /// Close the circuit of an address after a successful connection
+    pub fn record_success(&self, address: &str) {
+        self.circuits().remove(address);
+    }
+
+    /// Count a failure to connect to an address, and open its circuit if the failure threshold
+    /// is reached or if the failed request was probing the address
+    pub fn record_failure(&self, address: &str) {
+        self.record_failure_at(address, Instant::now())
+    }
+
+    fn circuits(&self) -> std::sync::MutexGuard<'_, HashMap<String, Circuit>> {
+        // a circuit is always left in a consistent state, even by a panicking thread
+        self.circuits
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn state_at(&self, address: &str, now: Instant) -> CircuitState {
+        match self.circuits().get(address) {
+            Some(circuit) => self.circuit_state(circuit, now),
+            None => CircuitState::Closed,
+        }
+    }
+
+    fn circuit_state(&self, circuit: &Circuit, now: Instant) -> CircuitState {
+        match circuit.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if now.duration_since(opened_at) < self.cooldown => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    fn acquire_at(&self, address: &str, now: Instant) -> Result<()> {
+        let mut circuits = self.circuits();
+        let circuit = match circuits.get_mut(address) {
+            Some(circuit) => circuit,
+            None => return Ok(()),
+        };
+        match self.circuit_state(circuit, now) {
+            CircuitState::Closed => Ok(()),
+            CircuitState::Open => Err(unavailable(address, "after repeated connection failures")),
+            CircuitState::HalfOpen => match circuit.probing_since {
+                // a probe which didn't report its outcome within the cooldown is abandoned
+                Some(since) if now.duration_since(since) < self.cooldown => Err(unavailable(
+                    address,
+                    "while another request checks if it can be reached again",
+                )),
+                _ => {
+                    circuit.probing_since = Some(now);
+                    Ok(())
+                }
+            },
+        }
+    }
+
+    fn record_failure_at(&self, address: &str, now: Instant) {
+        let mut circuits = self.circuits();
+        let circuit = circuits.entry(address.to_string()).or_default();
+        circuit.failures += 1;
+        let probe_failed = circuit.probing_since.take().is_some();
+        if probe_failed || circuit.failures >= self.failure_threshold {
+            warn!(
+                %address,
+                failures = circuit.failures,
+                cooldown = ?self.cooldown,
+                "opening the circuit of the controller address"
+            );
+            circuit.opened_at = Some(now);
+        }
+    }
+}
+
+fn unavailable(address: &str, reason: &str) -> ockam_core::Error {
+    ockam_core::Error::new(
+        Origin::Api,
+        Kind::Timeout,
+        format!(
+            "The requests to the controller at {address} fail right away {reason}, try again later"
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "/dnsaddr/orchestrator.ockam.io/tcp/6252/service/api";
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(3, Duration::from_secs(30))
+    }
+
+    #[test]
+    fn the_circuit_is_opened_after_repeated_failures() {
+        let breaker = breaker();
+        let now = Instant::now();
+        for _ in 0..2 {
+            assert!(breaker.acquire_at(ADDRESS, now).is_ok());
+            breaker.record_failure_at(ADDRESS, now);
+            assert_eq!(breaker.state_at(ADDRESS, now), CircuitState::Closed);
+        }
+        breaker.record_failure_at(ADDRESS, now);
+        assert_eq!(breaker.state_at(ADDRESS, now), CircuitState::Open);
+        assert!(breaker.acquire_at(ADDRESS, now).is_err());
+
+        // the other addresses are not affected
+        assert_eq!(
+            breaker.state_at("/ip4/127.0.0.1/tcp/4000", now),
+            CircuitState::Closed
+        );
+        assert!(breaker.acquire_at("/ip4/127.0.0.1/tcp/4000", now).is_ok());
+    }
+
+    #[test]
+    fn a_success_resets_the_failures() {
+        let breaker = breaker();
+        let now = Instant::now();
+        breaker.record_failure_at(ADDRESS, now);
+        breaker.record_failure_at(ADDRESS, now);
+        breaker.record_success(ADDRESS);
+        breaker.record_failure_at(ADDRESS, now);
+        assert_eq!(breaker.state_at(ADDRESS, now), CircuitState::Closed);
+    }
+
+    #[test]
+    fn a_single_probe_is_sent_once_the_cooldown_has_elapsed() {
+        let breaker = breaker();
+        let opened_at = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure_at(ADDRESS, opened_at);
+        }
+
+        let later = opened_at + Duration::from_secs(31);
+        assert_eq!(breaker.state_at(ADDRESS, later), CircuitState::HalfOpen);
+        assert!(breaker.acquire_at(ADDRESS, later).is_ok());
+        assert!(breaker.acquire_at(ADDRESS, later).is_err());
+
+        // a successful probe closes the circuit
+        breaker.record_success(ADDRESS);
+        assert_eq!(breaker.state_at(ADDRESS, later), CircuitState::Closed);
+        assert!(breaker.acquire_at(ADDRESS, later).is_ok());
+    }
+
+    #[test]
+    fn a_failed_probe_reopens_the_circuit() {
+        let breaker = breaker();
+        let opened_at = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure_at(ADDRESS, opened_at);
+        }
+
+        let later = opened_at + Duration::from_secs(31);
+        assert!(breaker.acquire_at(ADDRESS, later).is_ok());
+        breaker.record_failure_at(ADDRESS, later);
+        assert_eq!(breaker.state_at(ADDRESS, later), CircuitState::Open);
+        assert!(breaker.acquire_at(ADDRESS, later).is_err());
+
+        let much_later = later + Duration::from_secs(31);
+        assert_eq!(
+            breaker.state_at(ADDRESS, much_later),
+            CircuitState::HalfOpen
+        );
+        assert!(breaker.acquire_at(ADDRESS, much_later).is_ok());
+    }
+}
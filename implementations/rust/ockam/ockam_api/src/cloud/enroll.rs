@@ -26,14 +26,20 @@ mod node {
 
     use ockam::identity::credential::Attributes;
     use ockam_core::api::Request;
-    use ockam_core::{self, Result};
+    use ockam_core::{self, route, Result};
     use ockam_multiaddr::MultiAddr;
-    use ockam_node::Context;
+    use ockam_node::{Context, RpcClient};
 
+    use crate::authenticator::direct::TokenAcceptorClient;
     use crate::cloud::enroll::auth0::{AuthenticateOidcToken, OidcToken};
     use crate::cloud::enroll::enrollment_token::{EnrollmentToken, RequestEnrollmentToken};
     use crate::cloud::{CloudRequestWrapper, ORCHESTRATOR_RESTART_TIMEOUT};
+    use crate::error::ApiError;
+    use crate::identity::EnrollmentTicket;
+    use crate::nodes::connection::Connection;
+    use crate::nodes::models::secure_channel::CredentialExchangeMode;
     use crate::nodes::{NodeManager, NodeManagerWorker};
+    use crate::{multiaddr_to_route, DefaultAddress};
 
     const TARGET: &str = "ockam_api::cloud::enroll";
 
@@ -140,6 +146,61 @@ mod node {
             )
             .await
         }
+
+        /// Presents the one-time code of an enrollment ticket to the authority of the ticket project.
+        ///
+        /// A secure channel is first created to the authority, which must have the identity
+        /// declared in the ticket.
+        pub async fn present_enrollment_ticket(
+            &self,
+            ctx: &Context,
+            ticket: &EnrollmentTicket,
+        ) -> Result<()> {
+            let project = ticket
+                .project()
+                .ok_or_else(|| ApiError::message("The enrollment ticket has no project"))?;
+            let authority = project.authority.as_ref().ok_or_else(|| {
+                ApiError::message("The enrollment ticket project has no authority")
+            })?;
+
+            trace!(target: TARGET, project = %project.name, "presenting an enrollment ticket");
+            let connection = Connection::new(ctx, authority.address());
+            let connection_instance = NodeManager::connect(self.get().clone(), connection).await?;
+
+            let secure_channel = {
+                let mut node_manager = self.get().write().await;
+                let result = multiaddr_to_route(
+                    &connection_instance.normalized_addr,
+                    &node_manager.tcp_transport,
+                )
+                .await
+                .ok_or_else(|| ApiError::message("Invalid authority address"))?;
+                node_manager
+                    .create_secure_channel_impl(
+                        result.route,
+                        Some(vec![authority.identity_id().clone()]),
+                        CredentialExchangeMode::None,
+                        Some(Duration::from_secs(ORCHESTRATOR_RESTART_TIMEOUT)),
+                        None,
+                        ctx,
+                        None,
+                        None,
+                    )
+                    .await?
+            };
+
+            let client = TokenAcceptorClient::new(
+                RpcClient::new(
+                    route![
+                        secure_channel.encryptor_address().clone(),
+                        DefaultAddress::ENROLLMENT_TOKEN_ACCEPTOR
+                    ],
+                    ctx,
+                )
+                .await?,
+            );
+            client.present_token(ticket.one_time_code()).await
+        }
     }
 }
 
@@ -182,7 +243,7 @@ pub mod auth0 {
         pub access_token: Token,
     }
 
-    #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+    #[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Clone)]
     #[cfg_attr(test, derive(PartialEq, Eq))]
     pub struct UserInfo {
         pub sub: String,
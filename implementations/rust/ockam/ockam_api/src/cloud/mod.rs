@@ -9,10 +9,12 @@ use ockam_multiaddr::MultiAddr;
 use crate::error::ApiError;
 
 pub mod addon;
+pub mod circuit_breaker;
 pub mod enroll;
 pub mod lease_manager;
 pub mod operation;
 pub mod project;
+pub mod project_route;
 pub mod space;
 pub mod subscription;
 
@@ -90,6 +92,7 @@ mod node {
     use ockam_node::api::request_with_options;
     use ockam_node::{Context, MessageSendReceiveOptions, DEFAULT_TIMEOUT};
 
+    use crate::cloud::circuit_breaker::CircuitBreaker;
     use crate::cloud::OCKAM_CONTROLLER_IDENTITY_ID;
     use crate::error::ApiError;
     use crate::nodes::{NodeManager, NodeManagerWorker};
@@ -159,7 +162,14 @@ mod node {
 
             let secure_channels = self.secure_channels.clone();
 
-            let sc = {
+            // fail right away if the controller could not be reached by the previous requests
+            let circuit_breaker = CircuitBreaker::global();
+            let address = cloud_multiaddr.to_string();
+            circuit_breaker.acquire(&address)?;
+
+            // both the connection, opened when the route is created, and the handshake
+            // are counted as an attempt to reach the controller
+            let sc = async {
                 let cloud_route = crate::multiaddr_to_route(cloud_multiaddr, &self.tcp_transport)
                     .await
                     .ok_or_else(|| ApiError::generic("Invalid Multiaddr"))?;
@@ -168,7 +178,18 @@ mod node {
                     .with_trust_policy(TrustIdentifierPolicy::new(self.controller_identifier()));
                 secure_channels
                     .create_secure_channel(ctx, &identifier, cloud_route.route, options)
-                    .await?
+                    .await
+            }
+            .await;
+            let sc = match sc {
+                Ok(sc) => {
+                    circuit_breaker.record_success(&address);
+                    sc
+                }
+                Err(e) => {
+                    circuit_breaker.record_failure(&address);
+                    return Err(e);
+                }
             };
 
             let route = route![sc.clone(), api_service];
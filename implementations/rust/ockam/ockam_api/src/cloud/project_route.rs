@@ -0,0 +1,149 @@
+use core::fmt;
+
+use ockam_core::compat::str::FromStr;
+use ockam_core::Result;
+use ockam_multiaddr::proto::Project;
+use ockam_multiaddr::{MultiAddr, Protocol};
+
+use crate::error::ApiError;
+
+/// A route to a service of a project, like `/project/default/service/echo`.
+///
+/// A project route starts with a single `/project/<name>` protocol, and the rest of the route,
+/// which may be empty, addresses a service of that project.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProjectRoute {
+    project_name: String,
+    multiaddr: MultiAddr,
+}
+
+impl ProjectRoute {
+    /// Validate that a multiaddr is a project route
+    pub fn new(multiaddr: MultiAddr) -> Result<Self> {
+        let project_name = match multiaddr.first() {
+            Some(p) if p.code() == Project::CODE => p
+                .cast::<Project>()
+                .map(|project| project.to_string())
+                .ok_or_else(|| ApiError::message(format!("Invalid project in {multiaddr}")))?,
+            _ => {
+                return Err(ApiError::message(format!(
+                    "The route {multiaddr} must start with a /project protocol"
+                )))
+            }
+        };
+        if project_name.is_empty() {
+            return Err(ApiError::message(format!(
+                "The route {multiaddr} must specify a project name"
+            )));
+        }
+        if multiaddr.iter().skip(1).any(|p| p.code() == Project::CODE) {
+            return Err(ApiError::message(format!(
+                "The route {multiaddr} must contain a single /project protocol"
+            )));
+        }
+        Ok(Self {
+            project_name,
+            multiaddr,
+        })
+    }
+
+    /// Return a project route if the multiaddr starts with a /project protocol,
+    /// `None` if it doesn't, and an error if it is a malformed project route
+    pub fn detect(multiaddr: &MultiAddr) -> Result<Option<Self>> {
+        if multiaddr.matches(0, &[Project::CODE.into()]) {
+            Ok(Some(Self::new(multiaddr.clone())?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Name of the project
+    pub fn project_name(&self) -> &str {
+        &self.project_name
+    }
+
+    pub fn multiaddr(&self) -> &MultiAddr {
+        &self.multiaddr
+    }
+
+    pub fn into_multiaddr(self) -> MultiAddr {
+        self.multiaddr
+    }
+}
+
+impl TryFrom<MultiAddr> for ProjectRoute {
+    type Error = ockam_core::Error;
+
+    fn try_from(multiaddr: MultiAddr) -> Result<Self> {
+        Self::new(multiaddr)
+    }
+}
+
+impl From<ProjectRoute> for MultiAddr {
+    fn from(route: ProjectRoute) -> Self {
+        route.multiaddr
+    }
+}
+
+impl FromStr for ProjectRoute {
+    type Err = ockam_core::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let multiaddr = MultiAddr::from_str(s)
+            .map_err(|e| ApiError::message(format!("Invalid project route {s}: {e}")))?;
+        Self::new(multiaddr)
+    }
+}
+
+impl fmt::Display for ProjectRoute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.multiaddr.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_project_routes() {
+        let route = ProjectRoute::from_str("/project/default").unwrap();
+        assert_eq!(route.project_name(), "default");
+        assert_eq!(route.to_string(), "/project/default");
+
+        let route = ProjectRoute::from_str("/project/p1/service/echo").unwrap();
+        assert_eq!(route.project_name(), "p1");
+        assert_eq!(route.to_string(), "/project/p1/service/echo");
+        assert_eq!(
+            route.into_multiaddr(),
+            MultiAddr::from_str("/project/p1/service/echo").unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_project_routes() {
+        for s in [
+            "",
+            "/dnsaddr/localhost/tcp/4000",
+            "/service/echo",
+            "/node/n1/project/default",
+            "/project/p1/project/p2",
+            "/project",
+            "/project/",
+        ] {
+            assert!(ProjectRoute::from_str(s).is_err(), "{s} should be invalid");
+        }
+    }
+
+    #[test]
+    fn detect_project_routes() {
+        let node_route = MultiAddr::from_str("/node/n1/service/echo").unwrap();
+        assert!(ProjectRoute::detect(&node_route).unwrap().is_none());
+
+        let project_route = MultiAddr::from_str("/project/p1/service/echo").unwrap();
+        assert!(ProjectRoute::detect(&project_route).unwrap().is_some());
+
+        let malformed = MultiAddr::from_str("/project/p1/project/p2").unwrap();
+        assert!(ProjectRoute::detect(&malformed).is_err());
+    }
+}
@@ -1,6 +1,6 @@
 //! Configuration files used by the ockam CLI
 
-use crate::cli_state::{CliStateError, CredentialState, StateItemTrait};
+use crate::cli_state::{CliState, CliStateError, CredentialState, StateDirTrait, StateItemTrait};
 use crate::cloud::project::Project;
 use crate::config::{lookup::ConfigLookup, ConfigValues};
 use crate::error::ApiError;
@@ -15,6 +15,7 @@ use ockam_identity::{
 };
 use ockam_multiaddr::MultiAddr;
 use ockam_transport_tcp::TcpTransport;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::path::PathBuf;
@@ -76,7 +77,7 @@ impl LegacyCliConfig {
 
 /// A configuration struct to serialize and deserialize a trust context
 /// used within the ockam CLI and ockam node
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, JsonSchema)]
 pub struct TrustContextConfig {
     id: String,
     authority: Option<TrustAuthorityConfig>,
@@ -110,6 +111,19 @@ impl TrustContextConfig {
             .ok_or_else(|| ApiError::generic("Missing authority on trust context config"))
     }
 
+    /// Replace the way the credential issued by the authority of the trust context is retrieved
+    pub fn with_own_credential(
+        mut self,
+        own_credential: CredentialRetrieverConfig,
+    ) -> Result<Self> {
+        let authority = self
+            .authority
+            .as_mut()
+            .ok_or_else(|| ApiError::generic("Missing authority on trust context config"))?;
+        authority.own_credential = Some(own_credential);
+        Ok(self)
+    }
+
     pub async fn to_trust_context(
         &self,
         secure_channels: Arc<SecureChannels>,
@@ -141,6 +155,45 @@ impl TrustContextConfig {
         Ok(TrustContext::new(self.id.clone(), authority))
     }
 
+    /// Replace the references to the credentials of the cli state, see
+    /// [`CredentialRetrieverConfig::FromCredentialName`], by the credentials themselves
+    pub fn resolve_credential_names(&self, cli_state: &CliState) -> Result<Self> {
+        let mut config = self.clone();
+        if let Some(authority) = config.authority.as_mut() {
+            if let Some(CredentialRetrieverConfig::FromCredentialName(name)) =
+                &authority.own_credential
+            {
+                let state = cli_state.credentials.get(name)?;
+                authority.own_credential = Some(CredentialRetrieverConfig::FromPath(state));
+            }
+        }
+        Ok(config)
+    }
+
+    /// Replace the credentials of the cli state by references to their names, so that the
+    /// configuration can be shared without its credentials.
+    /// A credential which is only stored in memory can't be referenced and returns an error
+    pub fn with_credential_names(&self) -> Result<Self> {
+        let mut config = self.clone();
+        if let Some(authority) = config.authority.as_mut() {
+            match &authority.own_credential {
+                Some(CredentialRetrieverConfig::FromPath(state)) => {
+                    authority.own_credential = Some(CredentialRetrieverConfig::FromCredentialName(
+                        state.name().to_string(),
+                    ))
+                }
+                Some(CredentialRetrieverConfig::FromMemory(_)) => {
+                    return Err(ApiError::generic(&format!(
+                        "The credential of the trust context {} is not stored in the cli state",
+                        self.id
+                    )))
+                }
+                _ => {}
+            }
+        }
+        Ok(config)
+    }
+
     pub fn from_authority_identity(
         authority_identity: &str,
         credential: Option<CredentialState>,
@@ -223,8 +276,9 @@ impl TryFrom<ProjectLookup> for TrustContextConfig {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 pub struct TrustAuthorityConfig {
+    /// Hex encoded identity of the authority
     identity: String,
     own_credential: Option<CredentialRetrieverConfig>,
 }
@@ -259,14 +313,21 @@ impl TrustAuthorityConfig {
 }
 
 /// Type of credential retriever
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+///
+/// The credentials are not meant to be written by hand, so their content is not described
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, JsonSchema)]
 pub enum CredentialRetrieverConfig {
     /// Credential is stored in memory
+    #[schemars(with = "serde_json::Value")]
     FromMemory(Credential),
     /// Path to credential file
+    #[schemars(with = "serde_json::Value")]
     FromPath(CredentialState),
     /// MultiAddr to Credential Issuer
     FromCredentialIssuer(CredentialIssuerConfig),
+    /// Name of a credential stored in the cli state.
+    /// It must be resolved with [`TrustContextConfig::resolve_credential_names`] before it is used
+    FromCredentialName(String),
 }
 
 impl CredentialRetrieverConfig {
@@ -295,6 +356,9 @@ impl CredentialRetrieverConfig {
                     credential_issuer_info,
                 )))
             }
+            CredentialRetrieverConfig::FromCredentialName(name) => Err(ApiError::generic(
+                &format!("The credential {name} was not loaded from the cli state"),
+            )),
         }
     }
 }
@@ -356,9 +420,10 @@ impl Authority {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 pub struct CredentialIssuerConfig {
     pub identity: String,
+    #[schemars(with = "String")]
     pub multiaddr: MultiAddr,
 }
 
@@ -0,0 +1,379 @@
+//! Audit trail of the access decisions taken by the enforcement points of a node: the access
+//! controls of the portals and services, and the trust policies of the secure channel listeners.
+
+use std::cell::RefCell;
+use std::fs::{File, OpenOptions};
+use std::future::Future;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+use ockam::identity::{
+    IdentityIdentifier, IdentitySecureChannelLocalInfo, SecureChannelTrustInfo, TrustPolicy,
+};
+use ockam_abac::{Action, DenyReason, PolicyAccessControl, Resource};
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, IncomingAccessControl, RelayMessage, Result};
+use ockam_node::compat::tokio::sync::mpsc;
+use serde::Serialize;
+
+/// Name of the environment variable giving the path of the JSON lines file where a node
+/// writes its access decisions
+pub const OCKAM_DECISION_LOG: &str = "OCKAM_DECISION_LOG";
+
+/// Number of decisions waiting to be written before the next ones are dropped
+pub const DECISION_LOG_CAPACITY: usize = 1024;
+
+/// Action recorded for the decisions of the secure channel listeners
+pub const ACCEPT_SECURE_CHANNEL: &str = "accept_secure_channel";
+
+/// An access decision taken by an enforcement point
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DecisionEvent {
+    /// Time of the decision, in milliseconds since the UNIX epoch
+    pub timestamp: u64,
+    /// Identifier of the subject, unknown when a message doesn't come from a secure channel
+    pub subject: Option<String>,
+    pub resource: String,
+    pub action: String,
+    pub allowed: bool,
+    /// Code of the reason of a denial, see [`DenialReason::code`]
+    pub reason: Option<String>,
+}
+
+impl DecisionEvent {
+    /// Create the event of a decision. The reason is only recorded for a denial
+    pub fn new(
+        subject: Option<&IdentityIdentifier>,
+        resource: impl Into<String>,
+        action: impl Into<String>,
+        allowed: bool,
+        reason: Option<&DenialReason>,
+    ) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        Self {
+            timestamp,
+            subject: subject.map(|s| s.to_string()),
+            resource: resource.into(),
+            action: action.into(),
+            allowed,
+            reason: reason
+                .filter(|_| !allowed)
+                .map(|reason| reason.code().to_string()),
+        }
+    }
+}
+
+/// Reason of a denial by one of the access controls or trust policies of a node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DenialReason {
+    /// The peer has been revoked, see `NodeManager::revoke`
+    Revoked,
+    /// The node is paused, see `NodeManager::pause`
+    Paused,
+    /// The peer didn't present a valid credential issued by the authority of the trust context
+    CredentialRequired,
+    /// The activation policy of the outlet doesn't hold
+    Inactive,
+    /// A policy denied the access
+    Policy(DenyReason),
+}
+
+impl DenialReason {
+    /// A short, stable code for this reason, recorded in the decision log
+    pub fn code(&self) -> &'static str {
+        match self {
+            DenialReason::Revoked => "revoked",
+            DenialReason::Paused => "paused",
+            DenialReason::CredentialRequired => "credential_required",
+            DenialReason::Inactive => "inactive",
+            DenialReason::Policy(reason) => reason.code(),
+        }
+    }
+}
+
+ockam_node::tokio::task_local! {
+    /// Reason of the denial of the decision being taken by the current task
+    static DENIAL: RefCell<Option<DenialReason>>;
+}
+
+/// Record the reason why an access control or a trust policy denies an access.
+///
+/// The reason is read by the decision log wrapping the whole access control or trust policy.
+/// Only the first reason is kept, and nothing is recorded when there is no decision log
+pub(crate) fn record_denial(reason: DenialReason) {
+    let _ = DENIAL.try_with(|denial| {
+        denial.borrow_mut().get_or_insert(reason);
+    });
+}
+
+/// Take a decision and return the reason recorded by the layer which denied it, if any
+async fn decide(
+    decision: impl Future<Output = Result<bool>>,
+) -> Result<(bool, Option<DenialReason>)> {
+    DENIAL
+        .scope(RefCell::new(None), async move {
+            let allowed = decision.await?;
+            let reason = DENIAL.with(|denial| denial.borrow_mut().take());
+            Ok((allowed, reason))
+        })
+        .await
+}
+
+/// Destination of the access decisions of a node.
+///
+/// The sink is called on the path of the messages, so it must not block: a sink doing some
+/// I/O should queue the events and drop them when the queue is full
+pub trait DecisionLogSink: Send + Sync + 'static {
+    fn on_decision(&self, event: DecisionEvent);
+}
+
+/// Decision log appending each event as a line of JSON to a file.
+///
+/// The events are queued and written by a dedicated thread. When the queue is full the new
+/// events are dropped and counted, instead of slowing down the messages
+pub struct JsonLinesDecisionLog {
+    sender: mpsc::Sender<DecisionEvent>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl JsonLinesDecisionLog {
+    /// Append the decisions to a file, which is created if it doesn't exist.
+    /// At most `capacity` decisions wait to be written
+    pub fn create(path: &Path, capacity: usize) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| ockam_core::Error::new(Origin::Node, Kind::Io, e))?;
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        std::thread::Builder::new()
+            .name("decision-log".to_string())
+            .spawn(move || write_events(file, receiver))
+            .map_err(|e| ockam_core::Error::new(Origin::Node, Kind::Io, e))?;
+        Ok(Self {
+            sender,
+            dropped: Default::default(),
+        })
+    }
+
+    /// Return the number of decisions which were dropped because the queue was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl DecisionLogSink for JsonLinesDecisionLog {
+    fn on_decision(&self, event: DecisionEvent) {
+        if self.sender.try_send(event).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Write the queued events until the log is dropped, flushing the file each time the queue
+/// is empty
+fn write_events(file: File, mut receiver: mpsc::Receiver<DecisionEvent>) {
+    let mut writer = BufWriter::new(file);
+    while let Some(event) = receiver.blocking_recv() {
+        let mut next = Some(event);
+        while let Some(event) = next {
+            if let Err(e) = serde_json::to_writer(&mut writer, &event)
+                .map_err(std::io::Error::from)
+                .and_then(|_| writer.write_all(b"\n"))
+            {
+                warn!(%e, "failed to write an access decision");
+            }
+            next = receiver.try_recv().ok();
+        }
+        if let Err(e) = writer.flush() {
+            warn!(%e, "failed to write the access decisions");
+        }
+    }
+}
+
+/// Access control recording the decisions of an access control.
+///
+/// It must wrap the whole access control of a resource, so that the denials of all its layers
+/// are recorded with their reason
+#[derive(Debug)]
+pub(crate) struct DecisionLogAccessControl {
+    inner: Arc<dyn IncomingAccessControl>,
+    sink: Arc<dyn DecisionLogSink>,
+    resource: Resource,
+    action: Action,
+}
+
+impl DecisionLogAccessControl {
+    pub(crate) fn new(
+        inner: Arc<dyn IncomingAccessControl>,
+        sink: Arc<dyn DecisionLogSink>,
+        resource: Resource,
+        action: Action,
+    ) -> Self {
+        Self {
+            inner,
+            sink,
+            resource,
+            action,
+        }
+    }
+}
+
+#[async_trait]
+impl IncomingAccessControl for DecisionLogAccessControl {
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        let (allowed, reason) = decide(self.inner.is_authorized(relay_msg)).await?;
+        let subject = IdentitySecureChannelLocalInfo::find_info(relay_msg.local_message())
+            .ok()
+            .map(|info| info.their_identity_id());
+        self.sink.on_decision(DecisionEvent::new(
+            subject.as_ref(),
+            self.resource.as_str(),
+            self.action.as_str(),
+            allowed,
+            Some(&reason.unwrap_or(DenialReason::Policy(DenyReason::PolicyFalse))),
+        ));
+        Ok(allowed)
+    }
+}
+
+/// Policy access control recording the reason of its denials for the decision log
+#[derive(Debug)]
+pub(crate) struct PolicyDenialAccessControl {
+    inner: PolicyAccessControl,
+}
+
+impl PolicyDenialAccessControl {
+    pub(crate) fn new(inner: PolicyAccessControl) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl IncomingAccessControl for PolicyDenialAccessControl {
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        let decision = self.inner.decide(relay_msg).await?;
+        if let Some(reason) = decision.reason {
+            debug!(destination = %relay_msg.destination(), reason = %reason.code(), "the policy denied a message: {reason}");
+            record_denial(DenialReason::Policy(reason));
+        }
+        Ok(decision.allowed)
+    }
+}
+
+/// Trust policy of a secure channel listener recording the decisions of an inner trust policy,
+/// when there is a sink
+pub(crate) struct DecisionLogTrustPolicy<P> {
+    inner: P,
+    sink: Option<Arc<dyn DecisionLogSink>>,
+    listener: String,
+}
+
+impl<P: TrustPolicy> DecisionLogTrustPolicy<P> {
+    pub(crate) fn new(inner: P, sink: Option<Arc<dyn DecisionLogSink>>, listener: &str) -> Self {
+        Self {
+            inner,
+            sink,
+            listener: listener.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl<P: TrustPolicy> TrustPolicy for DecisionLogTrustPolicy<P> {
+    async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
+        let sink = match &self.sink {
+            Some(sink) => sink,
+            None => return self.inner.check(trust_info).await,
+        };
+        let (allowed, reason) = decide(self.inner.check(trust_info)).await?;
+        sink.on_decision(DecisionEvent::new(
+            Some(trust_info.their_identity_id()),
+            &self.listener,
+            ACCEPT_SECURE_CHANNEL,
+            allowed,
+            Some(&reason.unwrap_or(DenialReason::Policy(DenyReason::PolicyFalse))),
+        ));
+        Ok(allowed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ockam::identity::{TrustEveryonePolicy, TrustMultiIdentifiersPolicy};
+
+    use super::*;
+
+    /// Wait until the file contains the expected number of lines and return them
+    async fn read_lines(path: &Path, expected: usize) -> Vec<serde_json::Value> {
+        for _ in 0..50 {
+            let contents = std::fs::read_to_string(path).unwrap();
+            if contents.lines().count() >= expected {
+                return contents
+                    .lines()
+                    .map(|line| serde_json::from_str(line).unwrap())
+                    .collect();
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("the decisions were not written in time")
+    }
+
+    #[tokio::test]
+    async fn the_decisions_of_a_listener_are_logged() -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("decisions.jsonl");
+        let log = Arc::new(JsonLinesDecisionLog::create(&path, DECISION_LOG_CAPACITY)?);
+        let sink = Some(log.clone() as Arc<dyn DecisionLogSink>);
+        let alice = IdentityIdentifier::from_hex("a11ce");
+        let bob = IdentityIdentifier::from_hex("b0b");
+
+        let policy = DecisionLogTrustPolicy::new(
+            TrustMultiIdentifiersPolicy::new(vec![alice.clone()]),
+            sink.clone(),
+            "listener",
+        );
+        assert!(
+            policy
+                .check(&SecureChannelTrustInfo::new(alice.clone()))
+                .await?
+        );
+        assert!(
+            !policy
+                .check(&SecureChannelTrustInfo::new(bob.clone()))
+                .await?
+        );
+        let policy = DecisionLogTrustPolicy::new(TrustEveryonePolicy, sink, "api");
+        assert!(
+            policy
+                .check(&SecureChannelTrustInfo::new(bob.clone()))
+                .await?
+        );
+
+        let lines = read_lines(&path, 3).await;
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["subject"], alice.to_string());
+        assert_eq!(lines[0]["resource"], "listener");
+        assert_eq!(lines[0]["action"], ACCEPT_SECURE_CHANNEL);
+        assert_eq!(lines[0]["allowed"], true);
+        assert!(lines[0]["reason"].is_null());
+        assert!(lines[0]["timestamp"].as_u64().unwrap() > 0);
+
+        assert_eq!(lines[1]["subject"], bob.to_string());
+        assert_eq!(lines[1]["allowed"], false);
+        assert_eq!(lines[1]["reason"], "policy_false");
+
+        assert_eq!(lines[2]["resource"], "api");
+        assert_eq!(lines[2]["allowed"], true);
+        assert_eq!(log.dropped(), 0);
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use ockam_identity::credential::OneTimeCode;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::config::{cli::TrustContextConfig, lookup::ProjectLookup};
 
@@ -8,6 +9,10 @@ pub struct EnrollmentTicket {
     one_time_code: OneTimeCode,
     project: Option<ProjectLookup>,
     trust_context: Option<TrustContextConfig>,
+    /// Time after which the one-time code is not accepted by the authority anymore,
+    /// in seconds since the Unix epoch. Not set for the tickets created by older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expires_at: Option<u64>,
 }
 
 impl EnrollmentTicket {
@@ -20,9 +25,16 @@ impl EnrollmentTicket {
             one_time_code,
             project,
             trust_context,
+            expires_at: None,
         }
     }
 
+    /// Set the time after which the ticket expires, in seconds since the Unix epoch
+    pub fn with_expires_at(mut self, expires_at: u64) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
     pub fn one_time_code(&self) -> &OneTimeCode {
         &self.one_time_code
     }
@@ -30,4 +42,36 @@ impl EnrollmentTicket {
     pub fn project(&self) -> Option<&ProjectLookup> {
         self.project.as_ref()
     }
+
+    pub fn expires_at(&self) -> Option<u64> {
+        self.expires_at
+    }
+
+    /// Check that the ticket can be used to enroll at `now`, in seconds since the Unix epoch:
+    /// it must describe a project with an authority, and it must not be expired.
+    ///
+    /// Return the project of the ticket
+    pub fn validate(&self, now: u64) -> Result<&ProjectLookup, InvalidEnrollmentTicket> {
+        let project = self.project().ok_or(InvalidEnrollmentTicket::NoProject)?;
+        if project.authority.is_none() {
+            return Err(InvalidEnrollmentTicket::NoAuthority(project.name.clone()));
+        }
+        match self.expires_at {
+            Some(expires_at) if expires_at <= now => {
+                Err(InvalidEnrollmentTicket::Expired(project.name.clone()))
+            }
+            _ => Ok(project),
+        }
+    }
+}
+
+/// Reason why an enrollment ticket can't be used to enroll
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum InvalidEnrollmentTicket {
+    #[error("the enrollment ticket has no project")]
+    NoProject,
+    #[error("the project '{0}' of the enrollment ticket has no authority")]
+    NoAuthority(String),
+    #[error("the enrollment ticket for the project '{0}' has expired")]
+    Expired(String),
 }
@@ -0,0 +1,88 @@
+//! JSON Schemas of the configuration and state types, so that editors can validate
+//! and autocomplete the files using them.
+//!
+//! The schemas are derived from the types with [`JsonSchema`], which follows their serde
+//! attributes, so that a field added to a type is always described by its schema.
+//! The fields with a custom serde representation must declare the type of this representation,
+//! for example `#[schemars(with = "String")]` for a `MultiAddr`.
+
+use jsonschema::JSONSchema;
+use schemars::gen::SchemaSettings;
+use serde_json::{json, Value};
+
+pub use schemars::JsonSchema;
+
+/// Version of the JSON Schema specification used by the schemas
+pub const JSON_SCHEMA_DIALECT: &str = "http://json-schema.org/draft-07/schema#";
+
+/// Return the schema of a type, with the schemas of its fields in its `definitions`
+pub fn json_schema<T: JsonSchema>() -> Value {
+    let schema = SchemaSettings::draft07()
+        .into_generator()
+        .into_root_schema_for::<T>();
+    serde_json::to_value(schema).unwrap_or_default()
+}
+
+/// Return the schema of a type as a standalone document
+pub fn schema_document<T: JsonSchema>(title: &str) -> Value {
+    let mut schema = json_schema::<T>();
+    if let Some(object) = schema.as_object_mut() {
+        object.insert("$schema".to_string(), json!(JSON_SCHEMA_DIALECT));
+        object.insert("title".to_string(), json!(title));
+    }
+    schema
+}
+
+/// Validate a value against the schema of a type.
+/// The errors start with the JSON pointer of the invalid value, for example `/outlets/0/to`
+pub fn validate<T: JsonSchema>(value: &Value) -> Result<(), Vec<String>> {
+    let schema = json_schema::<T>();
+    let schema = JSONSchema::compile(&schema).map_err(|e| vec![format!("invalid schema: {e}")])?;
+    let result = schema.validate(value).map_err(|errors| {
+        errors
+            .map(|e| format!("{}: {e}", e.instance_path))
+            .collect()
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::cloud::enroll::auth0::UserInfo;
+    use crate::nodes::models::portal::OutletStatus;
+
+    use super::*;
+
+    #[test]
+    fn a_schema_document_has_a_dialect_and_a_title() {
+        let document = schema_document::<OutletStatus>("Outlet");
+        assert_eq!(document["$schema"], json!(JSON_SCHEMA_DIALECT));
+        assert_eq!(document["title"], json!("Outlet"));
+        assert!(JSONSchema::compile(&document).is_ok());
+    }
+
+    #[test]
+    fn the_schemas_of_the_api_types_match_their_serialization() {
+        let outlet = OutletStatus::new("127.0.0.1:5000-5010", "0#outlet", "db", None)
+            .with_active_connections(2)
+            .with_idle_timeout(Some(core::time::Duration::from_secs(30)))
+            .with_max_connections(Some(10))
+            .with_tags(BTreeMap::from([("env".to_string(), "prod".to_string())]));
+        let mut value = serde_json::to_value(outlet).unwrap();
+        value["payload"] = json!("payload");
+        assert_eq!(validate::<OutletStatus>(&value), Ok(()));
+        value["port_range"] = json!({ "first": 5000 });
+        assert!(validate::<OutletStatus>(&value).unwrap_err()[0].starts_with("/port_range"));
+
+        let user_info = json!({
+            "sub": "sub", "nickname": "nickname", "name": "name", "picture": "picture",
+            "updated_at": "2023-01-01", "email": "a@b.c", "email_verified": true
+        });
+        let parsed: UserInfo = serde_json::from_value(user_info.clone()).unwrap();
+        let value = serde_json::to_value(parsed).unwrap();
+        assert_eq!(value, user_info);
+        assert_eq!(validate::<UserInfo>(&value), Ok(()));
+    }
+}
@@ -125,15 +125,20 @@
 //!
 pub mod auth;
 pub mod authenticator;
+pub mod backoff;
 pub mod bootstrapped_identities_store;
 pub mod cli_state;
 pub mod cloud;
 pub mod config;
+pub mod decision_log;
 pub mod echoer;
 pub mod error;
 pub mod hop;
 pub mod identity;
+pub mod json_schema;
 pub mod kafka;
+pub mod local_attributes_store;
+pub mod logs;
 pub mod minicbor_url;
 pub mod nodes;
 pub mod okta;
@@ -202,6 +207,7 @@ pub mod actions {
     use ockam_abac::Action;
 
     pub const HANDLE_MESSAGE: Action = Action::assert_inline("handle_message");
+    pub const SERVE: Action = Action::assert_inline("serve");
 }
 
 pub mod resources {
@@ -209,6 +215,7 @@ pub mod resources {
 
     pub const INLET: Resource = Resource::assert_inline("tcp-inlet");
     pub const OUTLET: Resource = Resource::assert_inline("tcp-outlet");
+    pub const NODE: Resource = Resource::assert_inline("node");
 }
 
 use core::fmt;
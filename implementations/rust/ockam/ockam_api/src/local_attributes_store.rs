@@ -0,0 +1,259 @@
+use ockam::identity::{
+    AttributesEntry, IdentitiesReader, IdentitiesRepository, IdentitiesWriter, Identity,
+    IdentityAttributesReader, IdentityAttributesWriter, IdentityIdentifier,
+};
+use ockam_core::async_trait;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use tracing::trace;
+
+/// Identities repository adding the local attributes of the identities to
+/// the attributes of the repository.
+///
+/// The attributes of the repository, which come from the credentials presented by the
+/// identities, take precedence over the local attributes: a local attribute is only
+/// used when no credential attests an attribute with the same name. The expiration date
+/// and the attester of an entry are the ones of the repository entry, if there is one.
+#[derive(Clone)]
+pub struct LocalAttributesStore {
+    local: Arc<dyn IdentityAttributesReader>,
+    repository: Arc<dyn IdentitiesRepository>,
+}
+
+impl LocalAttributesStore {
+    pub fn new(
+        local: Arc<dyn IdentityAttributesReader>,
+        repository: Arc<dyn IdentitiesRepository>,
+    ) -> Self {
+        Self { local, repository }
+    }
+}
+
+/// Overlay the attributes of the repository entry on the local attributes
+fn merge(
+    local: Option<AttributesEntry>,
+    stored: Option<AttributesEntry>,
+) -> Option<AttributesEntry> {
+    match (local, stored) {
+        (Some(local), Some(stored)) => {
+            let mut attrs = local.attrs().clone();
+            attrs.extend(stored.attrs().clone());
+            Some(AttributesEntry::new(
+                attrs,
+                stored.added(),
+                stored.expires(),
+                stored.attested_by(),
+            ))
+        }
+        (local, stored) => stored.or(local),
+    }
+}
+
+#[async_trait]
+impl IdentityAttributesReader for LocalAttributesStore {
+    async fn get_attributes(
+        &self,
+        identity_id: &IdentityIdentifier,
+    ) -> Result<Option<AttributesEntry>> {
+        trace! {
+            target: "ockam_api::local_attributes_store",
+            id     = %identity_id,
+            "get_attributes"
+        }
+        let stored = self.repository.get_attributes(identity_id).await?;
+        let local = self.local.get_attributes(identity_id).await?;
+        Ok(merge(local, stored))
+    }
+
+    async fn list(&self) -> Result<Vec<(IdentityIdentifier, AttributesEntry)>> {
+        let mut entries: BTreeMap<IdentityIdentifier, AttributesEntry> =
+            self.local.list().await?.into_iter().collect();
+        for (identifier, stored) in self.repository.list().await? {
+            let local = entries.remove(&identifier);
+            if let Some(entry) = merge(local, Some(stored)) {
+                entries.insert(identifier, entry);
+            }
+        }
+        Ok(entries.into_iter().collect())
+    }
+}
+
+#[async_trait]
+impl IdentityAttributesWriter for LocalAttributesStore {
+    async fn put_attributes(
+        &self,
+        sender: &IdentityIdentifier,
+        entry: AttributesEntry,
+    ) -> Result<()> {
+        self.repository.put_attributes(sender, entry).await
+    }
+
+    async fn put_attribute_value(
+        &self,
+        subject: &IdentityIdentifier,
+        attribute_name: &str,
+        attribute_value: &str,
+    ) -> Result<()> {
+        self.repository
+            .put_attribute_value(subject, attribute_name, attribute_value)
+            .await
+    }
+
+    async fn delete(&self, identity: &IdentityIdentifier) -> Result<()> {
+        self.repository.delete(identity).await
+    }
+}
+
+#[async_trait]
+impl IdentitiesReader for LocalAttributesStore {
+    async fn retrieve_identity(&self, identifier: &IdentityIdentifier) -> Result<Option<Identity>> {
+        self.repository.retrieve_identity(identifier).await
+    }
+    async fn get_identity(&self, identifier: &IdentityIdentifier) -> Result<Identity> {
+        self.repository.get_identity(identifier).await
+    }
+}
+
+#[async_trait]
+impl IdentitiesWriter for LocalAttributesStore {
+    async fn update_identity(&self, identity: &Identity) -> Result<()> {
+        self.repository.update_identity(identity).await
+    }
+}
+
+impl IdentitiesRepository for LocalAttributesStore {
+    fn as_attributes_reader(&self) -> Arc<dyn IdentityAttributesReader> {
+        Arc::new(self.clone())
+    }
+
+    fn as_attributes_writer(&self) -> Arc<dyn IdentityAttributesWriter> {
+        Arc::new(self.clone())
+    }
+
+    fn as_identities_reader(&self) -> Arc<dyn IdentitiesReader> {
+        Arc::new(self.clone())
+    }
+
+    fn as_identities_writer(&self) -> Arc<dyn IdentitiesWriter> {
+        Arc::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use ockam::compat::tokio;
+    use ockam::identity::Timestamp;
+    use ockam_abac::{AbacAccessControl, Env};
+    use ockam_node::Context;
+
+    use crate::cli_state::traits::StateDirTrait;
+    use crate::cli_state::IdentityConfig;
+    use crate::util::test_utils::start_manager_for_tests;
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn local_attributes_are_used_by_policies(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let alice = handler
+            .secure_channels
+            .identities()
+            .identities_creation()
+            .create_identity()
+            .await?;
+        let mut alice_state = handler
+            .cli_state
+            .identities
+            .create("alice", IdentityConfig::new(&alice.identifier()).await)?;
+
+        let repository = handler.secure_channels.identities().repository();
+        let expression = ockam_abac::parse(r#"(= subject.role "admin")"#)?.unwrap();
+        let access_control = AbacAccessControl::new(repository.clone(), expression, Env::new());
+        assert!(
+            !access_control
+                .is_identity_authorized(alice.identifier())
+                .await?
+        );
+
+        // the attribute is used without restarting the node
+        alice_state.set_attribute("role", "admin")?;
+        assert!(
+            access_control
+                .is_identity_authorized(alice.identifier())
+                .await?
+        );
+
+        // the attributes of a credential take precedence over the local ones
+        let attrs = [("role".to_string(), b"member".to_vec())].into();
+        let entry = super::AttributesEntry::new(attrs, Timestamp::now().unwrap(), None, None);
+        repository
+            .put_attributes(&alice.identifier(), entry)
+            .await?;
+        assert!(
+            !access_control
+                .is_identity_authorized(alice.identifier())
+                .await?
+        );
+
+        alice_state.set_attribute("team", "ops")?;
+        let entry = repository
+            .get_attributes(&alice.identifier())
+            .await?
+            .unwrap();
+        assert_eq!(entry.attrs().get("role"), Some(&b"member".to_vec()));
+        assert_eq!(entry.attrs().get("team"), Some(&b"ops".to_vec()));
+
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn expired_local_attributes_are_not_used_by_policies(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let alice = handler
+            .secure_channels
+            .identities()
+            .identities_creation()
+            .create_identity()
+            .await?;
+        let mut alice_state = handler
+            .cli_state
+            .identities
+            .create("alice", IdentityConfig::new(&alice.identifier()).await)?;
+
+        let repository = handler.secure_channels.identities().repository();
+        let expression = ockam_abac::parse(r#"(= subject.role "admin")"#)?.unwrap();
+        let access_control = AbacAccessControl::new(repository.clone(), expression, Env::new());
+
+        let expires_at = SystemTime::now() + Duration::from_millis(500);
+        alice_state.set_attribute_with_expiration("role", "admin", Some(expires_at))?;
+        alice_state.set_attribute("team", "ops")?;
+        assert!(
+            access_control
+                .is_identity_authorized(alice.identifier())
+                .await?
+        );
+
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        assert!(
+            !access_control
+                .is_identity_authorized(alice.identifier())
+                .await?
+        );
+
+        // the expired attribute is removed from the state, the other ones are kept
+        handler.cli_state.identities.prune_expired_attributes()?;
+        let alice_state = handler.cli_state.identities.get("alice")?;
+        assert!(!alice_state.attributes().contains_key("role"));
+        assert_eq!(alice_state.attribute_expiration("role"), None);
+        assert_eq!(
+            alice_state.attributes().get("team").map(String::as_str),
+            Some("ops")
+        );
+
+        context.stop().await
+    }
+}
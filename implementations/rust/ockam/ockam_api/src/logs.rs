@@ -0,0 +1,142 @@
+//! Broadcast of the log lines of the process, so that clients can follow the logs of a node.
+
+use core::fmt::{self, Write};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, Weak};
+
+use time::format_description::well_known::Iso8601;
+use time::OffsetDateTime;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Number of lines kept for a subscription until they are polled.
+/// A subscription lagging behind by more lines loses the oldest ones
+pub const LOG_LINES_CAPACITY: usize = 1024;
+
+/// Buffers of the current subscriptions, with their most verbose level.
+/// A buffer is released as soon as its [`LogSubscription`] is dropped
+static SUBSCRIPTIONS: Mutex<Vec<(Level, Weak<Mutex<LogBuffer>>)>> = Mutex::new(Vec::new());
+
+/// Lines of a subscription which were not polled yet
+#[derive(Default)]
+struct LogBuffer {
+    lines: VecDeque<String>,
+    dropped: usize,
+}
+
+impl LogBuffer {
+    fn push(&mut self, line: String) {
+        if self.lines.len() == LOG_LINES_CAPACITY {
+            self.lines.pop_front();
+            self.dropped += 1;
+        }
+        self.lines.push_back(line);
+    }
+}
+
+/// Subscription to the log lines broadcast by the [`LogBroadcastLayer`].
+///
+/// The lines are filtered by level when they are logged, so the lines which are too verbose
+/// for this subscription never take any room in its buffer.
+/// Logging a line never waits for the subscriptions: a subscription which is not polled in time
+/// loses its oldest lines, see [`LOG_LINES_CAPACITY`]
+pub struct LogSubscription {
+    level: Level,
+    buffer: Arc<Mutex<LogBuffer>>,
+}
+
+impl LogSubscription {
+    /// Most verbose level of the lines of this subscription
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Return the lines received since the previous call.
+    /// If some lines were dropped, the first line gives their number
+    pub fn take_lines(&self) -> Vec<String> {
+        let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+        let mut lines = Vec::with_capacity(buffer.lines.len() + 1);
+        if buffer.dropped > 0 {
+            lines.push(format!("{} lines dropped", buffer.dropped));
+            buffer.dropped = 0;
+        }
+        lines.extend(buffer.lines.drain(..));
+        lines
+    }
+}
+
+/// Receive the log lines broadcast by the [`LogBroadcastLayer`] from now on,
+/// up to the `level` verbosity
+pub fn subscribe(level: Level) -> LogSubscription {
+    let buffer = Arc::new(Mutex::new(LogBuffer::default()));
+    let mut subscriptions = SUBSCRIPTIONS.lock().unwrap_or_else(|e| e.into_inner());
+    subscriptions.retain(|(_, b)| b.strong_count() > 0);
+    subscriptions.push((level, Arc::downgrade(&buffer)));
+    LogSubscription { level, buffer }
+}
+
+/// Tracing layer broadcasting the events which pass the filters of the subscriber.
+/// An event is only formatted when a subscription accepts its level
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogBroadcastLayer;
+
+impl<S: Subscriber> Layer<S> for LogBroadcastLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let level = *metadata.level();
+        let buffers: Vec<Arc<Mutex<LogBuffer>>> = {
+            let mut subscriptions = SUBSCRIPTIONS.lock().unwrap_or_else(|e| e.into_inner());
+            subscriptions.retain(|(_, b)| b.strong_count() > 0);
+            subscriptions
+                .iter()
+                .filter(|(l, _)| level <= *l)
+                .filter_map(|(_, b)| b.upgrade())
+                .collect()
+        };
+        if buffers.is_empty() {
+            return;
+        }
+        let mut visitor = LineVisitor::default();
+        event.record(&mut visitor);
+        let time = OffsetDateTime::now_utc()
+            .format(&Iso8601::DEFAULT)
+            .unwrap_or_default();
+        let line = format!(
+            "{time} {level:>5} {}: {}{}",
+            metadata.target(),
+            visitor.message,
+            visitor.fields
+        );
+        for buffer in buffers {
+            buffer
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(line.clone());
+        }
+    }
+}
+
+#[derive(Default)]
+struct LineVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for LineVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value)
+        } else {
+            let _ = write!(self.fields, " {}={value}", field.name());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.fields, " {}={value:?}", field.name());
+        }
+    }
+}
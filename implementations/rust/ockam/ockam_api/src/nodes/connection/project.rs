@@ -80,6 +80,7 @@ impl Instantiator for ProjectInstantiator {
                 self.identity_name.clone(),
                 &self.context,
                 self.credential_name.clone(),
+                None,
             )
             .await?;
 
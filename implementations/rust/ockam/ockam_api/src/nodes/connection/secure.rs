@@ -70,6 +70,7 @@ impl Instantiator for SecureChannelInstantiator {
                 None,
                 &self.context,
                 None,
+                None,
             )
             .await?;
 
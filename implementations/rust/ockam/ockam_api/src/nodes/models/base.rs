@@ -1,5 +1,8 @@
 //! Nodemanager API types
 
+use core::fmt;
+use core::str::FromStr;
+
 use minicbor::{Decode, Encode};
 
 #[cfg(feature = "tag")]
@@ -21,6 +24,11 @@ pub struct NodeStatus {
 }
 
 impl NodeStatus {
+    /// Return the status of the node, `None` if it is unknown to this version
+    pub fn run_status(&self) -> Option<NodeRunStatus> {
+        self.status.parse().ok()
+    }
+
     pub fn new(
         node_name: impl Into<String>,
         status: impl Into<String>,
@@ -37,3 +45,67 @@ impl NodeStatus {
         }
     }
 }
+
+/// Status of a node, sent as a string in [`NodeStatus`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeRunStatus {
+    /// The node is restoring its startup configuration
+    Starting,
+    /// The node is running but doesn't accept messages, see `NodeManager::pause`
+    Paused,
+    Running,
+    /// The node doesn't respond
+    NotRunning,
+}
+
+impl NodeRunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodeRunStatus::Starting => "Starting",
+            NodeRunStatus::Paused => "Paused",
+            NodeRunStatus::Running => "Running",
+            NodeRunStatus::NotRunning => "Not running",
+        }
+    }
+}
+
+impl fmt::Display for NodeRunStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for NodeRunStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            NodeRunStatus::Starting,
+            NodeRunStatus::Paused,
+            NodeRunStatus::Running,
+            NodeRunStatus::NotRunning,
+        ]
+        .into_iter()
+        .find(|status| status.as_str() == s)
+        .ok_or_else(|| format!("unknown node status {s}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_status_of_a_node_is_sent_as_a_string() {
+        for status in [
+            NodeRunStatus::Starting,
+            NodeRunStatus::Paused,
+            NodeRunStatus::Running,
+            NodeRunStatus::NotRunning,
+        ] {
+            let node_status = NodeStatus::new("node", status.as_str(), 1, 1);
+            assert_eq!(node_status.run_status(), Some(status));
+        }
+        assert_eq!(NodeStatus::new("node", "Sleeping", 1, 1).run_status(), None);
+    }
+}
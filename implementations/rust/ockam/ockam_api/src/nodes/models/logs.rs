@@ -0,0 +1,66 @@
+use minicbor::{Decode, Encode};
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Request body when subscribing to the log lines of a node
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SubscribeToLogs {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4382715>,
+    /// Most verbose level of the lines to return: error, warn, info, debug or trace
+    #[n(1)] pub level: String,
+}
+
+impl SubscribeToLogs {
+    pub fn new(level: impl Into<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            level: level.into(),
+        }
+    }
+}
+
+/// Response body when subscribing to the log lines of a node
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct LogsSubscription {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7015394>,
+    /// Identifier of the subscription, used to get its lines and to unsubscribe
+    #[n(1)] pub id: String,
+}
+
+impl LogsSubscription {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            id: id.into(),
+        }
+    }
+}
+
+/// Response body when returning the log lines of a subscription
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct LogLines {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5521860>,
+    #[n(1)] pub lines: Vec<String>,
+}
+
+impl LogLines {
+    pub fn new(lines: Vec<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            lines,
+        }
+    }
+}
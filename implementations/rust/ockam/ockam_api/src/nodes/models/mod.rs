@@ -7,9 +7,12 @@ pub mod credentials;
 pub mod flow_controls;
 pub mod forwarder;
 pub mod identity;
+pub mod logs;
 pub mod policy;
 pub mod portal;
 pub mod secure_channel;
 pub mod services;
+pub mod startup_config;
 pub mod transport;
+pub mod trust_context;
 pub mod workers;
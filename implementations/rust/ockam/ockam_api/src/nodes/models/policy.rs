@@ -1,5 +1,9 @@
+use std::fmt::{Display, Formatter};
+
 use minicbor::{Decode, Encode};
-use ockam_abac::{Action, Expr};
+use ockam_abac::{Action, Expr, Resource, TraceStep};
+use ockam_identity::IdentityIdentifier;
+use serde::{Serialize, Serializer};
 
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
@@ -11,6 +15,8 @@ pub struct Policy {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<2000111>,
     #[n(1)] expression: Expr,
+    /// Why the policy exists, it is not used to evaluate the policy
+    #[n(2)] description: Option<String>,
 }
 
 impl Policy {
@@ -19,12 +25,22 @@ impl Policy {
             #[cfg(feature = "tag")]
             tag: TypeTag,
             expression: e,
+            description: None,
         }
     }
 
+    pub fn with_description(mut self, description: Option<String>) -> Self {
+        self.description = description;
+        self
+    }
+
     pub fn expression(&self) -> &Expr {
         &self.expression
     }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
 }
 
 #[derive(Debug, Decode, Encode)]
@@ -79,3 +95,323 @@ impl Expression {
         &self.expr
     }
 }
+
+/// Request for the policies of a node, grouped by resource
+#[derive(Debug, Default, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PolicyMapRequest {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5302871>,
+    /// Only return the resources starting with this prefix
+    #[n(1)] resource_prefix: Option<String>,
+}
+
+impl PolicyMapRequest {
+    pub fn new(resource_prefix: Option<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            resource_prefix,
+        }
+    }
+
+    pub fn resource_prefix(&self) -> Option<&str> {
+        self.resource_prefix.as_deref()
+    }
+}
+
+/// All the policies of a node, grouped by resource.
+///
+/// The resources are sorted by name, and the policies of each resource by action
+#[derive(Debug, Default, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PolicyMap {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<1178046>,
+    #[n(1)] resources: Vec<ResourcePolicies>,
+}
+
+impl PolicyMap {
+    pub fn new(resources: Vec<ResourcePolicies>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            resources,
+        }
+    }
+
+    pub fn resources(&self) -> &[ResourcePolicies] {
+        &self.resources
+    }
+}
+
+/// The policies defined on a resource
+#[derive(Debug, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ResourcePolicies {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<8841320>,
+    #[serde(serialize_with = "serialize_display")]
+    #[n(1)] resource: Resource,
+    #[n(2)] policies: Vec<ActionPolicy>,
+}
+
+impl ResourcePolicies {
+    pub fn new(resource: Resource, policies: Vec<ActionPolicy>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            resource,
+            policies,
+        }
+    }
+
+    pub fn resource(&self) -> &Resource {
+        &self.resource
+    }
+
+    pub fn policies(&self) -> &[ActionPolicy] {
+        &self.policies
+    }
+}
+
+/// The policy of a resource for an action
+#[derive(Debug, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ActionPolicy {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<2467915>,
+    #[serde(serialize_with = "serialize_display")]
+    #[n(1)] action: Action,
+    #[serde(serialize_with = "serialize_display")]
+    #[n(2)] expression: Expr,
+    /// Why the policy exists, it is not used to evaluate the policy
+    #[n(3)] description: Option<String>,
+}
+
+impl ActionPolicy {
+    pub fn new(action: Action, expression: Expr, description: Option<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            action,
+            expression,
+            description,
+        }
+    }
+
+    pub fn action(&self) -> &Action {
+        &self.action
+    }
+
+    pub fn expression(&self) -> &Expr {
+        &self.expression
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+}
+
+/// Serialize the resources, actions and expressions with their textual form
+fn serialize_display<T: Display, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+/// Where the effective policy of a resource and action comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+pub enum PolicyOrigin {
+    /// The policy is defined on the resource itself
+    #[n(0)] Exact,
+    /// The policy is inherited from an ancestor of the resource
+    #[n(1)] Parent,
+    /// No policy is defined, the access is denied
+    #[n(2)] Default,
+}
+
+impl Display for PolicyOrigin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PolicyOrigin::Exact => "exact",
+            PolicyOrigin::Parent => "parent",
+            PolicyOrigin::Default => "default",
+        })
+    }
+}
+
+/// Policy applied to a resource and action, after the resolution of the inherited policies
+#[derive(Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct EffectivePolicy {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6620478>,
+    #[n(1)] origin: PolicyOrigin,
+    /// Resource defining the policy, `None` for the default policy
+    #[n(2)] defined_on: Option<Resource>,
+    #[n(3)] expression: Expr,
+}
+
+impl EffectivePolicy {
+    pub fn new(origin: PolicyOrigin, defined_on: Option<Resource>, expression: Expr) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            origin,
+            defined_on,
+            expression,
+        }
+    }
+
+    pub fn origin(&self) -> PolicyOrigin {
+        self.origin
+    }
+
+    pub fn defined_on(&self) -> Option<&Resource> {
+        self.defined_on.as_ref()
+    }
+
+    pub fn expression(&self) -> &Expr {
+        &self.expression
+    }
+}
+
+/// Request to evaluate the policy of a resource and action for an identity,
+/// without performing the action
+#[derive(Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PolicyEvaluationRequest {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4711293>,
+    #[n(1)] identifier: IdentityIdentifier,
+    #[n(2)] trace: bool,
+}
+
+impl PolicyEvaluationRequest {
+    pub fn new(identifier: IdentityIdentifier, trace: bool) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            identifier,
+            trace,
+        }
+    }
+
+    pub fn identifier(&self) -> &IdentityIdentifier {
+        &self.identifier
+    }
+
+    pub fn trace(&self) -> bool {
+        self.trace
+    }
+}
+
+/// Outcome of a policy evaluation
+#[derive(Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PolicyEvaluation {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<1958804>,
+    #[n(1)] allowed: bool,
+    /// Reason for a denial
+    #[n(2)] reason: Option<String>,
+    /// Evaluated policy, if one was found
+    #[n(3)] expression: Option<Expr>,
+    #[n(4)] trace: Vec<PolicyTraceStep>,
+}
+
+impl PolicyEvaluation {
+    pub fn new(
+        allowed: bool,
+        reason: Option<String>,
+        expression: Option<Expr>,
+        trace: Vec<PolicyTraceStep>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            allowed,
+            reason,
+            expression,
+            trace,
+        }
+    }
+
+    pub fn allowed(&self) -> bool {
+        self.allowed
+    }
+
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+
+    pub fn expression(&self) -> Option<&Expr> {
+        self.expression.as_ref()
+    }
+
+    pub fn trace(&self) -> &[PolicyTraceStep] {
+        &self.trace
+    }
+}
+
+/// A step of a policy evaluation trace
+#[derive(Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PolicyTraceStep {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<8230571>,
+    #[n(1)] depth: usize,
+    #[n(2)] expression: Expr,
+    #[n(3)] value: Option<Expr>,
+    #[n(4)] error: Option<String>,
+}
+
+impl PolicyTraceStep {
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn expression(&self) -> &Expr {
+        &self.expression
+    }
+
+    pub fn value(&self) -> Option<&Expr> {
+        self.value.as_ref()
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+impl From<TraceStep> for PolicyTraceStep {
+    fn from(step: TraceStep) -> Self {
+        let (value, error) = match step.result {
+            Ok(value) => (Some(value), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            depth: step.depth,
+            expression: step.expr,
+            value,
+            error,
+        }
+    }
+}
@@ -1,6 +1,9 @@
 //! Inlets and outlet request/response types
 
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
 use std::net::SocketAddr;
+use std::ops::RangeInclusive;
 use std::time::Duration;
 
 use minicbor::{Decode, Encode};
@@ -11,6 +14,7 @@ use ockam_core::TypeTag;
 use ockam_core::{CowStr, Route};
 use ockam_identity::IdentityIdentifier;
 use ockam_multiaddr::MultiAddr;
+use schemars::JsonSchema;
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize};
 
@@ -139,6 +143,29 @@ pub struct CreateOutlet {
     /// Allow the outlet to be reachable from the default secure channel, useful when we want to
     /// tighten the flow control
     #[n(4)] pub reachable_from_default_secure_channel: bool,
+    /// The name of the trust context used by the outlet policy, the default one if not set
+    #[n(5)] pub trust_context_name: Option<String>,
+    /// Close a connection of the outlet when no data went through it for this duration
+    #[n(6)] pub idle_timeout: Option<Duration>,
+    /// Only accept the peers which presented a valid credential issued by the authority
+    /// of the trust context of the outlet
+    #[n(7)] pub require_credential: bool,
+    /// Maximum number of connections of the outlet open at the same time
+    #[n(8)] pub max_connections: Option<usize>,
+    /// Tags describing the outlet, used to find it, for example `service=postgres`
+    #[n(9)] pub tags: Option<BTreeMap<String, String>>,
+    /// Policy evaluated against the attributes of the node itself each time a connection
+    /// of the outlet is opened
+    #[n(10)] pub activation: Option<OutletActivation>,
+}
+
+/// Resource and action of the policy activating an outlet, see [`CreateOutlet::with_activation`]
+#[derive(Clone, Debug, PartialEq, Eq, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct OutletActivation {
+    #[n(1)] pub resource: String,
+    #[n(2)] pub action: String,
 }
 
 impl CreateOutlet {
@@ -155,8 +182,58 @@ impl CreateOutlet {
             worker_addr: worker_addr.into(),
             alias: alias.into(),
             reachable_from_default_secure_channel,
+            trust_context_name: None,
+            idle_timeout: None,
+            require_credential: false,
+            max_connections: None,
+            tags: None,
+            activation: None,
         }
     }
+
+    /// Use a named trust context of the node instead of its default trust context
+    pub fn with_trust_context_name(mut self, trust_context_name: impl Into<String>) -> Self {
+        self.trust_context_name = Some(trust_context_name.into());
+        self
+    }
+
+    /// Close each connection of the outlet after it has been idle for the given duration
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// Deny the peers which didn't present a valid credential
+    pub fn with_require_credential(mut self, require_credential: bool) -> Self {
+        self.require_credential = require_credential;
+        self
+    }
+
+    /// Make the new connections wait while `max_connections` connections of the outlet are open
+    pub fn with_max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = Some(max_connections);
+        self
+    }
+
+    /// Tag the outlet, so that it can be found by its tags
+    pub fn with_tags(mut self, tags: BTreeMap<String, String>) -> Self {
+        self.tags = (!tags.is_empty()).then_some(tags);
+        self
+    }
+
+    /// Only accept the connections of the outlet while the policy of the resource for the
+    /// action holds for the node itself
+    pub fn with_activation(
+        mut self,
+        resource: impl Into<String>,
+        action: impl Into<String>,
+    ) -> Self {
+        self.activation = Some(OutletActivation {
+            resource: resource.into(),
+            action: action.into(),
+        });
+        self
+    }
 }
 
 /// Response body when interacting with a portal endpoint
@@ -222,18 +299,82 @@ impl InletStatus {
 }
 
 /// Response body when interacting with a portal endpoint
-#[derive(Clone, Debug, Decode, Encode, Serialize, Deserialize)]
+///
+/// When serialized with serde (for example with `--output json`), the field names are
+/// `tcp_addr`, `worker_addr`, `alias`, `payload`, `active_connections`, `idle_timeout_secs`,
+/// `port_range`, `max_connections` and `tags`. `payload`, `idle_timeout_secs`, `port_range`,
+/// `max_connections` and `tags` are omitted when empty.
+#[derive(Clone, Debug, PartialEq, Decode, Encode, Serialize, Deserialize, JsonSchema)]
 #[rustfmt::skip]
 #[cbor(map)]
 pub struct OutletStatus {
     #[cfg(feature = "tag")]
     #[serde(skip)]
     #[n(0)] tag: TypeTag<4012569>,
+    /// The address of the TCP service exposed by the outlet
     #[n(1)] pub tcp_addr: String,
+    /// The address of the outlet worker
     #[n(2)] pub worker_addr: String,
+    /// The alias of the outlet
     #[n(3)] pub alias:String,
     /// An optional status payload
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[n(4)] pub payload: Option<String>,
+    /// The number of connections currently going through the outlet
+    #[serde(default)]
+    #[n(5)] pub active_connections: usize,
+    /// The number of seconds after which an idle connection of the outlet is closed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(6)] pub idle_timeout_secs: Option<u64>,
+    /// The range of ports of the outlet, if its TCP address is a port range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(7)] pub port_range: Option<PortRange>,
+    /// The maximum number of connections of the outlet open at the same time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(8)] pub max_connections: Option<usize>,
+    /// The tags describing the outlet, used to find it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(9)] pub tags: Option<BTreeMap<String, String>>,
+}
+
+/// A contiguous range of ports exposed by a single outlet.
+///
+/// The TCP address of such an outlet is written `host:first-last`, for example
+/// `127.0.0.1:5000-5010`. Each connection of the outlet goes to the port requested by its inlet,
+/// with the address following the outlet address in the outlet route: an inlet created with the
+/// outlet address `/service/db/service/5001` is connected to `127.0.0.1:5001`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Decode, Encode, Serialize, Deserialize, JsonSchema)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PortRange {
+    /// The first port of the range
+    #[n(1)] pub first: u16,
+    /// The last port of the range, included
+    #[n(2)] pub last: u16,
+}
+
+impl PortRange {
+    /// Split the TCP address of an outlet, written `host:first-last`, into its host and
+    /// its port range. Return `None` if the address is not a port range
+    pub fn parse_tcp_addr(tcp_addr: &str) -> Option<(&str, PortRange)> {
+        let (host, ports) = tcp_addr.rsplit_once(':')?;
+        let (first, last) = ports.split_once('-')?;
+        let (first, last) = (first.parse().ok()?, last.parse().ok()?);
+        if host.is_empty() || first > last {
+            return None;
+        }
+        Some((host, PortRange { first, last }))
+    }
+
+    pub fn ports(&self) -> RangeInclusive<u16> {
+        self.first..=self.last
+    }
+}
+
+impl Display for PortRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.first, self.last)
+    }
 }
 
 impl OutletStatus {
@@ -245,22 +386,92 @@ impl OutletStatus {
             worker_addr: "".into(),
             alias: "".into(),
             payload: Some(reason.into()),
+            active_connections: 0,
+            idle_timeout_secs: None,
+            port_range: None,
+            max_connections: None,
+            tags: None,
         }
     }
 
+    /// Create the status of an outlet. The port range is derived from its TCP address
     pub fn new(
         tcp_addr: impl Into<String>,
         worker_addr: impl Into<String>,
         alias: impl Into<String>,
         payload: impl Into<Option<String>>,
     ) -> Self {
+        let tcp_addr = tcp_addr.into();
+        let port_range = PortRange::parse_tcp_addr(&tcp_addr).map(|(_, range)| range);
         Self {
             #[cfg(feature = "tag")]
             tag: TypeTag,
-            tcp_addr: tcp_addr.into(),
+            tcp_addr,
             worker_addr: worker_addr.into(),
             alias: alias.into(),
             payload: payload.into(),
+            active_connections: 0,
+            idle_timeout_secs: None,
+            port_range,
+            max_connections: None,
+            tags: None,
+        }
+    }
+
+    pub fn with_active_connections(mut self, active_connections: usize) -> Self {
+        self.active_connections = active_connections;
+        self
+    }
+
+    pub fn with_idle_timeout(mut self, idle_timeout: Option<Duration>) -> Self {
+        self.idle_timeout_secs = idle_timeout.map(|t| t.as_secs());
+        self
+    }
+
+    /// Return the duration after which an idle connection of the outlet is closed, if any
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout_secs.map(Duration::from_secs)
+    }
+
+    pub fn with_max_connections(mut self, max_connections: Option<usize>) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+
+    /// Set the tags of the outlet. An outlet without tags has no `tags` field
+    pub fn with_tags(mut self, tags: BTreeMap<String, String>) -> Self {
+        self.tags = (!tags.is_empty()).then_some(tags);
+        self
+    }
+
+    /// Return the value of a tag of the outlet, if it has this tag
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.tags.as_ref()?.get(key).map(|t| t.as_str())
+    }
+
+    /// Return true if the outlet has all the given tags, with the same values.
+    /// An outlet has all the tags of an empty list
+    pub fn has_tags<K: AsRef<str>, V: AsRef<str>>(&self, tags: &[(K, V)]) -> bool {
+        tags.iter()
+            .all(|(k, v)| self.tag(k.as_ref()) == Some(v.as_ref()))
+    }
+
+    /// Return true if the outlet can forward its connections to `socket_addr`: its TCP address
+    /// is `socket_addr`, or a port range of the same IP address containing its port
+    pub fn forwards_to(&self, socket_addr: &SocketAddr) -> bool {
+        if self.tcp_addr.parse::<SocketAddr>().ok().as_ref() == Some(socket_addr) {
+            return true;
+        }
+        match PortRange::parse_tcp_addr(&self.tcp_addr) {
+            Some((host, range)) => {
+                let host = host
+                    .strip_prefix('[')
+                    .and_then(|h| h.strip_suffix(']'))
+                    .unwrap_or(host);
+                host.parse().ok() == Some(socket_addr.ip())
+                    && range.ports().contains(&socket_addr.port())
+            }
+            None => false,
         }
     }
 
@@ -270,6 +481,17 @@ impl OutletStatus {
     }
 }
 
+/// Display an outlet as a row of the table displayed by `OutletList`
+impl Display for OutletStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:<20} {:<21} {:<20} {}",
+            self.alias, self.tcp_addr, self.worker_addr, self.active_connections
+        )
+    }
+}
+
 /// Response body when returning a list of Inlets
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
@@ -309,3 +531,220 @@ impl OutletList {
         }
     }
 }
+
+/// Request body to list the outlets having some tags.
+/// All the outlets are listed when the list request has no body
+#[derive(Clone, Debug, Default, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ListOutlets {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2813094>,
+    /// Tags the listed outlets must all have, with the same values
+    #[n(1)] pub tags: Option<BTreeMap<String, String>>,
+}
+
+impl ListOutlets {
+    pub fn new(tags: BTreeMap<String, String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            tags: Some(tags),
+        }
+    }
+
+    /// Return the tags the listed outlets must have, as `(key, value)` pairs
+    pub fn tags(&self) -> Vec<(&str, &str)> {
+        self.tags
+            .iter()
+            .flatten()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect()
+    }
+}
+
+/// Display the outlets as a table with one outlet per row
+impl Display for OutletList {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<20} {:<21} {:<20} ACTIVE CONNECTIONS",
+            "ALIAS", "TCP ADDRESS", "WORKER ADDRESS"
+        )?;
+        for outlet in &self.list {
+            writeln!(f, "{outlet}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Response body when subscribing to the events of the outlets of a node
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct OutletEventsSubscription {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3264217>,
+    /// Identifier of the subscription, used to get its events and to unsubscribe
+    #[n(1)] pub id: String,
+}
+
+impl OutletEventsSubscription {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            id: id.into(),
+        }
+    }
+}
+
+/// Kind of change of the status of an outlet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+#[serde(rename_all = "snake_case")]
+pub enum OutletEventKind {
+    #[n(0)] Created,
+    #[n(1)] Removed,
+    /// The number of active connections of the outlet changed
+    #[n(2)] ConnectionsChanged,
+}
+
+impl Display for OutletEventKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            OutletEventKind::Created => "created",
+            OutletEventKind::Removed => "removed",
+            OutletEventKind::ConnectionsChanged => "connections_changed",
+        })
+    }
+}
+
+/// A change of the status of an outlet
+#[derive(Debug, Clone, PartialEq, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct OutletEvent {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<6129540>,
+    #[n(1)] pub kind: OutletEventKind,
+    /// Status of the outlet after the change, or before its removal
+    #[n(2)] pub outlet: OutletStatus,
+}
+
+impl OutletEvent {
+    pub fn new(kind: OutletEventKind, outlet: OutletStatus) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            kind,
+            outlet,
+        }
+    }
+}
+
+/// Response body when returning the events of an outlets subscription
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct OutletEventList {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2957023>,
+    #[n(1)] pub list: Vec<OutletEvent>,
+}
+
+impl OutletEventList {
+    pub fn new(list: Vec<OutletEvent>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            list,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outlet_status_json_roundtrip() {
+        let statuses = vec![
+            OutletStatus::new("127.0.0.1:5000", "0#outlet", "db", None),
+            OutletStatus::new("127.0.0.1:6000", "0#web", "web", Some("ok".to_string()))
+                .with_active_connections(3),
+        ];
+        for status in statuses {
+            let json = serde_json::to_string(&status).unwrap();
+            let decoded: OutletStatus = serde_json::from_str(&json).unwrap();
+            assert_eq!(status, decoded);
+        }
+    }
+
+    #[test]
+    fn outlet_status_json_field_names() {
+        let status = OutletStatus::new("127.0.0.1:5000", "0#outlet", "db", None);
+        let json = serde_json::to_value(status).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "tcp_addr": "127.0.0.1:5000",
+                "worker_addr": "0#outlet",
+                "alias": "db",
+                "active_connections": 0
+            })
+        );
+    }
+
+    #[test]
+    fn outlet_without_tags_is_decoded() {
+        // the payloads of the nodes which don't know about the tags don't have the field 9
+        let status = OutletStatus::new("127.0.0.1:5000", "0#outlet", "db", None);
+        let decoded: OutletStatus = minicbor::decode(&minicbor::to_vec(&status).unwrap()).unwrap();
+        assert_eq!(decoded.tags, None);
+        let create = CreateOutlet::new("127.0.0.1:5000", "outlet", None, true);
+        let decoded: CreateOutlet = minicbor::decode(&minicbor::to_vec(&create).unwrap()).unwrap();
+        assert_eq!(decoded.tags, None);
+
+        let tags = BTreeMap::from([("env".to_string(), "prod".to_string())]);
+        let status = status.with_tags(tags);
+        let decoded: OutletStatus = minicbor::decode(&minicbor::to_vec(&status).unwrap()).unwrap();
+        assert_eq!(decoded.tag("env"), Some("prod"));
+    }
+
+    #[test]
+    fn an_outlet_forwards_to_its_address_or_its_port_range() {
+        let address: SocketAddr = "127.0.0.1:5005".parse().unwrap();
+        let outlet = OutletStatus::new("127.0.0.1:5005", "0#outlet", "db", None);
+        assert!(outlet.forwards_to(&address));
+        let outlet = OutletStatus::new("127.0.0.1:5000-5010", "0#outlet", "db", None);
+        assert!(outlet.forwards_to(&address));
+        assert!(!outlet.forwards_to(&"127.0.0.1:5011".parse().unwrap()));
+        assert!(!outlet.forwards_to(&"10.0.0.1:5005".parse().unwrap()));
+        let outlet = OutletStatus::new("[::1]:5000-5010", "0#outlet", "db", None);
+        assert!(outlet.forwards_to(&"[::1]:5005".parse().unwrap()));
+    }
+
+    #[test]
+    fn outlet_list_table() {
+        let list = OutletList::new(vec![OutletStatus::new(
+            "127.0.0.1:5000",
+            "0#outlet",
+            "db",
+            None,
+        )]);
+        let table = list.to_string();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("ALIAS"));
+        assert_eq!(
+            lines[1],
+            format!(
+                "{:<20} {:<21} {:<20} {}",
+                "db", "127.0.0.1:5000", "0#outlet", 0
+            )
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::time::Duration;
 
 use minicbor::{Decode, Encode};
@@ -36,6 +37,7 @@ pub struct CreateSecureChannelRequest {
     #[n(4)] pub timeout: Option<Duration>,
     #[n(5)] pub identity_name: Option<String>,
     #[n(6)] pub credential_name: Option<String>,
+    #[n(7)] pub trust_context_name: Option<String>,
 }
 
 impl CreateSecureChannelRequest {
@@ -56,8 +58,15 @@ impl CreateSecureChannelRequest {
             timeout: None,
             identity_name,
             credential_name,
+            trust_context_name: None,
         }
     }
+
+    /// Use a named trust context of the node instead of its default trust context
+    pub fn with_trust_context_name(mut self, trust_context_name: impl Into<String>) -> Self {
+        self.trust_context_name = Some(trust_context_name.into());
+        self
+    }
 }
 
 /// Response body when instructing a node to create a Secure Channel
@@ -102,6 +111,7 @@ pub struct CreateSecureChannelListenerRequest {
     #[n(2)] pub authorized_identifiers: Option<Vec<String>>,
     #[n(3)] pub vault: Option<String>,
     #[n(4)] pub identity: Option<String>,
+    #[n(5)] pub trust_context_name: Option<String>,
 }
 
 impl CreateSecureChannelListenerRequest {
@@ -119,8 +129,15 @@ impl CreateSecureChannelListenerRequest {
                 .map(|x| x.into_iter().map(|y| y.to_string()).collect()),
             vault,
             identity,
+            trust_context_name: None,
         }
     }
+
+    /// Use a named trust context of the node instead of its default trust context
+    pub fn with_trust_context_name(mut self, trust_context_name: impl Into<String>) -> Self {
+        self.trust_context_name = Some(trust_context_name.into());
+        self
+    }
 }
 
 /// Request body when deleting a Secure Channel Listener
@@ -293,6 +310,68 @@ impl ShowSecureChannelResponse {
     }
 }
 
+/// A secure channel of a node, together with the identity authenticated at the other end
+#[derive(Debug, Clone, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SecureChannelStatus {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<6924187>,
+    /// Address of the local end of the channel, used to send messages through it
+    #[n(1)] pub local_address: String,
+    /// Address of the remote end of the channel
+    #[n(2)] pub remote_address: Option<String>,
+    /// Route used to create the channel, if it was created by this node
+    #[n(3)] pub route: Option<String>,
+    #[n(4)] pub is_initiator: bool,
+    /// Identifier of the peer, if it has been authenticated
+    #[n(5)] pub peer_identifier: Option<String>,
+    /// Verified attributes of the peer
+    #[n(6)] pub peer_attributes: BTreeMap<String, String>,
+}
+
+impl SecureChannelStatus {
+    pub fn new(
+        local_address: &Address,
+        remote_address: Option<&Address>,
+        route: Option<String>,
+        is_initiator: bool,
+        peer_identifier: Option<&IdentityIdentifier>,
+        peer_attributes: BTreeMap<String, String>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            local_address: local_address.to_string(),
+            remote_address: remote_address.map(|a| a.to_string()),
+            route,
+            is_initiator,
+            peer_identifier: peer_identifier.map(|i| i.to_string()),
+            peer_attributes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SecureChannelList {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3085716>,
+    #[n(1)] pub list: Vec<SecureChannelStatus>,
+}
+
+impl SecureChannelList {
+    pub fn new(list: Vec<SecureChannelStatus>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            list,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
 #[cbor(map)]
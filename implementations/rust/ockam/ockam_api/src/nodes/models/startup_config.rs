@@ -0,0 +1,46 @@
+use minicbor::{Decode, Encode};
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Request body when exporting the running configuration of a node
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ExportStartupConfig {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6129470>,
+    /// Export the credentials of the trust contexts instead of their names in the cli state
+    #[n(1)] pub inline_credentials: bool,
+}
+
+impl ExportStartupConfig {
+    pub fn new(inline_credentials: bool) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            inline_credentials,
+        }
+    }
+}
+
+/// Response body when exporting the running configuration of a node
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StartupConfigFile {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3908215>,
+    /// Contents of a `startup.yaml` file
+    #[n(1)] pub contents: String,
+}
+
+impl StartupConfigFile {
+    pub fn new(contents: impl Into<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            contents: contents.into(),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Trust context request/response types
+
+use minicbor::{Decode, Encode};
+use ockam::identity::Credential;
+use serde::Serialize;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// A trust context configured on a node
+#[derive(Debug, Clone, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TrustContextStatus {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<4219655>,
+    /// Name of the trust context, `None` for the default trust context of the node
+    #[n(1)] pub name: Option<String>,
+    #[n(2)] pub id: String,
+    #[n(3)] pub authority_identifier: Option<String>,
+}
+
+impl TrustContextStatus {
+    pub fn new(
+        name: Option<String>,
+        id: impl Into<String>,
+        authority_identifier: Option<String>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            name,
+            id: id.into(),
+            authority_identifier,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TrustContextList {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7681190>,
+    #[n(1)] pub list: Vec<TrustContextStatus>,
+}
+
+impl TrustContextList {
+    pub fn new(list: Vec<TrustContextStatus>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            list,
+        }
+    }
+}
+
+/// Request to replace the credential presented by a node for one of its trust contexts
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct UpdateTrustContextCredential {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2803464>,
+    /// Name of the trust context, `None` for the default trust context of the node
+    #[n(1)] pub name: Option<String>,
+    #[n(2)] pub credential: Credential,
+}
+
+impl UpdateTrustContextCredential {
+    pub fn new(name: Option<String>, credential: Credential) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            name,
+            credential,
+        }
+    }
+}
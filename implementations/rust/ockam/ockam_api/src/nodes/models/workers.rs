@@ -3,26 +3,33 @@ use minicbor::{Decode, Encode};
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
 
-#[derive(Debug, Clone, Decode, Encode)]
+#[derive(Debug, Clone, PartialEq, Eq, Decode, Encode)]
 #[rustfmt::skip]
 #[cbor(map)]
 pub struct WorkerStatus {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<2610323>,
     #[n(2)] pub addr: String,
+    /// What the worker is used for: outlet, inlet, secure_channel, secure_channel_listener,
+    /// service, or other for the workers which are not managed by the node manager
+    #[n(3)] pub kind: String,
+    /// The worker was started for an outlet, an inlet or a secure channel which was removed
+    #[n(4)] pub stale: bool,
 }
 
 impl WorkerStatus {
-    pub fn new(addr: impl Into<String>) -> Self {
+    pub fn new(addr: impl Into<String>, kind: impl Into<String>, stale: bool) -> Self {
         Self {
             #[cfg(feature = "tag")]
             tag: TypeTag,
             addr: addr.into(),
+            kind: kind.into(),
+            stale,
         }
     }
 }
 
-/// Response body for listing workers
+/// Response body for listing workers, or the stale workers which were stopped
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
 #[cbor(map)]
@@ -1,10 +1,17 @@
+use crate::logs::{self, LogSubscription};
+use crate::nodes::models::portal::OutletStatus;
 use crate::nodes::service::Alias;
+use crate::session::sessions::Key;
 use ockam::identity::IdentityIdentifier;
 use ockam::remote::RemoteForwarderInfo;
 use ockam_core::compat::collections::BTreeMap;
 use ockam_core::{Address, Route};
 use ockam_identity::{SecureChannel, SecureChannelListener};
+use ockam_multiaddr::MultiAddr;
+use ockam_node::compat::tokio::sync::watch;
 use std::fmt::Display;
+use std::time::{Duration, Instant};
+use tracing::Level;
 
 #[derive(Default)]
 pub(crate) struct SecureChannelRegistry {
@@ -142,11 +149,41 @@ impl KafkaServiceInfo {
     }
 }
 
+/// What a worker of the node is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WorkerKind {
+    Outlet,
+    Inlet,
+    SecureChannel,
+    SecureChannelListener,
+    Service,
+    /// A worker which is not managed by the node manager, for example a transport worker
+    Other,
+}
+
+impl Display for WorkerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            WorkerKind::Outlet => "outlet",
+            WorkerKind::Inlet => "inlet",
+            WorkerKind::SecureChannel => "secure_channel",
+            WorkerKind::SecureChannelListener => "secure_channel_listener",
+            WorkerKind::Service => "service",
+            WorkerKind::Other => "other",
+        };
+        write!(f, "{kind}")
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct InletInfo {
     pub(crate) bind_addr: String,
     pub(crate) worker_addr: Address,
     pub(crate) outlet_route: Route,
+    /// Address of the outlet, as given when the inlet was created
+    pub(crate) outlet_addr: MultiAddr,
+    /// Session recreating the inlet when its connection to the outlet is lost
+    pub(crate) session_key: Option<Key>,
 }
 
 impl InletInfo {
@@ -154,6 +191,7 @@ impl InletInfo {
         bind_addr: &str,
         worker_addr: Option<&Address>,
         outlet_route: &Route,
+        outlet_addr: &MultiAddr,
     ) -> Self {
         let worker_addr = match worker_addr {
             Some(addr) => addr.clone(),
@@ -163,14 +201,31 @@ impl InletInfo {
             bind_addr: bind_addr.to_owned(),
             worker_addr,
             outlet_route: outlet_route.to_owned(),
+            outlet_addr: outlet_addr.to_owned(),
+            session_key: None,
         }
     }
+
+    pub(crate) fn with_session_key(mut self, session_key: Option<Key>) -> Self {
+        self.session_key = session_key;
+        self
+    }
 }
 
 #[derive(Clone)]
 pub(crate) struct OutletInfo {
     pub(crate) tcp_addr: String,
     pub(crate) worker_addr: Address,
+    /// Name of the trust context used by the outlet policy, if it is not the default one
+    pub(crate) trust_context_name: Option<String>,
+    /// Duration after which an idle connection of the outlet is closed
+    pub(crate) idle_timeout: Option<Duration>,
+    /// The peers of the outlet must present a valid credential
+    pub(crate) require_credential: bool,
+    /// Maximum number of connections of the outlet open at the same time
+    pub(crate) max_connections: Option<usize>,
+    /// Tags describing the outlet, used to find it
+    pub(crate) tags: BTreeMap<String, String>,
 }
 
 impl OutletInfo {
@@ -182,6 +237,96 @@ impl OutletInfo {
         Self {
             tcp_addr: tcp_addr.to_owned(),
             worker_addr,
+            trust_context_name: None,
+            idle_timeout: None,
+            require_credential: false,
+            max_connections: None,
+            tags: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn with_trust_context_name(mut self, trust_context_name: Option<&str>) -> Self {
+        self.trust_context_name = trust_context_name.map(|n| n.to_string());
+        self
+    }
+
+    pub(crate) fn with_idle_timeout(mut self, idle_timeout: Option<Duration>) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    pub(crate) fn with_require_credential(mut self, require_credential: bool) -> Self {
+        self.require_credential = require_credential;
+        self
+    }
+
+    pub(crate) fn with_max_connections(mut self, max_connections: Option<usize>) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+
+    pub(crate) fn with_tags(mut self, tags: BTreeMap<String, String>) -> Self {
+        self.tags = tags;
+        self
+    }
+}
+
+/// A subscription to the events of the outlets.
+/// The events are found by comparing the outlets with the ones seen at the previous poll,
+/// which waits for a change of the outlets or of their connections
+pub(crate) struct OutletSubscriptionInfo {
+    pub(crate) outlets: BTreeMap<Alias, OutletStatus>,
+    pub(crate) outlets_changed: watch::Receiver<()>,
+    pub(crate) connections_changed: watch::Receiver<()>,
+    pub(crate) last_poll: Instant,
+}
+
+impl OutletSubscriptionInfo {
+    pub(crate) fn new(
+        outlets: BTreeMap<Alias, OutletStatus>,
+        outlets_changed: watch::Receiver<()>,
+        connections_changed: watch::Receiver<()>,
+    ) -> Self {
+        Self {
+            outlets,
+            outlets_changed,
+            connections_changed,
+            last_poll: Instant::now(),
+        }
+    }
+}
+
+/// Notifies the subscriptions to the outlet events each time an outlet is created,
+/// renamed or deleted
+pub(crate) struct OutletsChanged(watch::Sender<()>);
+
+impl Default for OutletsChanged {
+    fn default() -> Self {
+        Self(watch::channel(()).0)
+    }
+}
+
+impl OutletsChanged {
+    pub(crate) fn notify(&self) {
+        self.0.send_replace(());
+    }
+
+    pub(crate) fn subscribe(&self) -> watch::Receiver<()> {
+        self.0.subscribe()
+    }
+}
+
+/// A subscription to the log lines of the node, keeping the lines which were not polled yet
+pub(crate) struct LogSubscriptionInfo {
+    pub(crate) subscription: LogSubscription,
+    pub(crate) last_poll: Instant,
+}
+
+impl LogSubscriptionInfo {
+    pub(crate) fn new(level: Level) -> Self {
+        Self {
+            subscription: logs::subscribe(level),
+            last_poll: Instant::now(),
         }
     }
 }
@@ -206,4 +351,47 @@ pub(crate) struct Registry {
     pub(crate) forwarders: BTreeMap<String, RemoteForwarderInfo>,
     pub(crate) inlets: BTreeMap<Alias, InletInfo>,
     pub(crate) outlets: BTreeMap<Alias, OutletInfo>,
+    pub(crate) outlets_changed: OutletsChanged,
+    pub(crate) outlet_subscriptions: BTreeMap<String, OutletSubscriptionInfo>,
+    pub(crate) log_subscriptions: BTreeMap<String, LogSubscriptionInfo>,
+    /// Workers started for the portals and the secure channels, so that the ones left running
+    /// when they are removed from the registry can be found and stopped
+    pub(crate) started_workers: BTreeMap<Address, WorkerKind>,
+}
+
+impl Registry {
+    /// Return what a worker is used for, if it is referenced by the registry
+    pub(crate) fn worker_kind(&self, address: &Address) -> Option<WorkerKind> {
+        if self.outlets.values().any(|o| &o.worker_addr == address) {
+            Some(WorkerKind::Outlet)
+        } else if self.inlets.values().any(|i| &i.worker_addr == address) {
+            Some(WorkerKind::Inlet)
+        } else if self.secure_channels.list().iter().any(|c| {
+            c.sc().encryptor_address() == address || c.sc().encryptor_api_address() == address
+        }) {
+            Some(WorkerKind::SecureChannel)
+        } else if self.secure_channel_listeners.contains_key(address) {
+            Some(WorkerKind::SecureChannelListener)
+        } else if self.is_service(address) {
+            Some(WorkerKind::Service)
+        } else {
+            None
+        }
+    }
+
+    fn is_service(&self, address: &Address) -> bool {
+        #[cfg(feature = "direct-authenticator")]
+        if self.authenticator_service.contains_key(address) {
+            return true;
+        }
+        self.identity_services.contains_key(address)
+            || self.authenticated_services.contains_key(address)
+            || self.okta_identity_provider_services.contains_key(address)
+            || self.uppercase_services.contains_key(address)
+            || self.echoer_services.contains_key(address)
+            || self.kafka_services.contains_key(address)
+            || self.hop_services.contains_key(address)
+            || self.verifier_services.contains_key(address)
+            || self.credentials_services.contains_key(address)
+    }
 }
@@ -2,11 +2,14 @@
 
 use std::collections::BTreeMap;
 use std::error::Error as _;
+use std::future::Future;
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use minicbor::{Decoder, Encode};
 
+pub use metrics::{MetricsSnapshot, OutletMetrics};
 pub use node_identities::*;
 use ockam::identity::{
     Credentials, CredentialsServer, CredentialsServerModule, Identities, IdentitiesRepository,
@@ -21,6 +24,7 @@ use ockam_abac::expr::{and, eq, ident, str};
 use ockam_abac::{Action, Env, Expr, PolicyAccessControl, PolicyStorage, Resource};
 use ockam_core::api::{Error, Method, Request, Response, ResponseBuilder, Status};
 use ockam_core::compat::{boxed::Box, string::String, sync::Arc};
+use ockam_core::env::get_env;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::IncomingAccessControl;
@@ -28,44 +32,85 @@ use ockam_core::{AllowAll, AsyncTryClone};
 use ockam_identity::TrustContext;
 use ockam_multiaddr::MultiAddr;
 use ockam_node::compat::asynchronous::RwLock;
+use ockam_node::tokio::task::JoinHandle;
+use pause::PauseSwitch;
+pub use portals::{InletOptions, OutletSpec};
+pub use revocation::RevocationList;
+pub use shutdown::{ShutdownReport, StopStatus, StoppedComponent, Subsystem, STOP_TIMEOUT};
 
 use crate::bootstrapped_identities_store::BootstrapedIdentityStore;
 use crate::bootstrapped_identities_store::PreTrustedIdentities;
-use crate::cli_state::{CliState, StateDirTrait, StateItemTrait};
+use crate::cli_state::{CliState, IdentitiesState, StateDirTrait, StateItemTrait};
 use crate::config::cli::TrustContextConfig;
 use crate::config::lookup::ProjectLookup;
+use crate::decision_log::{
+    DecisionLogAccessControl, DecisionLogSink, JsonLinesDecisionLog, PolicyDenialAccessControl,
+    DECISION_LOG_CAPACITY, OCKAM_DECISION_LOG,
+};
 use crate::error::ApiError;
+use crate::local_attributes_store::LocalAttributesStore;
 use crate::nodes::connection::{
     Connection, ConnectionInstance, ConnectionInstanceBuilder, PlainTcpInstantiator,
     ProjectInstantiator, SecureChannelInstantiator,
 };
-use crate::nodes::models::base::NodeStatus;
-use crate::nodes::models::portal::{OutletList, OutletStatus};
+use crate::nodes::models::base::{NodeRunStatus, NodeStatus};
+use crate::nodes::models::portal::{InletList, InletStatus, OutletList, OutletStatus};
 use crate::nodes::models::transport::{TransportMode, TransportType};
-use crate::nodes::models::workers::{WorkerList, WorkerStatus};
 use crate::nodes::registry::KafkaServiceKind;
 use crate::session::sessions::{Key, Session};
 use crate::session::MedicHandle;
 use crate::DefaultAddress;
 use crate::RpcProxyService;
 
-use super::registry::Registry;
+use super::registry::{OutletInfo, Registry};
 
+mod credential_required;
 mod credentials;
 mod flow_controls;
 mod forwarder;
+mod logs;
 pub mod message;
+mod metrics;
 mod node_identities;
 mod node_services;
+mod outlet_activation;
+mod outlet_events;
+mod pause;
 mod policy;
 mod portals;
+mod revocation;
 mod secure_channel;
+mod shutdown;
+pub mod startup_config;
 mod transport;
+mod trust_context;
+mod workers;
 
 const TARGET: &str = "ockam_api::nodemanager::service";
 
 pub(crate) type Alias = String;
 
+/// Maximum duration of the handling of a request by the [`NodeManagerWorker`], unless the
+/// request sets its own timeout. Only the requests which can be interrupted time out,
+/// see `can_time_out`
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Return true if the handling of a request can be interrupted once its timeout expires:
+/// the reads, and the requests forwarded to a remote node or to the controller.
+///
+/// The other requests modify the node, they are never interrupted so that the node isn't left
+/// with a half-created portal or service
+fn can_time_out(req: &Request) -> bool {
+    req.method() == Some(Method::Get)
+        || matches!(
+            req.path_segments::<5>().as_slice(),
+            ["v0", ..] | ["v1", ..] | ["subscription", ..]
+        )
+}
+
+/// Interval between two removals of the expired local attributes from the identities files
+const ATTRIBUTES_PRUNING_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Generate a new alias for some user created extension
 #[inline]
 fn random_alias() -> String {
@@ -82,6 +127,45 @@ pub(crate) fn map_multiaddr_err(_err: ockam_multiaddr::Error) -> ockam_core::Err
     invalid_multiaddr_error()
 }
 
+/// Remove the expired local attributes of the identities periodically.
+///
+/// The expired attributes are already ignored by the policies, this only keeps them from
+/// accumulating in the identities files
+async fn prune_expired_attributes_periodically(identities: IdentitiesState) {
+    loop {
+        ockam_node::tokio::time::sleep(ATTRIBUTES_PRUNING_INTERVAL).await;
+        if let Err(e) = identities.prune_expired_attributes() {
+            warn!(%e, "failed to remove the expired local attributes");
+        }
+    }
+}
+
+/// Tasks running in the background for as long as a node manager.
+/// They are aborted when the node manager is shut down or dropped
+#[derive(Default)]
+pub(crate) struct BackgroundTasks {
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl BackgroundTasks {
+    pub(crate) fn spawn(&mut self, task: impl Future<Output = ()> + Send + 'static) {
+        self.tasks.retain(|task| !task.is_finished());
+        self.tasks.push(ockam_node::tokio::spawn(task));
+    }
+
+    pub(crate) fn abort_all(&mut self) {
+        for task in self.tasks.drain(..) {
+            task.abort()
+        }
+    }
+}
+
+impl Drop for BackgroundTasks {
+    fn drop(&mut self) {
+        self.abort_all()
+    }
+}
+
 pub(crate) fn encode_request_result<T: Encode<()>>(
     res: std::result::Result<ResponseBuilder<T>, ResponseBuilder<ockam_core::api::Error>>,
 ) -> Result<Vec<u8>> {
@@ -105,9 +189,19 @@ pub struct NodeManager {
     identifier: IdentityIdentifier,
     pub(crate) secure_channels: Arc<SecureChannels>,
     trust_context: Option<TrustContext>,
+    /// Additional trust contexts, used when a name is given to select them
+    trust_contexts: BTreeMap<String, TrustContext>,
+    /// Configurations of the additional trust contexts, so that they can be exported
+    trust_context_configs: BTreeMap<String, TrustContextConfig>,
     pub(crate) registry: Registry,
     medic_handle: MedicHandle,
     policies: Arc<dyn PolicyStorage>,
+    pause_switch: PauseSwitch,
+    revocations: RevocationList,
+    decision_log: Option<Arc<dyn DecisionLogSink>>,
+    /// True until the startup configuration of the node is loaded
+    starting: bool,
+    background_tasks: BackgroundTasks,
 }
 
 impl NodeManager {
@@ -143,13 +237,82 @@ impl NodeManager {
         self.secure_channels.vault().clone()
     }
 
+    /// Return the number of connections currently going through the outlet worker at `worker_addr`
+    pub(crate) fn outlet_connections_count(&self, worker_addr: &Address) -> usize {
+        self.tcp_transport
+            .registry()
+            .get_outlet_connections_count(worker_addr)
+    }
+
+    /// Return the socket addresses of the TCP listeners of this node
+    pub fn tcp_listener_addresses(&self) -> Vec<SocketAddr> {
+        self.tcp_transport
+            .registry()
+            .get_all_listeners()
+            .iter()
+            .map(|listener| listener.socket_address())
+            .collect()
+    }
+
+    /// Return the outlets of the node, sorted by alias.
+    /// The order doesn't depend on the order in which the outlets were created
     pub fn list_outlets(&self) -> OutletList {
-        let outlets = self.registry.outlets.clone();
+        // the outlets are stored in a BTreeMap, which iterates over the aliases in order
         OutletList::new(
-            outlets
+            self.registry
+                .outlets
+                .iter()
+                .map(|(alias, info)| self.outlet_status(alias, info))
+                .collect(),
+        )
+    }
+
+    /// Return the outlets having all the given tags, with the same values, sorted by alias.
+    /// All the outlets are returned when no tag is given
+    pub fn find_outlets_by_tags<K: AsRef<str>, V: AsRef<str>>(
+        &self,
+        tags: &[(K, V)],
+    ) -> Vec<OutletStatus> {
+        self.registry
+            .outlets
+            .iter()
+            .map(|(alias, info)| self.outlet_status(alias, info))
+            .filter(|outlet| outlet.has_tags(tags))
+            .collect()
+    }
+
+    /// Return the outlet with the given alias, or `None` if there is no such outlet.
+    /// The outlet is looked up directly in the registry, without listing all the outlets
+    pub fn get_outlet(&self, alias: &str) -> Option<OutletStatus> {
+        self.registry
+            .outlets
+            .get(alias)
+            .map(|info| self.outlet_status(alias, info))
+    }
+
+    /// Return the current status of a registered outlet
+    pub(crate) fn outlet_status(&self, alias: &str, info: &OutletInfo) -> OutletStatus {
+        OutletStatus::new(&info.tcp_addr, info.worker_addr.to_string(), alias, None)
+            .with_active_connections(self.outlet_connections_count(&info.worker_addr))
+            .with_idle_timeout(info.idle_timeout)
+            .with_max_connections(info.max_connections)
+            .with_tags(info.tags.clone())
+    }
+
+    /// Return the inlets of the node, sorted by alias
+    pub fn list_inlets(&self) -> InletList {
+        InletList::new(
+            self.registry
+                .inlets
                 .iter()
                 .map(|(alias, info)| {
-                    OutletStatus::new(&info.tcp_addr, info.worker_addr.to_string(), alias, None)
+                    InletStatus::new(
+                        &info.bind_addr,
+                        info.worker_addr.to_string(),
+                        alias,
+                        None,
+                        info.outlet_route.to_string(),
+                    )
                 })
                 .collect(),
         )
@@ -159,15 +322,44 @@ impl NodeManager {
 #[derive(Clone)]
 pub struct NodeManagerWorker {
     node_manager: Arc<RwLock<NodeManager>>,
+    request_timeout: Duration,
 }
 
 impl NodeManagerWorker {
     pub fn new(node_manager: NodeManager) -> Self {
         NodeManagerWorker {
             node_manager: Arc::new(RwLock::new(node_manager)),
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
         }
     }
 
+    /// Set the maximum duration of the handling of the requests which don't set their own timeout
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// Return the maximum duration of the handling of a request
+    pub fn request_timeout(&self) -> Duration {
+        self.request_timeout
+    }
+
+    /// Run an operation of the node manager for at most `timeout`.
+    ///
+    /// If the operation takes longer, for example because a worker it waits for is stuck,
+    /// it is dropped and a timeout error is returned
+    pub async fn with_timeout<F: Future>(timeout: Duration, operation: F) -> Result<F::Output> {
+        ockam::compat::tokio::time::timeout(timeout, operation)
+            .await
+            .map_err(|_| {
+                ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::Timeout,
+                    format!("the operation timed out after {}ms", timeout.as_millis()),
+                )
+            })
+    }
+
     pub fn get(&self) -> &Arc<RwLock<NodeManager>> {
         &self.node_manager
     }
@@ -185,6 +377,12 @@ impl NodeManagerWorker {
         let nm = self.node_manager.read().await;
         nm.medic_handle.stop_medic(ctx).await
     }
+
+    /// Restart a node manager stopped with `stop`, with the same sessions
+    pub async fn restart(&self, ctx: &Context) -> Result<()> {
+        let mut nm = self.node_manager.write().await;
+        nm.medic_handle.restart_medic(ctx).await
+    }
 }
 
 pub struct IdentityOverride {
@@ -229,23 +427,78 @@ impl NodeManager {
                 self.policies.set_policy(r, a, &fallback).await?
             }
             let policies = self.policies.clone();
-            Ok(Arc::new(PolicyAccessControl::new(
+            let access_control = PolicyAccessControl::new(
                 policies,
                 self.identities_repository(),
                 r.clone(),
                 a.clone(),
                 env,
-            )))
+            );
+            Ok(match &self.decision_log {
+                Some(_) => Arc::new(PolicyDenialAccessControl::new(access_control)),
+                None => Arc::new(access_control),
+            })
         } else {
             Ok(Arc::new(AllowAll))
         }
     }
 
+    /// Record the decisions of the access control of a resource, if the node has a decision log.
+    ///
+    /// The access control must be complete, including the revocations and the pause of the
+    /// node, so that the reasons of all the denials are recorded
+    fn with_decision_log(
+        &self,
+        access_control: Arc<dyn IncomingAccessControl>,
+        r: &Resource,
+        a: &Action,
+    ) -> Arc<dyn IncomingAccessControl> {
+        match &self.decision_log {
+            Some(sink) => Arc::new(DecisionLogAccessControl::new(
+                access_control,
+                sink.clone(),
+                r.clone(),
+                a.clone(),
+            )),
+            None => access_control,
+        }
+    }
+
+    /// Return the default trust context, which can be restored with `restore_trust_context`
+    /// if a change of trust context is rolled back
+    pub fn default_trust_context(&self) -> Option<TrustContext> {
+        self.trust_context.clone()
+    }
+
+    /// Replace the default trust context with one returned by `default_trust_context`
+    pub fn restore_trust_context(&mut self, trust_context: Option<TrustContext>) {
+        self.trust_context = trust_context
+    }
+
     pub(crate) fn trust_context(&self) -> Result<&TrustContext> {
         self.trust_context
             .as_ref()
             .ok_or_else(|| ApiError::generic("Trust context doesn't exist"))
     }
+
+    /// Return the trust context with a given name, or the default trust context if no name is given
+    pub(crate) fn named_trust_context(&self, name: Option<&str>) -> Result<&TrustContext> {
+        match name {
+            None => self.trust_context(),
+            Some(name) => self.trust_contexts.get(name).ok_or_else(|| {
+                ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::NotFound,
+                    format!("Trust context {name} doesn't exist"),
+                )
+            }),
+        }
+    }
+
+    /// Return the names of the additional trust contexts of this node
+    pub fn trust_context_names(&self) -> Vec<String> {
+        self.trust_contexts.keys().cloned().collect()
+    }
 }
 
 pub struct NodeManagerGeneralOptions {
@@ -253,6 +506,10 @@ pub struct NodeManagerGeneralOptions {
     node_name: String,
     skip_defaults: bool,
     pre_trusted_identities: Option<PreTrustedIdentities>,
+    vault: Option<Arc<dyn IdentitiesVault>>,
+    initial_policies: Vec<(Resource, Action, Expr)>,
+    force_initial_policies: bool,
+    decision_log: Option<Arc<dyn DecisionLogSink>>,
 }
 
 impl NodeManagerGeneralOptions {
@@ -267,8 +524,58 @@ impl NodeManagerGeneralOptions {
             node_name,
             skip_defaults,
             pre_trusted_identities,
+            vault: None,
+            initial_policies: vec![],
+            force_initial_policies: false,
+            decision_log: None,
         }
     }
+
+    /// Use a specific vault instead of the vault configured for the node
+    pub fn with_vault(mut self, vault: Arc<dyn IdentitiesVault>) -> Self {
+        self.vault = Some(vault);
+        self
+    }
+
+    /// Store these policies when the node manager is created, before any message is handled.
+    /// A policy already stored for the same resource and action, for example by a previous
+    /// run of the node, is kept unless the initial policies are forced
+    pub fn with_initial_policies(mut self, policies: Vec<(Resource, Action, Expr)>) -> Self {
+        self.initial_policies = policies;
+        self
+    }
+
+    /// Replace the stored policies with the initial policies
+    pub fn with_forced_initial_policies(mut self, force: bool) -> Self {
+        self.force_initial_policies = force;
+        self
+    }
+
+    /// Record the access decisions of the portals, services and secure channel listeners.
+    /// Without a sink, the decisions are written to the file given by the
+    /// [`OCKAM_DECISION_LOG`] environment variable, if it is set
+    pub fn with_decision_log(mut self, sink: Arc<dyn DecisionLogSink>) -> Self {
+        self.decision_log = Some(sink);
+        self
+    }
+}
+
+/// Store the initial policies of a node, keeping the policies already stored for the same
+/// resources and actions unless `force` is set
+async fn seed_policies(
+    policies: &dyn PolicyStorage,
+    initial_policies: &[(Resource, Action, Expr)],
+    force: bool,
+) -> Result<()> {
+    for (resource, action, expr) in initial_policies {
+        if !force && policies.get_policy(resource, action).await?.is_some() {
+            debug!(%resource, %action, "keeping the stored policy instead of the initial one");
+            continue;
+        }
+        debug!(%resource, %action, "storing an initial policy");
+        policies.set_policy(resource, action, expr).await?;
+    }
+    Ok(())
 }
 
 #[derive(Clone)]
@@ -304,14 +611,27 @@ impl NodeManagerTransportOptions {
 
 pub struct NodeManagerTrustOptions {
     trust_context_config: Option<TrustContextConfig>,
+    named_trust_context_configs: Vec<(String, TrustContextConfig)>,
 }
 
 impl NodeManagerTrustOptions {
     pub fn new(trust_context_config: Option<TrustContextConfig>) -> Self {
         Self {
             trust_context_config,
+            named_trust_context_configs: vec![],
         }
     }
+
+    /// Add a trust context which can be selected by name, in addition to the default one
+    pub fn with_trust_context(
+        mut self,
+        name: impl Into<String>,
+        trust_context_config: TrustContextConfig,
+    ) -> Self {
+        self.named_trust_context_configs
+            .push((name.into(), trust_context_config));
+        self
+    }
 }
 
 impl NodeManager {
@@ -339,7 +659,21 @@ impl NodeManager {
 
         //TODO: fix this.  Either don't require it to be a bootstrappedidentitystore (and use the
         //trait instead),  or pass it from the general_options always.
-        let vault: Arc<dyn IdentitiesVault> = node_state.config().vault().await?;
+        let vault: Arc<dyn IdentitiesVault> = match general_options.vault {
+            Some(vault) => vault,
+            None => node_state.config().vault().await?,
+        };
+        // the pre-trusted attributes take precedence over the attributes of the credentials,
+        // which take precedence over the local attributes of the identities
+        let repository: Arc<dyn IdentitiesRepository> = Arc::new(LocalAttributesStore::new(
+            cli_state.identities.attributes_reader(),
+            repository,
+        ));
+        debug!("start pruning the expired local attributes");
+        let mut background_tasks = BackgroundTasks::default();
+        background_tasks.spawn(prune_expired_attributes_periodically(
+            cli_state.identities.clone(),
+        ));
         let identities_repository: Arc<dyn IdentitiesRepository> =
             Arc::new(match general_options.pre_trusted_identities {
                 None => BootstrapedIdentityStore::new(
@@ -356,6 +690,25 @@ impl NodeManager {
             .build();
 
         let policies: Arc<dyn PolicyStorage> = Arc::new(node_state.policies_storage().await?);
+        seed_policies(
+            policies.as_ref(),
+            &general_options.initial_policies,
+            general_options.force_initial_policies,
+        )
+        .await?;
+
+        let revocations = RevocationList::load(&node_state.revocation_list_path())?;
+        let decision_log = match general_options.decision_log {
+            Some(sink) => Some(sink),
+            None => match get_env::<PathBuf>(OCKAM_DECISION_LOG)? {
+                Some(path) => {
+                    debug!(path = %path.display(), "write the access decisions to a file");
+                    let log = JsonLinesDecisionLog::create(&path, DECISION_LOG_CAPACITY)?;
+                    Some(Arc::new(log) as Arc<dyn DecisionLogSink>)
+                }
+                None => None,
+            },
+        };
 
         debug!("start the Medic");
         let medic_handle = MedicHandle::start_medic(ctx).await?;
@@ -377,9 +730,16 @@ impl NodeManager {
             identifier: node_state.config().identifier().await?,
             secure_channels,
             trust_context: None,
+            trust_contexts: BTreeMap::new(),
+            trust_context_configs: BTreeMap::new(),
             registry: Default::default(),
             medic_handle,
             policies,
+            pause_switch: PauseSwitch::default(),
+            revocations,
+            decision_log,
+            starting: false,
+            background_tasks,
         };
 
         if !general_options.skip_defaults {
@@ -388,13 +748,17 @@ impl NodeManager {
                 debug!("configuring trust context");
                 s.configure_trust_context(&tc).await?;
             }
+            for (name, tc) in trust_options.named_trust_context_configs {
+                debug!(%name, "configuring trust context");
+                s.add_trust_context(&name, &tc).await?;
+            }
         }
         info!("created a node manager for the node: {}", s.node_name);
 
         Ok(s)
     }
 
-    async fn configure_trust_context(&mut self, tc: &TrustContextConfig) -> Result<()> {
+    pub async fn configure_trust_context(&mut self, tc: &TrustContextConfig) -> Result<()> {
         self.trust_context = Some(
             tc.to_trust_context(
                 self.secure_channels.clone(),
@@ -408,6 +772,22 @@ impl NodeManager {
         Ok(())
     }
 
+    /// Add a trust context which can then be selected by name when creating secure channels,
+    /// secure channel listeners and outlets. An existing trust context with the same name is replaced
+    pub async fn add_trust_context(&mut self, name: &str, tc: &TrustContextConfig) -> Result<()> {
+        let tc = tc.resolve_credential_names(&self.cli_state)?;
+        let trust_context = tc
+            .to_trust_context(
+                self.secure_channels.clone(),
+                Some(self.tcp_transport.async_try_clone().await?),
+            )
+            .await?;
+        self.trust_contexts.insert(name.to_string(), trust_context);
+        self.trust_context_configs.insert(name.to_string(), tc);
+        info!(%name, "NodeManager::add_trust_context: trust context configured");
+        Ok(())
+    }
+
     async fn initialize_defaults(
         &mut self,
         ctx: &Context,
@@ -433,6 +813,7 @@ impl NodeManager {
             None, // Not checking identifiers here in favor of credential check
             None,
             None,
+            None,
             ctx,
         )
         .await?;
@@ -527,6 +908,11 @@ impl NodeManager {
     pub fn add_session(&self, session: Session) -> Key {
         self.medic_handle.add_session(session)
     }
+
+    /// Stop monitoring a session, so that it is not replaced anymore
+    pub fn remove_session(&self, key: &Key) -> Option<Session> {
+        self.medic_handle.remove_session(key)
+    }
 }
 
 impl NodeManagerWorker {
@@ -559,11 +945,18 @@ impl NodeManagerWorker {
             // ==*== Basic node information ==*==
             // TODO: create, delete, destroy remote nodes
             (Get, ["node"]) => {
-                let node_name = &self.node_manager.read().await.node_name;
+                let node_manager = self.node_manager.read().await;
+                let status = if node_manager.is_starting() {
+                    NodeRunStatus::Starting
+                } else if node_manager.is_paused() {
+                    NodeRunStatus::Paused
+                } else {
+                    NodeRunStatus::Running
+                };
                 Response::ok(req.id())
                     .body(NodeStatus::new(
-                        node_name,
-                        "Running",
+                        &node_manager.node_name,
+                        status.as_str(),
                         ctx.list_workers().await?.len() as u32,
                         std::process::id() as i32,
                     ))
@@ -605,6 +998,18 @@ impl NodeManagerWorker {
 
             // ==*== Secure channels ==*==
             (Get, ["node", "secure_channel"]) => self.list_secure_channels(req).await.to_vec()?,
+            (Get, ["node", "trust_contexts"]) => {
+                encode_request_result(self.list_trust_contexts(req).await)?
+            }
+            (Delete, ["node", "trust_contexts", name]) => {
+                encode_request_result(self.delete_trust_context(req, name).await)?
+            }
+            (Put, ["node", "trust_contexts", "credential"]) => {
+                encode_request_result(self.update_trust_context_credential(req, dec).await)?
+            }
+            (Get, ["node", "secure_channels"]) => {
+                encode_request_result(self.list_secure_channels_status(req).await)?
+            }
             (Get, ["node", "secure_channel_listener"]) => {
                 self.list_secure_channel_listener(req).await.to_vec()?
             }
@@ -700,7 +1105,7 @@ impl NodeManagerWorker {
             (Get, ["node", "inlet", alias]) => {
                 encode_request_result(self.show_inlet(req, alias).await)?
             }
-            (Get, ["node", "outlet"]) => self.get_outlets(req).await.to_vec()?,
+            (Get, ["node", "outlet"]) => self.get_outlets(req, dec).await?.to_vec()?,
             (Get, ["node", "outlet", alias]) => {
                 encode_request_result(self.show_outlet(req, alias).await)?
             }
@@ -716,26 +1121,64 @@ impl NodeManagerWorker {
             (Delete, ["node", "inlet", alias]) => {
                 encode_request_result(self.delete_inlet(req, alias).await)?
             }
+            (Post, ["node", "outlet_events"]) => {
+                self.subscribe_to_outlet_events(req).await.to_vec()?
+            }
+            (Delete, ["node", "outlet_events", id]) => {
+                encode_request_result(self.unsubscribe_from_outlet_events(req, id).await)?
+            }
+            (Get, ["node", "startup_config"]) => {
+                encode_request_result(self.export_startup_config(req, dec).await)?
+            }
+            (Post, ["node", "logs"]) => {
+                encode_request_result(self.subscribe_to_logs(req, dec).await)?
+            }
+            (Get, ["node", "logs", id]) => {
+                encode_request_result(self.get_log_lines(req, id).await)?
+            }
+            (Delete, ["node", "logs", id]) => {
+                encode_request_result(self.unsubscribe_from_logs(req, id).await)?
+            }
             (Delete, ["node", "portal"]) => todo!(),
 
+            // ==*== Revocations ==*==
+            (Get, ["node", "revocations"]) => {
+                encode_request_result(self.node_manager.read().await.list_revoked_identities(req))?
+            }
+            (Put, ["node", "revocations", identifier]) => encode_request_result(
+                self.node_manager
+                    .write()
+                    .await
+                    .revoke_identity(ctx, req, identifier)
+                    .await,
+            )?,
+            (Delete, ["node", "revocations", identifier]) => encode_request_result(
+                self.node_manager
+                    .read()
+                    .await
+                    .unrevoke_identity(req, identifier),
+            )?,
+
+            // ==*== Metrics ==*==
+            (Get, ["node", "metrics"]) => encode_request_result(self.get_metrics(req).await)?,
+
             // ==*== Flow Controls ==*==
             (Post, ["node", "flow_controls", "add_consumer"]) => {
                 encode_request_result(self.add_consumer(ctx, req, dec))?
             }
 
             // ==*== Workers ==*==
-            (Get, ["node", "workers"]) => {
-                let workers = ctx.list_workers().await?;
-
-                let mut list = Vec::new();
-                workers
-                    .iter()
-                    .for_each(|addr| list.push(WorkerStatus::new(addr.address())));
-
-                Response::ok(req.id())
-                    .body(WorkerList::new(list))
-                    .to_vec()?
+            (Get, ["node", "workers"]) => encode_request_result(self.list_workers(ctx, req).await)?,
+            (Post, ["node", "workers", "actions", "prune"]) => {
+                encode_request_result(self.prune_stale_workers(ctx, req).await)?
             }
+            (Get, ["policy"]) => encode_request_result(
+                self.node_manager
+                    .read()
+                    .await
+                    .get_policy_map(req, dec)
+                    .await,
+            )?,
             (Post, ["policy", resource, action]) => encode_request_result(
                 self.node_manager
                     .read()
@@ -757,6 +1200,20 @@ impl NodeManagerWorker {
                 .get_policy(req, resource, action)
                 .await?
                 .either(ResponseBuilder::to_vec, ResponseBuilder::to_vec)?,
+            (Get, ["policy", resource, action, "effective"]) => encode_request_result(
+                self.node_manager
+                    .read()
+                    .await
+                    .get_effective_policy(req, resource, action)
+                    .await,
+            )?,
+            (Get, ["policy", resource, action, "evaluate"]) => encode_request_result(
+                self.node_manager
+                    .read()
+                    .await
+                    .evaluate_policy(req, resource, action, dec)
+                    .await,
+            )?,
             (Delete, ["policy", resource, action]) => encode_request_result(
                 self.node_manager
                     .read()
@@ -888,7 +1345,24 @@ impl Worker for NodeManagerWorker {
             }
         };
 
-        let r = match self.handle_request(ctx, &req, &mut dec).await {
+        // the response to this request is sent once the outlets change, see get_outlet_events
+        if let (Some(Method::Get), ["node", "outlet_events", id]) =
+            (req.method(), req.path_segments::<5>().as_slice())
+        {
+            return self
+                .get_outlet_events(ctx, &req, id, msg.return_route())
+                .await;
+        }
+
+        let r = if can_time_out(&req) {
+            let timeout = req.timeout().unwrap_or(self.request_timeout);
+            Self::with_timeout(timeout, self.handle_request(ctx, &req, &mut dec))
+                .await
+                .and_then(|r| r)
+        } else {
+            self.handle_request(ctx, &req, &mut dec).await
+        };
+        let r = match r {
             Ok(r) => r,
             Err(err) => {
                 error! {
@@ -900,11 +1374,14 @@ impl Worker for NodeManagerWorker {
                     cause  = ?err.source(),
                     "failed to handle request"
                 }
+                let status = if err.code().kind == Kind::Timeout {
+                    Status::RequestTimeout
+                } else {
+                    Status::InternalServerError
+                };
                 let err = Error::new(req.path())
                     .with_message(format!("failed to handle request: {err} {req:?}"));
-                Response::builder(req.id(), Status::InternalServerError)
-                    .body(err)
-                    .to_vec()?
+                Response::builder(req.id(), status).body(err).to_vec()?
             }
         };
         debug! {
@@ -917,3 +1394,228 @@ impl Worker for NodeManagerWorker {
         ctx.send(msg.return_route(), r).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ockam::{Context, Worker};
+    use ockam_core::flow_control::FlowControls;
+    use ockam_core::{async_trait, route};
+    use ockam_multiaddr::proto::Service;
+
+    use crate::cli_state::NodeConfig;
+    use crate::nodes::service::message::SendMessage;
+    use crate::nodes::NODEMANAGER_ADDR;
+    use crate::util::test_utils::{start_manager_for_tests, NodeManagerHandle};
+
+    use super::*;
+
+    /// This worker answers each message after a delay
+    struct SlowWorker(Duration);
+
+    #[async_trait]
+    impl Worker for SlowWorker {
+        type Message = Vec<u8>;
+        type Context = Context;
+
+        async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Vec<u8>>) -> Result<()> {
+            ockam::compat::tokio::time::sleep(self.0).await;
+            ctx.send(msg.return_route(), msg.body()).await
+        }
+    }
+
+    #[ockam_macros::test(timeout = 10_000)]
+    async fn a_stuck_request_times_out(context: &mut Context) -> Result<()> {
+        let _handler = start_manager_for_tests(context).await?;
+        context
+            .start_worker("slow", SlowWorker(Duration::from_secs(1)))
+            .await?;
+        let mut slow_worker = MultiAddr::default();
+        slow_worker.push_back(Service::new("slow"))?;
+        let send_message =
+            || Request::post("/v0/message").body(SendMessage::new(&slow_worker, b"hello".to_vec()));
+
+        // the request fails once its timeout is expired
+        let request = send_message().timeout(Duration::from_millis(100));
+        let response: Vec<u8> = context
+            .send_and_receive(route![NODEMANAGER_ADDR], request.to_vec()?)
+            .await?;
+        let (header, decoder) = Response::parse_response_header(&response)?;
+        assert_eq!(header.status(), Some(Status::RequestTimeout));
+        assert!(Response::parse_err_msg(header, decoder).contains("timed out"));
+
+        // the worker can still handle the next requests, which can take longer
+        let request = send_message().timeout(Duration::from_secs(5));
+        let response: Vec<u8> = context
+            .send_and_receive(route![NODEMANAGER_ADDR], request.to_vec()?)
+            .await?;
+        let answer: Vec<u8> = Response::parse_response_body(&response)?;
+        assert_eq!(answer, b"hello".to_vec());
+        context.stop().await
+    }
+
+    /// Return the policies of a resource, with their expressions as strings
+    async fn stored_policies(
+        policies: &dyn PolicyStorage,
+        resource: &Resource,
+    ) -> Result<Vec<(Action, String)>> {
+        Ok(policies
+            .policies(resource)
+            .await?
+            .into_iter()
+            .map(|(action, expr)| (action, expr.to_string()))
+            .collect())
+    }
+
+    /// Create a node manager for the node `node_name`, storing the initial policies
+    async fn create_with_initial_policies(
+        context: &Context,
+        handler: &NodeManagerHandle,
+        node_name: &str,
+        initial_policies: Vec<(Resource, Action, Expr)>,
+        force: bool,
+    ) -> Result<NodeManager> {
+        NodeManager::create(
+            context,
+            NodeManagerGeneralOptions::new(
+                handler.cli_state.clone(),
+                node_name.to_string(),
+                true,
+                None,
+            )
+            .with_initial_policies(initial_policies)
+            .with_forced_initial_policies(force),
+            NodeManagerTransportOptions::new(
+                FlowControls::generate_flow_control_id(),
+                handler.tcp.async_try_clone().await?,
+            ),
+            NodeManagerTrustOptions::new(None),
+        )
+        .await
+    }
+
+    #[ockam_macros::test(timeout = 10_000)]
+    async fn the_initial_policies_dont_replace_the_stored_ones(
+        context: &mut Context,
+    ) -> Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        // only one medic can run at a time
+        handler
+            .node_manager
+            .read()
+            .await
+            .medic_handle
+            .stop_medic(context)
+            .await?;
+        let node_name = "restarted";
+        let node_config = NodeConfig::try_from(&handler.cli_state).unwrap();
+        handler.cli_state.nodes.create(node_name, node_config)?;
+
+        let resource = Resource::new("db");
+        let action = Action::new("handle_message");
+        let initial = eq([ident("subject.component"), str("web")]);
+        let initial_policies = vec![(resource.clone(), action.clone(), initial.clone())];
+
+        let node_manager = create_with_initial_policies(
+            context,
+            &handler,
+            node_name,
+            initial_policies.clone(),
+            false,
+        )
+        .await?;
+        assert_eq!(
+            stored_policies(node_manager.policies.as_ref(), &resource).await?,
+            vec![(action.clone(), initial.to_string())]
+        );
+
+        // a policy modified after the first start is kept when the node restarts
+        let modified = eq([ident("subject.component"), str("api")]);
+        node_manager
+            .policies
+            .set_policy(&resource, &action, &modified)
+            .await?;
+        node_manager.medic_handle.stop_medic(context).await?;
+        drop(node_manager);
+
+        let node_manager = create_with_initial_policies(
+            context,
+            &handler,
+            node_name,
+            initial_policies.clone(),
+            false,
+        )
+        .await?;
+        assert_eq!(
+            stored_policies(node_manager.policies.as_ref(), &resource).await?,
+            vec![(action.clone(), modified.to_string())]
+        );
+        node_manager.medic_handle.stop_medic(context).await?;
+        drop(node_manager);
+
+        // unless the initial policies are forced
+        let node_manager =
+            create_with_initial_policies(context, &handler, node_name, initial_policies, true)
+                .await?;
+        assert_eq!(
+            stored_policies(node_manager.policies.as_ref(), &resource).await?,
+            vec![(action, initial.to_string())]
+        );
+        node_manager.medic_handle.stop_medic(context).await?;
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn the_background_tasks_are_aborted_with_the_node_manager(
+        context: &mut Context,
+    ) -> Result<()> {
+        let running = Arc::new(());
+        let mut background_tasks = BackgroundTasks::default();
+        let task_running = running.clone();
+        background_tasks.spawn(async move {
+            let _running = task_running;
+            std::future::pending::<()>().await
+        });
+        assert_eq!(Arc::strong_count(&running), 2);
+
+        drop(background_tasks);
+        while Arc::strong_count(&running) > 1 {
+            ockam_node::tokio::task::yield_now().await;
+        }
+
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+        assert_eq!(node_manager.background_tasks.tasks.len(), 1);
+        node_manager.shutdown(context).await;
+        assert!(node_manager.background_tasks.tasks.is_empty());
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[test]
+    fn only_the_reads_and_the_remote_calls_time_out() {
+        assert!(can_time_out(Request::get("/node/outlet").header()));
+        assert!(can_time_out(Request::post("/v0/message").header()));
+        assert!(can_time_out(
+            Request::post("/v1/spaces/1/projects").header()
+        ));
+        assert!(!can_time_out(Request::post("/node/outlet").header()));
+        assert!(!can_time_out(Request::delete("/node/outlet/db").header()));
+    }
+
+    #[test]
+    fn an_operation_is_dropped_after_its_timeout() {
+        let runtime = ockam::compat::tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let result = NodeManagerWorker::with_timeout(
+                Duration::from_millis(10),
+                std::future::pending::<()>(),
+            )
+            .await;
+            assert_eq!(result.unwrap_err().code().kind, Kind::Timeout);
+            let result =
+                NodeManagerWorker::with_timeout(DEFAULT_REQUEST_TIMEOUT, async { 42 }).await;
+            assert_eq!(result.unwrap(), 42);
+        });
+    }
+}
@@ -0,0 +1,276 @@
+use ockam::identity::{IdentitiesRepository, IdentityIdentifier, IdentitySecureChannelLocalInfo};
+use ockam::Result;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{async_trait, IncomingAccessControl, RelayMessage};
+
+use crate::decision_log::{record_denial, DenialReason};
+
+/// Access control of an outlet created with a required credential.
+///
+/// The messages are only given to the inner access control if they come from a secure channel
+/// whose peer presented a credential issued by one of the authorities of the trust context of
+/// the outlet. The expired credentials are not returned by the identities repository, so a
+/// peer whose credential expired is rejected as well.
+pub(crate) struct CredentialRequiredAccessControl {
+    inner: Arc<dyn IncomingAccessControl>,
+    identities_repository: Arc<dyn IdentitiesRepository>,
+    authorities: Vec<IdentityIdentifier>,
+    outlet_alias: String,
+}
+
+impl CredentialRequiredAccessControl {
+    pub(crate) fn new(
+        inner: Arc<dyn IncomingAccessControl>,
+        identities_repository: Arc<dyn IdentitiesRepository>,
+        authorities: Vec<IdentityIdentifier>,
+        outlet_alias: &str,
+    ) -> Self {
+        Self {
+            inner,
+            identities_repository,
+            authorities,
+            outlet_alias: outlet_alias.to_string(),
+        }
+    }
+
+    /// Return true if the identity has a valid credential issued by one of the authorities
+    async fn has_valid_credential(&self, identifier: &IdentityIdentifier) -> Result<bool> {
+        let entry = match self
+            .identities_repository
+            .get_attributes(identifier)
+            .await?
+        {
+            Some(entry) => entry,
+            None => return Ok(false),
+        };
+        Ok(entry
+            .attested_by()
+            .map_or(false, |authority| self.authorities.contains(&authority)))
+    }
+}
+
+impl core::fmt::Debug for CredentialRequiredAccessControl {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CredentialRequiredAccessControl")
+            .field("authorities", &self.authorities)
+            .field("outlet_alias", &self.outlet_alias)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl IncomingAccessControl for CredentialRequiredAccessControl {
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        let identifier = match IdentitySecureChannelLocalInfo::find_info(relay_msg.local_message())
+        {
+            Ok(info) => info.their_identity_id(),
+            Err(_) => {
+                warn!(
+                    outlet = %self.outlet_alias,
+                    "credential required: the message doesn't come from a secure channel, message denied"
+                );
+                record_denial(DenialReason::CredentialRequired);
+                return Ok(false);
+            }
+        };
+        match self.has_valid_credential(&identifier).await {
+            Ok(true) => {}
+            Ok(false) => {
+                warn!(
+                    outlet = %self.outlet_alias,
+                    peer = %identifier,
+                    "credential required: the peer didn't present a valid credential issued by the trust context authority, message denied"
+                );
+                record_denial(DenialReason::CredentialRequired);
+                return Ok(false);
+            }
+            Err(e) => {
+                warn!(
+                    outlet = %self.outlet_alias,
+                    peer = %identifier,
+                    error = %e,
+                    "credential required: the credential of the peer couldn't be checked, message denied"
+                );
+                record_denial(DenialReason::CredentialRequired);
+                return Ok(false);
+            }
+        }
+        let authorized = self.inner.is_authorized(relay_msg).await?;
+        if !authorized {
+            warn!(
+                outlet = %self.outlet_alias,
+                peer = %identifier,
+                "credential required: the peer presented a valid credential but the outlet policy denied the message"
+            );
+        }
+        Ok(authorized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use ockam::identity::{
+        AttributesEntry, IdentitiesStorage, IdentityAttributesWriter, SecureChannelOptions,
+        Timestamp,
+    };
+    use ockam_core::{route, Address, AllowAll, DenyAll, LocalMessage, TransportMessage};
+    use ockam_node::Context;
+    use ockam_transport_tcp::TcpInletOptions;
+
+    use crate::nodes::service::OutletSpec;
+    use crate::util::test_utils::{reachable, start_echo_server, start_manager_for_tests};
+    use crate::DefaultAddress;
+
+    use super::*;
+
+    fn identifier(n: u8) -> IdentityIdentifier {
+        IdentityIdentifier::from_hex(&hex::encode([n; 32]))
+    }
+
+    fn message_from(peer: Option<IdentityIdentifier>) -> Result<RelayMessage> {
+        let transport = TransportMessage::v1(route!["outlet"], route!["inlet"], vec![]);
+        let local_info = match peer {
+            Some(peer) => IdentitySecureChannelLocalInfo::mark(vec![], peer)?,
+            None => vec![],
+        };
+        Ok(RelayMessage::new(
+            Address::from_string("inlet"),
+            Address::from_string("outlet"),
+            LocalMessage::new(transport, local_info),
+        ))
+    }
+
+    async fn attest(
+        repository: &Arc<IdentitiesStorage>,
+        subject: &IdentityIdentifier,
+        authority: &IdentityIdentifier,
+    ) -> Result<()> {
+        let entry = AttributesEntry::new(
+            BTreeMap::from([("role".to_string(), b"member".to_vec())]),
+            Timestamp::now().unwrap(),
+            None,
+            Some(authority.clone()),
+        );
+        repository.put_attributes(subject, entry).await
+    }
+
+    #[tokio::test]
+    async fn only_the_peers_with_a_valid_credential_are_accepted() -> Result<()> {
+        let repository = IdentitiesStorage::create();
+        let authority = identifier(1);
+        let other_authority = identifier(2);
+        let (member, former_member, stranger) = (identifier(3), identifier(4), identifier(5));
+        attest(&repository, &member, &authority).await?;
+        attest(&repository, &former_member, &other_authority).await?;
+
+        let access_control = CredentialRequiredAccessControl::new(
+            Arc::new(AllowAll),
+            repository.clone(),
+            vec![authority],
+            "db",
+        );
+        assert!(
+            access_control
+                .is_authorized(&message_from(Some(member.clone()))?)
+                .await?
+        );
+        // a credential issued by another authority is not valid
+        assert!(
+            !access_control
+                .is_authorized(&message_from(Some(former_member))?)
+                .await?
+        );
+        assert!(
+            !access_control
+                .is_authorized(&message_from(Some(stranger))?)
+                .await?
+        );
+        // the messages which don't come from a secure channel have no credential
+        assert!(!access_control.is_authorized(&message_from(None)?).await?);
+
+        // the inner access control is still checked
+        let access_control = CredentialRequiredAccessControl::new(
+            Arc::new(DenyAll),
+            repository,
+            vec![identifier(1)],
+            "db",
+        );
+        assert!(
+            !access_control
+                .is_authorized(&message_from(Some(member))?)
+                .await?
+        );
+        Ok(())
+    }
+
+    #[ockam_macros::test(timeout = 10_000)]
+    async fn an_outlet_requiring_a_credential_is_only_reachable_by_the_attested_peers(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let target = start_echo_server().await;
+        let handler = start_manager_for_tests(context).await?;
+        let peer = handler
+            .secure_channels
+            .identities()
+            .identities_creation()
+            .create_identity()
+            .await?
+            .identifier();
+        let channel = handler
+            .secure_channels
+            .create_secure_channel(
+                context,
+                &peer,
+                route![DefaultAddress::SECURE_CHANNEL_LISTENER],
+                SecureChannelOptions::new(),
+            )
+            .await?;
+
+        let mut node_manager = handler.node_manager.write().await;
+        node_manager
+            .create_outlet_from_spec(
+                context,
+                OutletSpec::new(target.to_string(), "outlet")
+                    .with_alias("echo")
+                    .reachable_from_default_secure_channel(true)
+                    .with_require_credential(true),
+            )
+            .await?;
+        let (inlet_address, _) = handler
+            .tcp
+            .create_inlet(
+                "127.0.0.1:0",
+                route![channel.encryptor_address().clone(), "outlet"],
+                TcpInletOptions::new(),
+            )
+            .await?;
+
+        // the attributes of the peer satisfy the outlet policy, but they were not attested
+        // by the authority of the trust context of the outlet
+        let project = ("project_id".to_string(), b"test_trust_context".to_vec());
+        let attributes = |authority: IdentityIdentifier| {
+            AttributesEntry::new(
+                BTreeMap::from([project.clone()]),
+                Timestamp::now().unwrap(),
+                None,
+                Some(authority),
+            )
+        };
+        node_manager
+            .attributes_writer()
+            .put_attributes(&peer, attributes(identifier(1)))
+            .await?;
+        assert!(!reachable(inlet_address).await);
+
+        node_manager
+            .attributes_writer()
+            .put_attributes(&peer, attributes(handler.identifier.clone()))
+            .await?;
+        assert!(reachable(inlet_address).await);
+
+        drop(node_manager);
+        context.stop().await
+    }
+}
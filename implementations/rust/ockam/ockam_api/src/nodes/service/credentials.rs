@@ -1,8 +1,10 @@
+use std::future::Future;
 use std::str::FromStr;
 
 use either::Either;
 use minicbor::Decoder;
 
+use ockam::identity::credential::{CredentialData, Timestamp, Unverified};
 use ockam::identity::Credential;
 use ockam::Result;
 use ockam_core::api::{Error, Request, Response, ResponseBuilder};
@@ -15,7 +17,44 @@ use crate::local_multiaddr_to_route;
 use crate::nodes::models::credentials::{GetCredentialRequest, PresentCredentialRequest};
 use crate::nodes::service::map_multiaddr_err;
 
-use super::NodeManagerWorker;
+use super::{NodeManager, NodeManagerWorker};
+
+impl NodeManager {
+    /// Return the credential of the node identity, issued by the authority of the default
+    /// trust context. The authority is only contacted if there is no valid cached credential
+    pub async fn credential(&self, ctx: &Context) -> Result<Credential> {
+        self.trust_context()?
+            .authority()?
+            .credential(ctx, &self.identifier())
+            .await
+    }
+
+    /// Return a future retrieving the credential of the node identity and returning its
+    /// expiration time, see [`credential_expiration`].
+    ///
+    /// The future doesn't borrow the node manager, so a lock on the node manager can be released
+    /// while the authority is contacted
+    pub fn credential_expiration<'a>(
+        &self,
+        ctx: &'a Context,
+    ) -> impl Future<Output = Result<Timestamp>> + 'a {
+        let authority = self
+            .trust_context()
+            .and_then(|trust_context| trust_context.authority().cloned());
+        let identifier = self.identifier();
+        async move {
+            let credential = authority?.credential(ctx, &identifier).await?;
+            credential_expiration(&credential)
+        }
+    }
+}
+
+/// Return the expiration time of a credential.
+/// The credential is not verified, so the expiration time must only be used for display
+pub fn credential_expiration(credential: &Credential) -> Result<Timestamp> {
+    let data: CredentialData<Unverified> = minicbor::decode(credential.unverified_data())?;
+    Ok(data.unverified_expires_at())
+}
 
 impl NodeManagerWorker {
     pub(super) async fn get_credential(
@@ -72,11 +111,7 @@ impl NodeManagerWorker {
             None => return Err(ApiError::generic("Invalid credentials service route").into()),
         };
 
-        let credential = node_manager
-            .trust_context()?
-            .authority()?
-            .credential(ctx, &node_manager.identifier())
-            .await?;
+        let credential = node_manager.credential(ctx).await?;
 
         if request.oneway {
             node_manager
@@ -21,6 +21,26 @@ use crate::session::sessions::{MAX_CONNECT_TIME, MAX_RECOVERY_TIME};
 
 use super::{NodeManager, NodeManagerWorker};
 
+impl NodeManager {
+    /// Stop a forwarder and remove it from the registry.
+    /// Return `None` if there is no forwarder with this remote address
+    pub async fn delete_forwarder(
+        &mut self,
+        ctx: &Context,
+        remote_address: &str,
+    ) -> Result<Option<ForwarderInfo>> {
+        let forwarder_to_delete = match self.registry.forwarders.remove(remote_address) {
+            Some(forwarder_to_delete) => forwarder_to_delete,
+            None => return Ok(None),
+        };
+        debug!(%remote_address, "Successfully removed forwarder from node registry");
+        ctx.stop_worker(forwarder_to_delete.worker_address().clone())
+            .await?;
+        debug!(%remote_address, "Successfully stopped forwarder");
+        Ok(Some(ForwarderInfo::from(forwarder_to_delete)))
+    }
+}
+
 impl NodeManagerWorker {
     pub(super) async fn create_forwarder_response(
         &self,
@@ -0,0 +1,179 @@
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use minicbor::Decoder;
+use ockam::Result;
+use ockam_core::api::{Error, Request, Response, ResponseBuilder};
+use ockam_core::errcode::{Kind, Origin};
+use tracing::Level;
+
+use crate::nodes::models::logs::{LogLines, LogsSubscription, SubscribeToLogs};
+use crate::nodes::registry::LogSubscriptionInfo;
+use crate::nodes::service::random_alias;
+
+use super::{NodeManager, NodeManagerWorker};
+
+/// Subscriptions which are not polled for this duration are removed,
+/// in case their client stopped without unsubscribing
+const SUBSCRIPTION_EXPIRATION: Duration = Duration::from_secs(60);
+
+impl NodeManager {
+    /// Subscribe to the log lines of the node, up to the `level` verbosity,
+    /// and return the subscription identifier.
+    /// Only the lines logged after the subscription are returned
+    pub fn subscribe_to_logs(&mut self, level: Level) -> String {
+        self.registry
+            .log_subscriptions
+            .retain(|_, s| s.last_poll.elapsed() < SUBSCRIPTION_EXPIRATION);
+        let id = random_alias();
+        self.registry
+            .log_subscriptions
+            .insert(id.clone(), LogSubscriptionInfo::new(level));
+        id
+    }
+
+    /// Return the log lines logged since the previous call, or since the subscription was created.
+    ///
+    /// The lines which are not polled in time are dropped, see [`crate::logs::LOG_LINES_CAPACITY`],
+    /// and replaced by a line with the number of dropped lines. So a slow client never
+    /// makes the node buffer an unbounded number of lines.
+    pub fn log_lines(&mut self, id: &str) -> Result<Vec<String>> {
+        let subscription = self.registry.log_subscriptions.get_mut(id).ok_or_else(|| {
+            ockam_core::Error::new(
+                Origin::Node,
+                Kind::NotFound,
+                format!("The logs subscription {id} doesn't exist"),
+            )
+        })?;
+        subscription.last_poll = Instant::now();
+        let lines = subscription.subscription.take_lines();
+        Ok(lines)
+    }
+
+    /// Remove a subscription. Return false if it doesn't exist
+    pub fn unsubscribe_from_logs(&mut self, id: &str) -> bool {
+        self.registry.log_subscriptions.remove(id).is_some()
+    }
+}
+
+impl NodeManagerWorker {
+    pub(super) async fn subscribe_to_logs(
+        &self,
+        req: &Request,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder<LogsSubscription>, ResponseBuilder<Error>> {
+        let request: SubscribeToLogs = dec.decode()?;
+        let level = match Level::from_str(&request.level) {
+            Ok(level) => level,
+            Err(_) => {
+                let err_body = Error::new(req.path())
+                    .with_message(format!("Invalid log level: {}", request.level));
+                return Err(Response::bad_request(req.id()).body(err_body));
+            }
+        };
+        let mut node_manager = self.node_manager.write().await;
+        let id = node_manager.subscribe_to_logs(level);
+        Ok(Response::ok(req.id()).body(LogsSubscription::new(id)))
+    }
+
+    pub(super) async fn get_log_lines(
+        &self,
+        req: &Request,
+        id: &str,
+    ) -> Result<ResponseBuilder<LogLines>, ResponseBuilder<Error>> {
+        let mut node_manager = self.node_manager.write().await;
+        match node_manager.log_lines(id) {
+            Ok(lines) => Ok(Response::ok(req.id()).body(LogLines::new(lines))),
+            Err(e) => {
+                let err_body = Error::new(req.path()).with_message(e.to_string());
+                Err(Response::not_found(req.id()).body(err_body))
+            }
+        }
+    }
+
+    pub(super) async fn unsubscribe_from_logs(
+        &self,
+        req: &Request,
+        id: &str,
+    ) -> Result<ResponseBuilder, ResponseBuilder<Error>> {
+        let mut node_manager = self.node_manager.write().await;
+        if node_manager.unsubscribe_from_logs(id) {
+            Ok(Response::ok(req.id()))
+        } else {
+            let err_body = Error::new(req.path())
+                .with_message(format!("The logs subscription {id} doesn't exist"));
+            Err(Response::not_found(req.id()).body(err_body))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ockam_core::errcode::Kind;
+    use ockam_node::Context;
+    use tracing::{debug, info, warn};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use crate::logs::{LogBroadcastLayer, LOG_LINES_CAPACITY};
+    use crate::util::test_utils::start_manager_for_tests;
+
+    use super::*;
+
+    /// Log with the broadcast layer
+    fn log(f: impl FnOnce()) {
+        let subscriber = tracing_subscriber::registry().with(LogBroadcastLayer);
+        tracing::subscriber::with_default(subscriber, f)
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn follow_the_logs_of_a_node(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+
+        let id = node_manager.subscribe_to_logs(Level::INFO);
+        log(|| {
+            info!(user = "alice", "following the logs");
+            debug!("too verbose");
+            warn!(count = 2, "careful");
+        });
+        let lines = node_manager.log_lines(&id)?;
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(
+            " INFO ockam_api::nodes::service::logs::tests: following the logs user=alice"
+        ));
+        assert!(lines[1].ends_with(" WARN ockam_api::nodes::service::logs::tests: careful count=2"));
+        assert!(node_manager.log_lines(&id)?.is_empty());
+
+        // the lines which are not polled in time are dropped
+        log(|| {
+            for i in 0..LOG_LINES_CAPACITY + 10 {
+                info!("line {i}");
+            }
+        });
+        let lines = node_manager.log_lines(&id)?;
+        assert_eq!(lines.len(), LOG_LINES_CAPACITY + 1);
+        assert_eq!(lines[0], "10 lines dropped");
+        assert!(lines[1].ends_with("line 10"));
+
+        // the lines which are too verbose are filtered before being buffered
+        // so they don't make the subscription drop any line
+        log(|| {
+            info!("first");
+            for i in 0..LOG_LINES_CAPACITY + 10 {
+                debug!("debug line {i}");
+            }
+            info!("last");
+        });
+        let lines = node_manager.log_lines(&id)?;
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("first"));
+        assert!(lines[1].ends_with("last"));
+
+        assert!(node_manager.unsubscribe_from_logs(&id));
+        let error = node_manager.log_lines(&id).unwrap_err();
+        assert_eq!(error.code().kind, Kind::NotFound);
+        assert!(!node_manager.unsubscribe_from_logs(&id));
+        drop(node_manager);
+        context.stop().await
+    }
+}
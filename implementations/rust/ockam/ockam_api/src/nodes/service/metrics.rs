@@ -0,0 +1,262 @@
+use std::fmt;
+use std::fmt::Write;
+
+use ockam::Result;
+use ockam_core::api::{Error, Request, Response, ResponseBuilder};
+
+use super::{NodeManager, NodeManagerWorker};
+
+/// Metrics of a TCP outlet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutletMetrics {
+    pub alias: String,
+    pub tcp_addr: String,
+    pub active_connections: usize,
+}
+
+/// Metrics of a node at a given time.
+///
+/// The outlets are sorted by alias, so that two snapshots of the same node are rendered in the
+/// same order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    pub outlets: Vec<OutletMetrics>,
+    pub inlets: usize,
+    pub secure_channels: usize,
+    pub tcp_listeners: usize,
+    pub tcp_connections: usize,
+}
+
+impl MetricsSnapshot {
+    /// Render the metrics in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        // each metric family takes a bit less than 200 bytes
+        let mut text = String::with_capacity(200 * 6 + 100 * self.outlets.len());
+        // writing to a String never fails
+        let _ = self.write_prometheus(&mut text);
+        text
+    }
+
+    /// Write the metrics in the Prometheus text exposition format
+    pub fn write_prometheus(&self, w: &mut impl Write) -> fmt::Result {
+        let gauges = [
+            (
+                "ockam_node_outlets",
+                "Number of TCP outlets of the node",
+                self.outlets.len(),
+            ),
+            (
+                "ockam_node_inlets",
+                "Number of TCP inlets of the node",
+                self.inlets,
+            ),
+            (
+                "ockam_node_secure_channels",
+                "Number of secure channels of the node",
+                self.secure_channels,
+            ),
+            (
+                "ockam_node_tcp_listeners",
+                "Number of TCP listeners of the node",
+                self.tcp_listeners,
+            ),
+            (
+                "ockam_node_tcp_connections",
+                "Number of TCP connections of the node transport",
+                self.tcp_connections,
+            ),
+        ];
+        for (name, help, value) in gauges {
+            write_family(w, name, help, "gauge")?;
+            writeln!(w, "{name} {value}")?;
+        }
+
+        let name = "ockam_outlet_active_connections";
+        write_family(
+            w,
+            name,
+            "Number of connections currently going through a TCP outlet",
+            "gauge",
+        )?;
+        for outlet in &self.outlets {
+            writeln!(
+                w,
+                "{name}{{alias=\"{}\",tcp_addr=\"{}\"}} {}",
+                LabelValue(&outlet.alias),
+                LabelValue(&outlet.tcp_addr),
+                outlet.active_connections
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Write the HELP and TYPE lines of a metric family
+fn write_family(w: &mut impl Write, name: &str, help: &str, kind: &str) -> fmt::Result {
+    writeln!(w, "# HELP {name} {help}")?;
+    writeln!(w, "# TYPE {name} {kind}")
+}
+
+/// Label value escaped as required by the exposition format:
+/// backslashes, double quotes and line feeds are escaped with a backslash
+struct LabelValue<'a>(&'a str);
+
+impl fmt::Display for LabelValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '\\' => f.write_str("\\\\")?,
+                '"' => f.write_str("\\\"")?,
+                '\n' => f.write_str("\\n")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl NodeManager {
+    /// Return the current metrics of the node
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        let outlets = self
+            .list_outlets()
+            .list
+            .into_iter()
+            .map(|outlet| OutletMetrics {
+                alias: outlet.alias,
+                tcp_addr: outlet.tcp_addr,
+                active_connections: outlet.active_connections,
+            })
+            .collect();
+        let tcp = self.tcp_transport.registry();
+        MetricsSnapshot {
+            outlets,
+            inlets: self.registry.inlets.len(),
+            secure_channels: self
+                .secure_channels
+                .secure_channel_registry()
+                .get_channel_list()
+                .len(),
+            tcp_listeners: tcp.get_all_listeners().len(),
+            tcp_connections: tcp.get_all_sender_workers().len(),
+        }
+    }
+}
+
+impl NodeManagerWorker {
+    pub(super) async fn get_metrics(
+        &self,
+        req: &Request,
+    ) -> Result<ResponseBuilder<String>, ResponseBuilder<Error>> {
+        let node_manager = self.node_manager.read().await;
+        let metrics = node_manager.metrics_snapshot();
+        Ok(Response::ok(req.id()).body(metrics.to_prometheus()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check that the text follows the exposition format: each sample belongs to the last
+    /// declared metric family, and has a valid name, valid labels and a numeric value
+    fn assert_valid_exposition(text: &str) {
+        let mut family: Option<&str> = None;
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix("# ") {
+                let mut parts = comment.splitn(3, ' ');
+                let keyword = parts.next().unwrap();
+                let name = parts.next().expect("a metric name");
+                assert!(is_metric_name(name), "invalid name in {line}");
+                match keyword {
+                    "HELP" => family = Some(name),
+                    "TYPE" => {
+                        assert_eq!(family, Some(name), "TYPE without HELP: {line}");
+                        let kind = parts.next().unwrap();
+                        assert!(
+                            ["counter", "gauge", "histogram", "summary", "untyped"].contains(&kind)
+                        );
+                    }
+                    _ => panic!("unexpected comment {line}"),
+                }
+                continue;
+            }
+            let (series, value) = line.rsplit_once(' ').expect("a value");
+            assert!(value.parse::<f64>().is_ok(), "invalid value in {line}");
+            let name = match series.split_once('{') {
+                Some((name, labels)) => {
+                    let labels = labels.strip_suffix('}').expect("closed labels");
+                    assert_valid_labels(labels);
+                    name
+                }
+                None => series,
+            };
+            assert!(is_metric_name(name), "invalid name in {line}");
+            assert_eq!(family, Some(name), "sample outside of its family: {line}");
+        }
+    }
+
+    fn assert_valid_labels(mut labels: &str) {
+        while !labels.is_empty() {
+            let (name, rest) = labels.split_once("=\"").expect("a label value");
+            assert!(is_metric_name(name), "invalid label name {name}");
+            // find the closing quote, skipping the escaped characters
+            let mut chars = rest.char_indices();
+            let end = loop {
+                match chars.next().expect("a closing quote") {
+                    (_, '\\') => {
+                        let (_, escaped) = chars.next().unwrap();
+                        assert!(['\\', '"', 'n'].contains(&escaped));
+                    }
+                    (i, '"') => break i,
+                    (_, c) => assert_ne!(c, '\n'),
+                }
+            };
+            labels = &rest[end + 1..];
+            labels = labels.strip_prefix(',').unwrap_or(labels);
+        }
+    }
+
+    fn is_metric_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+
+    fn snapshot() -> MetricsSnapshot {
+        MetricsSnapshot {
+            outlets: vec![
+                OutletMetrics {
+                    alias: "db".to_string(),
+                    tcp_addr: "127.0.0.1:5432".to_string(),
+                    active_connections: 2,
+                },
+                OutletMetrics {
+                    alias: "we\"ird\\alias\n".to_string(),
+                    tcp_addr: "127.0.0.1:8080".to_string(),
+                    active_connections: 0,
+                },
+            ],
+            inlets: 1,
+            secure_channels: 3,
+            tcp_listeners: 1,
+            tcp_connections: 4,
+        }
+    }
+
+    #[test]
+    fn the_metrics_are_rendered_in_the_exposition_format() {
+        let text = snapshot().to_prometheus();
+        assert_valid_exposition(&text);
+        assert!(text.contains("ockam_node_outlets 2\n"));
+        assert!(text.contains("ockam_node_secure_channels 3\n"));
+        assert!(text.contains(
+            "ockam_outlet_active_connections{alias=\"db\",tcp_addr=\"127.0.0.1:5432\"} 2\n"
+        ));
+        assert!(text.contains(r#"{alias="we\"ird\\alias\n",tcp_addr="127.0.0.1:8080"} 0"#));
+
+        // the rendering is stable
+        assert_eq!(text, snapshot().to_prometheus());
+        assert_valid_exposition(&MetricsSnapshot::default().to_prometheus());
+    }
+}
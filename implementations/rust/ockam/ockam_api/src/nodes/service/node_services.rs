@@ -42,7 +42,7 @@ use crate::uppercase::Uppercase;
 use crate::DefaultAddress;
 use crate::{actions, resources};
 
-use super::NodeManagerWorker;
+use super::{NodeManagerWorker, OutletSpec};
 
 impl NodeManager {
     pub(super) async fn start_identity_service_impl(
@@ -152,6 +152,7 @@ impl NodeManager {
                 None,
             )
             .await?;
+        let ac = self.with_decision_log(ac, &resource, &actions::HANDLE_MESSAGE);
 
         WorkerBuilder::new(Echoer)
             .with_address(addr.clone())
@@ -258,6 +259,7 @@ impl NodeManager {
         let abac = self
             .access_control(&resource, &action, Some(project.as_str()), None)
             .await?;
+        let abac = self.with_decision_log(abac, &resource, &action);
 
         let direct = crate::authenticator::direct::DirectAuthenticator::new(
             project.clone(),
@@ -599,10 +601,11 @@ impl NodeManagerWorker {
             .create_outlet_impl(
                 context,
                 request.id(),
-                body.request().bootstrap_server_addr.clone(),
-                KAFKA_OUTLET_BOOTSTRAP_ADDRESS.to_string(),
-                Some(KAFKA_OUTLET_BOOTSTRAP_ADDRESS.to_string()),
-                false,
+                OutletSpec::new(
+                    body.request().bootstrap_server_addr.clone(),
+                    KAFKA_OUTLET_BOOTSTRAP_ADDRESS,
+                )
+                .with_alias(KAFKA_OUTLET_BOOTSTRAP_ADDRESS),
             )
             .await
         {
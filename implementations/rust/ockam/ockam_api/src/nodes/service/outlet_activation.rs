@@ -0,0 +1,211 @@
+use ockam::identity::{IdentitiesRepository, IdentityIdentifier};
+use ockam_abac::expr::str;
+use ockam_abac::{
+    AbacAccessControl, AccessDecision, Action, DenyReason, Env, PolicyStorage, Resource,
+};
+use ockam_core::compat::sync::Arc;
+use ockam_core::{async_trait, Decodable, IncomingAccessControl, RelayMessage, Result};
+use ockam_transport_tcp::PortalMessage;
+
+use crate::decision_log::{record_denial, DenialReason};
+
+/// Access control of an outlet created with an activation policy.
+///
+/// The policy of the resource and action is evaluated against the attributes of the node
+/// itself, available as `subject.<name>`, each time a connection is opened. While it doesn't
+/// hold, the outlet rejects the new connections as if it was disabled. The data of the existing
+/// connections is left to the inner access control.
+pub(crate) struct OutletActivationAccessControl {
+    inner: Arc<dyn IncomingAccessControl>,
+    policies: Arc<dyn PolicyStorage>,
+    identities_repository: Arc<dyn IdentitiesRepository>,
+    node_identifier: IdentityIdentifier,
+    resource: Resource,
+    action: Action,
+    outlet_alias: String,
+}
+
+impl OutletActivationAccessControl {
+    pub(crate) fn new(
+        inner: Arc<dyn IncomingAccessControl>,
+        policies: Arc<dyn PolicyStorage>,
+        identities_repository: Arc<dyn IdentitiesRepository>,
+        node_identifier: IdentityIdentifier,
+        resource: Resource,
+        action: Action,
+        outlet_alias: &str,
+    ) -> Self {
+        Self {
+            inner,
+            policies,
+            identities_repository,
+            node_identifier,
+            resource,
+            action,
+            outlet_alias: outlet_alias.to_string(),
+        }
+    }
+
+    /// Evaluate the activation policy for the node
+    async fn decide(&self) -> Result<AccessDecision> {
+        let expr = match self
+            .policies
+            .get_effective_policy(&self.resource, &self.action)
+            .await?
+        {
+            Some(expr) => expr,
+            None => return Ok(AccessDecision::deny(DenyReason::NoPolicy)),
+        };
+        let env = Env::new()
+            .with("resource.id", str(self.resource.as_str()))
+            .with("action.id", str(self.action.as_str()));
+        AbacAccessControl::new(self.identities_repository.clone(), expr, env)
+            .decide_for_identity(self.node_identifier.clone())
+            .await
+    }
+}
+
+impl core::fmt::Debug for OutletActivationAccessControl {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("OutletActivationAccessControl")
+            .field("resource", &self.resource)
+            .field("action", &self.action)
+            .field("outlet_alias", &self.outlet_alias)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl IncomingAccessControl for OutletActivationAccessControl {
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        let payload = &relay_msg.local_message().transport().payload;
+        if let Ok(PortalMessage::Ping) = PortalMessage::decode(payload) {
+            let decision = self.decide().await?;
+            if let Some(reason) = decision.reason {
+                debug!(
+                    outlet = %self.outlet_alias,
+                    resource = %self.resource,
+                    action = %self.action,
+                    reason = %reason.code(),
+                    "the outlet is not active, connection denied: {reason}"
+                );
+                record_denial(DenialReason::Inactive);
+                return Ok(false);
+            }
+        }
+        self.inner.is_authorized(relay_msg).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use ockam::identity::{AttributesEntry, Timestamp};
+    use ockam_abac::expr::{eq, ident};
+    use ockam_core::api::Request;
+    use ockam_core::route;
+    use ockam_node::Context;
+    use ockam_transport_tcp::TcpInletOptions;
+
+    use crate::nodes::models::portal::CreateOutlet;
+    use crate::nodes::service::OutletSpec;
+    use crate::nodes::NodeManager;
+    use crate::util::test_utils::{reachable, start_echo_server, start_manager_for_tests};
+    use crate::{actions, resources};
+
+    use super::*;
+
+    /// Set the `serving` attribute of the node identity
+    async fn set_serving(node_manager: &NodeManager, serving: &str) -> Result<()> {
+        let entry = AttributesEntry::new(
+            BTreeMap::from([("serving".to_string(), serving.as_bytes().to_vec())]),
+            Timestamp::now().unwrap(),
+            None,
+            None,
+        );
+        node_manager
+            .attributes_writer()
+            .put_attributes(&node_manager.identifier(), entry)
+            .await
+    }
+
+    #[ockam_macros::test(timeout = 10_000)]
+    async fn the_outlet_is_reachable_while_its_activation_policy_holds(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let target = start_echo_server().await;
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+        // the inlet of the test connects to the outlet without a secure channel
+        node_manager.enable_credential_checks = false;
+        node_manager
+            .policies
+            .set_policy(
+                &resources::NODE,
+                &actions::SERVE,
+                &eq([ident("subject.serving"), str("true")]),
+            )
+            .await?;
+        node_manager
+            .create_outlet_from_spec(
+                context,
+                OutletSpec::new(target.to_string(), "outlet")
+                    .with_alias("echo")
+                    .with_activation(resources::NODE, actions::SERVE),
+            )
+            .await?;
+        let (inlet_address, _) = handler
+            .tcp
+            .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+            .await?;
+
+        // the node doesn't have the attribute yet
+        assert!(!reachable(inlet_address).await);
+
+        set_serving(&node_manager, "true").await?;
+        assert!(reachable(inlet_address).await);
+
+        set_serving(&node_manager, "false").await?;
+        assert!(!reachable(inlet_address).await);
+
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 10_000)]
+    async fn the_activation_policy_is_given_when_creating_the_outlet_with_the_api(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let target = start_echo_server().await;
+        let handler = start_manager_for_tests(context).await?;
+        {
+            let mut node_manager = handler.node_manager.write().await;
+            node_manager.enable_credential_checks = false;
+            node_manager
+                .policies
+                .set_policy(
+                    &resources::NODE,
+                    &actions::SERVE,
+                    &eq([ident("subject.serving"), str("true")]),
+                )
+                .await?;
+        }
+        let request = CreateOutlet::new(target.to_string(), "outlet", Some("echo".into()), false)
+            .with_activation(resources::NODE.as_str(), actions::SERVE.as_str());
+        let mut worker = handler.node_manager_worker.clone();
+        assert!(worker
+            .create_outlet(context, Request::post("/node/outlet").header(), request)
+            .await
+            .is_ok());
+        let (inlet_address, _) = handler
+            .tcp
+            .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+            .await?;
+        assert!(!reachable(inlet_address).await);
+
+        set_serving(&*handler.node_manager.read().await, "true").await?;
+        assert!(reachable(inlet_address).await);
+        context.stop().await
+    }
+}
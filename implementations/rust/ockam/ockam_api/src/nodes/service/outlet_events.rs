@@ -0,0 +1,370 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use ockam::{Context, Result};
+use ockam_core::api::{Error, Request, Response, ResponseBuilder};
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Address, AllowAll, DenyAll, Route};
+use ockam_node::compat::asynchronous::RwLock;
+use ockam_node::tokio;
+use ockam_node::tokio::sync::watch;
+use ockam_node::tokio::time::{sleep_until, Instant as TokioInstant};
+
+use crate::nodes::models::portal::{
+    OutletEvent, OutletEventKind, OutletEventList, OutletEventsSubscription, OutletStatus,
+};
+use crate::nodes::registry::OutletSubscriptionInfo;
+use crate::nodes::service::{random_alias, Alias};
+
+use super::{NodeManager, NodeManagerWorker};
+
+/// Subscriptions which are not polled for this duration are removed,
+/// in case their client stopped without unsubscribing
+const SUBSCRIPTION_EXPIRATION: Duration = Duration::from_secs(60);
+
+/// Maximum duration a request for the outlet events waits for a change before
+/// returning no events, so that the client can check that the node is still there
+const OUTLET_EVENTS_MAX_WAIT: Duration = Duration::from_secs(10);
+
+impl NodeManager {
+    /// Subscribe to the changes of the outlets of the node and return the subscription identifier.
+    /// Only the changes happening after the subscription are reported
+    pub fn subscribe_to_outlet_events(&mut self) -> String {
+        self.registry
+            .outlet_subscriptions
+            .retain(|_, s| s.last_poll.elapsed() < SUBSCRIPTION_EXPIRATION);
+        let id = random_alias();
+        // the receivers are created first so that no change is missed
+        let outlets_changed = self.registry.outlets_changed.subscribe();
+        let connections_changed = self.tcp_transport.registry().watch_outlet_connections();
+        let outlets = self.outlets_by_alias();
+        self.registry.outlet_subscriptions.insert(
+            id.clone(),
+            OutletSubscriptionInfo::new(outlets, outlets_changed, connections_changed),
+        );
+        id
+    }
+
+    /// Return the changes of the outlets since the subscription was created or,
+    /// if the subscription was already polled, since the previous call
+    pub fn outlet_events(&mut self, id: &str) -> Result<Vec<OutletEvent>> {
+        // the changes are marked as seen before the outlets are listed so that
+        // a change happening after the listing is seen by the next call
+        let subscription = self.outlet_subscription(id)?;
+        subscription.outlets_changed.borrow_and_update();
+        subscription.connections_changed.borrow_and_update();
+        let outlets = self.outlets_by_alias();
+        let subscription = self.outlet_subscription(id)?;
+        let events = diff_outlets(&subscription.outlets, &outlets);
+        subscription.outlets = outlets;
+        subscription.last_poll = Instant::now();
+        Ok(events)
+    }
+
+    /// Return receivers notified when the outlets, or their connections, change after
+    /// the last call to [`NodeManager::outlet_events`] for this subscription
+    fn outlet_changes(&mut self, id: &str) -> Result<(watch::Receiver<()>, watch::Receiver<()>)> {
+        let subscription = self.outlet_subscription(id)?;
+        Ok((
+            subscription.outlets_changed.clone(),
+            subscription.connections_changed.clone(),
+        ))
+    }
+
+    fn outlet_subscription(&mut self, id: &str) -> Result<&mut OutletSubscriptionInfo> {
+        self.registry
+            .outlet_subscriptions
+            .get_mut(id)
+            .ok_or_else(|| {
+                ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::NotFound,
+                    format!("The outlets subscription {id} doesn't exist"),
+                )
+            })
+    }
+
+    /// Remove a subscription. Return false if it doesn't exist
+    pub fn unsubscribe_from_outlet_events(&mut self, id: &str) -> bool {
+        self.registry.outlet_subscriptions.remove(id).is_some()
+    }
+
+    fn outlets_by_alias(&self) -> BTreeMap<Alias, OutletStatus> {
+        self.list_outlets()
+            .list
+            .into_iter()
+            .map(|outlet| (outlet.alias.clone(), outlet))
+            .collect()
+    }
+}
+
+/// Return the next events of a subscription. When there is none yet, wait for the outlets
+/// or their connections to change, for at most `max_wait`.
+async fn wait_for_outlet_events(
+    node_manager: &RwLock<NodeManager>,
+    id: &str,
+    max_wait: Duration,
+) -> Result<Vec<OutletEvent>> {
+    let deadline = TokioInstant::now() + max_wait;
+    loop {
+        let (mut outlets_changed, mut connections_changed) = {
+            let mut node_manager = node_manager.write().await;
+            let events = node_manager.outlet_events(id)?;
+            if !events.is_empty() {
+                return Ok(events);
+            }
+            node_manager.outlet_changes(id)?
+        };
+        // the senders are only dropped with the node manager, which is stopping
+        tokio::select! {
+            res = outlets_changed.changed() => if res.is_err() { return Ok(vec![]) },
+            res = connections_changed.changed() => if res.is_err() { return Ok(vec![]) },
+            _ = sleep_until(deadline) => return Ok(vec![]),
+        }
+    }
+}
+
+/// Return the events turning the `previous` outlets into the `current` ones.
+/// An outlet whose alias is now used for another tcp address is reported as removed and created
+fn diff_outlets(
+    previous: &BTreeMap<Alias, OutletStatus>,
+    current: &BTreeMap<Alias, OutletStatus>,
+) -> Vec<OutletEvent> {
+    let mut events = vec![];
+    for (alias, outlet) in previous {
+        match current.get(alias) {
+            Some(c) if c.tcp_addr == outlet.tcp_addr && c.worker_addr == outlet.worker_addr => {}
+            _ => events.push(OutletEvent::new(OutletEventKind::Removed, outlet.clone())),
+        }
+    }
+    for (alias, outlet) in current {
+        match previous.get(alias) {
+            Some(p) if p.tcp_addr == outlet.tcp_addr && p.worker_addr == outlet.worker_addr => {
+                if p.active_connections != outlet.active_connections {
+                    events.push(OutletEvent::new(
+                        OutletEventKind::ConnectionsChanged,
+                        outlet.clone(),
+                    ))
+                }
+            }
+            _ => events.push(OutletEvent::new(OutletEventKind::Created, outlet.clone())),
+        }
+    }
+    events
+}
+
+impl NodeManagerWorker {
+    pub(super) async fn subscribe_to_outlet_events(
+        &self,
+        req: &Request,
+    ) -> ResponseBuilder<OutletEventsSubscription> {
+        let mut node_manager = self.node_manager.write().await;
+        let id = node_manager.subscribe_to_outlet_events();
+        Response::ok(req.id()).body(OutletEventsSubscription::new(id))
+    }
+
+    /// Reply to a request for the next events of a subscription.
+    ///
+    /// The request waits for the outlets to change so the response is sent from a separate task,
+    /// to not block the other requests to the node manager.
+    pub(super) async fn get_outlet_events(
+        &self,
+        ctx: &Context,
+        req: &Request,
+        id: &str,
+        return_route: Route,
+    ) -> Result<()> {
+        let ctx = ctx
+            .new_detached(
+                Address::random_tagged("NodeManagerWorker.outlet_events"),
+                DenyAll,
+                AllowAll,
+            )
+            .await?;
+        let node_manager: Arc<RwLock<NodeManager>> = self.node_manager.clone();
+        let req_id = req.id();
+        let path = req.path().to_string();
+        let id = id.to_string();
+        tokio::spawn(async move {
+            let response =
+                match wait_for_outlet_events(&node_manager, &id, OUTLET_EVENTS_MAX_WAIT).await {
+                    Ok(events) => Response::ok(req_id)
+                        .body(OutletEventList::new(events))
+                        .to_vec(),
+                    Err(e) => {
+                        let err_body = Error::new(&path).with_message(e.to_string());
+                        Response::not_found(req_id).body(err_body).to_vec()
+                    }
+                };
+            let sent = match response {
+                Ok(response) => ctx.send(return_route, response).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = sent {
+                warn!(%id, %e, "failed to send the outlet events");
+            }
+        });
+        Ok(())
+    }
+
+    pub(super) async fn unsubscribe_from_outlet_events(
+        &self,
+        req: &Request,
+        id: &str,
+    ) -> Result<ResponseBuilder, ResponseBuilder<Error>> {
+        let mut node_manager = self.node_manager.write().await;
+        if node_manager.unsubscribe_from_outlet_events(id) {
+            Ok(Response::ok(req.id()))
+        } else {
+            let err_body = Error::new(req.path())
+                .with_message(format!("The outlets subscription {id} doesn't exist"));
+            Err(Response::not_found(req.id()).body(err_body))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ockam_core::errcode::Kind;
+    use ockam_node::Context;
+
+    use crate::util::test_utils::start_manager_for_tests;
+
+    use super::*;
+
+    fn outlets(list: &[(&str, &str, usize)]) -> BTreeMap<Alias, OutletStatus> {
+        list.iter()
+            .map(|(alias, tcp_addr, connections)| {
+                let outlet = OutletStatus::new(*tcp_addr, *alias, *alias, None)
+                    .with_active_connections(*connections);
+                (alias.to_string(), outlet)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn outlet_events_are_the_differences_between_two_polls() {
+        let previous = outlets(&[
+            ("db", "127.0.0.1:5432", 0),
+            ("web", "127.0.0.1:80", 1),
+            ("gone", "127.0.0.1:22", 0),
+            ("moved", "127.0.0.1:6000", 0),
+        ]);
+        let current = outlets(&[
+            ("db", "127.0.0.1:5432", 0),
+            ("web", "127.0.0.1:80", 2),
+            ("moved", "127.0.0.1:6001", 0),
+            ("new", "127.0.0.1:8080", 0),
+        ]);
+        let events: Vec<(OutletEventKind, String)> = diff_outlets(&previous, &current)
+            .into_iter()
+            .map(|e| (e.kind, e.outlet.alias))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (OutletEventKind::Removed, "gone".to_string()),
+                (OutletEventKind::Removed, "moved".to_string()),
+                (OutletEventKind::Created, "moved".to_string()),
+                (OutletEventKind::Created, "new".to_string()),
+                (OutletEventKind::ConnectionsChanged, "web".to_string()),
+            ]
+        );
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn subscribe_to_outlet_events(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+        node_manager
+            .create_outlet(
+                context,
+                "127.0.0.1:6001".to_string(),
+                "outlet-1".to_string(),
+                Some("first".to_string()),
+                false,
+            )
+            .await?;
+
+        // the outlets existing before the subscription are not reported
+        let id = node_manager.subscribe_to_outlet_events();
+        assert!(node_manager.outlet_events(&id)?.is_empty());
+
+        node_manager
+            .create_outlet(
+                context,
+                "127.0.0.1:6002".to_string(),
+                "outlet-2".to_string(),
+                Some("second".to_string()),
+                false,
+            )
+            .await?;
+        node_manager.delete_outlet("first").await?;
+        let events: Vec<(OutletEventKind, String)> = node_manager
+            .outlet_events(&id)?
+            .into_iter()
+            .map(|e| (e.kind, e.outlet.alias))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (OutletEventKind::Removed, "first".to_string()),
+                (OutletEventKind::Created, "second".to_string()),
+            ]
+        );
+        assert!(node_manager.outlet_events(&id)?.is_empty());
+
+        assert!(node_manager.unsubscribe_from_outlet_events(&id));
+        let error = node_manager.outlet_events(&id).unwrap_err();
+        assert_eq!(error.code().kind, Kind::NotFound);
+        assert!(!node_manager.unsubscribe_from_outlet_events(&id));
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn wait_for_the_outlet_events(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let node_manager = handler.node_manager.clone();
+        let id = node_manager.write().await.subscribe_to_outlet_events();
+
+        // without any change, no event is returned once the maximum wait is reached
+        let events = wait_for_outlet_events(&node_manager, &id, Duration::from_millis(100)).await?;
+        assert!(events.is_empty());
+
+        // a waiting request returns as soon as an outlet is created
+        let waiting = {
+            let node_manager = node_manager.clone();
+            let id = id.clone();
+            tokio::spawn(async move {
+                wait_for_outlet_events(&node_manager, &id, Duration::from_secs(4)).await
+            })
+        };
+        node_manager
+            .write()
+            .await
+            .create_outlet(
+                context,
+                "127.0.0.1:6001".to_string(),
+                "outlet-1".to_string(),
+                Some("first".to_string()),
+                false,
+            )
+            .await?;
+        let events: Vec<(OutletEventKind, String)> = waiting
+            .await
+            .unwrap()?
+            .into_iter()
+            .map(|e| (e.kind, e.outlet.alias))
+            .collect();
+        assert_eq!(
+            events,
+            vec![(OutletEventKind::Created, "first".to_string())]
+        );
+
+        drop(node_manager);
+        drop(handler);
+        context.stop().await
+    }
+}
@@ -0,0 +1,216 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use ockam::identity::{SecureChannelTrustInfo, TrustPolicy};
+use ockam_core::compat::sync::Arc;
+use ockam_core::{async_trait, Decodable, IncomingAccessControl, RelayMessage, Result};
+use ockam_transport_tcp::PortalMessage;
+
+use crate::decision_log::{record_denial, DenialReason};
+
+use super::NodeManager;
+
+const RUNNING: u8 = 0;
+const PAUSED: u8 = 1;
+
+/// Pause state of a node.
+///
+/// The state is shared with the access controls of the portals and with the trust policies of
+/// the secure channel listeners. They check it for each message, so pausing or resuming the node
+/// doesn't stop or recreate any worker.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PauseSwitch {
+    state: Arc<AtomicU8>,
+}
+
+impl PauseSwitch {
+    fn set(&self, state: u8) {
+        self.state.store(state, Ordering::SeqCst)
+    }
+
+    fn get(&self) -> u8 {
+        self.state.load(Ordering::SeqCst)
+    }
+
+    /// Wrap the access control of a portal so that it denies new connections
+    /// while the node is paused
+    pub(crate) fn access_control(
+        &self,
+        inner: Arc<dyn IncomingAccessControl>,
+    ) -> Arc<dyn IncomingAccessControl> {
+        Arc::new(PausableAccessControl {
+            inner,
+            switch: self.clone(),
+        })
+    }
+
+    /// Return a trust policy rejecting the new secure channels while the node is paused
+    pub(crate) fn trust_policy(&self) -> PausableTrustPolicy {
+        PausableTrustPolicy {
+            switch: self.clone(),
+        }
+    }
+}
+
+impl NodeManager {
+    /// Pause the node: the portals stop accepting new connections and the secure channel
+    /// listeners stop accepting new secure channels.
+    ///
+    /// If `quiesce` is true, the existing portal connections are closed as well: their TCP
+    /// streams are shut down on both sides of the portals, so that the TCP clients see a clean
+    /// disconnection rather than a connection where the data is lost.
+    /// The portals and the listeners are kept as they are, so that [`NodeManager::resume`]
+    /// restores the node exactly.
+    pub fn pause(&self, quiesce: bool) {
+        info!(%quiesce, node = %self.node_name, "Pausing the node");
+        self.pause_switch.set(PAUSED);
+        if quiesce {
+            self.tcp_transport.close_portal_connections();
+        }
+    }
+
+    /// Resume a paused node. Nothing happens if the node is not paused
+    pub fn resume(&self) {
+        info!(node = %self.node_name, "Resuming the node");
+        self.pause_switch.set(RUNNING)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause_switch.get() != RUNNING
+    }
+}
+
+/// Access control of a portal, which refuses the messages opening new connections
+/// while the node is paused
+#[derive(Debug)]
+struct PausableAccessControl {
+    inner: Arc<dyn IncomingAccessControl>,
+    switch: PauseSwitch,
+}
+
+#[async_trait]
+impl IncomingAccessControl for PausableAccessControl {
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        if self.switch.get() != RUNNING {
+            let payload = &relay_msg.local_message().transport().payload;
+            let opens_connection = matches!(
+                PortalMessage::decode(payload),
+                Ok(PortalMessage::Ping) | Ok(PortalMessage::Pong)
+            );
+            if opens_connection {
+                debug!(destination = %relay_msg.destination(), "the node is paused, message denied");
+                record_denial(DenialReason::Paused);
+                return Ok(false);
+            }
+        }
+        self.inner.is_authorized(relay_msg).await
+    }
+}
+
+/// Trust policy of a secure channel listener, rejecting all the peers while the node is paused
+pub(crate) struct PausableTrustPolicy {
+    switch: PauseSwitch,
+}
+
+#[async_trait]
+impl TrustPolicy for PausableTrustPolicy {
+    async fn check(&self, _trust_info: &SecureChannelTrustInfo) -> Result<bool> {
+        if self.switch.get() != RUNNING {
+            record_denial(DenialReason::Paused);
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use ockam::compat::tokio;
+    use ockam::compat::tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use ockam::compat::tokio::net::TcpStream;
+    use ockam_core::route;
+    use ockam_node::Context;
+    use ockam_transport_tcp::TcpInletOptions;
+
+    use crate::util::test_utils::{start_echo_server, start_manager_for_tests};
+
+    /// Return true if the message is echoed back in time
+    async fn round_trip(stream: &mut TcpStream, message: &[u8]) -> bool {
+        stream.write_all(message).await.unwrap();
+        let mut buffer = vec![0u8; message.len()];
+        match tokio::time::timeout(Duration::from_millis(500), stream.read_exact(&mut buffer)).await
+        {
+            Ok(Ok(_)) => buffer == message,
+            _ => false,
+        }
+    }
+
+    #[ockam_macros::test(timeout = 10_000)]
+    async fn pause_and_resume_around_a_live_outlet(context: &mut Context) -> ockam::Result<()> {
+        let target = start_echo_server().await;
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+        // the inlet of the test connects to the outlet without a secure channel
+        node_manager.enable_credential_checks = false;
+        node_manager
+            .create_outlet(
+                context,
+                target.to_string(),
+                "outlet".to_string(),
+                Some("echo".to_string()),
+                false,
+            )
+            .await?;
+        let (inlet_address, _) = handler
+            .tcp
+            .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+            .await?;
+        let outlets = |node_manager: &super::NodeManager| -> Vec<(String, String, String)> {
+            node_manager
+                .list_outlets()
+                .list
+                .into_iter()
+                .map(|o| (o.alias, o.tcp_addr, o.worker_addr))
+                .collect()
+        };
+        let outlets_before_pause = outlets(&node_manager);
+
+        let mut live = TcpStream::connect(inlet_address).await.unwrap();
+        assert!(round_trip(&mut live, b"before").await);
+
+        // the existing connection keeps working, but no new connection is accepted
+        node_manager.pause(false);
+        assert!(node_manager.is_paused());
+        assert!(round_trip(&mut live, b"paused").await);
+        let mut new = TcpStream::connect(inlet_address).await.unwrap();
+        assert!(!round_trip(&mut new, b"refused").await);
+
+        node_manager.resume();
+        assert!(!node_manager.is_paused());
+        assert_eq!(outlets(&node_manager), outlets_before_pause);
+        let mut new = TcpStream::connect(inlet_address).await.unwrap();
+        assert!(round_trip(&mut new, b"resumed").await);
+        assert!(round_trip(&mut live, b"resumed").await);
+
+        // the existing connections are closed when the node is quiesced
+        let mut other = TcpStream::connect(inlet_address).await.unwrap();
+        assert!(round_trip(&mut other, b"other").await);
+        node_manager.pause(true);
+        for stream in [&mut live, &mut other] {
+            let mut buffer = [0u8; 8];
+            let read = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buffer))
+                .await
+                .unwrap();
+            // the inlet closes the connection: end of stream rather than an error
+            assert_eq!(read.unwrap(), 0);
+        }
+        node_manager.resume();
+        let mut new = TcpStream::connect(inlet_address).await.unwrap();
+        assert!(round_trip(&mut new, b"after").await);
+        assert_eq!(outlets(&node_manager), outlets_before_pause);
+
+        drop(node_manager);
+        context.stop().await
+    }
+}
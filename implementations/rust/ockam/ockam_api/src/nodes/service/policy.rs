@@ -1,11 +1,15 @@
 use either::Either;
 use minicbor::Decoder;
 
-use ockam_abac::{Action, Resource};
+use ockam_abac::expr::str;
+use ockam_abac::{trace, AbacAccessControl, Action, Env, Expr, PolicySnapshot, Resource};
 use ockam_core::api::{Error, Request, Response, ResponseBuilder};
 use ockam_core::Result;
 
-use crate::nodes::models::policy::{Expression, Policy, PolicyList};
+use crate::nodes::models::policy::{
+    ActionPolicy, EffectivePolicy, Expression, Policy, PolicyEvaluation, PolicyEvaluationRequest,
+    PolicyList, PolicyMap, PolicyMapRequest, PolicyOrigin, ResourcePolicies,
+};
 
 use super::NodeManager;
 
@@ -20,7 +24,9 @@ impl NodeManager {
         let p: Policy = dec.decode()?;
         let r = Resource::new(resource);
         let a = Action::new(action);
-        self.policies.set_policy(&r, &a, p.expression()).await?;
+        self.policies
+            .set_policy_with_meta(&r, &a, p.expression(), p.description())
+            .await?;
         Ok(Response::ok(req.id()))
     }
 
@@ -33,7 +39,9 @@ impl NodeManager {
         let r = Resource::new(resource);
         let a = Action::new(action);
         if let Some(e) = self.policies.get_policy(&r, &a).await? {
-            Ok(Either::Right(Response::ok(req.id()).body(Policy::new(e))))
+            let description = self.policies.get_policy_meta(&r, &a).await?;
+            let policy = Policy::new(e).with_description(description);
+            Ok(Either::Right(Response::ok(req.id()).body(policy)))
         } else {
             let mut err = Error::new(req.path()).with_message("policy not found");
             if let Some(m) = req.method() {
@@ -54,6 +62,47 @@ impl NodeManager {
         Ok(Response::ok(req.id()).body(PolicyList::new(p)))
     }
 
+    /// Return all the policies of the node grouped by resource, optionally only for the
+    /// resources starting with a prefix.
+    ///
+    /// The policies are read from a single snapshot of the policies storage
+    pub async fn policy_map(&self, resource_prefix: Option<&str>) -> Result<PolicyMap> {
+        let snapshot = PolicySnapshot::take(self.policies.as_ref()).await?;
+        // the snapshot is ordered by resource then action
+        let mut map: Vec<(Resource, Vec<ActionPolicy>)> = Vec::new();
+        for (r, a, expr, meta) in snapshot.iter() {
+            if let Some(prefix) = resource_prefix {
+                if !r.as_str().starts_with(prefix) {
+                    continue;
+                }
+            }
+            let policy = ActionPolicy::new(a.clone(), expr.clone(), meta.map(String::from));
+            match map.last_mut() {
+                Some((last, policies)) if last == r => policies.push(policy),
+                _ => map.push((r.clone(), vec![policy])),
+            }
+        }
+        Ok(PolicyMap::new(
+            map.into_iter()
+                .map(|(r, policies)| ResourcePolicies::new(r, policies))
+                .collect(),
+        ))
+    }
+
+    pub(super) async fn get_policy_map(
+        &self,
+        req: &Request,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder<PolicyMap>, ResponseBuilder<Error>> {
+        let body: PolicyMapRequest = if req.has_body() {
+            dec.decode()?
+        } else {
+            PolicyMapRequest::default()
+        };
+        let map = self.policy_map(body.resource_prefix()).await?;
+        Ok(Response::ok(req.id()).body(map))
+    }
+
     pub(super) async fn del_policy(
         &self,
         req: &Request,
@@ -65,4 +114,188 @@ impl NodeManager {
         self.policies.del_policy(&r, &a).await?;
         Ok(Response::ok(req.id()))
     }
+
+    /// Return the policy applied to a resource and action: its own policy, the policy inherited
+    /// from its closest ancestor or, if there is none, the default policy denying the access
+    pub async fn effective_policy(&self, r: &Resource, a: &Action) -> Result<EffectivePolicy> {
+        Ok(match self.policies.find_effective_policy(r, a).await? {
+            Some((defined_on, expr)) if &defined_on == r => {
+                EffectivePolicy::new(PolicyOrigin::Exact, Some(defined_on), expr)
+            }
+            Some((defined_on, expr)) => {
+                EffectivePolicy::new(PolicyOrigin::Parent, Some(defined_on), expr)
+            }
+            None => EffectivePolicy::new(PolicyOrigin::Default, None, Expr::Bool(false)),
+        })
+    }
+
+    pub(super) async fn get_effective_policy(
+        &self,
+        req: &Request,
+        resource: &str,
+        action: &str,
+    ) -> Result<ResponseBuilder<EffectivePolicy>, ResponseBuilder<Error>> {
+        let r = Resource::new(resource);
+        let a = Action::new(action);
+        let policy = self.effective_policy(&r, &a).await?;
+        Ok(Response::ok(req.id()).body(policy))
+    }
+
+    /// Evaluate the policy of a resource and action for an identity, using the attributes
+    /// known for that identity. No message is sent and no state is modified.
+    pub(super) async fn evaluate_policy(
+        &self,
+        req: &Request,
+        resource: &str,
+        action: &str,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder<PolicyEvaluation>, ResponseBuilder<Error>> {
+        let body: PolicyEvaluationRequest = dec.decode()?;
+        let r = Resource::new(resource);
+        let a = Action::new(action);
+        let expr = match self.policies.get_effective_policy(&r, &a).await? {
+            Some(expr) => expr,
+            None => {
+                let evaluation =
+                    PolicyEvaluation::new(false, Some("no policy found".into()), None, vec![]);
+                return Ok(Response::ok(req.id()).body(evaluation));
+            }
+        };
+
+        // Populate the environment like the access control of the node services
+        let mut env = Env::new();
+        env.put("resource.id", str(r.as_str()));
+        env.put("action.id", str(a.as_str()));
+        if let Ok(tc) = self.trust_context() {
+            env.put("resource.project_id", str(tc.id()));
+            env.put("resource.trust_context_id", str(tc.id()));
+        }
+
+        let abac = AbacAccessControl::new(self.identities_repository(), expr.clone(), env);
+        let decision = abac.decide_for_identity(body.identifier().clone()).await?;
+        let steps = if body.trace() {
+            let env = abac.environment_for_identity(body.identifier()).await?;
+            trace(&expr, &env).into_iter().map(|s| s.into()).collect()
+        } else {
+            vec![]
+        };
+        let evaluation = PolicyEvaluation::new(
+            decision.allowed,
+            decision.reason.map(|r| r.to_string()),
+            Some(expr),
+            steps,
+        );
+        Ok(Response::ok(req.id()).body(evaluation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ockam_node::Context;
+
+    use crate::util::test_utils::start_manager_for_tests;
+
+    use super::*;
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn effective_policy_origin(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let node_manager = handler.node_manager.read().await;
+        let action = Action::new("handle_message");
+        let parent = Resource::new("parent");
+        let child = Resource::new("child");
+        node_manager
+            .policies
+            .set_resource_parent(&child, &parent)
+            .await?;
+
+        let policy = node_manager.effective_policy(&child, &action).await?;
+        assert_eq!(policy.origin(), PolicyOrigin::Default);
+        assert_eq!(policy.defined_on(), None);
+        assert_eq!(policy.expression().to_string(), "false");
+
+        node_manager
+            .policies
+            .set_policy(&parent, &action, &Expr::Bool(true))
+            .await?;
+        let policy = node_manager.effective_policy(&child, &action).await?;
+        assert_eq!(policy.origin(), PolicyOrigin::Parent);
+        assert_eq!(policy.defined_on(), Some(&parent));
+
+        let expr = ockam_abac::parse(r#"(= subject.component "web")"#)?.unwrap();
+        node_manager
+            .policies
+            .set_policy(&child, &action, &expr)
+            .await?;
+        let policy = node_manager.effective_policy(&child, &action).await?;
+        assert_eq!(policy.origin(), PolicyOrigin::Exact);
+        assert_eq!(policy.defined_on(), Some(&child));
+        assert_eq!(policy.expression().to_string(), expr.to_string());
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn policies_are_grouped_by_resource(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let node_manager = handler.node_manager.read().await;
+        let initial = node_manager.policy_map(None).await?.resources().len();
+        let expr = ockam_abac::parse(r#"(= subject.component "web")"#)?.unwrap();
+        for (r, a) in [
+            ("web/outlet", "handle_message"),
+            ("web/inlet", "handle_message"),
+            ("web/outlet", "connect"),
+            ("db/outlet", "handle_message"),
+        ] {
+            node_manager
+                .policies
+                .set_policy_with_meta(
+                    &Resource::new(r),
+                    &Action::new(a),
+                    &expr,
+                    Some("the web components"),
+                )
+                .await?;
+        }
+
+        let map = node_manager.policy_map(None).await?;
+        assert_eq!(map.resources().len(), initial + 3);
+
+        let map = node_manager.policy_map(Some("web/")).await?;
+        let grouped: Vec<(&str, Vec<&str>)> = map
+            .resources()
+            .iter()
+            .map(|r| {
+                let actions = r.policies().iter().map(|p| p.action().as_str()).collect();
+                (r.resource().as_str(), actions)
+            })
+            .collect();
+        assert_eq!(
+            grouped,
+            vec![
+                ("web/inlet", vec!["handle_message"]),
+                ("web/outlet", vec!["connect", "handle_message"]),
+            ]
+        );
+        let policy = &map.resources()[1].policies()[0];
+        assert_eq!(policy.expression().to_string(), expr.to_string());
+        assert_eq!(policy.description(), Some("the web components"));
+
+        // the map is serialized in the same order
+        let json = serde_json::to_value(&map).unwrap();
+        assert_eq!(json["resources"][1]["resource"], "web/outlet");
+        assert_eq!(json["resources"][1]["policies"][0]["action"], "connect");
+        assert_eq!(
+            json["resources"][1]["policies"][0]["expression"],
+            expr.to_string()
+        );
+
+        assert!(node_manager
+            .policy_map(Some("unknown"))
+            .await?
+            .resources()
+            .is_empty());
+        drop(node_manager);
+        context.stop().await
+    }
 }
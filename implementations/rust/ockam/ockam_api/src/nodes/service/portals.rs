@@ -1,3 +1,6 @@
+use std::collections::BTreeMap;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::num::NonZeroUsize;
 use std::sync::Mutex;
 use std::time::Duration;
 
@@ -6,7 +9,7 @@ use minicbor::Decoder;
 use ockam::compat::tokio::time::timeout;
 use ockam::identity::IdentityIdentifier;
 use ockam::{Address, AsyncTryClone, Result};
-use ockam_abac::Resource;
+use ockam_abac::{Action, Resource};
 use ockam_core::api::{Error, Id, Request, Response, ResponseBuilder};
 use ockam_core::compat::sync::Arc;
 use ockam_core::errcode::{Kind, Origin};
@@ -23,16 +26,174 @@ use crate::error::ApiError;
 use crate::local_multiaddr_to_route;
 use crate::nodes::connection::{Connection, ConnectionInstance};
 use crate::nodes::models::portal::{
-    CreateInlet, CreateOutlet, InletList, InletStatus, OutletList, OutletStatus,
+    CreateInlet, CreateOutlet, InletList, InletStatus, ListOutlets, OutletList, OutletStatus,
+    PortRange,
 };
-use crate::nodes::registry::{InletInfo, OutletInfo};
+use crate::nodes::registry::{InletInfo, OutletInfo, WorkerKind};
+use crate::nodes::service::credential_required::CredentialRequiredAccessControl;
+use crate::nodes::service::outlet_activation::OutletActivationAccessControl;
 use crate::nodes::service::random_alias;
 use crate::session::sessions::{Replacer, Session, MAX_CONNECT_TIME, MAX_RECOVERY_TIME};
 use crate::{actions, resources, DefaultAddress};
 
 use super::{NodeManager, NodeManagerWorker};
 
+/// Parameters of an outlet created with [`NodeManager::create_outlets`]
+#[derive(Debug, Clone)]
+pub struct OutletSpec {
+    pub tcp_addr: String,
+    pub worker_addr: String,
+    pub alias: Option<String>,
+    pub reachable_from_default_secure_channel: bool,
+    /// Name of the trust context used by the access control of the outlet,
+    /// the default trust context is used if not set
+    pub trust_context_name: Option<String>,
+    pub idle_timeout: Option<Duration>,
+    /// Only accept the peers which presented a valid credential issued by the
+    /// authority of the trust context of the outlet
+    pub require_credential: bool,
+    /// Resource and action of a policy evaluated against the attributes of the node itself
+    /// when a connection is opened. The outlet rejects the connections while it is false
+    pub activation: Option<(Resource, Action)>,
+    /// Maximum number of connections of the outlet open at the same time, at least 1.
+    /// When it is reached, the new connections wait for an open connection to be closed
+    pub max_connections: Option<usize>,
+    /// Tags describing the outlet, see [`NodeManager::find_outlets_by_tags`]
+    pub tags: BTreeMap<String, String>,
+}
+
+impl OutletSpec {
+    pub fn new(tcp_addr: impl Into<String>, worker_addr: impl Into<String>) -> Self {
+        Self {
+            tcp_addr: tcp_addr.into(),
+            worker_addr: worker_addr.into(),
+            alias: None,
+            reachable_from_default_secure_channel: false,
+            trust_context_name: None,
+            idle_timeout: None,
+            require_credential: false,
+            activation: None,
+            max_connections: None,
+            tags: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_alias(mut self, alias: impl Into<String>) -> Self {
+        self.alias = Some(alias.into());
+        self
+    }
+
+    pub fn reachable_from_default_secure_channel(mut self, reachable: bool) -> Self {
+        self.reachable_from_default_secure_channel = reachable;
+        self
+    }
+
+    pub fn with_trust_context_name(mut self, trust_context_name: Option<String>) -> Self {
+        self.trust_context_name = trust_context_name;
+        self
+    }
+
+    pub fn with_idle_timeout(mut self, idle_timeout: Option<Duration>) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    pub fn with_require_credential(mut self, require_credential: bool) -> Self {
+        self.require_credential = require_credential;
+        self
+    }
+
+    /// Only accept connections while the policy of the resource and action, for example
+    /// [`resources::NODE`] and [`actions::SERVE`], evaluates to true for the node
+    pub fn with_activation(mut self, resource: Resource, action: Action) -> Self {
+        self.activation = Some((resource, action));
+        self
+    }
+
+    pub fn with_max_connections(mut self, max_connections: Option<usize>) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+
+    pub fn with_tags(mut self, tags: BTreeMap<String, String>) -> Self {
+        self.tags = tags;
+        self
+    }
+}
+
+/// Options of an inlet created with [`NodeManager::create_inlet`]
+#[derive(Debug, Clone)]
+pub struct InletOptions {
+    /// Route applied before the outlet address, not used to monitor the connection
+    pub prefix_route: Route,
+    /// Route applied after the outlet address, not used to monitor the connection
+    pub suffix_route: Route,
+    /// Identity authorized for the secure channels to the outlet, for non-project addresses
+    pub authorized: Option<IdentityIdentifier>,
+    /// Maximum duration to wait for the outlet to be available, 5 seconds if not set
+    pub wait_for_outlet_duration: Option<Duration>,
+}
+
+impl Default for InletOptions {
+    fn default() -> Self {
+        Self {
+            prefix_route: route![],
+            suffix_route: route![],
+            authorized: None,
+            wait_for_outlet_duration: None,
+        }
+    }
+}
+
+impl InletOptions {
+    pub fn with_authorized(mut self, authorized: Option<IdentityIdentifier>) -> Self {
+        self.authorized = authorized;
+        self
+    }
+
+    pub fn with_wait_for_outlet_duration(mut self, duration: Duration) -> Self {
+        self.wait_for_outlet_duration = Some(duration);
+        self
+    }
+}
+
 impl NodeManager {
+    /// Create several outlets and return the result of each creation, in the same order.
+    ///
+    /// A failure doesn't prevent the creation of the next outlets. The outlets using an alias
+    /// which appears several times in the batch are not created, since it is not possible to
+    /// tell which one is expected
+    pub async fn create_outlets(
+        &mut self,
+        ctx: &Context,
+        specs: Vec<OutletSpec>,
+    ) -> Vec<Result<OutletStatus>> {
+        let mut results = vec![];
+        for spec in specs.iter() {
+            let duplicated = spec.alias.as_ref().map_or(false, |alias| {
+                specs
+                    .iter()
+                    .filter(|s| s.alias.as_ref() == Some(alias))
+                    .count()
+                    > 1
+            });
+            let result = if duplicated {
+                Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::Conflict,
+                    format!(
+                        "The alias '{}' is used by several outlets of the batch",
+                        spec.alias.as_deref().unwrap_or_default()
+                    ),
+                ))
+            } else {
+                self.create_outlet_from_spec(ctx, spec.clone()).await
+            };
+            results.push(result);
+        }
+        results
+    }
+
     pub async fn create_outlet(
         &mut self,
         ctx: &Context,
@@ -40,8 +201,82 @@ impl NodeManager {
         worker_addr: String,
         alias: Option<String>,
         reachable_from_default_secure_channel: bool,
+    ) -> Result<OutletStatus> {
+        self.create_outlet_with_trust_context(
+            ctx,
+            tcp_addr,
+            worker_addr,
+            alias,
+            reachable_from_default_secure_channel,
+            None,
+            None,
+        )
+        .await
+    }
+
+    /// Create an outlet whose access control uses a named trust context of the node.
+    /// The default trust context is used if no name is given.
+    /// If an idle timeout is given, each connection of the outlet is closed once no data
+    /// went through it, in either direction, for that duration
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_outlet_with_trust_context(
+        &mut self,
+        ctx: &Context,
+        tcp_addr: String,
+        worker_addr: String,
+        alias: Option<String>,
+        reachable_from_default_secure_channel: bool,
+        trust_context_name: Option<&str>,
+        idle_timeout: Option<Duration>,
+    ) -> Result<OutletStatus> {
+        let spec = OutletSpec {
+            tcp_addr,
+            worker_addr,
+            alias,
+            reachable_from_default_secure_channel,
+            trust_context_name: trust_context_name.map(|n| n.to_string()),
+            idle_timeout,
+            require_credential: false,
+            activation: None,
+            max_connections: None,
+            tags: BTreeMap::new(),
+        };
+        self.create_outlet_from_spec(ctx, spec).await
+    }
+
+    /// Create an outlet from its parameters.
+    ///
+    /// The TCP address can be a port range, written `host:first-last`, see [`PortRange`].
+    ///
+    /// If a credential is required, the messages of the peers which didn't present a valid
+    /// credential issued by the authority of the trust context of the outlet are denied,
+    /// before the policy of the outlet is checked.
+    ///
+    /// If an activation policy is given, it is evaluated for the node each time a connection
+    /// is opened, and the connection is rejected if the policy doesn't hold.
+    ///
+    /// If a maximum number of connections is given, it must be at least 1. Once it is reached,
+    /// a new connection waits for an open connection of the outlet to be closed, and is closed
+    /// if no connection is closed before a timeout
+    pub async fn create_outlet_from_spec(
+        &mut self,
+        ctx: &Context,
+        spec: OutletSpec,
     ) -> Result<OutletStatus> {
         info!("Handling request to create outlet portal");
+        let OutletSpec {
+            tcp_addr,
+            worker_addr,
+            alias,
+            reachable_from_default_secure_channel,
+            trust_context_name,
+            idle_timeout,
+            require_credential,
+            activation,
+            max_connections,
+            tags,
+        } = spec;
+        let trust_context_name = trust_context_name.as_deref();
         let resource = alias
             .as_deref()
             .map(Resource::new)
@@ -49,6 +284,17 @@ impl NodeManager {
 
         let alias = alias.unwrap_or_else(random_alias);
 
+        let max_connections_option = match max_connections.map(NonZeroUsize::new) {
+            Some(Some(max_connections)) => Some(max_connections),
+            Some(None) => {
+                let message = format!(
+                    "The maximum number of connections of the TCP outlet '{alias}' must be at least 1"
+                );
+                return Err(ockam_core::Error::new(Origin::Node, Kind::Invalid, message));
+            }
+            None => None,
+        };
+
         // Check that there is no entry in the registry with the same alias
         if self.registry.outlets.contains_key(&alias) {
             let message = format!("A TCP outlet with alias '{alias}' already exists");
@@ -74,11 +320,31 @@ impl NodeManager {
             ));
         }
 
+        // Check that the outlet doesn't forward the connections back to the node itself
+        let port_range = PortRange::parse_tcp_addr(&tcp_addr);
+        if let Some(listener) =
+            self.tcp_listener_addresses()
+                .into_iter()
+                .find(|listener| match port_range {
+                    Some((host, range)) => range
+                        .ports()
+                        .any(|port| targets_listener(&format!("{host}:{port}"), listener)),
+                    None => targets_listener(&tcp_addr, listener),
+                })
+        {
+            let message = format!(
+                "The TCP outlet target '{tcp_addr}' is the TCP listener of the node ({listener}), \
+                 this would create a loop"
+            );
+            return Err(ockam_core::Error::new(Origin::Node, Kind::Invalid, message));
+        }
+
         let worker_addr = Address::from_string(&worker_addr);
 
-        let check_credential = self.enable_credential_checks;
+        let check_credential =
+            self.enable_credential_checks || trust_context_name.is_some() || require_credential;
         let trust_context_id = if check_credential {
-            Some(self.trust_context()?.id())
+            Some(self.named_trust_context(trust_context_name)?.id())
         } else {
             None
         };
@@ -86,8 +352,52 @@ impl NodeManager {
         let access_control = self
             .access_control(&resource, &actions::HANDLE_MESSAGE, trust_context_id, None)
             .await?;
+        let access_control: Arc<dyn IncomingAccessControl> = if require_credential {
+            let authorities = self
+                .named_trust_context(trust_context_name)?
+                .authorities()
+                .await
+                .map_err(|e| {
+                    ockam_core::Error::new(
+                        Origin::Node,
+                        Kind::Invalid,
+                        format!(
+                            "The TCP outlet '{alias}' requires a credential but its trust context has no authority: {e}"
+                        ),
+                    )
+                })?;
+            Arc::new(CredentialRequiredAccessControl::new(
+                access_control,
+                self.identities_repository(),
+                authorities.iter().map(|a| a.identifier()).collect(),
+                &alias,
+            ))
+        } else {
+            access_control
+        };
+        let access_control: Arc<dyn IncomingAccessControl> = match activation {
+            Some((resource, action)) => Arc::new(OutletActivationAccessControl::new(
+                access_control,
+                self.policies.clone(),
+                self.identities_repository(),
+                self.identifier(),
+                resource,
+                action,
+                &alias,
+            )),
+            None => access_control,
+        };
+        let access_control = self.portal_access_control(access_control, &resource);
 
         let options = TcpOutletOptions::new().with_incoming_access_control(access_control);
+        let options = match idle_timeout {
+            Some(idle_timeout) => options.with_idle_timeout(idle_timeout),
+            None => options,
+        };
+        let options = match max_connections_option {
+            Some(max_connections) => options.with_max_connections(max_connections),
+            None => options,
+        };
         let options = if !check_credential {
             options.as_consumer(&self.api_transport_flow_control_id)
         } else {
@@ -108,20 +418,40 @@ impl NodeManager {
             options
         };
 
-        let res = self
-            .tcp_transport
-            .create_outlet(worker_addr.clone(), tcp_addr.clone(), options)
-            .await;
+        let res = match port_range {
+            Some((host, range)) => {
+                self.tcp_transport
+                    .create_outlet_port_range(worker_addr.clone(), host, range.ports(), options)
+                    .await
+            }
+            None => {
+                self.tcp_transport
+                    .create_outlet(worker_addr.clone(), tcp_addr.clone(), options)
+                    .await
+            }
+        };
 
         Ok(match res {
             Ok(_) => {
+                self.registry
+                    .started_workers
+                    .insert(worker_addr.clone(), WorkerKind::Outlet);
                 // TODO: Use better way to store outlets?
                 self.registry.outlets.insert(
                     alias.clone(),
-                    OutletInfo::new(&tcp_addr, Some(&worker_addr)),
+                    OutletInfo::new(&tcp_addr, Some(&worker_addr))
+                        .with_trust_context_name(trust_context_name)
+                        .with_idle_timeout(idle_timeout)
+                        .with_require_credential(require_credential)
+                        .with_max_connections(max_connections)
+                        .with_tags(tags.clone()),
                 );
+                self.registry.outlets_changed.notify();
 
                 OutletStatus::new(tcp_addr, worker_addr.to_string(), alias, None)
+                    .with_idle_timeout(idle_timeout)
+                    .with_max_connections(max_connections)
+                    .with_tags(tags)
             }
             Err(e) => {
                 warn!(at = %tcp_addr, err = %e, "Failed to create TCP outlet");
@@ -134,48 +464,169 @@ impl NodeManager {
             }
         })
     }
-}
 
-impl NodeManagerWorker {
-    pub(super) async fn get_inlets(&self, req: &Request) -> ResponseBuilder<InletList> {
-        let registry = &self.node_manager.read().await.registry.inlets;
-        Response::ok(req.id()).body(InletList::new(
-            registry
-                .iter()
-                .map(|(alias, info)| {
-                    InletStatus::new(
-                        &info.bind_addr,
-                        info.worker_addr.to_string(),
-                        alias,
-                        None,
-                        info.outlet_route.to_string(),
-                    )
-                })
-                .collect(),
-        ))
+    /// Return the access control of a portal: its inner access control is only used while the
+    /// node is running and for the identities which are not revoked.
+    /// The decisions are recorded if the node has a decision log
+    fn portal_access_control(
+        &self,
+        inner: Arc<dyn IncomingAccessControl>,
+        resource: &Resource,
+    ) -> Arc<dyn IncomingAccessControl> {
+        let access_control = self
+            .pause_switch
+            .access_control(self.revocations.access_control(inner));
+        self.with_decision_log(access_control, resource, &actions::HANDLE_MESSAGE)
+    }
+
+    /// Change the alias of an existing outlet.
+    ///
+    /// The outlet worker is left untouched so that the connections going through the outlet
+    /// are not interrupted. Its access control keeps using the policies of the resource it
+    /// was created with, so these policies are copied to the resource of the new alias:
+    /// the outlet is then authorized in the same way once it is recreated with its new alias.
+    pub async fn rename_outlet(
+        &mut self,
+        old_alias: &str,
+        new_alias: &str,
+    ) -> Result<OutletStatus> {
+        if old_alias != new_alias && self.registry.outlets.contains_key(new_alias) {
+            let message = format!("A TCP outlet with alias '{new_alias}' already exists");
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::AlreadyExists,
+                message,
+            ));
+        }
+
+        let outlet_info = self.registry.outlets.remove(old_alias).ok_or_else(|| {
+            let message = format!("Outlet with alias {old_alias} not found");
+            ockam_core::Error::new(Origin::Node, Kind::NotFound, message)
+        })?;
+        if let Err(e) = self.copy_policies(old_alias, new_alias).await {
+            self.registry
+                .outlets
+                .insert(old_alias.to_string(), outlet_info);
+            return Err(e);
+        }
+        info!(%old_alias, %new_alias, "Renamed outlet");
+
+        let status = self.outlet_status(new_alias, &outlet_info);
+        self.registry
+            .outlets
+            .insert(new_alias.to_string(), outlet_info);
+        self.registry.outlets_changed.notify();
+        Ok(status)
     }
 
-    pub(super) async fn get_outlets(&self, req: &Request) -> ResponseBuilder<OutletList> {
-        Response::ok(req.id()).body(self.list_outlets().await)
+    /// Copy the policies of the resource named `from` to the resource named `to`
+    async fn copy_policies(&self, from: &str, to: &str) -> Result<()> {
+        if from == to {
+            return Ok(());
+        }
+        let (from, to) = (Resource::new(from), Resource::new(to));
+        for (action, expr) in self.policies.policies(&from).await? {
+            let meta = self.policies.get_policy_meta(&from, &action).await?;
+            self.policies
+                .set_policy_with_meta(&to, &action, &expr, meta.as_deref())
+                .await?;
+        }
+        Ok(())
     }
 
-    pub(super) async fn create_inlet(
-        &mut self,
-        req: &Request,
-        dec: &mut Decoder<'_>,
+    /// Stop an outlet and remove it from the registry.
+    /// Return `None` if there is no outlet with this alias
+    pub async fn delete_outlet(&mut self, alias: &str) -> Result<Option<OutletStatus>> {
+        info!(%alias, "Handling request to delete outlet portal");
+        let outlet_to_delete = match self.registry.outlets.remove(alias) {
+            Some(outlet_to_delete) => outlet_to_delete,
+            None => return Ok(None),
+        };
+        self.registry.outlets_changed.notify();
+        debug!(%alias, "Successfully removed outlet from node registry");
+        self.tcp_transport
+            .stop_outlet(outlet_to_delete.worker_addr.clone())
+            .await?;
+        self.registry
+            .started_workers
+            .remove(&outlet_to_delete.worker_addr);
+        debug!(%alias, "Successfully stopped outlet");
+        Ok(Some(OutletStatus::new(
+            outlet_to_delete.tcp_addr,
+            outlet_to_delete.worker_addr.to_string(),
+            alias,
+            None,
+        )))
+    }
+
+    /// Stop an inlet and remove it from the registry.
+    /// Return `None` if there is no inlet with this alias.
+    ///
+    /// Like for an outlet, the listener of the inlet is stopped so that no new connection is
+    /// accepted, while the connections already established are left open until they are closed
+    /// by their peers. The inlet is not recreated anymore when its connection to the outlet is lost
+    pub async fn delete_inlet(&mut self, alias: &str) -> Result<Option<InletStatus>> {
+        info!(%alias, "Handling request to delete inlet portal");
+        let inlet_to_delete = match self.registry.inlets.remove(alias) {
+            Some(inlet_to_delete) => inlet_to_delete,
+            None => return Ok(None),
+        };
+        debug!(%alias, "Successfully removed inlet from node registry");
+        if let Some(session_key) = &inlet_to_delete.session_key {
+            self.remove_session(session_key);
+        }
+        self.tcp_transport
+            .stop_inlet(inlet_to_delete.worker_addr.clone())
+            .await?;
+        self.registry
+            .started_workers
+            .remove(&inlet_to_delete.worker_addr);
+        debug!(%alias, "Successfully stopped inlet");
+        Ok(Some(InletStatus::new(
+            inlet_to_delete.bind_addr,
+            inlet_to_delete.worker_addr.to_string(),
+            alias,
+            None,
+            inlet_to_delete.outlet_route.to_string(),
+        )))
+    }
+
+    /// Create an inlet listening on `listen_addr` and forwarding its connections to the outlet
+    /// at `outlet_addr`.
+    ///
+    /// The alias must not be used by another inlet of the node. When the connection to the outlet
+    /// is lost, the inlet is recreated with a new connection until it is deleted with
+    /// [`NodeManager::delete_inlet`].
+    ///
+    /// Like [`NodeManager::connect`], it takes the locked node manager, since the lock is released
+    /// while the outlet is connected to, and is used again by the session recreating the inlet
+    pub async fn create_inlet(
+        manager: Arc<RwLock<NodeManager>>,
         ctx: &Context,
-    ) -> Result<ResponseBuilder<InletStatus>, ResponseBuilder<Error>> {
-        let rid = req.id();
-        let req: CreateInlet = dec.decode()?;
-        self.create_inlet_impl(rid, req, ctx).await
+        alias: &str,
+        listen_addr: SocketAddr,
+        outlet_addr: MultiAddr,
+        options: InletOptions,
+    ) -> Result<InletStatus> {
+        let mut req = CreateInlet::to_node(
+            listen_addr,
+            outlet_addr,
+            options.prefix_route,
+            options.suffix_route,
+            options.authorized,
+        );
+        req.set_alias(alias.to_string());
+        if let Some(duration) = options.wait_for_outlet_duration {
+            req.set_wait_ms(duration.as_millis() as u64);
+        }
+        Self::create_inlet_from_request(manager, ctx, &req).await
     }
 
-    pub(super) async fn create_inlet_impl(
-        &mut self,
-        req_id: Id,
-        req: CreateInlet<'_>,
+    async fn create_inlet_from_request(
+        manager: Arc<RwLock<NodeManager>>,
         ctx: &Context,
-    ) -> Result<ResponseBuilder<InletStatus>, ResponseBuilder<Error>> {
+        req: &CreateInlet<'_>,
+    ) -> Result<InletStatus> {
         info!("Handling request to create inlet portal");
 
         let listen_addr = req.listen_addr().to_string();
@@ -193,13 +644,16 @@ impl NodeManagerWorker {
         }
 
         {
-            let registry = &self.node_manager.read().await.registry.inlets;
+            let registry = &manager.read().await.registry.inlets;
 
             // Check that there is no entry in the registry with the same alias
             if registry.contains_key(&alias) {
-                let err_body = Error::new_without_path()
-                    .with_message(format!("A TCP inlet with alias '{alias}' already exists"));
-                return Err(Response::bad_request(req_id).body(err_body));
+                let message = format!("A TCP inlet with alias '{alias}' already exists");
+                return Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::AlreadyExists,
+                    message,
+                ));
             }
 
             // Check that there is no entry in the registry with the same TCP bind address
@@ -207,10 +661,13 @@ impl NodeManagerWorker {
                 .values()
                 .any(|inlet| inlet.bind_addr == listen_addr)
             {
-                let err_body = Error::new_without_path().with_message(format!(
-                    "A TCP inlet with bind tcp address '{listen_addr}' already exists",
+                let message =
+                    format!("A TCP inlet with bind tcp address '{listen_addr}' already exists");
+                return Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::AlreadyExists,
+                    message,
                 ));
-                return Err(Response::bad_request(req_id).body(err_body));
             }
         }
 
@@ -229,14 +686,17 @@ impl NodeManagerWorker {
                 .with_authorized_identity(req.authorized())
                 .with_timeout(duration);
 
-            NodeManager::connect(self.node_manager.clone(), connection).await?
+            NodeManager::connect(manager.clone(), connection).await?
         };
 
         let outlet_route = match local_multiaddr_to_route(&connection_instance.normalized_addr) {
             Some(route) => route,
             None => {
-                let err_body = Error::new_without_path().with_message("Invalid outlet route.");
-                return Err(Response::bad_request(req_id).body(err_body));
+                return Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::Invalid,
+                    "Invalid outlet route.",
+                ))
             }
         };
 
@@ -248,15 +708,15 @@ impl NodeManagerWorker {
 
         let resource = req.alias().map(Resource::new).unwrap_or(resources::INLET);
 
-        let mut node_manager = self.node_manager.write().await;
-        let projects = node_manager.cli_state.projects.list().map_err(|e| {
-            Response::bad_request(req_id)
-                .body(Error::new_without_path().with_message(e.to_string()))
-        })?;
-        let projects = ProjectLookup::from_state(projects).await.map_err(|e| {
-            Response::bad_request(req_id)
-                .body(Error::new_without_path().with_message(e.to_string()))
-        })?;
+        let mut node_manager = manager.write().await;
+        let projects = node_manager
+            .cli_state
+            .projects
+            .list()
+            .map_err(|e| ockam_core::Error::new(Origin::Node, Kind::Invalid, e.to_string()))?;
+        let projects = ProjectLookup::from_state(projects)
+            .await
+            .map_err(|e| ockam_core::Error::new(Origin::Node, Kind::Invalid, e.to_string()))?;
         let check_credential = node_manager.enable_credential_checks;
         let project_id = if check_credential {
             let pid = req
@@ -271,72 +731,163 @@ impl NodeManagerWorker {
                 })
                 .or_else(|| Some(node_manager.trust_context().ok()?.id()));
             if pid.is_none() {
-                let err_body = Error::new_without_path()
-                    .with_message("Credential check requires a project or trust context");
-                return Err(Response::bad_request(req_id).body(err_body));
+                return Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::Invalid,
+                    "Credential check requires a project or trust context",
+                ));
             }
             pid
         } else {
             None
         };
 
-        let access_control = node_manager
-            .access_control(&resource, &actions::HANDLE_MESSAGE, project_id, None)
-            .await?;
+        let access_control = node_manager.portal_access_control(
+            node_manager
+                .access_control(&resource, &actions::HANDLE_MESSAGE, project_id, None)
+                .await?,
+            &resource,
+        );
 
         let options = TcpInletOptions::new().with_incoming_access_control(access_control.clone());
 
-        let res = node_manager
+        let (socket_address, worker_addr) = node_manager
             .tcp_transport
             .create_inlet(listen_addr.clone(), outlet_route.clone(), options)
-            .await;
+            .await
+            .map_err(|e| {
+                warn!(to = %req.outlet_addr(), err = %e, "Failed to create TCP inlet");
+                ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::Invalid,
+                    format!("Failed to create TCP inlet: {}", e),
+                )
+            })?;
 
-        Ok(match res {
-            Ok((socket_address, worker_addr)) => {
-                //when using 0 port, the chosen port will be populated
-                //in the returned socket address
-                let listen_addr = socket_address.to_string();
+        //when using 0 port, the chosen port will be populated
+        //in the returned socket address
+        let listen_addr = socket_address.to_string();
 
-                // TODO: Use better way to store inlets?
-                node_manager.registry.inlets.insert(
-                    alias.clone(),
-                    InletInfo::new(&listen_addr, Some(&worker_addr), &outlet_route),
-                );
-                if !connection_instance.normalized_addr.is_empty() {
-                    let mut session = Session::new(connection_instance.transport_route.clone());
-
-                    let ctx = Arc::new(ctx.async_try_clone().await?);
-                    let repl = replacer(
-                        self.node_manager.clone(),
-                        connection_instance,
-                        worker_addr.clone(),
-                        listen_addr.clone(),
-                        req.outlet_addr().clone(),
-                        req.prefix_route().clone(),
-                        req.suffix_route().clone(),
-                        req.authorized(),
-                        access_control.clone(),
-                        ctx,
-                    );
-                    session.set_replacer(repl);
-                    node_manager.add_session(session);
-                }
+        node_manager
+            .registry
+            .started_workers
+            .insert(worker_addr.clone(), WorkerKind::Inlet);
+        let session_key = if !connection_instance.normalized_addr.is_empty() {
+            let mut session = Session::new(connection_instance.transport_route.clone());
+
+            let ctx = Arc::new(ctx.async_try_clone().await?);
+            let repl = replacer(
+                manager.clone(),
+                connection_instance,
+                alias.clone(),
+                worker_addr.clone(),
+                listen_addr.clone(),
+                req.outlet_addr().clone(),
+                req.prefix_route().clone(),
+                req.suffix_route().clone(),
+                req.authorized(),
+                access_control.clone(),
+                ctx,
+            );
+            session.set_replacer(repl);
+            Some(node_manager.add_session(session))
+        } else {
+            None
+        };
+        // TODO: Use better way to store inlets?
+        node_manager.registry.inlets.insert(
+            alias.clone(),
+            InletInfo::new(
+                &listen_addr,
+                Some(&worker_addr),
+                &outlet_route,
+                req.outlet_addr(),
+            )
+            .with_session_key(session_key),
+        );
 
-                Response::ok(req_id).body(InletStatus::new(
-                    listen_addr,
-                    worker_addr.to_string(),
-                    alias,
-                    None,
-                    outlet_route.to_string(),
-                ))
-            }
+        Ok(InletStatus::new(
+            listen_addr,
+            worker_addr.to_string(),
+            alias,
+            None,
+            outlet_route.to_string(),
+        ))
+    }
+}
+
+/// Return true if connecting to `target` reaches a TCP listener bound to `listener`.
+/// Only IP addresses and `localhost` are checked, other host names are not resolved
+pub(super) fn targets_listener(target: &str, listener: &SocketAddr) -> bool {
+    let target = match target.parse::<SocketAddr>() {
+        Ok(target) => target,
+        Err(_) => match target.rsplit_once(':') {
+            Some(("localhost", port)) => match port.parse::<u16>() {
+                Ok(port) => SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port),
+                Err(_) => return false,
+            },
+            _ => return false,
+        },
+    };
+    if target.port() != listener.port() {
+        return false;
+    }
+    if listener.ip().is_unspecified() {
+        // a listener bound to all the interfaces is reachable on the loopback interface
+        target.ip().is_loopback() || target.ip().is_unspecified()
+    } else {
+        target.ip() == listener.ip() || target.ip().is_unspecified()
+    }
+}
+
+impl NodeManagerWorker {
+    pub(super) async fn get_inlets(&self, req: &Request) -> ResponseBuilder<InletList> {
+        Response::ok(req.id()).body(self.node_manager.read().await.list_inlets())
+    }
+
+    /// List the outlets, only the ones having the tags of the request body if there is one
+    pub(super) async fn get_outlets(
+        &self,
+        req: &Request,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder<OutletList>> {
+        if !req.has_body() {
+            return Ok(Response::ok(req.id()).body(self.list_outlets().await));
+        }
+        let list_outlets: ListOutlets = dec.decode()?;
+        let node_manager = self.node_manager.read().await;
+        let outlets = node_manager.find_outlets_by_tags(&list_outlets.tags());
+        Ok(Response::ok(req.id()).body(OutletList::new(outlets)))
+    }
+
+    pub(super) async fn create_inlet(
+        &mut self,
+        req: &Request,
+        dec: &mut Decoder<'_>,
+        ctx: &Context,
+    ) -> Result<ResponseBuilder<InletStatus>, ResponseBuilder<Error>> {
+        let rid = req.id();
+        let req: CreateInlet = dec.decode()?;
+        self.create_inlet_impl(rid, req, ctx).await
+    }
+
+    pub async fn create_inlet_impl(
+        &mut self,
+        req_id: Id,
+        req: CreateInlet<'_>,
+        ctx: &Context,
+    ) -> Result<ResponseBuilder<InletStatus>, ResponseBuilder<Error>> {
+        match NodeManager::create_inlet_from_request(self.node_manager.clone(), ctx, &req).await {
+            Ok(status) => Ok(Response::ok(req_id).body(status)),
             Err(e) => {
-                warn!(to = %req.outlet_addr(), err = %e, "Failed to create TCP inlet");
-                let err_body = Error::new_without_path()
-                    .with_message(format!("Failed to create TCP inlet: {}", e));
-                return Err(Response::bad_request(req_id).body(err_body));
+                let response = match e.code().kind {
+                    Kind::AlreadyExists | Kind::Invalid => Response::bad_request(req_id),
+                    _ => Response::internal_error(req_id),
+                };
+                let err_body = Error::new_without_path().with_message(e.to_string());
+                Err(response.body(err_body))
             }
-        })
+        }
     }
 
     pub(super) async fn delete_inlet<'a>(
@@ -345,37 +896,20 @@ impl NodeManagerWorker {
         alias: &'a str,
     ) -> Result<ResponseBuilder<InletStatus>, ResponseBuilder<Error>> {
         let mut node_manager = self.node_manager.write().await;
-
-        info!(%alias, "Handling request to delete inlet portal");
-        if let Some(inlet_to_delete) = node_manager.registry.inlets.remove(alias) {
-            debug!(%alias, "Successfully removed inlet from node registry");
-            match node_manager
-                .tcp_transport
-                .stop_inlet(inlet_to_delete.worker_addr.clone())
-                .await
-            {
-                Ok(_) => {
-                    debug!(%alias, "Successfully stopped inlet");
-                    Ok(Response::ok(req.id()).body(InletStatus::new(
-                        inlet_to_delete.bind_addr,
-                        inlet_to_delete.worker_addr.to_string(),
-                        alias,
-                        None,
-                        inlet_to_delete.outlet_route.to_string(),
-                    )))
-                }
-                Err(e) => {
-                    error!(%alias, "Failed to remove inlet from node registry");
-                    let err_body = Error::new(req.path())
-                        .with_message(format!("Failed to remove inlet with alias {alias}. {}", e));
-                    Err(Response::internal_error(req.id()).body(err_body))
-                }
+        match node_manager.delete_inlet(alias).await {
+            Ok(Some(status)) => Ok(Response::ok(req.id()).body(status)),
+            Ok(None) => {
+                error!(%alias, "Inlet not found in the node registry");
+                let err_body = Error::new(req.path())
+                    .with_message(format!("Inlet with alias {alias} not found"));
+                Err(Response::not_found(req.id()).body(err_body))
+            }
+            Err(e) => {
+                error!(%alias, "Failed to remove inlet from node registry");
+                let err_body = Error::new(req.path())
+                    .with_message(format!("Failed to remove inlet with alias {alias}. {}", e));
+                Err(Response::internal_error(req.id()).body(err_body))
             }
-        } else {
-            error!(%alias, "Inlet not found in the node registry");
-            let err_body =
-                Error::new(req.path()).with_message(format!("Inlet with alias {alias} not found"));
-            Err(Response::not_found(req.id()).body(err_body))
         }
     }
 
@@ -415,40 +949,43 @@ impl NodeManagerWorker {
             worker_addr,
             alias,
             reachable_from_default_secure_channel,
+            trust_context_name,
+            idle_timeout,
+            require_credential,
+            max_connections,
+            tags,
+            activation,
             ..
         } = create_outlet;
 
-        self.create_outlet_impl(
-            ctx,
-            req.id(),
+        let spec = OutletSpec {
             tcp_addr,
             worker_addr,
             alias,
             reachable_from_default_secure_channel,
-        )
-        .await
+            trust_context_name,
+            idle_timeout,
+            require_credential,
+            activation: activation.map(|a| {
+                (
+                    Resource::new(a.resource.as_str()),
+                    Action::new(a.action.as_str()),
+                )
+            }),
+            max_connections,
+            tags: tags.unwrap_or_default(),
+        };
+        self.create_outlet_impl(ctx, req.id(), spec).await
     }
 
     pub async fn create_outlet_impl(
         &self,
         ctx: &Context,
         req_id: Id,
-        tcp_addr: String,
-        worker_addr: String,
-        alias: Option<String>,
-        reachable_from_default_secure_channel: bool,
+        spec: OutletSpec,
     ) -> Result<ResponseBuilder<OutletStatus>, ResponseBuilder<Error>> {
         let mut node_manager = self.get().write().await;
-        match node_manager
-            .create_outlet(
-                ctx,
-                tcp_addr,
-                worker_addr,
-                alias,
-                reachable_from_default_secure_channel,
-            )
-            .await
-        {
+        match node_manager.create_outlet_from_spec(ctx, spec).await {
             Ok(outlet_status) => Ok(Response::ok(req_id).body(outlet_status)),
             Err(e) => {
                 let err_body = Error::new_without_path().with_message(format!("{e:?}"));
@@ -463,36 +1000,20 @@ impl NodeManagerWorker {
         alias: &'a str,
     ) -> Result<ResponseBuilder<OutletStatus>, ResponseBuilder<Error>> {
         let mut node_manager = self.node_manager.write().await;
-
-        info!(%alias, "Handling request to delete outlet portal");
-        if let Some(outlet_to_delete) = node_manager.registry.outlets.remove(alias) {
-            debug!(%alias, "Successfully removed outlet from node registry");
-            match node_manager
-                .tcp_transport
-                .stop_outlet(outlet_to_delete.worker_addr.clone())
-                .await
-            {
-                Ok(_) => {
-                    debug!(%alias, "Successfully stopped outlet");
-                    Ok(Response::ok(req.id()).body(OutletStatus::new(
-                        outlet_to_delete.tcp_addr,
-                        outlet_to_delete.worker_addr.to_string(),
-                        alias,
-                        None,
-                    )))
-                }
-                Err(e) => {
-                    error!(%alias, "Failed to remove outlet from node registry");
-                    let err_body = Error::new(req.path())
-                        .with_message(format!("Failed to remove outlet with alias {alias}. {}", e));
-                    Err(Response::internal_error(req.id()).body(err_body))
-                }
+        match node_manager.delete_outlet(alias).await {
+            Ok(Some(status)) => Ok(Response::ok(req.id()).body(status)),
+            Ok(None) => {
+                error!(%alias, "Outlet not found in the node registry");
+                let err_body = Error::new(req.path())
+                    .with_message(format!("Outlet with alias {alias} not found"));
+                Err(Response::not_found(req.id()).body(err_body))
+            }
+            Err(e) => {
+                error!(%alias, "Failed to remove outlet from node registry");
+                let err_body = Error::new(req.path())
+                    .with_message(format!("Failed to remove outlet with alias {alias}. {}", e));
+                Err(Response::internal_error(req.id()).body(err_body))
             }
-        } else {
-            error!(%alias, "Outlet not found in the node registry");
-            let err_body =
-                Error::new(req.path()).with_message(format!("Outlet with alias {alias} not found"));
-            Err(Response::not_found(req.id()).body(err_body))
         }
     }
 
@@ -504,14 +1025,8 @@ impl NodeManagerWorker {
         let node_manager = self.node_manager.read().await;
 
         info!(%alias, "Handling request to show outlet portal");
-        if let Some(outlet_to_show) = node_manager.registry.outlets.get(alias) {
-            debug!(%alias, "Outlet not found in node registry");
-            Ok(Response::ok(req.id()).body(OutletStatus::new(
-                outlet_to_show.tcp_addr.to_string(),
-                outlet_to_show.worker_addr.to_string(),
-                alias,
-                None,
-            )))
+        if let Some(outlet_to_show) = node_manager.get_outlet(alias) {
+            Ok(Response::ok(req.id()).body(outlet_to_show))
         } else {
             error!(%alias, "Outlet not found in the node registry");
             let err_body =
@@ -534,6 +1049,7 @@ impl NodeManagerWorker {
 fn replacer(
     manager: Arc<RwLock<NodeManager>>,
     connection_instance: ConnectionInstance,
+    alias: String,
     inlet_address: Address,
     bind: String,
     addr: MultiAddr,
@@ -548,6 +1064,7 @@ fn replacer(
 
     Box::new(move |previous_addr| {
         let addr = addr.clone();
+        let alias = alias.clone();
         let auth = auth.clone();
         let bind = bind.clone();
         let node_manager_arc = manager.clone();
@@ -611,7 +1128,7 @@ fn replacer(
                     suffix_route
                 ];
 
-                let node_manager = node_manager_arc.write().await;
+                let mut node_manager = node_manager_arc.write().await;
 
                 let options = TcpInletOptions::new().with_incoming_access_control(access);
 
@@ -621,7 +1138,17 @@ fn replacer(
                     .create_inlet(bind, normalized_route, options)
                     .await?
                     .1;
-                *inlet_address_arc.lock().unwrap() = new_inlet_address;
+                *inlet_address_arc.lock().unwrap() = new_inlet_address.clone();
+
+                // The registry refers to the new inlet, so that it is the one stopped on deletion
+                let registry = &mut node_manager.registry;
+                registry.started_workers.remove(&inlet_address);
+                registry
+                    .started_workers
+                    .insert(new_inlet_address.clone(), WorkerKind::Inlet);
+                if let Some(inlet) = registry.inlets.get_mut(&alias) {
+                    inlet.worker_addr = new_inlet_address;
+                }
 
                 Ok(new_connection_instance.transport_route.clone())
             };
@@ -641,3 +1168,759 @@ fn replacer(
         })
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use ockam_core::errcode::Kind;
+    use ockam_node::Context;
+
+    use std::net::SocketAddr;
+    use std::time::Duration;
+
+    use ockam::compat::tokio;
+    use ockam_transport_tcp::TcpListenerOptions;
+
+    use std::str::FromStr;
+
+    use ockam_multiaddr::MultiAddr;
+
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    use minicbor::Decoder;
+    use ockam::identity::{
+        AttributesEntry, IdentityAttributesWriter, IdentityIdentifier,
+        IdentitySecureChannelLocalInfo, Timestamp,
+    };
+    use ockam_abac::Resource;
+    use ockam_core::api::Request;
+    use ockam_core::compat::sync::Arc;
+    use ockam_core::{route, Address, Encodable, LocalMessage, RelayMessage, TransportMessage};
+    use ockam_transport_tcp::PortalMessage;
+
+    use crate::actions;
+    use crate::config::cli::TrustContextConfig;
+    use crate::decision_log::{DecisionEvent, DecisionLogSink};
+    use crate::nodes::models::portal::{InletList, ListOutlets, OutletList};
+    use crate::util::test_utils::start_manager_for_tests;
+
+    use super::{targets_listener, InletOptions, NodeManager, OutletSpec, PortRange};
+
+    /// Decision log keeping the decisions in memory
+    #[derive(Default)]
+    struct DecisionsInMemory {
+        events: Mutex<Vec<DecisionEvent>>,
+    }
+
+    impl DecisionLogSink for DecisionsInMemory {
+        fn on_decision(&self, event: DecisionEvent) {
+            self.events.lock().unwrap().push(event)
+        }
+    }
+
+    fn portal_message(peer: &IdentityIdentifier, message: PortalMessage) -> RelayMessage {
+        let transport =
+            TransportMessage::v1(route!["outlet"], route!["inlet"], message.encode().unwrap());
+        let local_info = IdentitySecureChannelLocalInfo::mark(vec![], peer.clone()).unwrap();
+        RelayMessage::new(
+            Address::from_string("inlet"),
+            Address::from_string("outlet"),
+            LocalMessage::new(transport, local_info),
+        )
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn the_decisions_of_a_portal_are_logged_with_their_reason(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+        let decisions = Arc::new(DecisionsInMemory::default());
+        node_manager.decision_log = Some(decisions.clone());
+
+        let member = IdentityIdentifier::from_hex(&hex::encode([1u8; 32]));
+        let stranger = IdentityIdentifier::from_hex(&hex::encode([2u8; 32]));
+        let entry = AttributesEntry::new(
+            BTreeMap::from([("project_id".to_string(), b"project".to_vec())]),
+            Timestamp::now().unwrap(),
+            None,
+            None,
+        );
+        node_manager
+            .attributes_writer()
+            .put_attributes(&member, entry)
+            .await?;
+
+        let resource = Resource::new("db");
+        let access_control = node_manager.portal_access_control(
+            node_manager
+                .access_control(&resource, &actions::HANDLE_MESSAGE, Some("project"), None)
+                .await?,
+            &resource,
+        );
+        let ping = |peer: &IdentityIdentifier| portal_message(peer, PortalMessage::Ping);
+
+        assert!(access_control.is_authorized(&ping(&member)).await?);
+        assert!(!access_control.is_authorized(&ping(&stranger)).await?);
+        node_manager.revoke(context, &member).await?;
+        assert!(!access_control.is_authorized(&ping(&member)).await?);
+        node_manager.unrevoke(&member)?;
+        node_manager.pause(false);
+        assert!(!access_control.is_authorized(&ping(&member)).await?);
+        node_manager.resume();
+
+        let events = decisions.events.lock().unwrap().clone();
+        let logged: Vec<(String, bool, Option<&str>)> = events
+            .iter()
+            .map(|e| {
+                assert_eq!(e.resource, "db");
+                assert_eq!(e.action, actions::HANDLE_MESSAGE.as_str());
+                (e.subject.clone().unwrap(), e.allowed, e.reason.as_deref())
+            })
+            .collect();
+        assert_eq!(
+            logged,
+            vec![
+                (member.to_string(), true, None),
+                (stranger.to_string(), false, Some("policy_false")),
+                (member.to_string(), false, Some("revoked")),
+                (member.to_string(), false, Some("paused")),
+            ]
+        );
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn create_outlets_reports_each_result(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+        node_manager
+            .create_outlet(
+                context,
+                "127.0.0.1:6001".to_string(),
+                "existing".to_string(),
+                Some("existing".to_string()),
+                false,
+            )
+            .await?;
+
+        let specs = vec![
+            OutletSpec::new("127.0.0.1:6002", "db").with_alias("db"),
+            OutletSpec::new("127.0.0.1:6003", "web-1").with_alias("web"),
+            // an outlet already exists for this address
+            OutletSpec::new("127.0.0.1:6001", "other").with_alias("other"),
+            OutletSpec::new("127.0.0.1:6004", "web-2").with_alias("web"),
+            OutletSpec::new("127.0.0.1:6005", "cache")
+                .with_alias("cache")
+                .with_idle_timeout(Some(Duration::from_secs(60))),
+        ];
+        let results = node_manager.create_outlets(context, specs).await;
+        let kinds: Vec<Option<Kind>> = results
+            .iter()
+            .map(|r| r.as_ref().err().map(|e| e.code().kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                None,
+                Some(Kind::Conflict),
+                Some(Kind::AlreadyExists),
+                Some(Kind::Conflict),
+                None
+            ]
+        );
+        assert_eq!(results[4].as_ref().unwrap().idle_timeout_secs, Some(60));
+
+        let OutletList { list, .. } = node_manager.list_outlets();
+        let aliases: Vec<&str> = list.iter().map(|o| o.alias.as_str()).collect();
+        assert_eq!(aliases, vec!["cache", "db", "existing"]);
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn create_outlet_with_named_trust_context(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+
+        // an unknown trust context is rejected
+        let error = node_manager
+            .create_outlet_with_trust_context(
+                context,
+                "127.0.0.1:6001".to_string(),
+                "outlet-1".to_string(),
+                Some("first".to_string()),
+                false,
+                Some("project-2"),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(error.code().kind, Kind::NotFound);
+
+        let config = TrustContextConfig::new("project-2-id".to_string(), None);
+        node_manager.add_trust_context("project-2", &config).await?;
+        assert_eq!(node_manager.trust_context_names(), vec!["project-2"]);
+        assert_eq!(
+            node_manager.named_trust_context(Some("project-2"))?.id(),
+            "project-2-id"
+        );
+
+        let status = node_manager
+            .create_outlet_with_trust_context(
+                context,
+                "127.0.0.1:6001".to_string(),
+                "outlet-1".to_string(),
+                Some("first".to_string()),
+                false,
+                Some("project-2"),
+                None,
+            )
+            .await?;
+        assert_eq!(status.alias, "first");
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn require_a_credential_to_use_an_outlet(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+
+        // the credentials are checked against the authority of the trust context
+        let config = TrustContextConfig::new("project-2-id".to_string(), None);
+        node_manager.add_trust_context("project-2", &config).await?;
+        let spec = OutletSpec::new("127.0.0.1:6001", "outlet-1")
+            .with_alias("db")
+            .with_require_credential(true);
+        let error = node_manager
+            .create_outlet_from_spec(
+                context,
+                spec.clone()
+                    .with_trust_context_name(Some("project-2".to_string())),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(error.code().kind, Kind::Invalid);
+        assert!(!node_manager.registry.outlets.contains_key("db"));
+
+        node_manager.create_outlet_from_spec(context, spec).await?;
+        assert!(node_manager.registry.outlets["db"].require_credential);
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn reject_a_maximum_of_0_connections(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+        let spec = OutletSpec::new("127.0.0.1:6001", "outlet-1").with_alias("db");
+        let error = node_manager
+            .create_outlet_from_spec(context, spec.clone().with_max_connections(Some(0)))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code().kind, Kind::Invalid);
+        assert!(!node_manager.registry.outlets.contains_key("db"));
+
+        let status = node_manager
+            .create_outlet_from_spec(context, spec.with_max_connections(Some(1)))
+            .await?;
+        assert_eq!(status.max_connections, Some(1));
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn get_an_outlet_by_alias(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+        node_manager
+            .create_outlet(
+                context,
+                "127.0.0.1:6001".to_string(),
+                "outlet-1".to_string(),
+                Some("db".to_string()),
+                false,
+            )
+            .await?;
+
+        let outlet = node_manager.get_outlet("db").unwrap();
+        assert_eq!(outlet.alias, "db");
+        assert_eq!(outlet.tcp_addr, "127.0.0.1:6001");
+        assert_eq!(outlet.worker_addr, "0#outlet-1");
+        assert_eq!(node_manager.list_outlets().list, vec![outlet]);
+
+        assert_eq!(node_manager.get_outlet("unknown"), None);
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn rename_outlet(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+        node_manager
+            .create_outlet(
+                context,
+                "127.0.0.1:6001".to_string(),
+                "outlet-1".to_string(),
+                Some("first".to_string()),
+                false,
+            )
+            .await?;
+        node_manager
+            .create_outlet(
+                context,
+                "127.0.0.1:6002".to_string(),
+                "outlet-2".to_string(),
+                Some("second".to_string()),
+                false,
+            )
+            .await?;
+
+        // a new alias colliding with an existing outlet is rejected
+        let error = node_manager
+            .rename_outlet("first", "second")
+            .await
+            .unwrap_err();
+        assert_eq!(error.code().kind, Kind::AlreadyExists);
+
+        // renaming a missing outlet is an error
+        let error = node_manager
+            .rename_outlet("missing", "other")
+            .await
+            .unwrap_err();
+        assert_eq!(error.code().kind, Kind::NotFound);
+
+        let status = node_manager.rename_outlet("first", "renamed").await?;
+        assert_eq!(status.alias, "renamed");
+        assert_eq!(status.worker_addr, "0#outlet-1");
+        assert_eq!(status.tcp_addr, "127.0.0.1:6001");
+
+        let aliases: Vec<String> = node_manager
+            .list_outlets()
+            .list
+            .into_iter()
+            .map(|o| o.alias)
+            .collect();
+        assert_eq!(aliases, vec!["renamed", "second"]);
+
+        // the outlet worker is still running under its original address
+        assert!(context
+            .list_workers()
+            .await?
+            .iter()
+            .any(|a| a.address() == "outlet-1"));
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn a_renamed_outlet_keeps_the_policies_of_its_old_alias(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+
+        let admin = IdentityIdentifier::from_hex(&hex::encode([1u8; 32]));
+        let member = IdentityIdentifier::from_hex(&hex::encode([2u8; 32]));
+        for (identifier, role) in [(&admin, "admin"), (&member, "member")] {
+            let entry = AttributesEntry::new(
+                BTreeMap::from([
+                    ("project_id".to_string(), b"project".to_vec()),
+                    ("role".to_string(), role.as_bytes().to_vec()),
+                ]),
+                Timestamp::now().unwrap(),
+                None,
+                None,
+            );
+            node_manager
+                .attributes_writer()
+                .put_attributes(identifier, entry)
+                .await?;
+        }
+        let expr = ockam_abac::parse(r#"(= subject.role "admin")"#)?.unwrap();
+        node_manager
+            .policies
+            .set_policy(&Resource::new("first"), &actions::HANDLE_MESSAGE, &expr)
+            .await?;
+        node_manager
+            .create_outlet(
+                context,
+                "127.0.0.1:6001".to_string(),
+                "outlet-1".to_string(),
+                Some("first".to_string()),
+                false,
+            )
+            .await?;
+        let ping = |peer: &IdentityIdentifier| portal_message(peer, PortalMessage::Ping);
+
+        // the running outlet is authorized with the policies of the resource it was created with
+        let running = node_manager
+            .access_control(
+                &Resource::new("first"),
+                &actions::HANDLE_MESSAGE,
+                Some("project"),
+                None,
+            )
+            .await?;
+        node_manager.rename_outlet("first", "second").await?;
+        assert!(running.is_authorized(&ping(&admin)).await?);
+        assert!(!running.is_authorized(&ping(&member)).await?);
+        assert_eq!(
+            node_manager
+                .policies
+                .get_policy(&Resource::new("second"), &actions::HANDLE_MESSAGE)
+                .await?
+                .map(|e| e.to_string()),
+            Some(expr.to_string())
+        );
+
+        // the recreated outlet is authorized in the same way with the resource of its new alias
+        node_manager.delete_outlet("second").await?;
+        node_manager
+            .create_outlet(
+                context,
+                "127.0.0.1:6001".to_string(),
+                "outlet-2".to_string(),
+                Some("second".to_string()),
+                false,
+            )
+            .await?;
+        let recreated = node_manager
+            .access_control(
+                &Resource::new("second"),
+                &actions::HANDLE_MESSAGE,
+                Some("project"),
+                None,
+            )
+            .await?;
+        assert!(recreated.is_authorized(&ping(&admin)).await?);
+        assert!(!recreated.is_authorized(&ping(&member)).await?);
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn list_outlets_is_sorted_by_alias(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+        for (port, alias) in [(6001, "charlie"), (6002, "alpha"), (6003, "bravo")] {
+            node_manager
+                .create_outlet(
+                    context,
+                    format!("127.0.0.1:{port}"),
+                    format!("outlet-{port}"),
+                    Some(alias.to_string()),
+                    false,
+                )
+                .await?;
+        }
+
+        let aliases = |list: OutletList| -> Vec<String> {
+            list.list.into_iter().map(|o| o.alias).collect::<Vec<_>>()
+        };
+        let first = aliases(node_manager.list_outlets());
+        assert_eq!(first, vec!["alpha", "bravo", "charlie"]);
+        assert_eq!(aliases(node_manager.list_outlets()), first);
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn find_outlets_by_tags(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+        let outlets: [(u16, &str, &[(&str, &str)]); 4] = [
+            (6011, "pg-prod", &[("service", "postgres"), ("env", "prod")]),
+            (6012, "pg-dev", &[("service", "postgres"), ("env", "dev")]),
+            (6013, "web-prod", &[("service", "http"), ("env", "prod")]),
+            (6014, "untagged", &[]),
+        ];
+        for (port, alias, tags) in outlets {
+            let tags = tags
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            node_manager
+                .create_outlet_from_spec(
+                    context,
+                    OutletSpec::new(format!("127.0.0.1:{port}"), alias)
+                        .with_alias(alias)
+                        .with_tags(tags),
+                )
+                .await?;
+        }
+
+        let find = |tags: &[(&str, &str)]| -> Vec<String> {
+            node_manager
+                .find_outlets_by_tags(tags)
+                .into_iter()
+                .map(|o| o.alias)
+                .collect()
+        };
+        assert_eq!(find(&[("service", "postgres")]), vec!["pg-dev", "pg-prod"]);
+        assert_eq!(find(&[("env", "prod")]), vec!["pg-prod", "web-prod"]);
+        assert_eq!(
+            find(&[("service", "postgres"), ("env", "prod")]),
+            vec!["pg-prod"]
+        );
+        assert!(find(&[("service", "postgres"), ("env", "staging")]).is_empty());
+        assert!(find(&[("region", "eu")]).is_empty());
+        assert_eq!(find(&[]).len(), 4);
+
+        // the tags are part of the status of the outlets
+        let outlet = node_manager.get_outlet("web-prod").unwrap();
+        assert_eq!(outlet.tag("service"), Some("http"));
+        assert_eq!(node_manager.get_outlet("untagged").unwrap().tags, None);
+        drop(node_manager);
+
+        // the outlets are filtered by the node when the list request has some tags
+        let tags = BTreeMap::from([("env".to_string(), "prod".to_string())]);
+        let request = Request::get("/node/outlet")
+            .body(ListOutlets::new(tags))
+            .to_vec()?;
+        let mut dec = Decoder::new(&request);
+        let header: Request = dec.decode()?;
+        let (_, outlets) = handler
+            .node_manager_worker
+            .get_outlets(&header, &mut dec)
+            .await?
+            .into_parts();
+        let aliases: Vec<String> = outlets.unwrap().list.into_iter().map(|o| o.alias).collect();
+        assert_eq!(aliases, vec!["pg-prod", "web-prod"]);
+
+        // all the outlets are listed when the request has no body
+        let request = Request::get("/node/outlet").to_vec()?;
+        let mut dec = Decoder::new(&request);
+        let header: Request = dec.decode()?;
+        let (_, outlets) = handler
+            .node_manager_worker
+            .get_outlets(&header, &mut dec)
+            .await?
+            .into_parts();
+        assert_eq!(outlets.unwrap().list.len(), 4);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn outlet_targeting_the_node_listener_is_refused(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let listener = handler
+            .tcp
+            .listen("127.0.0.1:0", TcpListenerOptions::new())
+            .await?;
+        let port = listener.socket_address().port();
+        let mut node_manager = handler.node_manager.write().await;
+        // the listener is registered once its processor is initialized
+        while node_manager.tcp_listener_addresses().is_empty() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        for target in [
+            listener.socket_string(),
+            format!("localhost:{port}"),
+            format!("0.0.0.0:{port}"),
+        ] {
+            let error = node_manager
+                .create_outlet(context, target, "outlet".to_string(), None, false)
+                .await
+                .unwrap_err();
+            assert_eq!(error.code().kind, Kind::Invalid);
+            assert!(error.to_string().contains("loop"));
+        }
+        assert!(node_manager.list_outlets().list.is_empty());
+
+        // another port of the same host can be targeted
+        let other_port = if port == u16::MAX { port - 1 } else { port + 1 };
+        node_manager
+            .create_outlet(
+                context,
+                format!("127.0.0.1:{other_port}"),
+                "outlet".to_string(),
+                None,
+                false,
+            )
+            .await?;
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[test]
+    fn targets_of_a_listener() {
+        let listener: SocketAddr = "127.0.0.1:4000".parse().unwrap();
+        assert!(targets_listener("127.0.0.1:4000", &listener));
+        assert!(targets_listener("localhost:4000", &listener));
+        assert!(!targets_listener("127.0.0.2:4000", &listener));
+        assert!(!targets_listener("127.0.0.1:4001", &listener));
+        assert!(!targets_listener("example.com:4000", &listener));
+
+        let listener: SocketAddr = "0.0.0.0:4000".parse().unwrap();
+        assert!(targets_listener("127.0.0.1:4000", &listener));
+        assert!(targets_listener("[::1]:4000", &listener));
+        assert!(!targets_listener("192.0.2.1:4000", &listener));
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn delete_outlet(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+        node_manager
+            .create_outlet(
+                context,
+                "127.0.0.1:6003".to_string(),
+                "outlet-3".to_string(),
+                Some("third".to_string()),
+                false,
+            )
+            .await?;
+
+        let status = node_manager.delete_outlet("third").await?.unwrap();
+        assert_eq!(status.alias, "third");
+        assert!(node_manager.list_outlets().list.is_empty());
+
+        // deleting a missing outlet is not an error
+        assert!(node_manager.delete_outlet("third").await?.is_none());
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 10_000)]
+    async fn create_list_and_delete_inlets(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        handler
+            .node_manager
+            .write()
+            .await
+            .create_outlet(
+                context,
+                "127.0.0.1:6010".to_string(),
+                "db".to_string(),
+                Some("db".to_string()),
+                true,
+            )
+            .await?;
+        let node_manager = handler.node_manager.clone();
+        let outlet_addr = MultiAddr::from_str("/service/db")?;
+        let any_port: SocketAddr = "127.0.0.1:0".parse().unwrap();
+
+        let web = NodeManager::create_inlet(
+            node_manager.clone(),
+            context,
+            "web",
+            any_port,
+            outlet_addr.clone(),
+            InletOptions::default(),
+        )
+        .await?;
+        assert_eq!(web.alias, "web");
+        assert_ne!(web.bind_addr, "127.0.0.1:0");
+
+        // the aliases are unique among the inlets
+        let error = NodeManager::create_inlet(
+            node_manager.clone(),
+            context,
+            "web",
+            any_port,
+            outlet_addr.clone(),
+            InletOptions::default(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.code().kind, Kind::AlreadyExists);
+
+        NodeManager::create_inlet(
+            node_manager.clone(),
+            context,
+            "api",
+            any_port,
+            outlet_addr,
+            InletOptions::default().with_wait_for_outlet_duration(Duration::from_secs(1)),
+        )
+        .await?;
+        let InletList { list, .. } = node_manager.read().await.list_inlets();
+        let aliases: Vec<&str> = list.iter().map(|i| i.alias.as_str()).collect();
+        assert_eq!(aliases, vec!["api", "web"]);
+
+        // the listener of a deleted inlet is stopped
+        let deleted = node_manager.write().await.delete_inlet("web").await?;
+        assert_eq!(deleted.map(|i| i.bind_addr), Some(web.bind_addr.clone()));
+        assert!(tokio::net::TcpStream::connect(&web.bind_addr)
+            .await
+            .is_err());
+        let InletList { list, .. } = node_manager.read().await.list_inlets();
+        let aliases: Vec<&str> = list.iter().map(|i| i.alias.as_str()).collect();
+        assert_eq!(aliases, vec!["api"]);
+        assert!(node_manager
+            .write()
+            .await
+            .delete_inlet("web")
+            .await?
+            .is_none());
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn create_a_port_range_outlet(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let listener = handler
+            .tcp
+            .listen("127.0.0.1:0", TcpListenerOptions::new())
+            .await?;
+        let port = listener.socket_address().port();
+        let mut node_manager = handler.node_manager.write().await;
+        let status = node_manager
+            .create_outlet(
+                context,
+                "127.0.0.1:6020-6029".to_string(),
+                "range".to_string(),
+                Some("range".to_string()),
+                false,
+            )
+            .await?;
+        assert_eq!(
+            status.port_range,
+            Some(PortRange {
+                first: 6020,
+                last: 6029
+            })
+        );
+        assert_eq!(
+            node_manager.list_outlets().list[0].port_range,
+            status.port_range
+        );
+
+        // a range containing the port of the node listener is refused
+        while node_manager.tcp_listener_addresses().is_empty() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let target = format!("127.0.0.1:{}-{}", port.saturating_sub(1), port);
+        let error = node_manager
+            .create_outlet(context, target, "loop".to_string(), None, false)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code().kind, Kind::Invalid);
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[test]
+    fn parse_a_port_range() {
+        assert_eq!(
+            PortRange::parse_tcp_addr("localhost:5000-5010"),
+            Some((
+                "localhost",
+                PortRange {
+                    first: 5000,
+                    last: 5010
+                }
+            ))
+        );
+        assert_eq!(PortRange::parse_tcp_addr("127.0.0.1:5000"), None);
+        assert_eq!(PortRange::parse_tcp_addr("127.0.0.1:5010-5000"), None);
+        assert_eq!(PortRange::parse_tcp_addr(":5000-5010"), None);
+    }
+}
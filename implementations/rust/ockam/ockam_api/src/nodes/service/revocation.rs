@@ -0,0 +1,309 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use ockam::identity::{
+    IdentityIdentifier, IdentitySecureChannelLocalInfo, SecureChannelTrustInfo, TrustPolicy,
+};
+use ockam::{Address, Context};
+use ockam_core::api::{Error, Request, Response, ResponseBuilder};
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, IncomingAccessControl, RelayMessage, Result};
+
+use crate::decision_log::{record_denial, DenialReason};
+
+use super::NodeManager;
+
+/// Identities revoked by a node: their new secure channels are rejected by the secure channel
+/// listeners and the messages they send through a secure channel are refused by the portals.
+///
+/// The list is stored in the directory of the node, so that the revocations survive a restart.
+/// It is shared with the trust policies and the access controls, which check it for each new
+/// secure channel and each message, so revoking an identity doesn't recreate any worker.
+#[derive(Clone, Debug, Default)]
+pub struct RevocationList {
+    path: Option<PathBuf>,
+    revoked: Arc<RwLock<BTreeSet<IdentityIdentifier>>>,
+}
+
+impl RevocationList {
+    /// Load the revocation list stored at this path. The list is empty if the file doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        let revoked = if path.exists() {
+            let contents = std::fs::read_to_string(path).map_err(io_error)?;
+            serde_json::from_str(&contents).map_err(io_error)?
+        } else {
+            BTreeSet::new()
+        };
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            revoked: Arc::new(RwLock::new(revoked)),
+        })
+    }
+
+    pub fn is_revoked(&self, identifier: &IdentityIdentifier) -> bool {
+        self.revoked.read().unwrap().contains(identifier)
+    }
+
+    /// Return the revoked identities, sorted by identifier
+    pub fn list(&self) -> Vec<IdentityIdentifier> {
+        self.revoked.read().unwrap().iter().cloned().collect()
+    }
+
+    /// Add an identity to the list. Return false if it was already revoked
+    fn insert(&self, identifier: &IdentityIdentifier) -> Result<bool> {
+        let mut revoked = self.revoked.write().unwrap();
+        if !revoked.insert(identifier.clone()) {
+            return Ok(false);
+        }
+        self.persist(&revoked)?;
+        Ok(true)
+    }
+
+    /// Remove an identity from the list. Return false if it was not revoked
+    fn remove(&self, identifier: &IdentityIdentifier) -> Result<bool> {
+        let mut revoked = self.revoked.write().unwrap();
+        if !revoked.remove(identifier) {
+            return Ok(false);
+        }
+        self.persist(&revoked)?;
+        Ok(true)
+    }
+
+    fn persist(&self, revoked: &BTreeSet<IdentityIdentifier>) -> Result<()> {
+        if let Some(path) = &self.path {
+            let contents = serde_json::to_string(revoked).map_err(io_error)?;
+            std::fs::write(path, contents).map_err(io_error)?;
+        }
+        Ok(())
+    }
+
+    /// Wrap the access control of a portal so that it denies the messages sent by a revoked
+    /// identity through a secure channel
+    pub(crate) fn access_control(
+        &self,
+        inner: Arc<dyn IncomingAccessControl>,
+    ) -> Arc<dyn IncomingAccessControl> {
+        Arc::new(RevocationAccessControl {
+            inner,
+            revocations: self.clone(),
+        })
+    }
+
+    /// Return a trust policy rejecting the secure channels initiated by a revoked identity
+    pub(crate) fn trust_policy(&self) -> RevocationTrustPolicy {
+        RevocationTrustPolicy {
+            revocations: self.clone(),
+        }
+    }
+}
+
+fn io_error(e: impl std::error::Error + Send + Sync + 'static) -> ockam_core::Error {
+    ockam_core::Error::new(Origin::Node, Kind::Io, e)
+}
+
+impl NodeManager {
+    /// Revoke an identity: its new secure channels are rejected, the messages it sends to the
+    /// portals of the node are refused and its existing secure channels are closed.
+    ///
+    /// Return false if the identity was already revoked. Its secure channels are closed anyway
+    pub async fn revoke(&mut self, ctx: &Context, identifier: &IdentityIdentifier) -> Result<bool> {
+        let added = self.revocations.insert(identifier)?;
+        info!(node = %self.node_name, %identifier, "Revoking an identity");
+
+        let channels: Vec<Address> = self
+            .secure_channels
+            .secure_channel_registry()
+            .get_channel_list()
+            .iter()
+            .filter(|entry| &entry.their_id() == identifier)
+            .map(|entry| entry.encryptor_messaging_address().clone())
+            .collect();
+        for address in channels {
+            if let Err(e) = self.close_secure_channel(ctx, &address).await {
+                warn!(%identifier, %address, %e, "failed to close a secure channel of a revoked identity");
+            }
+        }
+        Ok(added)
+    }
+
+    /// Cancel the revocation of an identity. Return false if the identity was not revoked
+    pub fn unrevoke(&self, identifier: &IdentityIdentifier) -> Result<bool> {
+        info!(node = %self.node_name, %identifier, "Cancelling the revocation of an identity");
+        self.revocations.remove(identifier)
+    }
+
+    pub fn is_revoked(&self, identifier: &IdentityIdentifier) -> bool {
+        self.revocations.is_revoked(identifier)
+    }
+
+    /// Return the identities revoked by the node
+    pub fn revoked_identities(&self) -> Vec<IdentityIdentifier> {
+        self.revocations.list()
+    }
+
+    pub(super) async fn revoke_identity(
+        &mut self,
+        ctx: &Context,
+        req: &Request,
+        identifier: &str,
+    ) -> Result<ResponseBuilder<()>, ResponseBuilder<Error>> {
+        let identifier = IdentityIdentifier::try_from(identifier)?;
+        self.revoke(ctx, &identifier).await?;
+        Ok(Response::ok(req.id()))
+    }
+
+    pub(super) fn unrevoke_identity(
+        &self,
+        req: &Request,
+        identifier: &str,
+    ) -> Result<ResponseBuilder<()>, ResponseBuilder<Error>> {
+        let identifier = IdentityIdentifier::try_from(identifier)?;
+        self.unrevoke(&identifier)?;
+        Ok(Response::ok(req.id()))
+    }
+
+    pub(super) fn list_revoked_identities(
+        &self,
+        req: &Request,
+    ) -> Result<ResponseBuilder<Vec<String>>, ResponseBuilder<Error>> {
+        let revoked = self
+            .revoked_identities()
+            .iter()
+            .map(|identifier| identifier.to_string())
+            .collect();
+        Ok(Response::ok(req.id()).body(revoked))
+    }
+}
+
+/// Access control of a portal, which refuses the messages sent by a revoked identity
+#[derive(Debug)]
+struct RevocationAccessControl {
+    inner: Arc<dyn IncomingAccessControl>,
+    revocations: RevocationList,
+}
+
+#[async_trait]
+impl IncomingAccessControl for RevocationAccessControl {
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        if let Ok(info) = IdentitySecureChannelLocalInfo::find_info(relay_msg.local_message()) {
+            let identifier = info.their_identity_id();
+            if self.revocations.is_revoked(&identifier) {
+                warn!(destination = %relay_msg.destination(), peer = %identifier, "the peer has been revoked, message denied");
+                record_denial(DenialReason::Revoked);
+                return Ok(false);
+            }
+        }
+        self.inner.is_authorized(relay_msg).await
+    }
+}
+
+/// Trust policy of a secure channel listener, rejecting the revoked identities
+pub(crate) struct RevocationTrustPolicy {
+    revocations: RevocationList,
+}
+
+#[async_trait]
+impl TrustPolicy for RevocationTrustPolicy {
+    async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
+        let identifier = trust_info.their_identity_id();
+        if self.revocations.is_revoked(identifier) {
+            warn!(peer = %identifier, "the peer has been revoked, secure channel rejected");
+            record_denial(DenialReason::Revoked);
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use ockam::identity::SecureChannelOptions;
+    use ockam_core::route;
+
+    use crate::cli_state::StateDirTrait;
+    use crate::util::test_utils::start_manager_for_tests;
+    use crate::DefaultAddress;
+
+    use super::*;
+
+    #[ockam_macros::test(timeout = 10_000)]
+    async fn a_revoked_identity_is_refused(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let bob = handler
+            .secure_channels
+            .identities()
+            .identities_creation()
+            .create_identity()
+            .await?;
+        let options = || SecureChannelOptions::new().with_timeout(Duration::from_millis(500));
+
+        handler
+            .secure_channels
+            .create_secure_channel(
+                context,
+                &bob.identifier(),
+                route![DefaultAddress::SECURE_CHANNEL_LISTENER],
+                options(),
+            )
+            .await?;
+        let is_connected = |node_manager: &NodeManager| {
+            node_manager
+                .secure_channels
+                .secure_channel_registry()
+                .get_channel_list()
+                .iter()
+                .any(|entry| entry.their_id() == bob.identifier())
+        };
+
+        let mut node_manager = handler.node_manager.write().await;
+        assert!(is_connected(&node_manager));
+        assert!(node_manager.revoke(context, &bob.identifier()).await?);
+        assert!(!node_manager.revoke(context, &bob.identifier()).await?);
+        assert!(node_manager.is_revoked(&bob.identifier()));
+        // the existing secure channel of the revoked identity is closed
+        assert!(!is_connected(&node_manager));
+        drop(node_manager);
+
+        // a new secure channel is rejected
+        assert!(handler
+            .secure_channels
+            .create_secure_channel(
+                context,
+                &bob.identifier(),
+                route![DefaultAddress::SECURE_CHANNEL_LISTENER],
+                options(),
+            )
+            .await
+            .is_err());
+
+        // the revocation is stored with the node
+        let node_manager = handler.node_manager.read().await;
+        let path = node_manager
+            .cli_state
+            .nodes
+            .get(&node_manager.node_name)?
+            .revocation_list_path();
+        let stored = RevocationList::load(&path)?;
+        assert_eq!(stored.list(), vec![bob.identifier()]);
+
+        // once the revocation is cancelled, the identity is accepted again
+        assert!(node_manager.unrevoke(&bob.identifier())?);
+        assert!(!node_manager.is_revoked(&bob.identifier()));
+        drop(node_manager);
+        handler
+            .secure_channels
+            .create_secure_channel(
+                context,
+                &bob.identifier(),
+                route![DefaultAddress::SECURE_CHANNEL_LISTENER],
+                options(),
+            )
+            .await?;
+
+        context.stop().await
+    }
+}
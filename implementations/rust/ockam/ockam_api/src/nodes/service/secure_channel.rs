@@ -1,12 +1,13 @@
+use std::collections::BTreeMap;
 use std::time::Duration;
 
 use minicbor::Decoder;
 
-use ockam::identity::TrustEveryonePolicy;
 use ockam::identity::{
     Identities, IdentitiesVault, IdentityIdentifier, SecureChannelListenerOptions,
     SecureChannelOptions, SecureChannels, TrustMultiIdentifiersPolicy,
 };
+use ockam::identity::{TrustEveryonePolicy, TrustPolicy};
 use ockam::{Address, Result, Route};
 use ockam_core::api::{Error, Request, Response, ResponseBuilder};
 use ockam_core::compat::sync::Arc;
@@ -17,15 +18,17 @@ use ockam_node::Context;
 
 use crate::cli_state::traits::StateDirTrait;
 use crate::cli_state::StateItemTrait;
+use crate::decision_log::DecisionLogTrustPolicy;
 use crate::nodes::connection::Connection;
 use crate::nodes::models::secure_channel::{
     CreateSecureChannelListenerRequest, CreateSecureChannelRequest, CreateSecureChannelResponse,
     CredentialExchangeMode, DeleteSecureChannelListenerRequest,
     DeleteSecureChannelListenerResponse, DeleteSecureChannelRequest, DeleteSecureChannelResponse,
-    SecureChannelListenersList, ShowSecureChannelListenerRequest,
-    ShowSecureChannelListenerResponse, ShowSecureChannelRequest, ShowSecureChannelResponse,
+    SecureChannelList, SecureChannelListenersList, SecureChannelStatus,
+    ShowSecureChannelListenerRequest, ShowSecureChannelListenerResponse, ShowSecureChannelRequest,
+    ShowSecureChannelResponse,
 };
-use crate::nodes::registry::SecureChannelListenerInfo;
+use crate::nodes::registry::{SecureChannelListenerInfo, WorkerKind};
 use crate::nodes::service::invalid_multiaddr_error;
 use crate::nodes::service::NodeIdentities;
 use crate::nodes::NodeManager;
@@ -34,6 +37,7 @@ use crate::{multiaddr_to_route, DefaultAddress};
 use super::{map_multiaddr_err, NodeManagerWorker};
 
 impl NodeManager {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn create_secure_channel_internal(
         &mut self,
         identifier: &IdentityIdentifier,
@@ -42,6 +46,7 @@ impl NodeManager {
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
         timeout: Option<Duration>,
         credential: Option<Credential>,
+        trust_context_name: Option<&str>,
     ) -> Result<SecureChannel> {
         debug!(%sc_route, "Creating secure channel");
         let options = SecureChannelOptions::new();
@@ -63,7 +68,11 @@ impl NodeManager {
             None => options.with_trust_policy(TrustEveryonePolicy),
         };
 
-        let options = match self.trust_context.clone() {
+        let trust_context = match trust_context_name {
+            Some(name) => Some(self.named_trust_context(Some(name))?.clone()),
+            None => self.trust_context.clone(),
+        };
+        let options = match trust_context {
             Some(trust_context) => options.with_trust_context(trust_context),
             None => options,
         };
@@ -75,6 +84,9 @@ impl NodeManager {
 
         debug!(%sc_route, %sc, "Created secure channel");
 
+        self.registry
+            .started_workers
+            .insert(sc.encryptor_address().clone(), WorkerKind::SecureChannel);
         self.registry
             .secure_channels
             .insert(sc_route, sc.clone(), authorized_identifiers);
@@ -92,6 +104,7 @@ impl NodeManager {
         identity_name: Option<String>,
         ctx: &Context,
         credential_name: Option<String>,
+        trust_context_name: Option<String>,
     ) -> Result<SecureChannel> {
         let identifier = self.get_identifier(identity_name.clone()).await?;
         let provided_credential = if let Some(credential_name) = credential_name {
@@ -109,7 +122,9 @@ impl NodeManager {
         // TODO: Determine when we can remove this? Or find a better way to determine
         //       when to check credentials. Currently enable_credential_checks only if a PROJECT AC and PROJECT ID are set
         //       -- Oakley
-        let actual_exchange_mode = if self.enable_credential_checks || provided_credential.is_some()
+        let actual_exchange_mode = if self.enable_credential_checks
+            || provided_credential.is_some()
+            || trust_context_name.is_some()
         {
             credential_exchange_mode
         } else {
@@ -126,7 +141,7 @@ impl NodeManager {
                 Some(match provided_credential {
                     Some(c) => c,
                     None => {
-                        self.trust_context()?
+                        self.named_trust_context(trust_context_name.as_deref())?
                             .authority()?
                             .credential(ctx, &identifier)
                             .await?
@@ -143,6 +158,7 @@ impl NodeManager {
                 authorized_identifiers,
                 timeout,
                 credential,
+                trust_context_name.as_deref(),
             )
             .await?;
 
@@ -156,6 +172,7 @@ impl NodeManager {
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
         vault_name: Option<String>,
         identity_name: Option<String>,
+        trust_context_name: Option<String>,
         ctx: &Context,
     ) -> Result<SecureChannelListener> {
         debug!(
@@ -169,15 +186,31 @@ impl NodeManager {
         let options =
             SecureChannelListenerOptions::new().as_consumer(&self.api_transport_flow_control_id);
 
+        // no new secure channel is accepted while the node is paused, nor from a revoked identity
+        let node_policy = self
+            .pause_switch
+            .trust_policy()
+            .and(self.revocations.trust_policy());
+        let decision_log = self.decision_log.clone();
         let options = match authorized_identifiers {
-            Some(ids) => options.with_trust_policy(TrustMultiIdentifiersPolicy::new(ids)),
-            None => options.with_trust_policy(TrustEveryonePolicy),
+            Some(ids) => options.with_trust_policy(DecisionLogTrustPolicy::new(
+                TrustMultiIdentifiersPolicy::new(ids).and(node_policy),
+                decision_log,
+                address.address(),
+            )),
+            None => options.with_trust_policy(DecisionLogTrustPolicy::new(
+                TrustEveryonePolicy.and(node_policy),
+                decision_log,
+                address.address(),
+            )),
         };
 
-        let options = if let Ok(trust_context) = self.trust_context() {
-            options.with_trust_context(trust_context.clone())
-        } else {
-            options
+        let options = match trust_context_name.as_deref() {
+            Some(name) => options.with_trust_context(self.named_trust_context(Some(name))?.clone()),
+            None => match self.trust_context() {
+                Ok(trust_context) => options.with_trust_context(trust_context.clone()),
+                Err(_) => options,
+            },
         };
 
         let listener = secure_channels
@@ -265,9 +298,104 @@ impl NodeManager {
         debug!(%addr, "deleting secure channel");
         self.secure_channels.stop_secure_channel(ctx, addr).await?;
         self.registry.secure_channels.remove_by_addr(addr);
+        self.registry.started_workers.remove(addr);
         Ok(())
     }
 
+    /// Close a secure channel given its encryptor address and stop its workers.
+    /// Return false if there is no such secure channel.
+    ///
+    /// Inlets routed over the channel can't reach their outlet anymore. When they were
+    /// created with a session the channel is re-established as if it had failed.
+    pub async fn close_secure_channel(&mut self, ctx: &Context, addr: &Address) -> Result<bool> {
+        let exists = self
+            .secure_channels
+            .secure_channel_registry()
+            .get_channel_by_encryptor_address(addr)
+            .is_some()
+            || self.registry.secure_channels.get_by_addr(addr).is_some();
+        if !exists {
+            debug!(%addr, "no secure channel to close");
+            return Ok(false);
+        }
+        self.delete_secure_channel(ctx, addr).await?;
+        // The channel is unregistered when its workers shut down, which happens
+        // asynchronously, so it is unregistered right away to not be listed anymore
+        self.secure_channels
+            .secure_channel_registry()
+            .unregister_channel(addr);
+
+        for (alias, inlet) in self.registry.inlets.iter() {
+            if inlet.outlet_route.iter().any(|a| a == addr) {
+                warn!(%alias, %addr, "the secure channel used by this inlet has been closed");
+            }
+        }
+        Ok(true)
+    }
+
+    /// Return the secure channels of this node, with the identifier and the verified
+    /// attributes of their peer when it has been authenticated
+    pub async fn list_secure_channels(&self) -> Result<Vec<SecureChannelStatus>> {
+        let identity_registry = self.secure_channels.secure_channel_registry();
+        let mut channels = vec![];
+        for entry in identity_registry.get_channel_list() {
+            let route = self
+                .registry
+                .secure_channels
+                .get_by_addr(entry.encryptor_messaging_address())
+                .map(|info| info.route().to_string());
+            let their_id = entry.their_id();
+            let attributes = self.peer_attributes(&their_id).await?;
+            channels.push(SecureChannelStatus::new(
+                entry.encryptor_messaging_address(),
+                Some(&entry.their_decryptor_address()),
+                route,
+                entry.is_initiator(),
+                Some(&their_id),
+                attributes,
+            ));
+        }
+
+        // Channels created by this node but not registered yet, i.e. not authenticated
+        for info in self.registry.secure_channels.list() {
+            let address = info.sc().encryptor_address();
+            if identity_registry
+                .get_channel_by_encryptor_address(address)
+                .is_none()
+            {
+                channels.push(SecureChannelStatus::new(
+                    address,
+                    None,
+                    Some(info.route().to_string()),
+                    true,
+                    None,
+                    BTreeMap::new(),
+                ));
+            }
+        }
+        Ok(channels)
+    }
+
+    /// Return the verified attributes of an identity, as strings
+    async fn peer_attributes(
+        &self,
+        identifier: &IdentityIdentifier,
+    ) -> Result<BTreeMap<String, String>> {
+        let attributes = self
+            .identities_repository()
+            .get_attributes(identifier)
+            .await?
+            .map(|entry| {
+                entry
+                    .attrs()
+                    .iter()
+                    .map(|(k, v)| (k.clone(), String::from_utf8_lossy(v).to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(attributes)
+    }
+
     pub(super) async fn delete_secure_channel_listener_impl(
         &mut self,
         addr: &Address,
@@ -289,6 +417,15 @@ impl NodeManagerWorker {
         )
     }
 
+    pub(super) async fn list_secure_channels_status(
+        &self,
+        req: &Request,
+    ) -> Result<ResponseBuilder<SecureChannelList>, ResponseBuilder<Error>> {
+        let node_manager = self.node_manager.read().await;
+        let channels = node_manager.list_secure_channels().await?;
+        Ok(Response::ok(req.id()).body(SecureChannelList::new(channels)))
+    }
+
     pub(super) async fn list_secure_channel_listener(
         &self,
         req: &Request,
@@ -320,6 +457,7 @@ impl NodeManagerWorker {
             timeout,
             identity_name: identity,
             credential_name,
+            trust_context_name,
             ..
         } = dec.decode()?;
 
@@ -362,6 +500,7 @@ impl NodeManagerWorker {
                 identity,
                 ctx,
                 credential_name,
+                trust_context_name,
             )
             .await?;
 
@@ -383,15 +522,19 @@ impl NodeManagerWorker {
         let addr = Address::from(body.channel);
         info!(%addr, "Handling request to delete secure channel");
         let mut node_manager = self.node_manager.write().await;
-        let res = match node_manager.delete_secure_channel(ctx, &addr).await {
-            Ok(()) => {
-                trace!(%addr, "Removed secure channel");
-                Some(addr)
-            }
-            Err(err) => {
-                trace!(%addr, %err, "Error removing secure channel");
-                None
-            }
+        let closed = node_manager
+            .close_secure_channel(ctx, &addr)
+            .await
+            .map_err(|err| {
+                warn!(%addr, %err, "Error removing secure channel");
+                err
+            })?;
+        let res = if closed {
+            trace!(%addr, "Removed secure channel");
+            Some(addr)
+        } else {
+            trace!(%addr, "No such secure channel to delete");
+            None
         };
         Ok(Response::ok(req.id()).body(DeleteSecureChannelResponse::new(res)))
     }
@@ -428,6 +571,7 @@ impl NodeManagerWorker {
             authorized_identifiers,
             vault,
             identity,
+            trust_context_name,
             ..
         } = dec.decode()?;
 
@@ -451,7 +595,14 @@ impl NodeManagerWorker {
         }
 
         node_manager
-            .create_secure_channel_listener_impl(addr, authorized_identifiers, vault, identity, ctx)
+            .create_secure_channel_listener_impl(
+                addr,
+                authorized_identifiers,
+                vault,
+                identity,
+                trust_context_name,
+                ctx,
+            )
             .await?;
 
         let response = Response::ok(req.id());
@@ -514,3 +665,140 @@ impl NodeManagerWorker {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ockam::identity::SecureChannelListenerOptions;
+    use ockam_core::{route, Address};
+    use ockam_node::tokio::time::sleep;
+    use ockam_node::Context;
+    use std::time::Duration;
+
+    use crate::util::test_utils::start_manager_for_tests;
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn list_secure_channels(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        handler
+            .secure_channels
+            .create_secure_channel_listener(
+                context,
+                &handler.identifier,
+                "listener",
+                SecureChannelListenerOptions::new(),
+            )
+            .await?;
+
+        let mut node_manager = handler.node_manager.write().await;
+        let channel = node_manager
+            .create_secure_channel_internal(
+                &handler.identifier,
+                context,
+                route!["listener"],
+                None,
+                None,
+                None,
+                None,
+            )
+            .await?;
+
+        // both ends of the channel are listed, with the authenticated peer
+        let channels = node_manager.list_secure_channels().await?;
+        assert_eq!(channels.len(), 2);
+        let peer = Some(handler.identifier.to_string());
+        assert!(channels.iter().all(|c| c.peer_identifier == peer));
+
+        let initiator = channels.iter().find(|c| c.is_initiator).unwrap();
+        assert_eq!(
+            initiator.local_address,
+            channel.encryptor_address().to_string()
+        );
+        assert!(initiator.remote_address.is_some());
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn close_secure_channel(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        handler
+            .secure_channels
+            .create_secure_channel_listener(
+                context,
+                &handler.identifier,
+                "listener",
+                SecureChannelListenerOptions::new(),
+            )
+            .await?;
+
+        let mut node_manager = handler.node_manager.write().await;
+        let channel = node_manager
+            .create_secure_channel_internal(
+                &handler.identifier,
+                context,
+                route!["listener"],
+                None,
+                None,
+                None,
+                None,
+            )
+            .await?;
+
+        // closing a missing channel is not an error
+        let missing = Address::from_string("missing");
+        assert!(!node_manager.close_secure_channel(context, &missing).await?);
+
+        let address = channel.encryptor_address().clone();
+        assert!(node_manager.close_secure_channel(context, &address).await?);
+        assert!(!node_manager.close_secure_channel(context, &address).await?);
+        let channels = node_manager.list_secure_channels().await?;
+        assert!(channels
+            .iter()
+            .all(|c| c.local_address != address.to_string()));
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn close_secure_channel_failures_are_returned(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        handler
+            .secure_channels
+            .create_secure_channel_listener(
+                context,
+                &handler.identifier,
+                "listener",
+                SecureChannelListenerOptions::new(),
+            )
+            .await?;
+
+        let mut node_manager = handler.node_manager.write().await;
+        let channel = node_manager
+            .create_secure_channel_internal(
+                &handler.identifier,
+                context,
+                route!["listener"],
+                None,
+                None,
+                None,
+                None,
+            )
+            .await?;
+
+        // the workers of the channel are stopped behind the back of the node manager
+        // which still has the channel in its registry, so it can't stop them anymore
+        let address = channel.encryptor_address().clone();
+        context.stop_worker(address.clone()).await?;
+        while context.list_workers().await?.contains(&address) {
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert!(node_manager
+            .close_secure_channel(context, &address)
+            .await
+            .is_err());
+        drop(node_manager);
+        context.stop().await
+    }
+}
@@ -0,0 +1,328 @@
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+use futures::future::join_all;
+use ockam::compat::tokio::time::{timeout_at, Instant};
+use ockam::Result;
+use ockam_node::Context;
+
+use super::NodeManager;
+
+/// Maximum duration to wait for all the components of the node to stop
+pub const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Subsystems of a node, in the order in which they are stopped by [`NodeManager::shutdown`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Subsystem {
+    Outlets,
+    Inlets,
+    SecureChannels,
+    Transports,
+}
+
+impl fmt::Display for Subsystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Subsystem::Outlets => write!(f, "outlets"),
+            Subsystem::Inlets => write!(f, "inlets"),
+            Subsystem::SecureChannels => write!(f, "secure channels"),
+            Subsystem::Transports => write!(f, "transports"),
+        }
+    }
+}
+
+/// Outcome of stopping a component of the node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopStatus {
+    Stopped,
+    Failed(String),
+    TimedOut,
+}
+
+impl StopStatus {
+    pub fn is_stopped(&self) -> bool {
+        self == &StopStatus::Stopped
+    }
+}
+
+impl fmt::Display for StopStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StopStatus::Stopped => write!(f, "stopped"),
+            StopStatus::Failed(e) => write!(f, "failed: {e}"),
+            StopStatus::TimedOut => write!(f, "timed out after {}s", STOP_TIMEOUT.as_secs()),
+        }
+    }
+}
+
+/// Component of a node stopped by [`NodeManager::shutdown`]: an outlet or an inlet by alias,
+/// a secure channel by address, or a TCP listener or connection by socket address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoppedComponent {
+    pub subsystem: Subsystem,
+    pub name: String,
+    pub status: StopStatus,
+}
+
+/// Outcome of [`NodeManager::shutdown`] for each component of the node, by subsystem
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    pub components: Vec<StoppedComponent>,
+}
+
+impl ShutdownReport {
+    /// Return true if all the components stopped cleanly
+    pub fn is_clean(&self) -> bool {
+        self.components.iter().all(|c| c.status.is_stopped())
+    }
+
+    /// Return the components which failed to stop or timed out
+    pub fn failures(&self) -> impl Iterator<Item = &StoppedComponent> {
+        self.components.iter().filter(|c| !c.status.is_stopped())
+    }
+
+    /// Return the status of the component of a subsystem with the given name
+    pub fn status(&self, subsystem: Subsystem, name: &str) -> Option<&StopStatus> {
+        self.components
+            .iter()
+            .find(|c| c.subsystem == subsystem && c.name == name)
+            .map(|c| &c.status)
+    }
+
+    /// Stop the components of a subsystem concurrently, waiting at most until the deadline,
+    /// and record their status
+    async fn stop_all<T>(
+        &mut self,
+        deadline: Instant,
+        subsystem: Subsystem,
+        stops: impl IntoIterator<Item = (String, impl Future<Output = Result<T>>)>,
+    ) {
+        let stops = stops.into_iter().map(|(name, stop)| async move {
+            let status = match timeout_at(deadline, stop).await {
+                Ok(Ok(_)) => StopStatus::Stopped,
+                Ok(Err(e)) => StopStatus::Failed(e.to_string()),
+                Err(_) => StopStatus::TimedOut,
+            };
+            if !status.is_stopped() {
+                warn!(%subsystem, %name, %status, "failed to stop a component of the node");
+            }
+            StoppedComponent {
+                subsystem,
+                name,
+                status,
+            }
+        });
+        self.components.extend(join_all(stops).await);
+    }
+}
+
+impl fmt::Display for ShutdownReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failures = self.failures().count();
+        write!(
+            f,
+            "{} components stopped, {failures} failed",
+            self.components.len() - failures
+        )?;
+        for c in self.failures() {
+            write!(f, "\n{} {}: {}", c.subsystem, c.name, c.status)?;
+        }
+        Ok(())
+    }
+}
+
+impl NodeManager {
+    /// Stop the outlets, the inlets, the secure channels and the TCP listeners and connections
+    /// of the node, in that order, and report the outcome for each of them.
+    ///
+    /// The components of a subsystem are stopped concurrently and the whole shutdown takes at
+    /// most [`STOP_TIMEOUT`]: the components which are not stopped by then are reported as
+    /// timed out. A component which fails to stop doesn't prevent the next ones from being stopped.
+    /// The background tasks of the node manager are aborted first
+    pub async fn shutdown(&mut self, ctx: &Context) -> ShutdownReport {
+        info!(node = %self.node_name, "Shutting down the node");
+        let deadline = Instant::now() + STOP_TIMEOUT;
+        let mut report = ShutdownReport::default();
+        self.background_tasks.abort_all();
+
+        // the outlets and the inlets are removed from the registry before their workers are
+        // stopped, so that they can be stopped concurrently
+        let outlets = std::mem::take(&mut self.registry.outlets);
+        self.registry.outlets_changed.notify();
+        let inlets = std::mem::take(&mut self.registry.inlets);
+        for inlet in inlets.values() {
+            if let Some(session_key) = &inlet.session_key {
+                self.remove_session(session_key);
+            }
+        }
+        let channels: Vec<_> = self
+            .secure_channels
+            .secure_channel_registry()
+            .get_channel_list()
+            .iter()
+            .map(|entry| entry.encryptor_messaging_address().clone())
+            .collect();
+
+        let tcp = &self.tcp_transport;
+        report
+            .stop_all(
+                deadline,
+                Subsystem::Outlets,
+                outlets
+                    .iter()
+                    .map(|(alias, o)| (alias.clone(), tcp.stop_outlet(o.worker_addr.clone()))),
+            )
+            .await;
+        report
+            .stop_all(
+                deadline,
+                Subsystem::Inlets,
+                inlets
+                    .iter()
+                    .map(|(alias, i)| (alias.clone(), tcp.stop_inlet(i.worker_addr.clone()))),
+            )
+            .await;
+        let secure_channels = &self.secure_channels;
+        report
+            .stop_all(
+                deadline,
+                Subsystem::SecureChannels,
+                channels.iter().map(|address| {
+                    (
+                        address.to_string(),
+                        secure_channels.stop_secure_channel(ctx, address),
+                    )
+                }),
+            )
+            .await;
+        let listeners = tcp.registry().get_all_listeners();
+        report
+            .stop_all(
+                deadline,
+                Subsystem::Transports,
+                listeners.iter().map(|listener| {
+                    (
+                        listener.socket_address().to_string(),
+                        tcp.stop_listener(listener.address()),
+                    )
+                }),
+            )
+            .await;
+        let senders = tcp.registry().get_all_sender_workers();
+        report
+            .stop_all(
+                deadline,
+                Subsystem::Transports,
+                senders.iter().map(|sender| {
+                    (
+                        sender.socket_address().to_string(),
+                        tcp.disconnect(sender.address().clone()),
+                    )
+                }),
+            )
+            .await;
+
+        for address in outlets
+            .values()
+            .map(|o| &o.worker_addr)
+            .chain(inlets.values().map(|i| &i.worker_addr))
+            .chain(channels.iter())
+        {
+            self.registry.started_workers.remove(address);
+        }
+        for address in channels.iter() {
+            self.registry.secure_channels.remove_by_addr(address);
+            self.secure_channels
+                .secure_channel_registry()
+                .unregister_channel(address);
+        }
+
+        info!(node = %self.node_name, %report, "Node shut down");
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ockam::Address;
+
+    use crate::nodes::registry::OutletInfo;
+    use crate::util::test_utils::start_manager_for_tests;
+
+    use super::*;
+
+    #[ockam_macros::test(timeout = 10_000)]
+    async fn a_failed_outlet_doesnt_prevent_the_shutdown(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+        for (alias, port) in [("db", 6001), ("web", 6002)] {
+            node_manager
+                .create_outlet(
+                    context,
+                    format!("127.0.0.1:{port}"),
+                    alias.to_string(),
+                    Some(alias.to_string()),
+                    false,
+                )
+                .await?;
+        }
+        // the worker of this outlet doesn't exist, so it can't be stopped
+        node_manager.registry.outlets.insert(
+            "broken".to_string(),
+            OutletInfo::new("127.0.0.1:6003", Some(&Address::from_string("missing"))),
+        );
+
+        let report = node_manager.shutdown(context).await;
+        assert!(!report.is_clean());
+        let failures: Vec<&str> = report.failures().map(|c| c.name.as_str()).collect();
+        assert_eq!(failures, vec!["broken"]);
+        assert!(matches!(
+            report.status(Subsystem::Outlets, "broken"),
+            Some(StopStatus::Failed(_))
+        ));
+        assert_eq!(
+            report.status(Subsystem::Outlets, "db"),
+            Some(&StopStatus::Stopped)
+        );
+        assert_eq!(
+            report.status(Subsystem::Outlets, "web"),
+            Some(&StopStatus::Stopped)
+        );
+
+        // the next subsystems are stopped as well
+        assert!(report
+            .components
+            .iter()
+            .any(|c| c.subsystem == Subsystem::Transports && c.status.is_stopped()));
+        assert!(node_manager.list_outlets().list.is_empty());
+
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[tokio::test]
+    async fn the_components_are_stopped_concurrently_under_one_deadline() {
+        let deadline = Instant::now() + Duration::from_millis(500);
+        let mut report = ShutdownReport::default();
+        // stopped one after the other, these components would take longer than the deadline
+        let stops = ["a", "b", "c", "d"].map(|name| {
+            (name.to_string(), async {
+                ockam::compat::tokio::time::sleep(Duration::from_millis(200)).await;
+                Ok(())
+            })
+        });
+        report.stop_all(deadline, Subsystem::Outlets, stops).await;
+        assert!(report.is_clean());
+
+        let stuck = [("stuck".to_string(), std::future::pending::<Result<()>>())];
+        report.stop_all(deadline, Subsystem::Inlets, stuck).await;
+        assert_eq!(
+            report.status(Subsystem::Inlets, "stuck"),
+            Some(&StopStatus::TimedOut)
+        );
+        assert!(Instant::now() < deadline + Duration::from_millis(100));
+    }
+}
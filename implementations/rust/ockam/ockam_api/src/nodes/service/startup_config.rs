@@ -0,0 +1,1636 @@
+//! A node can declare, in the `startup.yaml` file of its directory, the trust contexts, policies,
+//! outlets and inlets which must be created when it starts:
+//!
+//! ```yaml
+//! trust_contexts:
+//!   project-2:
+//!     id: 0b75e1e4c1c1f8c8
+//! policies:
+//!   - resource: db
+//!     action: handle_message
+//!     expression: (= subject.component "web")
+//!     description: only the web server can access the database
+//! outlets:
+//!   - alias: db
+//!     to: 127.0.0.1:5432
+//!     trust_context: project-2
+//!     idle_timeout_secs: 300
+//!     require_credential: true
+//!     max_connections: 100
+//!     tags:
+//!       service: postgres
+//!   - alias: web
+//!     to: 127.0.0.1:8000-8010
+//! inlets:
+//!   - alias: db-inlet
+//!     from: 127.0.0.1:15432
+//!     to: /service/db
+//! ```
+//!
+//! When the file is watched, with `ockam node create --watch-startup-config`, its edits are
+//! applied while the node is running.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use minicbor::Decoder;
+use ockam::Result;
+use ockam_abac::{Action, Expr, PolicySnapshot, Resource};
+use ockam_core::api::{Error, Id, Request, Response, ResponseBuilder};
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{route, Address, AllowAll, DenyAll};
+use ockam_multiaddr::MultiAddr;
+use ockam_node::Context;
+
+use crate::cli_state::StateDirTrait;
+use crate::config::cli::TrustContextConfig;
+use crate::error::ApiError;
+use crate::nodes::models::portal::{CreateInlet, PortRange};
+use crate::nodes::models::startup_config::{ExportStartupConfig, StartupConfigFile};
+
+use super::portals::targets_listener;
+use super::{NodeManager, NodeManagerWorker, OutletSpec};
+
+/// Interval between two checks of the startup configuration of a node, when it is watched
+pub const STARTUP_CONFIG_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Trust contexts, policies and portals created when a node starts
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NodeStartupConfig {
+    /// Named trust contexts, which can be used by the outlets
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub trust_contexts: BTreeMap<String, TrustContextConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policies: Vec<PolicyConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outlets: Vec<OutletConfig>,
+    /// Inlets are created after the outlets, so they can be routed to the outlets of the node
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inlets: Vec<InletConfig>,
+}
+
+/// Policy of a resource for an action
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyConfig {
+    pub resource: String,
+    pub action: String,
+    /// Policy expression, for example (= subject.component "web")
+    #[serde(with = "expression")]
+    #[schemars(with = "String")]
+    pub expression: Expr,
+    /// Why the policy exists, it is not used to evaluate the policy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct OutletConfig {
+    pub alias: String,
+    /// Address of the TCP service exposed by the outlet, or a port range written `ip:first-last`
+    #[schemars(with = "String")]
+    pub to: OutletTarget,
+    /// Address of the outlet worker, the alias is used if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// Name of a trust context used by the outlet policy, instead of the default trust context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trust_context: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_timeout_secs: Option<u64>,
+    /// Deny the peers which didn't present a valid credential issued by the trust context authority
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub require_credential: bool,
+    /// Maximum number of connections of the outlet open at the same time, at least 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<usize>,
+    /// Tags describing the outlet, used to find it
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+}
+
+/// Address of the TCP service exposed by an outlet: a socket address, or a range of ports
+/// of an IP address, see [`PortRange`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum OutletTarget {
+    Address(SocketAddr),
+    Range(IpAddr, PortRange),
+}
+
+impl OutletTarget {
+    /// Return the socket addresses the outlet can connect to
+    pub fn addresses(&self) -> impl Iterator<Item = SocketAddr> + '_ {
+        let (ip, ports) = match self {
+            OutletTarget::Address(address) => (address.ip(), address.port()..=address.port()),
+            OutletTarget::Range(ip, range) => (*ip, range.ports()),
+        };
+        ports.map(move |port| SocketAddr::new(ip, port))
+    }
+
+    /// Return true if both targets share at least one socket address
+    pub fn overlaps(&self, other: &OutletTarget) -> bool {
+        let (ip, first, last) = self.bounds();
+        let (other_ip, other_first, other_last) = other.bounds();
+        ip == other_ip && first <= other_last && other_first <= last
+    }
+
+    fn bounds(&self) -> (IpAddr, u16, u16) {
+        match self {
+            OutletTarget::Address(address) => (address.ip(), address.port(), address.port()),
+            OutletTarget::Range(ip, range) => (*ip, range.first, range.last),
+        }
+    }
+}
+
+impl FromStr for OutletTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if let Ok(address) = s.parse() {
+            return Ok(OutletTarget::Address(address));
+        }
+        PortRange::parse_tcp_addr(s)
+            .and_then(|(host, range)| {
+                let host = host
+                    .strip_prefix('[')
+                    .and_then(|h| h.strip_suffix(']'))
+                    .unwrap_or(host);
+                host.parse().ok().map(|ip| OutletTarget::Range(ip, range))
+            })
+            .ok_or_else(|| format!("invalid socket address or port range: {s}"))
+    }
+}
+
+impl TryFrom<String> for OutletTarget {
+    type Error = String;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl Display for OutletTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutletTarget::Address(address) => address.fmt(f),
+            OutletTarget::Range(ip @ IpAddr::V4(_), range) => write!(f, "{ip}:{range}"),
+            OutletTarget::Range(ip @ IpAddr::V6(_), range) => write!(f, "[{ip}]:{range}"),
+        }
+    }
+}
+
+impl From<OutletTarget> for String {
+    fn from(target: OutletTarget) -> Self {
+        target.to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct InletConfig {
+    pub alias: String,
+    /// Address the inlet listens at
+    pub from: SocketAddr,
+    /// Route to the outlet
+    #[schemars(with = "String")]
+    pub to: MultiAddr,
+}
+
+impl NodeStartupConfig {
+    /// Parse a configuration.
+    /// The error message gives the path and the line of the invalid field
+    pub fn parse(contents: &str) -> Result<Self> {
+        serde_yaml::from_str(contents).map_err(|e| invalid_config(e.to_string()))
+    }
+
+    /// Check a configuration without applying it and return all the errors found.
+    ///
+    /// Besides the syntax errors, all the invalid entries and policy expressions are reported,
+    /// as well as the duplicated aliases and policies, the outlets using an undeclared trust context and
+    /// the outlets forwarding their connections to an inlet of the node
+    pub fn validate(contents: &str) -> Vec<String> {
+        let mut value: serde_yaml::Value = match serde_yaml::from_str(contents) {
+            Ok(value) => value,
+            Err(e) => return vec![e.to_string()],
+        };
+
+        // the invalid expressions are replaced so that the rest of the configuration is checked
+        let mut errors = vec![];
+        if let Some(policies) = value.get_mut("policies").and_then(|p| p.as_sequence_mut()) {
+            for (i, policy) in policies.iter_mut().enumerate() {
+                let expression = match policy.get_mut("expression") {
+                    Some(serde_yaml::Value::String(expression)) => expression,
+                    _ => continue,
+                };
+                match ockam_abac::parse(expression) {
+                    Ok(Some(_)) => {}
+                    Ok(None) => {
+                        errors.push(format!(
+                            "policies[{i}].expression: the policy expression is empty"
+                        ));
+                        *expression = "true".to_string();
+                    }
+                    Err(e) => {
+                        errors.push(format!(
+                            "policies[{i}].expression: invalid policy expression `{expression}`: {e}"
+                        ));
+                        *expression = "true".to_string();
+                    }
+                }
+            }
+        }
+
+        // the errors only give the path and the line of a field when the original contents are parsed
+        if errors.is_empty() {
+            if let Ok(config) = serde_yaml::from_str::<Self>(contents) {
+                return config.semantic_errors();
+            }
+        }
+
+        // otherwise each entry is checked on its own, and the invalid ones are removed,
+        // so that the errors of all the entries are reported
+        errors.extend(invalid_entries::<TrustContextConfig>(
+            &mut value,
+            "trust_contexts",
+        ));
+        errors.extend(invalid_entries::<PolicyConfig>(&mut value, "policies"));
+        errors.extend(invalid_entries::<OutletConfig>(&mut value, "outlets"));
+        errors.extend(invalid_entries::<InletConfig>(&mut value, "inlets"));
+        match serde_yaml::from_value::<Self>(value) {
+            Ok(config) => errors.extend(config.semantic_errors()),
+            Err(e) => errors.push(e.to_string()),
+        }
+        errors
+    }
+
+    /// Return the errors which would prevent the configuration from being applied entirely
+    fn semantic_errors(&self) -> Vec<String> {
+        let mut errors = vec![];
+        let mut policies = BTreeSet::new();
+        for policy in &self.policies {
+            if !policies.insert((&policy.resource, &policy.action)) {
+                errors.push(format!(
+                    "the policy of the resource {} for the action {} is declared several times",
+                    policy.resource, policy.action
+                ));
+            }
+        }
+
+        for alias in duplicates(self.outlets.iter().map(|o| &o.alias)) {
+            errors.push(format!("the alias {alias} is used by several outlets"));
+        }
+        for (i, outlet) in self.outlets.iter().enumerate() {
+            if self.outlets[..i].iter().any(|o| o.to.overlaps(&outlet.to)) {
+                errors.push(format!(
+                    "the outlet {} forwards to {}, like another outlet",
+                    outlet.alias, outlet.to
+                ));
+            }
+            if outlet.max_connections == Some(0) {
+                errors.push(format!(
+                    "the outlet {} accepts a maximum of 0 connections, it must be at least 1",
+                    outlet.alias
+                ));
+            }
+            if let Some(trust_context) = &outlet.trust_context {
+                if !self.trust_contexts.contains_key(trust_context) {
+                    errors.push(format!(
+                        "the outlet {} uses the trust context {trust_context}, which is not declared",
+                        outlet.alias
+                    ));
+                }
+            }
+            for inlet in &self.inlets {
+                if inlet.from.port() != 0
+                    && outlet
+                        .to
+                        .addresses()
+                        .any(|to| targets_listener(&to.to_string(), &inlet.from))
+                {
+                    errors.push(format!(
+                        "the outlet {} forwards to the inlet {} of the node, this would create a loop",
+                        outlet.alias, inlet.alias
+                    ));
+                }
+            }
+        }
+
+        for alias in duplicates(self.inlets.iter().map(|i| &i.alias)) {
+            errors.push(format!("the alias {alias} is used by several inlets"));
+        }
+        errors
+    }
+
+    /// Load the configuration stored in a file. Return `None` if the file doesn't exist
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| ockam_core::Error::new(Origin::Node, Kind::Io, e))?;
+        serde_yaml::from_str(&contents)
+            .map(Some)
+            .map_err(|e| invalid_config(format!("{}: {e}", path.display())))
+    }
+
+    /// Return the changes turning the state created by this configuration into the state
+    /// created by a new configuration.
+    ///
+    /// A modified outlet or inlet is deleted then created again, a modified policy is replaced.
+    /// The trust contexts can only be added: the modified or removed ones are ignored
+    pub fn changes(&self, new: &Self) -> StartupConfigChanges {
+        let mut changes = StartupConfigChanges::default();
+        for (name, trust_context) in &new.trust_contexts {
+            match self.trust_contexts.get(name) {
+                None => {
+                    changes
+                        .added
+                        .trust_contexts
+                        .insert(name.clone(), trust_context.clone());
+                }
+                Some(previous) if previous != trust_context => {
+                    changes.ignored_trust_contexts.push(name.clone())
+                }
+                Some(_) => {}
+            }
+        }
+        for name in self.trust_contexts.keys() {
+            if !new.trust_contexts.contains_key(name) {
+                changes.ignored_trust_contexts.push(name.clone())
+            }
+        }
+
+        for policy in &self.policies {
+            if !new
+                .policies
+                .iter()
+                .any(|p| p.resource == policy.resource && p.action == policy.action)
+            {
+                changes
+                    .removed_policies
+                    .push((policy.resource.clone(), policy.action.clone()));
+            }
+        }
+        changes.added.policies = new
+            .policies
+            .iter()
+            .filter(|policy| !self.policies.iter().any(|p| p.same_as(policy)))
+            .cloned()
+            .collect();
+
+        for outlet in &self.outlets {
+            if !new.outlets.contains(outlet) {
+                changes.removed_outlets.push(outlet.alias.clone());
+            }
+        }
+        changes.added.outlets = new
+            .outlets
+            .iter()
+            .filter(|outlet| !self.outlets.contains(outlet))
+            .cloned()
+            .collect();
+
+        for inlet in &self.inlets {
+            if !new.inlets.contains(inlet) {
+                changes.removed_inlets.push(inlet.alias.clone());
+            }
+        }
+        changes.added.inlets = new
+            .inlets
+            .iter()
+            .filter(|inlet| !self.inlets.contains(inlet))
+            .cloned()
+            .collect();
+        changes
+    }
+}
+
+impl PolicyConfig {
+    /// Return true if both policies are identical. The expressions are compared by their text
+    fn same_as(&self, other: &PolicyConfig) -> bool {
+        self.resource == other.resource
+            && self.action == other.action
+            && self.expression.to_string() == other.expression.to_string()
+            && self.description == other.description
+    }
+}
+
+/// Changes to apply to a node when its startup configuration is edited
+#[derive(Debug, Clone, Default)]
+pub struct StartupConfigChanges {
+    /// Trust contexts, policies, outlets and inlets to create
+    pub added: NodeStartupConfig,
+    /// Resources and actions of the policies to delete
+    pub removed_policies: Vec<(String, String)>,
+    /// Aliases of the outlets to delete
+    pub removed_outlets: Vec<String>,
+    /// Aliases of the inlets to delete
+    pub removed_inlets: Vec<String>,
+    /// Names of the trust contexts which were modified or removed, the node must be restarted
+    /// to take them into account
+    pub ignored_trust_contexts: Vec<String>,
+}
+
+impl StartupConfigChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.trust_contexts.is_empty()
+            && self.added.policies.is_empty()
+            && self.added.outlets.is_empty()
+            && self.added.inlets.is_empty()
+            && self.removed_policies.is_empty()
+            && self.removed_outlets.is_empty()
+            && self.removed_inlets.is_empty()
+            && self.ignored_trust_contexts.is_empty()
+    }
+}
+
+/// Remove the entries of a section of a configuration which are invalid, and return their errors.
+/// The entries of `trust_contexts` are named by key and the entries of the other sections by index
+fn invalid_entries<T: serde::de::DeserializeOwned>(
+    value: &mut serde_yaml::Value,
+    section: &str,
+) -> Vec<String> {
+    let mut errors = vec![];
+    match value.get_mut(section) {
+        Some(serde_yaml::Value::Sequence(entries)) => {
+            let mut i = 0;
+            entries.retain(|entry| {
+                let result = serde_yaml::from_value::<T>(entry.clone());
+                if let Err(e) = &result {
+                    errors.push(format!("{section}[{i}]: {e}"));
+                }
+                i += 1;
+                result.is_ok()
+            });
+        }
+        Some(serde_yaml::Value::Mapping(entries)) => {
+            let invalid: Vec<serde_yaml::Value> = entries
+                .iter()
+                .filter_map(|(key, entry)| {
+                    let e = serde_yaml::from_value::<T>(entry.clone()).err()?;
+                    let key = key.as_str().map(str::to_string).unwrap_or_default();
+                    errors.push(format!("{section}.{key}: {e}"));
+                    Some(key.into())
+                })
+                .collect();
+            for key in invalid {
+                entries.remove(&key);
+            }
+        }
+        _ => {}
+    }
+    errors
+}
+
+/// Return the aliases which appear several times
+fn duplicates<'a>(aliases: impl Iterator<Item = &'a String>) -> BTreeSet<&'a String> {
+    let mut seen = BTreeSet::new();
+    aliases.filter(|alias| !seen.insert(*alias)).collect()
+}
+
+fn invalid_config(message: String) -> ockam_core::Error {
+    ockam_core::Error::new(
+        Origin::Node,
+        Kind::Invalid,
+        format!("Invalid node startup configuration: {message}"),
+    )
+}
+
+impl NodeManager {
+    /// Report the node as starting, in its status, until its startup configuration is loaded
+    /// by [`NodeManagerWorker::load_startup_config`], so that the clients can wait for its
+    /// portals to be created
+    pub fn set_starting(&mut self) {
+        self.starting = true
+    }
+
+    /// Return true if the startup configuration of the node is not loaded yet
+    pub fn is_starting(&self) -> bool {
+        self.starting
+    }
+
+    /// Return the configuration recreating the current trust contexts, policies, outlets and
+    /// inlets of the node when it starts.
+    ///
+    /// The default trust context is not part of the configuration since it is given when the node
+    /// is created. Unless `inline_credentials` is true, the credentials of the trust contexts are
+    /// referenced by their name in the cli state, so that the configuration can be shared
+    pub async fn export_startup_config(
+        &self,
+        inline_credentials: bool,
+    ) -> Result<NodeStartupConfig> {
+        let mut trust_contexts = BTreeMap::new();
+        for (name, config) in &self.trust_context_configs {
+            let config = if inline_credentials {
+                config.clone()
+            } else {
+                config.with_credential_names()?
+            };
+            trust_contexts.insert(name.clone(), config);
+        }
+
+        let policies = PolicySnapshot::take(self.policies.as_ref())
+            .await?
+            .iter()
+            .map(|(resource, action, expression, description)| PolicyConfig {
+                resource: resource.as_str().to_string(),
+                action: action.as_str().to_string(),
+                expression: expression.clone(),
+                description: description.map(|d| d.to_string()),
+            })
+            .collect();
+
+        let mut outlets = vec![];
+        for (alias, info) in &self.registry.outlets {
+            let worker_addr = info.worker_addr.address().to_string();
+            outlets.push(OutletConfig {
+                alias: alias.clone(),
+                to: info
+                    .tcp_addr
+                    .parse()
+                    .map_err(|_| not_exportable("outlet", alias, &info.tcp_addr))?,
+                from: (worker_addr != *alias).then_some(worker_addr),
+                trust_context: info.trust_context_name.clone(),
+                idle_timeout_secs: info.idle_timeout.map(|d| d.as_secs()),
+                require_credential: info.require_credential,
+                max_connections: info.max_connections,
+                tags: info.tags.clone(),
+            });
+        }
+
+        let mut inlets = vec![];
+        for (alias, info) in &self.registry.inlets {
+            inlets.push(InletConfig {
+                alias: alias.clone(),
+                from: socket_addr(&info.bind_addr, "inlet", alias)?,
+                to: info.outlet_addr.clone(),
+            });
+        }
+
+        Ok(NodeStartupConfig {
+            trust_contexts,
+            policies,
+            outlets,
+            inlets,
+        })
+    }
+}
+
+fn socket_addr(address: &str, portal: &str, alias: &str) -> Result<SocketAddr> {
+    address
+        .parse()
+        .map_err(|_| not_exportable(portal, alias, address))
+}
+
+fn not_exportable(portal: &str, alias: &str, address: &str) -> ockam_core::Error {
+    ockam_core::Error::new(
+        Origin::Node,
+        Kind::Invalid,
+        format!("The {portal} {alias} can't be exported, {address} is not a socket address"),
+    )
+}
+
+impl NodeManagerWorker {
+    pub(super) async fn export_startup_config(
+        &self,
+        req: &Request,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder<StartupConfigFile>, ResponseBuilder<Error>> {
+        let request: ExportStartupConfig = dec.decode()?;
+        let node_manager = self.node_manager.read().await;
+        let config = node_manager
+            .export_startup_config(request.inline_credentials)
+            .await?;
+        let contents =
+            serde_yaml::to_string(&config).map_err(|e| ApiError::generic(&e.to_string()))?;
+        Ok(Response::ok(req.id()).body(StartupConfigFile::new(contents)))
+    }
+
+    /// Load the startup configuration of the node, if it has one, and apply it.
+    /// Return true if a configuration was found.
+    ///
+    /// The node isn't reported as starting anymore once this function returns, even if the
+    /// configuration couldn't be applied
+    pub async fn load_startup_config(&mut self, ctx: &Context) -> Result<bool> {
+        let result = self.load_startup_config_impl(ctx).await;
+        self.node_manager.write().await.starting = false;
+        result
+    }
+
+    async fn load_startup_config_impl(&mut self, ctx: &Context) -> Result<bool> {
+        let path = self.startup_config_path().await?;
+        match NodeStartupConfig::load(&path)? {
+            Some(config) => {
+                info!(path = %path.display(), "applying the node startup configuration");
+                self.apply_startup_config(ctx, &config).await?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Create the trust contexts, policies, outlets and inlets of a startup configuration
+    pub async fn apply_startup_config(
+        &mut self,
+        ctx: &Context,
+        config: &NodeStartupConfig,
+    ) -> Result<()> {
+        {
+            let mut node_manager = self.node_manager.write().await;
+            for (name, trust_context) in &config.trust_contexts {
+                node_manager.add_trust_context(name, trust_context).await?;
+            }
+            for policy in &config.policies {
+                node_manager
+                    .policies
+                    .set_policy_with_meta(
+                        &Resource::new(policy.resource.as_str()),
+                        &Action::new(policy.action.as_str()),
+                        &policy.expression,
+                        policy.description.as_deref(),
+                    )
+                    .await?;
+            }
+            let specs = config
+                .outlets
+                .iter()
+                .map(|outlet| {
+                    OutletSpec::new(
+                        outlet.to.to_string(),
+                        outlet.from.clone().unwrap_or_else(|| outlet.alias.clone()),
+                    )
+                    .with_alias(outlet.alias.clone())
+                    .reachable_from_default_secure_channel(true)
+                    .with_trust_context_name(outlet.trust_context.clone())
+                    .with_idle_timeout(outlet.idle_timeout_secs.map(Duration::from_secs))
+                    .with_require_credential(outlet.require_credential)
+                    .with_max_connections(outlet.max_connections)
+                    .with_tags(outlet.tags.clone())
+                })
+                .collect();
+            // all the outlets are created before reporting the ones which failed
+            let failures: Vec<String> = node_manager
+                .create_outlets(ctx, specs)
+                .await
+                .into_iter()
+                .zip(config.outlets.iter())
+                .filter_map(|(result, outlet)| {
+                    result
+                        .err()
+                        .map(|e| format!("outlet {}: {}", outlet.alias, e))
+                })
+                .collect();
+            if !failures.is_empty() {
+                return Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::Internal,
+                    format!("Failed to create the outlets, {}", failures.join(", ")),
+                ));
+            }
+        }
+
+        for inlet in &config.inlets {
+            let mut req =
+                CreateInlet::to_node(inlet.from, inlet.to.clone(), route![], route![], None);
+            req.set_alias(inlet.alias.clone());
+            self.create_inlet_impl(Id::fresh(), req, ctx)
+                .await
+                .map_err(|e| {
+                    let message = e
+                        .into_parts()
+                        .1
+                        .and_then(|e| e.message().map(|m| m.to_string()))
+                        .unwrap_or_else(|| "unknown error".to_string());
+                    ockam_core::Error::new(
+                        Origin::Node,
+                        Kind::Internal,
+                        format!("Failed to create the inlet {}: {message}", inlet.alias),
+                    )
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Watch the startup configuration file of the node, checking it at each interval, and
+    /// apply its changes while the node is running.
+    ///
+    /// The changes are computed against the last configuration applied from the file, so the
+    /// policies and portals created with the commands are left untouched. An edit which can't
+    /// be parsed, is invalid or can't be applied is rejected entirely, and the node keeps running
+    /// with the last valid configuration.
+    /// The file is watched until the node manager is shut down or dropped
+    pub async fn watch_startup_config(&self, ctx: &Context, interval: Duration) -> Result<()> {
+        let path = self.startup_config_path().await?;
+        let ctx = ctx
+            .new_detached(
+                Address::random_tagged("StartupConfigWatcher.ctx"),
+                DenyAll,
+                AllowAll,
+            )
+            .await?;
+        let mut contents = std::fs::read_to_string(&path).ok();
+        let mut applied = contents
+            .as_deref()
+            .and_then(|contents| NodeStartupConfig::parse(contents).ok())
+            .unwrap_or_default();
+        // the task doesn't keep the node manager alive, since the node manager owns the task
+        let node_manager = Arc::downgrade(&self.node_manager);
+        let request_timeout = self.request_timeout;
+        info!(path = %path.display(), "watching the node startup configuration");
+        let watch = async move {
+            loop {
+                ockam_node::tokio::time::sleep(interval).await;
+                // a removed file is not a change, the node keeps its configuration
+                let new_contents = match std::fs::read_to_string(&path) {
+                    Ok(new_contents) => new_contents,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(e) => {
+                        warn!(path = %path.display(), %e, "failed to read the node startup configuration");
+                        continue;
+                    }
+                };
+                if contents.as_ref() == Some(&new_contents) {
+                    continue;
+                }
+                let mut worker = match node_manager.upgrade() {
+                    Some(node_manager) => NodeManagerWorker {
+                        node_manager,
+                        request_timeout,
+                    },
+                    None => break,
+                };
+                if let Err(e) = worker
+                    .reload_startup_config(&ctx, &mut applied, &new_contents)
+                    .await
+                {
+                    warn!(path = %path.display(), %e, "the node startup configuration was not applied");
+                }
+                contents = Some(new_contents);
+            }
+        };
+        self.node_manager
+            .write()
+            .await
+            .background_tasks
+            .spawn(watch);
+        Ok(())
+    }
+
+    /// Apply a new version of the startup configuration, given the configuration applied
+    /// previously, which is replaced by the new one when it is applied.
+    ///
+    /// Nothing is applied if the new configuration is malformed or invalid. If one of the
+    /// changes fails, the previous configuration is restored and `applied` is left unchanged
+    pub async fn reload_startup_config(
+        &mut self,
+        ctx: &Context,
+        applied: &mut NodeStartupConfig,
+        contents: &str,
+    ) -> Result<()> {
+        let config = NodeStartupConfig::parse(contents)?;
+        let errors = config.semantic_errors();
+        if !errors.is_empty() {
+            return Err(invalid_config(errors.join(", ")));
+        }
+        let changes = applied.changes(&config);
+        if !changes.is_empty() {
+            info!("applying the changes of the node startup configuration");
+            if let Err(e) = self.apply_startup_config_changes(ctx, &changes).await {
+                warn!(%e, "restoring the previous node startup configuration");
+                if let Err(e) = self.restore_startup_config(ctx, applied, &changes).await {
+                    warn!(%e, "failed to restore the previous node startup configuration");
+                }
+                return Err(e);
+            }
+        }
+        *applied = config;
+        Ok(())
+    }
+
+    /// Create the added policies and portals, then delete the removed ones.
+    /// The modified portals are deleted before being created again.
+    ///
+    /// The changes are applied until one of them fails, see `restore_startup_config` to undo them
+    pub async fn apply_startup_config_changes(
+        &mut self,
+        ctx: &Context,
+        changes: &StartupConfigChanges,
+    ) -> Result<()> {
+        for name in &changes.ignored_trust_contexts {
+            warn!(%name, "the trust context was modified or removed, the node must be restarted to take it into account");
+        }
+        for name in changes.added.trust_contexts.keys() {
+            info!(%name, "adding a trust context from the startup configuration");
+        }
+        for policy in &changes.added.policies {
+            info!(resource = %policy.resource, action = %policy.action, "setting a policy from the startup configuration");
+        }
+        for outlet in &changes.added.outlets {
+            info!(alias = %outlet.alias, to = %outlet.to, "creating an outlet from the startup configuration");
+        }
+        for inlet in &changes.added.inlets {
+            info!(alias = %inlet.alias, from = %inlet.from, "creating an inlet from the startup configuration");
+        }
+        {
+            let mut node_manager = self.node_manager.write().await;
+            for inlet in &changes.added.inlets {
+                if changes.removed_inlets.contains(&inlet.alias) {
+                    node_manager.delete_inlet(&inlet.alias).await?;
+                }
+            }
+            for outlet in &changes.added.outlets {
+                if changes.removed_outlets.contains(&outlet.alias) {
+                    node_manager.delete_outlet(&outlet.alias).await?;
+                }
+            }
+        }
+        self.apply_startup_config(ctx, &changes.added).await?;
+
+        let mut node_manager = self.node_manager.write().await;
+        for alias in &changes.removed_inlets {
+            if !changes.added.inlets.iter().any(|i| &i.alias == alias) {
+                info!(%alias, "deleting an inlet removed from the startup configuration");
+                node_manager.delete_inlet(alias).await?;
+            }
+        }
+        for alias in &changes.removed_outlets {
+            if !changes.added.outlets.iter().any(|o| &o.alias == alias) {
+                info!(%alias, "deleting an outlet removed from the startup configuration");
+                node_manager.delete_outlet(alias).await?;
+            }
+        }
+        for (resource, action) in &changes.removed_policies {
+            info!(%resource, %action, "deleting a policy removed from the startup configuration");
+            node_manager
+                .policies
+                .del_policy(
+                    &Resource::new(resource.as_str()),
+                    &Action::new(action.as_str()),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Restore the trust contexts, policies and portals touched by some changes, which were
+    /// partially applied, to their state in the previous configuration
+    async fn restore_startup_config(
+        &mut self,
+        ctx: &Context,
+        previous: &NodeStartupConfig,
+        changes: &StartupConfigChanges,
+    ) -> Result<()> {
+        let touched_inlets: BTreeSet<&String> = changes
+            .removed_inlets
+            .iter()
+            .chain(changes.added.inlets.iter().map(|i| &i.alias))
+            .collect();
+        let touched_outlets: BTreeSet<&String> = changes
+            .removed_outlets
+            .iter()
+            .chain(changes.added.outlets.iter().map(|o| &o.alias))
+            .collect();
+        let touched_policies: BTreeSet<(&String, &String)> = changes
+            .removed_policies
+            .iter()
+            .map(|(r, a)| (r, a))
+            .chain(
+                changes
+                    .added
+                    .policies
+                    .iter()
+                    .map(|p| (&p.resource, &p.action)),
+            )
+            .collect();
+        let restored = NodeStartupConfig {
+            trust_contexts: BTreeMap::new(),
+            policies: previous
+                .policies
+                .iter()
+                .filter(|p| touched_policies.contains(&(&p.resource, &p.action)))
+                .cloned()
+                .collect(),
+            outlets: previous
+                .outlets
+                .iter()
+                .filter(|o| touched_outlets.contains(&o.alias))
+                .cloned()
+                .collect(),
+            inlets: previous
+                .inlets
+                .iter()
+                .filter(|i| touched_inlets.contains(&i.alias))
+                .cloned()
+                .collect(),
+        };
+        {
+            let mut node_manager = self.node_manager.write().await;
+            for alias in touched_inlets {
+                node_manager.delete_inlet(alias).await?;
+            }
+            for alias in touched_outlets {
+                node_manager.delete_outlet(alias).await?;
+            }
+            for (resource, action) in touched_policies {
+                node_manager
+                    .policies
+                    .del_policy(
+                        &Resource::new(resource.as_str()),
+                        &Action::new(action.as_str()),
+                    )
+                    .await?;
+            }
+            for name in changes.added.trust_contexts.keys() {
+                if node_manager.trust_contexts.contains_key(name) {
+                    node_manager.remove_trust_context(name)?;
+                }
+            }
+        }
+        self.apply_startup_config(ctx, &restored).await
+    }
+
+    async fn startup_config_path(&self) -> Result<PathBuf> {
+        let node_manager = self.node_manager.read().await;
+        Ok(node_manager
+            .cli_state
+            .nodes
+            .get(&node_manager.node_name)?
+            .startup_config_path())
+    }
+}
+
+/// Policy expressions are written with the same syntax as in the `policy` commands
+mod expression {
+    use ockam_abac::Expr;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(expr: &Expr, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&expr.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Expr, D::Error> {
+        let s = String::deserialize(d)?;
+        match ockam_abac::parse(&s) {
+            Ok(Some(expr)) => Ok(expr),
+            Ok(None) => Err(de::Error::custom("the policy expression is empty")),
+            Err(e) => Err(de::Error::custom(format!(
+                "invalid policy expression `{s}`: {e}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ockam::compat::tokio;
+    use ockam::compat::tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use ockam_node::compat::asynchronous::RwLock;
+    use ockam_node::Context;
+    use ockam_transport_tcp::TcpInletOptions;
+
+    use std::str::FromStr;
+
+    use crate::config::cli::{
+        CredentialIssuerConfig, CredentialRetrieverConfig, TrustAuthorityConfig,
+    };
+    use serde_json::Value;
+
+    use crate::json_schema::validate;
+    use crate::util::test_utils::start_manager_for_tests;
+
+    use super::*;
+
+    const CONFIG: &str = r#"
+trust_contexts:
+  project-2:
+    id: project-2-id
+policies:
+  - resource: db
+    action: handle_message
+    expression: (= subject.component "web")
+    description: only the web server can access the database
+outlets:
+  - alias: db
+    to: 127.0.0.1:5432
+    trust_context: project-2
+    idle_timeout_secs: 300
+    max_connections: 10
+    tags:
+      service: postgres
+inlets:
+  - alias: db-inlet
+    from: 127.0.0.1:0
+    to: /service/db
+"#;
+
+    #[test]
+    fn parse_errors_name_the_field_and_the_line() {
+        let config = "outlets:\n  - alias: db\n    to: not-an-address\n";
+        let error = NodeStartupConfig::parse(config).unwrap_err().to_string();
+        assert!(error.contains("outlets[0].to"), "{error}");
+        assert!(error.contains("line 3"), "{error}");
+
+        let config =
+            "policies:\n  - resource: db\n    action: handle_message\n    expression: (= a\n";
+        let error = NodeStartupConfig::parse(config).unwrap_err().to_string();
+        // invalid expressions are reported at the line of their policy
+        assert!(error.contains("policies[0]"), "{error}");
+        assert!(
+            error.contains("invalid policy expression `(= a`"),
+            "{error}"
+        );
+        assert!(error.contains("line 2"), "{error}");
+
+        let config = "outlet:\n  - alias: db\n";
+        let error = NodeStartupConfig::parse(config).unwrap_err().to_string();
+        assert!(error.contains("unknown field `outlet`"), "{error}");
+    }
+
+    #[test]
+    fn validate_a_config() {
+        assert!(NodeStartupConfig::validate(CONFIG).is_empty());
+
+        let errors = NodeStartupConfig::validate("outlets: [\n");
+        assert_eq!(errors.len(), 1, "{errors:?}");
+
+        // all the invalid expressions are reported, with the errors of the rest of the config
+        let config = r#"
+policies:
+  - resource: db
+    action: handle_message
+    expression: (= a
+  - resource: web
+    action: handle_message
+    expression: (and
+outlets:
+  - alias: db
+    to: 127.0.0.1:5432
+  - alias: web
+    to: not-an-address
+"#;
+        let errors = NodeStartupConfig::validate(config);
+        assert_eq!(errors.len(), 3, "{errors:?}");
+        assert!(
+            errors[0].starts_with("policies[0].expression"),
+            "{errors:?}"
+        );
+        assert!(
+            errors[1].starts_with("policies[1].expression"),
+            "{errors:?}"
+        );
+        assert!(errors[2].starts_with("outlets[1]"), "{errors:?}");
+        assert!(errors[2].contains("invalid socket address"), "{errors:?}");
+
+        // all the invalid entries are reported, not only the first one
+        let config = r#"
+outlets:
+  - alias: db
+    to: not-an-address
+  - alias: web
+    to: 127.0.0.1:8080
+    from: 127.0.0.1:80
+  - alias: cache
+    to: 127.0.0.1:6379
+  - alias: cache
+    to: 127.0.0.1:6380
+inlets:
+  - alias: db-inlet
+    from: 127.0.0.1:15432
+"#;
+        let errors = NodeStartupConfig::validate(config);
+        assert_eq!(errors.len(), 4, "{errors:?}");
+        assert!(errors[0].starts_with("outlets[0]: "), "{errors:?}");
+        assert!(errors[0].contains("invalid socket address"), "{errors:?}");
+        assert!(errors[1].starts_with("outlets[1]: "), "{errors:?}");
+        assert!(errors[1].contains("unknown field `from`"), "{errors:?}");
+        assert!(errors[2].starts_with("inlets[0]: "), "{errors:?}");
+        assert!(errors[2].contains("missing field `to`"), "{errors:?}");
+        assert_eq!(errors[3], "the alias cache is used by several outlets");
+
+        let errors =
+            NodeStartupConfig::validate("outlets:\n  - alias: db\n    to: not-an-address\n");
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(errors[0].starts_with("outlets[0]"), "{errors:?}");
+    }
+
+    #[test]
+    fn validate_the_semantics_of_a_config() {
+        let config = r#"
+policies:
+  - resource: db
+    action: handle_message
+    expression: "true"
+  - resource: db
+    action: handle_message
+    expression: "false"
+outlets:
+  - alias: db
+    to: 127.0.0.1:5432
+  - alias: db
+    to: 127.0.0.1:5433
+  - alias: cache
+    to: 127.0.0.1:5432
+    trust_context: project-2
+  - alias: loop
+    to: 127.0.0.1:15432
+  - alias: closed
+    to: 127.0.0.1:6379
+    max_connections: 0
+inlets:
+  - alias: db-inlet
+    from: 127.0.0.1:15432
+    to: /service/db
+  - alias: db-inlet
+    from: 127.0.0.1:0
+    to: /service/db
+"#;
+        let errors = NodeStartupConfig::validate(config);
+        assert_eq!(
+            errors,
+            vec![
+                "the policy of the resource db for the action handle_message is declared several times",
+                "the alias db is used by several outlets",
+                "the outlet cache forwards to 127.0.0.1:5432, like another outlet",
+                "the outlet cache uses the trust context project-2, which is not declared",
+                "the outlet loop forwards to the inlet db-inlet of the node, this would create a loop",
+                "the outlet closed accepts a maximum of 0 connections, it must be at least 1",
+                "the alias db-inlet is used by several inlets",
+            ]
+        );
+    }
+
+    #[test]
+    fn serialize_and_parse_a_config() {
+        let config = NodeStartupConfig::parse(CONFIG).unwrap();
+        let serialized = serde_yaml::to_string(&config).unwrap();
+        let parsed = NodeStartupConfig::parse(&serialized).unwrap();
+        assert_eq!(serde_yaml::to_string(&parsed).unwrap(), serialized);
+        assert_eq!(parsed.outlets[0].idle_timeout_secs, Some(300));
+        assert_eq!(parsed.outlets[0].max_connections, Some(10));
+        assert_eq!(parsed.outlets[0].tags["service"], "postgres");
+        assert_eq!(
+            parsed.policies[0].expression.to_string(),
+            r#"(= subject.component "web")"#
+        );
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn load_a_startup_config(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut worker = handler.node_manager_worker.clone();
+
+        // nothing is done if the node has no startup config
+        handler.node_manager.write().await.set_starting();
+        assert!(!worker.load_startup_config(context).await?);
+        assert!(!handler.node_manager.read().await.is_starting());
+
+        let node_name = handler.node_manager.read().await.node_name.clone();
+        let path = handler
+            .cli_state
+            .nodes
+            .get(&node_name)?
+            .startup_config_path();
+        std::fs::write(path, CONFIG).unwrap();
+        assert!(worker.load_startup_config(context).await?);
+
+        let node_manager = handler.node_manager.read().await;
+        assert_eq!(node_manager.trust_context_names(), vec!["project-2"]);
+        let policy = node_manager
+            .policies
+            .get_policy(&Resource::new("db"), &Action::new("handle_message"))
+            .await?;
+        assert_eq!(
+            policy.map(|p| p.to_string()),
+            Some(r#"(= subject.component "web")"#.to_string())
+        );
+        let description = node_manager
+            .policies
+            .get_policy_meta(&Resource::new("db"), &Action::new("handle_message"))
+            .await?;
+        assert_eq!(
+            description.as_deref(),
+            Some("only the web server can access the database")
+        );
+        let outlets = node_manager.list_outlets().list;
+        assert_eq!(outlets.len(), 1);
+        assert_eq!(outlets[0].alias, "db");
+        assert_eq!(outlets[0].tcp_addr, "127.0.0.1:5432");
+        assert_eq!(outlets[0].worker_addr, "0#db");
+        assert_eq!(outlets[0].idle_timeout_secs, Some(300));
+        assert_eq!(outlets[0].max_connections, Some(10));
+        let outlet = node_manager.registry.outlets.get("db").unwrap();
+        assert_eq!(outlet.trust_context_name.as_deref(), Some("project-2"));
+        assert!(node_manager.registry.inlets.contains_key("db-inlet"));
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 10_000)]
+    async fn export_and_reload_a_startup_config(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut worker = handler.node_manager_worker.clone();
+        let config = NodeStartupConfig::parse(CONFIG)?;
+        worker.apply_startup_config(context, &config).await?;
+
+        let exported = handler
+            .node_manager
+            .read()
+            .await
+            .export_startup_config(false)
+            .await?;
+        let yaml = serde_yaml::to_string(&exported).unwrap();
+        assert_eq!(exported.trust_contexts.len(), 1);
+        assert_eq!(exported.outlets.len(), 1);
+        assert_eq!(exported.outlets[0].idle_timeout_secs, Some(300));
+        assert_eq!(exported.outlets[0].max_connections, Some(10));
+        assert_eq!(exported.outlets[0].tags["service"], "postgres");
+        // the inlet is exported with the port it listens at
+        assert_ne!(exported.inlets[0].from.port(), 0);
+        assert_eq!(exported.inlets[0].to.to_string(), "/service/db");
+        // the default policies of the node are exported with the configured ones
+        let db = exported
+            .policies
+            .iter()
+            .find(|p| p.resource == "db")
+            .unwrap();
+        assert_eq!(
+            db.description.as_deref(),
+            Some("only the web server can access the database")
+        );
+
+        // the exported configuration recreates the same state
+        {
+            let mut node_manager = handler.node_manager.write().await;
+            node_manager.delete_inlet("db-inlet").await?;
+            node_manager.delete_outlet("db").await?;
+            node_manager.remove_trust_context("project-2")?;
+            for resource in ["db", "db-inlet"] {
+                node_manager
+                    .policies
+                    .del_policy(&Resource::new(resource), &Action::new("handle_message"))
+                    .await?;
+            }
+            let removed = node_manager.export_startup_config(false).await?;
+            assert!(removed.outlets.is_empty() && removed.inlets.is_empty());
+            assert!(removed.trust_contexts.is_empty());
+        }
+        worker
+            .apply_startup_config(context, &NodeStartupConfig::parse(&yaml)?)
+            .await?;
+        let reloaded = handler
+            .node_manager
+            .read()
+            .await
+            .export_startup_config(false)
+            .await?;
+        assert_eq!(serde_yaml::to_string(&reloaded).unwrap(), yaml);
+        context.stop().await
+    }
+
+    #[test]
+    fn parse_and_check_a_port_range_outlet() {
+        let target: OutletTarget = "127.0.0.1:5000-5010".parse().unwrap();
+        assert_eq!(target.addresses().count(), 11);
+        assert_eq!(target.to_string(), "127.0.0.1:5000-5010");
+        let target: OutletTarget = "[::1]:5000-5010".parse().unwrap();
+        assert_eq!(target.to_string(), "[::1]:5000-5010");
+        assert!("localhost:5000-5010".parse::<OutletTarget>().is_err());
+        assert!("127.0.0.1:5010-5000".parse::<OutletTarget>().is_err());
+
+        let config = r#"
+outlets:
+  - alias: web
+    to: 127.0.0.1:8000-8010
+  - alias: admin
+    to: 127.0.0.1:8010
+  - alias: loop
+    to: 127.0.0.1:15000-15010
+inlets:
+  - alias: inlet
+    from: 127.0.0.1:15005
+    to: /service/web
+"#;
+        assert_eq!(
+            NodeStartupConfig::validate(config),
+            vec![
+                "the outlet admin forwards to 127.0.0.1:8010, like another outlet",
+                "the outlet loop forwards to the inlet inlet of the node, this would create a loop",
+            ]
+        );
+    }
+
+    #[ockam_macros::test(timeout = 10_000)]
+    async fn export_and_reload_a_port_range_outlet(context: &mut Context) -> ockam::Result<()> {
+        // echo server listening on one of the ports of the range
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let (mut read_half, mut write_half) = stream.split();
+                    let _ = tokio::io::copy(&mut read_half, &mut write_half).await;
+                });
+            }
+        });
+        let first = port.saturating_sub(1);
+
+        let handler = start_manager_for_tests(context).await?;
+        // the inlet of the test connects to the outlet without a secure channel
+        handler.node_manager.write().await.enable_credential_checks = false;
+        let mut worker = handler.node_manager_worker.clone();
+        let config = format!("outlets:\n  - alias: range\n    to: 127.0.0.1:{first}-{port}\n");
+        worker
+            .apply_startup_config(context, &NodeStartupConfig::parse(&config)?)
+            .await?;
+
+        let exported = handler
+            .node_manager
+            .read()
+            .await
+            .export_startup_config(false)
+            .await?;
+        assert_eq!(
+            exported.outlets[0].to.to_string(),
+            format!("127.0.0.1:{first}-{port}")
+        );
+        handler
+            .node_manager
+            .write()
+            .await
+            .delete_outlet("range")
+            .await?;
+        let yaml = serde_yaml::to_string(&exported).unwrap();
+        worker
+            .apply_startup_config(context, &NodeStartupConfig::parse(&yaml)?)
+            .await?;
+
+        // the connections of the reloaded outlet are forwarded to the requested port
+        let (inlet_address, _) = handler
+            .tcp
+            .create_inlet(
+                "127.0.0.1:0",
+                route!["range", port.to_string()],
+                TcpInletOptions::new(),
+            )
+            .await?;
+        let mut stream = tokio::net::TcpStream::connect(inlet_address).await.unwrap();
+        stream.write_all(b"hello").await.unwrap();
+        let mut buffer = [0u8; 5];
+        stream.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"hello");
+        context.stop().await
+    }
+
+    #[test]
+    fn compute_the_changes_of_a_config() {
+        let previous = NodeStartupConfig::parse(CONFIG).unwrap();
+        let config = r#"
+policies:
+  - resource: db
+    action: handle_message
+    expression: (= subject.component "web")
+    description: only the web server can access the database
+  - resource: cache
+    action: handle_message
+    expression: "true"
+outlets:
+  - alias: db
+    to: 127.0.0.1:5433
+    trust_context: project-2
+    idle_timeout_secs: 300
+    max_connections: 10
+"#;
+        let new = NodeStartupConfig::parse(config).unwrap();
+        assert!(previous.changes(&previous).is_empty());
+
+        let changes = previous.changes(&new);
+        assert!(changes.removed_policies.is_empty());
+        assert_eq!(changes.added.policies.len(), 1);
+        assert_eq!(changes.added.policies[0].resource, "cache");
+        // the modified outlet is created again
+        assert_eq!(changes.removed_outlets, vec!["db"]);
+        assert_eq!(changes.added.outlets.len(), 1);
+        assert_eq!(changes.added.outlets[0].to.to_string(), "127.0.0.1:5433");
+        assert_eq!(changes.removed_inlets, vec!["db-inlet"]);
+        assert!(changes.added.inlets.is_empty());
+        assert_eq!(changes.ignored_trust_contexts, vec!["project-2"]);
+    }
+
+    async fn outlet_aliases(node_manager: &RwLock<NodeManager>) -> Vec<String> {
+        let outlets = node_manager.read().await.list_outlets().list;
+        outlets.into_iter().map(|o| o.alias).collect()
+    }
+
+    /// Wait until the node has the expected outlets
+    async fn wait_for_outlets(node_manager: &RwLock<NodeManager>, expected: Vec<&str>) {
+        for _ in 0..100 {
+            if outlet_aliases(node_manager).await == expected {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("the outlets are not {expected:?}")
+    }
+
+    #[ockam_macros::test(timeout = 10_000)]
+    async fn edit_a_watched_startup_config(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let worker = handler.node_manager_worker.clone();
+        let node_name = handler.node_manager.read().await.node_name.clone();
+        let path = handler
+            .cli_state
+            .nodes
+            .get(&node_name)?
+            .startup_config_path();
+        std::fs::write(&path, "policies: []\n").unwrap();
+        worker
+            .watch_startup_config(context, Duration::from_millis(50))
+            .await?;
+
+        // an outlet added to the file is created
+        std::fs::write(&path, "outlets:\n  - alias: db\n    to: 127.0.0.1:5432\n").unwrap();
+        wait_for_outlets(&handler.node_manager, vec!["db"]).await;
+
+        // a malformed edit is rejected entirely
+        std::fs::write(
+            &path,
+            "outlets:\n  - alias: web\n    to: 127.0.0.1:8080\n  - alias: cache\n    to: not-an-address\n",
+        )
+        .unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(outlet_aliases(&handler.node_manager).await, vec!["db"]);
+
+        // an outlet removed from the file is deleted
+        std::fs::write(&path, "outlets:\n  - alias: web\n    to: 127.0.0.1:8080\n").unwrap();
+        wait_for_outlets(&handler.node_manager, vec!["web"]).await;
+
+        // the file is watched until the node manager is shut down
+        let mut node_manager = handler.node_manager.write().await;
+        assert_eq!(node_manager.background_tasks.tasks.len(), 2);
+        node_manager.shutdown(context).await;
+        assert!(node_manager.background_tasks.tasks.is_empty());
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 10_000)]
+    async fn a_config_which_cant_be_applied_is_rolled_back(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut worker = handler.node_manager_worker.clone();
+        let outlets = |node_manager: &NodeManager| -> Vec<(String, String)> {
+            node_manager
+                .list_outlets()
+                .list
+                .into_iter()
+                .map(|o| (o.alias, o.tcp_addr))
+                .collect()
+        };
+        let mut applied = NodeStartupConfig::default();
+        let config = r#"
+policies:
+  - resource: db
+    action: handle_message
+    expression: (= subject.component "web")
+outlets:
+  - alias: db
+    to: 127.0.0.1:5432
+"#;
+        worker
+            .reload_startup_config(context, &mut applied, config)
+            .await?;
+        let before = outlets(&*handler.node_manager.read().await);
+        assert_eq!(
+            before,
+            vec![("db".to_string(), "127.0.0.1:5432".to_string())]
+        );
+
+        // the inlet can't be created since its port is already used
+        let used = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = format!(
+            r#"
+policies:
+  - resource: web
+    action: handle_message
+    expression: "true"
+outlets:
+  - alias: db
+    to: 127.0.0.1:5433
+  - alias: web
+    to: 127.0.0.1:8080
+inlets:
+  - alias: web-inlet
+    from: {}
+    to: /service/web
+"#,
+            used.local_addr().unwrap()
+        );
+        assert!(worker
+            .reload_startup_config(context, &mut applied, &config)
+            .await
+            .is_err());
+
+        // the node and the applied configuration are left as they were
+        let node_manager = handler.node_manager.read().await;
+        assert_eq!(outlets(&node_manager), before);
+        assert!(node_manager.list_inlets().list.is_empty());
+        let db = Resource::new("db");
+        let handle_message = Action::new("handle_message");
+        assert!(node_manager
+            .policies
+            .get_policy(&db, &handle_message)
+            .await?
+            .is_some());
+        assert!(node_manager
+            .policies
+            .get_policy(&Resource::new("web"), &handle_message)
+            .await?
+            .is_none());
+        drop(node_manager);
+        assert_eq!(applied.outlets.len(), 1);
+        assert_eq!(applied.outlets[0].to.port(), 5432);
+        assert!(applied.inlets.is_empty());
+        context.stop().await
+    }
+
+    #[test]
+    fn the_credentials_are_referenced_by_name() {
+        let cli_state = crate::cli_state::CliState::test().unwrap();
+        let config = TrustContextConfig::new(
+            "id".to_string(),
+            Some(TrustAuthorityConfig::new(
+                "0123".to_string(),
+                Some(CredentialRetrieverConfig::FromCredentialName(
+                    "missing".to_string(),
+                )),
+            )),
+        );
+        // a reference to a credential which doesn't exist is an error
+        assert!(config.resolve_credential_names(&cli_state).is_err());
+        assert_eq!(config.with_credential_names().unwrap(), config);
+    }
+
+    #[test]
+    fn the_schema_validates_a_known_good_configuration() {
+        let config: Value = serde_yaml::from_str(CONFIG).unwrap();
+        assert_eq!(validate::<NodeStartupConfig>(&config), Ok(()));
+        assert!(NodeStartupConfig::parse(CONFIG).is_ok());
+    }
+
+    #[test]
+    fn the_schema_rejects_a_malformed_configuration() {
+        for (config, error) in [
+            ("outlets:\n  - alias: db\n    to: 5432\n", "/outlets/0/to"),
+            (
+                "outlets:\n  - alias: db\n    to: 127.0.0.1:5432\n    port: 5432\n",
+                "/outlets/0: Additional properties are not allowed ('port'",
+            ),
+            (
+                "inlets:\n  - alias: db-inlet\n    from: 127.0.0.1:0\n",
+                "/inlets/0: \"to\" is a required property",
+            ),
+            (
+                "trust_contexts:\n  project-2:\n    authority: {}\n",
+                "/trust_contexts/project-2",
+            ),
+        ] {
+            let value: Value = serde_yaml::from_str(config).unwrap();
+            let errors = validate::<NodeStartupConfig>(&value).unwrap_err();
+            assert!(errors.iter().any(|e| e.starts_with(error)), "{errors:?}");
+            assert!(NodeStartupConfig::parse(config).is_err(), "{config}");
+        }
+    }
+
+    #[test]
+    fn the_schema_matches_the_serialized_configuration() {
+        let mut config = NodeStartupConfig::parse(CONFIG).unwrap();
+        let issuer = CredentialIssuerConfig::new(
+            "0123".to_string(),
+            MultiAddr::from_str("/dnsaddr/localhost/tcp/4000/service/api").unwrap(),
+        );
+        let authority = TrustAuthorityConfig::new(
+            "0123".to_string(),
+            Some(CredentialRetrieverConfig::FromCredentialIssuer(issuer)),
+        );
+        config.trust_contexts.insert(
+            "with-authority".to_string(),
+            TrustContextConfig::new("id".to_string(), Some(authority)),
+        );
+        config.outlets[0].from = Some("outlet".to_string());
+        let value = serde_json::to_value(&config).unwrap();
+        assert_eq!(validate::<NodeStartupConfig>(&value), Ok(()));
+    }
+}
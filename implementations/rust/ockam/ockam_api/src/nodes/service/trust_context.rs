@@ -0,0 +1,269 @@
+use minicbor::Decoder;
+use ockam::identity::{Credential, CredentialsMemoryRetriever};
+use ockam::Result;
+use ockam_core::api::{Error, Request, Response, ResponseBuilder, Status};
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_identity::TrustContext;
+
+use crate::config::cli::CredentialRetrieverConfig;
+use crate::nodes::models::trust_context::{
+    TrustContextList, TrustContextStatus, UpdateTrustContextCredential,
+};
+
+use super::{NodeManager, NodeManagerWorker};
+
+impl NodeManager {
+    /// Return the default trust context of the node, if it has one, followed by its named
+    /// trust contexts
+    pub fn list_trust_contexts(&self) -> Vec<TrustContextStatus> {
+        let mut list = vec![];
+        if let Ok(trust_context) = self.trust_context() {
+            list.push(trust_context_status(None, trust_context));
+        }
+        for (name, trust_context) in self.trust_contexts.iter() {
+            list.push(trust_context_status(Some(name.clone()), trust_context));
+        }
+        list
+    }
+
+    /// Remove a named trust context.
+    /// The removal is refused if the policy of an outlet uses the trust context
+    pub fn remove_trust_context(&mut self, name: &str) -> Result<()> {
+        if !self.trust_contexts.contains_key(name) {
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::NotFound,
+                format!("Trust context {name} doesn't exist"),
+            ));
+        }
+        if let Some((alias, _)) = self
+            .registry
+            .outlets
+            .iter()
+            .find(|(_, info)| info.trust_context_name.as_deref() == Some(name))
+        {
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::Conflict,
+                format!(
+                    "Trust context {name} can't be deleted, it is used by the policy of the resource {alias}"
+                ),
+            ));
+        }
+        self.trust_contexts.remove(name);
+        self.trust_context_configs.remove(name);
+        Ok(())
+    }
+
+    /// Replace the credential presented for a trust context, the default trust context if no
+    /// name is given. The credential must be issued to the identity of the node by the
+    /// authority of the trust context.
+    ///
+    /// The secure channels which are already established keep the credential exchanged when
+    /// they were created, the new credential is presented by the secure channels created from now on
+    pub async fn update_trust_context_credential(
+        &mut self,
+        name: Option<&str>,
+        credential: Credential,
+    ) -> Result<()> {
+        let description = match name {
+            Some(name) => format!("trust context {name}"),
+            None => "default trust context".to_string(),
+        };
+        let authority = self.named_trust_context(name)?.authority().map_err(|_| {
+            ockam_core::Error::new(
+                Origin::Node,
+                Kind::Invalid,
+                format!("The {description} has no authority"),
+            )
+        })?;
+        let authority_identity = authority.identity().await?;
+        self.credentials()
+            .verify_credential(&self.identifier(), &[authority_identity], credential.clone())
+            .await
+            .map_err(|e| {
+                ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::Invalid,
+                    format!(
+                        "The credential doesn't verify against the authority of the {description}: {e}"
+                    ),
+                )
+            })?;
+        authority.replace_own_credential(Arc::new(CredentialsMemoryRetriever::new(
+            credential.clone(),
+        )));
+        if let Some(config) = name.and_then(|name| self.trust_context_configs.get_mut(name)) {
+            *config = config
+                .clone()
+                .with_own_credential(CredentialRetrieverConfig::FromMemory(credential))?;
+        }
+        info!(%description, "Replaced the credential of the trust context");
+        Ok(())
+    }
+}
+
+fn trust_context_status(name: Option<String>, trust_context: &TrustContext) -> TrustContextStatus {
+    let authority_identifier = trust_context
+        .authority()
+        .ok()
+        .map(|authority| authority.identifier().to_string());
+    TrustContextStatus::new(name, trust_context.id(), authority_identifier)
+}
+
+impl NodeManagerWorker {
+    pub(super) async fn list_trust_contexts(
+        &self,
+        req: &Request,
+    ) -> Result<ResponseBuilder<TrustContextList>, ResponseBuilder<Error>> {
+        let node_manager = self.node_manager.read().await;
+        let list = node_manager.list_trust_contexts();
+        Ok(Response::ok(req.id()).body(TrustContextList::new(list)))
+    }
+
+    pub(super) async fn update_trust_context_credential(
+        &self,
+        req: &Request,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder, ResponseBuilder<Error>> {
+        let request: UpdateTrustContextCredential = dec.decode()?;
+        let mut node_manager = self.node_manager.write().await;
+        match node_manager
+            .update_trust_context_credential(request.name.as_deref(), request.credential)
+            .await
+        {
+            Ok(()) => Ok(Response::ok(req.id())),
+            Err(e) => {
+                let status = match e.code().kind {
+                    Kind::NotFound => Status::NotFound,
+                    Kind::Invalid => Status::BadRequest,
+                    _ => Status::InternalServerError,
+                };
+                let err_body = Error::new(req.path()).with_message(e.to_string());
+                Err(Response::builder(req.id(), status).body(err_body))
+            }
+        }
+    }
+
+    pub(super) async fn delete_trust_context(
+        &self,
+        req: &Request,
+        name: &str,
+    ) -> Result<ResponseBuilder, ResponseBuilder<Error>> {
+        let mut node_manager = self.node_manager.write().await;
+        match node_manager.remove_trust_context(name) {
+            Ok(()) => Ok(Response::ok(req.id())),
+            Err(e) => {
+                let status = match e.code().kind {
+                    Kind::NotFound => Status::NotFound,
+                    Kind::Conflict => Status::Conflict,
+                    _ => Status::InternalServerError,
+                };
+                let err_body = Error::new(req.path()).with_message(e.to_string());
+                Err(Response::builder(req.id(), status).body(err_body))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ockam::identity::{CredentialData, IdentityIdentifier};
+    use ockam_core::errcode::Kind;
+    use ockam_node::Context;
+
+    use crate::config::cli::TrustContextConfig;
+    use crate::util::test_utils::start_manager_for_tests;
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn remove_trust_context(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+        let config = TrustContextConfig::new("project-2-id".to_string(), None);
+        node_manager.add_trust_context("project-2", &config).await?;
+
+        let names: Vec<Option<String>> = node_manager
+            .list_trust_contexts()
+            .into_iter()
+            .map(|tc| tc.name)
+            .collect();
+        assert!(names.contains(&Some("project-2".to_string())));
+
+        node_manager
+            .create_outlet_with_trust_context(
+                context,
+                "127.0.0.1:6001".to_string(),
+                "outlet-1".to_string(),
+                Some("first".to_string()),
+                false,
+                Some("project-2"),
+                None,
+            )
+            .await?;
+
+        // the trust context can't be removed while an outlet uses it
+        let error = node_manager.remove_trust_context("project-2").unwrap_err();
+        assert_eq!(error.code().kind, Kind::Conflict);
+        assert!(error.to_string().contains("first"));
+
+        node_manager.delete_outlet("first").await?;
+        node_manager.remove_trust_context("project-2")?;
+        assert!(node_manager.trust_context_names().is_empty());
+
+        let error = node_manager.remove_trust_context("project-2").unwrap_err();
+        assert_eq!(error.code().kind, Kind::NotFound);
+        drop(node_manager);
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn update_the_credential_of_a_trust_context(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let identities = handler.secure_channels.identities();
+        let issue = |issuer: IdentityIdentifier, role: &'static str| {
+            let credentials = identities.credentials();
+            let subject = handler.identifier.clone();
+            async move {
+                let data = CredentialData::builder(subject, issuer.clone())
+                    .with_attribute("role", role.as_bytes())
+                    .build()?;
+                credentials.issue_credential(&issuer, data).await
+            }
+        };
+        let mut node_manager = handler.node_manager.write().await;
+
+        // the authority of the test node is the node identity itself.
+        // A clone of the authority, as used by a secure channel listener, sees the new credential
+        let authority = node_manager.trust_context()?.authority()?.clone();
+        let credential = issue(handler.identifier.clone(), "admin").await?;
+        node_manager
+            .update_trust_context_credential(None, credential.clone())
+            .await?;
+        assert_eq!(
+            authority.credential(context, &handler.identifier).await?,
+            credential
+        );
+
+        // a credential issued by another identity is rejected and the current one is kept
+        let other = identities.identities_creation().create_identity().await?;
+        let forged = issue(other.identifier(), "forged").await?;
+        let error = node_manager
+            .update_trust_context_credential(None, forged)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code().kind, Kind::Invalid);
+        assert_eq!(
+            authority.credential(context, &handler.identifier).await?,
+            credential
+        );
+
+        let error = node_manager
+            .update_trust_context_credential(Some("unknown"), credential)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code().kind, Kind::NotFound);
+        drop(node_manager);
+        context.stop().await
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::BTreeSet;
+
+use ockam::Result;
+use ockam_core::api::{Error, Request, Response, ResponseBuilder};
+use ockam_core::Address;
+use ockam_node::Context;
+use tracing::{info, warn};
+
+use crate::nodes::models::workers::{WorkerList, WorkerStatus};
+use crate::nodes::registry::WorkerKind;
+
+use super::{NodeManager, NodeManagerWorker};
+
+impl NodeManager {
+    /// Return the workers running on the node with what they are used for.
+    ///
+    /// A worker started for an outlet, an inlet or a secure channel which is not in the registry
+    /// anymore is stale: its cleanup was missed and it can be stopped with [`Self::prune_stale_workers`]
+    pub async fn list_workers(&self, ctx: &Context) -> Result<Vec<WorkerStatus>> {
+        let workers = ctx
+            .list_workers()
+            .await?
+            .iter()
+            .map(|address| {
+                let (kind, stale) = match self.registry.worker_kind(address) {
+                    Some(kind) => (kind, false),
+                    None => match self.registry.started_workers.get(address) {
+                        Some(kind) => (*kind, true),
+                        None => (WorkerKind::Other, false),
+                    },
+                };
+                WorkerStatus::new(address.address(), kind.to_string(), stale)
+            })
+            .collect();
+        Ok(workers)
+    }
+
+    /// Stop the workers started for the outlets, inlets and secure channels which are not in the
+    /// registry anymore, and return them.
+    /// A worker which can't be stopped is logged and returned by the next calls
+    pub async fn prune_stale_workers(&mut self, ctx: &Context) -> Result<Vec<WorkerStatus>> {
+        let running: BTreeSet<Address> = ctx.list_workers().await?.into_iter().collect();
+        // the workers which were already stopped don't need to be tracked anymore
+        self.registry
+            .started_workers
+            .retain(|address, _| running.contains(address));
+        let stale: Vec<(Address, WorkerKind)> = self
+            .registry
+            .started_workers
+            .iter()
+            .filter(|(address, _)| self.registry.worker_kind(address).is_none())
+            .map(|(address, kind)| (address.clone(), *kind))
+            .collect();
+
+        let mut pruned = vec![];
+        for (address, kind) in stale {
+            let stopped = match kind {
+                WorkerKind::Outlet => self.tcp_transport.stop_outlet(address.clone()).await,
+                WorkerKind::Inlet => self.tcp_transport.stop_inlet(address.clone()).await,
+                _ => ctx.stop_worker(address.clone()).await,
+            };
+            match stopped {
+                Ok(()) => {
+                    info!(%address, %kind, "stopped a stale worker");
+                    self.registry.started_workers.remove(&address);
+                    pruned.push(WorkerStatus::new(address.address(), kind.to_string(), true));
+                }
+                Err(e) => warn!(%address, %kind, %e, "cannot stop a stale worker"),
+            }
+        }
+        Ok(pruned)
+    }
+}
+
+impl NodeManagerWorker {
+    pub(super) async fn list_workers(
+        &self,
+        ctx: &Context,
+        req: &Request,
+    ) -> Result<ResponseBuilder<WorkerList>, ResponseBuilder<Error>> {
+        let node_manager = self.node_manager.read().await;
+        let workers = node_manager.list_workers(ctx).await?;
+        Ok(Response::ok(req.id()).body(WorkerList::new(workers)))
+    }
+
+    pub(super) async fn prune_stale_workers(
+        &self,
+        ctx: &Context,
+        req: &Request,
+    ) -> Result<ResponseBuilder<WorkerList>, ResponseBuilder<Error>> {
+        let mut node_manager = self.node_manager.write().await;
+        let pruned = node_manager.prune_stale_workers(ctx).await?;
+        Ok(Response::ok(req.id()).body(WorkerList::new(pruned)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nodes::service::OutletSpec;
+    use crate::util::test_utils::start_manager_for_tests;
+
+    use super::*;
+
+    fn find<'a>(workers: &'a [WorkerStatus], address: &str) -> Option<&'a WorkerStatus> {
+        workers.iter().find(|w| w.addr == address)
+    }
+
+    /// The workers are removed from the list when they shut down, after they are stopped
+    async fn wait_until_stopped(
+        node_manager: &NodeManager,
+        context: &Context,
+        address: &str,
+    ) -> ockam::Result<()> {
+        while find(&node_manager.list_workers(context).await?, address).is_some() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        Ok(())
+    }
+
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn deleting_an_outlet_removes_its_worker(context: &mut Context) -> ockam::Result<()> {
+        let handler = start_manager_for_tests(context).await?;
+        let mut node_manager = handler.node_manager.write().await;
+        let specs = vec![
+            OutletSpec::new("127.0.0.1:5432", "db").with_alias("db"),
+            OutletSpec::new("127.0.0.1:5433", "leaked").with_alias("leaked"),
+        ];
+        for result in node_manager.create_outlets(context, specs).await {
+            result?;
+        }
+
+        let workers = node_manager.list_workers(context).await?;
+        let outlet = find(&workers, "db").unwrap();
+        assert_eq!(outlet.kind, "outlet");
+        assert!(!outlet.stale);
+
+        assert!(node_manager.delete_outlet("db").await?.is_some());
+        wait_until_stopped(&node_manager, context, "db").await?;
+        let workers = node_manager.list_workers(context).await?;
+        assert!(find(&workers, "leaked").is_some());
+
+        // an outlet removed from the registry without stopping its worker is stale
+        node_manager.registry.outlets.remove("leaked");
+        let workers = node_manager.list_workers(context).await?;
+        assert!(find(&workers, "leaked").unwrap().stale);
+        let pruned = node_manager.prune_stale_workers(context).await?;
+        assert_eq!(pruned, vec![WorkerStatus::new("leaked", "outlet", true)]);
+        wait_until_stopped(&node_manager, context, "leaked").await?;
+        assert!(node_manager.prune_stale_workers(context).await?.is_empty());
+
+        drop(node_manager);
+        context.stop().await
+    }
+}
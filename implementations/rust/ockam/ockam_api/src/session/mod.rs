@@ -49,6 +49,14 @@ impl Medic {
         }
     }
 
+    /// Create a medic checking sessions which were checked by a previous medic
+    fn with_sessions(sessions: Arc<Mutex<Sessions>>) -> Self {
+        Self {
+            sessions,
+            ..Self::new()
+        }
+    }
+
     pub async fn start(
         self,
         ctx: Context,
@@ -267,10 +275,24 @@ impl MedicHandle {
         Ok(())
     }
 
+    /// Start a new medic after `stop_medic`, checking the same sessions as before
+    pub async fn restart_medic(&mut self, ctx: &Context) -> Result<(), Error> {
+        let medic = Medic::with_sessions(self.sessions.clone());
+        let ctx = ctx.async_try_clone().await?;
+        let (handle, _) = medic.start(ctx).await?;
+        self.handle = handle;
+        Ok(())
+    }
+
     pub fn add_session(&self, session: Session) -> Key {
         let mut sessions = self.sessions.lock().unwrap();
         sessions.add(session)
     }
+
+    pub fn remove_session(&self, key: &Key) -> Option<Session> {
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.remove(key)
+    }
 }
 
 #[cfg(test)]
@@ -79,6 +79,19 @@ impl Sessions {
         self.map.get_mut(k)
     }
 
+    /// Stop monitoring a session
+    pub fn remove(&mut self, k: &Key) -> Option<Session> {
+        let session = self.map.remove(k);
+        if session.is_some() {
+            log::debug! {
+                target: "ockam_api::session",
+                key = %k,
+                "session removed"
+            }
+        }
+        session
+    }
+
     #[allow(unused)]
     pub fn iter(&self) -> impl Iterator<Item = (&Key, &Session)> + '_ {
         self.map.iter()
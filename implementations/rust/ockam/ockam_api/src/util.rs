@@ -1,5 +1,5 @@
 use miette::miette;
-use std::net::{SocketAddrV4, SocketAddrV6};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6, UdpSocket};
 
 use ockam::TcpTransport;
 use ockam_core::flow_control::FlowControlId;
@@ -265,6 +265,63 @@ pub fn addr_to_multiaddr<T: Into<Address>>(a: T) -> Option<MultiAddr> {
     route_to_multiaddr(&r)
 }
 
+/// Convert a socket address to a MultiAddr, for example `/ip4/127.0.0.1/tcp/4000`,
+/// optionally followed by a service: `/ip4/127.0.0.1/tcp/4000/service/echo`.
+pub fn socket_addr_to_multiaddr(addr: &SocketAddr, service: Option<&str>) -> Result<MultiAddr> {
+    let mut ma = MultiAddr::default();
+    match addr.ip() {
+        IpAddr::V4(ip) => ma.push_back(Ip4::new(ip))?,
+        IpAddr::V6(ip) => ma.push_back(Ip6::new(ip))?,
+    }
+    ma.push_back(Tcp::new(addr.port()))?;
+    if let Some(service) = service {
+        ma.push_back(Service::new(service))?;
+    }
+    Ok(ma)
+}
+
+/// Replace the IP of an address bound to all the interfaces, `0.0.0.0` or `::`,
+/// with the primary IP of the machine.
+///
+/// The address is returned unchanged if it is not unspecified or if the primary IP is unknown.
+pub fn replace_unspecified_ip(addr: SocketAddr) -> SocketAddr {
+    if !addr.ip().is_unspecified() {
+        return addr;
+    }
+    match primary_ip(addr.is_ipv6()) {
+        Some(ip) => SocketAddr::new(ip, addr.port()),
+        None => {
+            warn!(target: "ockam_api", %addr, "cannot determine the primary IP of the machine");
+            addr
+        }
+    }
+}
+
+/// Return the IP of the interface used by the machine to reach other hosts.
+///
+/// No packet is sent: connecting a UDP socket only selects the interface of the default route.
+pub fn primary_ip(ipv6: bool) -> Option<IpAddr> {
+    let (local, remote): (SocketAddr, SocketAddr) = if ipv6 {
+        (
+            (Ipv6Addr::UNSPECIFIED, 0).into(),
+            (
+                Ipv6Addr::new(0x2001, 0x4860, 0x4860, 0, 0, 0, 0, 0x8888),
+                53,
+            )
+                .into(),
+        )
+    } else {
+        (
+            (Ipv4Addr::UNSPECIFIED, 0).into(),
+            (Ipv4Addr::new(8, 8, 8, 8), 53).into(),
+        )
+    };
+    let socket = UdpSocket::bind(local).ok()?;
+    socket.connect(remote).ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_unspecified()).then_some(ip)
+}
+
 /// Tells whether the input MultiAddr references a local node or a remote node.
 ///
 /// This should be called before cleaning the MultiAddr.
@@ -331,6 +388,12 @@ pub fn local_worker(code: &Code) -> Result<bool> {
 
 #[cfg(test)]
 pub mod test_utils {
+    use core::time::Duration;
+    use std::net::SocketAddr;
+
+    use ockam::compat::tokio;
+    use ockam::compat::tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use ockam::compat::tokio::net::{TcpListener, TcpStream};
     use ockam::identity::SecureChannels;
     use ockam::Result;
     use ockam_core::compat::sync::Arc;
@@ -362,6 +425,7 @@ pub mod test_utils {
         pub tcp: TcpTransport,
         pub secure_channels: Arc<SecureChannels>,
         pub identifier: IdentityIdentifier,
+        pub node_manager_worker: NodeManagerWorker,
     }
 
     impl Drop for NodeManagerHandle {
@@ -450,7 +514,7 @@ pub mod test_utils {
         create_identity_zero(&secure_channels).await?;
 
         context
-            .start_worker(NODEMANAGER_ADDR, node_manager_worker)
+            .start_worker(NODEMANAGER_ADDR, node_manager_worker.clone())
             .await?;
 
         Ok(NodeManagerHandle {
@@ -459,9 +523,36 @@ pub mod test_utils {
             tcp: tcp.async_try_clone().await?,
             secure_channels: secure_channels.clone(),
             identifier: identity.identifier(),
+            node_manager_worker,
         })
     }
 
+    /// Start a TCP server echoing all the data it receives
+    pub async fn start_echo_server() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let (mut read_half, mut write_half) = stream.split();
+                    let _ = tokio::io::copy(&mut read_half, &mut write_half).await;
+                });
+            }
+        });
+        address
+    }
+
+    /// Return true if a new connection to the inlet echoes a message back in time
+    pub async fn reachable(inlet_address: SocketAddr) -> bool {
+        let mut stream = TcpStream::connect(inlet_address).await.unwrap();
+        stream.write_all(b"hello").await.unwrap();
+        let mut buffer = [0u8; 5];
+        matches!(
+            tokio::time::timeout(Duration::from_millis(500), stream.read_exact(&mut buffer)).await,
+            Ok(Ok(_))
+        ) && &buffer == b"hello"
+    }
+
     async fn create_identity_zero(secure_channels: &Arc<SecureChannels>) -> Result<Identity> {
         let identity_key_id = secure_channels
             .vault()
@@ -480,3 +571,40 @@ pub mod test_utils {
         Ok(identity)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::route;
+
+    #[test]
+    fn socket_addresses_as_multiaddr() -> Result<()> {
+        let addr: SocketAddr = "127.0.0.1:4000".parse().unwrap();
+        let ma = socket_addr_to_multiaddr(&addr, None)?;
+        assert_eq!(ma.to_string(), "/ip4/127.0.0.1/tcp/4000");
+        assert_eq!(
+            multiaddr_to_transport_route(&ma),
+            Some(route![(TCP, "127.0.0.1:4000")])
+        );
+
+        let ma = socket_addr_to_multiaddr(&addr, Some("echo"))?;
+        assert_eq!(ma.to_string(), "/ip4/127.0.0.1/tcp/4000/service/echo");
+
+        let addr: SocketAddr = "[::1]:4000".parse().unwrap();
+        let ma = socket_addr_to_multiaddr(&addr, Some("echo"))?;
+        assert_eq!(ma.to_string(), "/ip6/::1/tcp/4000/service/echo");
+        Ok(())
+    }
+
+    #[test]
+    fn only_unspecified_ips_are_replaced() {
+        let addr: SocketAddr = "127.0.0.1:4000".parse().unwrap();
+        assert_eq!(replace_unspecified_ip(addr), addr);
+
+        // the primary IP is not known on a machine without network
+        let addr: SocketAddr = "0.0.0.0:4000".parse().unwrap();
+        let replaced = replace_unspecified_ip(addr);
+        assert_eq!(replaced.port(), 4000);
+        assert_eq!(replaced.ip().is_unspecified(), primary_ip(false).is_none());
+    }
+}
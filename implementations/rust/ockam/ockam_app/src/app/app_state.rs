@@ -1,41 +1,104 @@
-use std::sync::Arc;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Once};
+use std::time::Duration;
 
 use miette::{miette, IntoDiagnostic};
-use tauri::async_runtime::{block_on, spawn, RwLock};
-use tracing::{error, info};
+use serde::Serialize;
+use tauri::async_runtime::{block_on, spawn, JoinHandle, RwLock};
+use tauri::{AppHandle, Manager, Wry};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
 
-use ockam::Context;
-use ockam::{NodeBuilder, TcpListenerOptions, TcpTransport};
-use ockam_api::cli_state::{CliState, StateDirTrait};
-use ockam_api::nodes::models::portal::OutletStatus;
+use ockam::compat::tokio;
+use ockam::compat::tokio::select;
+use ockam::compat::tokio::sync::watch;
+use ockam::identity::{IdentityIdentifier, TrustContext};
+use ockam::{Address, Context};
+use ockam::{NodeBuilder, TcpKeepaliveOptions, TcpListenerOptions, TcpTransport};
+use ockam_api::cli_state::{
+    CliState, ProjectState, SpaceState, StateDirTrait, StateItemTrait, TrustContextState,
+    VaultState,
+};
+use ockam_api::cloud::project::Project;
+use ockam_api::nodes::models::policy::PolicyMap;
+use ockam_api::nodes::models::portal::{InletStatus, OutletStatus};
+use ockam_api::nodes::models::secure_channel::SecureChannelStatus;
 use ockam_api::nodes::service::{
-    NodeManagerGeneralOptions, NodeManagerTransportOptions, NodeManagerTrustOptions,
+    InletOptions, NodeManagerGeneralOptions, NodeManagerTransportOptions, NodeManagerTrustOptions,
+    OutletSpec, ShutdownReport,
 };
 use ockam_api::nodes::{NodeManager, NodeManagerWorker};
+use ockam_api::{replace_unspecified_ip, socket_addr_to_multiaddr};
 use ockam_command::node::util::init_node_state;
 use ockam_command::util::api::{TrustContextConfigBuilder, TrustContextOpts};
 use ockam_command::{CommandGlobalOpts, GlobalArgs, Terminal};
+use ockam_multiaddr::MultiAddr;
 
+use crate::app::events::{ENROLLMENT_STATUS, PORTAL_REMOVED};
+use crate::app::listen_address::ListenerAddress;
 use crate::app::model_state::ModelState;
-use crate::app::model_state_repository::{LmdbModelStateRepository, ModelStateRepository};
+use crate::app::model_state_repository::{
+    model_state_repository_path, LmdbModelStateRepository, ModelStateRepository,
+};
+use crate::app::secret_store::{FileSecretStore, SecretStore};
+use crate::enroll::enroll_ticket::enroll_with_ticket_impl;
+use crate::enroll::enroll_user::enroll_with_token;
+use crate::enroll::{
+    credential_expiration_within, now, EnrollmentFlow, EnrollmentStatus, EnrollmentSummary,
+    VerificationError, AUTHORITY_TIMEOUT,
+};
+use crate::error::Error;
+use crate::shared_service::relay::outlet::{Relay, RelayedOutlet};
+use crate::shared_service::tcp::clear::{ClearedPortals, PortalRemoved};
+use crate::shared_service::tcp::inlet::model_state::TcpInletModel;
+use crate::shared_service::tcp::model_state::{restore_portals, NodeManagerPortalRestorer};
+use crate::shared_service::tcp::outlet::latency::{LatencySamples, LatencyStats};
+use crate::shared_service::tcp::outlet::list::LabeledOutlet;
+use crate::shared_service::tcp::outlet::probe::{probe, ProbeResult, PROBE_TIMEOUT};
 use crate::Result;
 
-pub const NODE_NAME: &str = "default";
+pub const DEFAULT_NODE_NAME: &str = "default";
 pub const PROJECT_NAME: &str = "default";
 
 /// The AppState struct contains all the state managed by `tauri`.
 /// It can be retrieved with the `AppHandle<Wry>` parameter and the `AppHandle::state()` method
 /// Note that it contains a `NodeManagerWorker`. This makes the desktop app a full-fledged node
 /// with its own set of secure channels, outlets, transports etc...
-/// However there is no associated persistence yet so outlets created with this `NodeManager` will
-/// have to be recreated when the application restarts.
+/// The portals created with this `NodeManager` are persisted in the `ModelState` and recreated when
+/// the application restarts.
 pub struct AppState {
     context: Arc<Context>,
+    node_name: String,
     global_args: GlobalArgs,
     state: Arc<RwLock<CliState>>,
     pub(crate) node_manager: NodeManagerWorker,
+    local_only: bool,
+    /// Address the TCP listener of the node is bound to, when the node is created and reset
+    listener_address: ListenerAddress,
     model_state: Arc<RwLock<ModelState>>,
     model_state_repository: Arc<RwLock<Arc<dyn ModelStateRepository>>>,
+    /// Set if the stored model state was corrupted and replaced with an empty one at startup,
+    /// so that the user can be told about it
+    model_state_error: Arc<RwLock<Option<String>>>,
+    secret_store: Arc<dyn SecretStore>,
+    enrollment_cancellation: Arc<RwLock<Option<CancellationToken>>>,
+    reset_cancellation: Arc<RwLock<Option<CancellationToken>>>,
+    /// Cancelled when the application shuts down, to interrupt the background operations
+    shutdown_cancellation: CancellationToken,
+    enrollment_status: Arc<watch::Sender<EnrollmentStatus>>,
+    /// Set if the credential couldn't be verified the last time the enrollment was reconciled
+    enrollment_verification: Arc<RwLock<Option<VerificationError>>>,
+    /// Task setting the enrollment status to `CredentialExpired` when the credential expires
+    enrollment_expiration: Arc<RwLock<Option<JoinHandle<()>>>>,
+    /// Latencies of the successful probes of each outlet, by alias
+    outlet_latencies: Arc<RwLock<HashMap<String, LatencySamples>>>,
+    /// Remote addresses of the forwarders registered for the relayed outlets, by worker
+    /// address of the outlet
+    relayed_forwarders: Arc<RwLock<HashMap<String, String>>>,
 }
 
 impl Default for AppState {
@@ -45,62 +108,263 @@ impl Default for AppState {
 }
 
 impl AppState {
-    /// Create a new AppState
+    /// Create a new AppState using the default node name.
+    /// This panics if the application state can't be created, see `try_new`
     pub fn new() -> AppState {
-        let options = CommandGlobalOpts::new(GlobalArgs::default().set_quiet());
-        let (context, mut executor) = NodeBuilder::new().no_logging().build();
-        let context = Arc::new(context);
-
-        // from now on we use the same runtime everywhere we need to run an async action
-        tauri::async_runtime::set(context.runtime().clone());
-        // start the router, it is needed for the node manager creation
-        spawn(async move { executor.start_router().await });
-        let mut node_manager = create_node_manager(context.clone(), options.clone());
-        let model_state_repository = create_model_state_repository(options.clone().state);
-        let model_state = load_model_state(
+        match Self::try_new() {
+            Ok(app_state) => app_state,
+            Err(e) => {
+                error!(%e, "cannot create the application state");
+                panic!("{e}")
+            }
+        }
+    }
+
+    /// Create a new AppState like `new`, but return an error instead of panicking when the
+    /// settings are invalid or when the node manager or the model state repository can't be
+    /// created.
+    ///
+    /// `Error::VaultLocked` is returned if the vault of the node is locked, for example while
+    /// the keychain of the system is locked. The user can then be asked to unlock it before the
+    /// creation is retried, see `try_with_settings`.
+    ///
+    /// The settings of the application are read from the environment, see `AppSettings::from_env`
+    pub fn try_new() -> Result<AppState> {
+        Self::with_node_name(DEFAULT_NODE_NAME)
+    }
+
+    /// Create a new AppState for a specific node name.
+    /// This allows several builds of the application to run side by side without sharing their state
+    pub fn with_node_name(node_name: impl Into<String>) -> Result<AppState> {
+        Self::with_worker_threads(node_name, None)
+    }
+
+    /// Create a new AppState for a specific node name, with a runtime using `worker_threads`
+    /// worker threads.
+    /// When `worker_threads` is `None` the runtime uses one worker thread per CPU core.
+    /// Using a single worker thread can be useful in tests or constrained environments.
+    pub fn with_worker_threads(
+        node_name: impl Into<String>,
+        worker_threads: Option<usize>,
+    ) -> Result<AppState> {
+        Self::with_secret_store(node_name, worker_threads, Arc::new(FileSecretStore))
+    }
+
+    /// Create a new AppState for a specific node name, where the secrets of the node vault
+    /// are persisted with `secret_store`.
+    /// The other settings are read from the environment, see `AppSettings::from_env`
+    pub fn with_secret_store(
+        node_name: impl Into<String>,
+        worker_threads: Option<usize>,
+        secret_store: Arc<dyn SecretStore>,
+    ) -> Result<AppState> {
+        Self::try_with_settings(
+            AppSettings::from_env(node_name)?.with_worker_threads(worker_threads),
+            secret_store,
+        )
+    }
+
+    /// Create a new AppState like `with_secret_store`, in local-only mode if `local_only` is true.
+    ///
+    /// In local-only mode no project is used, even if the cli state has a default project:
+    /// the node has no trust context, the application can't be enrolled and the cloud-dependent
+    /// features are disabled. The portals are still created and persisted
+    pub fn with_local_only(
+        node_name: impl Into<String>,
+        worker_threads: Option<usize>,
+        secret_store: Arc<dyn SecretStore>,
+        local_only: bool,
+    ) -> Result<AppState> {
+        Self::try_with_settings(
+            AppSettings::new(node_name)
+                .with_worker_threads(worker_threads)
+                .with_local_only(local_only),
+            secret_store,
+        )
+    }
+
+    /// Create a new AppState from its settings, where the secrets of the node vault are
+    /// persisted with `secret_store`.
+    ///
+    /// If the secret store reports that the vault of the node is locked, `Error::VaultLocked`
+    /// is returned with an `AppStateRetry`. It keeps the node started for this attempt, so that
+    /// the creation can be retried once the vault is unlocked without starting another node
+    pub fn try_with_settings(
+        settings: AppSettings,
+        secret_store: Arc<dyn SecretStore>,
+    ) -> Result<AppState> {
+        let context = start_node(settings.worker_threads);
+        Self::try_with_node(settings, secret_store, context)
+    }
+
+    /// Create a new AppState like `try_with_settings`, with a node which is already started
+    fn try_with_node(
+        settings: AppSettings,
+        secret_store: Arc<dyn SecretStore>,
+        context: Arc<Context>,
+    ) -> Result<AppState> {
+        let AppSettings {
+            node_name,
+            state_dir,
+            local_only,
+            listener_address,
+            ..
+        } = settings.clone();
+        let options = CommandGlobalOpts::embedded(state_dir);
+        let node_manager = match create_node_manager(
+            context.clone(),
+            options.clone(),
+            &node_name,
+            secret_store.clone(),
+            listener_address,
+            local_only,
+        ) {
+            Ok(node_manager) => NodeManagerWorker::new(node_manager),
+            // no worker was started before the vault was opened, the node can be reused
+            Err(Error::VaultLocked { reason, .. }) => {
+                let retry = AppStateRetry {
+                    settings,
+                    secret_store,
+                    context,
+                };
+                return Err(Error::VaultLocked {
+                    reason,
+                    retry: Some(Box::new(retry)),
+                });
+            }
+            Err(e) => return Err(e),
+        };
+        load_startup_config(node_manager.clone(), context.clone());
+        let model_state_repository =
+            create_model_state_repository(options.clone().state, &node_name)?;
+        let (model_state, model_state_error) = load_model_state(
             model_state_repository.clone(),
-            &mut node_manager,
+            node_manager.clone(),
             context.clone(),
-        );
+        )?;
+        let enrollment = model_state.get_enrollment().filter(|_| !local_only);
+        let (enrollment_status, _) = watch::channel(EnrollmentStatus::new(enrollment, None, now()));
 
-        AppState {
+        Ok(AppState {
             context,
+            node_name,
             global_args: options.global_args,
             state: Arc::new(RwLock::new(options.state)),
-            node_manager: NodeManagerWorker::new(node_manager),
+            node_manager,
+            local_only,
+            listener_address,
             model_state: Arc::new(RwLock::new(model_state)),
             model_state_repository: Arc::new(RwLock::new(model_state_repository)),
-        }
+            model_state_error: Arc::new(RwLock::new(model_state_error)),
+            secret_store,
+            enrollment_cancellation: Arc::new(RwLock::new(None)),
+            reset_cancellation: Arc::new(RwLock::new(None)),
+            shutdown_cancellation: CancellationToken::new(),
+            enrollment_status: Arc::new(enrollment_status),
+            enrollment_verification: Arc::new(RwLock::new(None)),
+            enrollment_expiration: Arc::new(RwLock::new(None)),
+            outlet_latencies: Arc::new(RwLock::new(HashMap::new())),
+            relayed_forwarders: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// Stop the node manager, reset the cli state and recreate the node manager
+    /// and the model state repository.
+    /// The returned report describes what was recreated so that it can be displayed to the user
+    #[allow(dead_code)]
+    pub async fn reset(&self) -> Result<ResetReport> {
+        self.reset_with_progress(|_| {}).await
+    }
+
+    /// Reset the application like `reset`, calling `progress` when each stage of the reset starts.
+    /// `progress` is called from the async context, so it must return quickly and never block.
+    /// The reset can be interrupted with `cancel_reset`, see `reset_with_cancellation`
+    pub async fn reset_with_progress(
+        &self,
+        progress: impl Fn(ResetStage) + Send,
+    ) -> Result<ResetReport> {
+        let cancellation = CancellationToken::new();
+        *self.reset_cancellation.write().await = Some(cancellation.clone());
+        let result = self.reset_with_cancellation(progress, &cancellation).await;
+        self.reset_cancellation.write().await.take();
+        result
     }
 
-    pub async fn reset(&self) -> miette::Result<()> {
+    /// Reset the application like `reset_with_progress`, unless `cancellation` is cancelled
+    /// before the cli state is reset. In that case `Error::Cancelled` is returned and the
+    /// application is left as it was: if the node manager was already stopped, it is restarted
+    /// with its portals and sessions.
+    ///
+    /// Once the cli state has been reset, the reset can't be cancelled anymore and runs to
+    /// completion, so that the node manager is always recreated
+    pub async fn reset_with_cancellation(
+        &self,
+        progress: impl Fn(ResetStage) + Send,
+        cancellation: &CancellationToken,
+    ) -> Result<ResetReport> {
+        progress(ResetStage::StoppingNodeManager);
+        if cancellation.is_cancelled() {
+            info!("the reset was cancelled before stopping the node manager");
+            return Err(Error::Cancelled);
+        }
         self.node_manager
             .stop(&self.context)
             .await
             .map_err(|e| miette!(e))?;
         info!("stopped the old node manager");
 
+        progress(ResetStage::ResettingState);
+        if cancellation.is_cancelled() {
+            self.node_manager
+                .restart(&self.context)
+                .await
+                .map_err(|e| miette!(e))?;
+            info!("the reset was cancelled, the node manager has been restarted");
+            return Err(Error::Cancelled);
+        }
         self.reset_state().await?;
         info!("reset the cli state");
 
-        let node_manager = make_node_manager(self.context.clone(), self.options().await).await?;
+        progress(ResetStage::CreatingNodeManager);
+        let node_manager = make_node_manager(
+            self.context.clone(),
+            self.options().await,
+            &self.node_name,
+            self.secret_store.clone(),
+            self.listener_address,
+            self.local_only,
+        )
+        .await?;
+        let listen_address = node_manager
+            .tcp_listener_addresses()
+            .first()
+            .map(|address| address.to_string());
         info!("created a new node manager");
 
         self.node_manager.set_node_manager(node_manager).await;
         info!("set a new node manager");
 
         // recreate the model state repository since the cli state has changed
-        let identity_path = self
-            .state()
-            .await
-            .identities
-            .identities_repository_path()
-            .unwrap();
-        let new_state_repository = LmdbModelStateRepository::new(identity_path).await?;
-        let mut model_state_repository = self.model_state_repository.write().await;
-        *model_state_repository = Arc::new(new_state_repository);
+        progress(ResetStage::RecreatingModelStateRepository);
+        let model_state_path = model_state_repository_path(&self.state().await)?;
+        let new_state_repository =
+            LmdbModelStateRepository::new(&model_state_path, &self.node_name).await?;
+        {
+            let mut model_state_repository = self.model_state_repository.write().await;
+            *model_state_repository = Arc::new(new_state_repository);
+        }
+        self.model_state_error.write().await.take();
+        self.model_mut(|m| m.clear_enrollment()).await?;
+        self.register_relays().await;
+        if cancellation.is_cancelled() {
+            warn!("the reset was cancelled after the cli state was reset, it has been completed");
+        }
 
-        Ok(())
+        Ok(ResetReport {
+            listen_address,
+            model_state_path,
+            enrolled: self.is_enrolled().await,
+        })
     }
 
     async fn reset_state(&self) -> miette::Result<()> {
@@ -112,6 +376,11 @@ impl AppState {
         Ok(())
     }
 
+    /// Return the name of the node managed by the application
+    pub fn node_name(&self) -> String {
+        self.node_name.clone()
+    }
+
     /// Return the application Context
     /// This can be used to run async actions involving the Router
     pub fn context(&self) -> Arc<Context> {
@@ -136,9 +405,274 @@ impl AppState {
 
     /// Return true if the user is enrolled
     /// At the moment this check only verifies that there is a default project.
-    /// This project should be the project that is created at the end of the enrollment procedure
+    /// This project should be the project that is created at the end of the enrollment procedure.
+    /// The application is never enrolled in local-only mode
     pub async fn is_enrolled(&self) -> bool {
-        self.state().await.projects.default().is_ok()
+        !self.local_only && self.state().await.projects.default().is_ok()
+    }
+
+    /// Return true if the application runs in local-only mode, without any project
+    pub fn is_local_only(&self) -> bool {
+        self.local_only
+    }
+
+    /// Run an enrollment flow.
+    ///
+    /// On success the node manager trust context and the model state are updated.
+    /// An `ENROLLMENT_STATUS` event is emitted when the enrollment starts and when it finishes.
+    /// The enrollment can be interrupted with `cancel_enrollment`, see `enroll_with_cancellation`.
+    /// It fails in local-only mode, and with `Error::EnrollmentInProgress` while another
+    /// enrollment is running.
+    pub async fn enroll(
+        &self,
+        app: &AppHandle<Wry>,
+        flow: EnrollmentFlow,
+    ) -> Result<IdentityIdentifier> {
+        if self.local_only {
+            return Err(Error::LocalOnly);
+        }
+        let cancellation = self.start_enrollment().await?;
+        self.enrollment_status
+            .send_replace(EnrollmentStatus::Enrolling);
+        app.trigger_global(ENROLLMENT_STATUS, Some("started".to_string()));
+
+        let result = self
+            .enroll_with_cancellation(self.run_enrollment(flow), &cancellation)
+            .await;
+        self.finish_enrollment().await;
+
+        let status = match &result {
+            Ok(_) => "enrolled",
+            Err(e) if e.is_cancelled() => "cancelled",
+            Err(_) => "failed",
+        };
+        info!(%status, "enrollment finished");
+        self.refresh_enrollment_status(true).await;
+        app.trigger_global(ENROLLMENT_STATUS, Some(status.to_string()));
+        result
+    }
+
+    async fn run_enrollment(&self, flow: EnrollmentFlow) -> Result<IdentityIdentifier> {
+        match flow {
+            EnrollmentFlow::Auth0 => enroll_with_token(self).await,
+            EnrollmentFlow::Ticket(ticket) => enroll_with_ticket_impl(self, &ticket).await,
+        }
+    }
+
+    /// Register the enrollment about to run, so that it can be cancelled with `cancel_enrollment`.
+    /// `Error::EnrollmentInProgress` is returned if another enrollment is running
+    async fn start_enrollment(&self) -> Result<CancellationToken> {
+        let mut enrollment = self.enrollment_cancellation.write().await;
+        if enrollment.is_some() {
+            return Err(Error::EnrollmentInProgress);
+        }
+        let cancellation = CancellationToken::new();
+        *enrollment = Some(cancellation.clone());
+        Ok(cancellation)
+    }
+
+    /// Forget the enrollment registered by `start_enrollment` once it has completed
+    async fn finish_enrollment(&self) {
+        self.enrollment_cancellation.write().await.take();
+    }
+
+    /// Run an enrollment until it completes or `cancellation` is cancelled.
+    ///
+    /// A cancelled enrollment returns `Error::EnrollmentCancelled` and is rolled back: the user
+    /// information and the enrollment summary of the model state are restored, the spaces,
+    /// projects and trust contexts of the cli state are restored, and so is the default trust
+    /// context of the node manager. The portals created meanwhile are kept
+    pub(crate) async fn enroll_with_cancellation<T>(
+        &self,
+        enrollment: impl Future<Output = Result<T>>,
+        cancellation: &CancellationToken,
+    ) -> Result<T> {
+        let snapshot = EnrollmentSnapshot::take(self).await?;
+        select! {
+            biased;
+            _ = cancellation.cancelled() => {
+                snapshot.restore(self).await?;
+                info!("the enrollment was cancelled and rolled back");
+                Err(Error::EnrollmentCancelled)
+            }
+            result = enrollment => result,
+        }
+    }
+
+    /// Record the enrollment of the application with a project in the model state
+    pub(crate) async fn record_enrollment(&self, project: &Project) -> Result<()> {
+        let summary = EnrollmentSummary::new(project).await;
+        self.enrollment_verification.write().await.take();
+        self.model_mut(|m| m.set_enrollment(summary)).await
+    }
+
+    /// Reconcile the last known enrollment with the cli state.
+    ///
+    /// The enrollment is forgotten if there is no default project anymore. Otherwise it is
+    /// updated with the default project and the expiration of the node credential, which is
+    /// retrieved from the project authority if it is not cached.
+    /// In local-only mode the enrollment is always forgotten.
+    ///
+    /// If the credential can't be retrieved within `AUTHORITY_TIMEOUT`, for example because the
+    /// authority is unreachable, the application is still enrolled, with the last known
+    /// credential expiration, and its enrollment status is `Unverified`. If the authority refuses
+    /// to issue the credential, its enrollment status is `CredentialRejected`.
+    /// The retrieval of the credential is interrupted when the application shuts down
+    pub async fn reconcile_enrollment(&self) -> Result<()> {
+        self.reconcile_enrollment_with_cancellation(&self.shutdown_cancellation.child_token())
+            .await
+    }
+
+    /// Reconcile the enrollment like `reconcile_enrollment`, unless `cancellation` is cancelled
+    /// while the credential is retrieved. In that case `Error::Cancelled` is returned and the
+    /// last known enrollment is kept
+    pub async fn reconcile_enrollment_with_cancellation(
+        &self,
+        cancellation: &CancellationToken,
+    ) -> Result<()> {
+        let project = match self.state().await.projects.default() {
+            Ok(project) if !self.local_only => project.config().clone(),
+            _ => return self.model_mut(|m| m.clear_enrollment()).await,
+        };
+        // the node manager is not locked while the authority is contacted
+        let context = self.context();
+        let credential_expiration = self
+            .node_manager
+            .get()
+            .read()
+            .await
+            .credential_expiration(&context);
+        let credential_expiration = select! {
+            biased;
+            _ = cancellation.cancelled() => return Err(Error::Cancelled),
+            expiration = credential_expiration_within(
+                credential_expiration,
+                AUTHORITY_TIMEOUT,
+            ) => expiration,
+        };
+        let verification = credential_expiration.err();
+        let verification_modified = {
+            let mut current = self.enrollment_verification.write().await;
+            let modified = *current != verification;
+            *current = verification;
+            modified
+        };
+        let credential_expires_at = match credential_expiration {
+            Ok(expires_at) => Some(expires_at),
+            Err(_) => {
+                self.model(|m| m.get_enrollment().and_then(|e| e.credential_expires_at))
+                    .await
+            }
+        };
+        let summary = EnrollmentSummary::new(&project)
+            .await
+            .with_credential_expiration(credential_expires_at);
+        self.model_mut(|m| m.set_enrollment(summary)).await?;
+        if verification_modified {
+            self.refresh_enrollment_status(false).await;
+        }
+        Ok(())
+    }
+
+    /// Return a receiver of the enrollment status, which always holds its latest value.
+    ///
+    /// Contrary to the `ENROLLMENT_STATUS` events, a status change can't be missed: the initial
+    /// value is the status at subscription time and `changed` returns as soon as it is updated,
+    /// when an enrollment starts or finishes, when the enrollment is reset or reconciled,
+    /// and when the credential expires
+    pub async fn enrollment_watch(&self) -> watch::Receiver<EnrollmentStatus> {
+        self.refresh_enrollment_status(false).await;
+        self.enrollment_status.subscribe()
+    }
+
+    /// Update the enrollment status with the last known enrollment in the model state,
+    /// and schedule its update when the credential expires.
+    /// The status stays `Enrolling` while an enrollment is in progress, unless `force` is true
+    async fn refresh_enrollment_status(&self, force: bool) {
+        if !force && *self.enrollment_status.borrow() == EnrollmentStatus::Enrolling {
+            return;
+        }
+        let summary = match self.local_only {
+            true => None,
+            false => self.model(|m| m.get_enrollment().cloned()).await,
+        };
+        let verification = *self.enrollment_verification.read().await;
+        let status = EnrollmentStatus::new(summary.as_ref(), verification, now());
+        self.enrollment_status.send_if_modified(|current| {
+            let modified = *current != status;
+            *current = status;
+            modified
+        });
+
+        let mut expiration = self.enrollment_expiration.write().await;
+        if let Some(task) = expiration.take() {
+            task.abort();
+        }
+        let expires_at = summary.and_then(|s| s.credential_expires_at);
+        let enrolled = [EnrollmentStatus::Enrolled, EnrollmentStatus::Unverified];
+        if let (true, Some(expires_at)) = (enrolled.contains(&status), expires_at) {
+            let sender = self.enrollment_status.clone();
+            *expiration = Some(spawn(async move {
+                let delay = Duration::from_secs(expires_at.saturating_sub(now()));
+                tokio::time::sleep(delay).await;
+                sender.send_if_modified(|current| {
+                    let modified = enrolled.contains(current);
+                    if modified {
+                        *current = EnrollmentStatus::CredentialExpired;
+                    }
+                    modified
+                });
+            }));
+        }
+    }
+
+    /// Cancel the enrollment in progress.
+    /// Return false if there was no enrollment to cancel.
+    /// The enrollment is still in progress until it has been rolled back, so that no other
+    /// enrollment can start in the meantime
+    pub async fn cancel_enrollment(&self) -> bool {
+        match self.enrollment_cancellation.read().await.as_ref() {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cancel the reset in progress, if the cli state hasn't been reset yet.
+    /// Return false if there was no reset to cancel
+    pub async fn cancel_reset(&self) -> bool {
+        cancel(&self.reset_cancellation).await
+    }
+
+    /// Return the address of the TCP listener of the node as a MultiAddr,
+    /// for example `/ip4/127.0.0.1/tcp/4000`, so that it can be given to peers
+    pub async fn listen_multiaddr(&self) -> Result<MultiAddr> {
+        let node_manager = self.node_manager.get().read().await;
+        let addr = node_manager
+            .tcp_listener_addresses()
+            .first()
+            .copied()
+            .ok_or_else(|| Error::Generic("The node has no TCP listener".to_string()))?;
+        socket_addr_to_multiaddr(&replace_unspecified_ip(addr), None)
+            .map_err(|e| Error::Generic(e.to_string()))
+    }
+
+    /// Pause the node: the outlets stop accepting new connections and no new secure
+    /// channel is accepted, for example while the computer is asleep.
+    /// If `quiesce` is true, the existing portal connections are closed as well
+    pub async fn pause_node(&self, quiesce: bool) {
+        self.node_manager.get().read().await.pause(quiesce)
+    }
+
+    /// Resume the node after a pause, with the same portals and secure channels as before
+    pub async fn resume_node(&self) {
+        self.node_manager.get().read().await.resume()
+    }
+
+    pub async fn is_node_paused(&self) -> bool {
+        self.node_manager.get().read().await.is_paused()
     }
 
     /// Return the list of currently running outlets
@@ -147,57 +681,808 @@ impl AppState {
         node_manager.list_outlets().list
     }
 
+    /// Return the list of currently running outlets, with their labels
+    pub async fn labeled_outlet_list(&self) -> Vec<LabeledOutlet> {
+        let outlets = self.tcp_outlet_list().await;
+        self.model(|m| {
+            outlets
+                .into_iter()
+                .map(|status| LabeledOutlet {
+                    label: m.get_tcp_outlet_label(&status.alias).map(|l| l.to_string()),
+                    status,
+                })
+                .collect()
+        })
+        .await
+    }
+
+    /// Return the running outlet with the given alias, if there is one
+    pub async fn outlet(&self, alias: &str) -> Option<OutletStatus> {
+        let node_manager = self.node_manager.get().read().await;
+        node_manager.get_outlet(alias)
+    }
+
+    /// Return the outlet forwarding to `socket_addr`, if there is one, including an outlet whose
+    /// port range contains its port.
+    /// The full address is compared, so outlets targeting the same port on another host don't match
+    pub async fn outlet_for_target(&self, socket_addr: SocketAddr) -> Option<OutletStatus> {
+        self.tcp_outlet_list()
+            .await
+            .into_iter()
+            .find(|o| o.forwards_to(&socket_addr))
+    }
+
+    /// Return the outlet whose worker has the address `worker_addr`, if there is one
+    pub async fn outlet_for_worker(&self, worker_addr: &str) -> Option<OutletStatus> {
+        let worker_addr = Address::from_string(worker_addr);
+        self.tcp_outlet_list()
+            .await
+            .into_iter()
+            .find(|o| Address::from_string(&o.worker_addr) == worker_addr)
+    }
+
+    /// Return the policies of the node grouped by resource, optionally only for the resources
+    /// starting with a prefix
+    pub async fn policies(&self, resource_prefix: Option<&str>) -> Result<PolicyMap> {
+        let node_manager = self.node_manager.get().read().await;
+        node_manager
+            .policy_map(resource_prefix)
+            .await
+            .map_err(|e| Error::Generic(e.to_string()))
+    }
+
+    /// Return the secure channels of the node, with their authenticated peer
+    pub async fn secure_channels(&self) -> Result<Vec<SecureChannelStatus>> {
+        let node_manager = self.node_manager.get().read().await;
+        node_manager
+            .list_secure_channels()
+            .await
+            .map_err(|e| Error::Generic(e.to_string()))
+    }
+
+    /// Close a secure channel of the node given its address.
+    /// Return false if there is no such secure channel
+    pub async fn close_secure_channel(&self, address: &Address) -> Result<bool> {
+        let mut node_manager = self.node_manager.get().write().await;
+        node_manager
+            .close_secure_channel(&self.context(), address)
+            .await
+            .map_err(|e| Error::Generic(e.to_string()))
+    }
+
+    /// Create an outlet and persist it so that it is recreated on restart.
+    /// If an idle timeout is given, each connection of the outlet is closed once no data
+    /// went through it, in either direction, for that duration
+    pub async fn create_outlet(
+        &self,
+        tcp_addr: String,
+        worker_addr: String,
+        idle_timeout: Option<Duration>,
+    ) -> Result<OutletStatus> {
+        self.create_outlet_with_tags(tcp_addr, worker_addr, idle_timeout, BTreeMap::new())
+            .await
+    }
+
+    /// Create an outlet like `create_outlet`, with tags used to find it.
+    /// The tags are persisted with the outlet
+    pub async fn create_outlet_with_tags(
+        &self,
+        tcp_addr: String,
+        worker_addr: String,
+        idle_timeout: Option<Duration>,
+        tags: BTreeMap<String, String>,
+    ) -> Result<OutletStatus> {
+        let spec = OutletSpec::new(tcp_addr, worker_addr)
+            .reachable_from_default_secure_channel(true)
+            .with_idle_timeout(idle_timeout)
+            .with_tags(tags);
+        let status = {
+            let mut node_manager = self.node_manager.get().write().await;
+            node_manager
+                .create_outlet_from_spec(&self.context(), spec)
+                .await
+                .map_err(|e| Error::Generic(e.to_string()))?
+        };
+        self.model_mut(|m| m.add_tcp_outlet(status.clone())).await?;
+        Ok(status)
+    }
+
+    /// Return the running outlets having all the given tags, with the same values,
+    /// sorted by alias
+    pub async fn find_outlets_by_tags<K: AsRef<str>, V: AsRef<str>>(
+        &self,
+        tags: &[(K, V)],
+    ) -> Vec<OutletStatus> {
+        let node_manager = self.node_manager.get().read().await;
+        node_manager.find_outlets_by_tags(tags)
+    }
+
+    /// Create an inlet listening on `bind_addr` and forwarding its connections to the outlet at
+    /// `outlet_addr`, and persist it so that it is recreated on restart.
+    /// When the outlet is an outlet of this node, its alias is given as `outlet_alias` so that
+    /// the inlet is only restored once its outlet is
+    pub async fn create_inlet(
+        &self,
+        alias: &str,
+        bind_addr: SocketAddr,
+        outlet_addr: MultiAddr,
+        outlet_alias: Option<String>,
+    ) -> Result<InletStatus> {
+        let status = NodeManager::create_inlet(
+            self.node_manager.get().clone(),
+            &self.context,
+            alias,
+            bind_addr,
+            outlet_addr.clone(),
+            InletOptions::default(),
+        )
+        .await
+        .map_err(|e| Error::Generic(e.to_string()))?;
+        let inlet = TcpInletModel::new(
+            alias,
+            status.bind_addr.clone(),
+            outlet_addr.to_string(),
+            outlet_alias,
+        );
+        self.model_mut(|m| m.add_tcp_inlet(inlet)).await?;
+        Ok(status)
+    }
+
+    /// Delete an inlet and forget it so that it is not recreated on restart
+    pub async fn delete_inlet(&self, alias: &str) -> Result<()> {
+        {
+            let mut node_manager = self.node_manager.get().write().await;
+            node_manager
+                .delete_inlet(alias)
+                .await
+                .map_err(|e| Error::Generic(e.to_string()))?;
+        }
+        self.model_mut(|m| m.remove_tcp_inlet(alias)).await
+    }
+
+    /// Create an outlet and register a forwarder for it at the relay of the project, so that
+    /// the peers outside of the local network can reach it through the project.
+    ///
+    /// The outlet is persisted like the other outlets. Its forwarder is registered again by
+    /// the node manager when the connection to the project is lost, and by `register_relays`
+    /// after a reset or a restart of the application, if it is still enrolled.
+    /// It fails if the application is not enrolled
+    pub async fn create_relayed_outlet(
+        &self,
+        alias: &str,
+        socket_addr: SocketAddr,
+    ) -> Result<RelayedOutlet> {
+        let relay = self.project_relay().await.ok_or_else(|| {
+            Error::Generic(
+                "The application must be enrolled to create an outlet reachable through a relay"
+                    .to_string(),
+            )
+        })?;
+        self.create_relayed_outlet_at(&relay, alias, socket_addr)
+            .await
+    }
+
+    /// Create an outlet and register its forwarder at a relay.
+    /// The outlet is deleted if its forwarder can't be registered
+    pub(crate) async fn create_relayed_outlet_at(
+        &self,
+        relay: &Relay,
+        alias: &str,
+        socket_addr: SocketAddr,
+    ) -> Result<RelayedOutlet> {
+        let outlet = {
+            let mut node_manager = self.node_manager.get().write().await;
+            node_manager
+                .create_outlet_with_trust_context(
+                    &self.context(),
+                    socket_addr.to_string(),
+                    alias.to_string(),
+                    Some(alias.to_string()),
+                    true,
+                    None,
+                    None,
+                )
+                .await
+                .map_err(|e| Error::Generic(e.to_string()))?
+        };
+        let relayed_outlet = match self.register_relay(relay, &outlet).await {
+            Ok(relayed_outlet) => relayed_outlet,
+            Err(e) => {
+                let mut node_manager = self.node_manager.get().write().await;
+                if let Err(e) = node_manager.delete_outlet(alias).await {
+                    warn!(%alias, %e, "failed to delete an outlet whose forwarder was not registered");
+                }
+                return Err(e);
+            }
+        };
+        self.model_mut(|m| {
+            m.add_tcp_outlet(outlet);
+            m.add_relayed_outlet(alias)
+        })
+        .await?;
+        Ok(relayed_outlet)
+    }
+
+    /// Register the forwarders of the running outlets reachable through the relay of the
+    /// project, if the application is enrolled, and return the registered outlets.
+    /// The outlets whose forwarder can't be registered are logged and skipped
+    pub async fn register_relays(&self) -> Vec<RelayedOutlet> {
+        let relay = match self.project_relay().await {
+            Some(relay) => relay,
+            None => return vec![],
+        };
+        let mut relayed_outlets = vec![];
+        for alias in self.model(|m| m.get_relayed_outlets().to_vec()).await {
+            let outlet = match self.outlet(&alias).await {
+                Some(outlet) => outlet,
+                None => {
+                    info!(%alias, "the relayed outlet is not running, its forwarder is not registered");
+                    continue;
+                }
+            };
+            match self.register_relay(&relay, &outlet).await {
+                Ok(relayed_outlet) => relayed_outlets.push(relayed_outlet),
+                Err(e) => warn!(%alias, %e, "failed to register the forwarder of an outlet"),
+            }
+        }
+        relayed_outlets
+    }
+
+    /// Return the relay of the default project, if the application is enrolled
+    async fn project_relay(&self) -> Option<Relay> {
+        if !self.is_enrolled().await {
+            return None;
+        }
+        let project = self.state().await.projects.default().ok()?;
+        Some(Relay::Project(project.config().name.clone()))
+    }
+
+    /// Register a forwarder for an outlet at a relay
+    async fn register_relay(&self, relay: &Relay, outlet: &OutletStatus) -> Result<RelayedOutlet> {
+        let forwarder = self
+            .node_manager
+            .create_forwarder(&self.context, relay.create_forwarder(outlet)?)
+            .await
+            .map_err(|e| Error::Generic(e.to_string()))?;
+        info!(alias = %outlet.alias, remote_address = %forwarder.remote_address(), "registered the forwarder of an outlet");
+        self.relayed_forwarders.write().await.insert(
+            outlet.worker_addr.clone(),
+            forwarder.remote_address().to_string(),
+        );
+        RelayedOutlet::new(relay, outlet.clone(), forwarder.remote_address())
+    }
+
+    /// Delete the forwarder registered for an outlet, if there is one, so that the outlet is
+    /// not reachable through its relay anymore
+    async fn delete_relay(&self, worker_addr: &str) {
+        let remote_address = match self.relayed_forwarders.write().await.remove(worker_addr) {
+            Some(remote_address) => remote_address,
+            None => return,
+        };
+        let mut node_manager = self.node_manager.get().write().await;
+        match node_manager
+            .delete_forwarder(&self.context, &remote_address)
+            .await
+        {
+            Ok(_) => info!(%worker_addr, %remote_address, "deleted the forwarder of an outlet"),
+            Err(e) => {
+                warn!(%worker_addr, %remote_address, %e, "failed to delete the forwarder of an outlet")
+            }
+        }
+    }
+
+    /// Rename a running outlet and persist the new alias so that it is used on restart.
+    /// The latencies recorded for the outlet are kept under its new alias
+    pub async fn rename_outlet(&self, old_alias: &str, new_alias: &str) -> Result<OutletStatus> {
+        let status = {
+            let mut node_manager = self.node_manager.get().write().await;
+            node_manager
+                .rename_outlet(old_alias, new_alias)
+                .await
+                .map_err(|e| Error::Generic(e.to_string()))?
+        };
+        {
+            let mut outlet_latencies = self.outlet_latencies.write().await;
+            if let Some(samples) = outlet_latencies.remove(old_alias) {
+                outlet_latencies.insert(new_alias.to_string(), samples);
+            }
+        }
+        self.model_mut(|m| m.rename_tcp_outlet(old_alias, new_alias))
+            .await?;
+        Ok(status)
+    }
+
+    /// Set the label displayed instead of the alias of a running outlet and persist it.
+    /// The alias of the outlet and its active connections are not modified.
+    /// An empty label removes the label of the outlet
+    pub async fn set_outlet_label(&self, alias: &str, label: &str) -> Result<()> {
+        if self.outlet(alias).await.is_none() {
+            return Err(Error::Generic(format!(
+                "Outlet with alias {alias} not found"
+            )));
+        }
+        let label = label.trim();
+        let label = (!label.is_empty()).then(|| label.to_string());
+        self.model_mut(|m| m.set_tcp_outlet_label(alias, label))
+            .await
+    }
+
+    /// Return the label of an outlet, if it has one
+    pub async fn outlet_label(&self, alias: &str) -> Option<String> {
+        self.model(|m| m.get_tcp_outlet_label(alias).map(|l| l.to_string()))
+            .await
+    }
+
+    /// Test if the target of an outlet accepts TCP connections
+    pub async fn probe_outlet(&self, alias: &str) -> Result<ProbeResult> {
+        let tcp_addr = {
+            let node_manager = self.node_manager.get().read().await;
+            node_manager
+                .get_outlet(alias)
+                .map(|o| o.tcp_addr)
+                .ok_or_else(|| Error::Generic(format!("Outlet with alias {alias} not found")))?
+        };
+        let result = probe(alias, &tcp_addr, PROBE_TIMEOUT).await?;
+        if let Some(latency_ms) = result.latency_ms {
+            self.record_outlet_latency(alias, Duration::from_millis(latency_ms as u64))
+                .await;
+        }
+        Ok(result)
+    }
+
+    /// Record the latency of a successful connection to the target of an outlet
+    pub(crate) async fn record_outlet_latency(&self, alias: &str, latency: Duration) {
+        self.outlet_latencies
+            .write()
+            .await
+            .entry(alias.to_string())
+            .or_default()
+            .add(latency);
+    }
+
+    /// Forget the latencies of the outlets which are not in `aliases`
+    pub(crate) async fn retain_outlet_latencies(&self, aliases: &[String]) {
+        self.outlet_latencies
+            .write()
+            .await
+            .retain(|alias, _| aliases.contains(alias));
+    }
+
+    /// Return the p50 and p95 latencies of an outlet, computed over its most recent probes
+    pub async fn outlet_latency(&self, alias: &str) -> Result<LatencyStats> {
+        if self.outlet(alias).await.is_none() {
+            return Err(Error::Generic(format!(
+                "Outlet with alias {alias} not found"
+            )));
+        }
+        Ok(self
+            .outlet_latencies
+            .read()
+            .await
+            .get(alias)
+            .cloned()
+            .unwrap_or_default()
+            .stats(alias))
+    }
+
+    /// Remove all the outlets and inlets from the node manager and from the model state,
+    /// including the portals only running on the node and the ones which couldn't be restored.
+    ///
+    /// The identity, the vault and the trust context are left untouched so that the application
+    /// stays enrolled. A `PORTAL_REMOVED` event is emitted for each removed portal.
+    /// The portals which can't be removed are kept: an error listing them is returned once
+    /// the other portals have been removed
+    pub async fn clear_portals(&self, app: &AppHandle<Wry>) -> Result<Vec<PortalRemoved>> {
+        let cleared = self.remove_portals().await?;
+        for portal in cleared.removed.iter() {
+            app.trigger_global(PORTAL_REMOVED, Some(serde_json::to_string(portal)?));
+        }
+        cleared.into_result()
+    }
+
+    /// Remove the portals of the node manager and of the model state, see `clear_portals`
+    pub(crate) async fn remove_portals(&self) -> Result<ClearedPortals> {
+        let (mut inlets, mut outlets): (BTreeSet<String>, BTreeSet<String>) = self
+            .model(|m| {
+                (
+                    m.get_tcp_inlets().iter().map(|i| i.alias.clone()).collect(),
+                    m.get_tcp_outlets()
+                        .iter()
+                        .map(|o| o.alias.clone())
+                        .collect(),
+                )
+            })
+            .await;
+        let mut cleared = ClearedPortals::default();
+        let mut deleted_workers = vec![];
+        {
+            let mut node_manager = self.node_manager.get().write().await;
+            inlets.extend(node_manager.list_inlets().list.into_iter().map(|i| i.alias));
+            outlets.extend(
+                node_manager
+                    .list_outlets()
+                    .list
+                    .into_iter()
+                    .map(|o| o.alias),
+            );
+            // inlets are removed first since they can be routed to the outlets of this node
+            for alias in inlets {
+                let result = node_manager.delete_inlet(&alias).await;
+                cleared.add(PortalRemoved::inlet(alias), result);
+            }
+            for alias in outlets {
+                let result = node_manager.delete_outlet(&alias).await;
+                if let Ok(Some(status)) = &result {
+                    deleted_workers.push(status.worker_addr.clone());
+                }
+                cleared.add(PortalRemoved::outlet(alias), result);
+            }
+        }
+        // the deleted outlets are not reachable through their relay anymore
+        for worker_addr in deleted_workers {
+            self.delete_relay(&worker_addr).await;
+        }
+        self.model_mut(|m| {
+            for portal in cleared.removed.iter() {
+                if portal.is_inlet() {
+                    m.remove_tcp_inlet(&portal.alias)
+                } else {
+                    m.remove_tcp_outlet(&portal.alias)
+                }
+            }
+        })
+        .await?;
+        Ok(cleared)
+    }
+
+    /// Stop the portals, the secure channels and the transports of the node before the
+    /// application exits, and return the outcome for each of them.
+    /// The components which failed to stop are logged
+    pub async fn shutdown(&self) -> ShutdownReport {
+        self.shutdown_cancellation.cancel();
+        if let Err(e) = self.node_manager.stop(&self.context).await {
+            warn!("failed to stop the sessions of the node: {e:?}");
+        }
+        let report = self
+            .node_manager
+            .get()
+            .write()
+            .await
+            .shutdown(&self.context)
+            .await;
+        if report.is_clean() {
+            info!(%report, "the node has been shut down");
+        } else {
+            warn!(%report, "the node has not been shut down cleanly");
+        }
+        report
+    }
+
+    /// Modify the model state and persist it.
+    /// The enrollment status is only refreshed if the enrollment has been modified
     pub async fn model_mut(&self, f: impl FnOnce(&mut ModelState)) -> Result<()> {
         let mut model_state = self.model_state.write().await;
+        let enrollment = model_state.get_enrollment().cloned();
         f(&mut model_state);
+        let enrollment_modified = model_state.get_enrollment() != enrollment.as_ref();
         self.model_state_repository
             .read()
             .await
             .store(&model_state)
             .await?;
+        drop(model_state);
+        if enrollment_modified {
+            self.refresh_enrollment_status(false).await;
+        }
         Ok(())
     }
 
+    /// Return the error describing why the stored model state was replaced with an empty one
+    /// when the application started, if it was corrupted. It is cleared by a reset
+    pub async fn model_state_error(&self) -> Option<String> {
+        self.model_state_error.read().await.clone()
+    }
+
     pub async fn model<T>(&self, f: impl FnOnce(&ModelState) -> T) -> T {
         let mut model_state = self.model_state.read().await;
         f(&mut model_state)
     }
 }
 
-/// Create a node manager
-fn create_node_manager(ctx: Arc<Context>, opts: CommandGlobalOpts) -> NodeManager {
-    let options = opts;
-    match block_on(async { make_node_manager(ctx.clone(), options).await }) {
-        Ok(node_manager) => node_manager,
-        Err(e) => {
-            println!("cannot create a node manager: {e:?}");
-            panic!("{e}")
+/// Cancel the operation in progress with the given token, if any
+async fn cancel(cancellation: &RwLock<Option<CancellationToken>>) -> bool {
+    match cancellation.write().await.take() {
+        Some(token) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+/// State modified by an enrollment, restored when the enrollment is cancelled
+struct EnrollmentSnapshot {
+    /// Only the user information and the enrollment of the model state are restored
+    model_state: ModelState,
+    spaces: StateDirSnapshot<SpaceState>,
+    projects: StateDirSnapshot<ProjectState>,
+    trust_contexts: StateDirSnapshot<TrustContextState>,
+    trust_context: Option<TrustContext>,
+}
+
+impl EnrollmentSnapshot {
+    async fn take(app_state: &AppState) -> Result<Self> {
+        let state = app_state.state().await;
+        Ok(Self {
+            model_state: app_state.model(|m| m.clone()).await,
+            spaces: StateDirSnapshot::take(&state.spaces)?,
+            projects: StateDirSnapshot::take(&state.projects)?,
+            trust_contexts: StateDirSnapshot::take(&state.trust_contexts)?,
+            trust_context: app_state
+                .node_manager
+                .get()
+                .read()
+                .await
+                .default_trust_context(),
+        })
+    }
+
+    async fn restore(self, app_state: &AppState) -> Result<()> {
+        let state = app_state.state().await;
+        self.spaces.restore(&state.spaces)?;
+        self.projects.restore(&state.projects)?;
+        self.trust_contexts.restore(&state.trust_contexts)?;
+        app_state
+            .node_manager
+            .get()
+            .write()
+            .await
+            .restore_trust_context(self.trust_context);
+        let model_state = self.model_state;
+        app_state
+            .model_mut(|m| m.restore_enrollment(model_state))
+            .await
+    }
+}
+
+/// Items of a directory of the cli state, with the name of its default item
+struct StateDirSnapshot<I> {
+    items: Vec<(String, I)>,
+    default: Option<String>,
+}
+
+impl<I: StateItemTrait> StateDirSnapshot<I> {
+    fn take(dir: &impl StateDirTrait<Item = I>) -> Result<Self> {
+        let mut items = vec![];
+        let mut default = None;
+        for name in dir.list_items_names()? {
+            if dir.is_default(&name).unwrap_or(false) {
+                default = Some(name.clone());
+            }
+            let item = dir.get(&name)?;
+            items.push((name, item));
+        }
+        Ok(Self { items, default })
+    }
+
+    /// Delete the items added since the snapshot was taken and write back the other ones
+    fn restore(self, dir: &impl StateDirTrait<Item = I>) -> Result<()> {
+        for name in dir.list_items_names()? {
+            if !self.items.iter().any(|(item_name, _)| item_name == &name) {
+                dir.delete(&name)?;
+            }
         }
+        for (_, item) in &self.items {
+            item.persist()?;
+        }
+        if let Some(default) = &self.default {
+            dir.set_default(default)?;
+        }
+        Ok(())
+    }
+}
+
+/// Stages of `AppState::reset`, in the order they are run
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResetStage {
+    StoppingNodeManager,
+    ResettingState,
+    CreatingNodeManager,
+    RecreatingModelStateRepository,
+}
+
+impl ResetStage {
+    /// Number of stages of a reset, to display the progress of a reset
+    pub const COUNT: usize = 4;
+
+    /// Position of the stage, starting at 1
+    pub fn step(&self) -> usize {
+        match self {
+            ResetStage::StoppingNodeManager => 1,
+            ResetStage::ResettingState => 2,
+            ResetStage::CreatingNodeManager => 3,
+            ResetStage::RecreatingModelStateRepository => 4,
+        }
+    }
+}
+
+impl fmt::Display for ResetStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            ResetStage::StoppingNodeManager => "Stopping the node manager",
+            ResetStage::ResettingState => "Resetting the state",
+            ResetStage::CreatingNodeManager => "Creating a new node manager",
+            ResetStage::RecreatingModelStateRepository => "Recreating the model state repository",
+        };
+        write!(f, "{description}")
+    }
+}
+
+/// Summary of the state recreated by `AppState::reset`
+#[derive(Serialize, Debug, Clone)]
+pub struct ResetReport {
+    /// Address of the TCP listener of the new node manager
+    pub listen_address: Option<String>,
+    /// Path of the recreated model state repository
+    pub model_state_path: PathBuf,
+    /// True if the user is still enrolled after the reset
+    pub enrolled: bool,
+}
+
+impl fmt::Display for ResetReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Reset complete")?;
+        if let Some(listen_address) = &self.listen_address {
+            write!(f, ", node listening on {listen_address}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Use the runtime of the first node started by the application for all its async actions.
+/// The tauri runtime can only be set once, before it is used: the nodes started afterwards in the
+/// same process, for example in tests, reuse the established runtime
+fn set_async_runtime(context: &Context) {
+    static ASYNC_RUNTIME: Once = Once::new();
+    if ASYNC_RUNTIME.is_completed() {
+        debug!("the async runtime is already set, it is reused");
+        return;
+    }
+    ASYNC_RUNTIME.call_once(|| tauri::async_runtime::set(context.runtime().clone()));
+}
+
+/// Run a future on the async runtime of the application.
+/// The tests use it instead of `tauri::async_runtime::block_on`, which would set a default
+/// tauri runtime if no node was started yet, so that `set_async_runtime` always comes first
+#[cfg(test)]
+pub(crate) fn test_block_on<F: Future>(future: F) -> F::Output {
+    static TEST_NODE: std::sync::OnceLock<Arc<Context>> = std::sync::OnceLock::new();
+    TEST_NODE.get_or_init(|| start_node(Some(1)));
+    block_on(future)
+}
+
+/// Creation of an application state which failed because the vault of its node is locked,
+/// returned with `Error::VaultLocked`.
+///
+/// The node started for the failed attempt is kept with the settings, so that the creation
+/// can be retried once the vault is unlocked. The node can't be stopped since its runtime may
+/// be the one running the async actions
+pub struct AppStateRetry {
+    settings: AppSettings,
+    secret_store: Arc<dyn SecretStore>,
+    context: Arc<Context>,
+}
+
+impl AppStateRetry {
+    /// Create the application state again, with the same settings and the same node
+    pub fn retry(self) -> Result<AppState> {
+        AppState::try_with_node(self.settings, self.secret_store, self.context)
     }
 }
 
-/// Make a node manager with a default node called "default"
+impl fmt::Debug for AppStateRetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AppStateRetry")
+            .field("settings", &self.settings)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Start a node whose runtime uses `worker_threads` worker threads, one per CPU core if `None`
+fn start_node(worker_threads: Option<usize>) -> Arc<Context> {
+    let node_builder = NodeBuilder::new().no_logging();
+    let node_builder = match worker_threads {
+        Some(worker_threads) => node_builder.with_worker_threads(worker_threads),
+        None => node_builder,
+    };
+    let (context, mut executor) = node_builder.build();
+    let context = Arc::new(context);
+
+    // from now on we use the same runtime everywhere we need to run an async action
+    set_async_runtime(&context);
+    // start the router, it is needed for the node manager creation
+    spawn(async move { executor.start_router().await });
+    context
+}
+
+/// Create a node manager, see `make_node_manager`
+fn create_node_manager(
+    ctx: Arc<Context>,
+    opts: CommandGlobalOpts,
+    node_name: &str,
+    secret_store: Arc<dyn SecretStore>,
+    listener_address: ListenerAddress,
+    local_only: bool,
+) -> Result<NodeManager> {
+    block_on(async {
+        make_node_manager(
+            ctx.clone(),
+            opts,
+            node_name,
+            secret_store,
+            listener_address,
+            local_only,
+        )
+        .await
+    })
+}
+
+/// Make a node manager for a node called `node_name`, listening on `listener_address`.
+/// If the port of the listener is already in use and the fallback is enabled, the node listens
+/// on a port chosen by the operating system instead.
+/// In local-only mode the node has no trust context, even if there is a default project.
+/// `Error::VaultLocked` is returned if the secret store reports that the vault is locked
 pub(crate) async fn make_node_manager(
     ctx: Arc<Context>,
     opts: CommandGlobalOpts,
-) -> miette::Result<NodeManager> {
-    init_node_state(&opts, NODE_NAME, None, None).await?;
+    node_name: &str,
+    secret_store: Arc<dyn SecretStore>,
+    listener_address: ListenerAddress,
+    local_only: bool,
+) -> Result<NodeManager> {
+    init_node_state(&opts, node_name, None, None).await?;
+    let node_state = opts.state.nodes.get(node_name)?;
+    let vault_state = VaultState::load(node_state.config().vault_path()?)?;
+    let vault = secret_store.vault(&vault_state).await?;
 
     let tcp = TcpTransport::create(&ctx).await.into_diagnostic()?;
-    let options = TcpListenerOptions::new();
-    let listener = tcp
-        .listen(&"127.0.0.1:0", options)
-        .await
-        .into_diagnostic()?;
-    let trust_context_config =
+    // keepalive detects the connections dropped while the computer was asleep
+    let options = || TcpListenerOptions::new().with_keepalive(TcpKeepaliveOptions::default());
+    let preferred_address = listener_address.socket_address();
+    let listener = match tcp.listen(preferred_address.to_string(), options()).await {
+        Err(e) if listener_address.can_fall_back(&e) => {
+            let listener = tcp
+                .listen(
+                    listener_address.ip.loopback_address().to_string(),
+                    options(),
+                )
+                .await;
+            if let Ok(listener) = &listener {
+                warn!(
+                    preferred = %preferred_address,
+                    actual = %listener.socket_address(),
+                    "the listener address of the node is already in use, another port is used"
+                );
+            }
+            listener
+        }
+        listener => listener,
+    }
+    .into_diagnostic()?;
+    let trust_context_config = if local_only {
+        None
+    } else {
         TrustContextConfigBuilder::new(&opts.state, &TrustContextOpts::default())?
             .with_authority_identity(None)
             .with_credential_name(None)
-            .build();
+            .build()
+    };
 
     let node_manager = NodeManager::create(
         &ctx,
-        NodeManagerGeneralOptions::new(opts.state.clone(), NODE_NAME.to_string(), false, None),
+        NodeManagerGeneralOptions::new(opts.state.clone(), node_name.to_string(), false, None)
+            .with_vault(vault),
         NodeManagerTransportOptions::new(listener.flow_control_id().clone(), tcp),
         NodeManagerTrustOptions::new(trust_context_config),
     )
@@ -206,40 +1491,758 @@ pub(crate) async fn make_node_manager(
     Ok(node_manager)
 }
 
-/// Create the repository containing the model state
-fn create_model_state_repository(state: CliState) -> Arc<dyn ModelStateRepository> {
-    let identity_path = state.identities.identities_repository_path().unwrap();
-    match block_on(async move { LmdbModelStateRepository::new(identity_path).await }) {
-        Ok(model_state_repository) => Arc::new(model_state_repository),
+/// Create the repository containing the model state.
+/// An error is returned if its path is invalid or if its directory is not writable
+fn create_model_state_repository(
+    state: CliState,
+    node_name: &str,
+) -> Result<Arc<dyn ModelStateRepository>> {
+    let model_state_path = model_state_repository_path(&state).map_err(|e| {
+        error!(%e, "cannot determine the model state repository path");
+        e
+    })?;
+    match block_on(async move { LmdbModelStateRepository::new(model_state_path, node_name).await })
+    {
+        Ok(model_state_repository) => Ok(Arc::new(model_state_repository)),
         Err(e) => {
-            println!("cannot create a model state repository manager: {e:?}");
-            panic!("{}", e)
+            error!(%e, "cannot create a model state repository manager");
+            Err(e)
         }
     }
 }
 
-/// Load a previously persisted ModelState
+/// Create the portals declared in the startup configuration of the node, if it has one.
+/// An invalid configuration is logged but doesn't prevent the application from starting
+fn load_startup_config(mut node_manager: NodeManagerWorker, context: Arc<Context>) {
+    block_on(async {
+        if let Err(e) = node_manager.load_startup_config(&context).await {
+            error!(%e, "cannot load the node startup configuration");
+        }
+    })
+}
+
+/// Load a previously persisted ModelState and restore its portals.
+/// A corrupted model state is replaced with an empty one, and the corruption is returned.
+/// The other errors are returned so that the application state isn't created
 fn load_model_state(
     model_state_repository: Arc<dyn ModelStateRepository>,
-    node_manager: &mut NodeManager,
+    node_manager: NodeManagerWorker,
     context: Arc<Context>,
-) -> ModelState {
+) -> Result<(ModelState, Option<String>)> {
     block_on(async {
         match model_state_repository.load().await {
             Ok(model_state) => {
-                let model_state = model_state.unwrap_or(ModelState::default());
-                crate::shared_service::tcp::outlet::model_state::load_model_state(
-                    context.clone(),
-                    node_manager,
-                    &model_state,
-                )
-                .await;
-                model_state
+                let mut model_state = model_state.unwrap_or(ModelState::default());
+                let mut restorer = NodeManagerPortalRestorer::new(context.clone(), node_manager);
+                restore_portals(&mut restorer, &mut model_state).await;
+                Ok((model_state, None))
+            }
+            // the application starts with an empty model state, which replaces the corrupted one
+            // when it is stored. The corrupted one has been backed up by the repository
+            Err(e @ Error::CorruptedModelState(_)) => {
+                error!(%e, "the model state is corrupted, starting with an empty model state");
+                Ok((ModelState::default(), Some(e.to_string())))
             }
             Err(e) => {
-                println!("cannot load the model state: {e:?}");
-                panic!("{}", e)
+                error!(%e, "cannot load the model state");
+                Err(e)
             }
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use ockam::vault::VaultStorage;
+    use ockam::TcpConnectionOptions;
+    use ockam_api::config::cli::TrustContextConfig;
+    use ockam_core::async_trait;
+    use tempfile::TempDir;
+
+    use crate::app::listen_address::ListenerIp;
+
+    use super::test_block_on as block_on;
+    use super::*;
+
+    /// Return the settings of an application state whose cli state is stored in `ockam_home`.
+    /// The `OCKAM_HOME` environment variable is not set since it is shared by the tests
+    /// running in parallel
+    fn settings(ockam_home: &TempDir, node_name: &str) -> AppSettings {
+        AppSettings::new(node_name)
+            .with_worker_threads(Some(1))
+            .with_state_dir(ockam_home.path())
+    }
+
+    /// Create an application state whose cli state is stored in `ockam_home`
+    fn app_state_in(ockam_home: &TempDir, node_name: &str) -> AppState {
+        AppState::try_with_settings(settings(ockam_home, node_name), Arc::new(FileSecretStore))
+            .unwrap()
+    }
+
+    /// Secret store whose vaults can't be opened while it is locked, like a locked keychain
+    struct LockableSecretStore {
+        locked: AtomicBool,
+    }
+
+    #[async_trait]
+    impl SecretStore for LockableSecretStore {
+        async fn vault_storage(&self, vault_state: &VaultState) -> Result<VaultStorage> {
+            if self.locked.load(Ordering::SeqCst) {
+                return Err(Error::vault_locked("the keychain is locked"));
+            }
+            FileSecretStore.vault_storage(vault_state).await
+        }
+    }
+
+    #[test]
+    fn the_app_state_can_be_created_once_the_vault_is_unlocked() {
+        let ockam_home = tempfile::tempdir().unwrap();
+        let secret_store = Arc::new(LockableSecretStore {
+            locked: AtomicBool::new(true),
+        });
+
+        let error =
+            AppState::try_with_settings(settings(&ockam_home, "locked"), secret_store.clone())
+                .err()
+                .unwrap();
+        assert!(error.to_string().contains("the keychain is locked"));
+        let retry = match error {
+            Error::VaultLocked {
+                retry: Some(retry), ..
+            } => retry,
+            error => panic!("the vault should be locked: {error:?}"),
+        };
+        // the retry keeps the settings of the failed attempt
+        assert_eq!(retry.settings, settings(&ockam_home, "locked"));
+
+        // the creation is retried in the same process once the vault is unlocked
+        secret_store.locked.store(false, Ordering::SeqCst);
+        let app_state = retry.retry().unwrap();
+        block_on(async {
+            assert!(app_state.tcp_outlet_list().await.is_empty());
+            assert!(app_state.listen_multiaddr().await.is_ok());
+        });
+    }
+
+    /// Secret store whose vaults can't be opened, like a corrupted vault file
+    struct BrokenSecretStore;
+
+    #[async_trait]
+    impl SecretStore for BrokenSecretStore {
+        async fn vault_storage(&self, _vault_state: &VaultState) -> Result<VaultStorage> {
+            Err(Error::Generic("the vault file is corrupted".to_string()))
+        }
+    }
+
+    #[test]
+    fn a_vault_which_cant_be_opened_is_not_retried() {
+        let ockam_home = tempfile::tempdir().unwrap();
+        let error = AppState::try_with_settings(
+            settings(&ockam_home, "broken"),
+            Arc::new(BrokenSecretStore),
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(error, Error::Generic(_)), "{error:?}");
+        assert!(error.to_string().contains("the vault file is corrupted"));
+    }
+
+    #[test]
+    fn two_app_states_can_be_created_in_the_same_process() {
+        let ockam_home = tempfile::tempdir().unwrap();
+
+        let first = app_state_in(&ockam_home, "first");
+        let second = app_state_in(&ockam_home, "second");
+
+        // both application states run their async actions on the established runtime
+        assert!(block_on(first.tcp_outlet_list()).is_empty());
+        assert!(block_on(second.tcp_outlet_list()).is_empty());
+        assert_eq!(first.node_name(), "first");
+        assert_eq!(second.node_name(), "second");
+    }
+
+    #[test]
+    fn the_enrollment_summary_is_updated_after_enroll_and_cleared_after_reset() {
+        let ockam_home = tempfile::tempdir().unwrap();
+        let app_state = app_state_in(&ockam_home, "enrollment");
+        let project = Project {
+            id: "project-id".to_string(),
+            name: PROJECT_NAME.to_string(),
+            ..Default::default()
+        };
+
+        block_on(async {
+            app_state.record_enrollment(&project).await.unwrap();
+            let summary = app_state.model(|m| m.get_enrollment().cloned()).await;
+            let summary = summary.unwrap();
+            assert_eq!(summary.project_id, "project-id");
+            assert_eq!(summary.project_name, PROJECT_NAME);
+            assert_eq!(summary.authority_identifier, None);
+
+            app_state.reset().await.unwrap();
+            assert!(app_state.model(|m| m.get_enrollment().is_none()).await);
+        });
+    }
+
+    #[test]
+    fn a_cancelled_reset_leaves_the_application_running() {
+        let ockam_home = tempfile::tempdir().unwrap();
+        let app_state = app_state_in(&ockam_home, "reset-cancelled");
+        let project = Project {
+            id: "project-id".to_string(),
+            name: PROJECT_NAME.to_string(),
+            ..Default::default()
+        };
+
+        block_on(async {
+            app_state.record_enrollment(&project).await.unwrap();
+            let outlet = app_state
+                .create_outlet("127.0.0.1:1".to_string(), "db".to_string(), None)
+                .await
+                .unwrap();
+            let listen_address = app_state.listen_multiaddr().await.unwrap();
+
+            // the reset is cancelled once the node manager has been stopped
+            let cancellation = CancellationToken::new();
+            let error = app_state
+                .reset_with_cancellation(
+                    |stage| {
+                        if stage == ResetStage::ResettingState {
+                            cancellation.cancel()
+                        }
+                    },
+                    &cancellation,
+                )
+                .await
+                .unwrap_err();
+            assert!(error.is_cancelled());
+
+            assert!(app_state.model(|m| m.get_enrollment().is_some()).await);
+            assert_eq!(app_state.tcp_outlet_list().await, vec![outlet]);
+            assert_eq!(app_state.listen_multiaddr().await.unwrap(), listen_address);
+
+            // the restarted node manager can be stopped again by a complete reset
+            app_state.reset().await.unwrap();
+            assert!(app_state.model(|m| m.get_enrollment().is_none()).await);
+        });
+    }
+
+    /// Return the identifier of the default trust context of the node manager
+    async fn default_trust_context_id(app_state: &AppState) -> Option<String> {
+        let node_manager = app_state.node_manager.get().read().await;
+        node_manager
+            .default_trust_context()
+            .map(|trust_context| trust_context.id().to_string())
+    }
+
+    #[test]
+    fn a_cancelled_enrollment_is_rolled_back() {
+        let ockam_home = tempfile::tempdir().unwrap();
+        let app_state = app_state_in(&ockam_home, "enrollment-cancelled");
+        let project = Project {
+            id: "project-id".to_string(),
+            name: PROJECT_NAME.to_string(),
+            ..Default::default()
+        };
+        let trust_context = TrustContextConfig::new(project.id.clone(), None);
+
+        block_on(async {
+            let initial_trust_context_id = default_trust_context_id(&app_state).await;
+
+            // the enrollment is cancelled after storing its project and its trust context,
+            // while an outlet is created
+            let cancellation = CancellationToken::new();
+            let enrollment = async {
+                let state = app_state.state().await;
+                state.projects.overwrite(&project.name, project.clone())?;
+                state
+                    .trust_contexts
+                    .overwrite(&project.name, trust_context.clone())?;
+                app_state
+                    .node_manager
+                    .get()
+                    .write()
+                    .await
+                    .configure_trust_context(&trust_context)
+                    .await?;
+                app_state.record_enrollment(&project).await?;
+                let outlet = app_state
+                    .create_outlet("127.0.0.1:1".to_string(), "db".to_string(), None)
+                    .await?;
+                app_state
+                    .set_outlet_label(&outlet.alias, "database")
+                    .await?;
+                cancellation.cancel();
+                std::future::pending::<Result<()>>().await
+            };
+            let error = app_state
+                .enroll_with_cancellation(enrollment, &cancellation)
+                .await
+                .unwrap_err();
+            assert!(matches!(error, Error::EnrollmentCancelled));
+
+            assert!(app_state.model(|m| m.get_enrollment().is_none()).await);
+            assert!(!app_state.is_enrolled().await);
+            let state = app_state.state().await;
+            assert!(state.projects.is_empty().unwrap());
+            assert!(state.trust_contexts.is_empty().unwrap());
+            assert_eq!(
+                default_trust_context_id(&app_state).await,
+                initial_trust_context_id
+            );
+
+            // the outlet and its label are not rolled back with the enrollment
+            let outlets = app_state.labeled_outlet_list().await;
+            assert_eq!(outlets.len(), 1);
+            assert_eq!(outlets[0].label.as_deref(), Some("database"));
+        });
+    }
+
+    #[test]
+    fn a_cancelled_credential_refresh_keeps_the_enrollment() {
+        let ockam_home = tempfile::tempdir().unwrap();
+        let app_state = app_state_in(&ockam_home, "refresh-cancelled");
+        let project = Project {
+            id: "project-id".to_string(),
+            name: PROJECT_NAME.to_string(),
+            ..Default::default()
+        };
+
+        block_on(async {
+            app_state.record_enrollment(&project).await.unwrap();
+            app_state
+                .state()
+                .await
+                .projects
+                .overwrite(&project.name, project.clone())
+                .unwrap();
+            let cancellation = CancellationToken::new();
+            cancellation.cancel();
+            let error = app_state
+                .reconcile_enrollment_with_cancellation(&cancellation)
+                .await
+                .unwrap_err();
+            assert!(error.is_cancelled());
+            assert!(app_state.model(|m| m.get_enrollment().is_some()).await);
+        });
+    }
+
+    #[test]
+    fn a_second_enrollment_is_rejected_while_one_is_in_progress() {
+        let ockam_home = tempfile::tempdir().unwrap();
+        let app_state = app_state_in(&ockam_home, "enrollment-concurrent");
+
+        block_on(async {
+            let first = app_state.start_enrollment().await.unwrap();
+            let error = app_state.start_enrollment().await.unwrap_err();
+            assert!(matches!(error, Error::EnrollmentInProgress), "{error:?}");
+
+            // the first enrollment can still be cancelled
+            assert!(app_state.cancel_enrollment().await);
+            assert!(first.is_cancelled());
+            assert!(app_state.start_enrollment().await.is_err());
+
+            // another enrollment can start once the first one has completed
+            app_state.finish_enrollment().await;
+            assert!(!app_state.cancel_enrollment().await);
+            assert!(app_state.start_enrollment().await.is_ok());
+        });
+    }
+
+    #[test]
+    fn the_enrollment_status_can_be_watched() {
+        let ockam_home = tempfile::tempdir().unwrap();
+        let app_state = app_state_in(&ockam_home, "enrollment-watch");
+        let project = Project {
+            id: "project-id".to_string(),
+            name: PROJECT_NAME.to_string(),
+            ..Default::default()
+        };
+
+        block_on(async {
+            let mut status = app_state.enrollment_watch().await;
+            assert_eq!(*status.borrow(), EnrollmentStatus::NotEnrolled);
+
+            app_state.record_enrollment(&project).await.unwrap();
+            status.changed().await.unwrap();
+            assert_eq!(*status.borrow_and_update(), EnrollmentStatus::Enrolled);
+
+            // a new subscriber gets the current status
+            assert_eq!(
+                *app_state.enrollment_watch().await.borrow(),
+                EnrollmentStatus::Enrolled
+            );
+
+            // the status changes when the credential expires
+            let summary = EnrollmentSummary::new(&project)
+                .await
+                .with_credential_expiration(Some(now() + 1));
+            app_state
+                .model_mut(|m| m.set_enrollment(summary))
+                .await
+                .unwrap();
+            status.changed().await.unwrap();
+            assert_eq!(
+                *status.borrow_and_update(),
+                EnrollmentStatus::CredentialExpired
+            );
+
+            app_state.reset().await.unwrap();
+            status.changed().await.unwrap();
+            assert_eq!(*status.borrow(), EnrollmentStatus::NotEnrolled);
+        });
+    }
+
+    #[test]
+    fn the_application_is_never_enrolled_in_local_only_mode() {
+        let ockam_home = tempfile::tempdir().unwrap();
+        let app_state = AppState::try_with_settings(
+            settings(&ockam_home, "local-only").with_local_only(true),
+            Arc::new(FileSecretStore),
+        )
+        .unwrap();
+        let project = Project {
+            id: "project-id".to_string(),
+            name: PROJECT_NAME.to_string(),
+            ..Default::default()
+        };
+
+        block_on(async {
+            assert!(app_state.is_local_only());
+            app_state.record_enrollment(&project).await.unwrap();
+            assert!(!app_state.is_enrolled().await);
+            assert_eq!(
+                *app_state.enrollment_watch().await.borrow(),
+                EnrollmentStatus::NotEnrolled
+            );
+            app_state.reconcile_enrollment().await.unwrap();
+            assert!(app_state.model(|m| m.get_enrollment().is_none()).await);
+
+            // the local outlets can still be created
+            let outlet = app_state
+                .create_outlet("127.0.0.1:1".to_string(), "local".to_string(), None)
+                .await
+                .unwrap();
+            assert_eq!(app_state.tcp_outlet_list().await, vec![outlet]);
+        });
+    }
+
+    #[test]
+    fn an_outlet_label_is_persisted_without_changing_the_outlet() {
+        let ockam_home = tempfile::tempdir().unwrap();
+        let app_state = app_state_in(&ockam_home, "outlet-label");
+
+        block_on(async {
+            let outlet = app_state
+                .create_outlet("127.0.0.1:1".to_string(), "db".to_string(), None)
+                .await
+                .unwrap();
+            app_state
+                .set_outlet_label(&outlet.alias, " Database ")
+                .await
+                .unwrap();
+            assert_eq!(
+                app_state.outlet_label(&outlet.alias).await.as_deref(),
+                Some("Database")
+            );
+            assert_eq!(app_state.tcp_outlet_list().await, vec![outlet.clone()]);
+            // the label is part of the listed outlets
+            let labeled = app_state.labeled_outlet_list().await;
+            assert_eq!(labeled[0].label.as_deref(), Some("Database"));
+            assert_eq!(labeled[0].display_name(), "Database");
+            let json = serde_json::to_value(&labeled[0]).unwrap();
+            assert_eq!(json["label"], "Database");
+            assert_eq!(json["alias"], outlet.alias);
+
+            // the label and the latencies follow the outlet when it is renamed
+            app_state
+                .record_outlet_latency(&outlet.alias, Duration::from_millis(10))
+                .await;
+            app_state
+                .rename_outlet(&outlet.alias, "db-2")
+                .await
+                .unwrap();
+            assert_eq!(app_state.outlet_label(&outlet.alias).await, None);
+            assert_eq!(
+                app_state.outlet_label("db-2").await.as_deref(),
+                Some("Database")
+            );
+            let latency = app_state.outlet_latency("db-2").await.unwrap();
+            assert_eq!(latency.samples, 1);
+            assert_eq!(latency.p50_ms, Some(10));
+
+            // an empty label removes the label
+            app_state.set_outlet_label("db-2", "").await.unwrap();
+            assert_eq!(app_state.outlet_label("db-2").await, None);
+
+            assert!(app_state
+                .set_outlet_label("unknown", "label")
+                .await
+                .is_err());
+        });
+    }
+
+    #[test]
+    fn an_inlet_is_restored_when_the_application_restarts() {
+        let ockam_home = tempfile::tempdir().unwrap();
+        // a free port for the inlet
+        let bind_addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let outlet_addr = MultiAddr::from_str("/service/db").unwrap();
+
+        let app_state = app_state_in(&ockam_home, "inlet-restart");
+        block_on(async {
+            app_state
+                .create_outlet("127.0.0.1:5432".to_string(), "db".to_string(), None)
+                .await
+                .unwrap();
+            let inlet = app_state
+                .create_inlet(
+                    "db-inlet",
+                    bind_addr,
+                    outlet_addr.clone(),
+                    Some("db".to_string()),
+                )
+                .await
+                .unwrap();
+            assert_eq!(inlet.bind_addr, bind_addr.to_string());
+            app_state.shutdown().await;
+        });
+        drop(app_state);
+
+        // the inlet is recreated after its outlet by the restarted application
+        let app_state = app_state_in(&ockam_home, "inlet-restart");
+        block_on(async {
+            let inlets = app_state.model(|m| m.get_tcp_inlets().to_vec()).await;
+            assert_eq!(inlets.len(), 1);
+            assert_eq!(inlets[0].alias, "db-inlet");
+            assert_eq!(inlets[0].outlet_addr, outlet_addr.to_string());
+            assert!(!inlets[0].pending);
+            let running = app_state.node_manager.get().read().await.list_inlets();
+            assert_eq!(running.list.len(), 1);
+            assert_eq!(running.list[0].alias, "db-inlet");
+
+            // a deleted inlet is not restored
+            app_state.delete_inlet("db-inlet").await.unwrap();
+            assert!(app_state.model(|m| m.get_tcp_inlets().is_empty()).await);
+        });
+    }
+
+    #[test]
+    fn the_portals_are_cleared_from_the_node_and_the_model_state() {
+        let ockam_home = tempfile::tempdir().unwrap();
+        let app_state = app_state_in(&ockam_home, "clear-portals");
+        let bind_addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        block_on(async {
+            // nothing to remove
+            let cleared = app_state.remove_portals().await.unwrap();
+            assert!(cleared.removed.is_empty());
+
+            app_state
+                .create_outlet("127.0.0.1:5432".to_string(), "db".to_string(), None)
+                .await
+                .unwrap();
+            app_state
+                .create_outlet("127.0.0.1:5433".to_string(), "stuck".to_string(), None)
+                .await
+                .unwrap();
+            // an inlet running on the node without being persisted
+            NodeManager::create_inlet(
+                app_state.node_manager.get().clone(),
+                &app_state.context,
+                "node-inlet",
+                bind_addr,
+                MultiAddr::from_str("/service/db").unwrap(),
+                InletOptions::default(),
+            )
+            .await
+            .unwrap();
+            // an outlet whose worker can't be stopped anymore
+            app_state
+                .context
+                .stop_worker(Address::from_string("stuck"))
+                .await
+                .unwrap();
+
+            let cleared = app_state.remove_portals().await.unwrap();
+            assert_eq!(
+                cleared.removed,
+                vec![
+                    PortalRemoved::inlet("node-inlet"),
+                    PortalRemoved::outlet("db")
+                ]
+            );
+            assert_eq!(cleared.failed.len(), 1);
+            assert_eq!(cleared.failed[0].0, PortalRemoved::outlet("stuck"));
+            assert!(cleared.into_result().is_err());
+
+            // the portal which couldn't be removed is still persisted
+            let outlets = app_state.model(|m| m.get_tcp_outlets().to_vec()).await;
+            assert_eq!(outlets.len(), 1);
+            assert_eq!(outlets[0].alias, "stuck");
+            assert!(app_state
+                .node_manager
+                .get()
+                .read()
+                .await
+                .list_inlets()
+                .list
+                .is_empty());
+        });
+    }
+
+    #[test]
+    fn the_node_can_listen_on_the_ipv6_loopback() {
+        let ockam_home = tempfile::tempdir().unwrap();
+        let app_state = app_state_in(&ockam_home, "ipv6");
+
+        block_on(async {
+            let node_manager = make_node_manager(
+                app_state.context.clone(),
+                app_state.options().await,
+                "ipv6-node",
+                app_state.secret_store.clone(),
+                ListenerIp::Ipv6.into(),
+                false,
+            )
+            .await
+            .unwrap();
+            let address = node_manager.tcp_listener_addresses()[0];
+            assert_eq!(address.ip(), std::net::Ipv6Addr::LOCALHOST);
+            let multiaddr = socket_addr_to_multiaddr(&replace_unspecified_ip(address), None);
+            assert_eq!(
+                multiaddr.unwrap().to_string(),
+                format!("/ip6/::1/tcp/{}", address.port())
+            );
+
+            // a peer can connect to the node over the loopback interface
+            let tcp = TcpTransport::create(&app_state.context).await.unwrap();
+            tcp.connect(address.to_string(), TcpConnectionOptions::new())
+                .await
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn the_listener_address_of_the_settings_is_kept_after_a_reset() {
+        let ockam_home = tempfile::tempdir().unwrap();
+        let settings =
+            settings(&ockam_home, "ipv6-settings").with_listener_address(ListenerIp::Ipv6.into());
+        let app_state = AppState::try_with_settings(settings, Arc::new(FileSecretStore)).unwrap();
+
+        block_on(async {
+            let multiaddr = app_state.listen_multiaddr().await.unwrap();
+            assert!(multiaddr.to_string().starts_with("/ip6/::1/tcp/"));
+
+            app_state.reset_with_progress(|_| {}).await.unwrap();
+            let multiaddr = app_state.listen_multiaddr().await.unwrap();
+            assert!(multiaddr.to_string().starts_with("/ip6/::1/tcp/"));
+        });
+    }
+
+    #[test]
+    fn a_relayed_outlet_is_registered_at_the_relay() {
+        let ockam_home = tempfile::tempdir().unwrap();
+        // the relay is another node, standing for the relay of a project, with its own state
+        let relay_home = tempfile::tempdir().unwrap();
+        let relay_state = app_state_in(&relay_home, "relay");
+        let app_state = app_state_in(&ockam_home, "relayed");
+        let socket_addr: SocketAddr = "127.0.0.1:5432".parse().unwrap();
+
+        block_on(async {
+            let relay_address = relay_state.listen_multiaddr().await.unwrap();
+            let relay = Relay::Node(relay_address.clone());
+            let relayed_outlet = app_state
+                .create_relayed_outlet_at(&relay, "db", socket_addr)
+                .await
+                .unwrap();
+            assert_eq!(relayed_outlet.outlet.alias, "db");
+            assert_eq!(relayed_outlet.remote_address, "db");
+            assert_eq!(
+                relayed_outlet.relay_route,
+                format!("{relay_address}/service/db/secure/api/service/db")
+            );
+
+            // the forwarder of the outlet is running at the relay
+            let workers = relay_state.context().list_workers().await.unwrap();
+            assert!(workers.contains(&Address::from_string("db")));
+            assert!(app_state.outlet("db").await.is_some());
+            assert_eq!(
+                app_state.model(|m| m.get_relayed_outlets().to_vec()).await,
+                vec!["db".to_string()]
+            );
+
+            // the relay of the project is only available once enrolled
+            let error = app_state.create_relayed_outlet("other", socket_addr).await;
+            assert!(error.is_err());
+            assert!(app_state.outlet("other").await.is_none());
+            assert!(app_state.register_relays().await.is_empty());
+
+            // the forwarder is named after the worker of the outlet, which is kept on rename
+            app_state.rename_outlet("db", "database").await.unwrap();
+            assert_eq!(
+                app_state.model(|m| m.get_relayed_outlets().to_vec()).await,
+                vec!["database".to_string()]
+            );
+            assert_eq!(
+                app_state.relayed_forwarders.read().await.get("db"),
+                Some(&"db".to_string())
+            );
+
+            // the forwarder of a deleted outlet is deleted
+            app_state.remove_portals().await.unwrap();
+            assert!(app_state.relayed_forwarders.read().await.is_empty());
+            assert!(
+                app_state
+                    .model(|m| m.get_relayed_outlets().is_empty())
+                    .await
+            );
+        });
+    }
+
+    #[test]
+    fn the_node_listens_on_another_port_when_its_port_is_in_use() {
+        let ockam_home = tempfile::tempdir().unwrap();
+        // the port is kept by a previous instance of the application
+        let occupied = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = occupied.local_addr().unwrap().port();
+        let listener_address = ListenerAddress::default().with_port(port);
+
+        let strict = settings(&ockam_home, "port-in-use-strict")
+            .with_listener_address(listener_address.with_ephemeral_port_fallback(false));
+        assert!(AppState::try_with_settings(strict, Arc::new(FileSecretStore)).is_err());
+
+        let settings = settings(&ockam_home, "port-in-use").with_listener_address(listener_address);
+        let app_state = AppState::try_with_settings(settings, Arc::new(FileSecretStore)).unwrap();
+        block_on(async {
+            let address = app_state
+                .node_manager
+                .get()
+                .read()
+                .await
+                .tcp_listener_addresses()[0];
+            assert_eq!(address.ip(), std::net::Ipv4Addr::LOCALHOST);
+            assert_ne!(address.port(), port);
+            assert_ne!(address.port(), 0);
+
+            // the fallback is used again when the node is reset
+            app_state.reset_with_progress(|_| {}).await.unwrap();
+            let address = app_state
+                .node_manager
+                .get()
+                .read()
+                .await
+                .tcp_listener_addresses()[0];
+            assert_ne!(address.port(), port);
+        });
+    }
+}
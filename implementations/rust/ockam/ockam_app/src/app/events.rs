@@ -1 +1,4 @@
 pub const SYSTEM_TRAY_ON_UPDATE: &str = "app/system_tray/on_update";
+pub const ENROLLMENT_STATUS: &str = "app/enrollment/status";
+pub const PORTAL_REMOVED: &str = "app/portal/removed";
+pub const RESET_PROGRESS: &str = "app/reset/progress";
@@ -0,0 +1,141 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str::FromStr;
+
+use ockam_core::errcode::Kind;
+use tauri::{AppHandle, Manager, Wry};
+use tracing::error;
+
+use crate::app::AppState;
+use crate::error::Error;
+
+/// Version of the loopback address the TCP listener of the node is bound to.
+/// IPv4 is the default so that peers without IPv6 support can still connect
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ListenerIp {
+    #[default]
+    Ipv4,
+    /// Used in the environments without IPv4, for example IPv6-only containers
+    Ipv6,
+}
+
+impl ListenerIp {
+    /// Loopback address with a port chosen by the operating system
+    pub fn loopback_address(&self) -> SocketAddr {
+        let ip: IpAddr = match self {
+            ListenerIp::Ipv4 => Ipv4Addr::LOCALHOST.into(),
+            ListenerIp::Ipv6 => Ipv6Addr::LOCALHOST.into(),
+        };
+        SocketAddr::new(ip, 0)
+    }
+}
+
+/// Address the TCP listener of the node is bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListenerAddress {
+    pub ip: ListenerIp,
+    /// Port of the listener, a port chosen by the operating system is used if not set
+    pub port: Option<u16>,
+    /// Listen on a port chosen by the operating system if the port is already in use,
+    /// for example by a previous instance of the application which didn't release it
+    pub ephemeral_port_fallback: bool,
+}
+
+impl Default for ListenerAddress {
+    fn default() -> Self {
+        ListenerIp::default().into()
+    }
+}
+
+impl From<ListenerIp> for ListenerAddress {
+    fn from(ip: ListenerIp) -> Self {
+        Self {
+            ip,
+            port: None,
+            ephemeral_port_fallback: true,
+        }
+    }
+}
+
+impl ListenerAddress {
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    pub fn with_ephemeral_port_fallback(mut self, fallback: bool) -> Self {
+        self.ephemeral_port_fallback = fallback;
+        self
+    }
+
+    /// Loopback address with the port of the listener, or a port chosen by the operating system
+    pub fn socket_address(&self) -> SocketAddr {
+        let mut address = self.ip.loopback_address();
+        if let Some(port) = self.port {
+            address.set_port(port)
+        }
+        address
+    }
+
+    /// Return true if the listener can be bound to a port chosen by the operating system
+    /// after failing with this error
+    pub fn can_fall_back(&self, error: &ockam_core::Error) -> bool {
+        self.port.is_some() && self.ephemeral_port_fallback && error.code().kind == Kind::Conflict
+    }
+}
+
+impl FromStr for ListenerIp {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ipv4" => Ok(ListenerIp::Ipv4),
+            "ipv6" => Ok(ListenerIp::Ipv6),
+            _ => Err(Error::Generic(format!(
+                "{s} is not a listener IP, the valid values are ipv4 and ipv6"
+            ))),
+        }
+    }
+}
+
+/// Return the address of the TCP listener of the node as a MultiAddr,
+/// for example `/ip4/127.0.0.1/tcp/4000`.
+#[tauri::command]
+pub async fn node_listen_multiaddr(app: AppHandle<Wry>) -> Result<String, String> {
+    let app_state = app.state::<AppState>();
+    app_state
+        .listen_multiaddr()
+        .await
+        .map(|ma| ma.to_string())
+        .map_err(|e| {
+            error!("{:?}", e);
+            e.to_string()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_the_listener_ip() {
+        assert_eq!("ipv4".parse::<ListenerIp>().unwrap(), ListenerIp::Ipv4);
+        assert_eq!("IPv6".parse::<ListenerIp>().unwrap(), ListenerIp::Ipv6);
+        assert!("dual".parse::<ListenerIp>().is_err());
+        assert_eq!(
+            ListenerIp::Ipv6.loopback_address().to_string(),
+            "[::1]:0".to_string()
+        );
+        assert_eq!(
+            ListenerIp::default().loopback_address().to_string(),
+            "127.0.0.1:0".to_string()
+        );
+    }
+
+    #[test]
+    fn the_listener_address_can_have_a_fixed_port() {
+        let address = ListenerAddress::default();
+        assert_eq!(address.socket_address().to_string(), "127.0.0.1:0");
+        let address = ListenerAddress::from(ListenerIp::Ipv6).with_port(4000);
+        assert_eq!(address.socket_address().to_string(), "[::1]:4000");
+    }
+}
@@ -1,37 +1,77 @@
 use std::error::Error;
 
-use tauri::{App, Manager, SystemTray, Wry};
+use tauri::{App, AppHandle, Manager, SystemTray, Wry};
+use tracing::{error, warn};
+
+use crate::shared_service::tcp::outlet::latency::sample_outlet_latencies;
 
 pub use app_state::*;
+pub use listen_address::*;
 pub use logging::*;
 pub use model_state::*;
+pub use model_state_diff::*;
 pub use process::*;
+pub use secret_store::*;
+pub use secure_channels::*;
+pub use settings::*;
 pub use tray_menu::*;
+pub use vault_locked::*;
 
 mod app_state;
 pub(crate) mod events;
+mod listen_address;
 mod logging;
 mod model_state;
+// the diffs are not used yet by the application, they will support backups and undos
+#[allow(dead_code)]
+mod model_state_diff;
 mod model_state_repository;
 mod process;
+mod secret_store;
+mod secure_channels;
+mod settings;
 mod tray_menu;
+mod vault_locked;
 
 /// Set up the Tauri application. This function is called once when the application starts.
 ///
 /// Create the initial version of the system tray menu and the event listeners to update it.
-pub fn setup_app(app: &mut App<Wry>) -> Result<(), Box<dyn Error>> {
-    let app_state = app.state::<AppState>();
+/// If the application state couldn't be created because the vault of the node is locked, the
+/// tray menu asks the user to unlock it and retry instead, see `on_vault_retry`
+pub fn setup_app(
+    app: &mut App<Wry>,
+    app_state: crate::Result<AppState>,
+) -> Result<(), Box<dyn Error>> {
+    let tray_menu = match app_state {
+        Ok(app_state) => {
+            app.manage(app_state);
+            tauri::async_runtime::block_on(build_tray_menu(&app.state::<AppState>()))
+        }
+        Err(crate::error::Error::VaultLocked { reason, retry }) => {
+            warn!(%reason, "the vault of the node is locked, the user is asked to unlock it");
+            app.manage(VaultLockedState::new(retry.map(|r| *r)));
+            build_vault_locked_menu()
+        }
+        Err(e) => return Err(e.into()),
+    };
     let moved_app = app.handle();
-    let tray_menu = tauri::async_runtime::block_on(build_tray_menu(&app_state));
-
     SystemTray::new()
         .with_menu(tray_menu)
         .on_event(move |event| process_system_tray_event(&moved_app, event))
         .build(app)
         .expect("Couldn't initialize the system tray menu");
 
+    if app.try_state::<AppState>().is_some() {
+        start_app(&app.handle());
+    }
+    Ok(())
+}
+
+/// Start the event listeners and the background tasks of the application, once its state
+/// is created
+pub(crate) fn start_app(app: &AppHandle<Wry>) {
     // Setup event listeners
-    let moved_app = app.handle();
+    let moved_app = app.clone();
     app.listen_global(events::SYSTEM_TRAY_ON_UPDATE, move |_event| {
         let moved_app = moved_app.clone();
         tauri::async_runtime::spawn(async move {
@@ -41,5 +81,27 @@ pub fn setup_app(app: &mut App<Wry>) -> Result<(), Box<dyn Error>> {
                 .set_menu(build_tray_menu(&app_state).await)
         });
     });
-    Ok(())
+
+    // Check the last known enrollment, which can be displayed in the meantime
+    let moved_app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let app_state = moved_app.state::<AppState>();
+        if let Err(e) = app_state.reconcile_enrollment().await {
+            error!(%e, "cannot reconcile the enrollment");
+        }
+        // the outlets restored at startup are reachable through the relay again
+        app_state.register_relays().await;
+    });
+
+    // Refresh the tray menu as soon as the enrollment status changes
+    let moved_app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut status = moved_app.state::<AppState>().enrollment_watch().await;
+        while status.changed().await.is_ok() {
+            moved_app.trigger_global(events::SYSTEM_TRAY_ON_UPDATE, None);
+        }
+    });
+
+    // Collect the latency of the outlets over time
+    tauri::async_runtime::spawn(sample_outlet_latencies(app.clone()));
 }
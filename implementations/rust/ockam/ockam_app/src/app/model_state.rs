@@ -1,18 +1,38 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use ockam_api::cloud::enroll::auth0::UserInfo;
 use ockam_api::nodes::models::portal::OutletStatus;
 
+use crate::enroll::EnrollmentSummary;
+use crate::shared_service::tcp::inlet::model_state::TcpInletModel;
+
 /// The ModelState stores all the data which is not maintained by the NodeManager:
 ///    - user information
-///    - shared services
+///    - shared services (outlets and inlets)
 ///    - sent invitations
 ///    - etc...
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
 pub struct ModelState {
     user_info: Option<UserInfo>,
+    /// Name of the project the application was enrolled with
+    #[serde(default)]
+    enrolled_project: Option<String>,
+    /// Last known enrollment, displayed until it is reconciled when the application starts
+    #[serde(default)]
+    enrollment: Option<EnrollmentSummary>,
     #[serde(default = "Vec::new")]
     pub(crate) tcp_outlets: Vec<OutletStatus>,
+    /// Labels displayed instead of the aliases of the outlets, by outlet alias
+    #[serde(default)]
+    pub(crate) tcp_outlet_labels: BTreeMap<String, String>,
+    #[serde(default = "Vec::new")]
+    pub(crate) tcp_inlets: Vec<TcpInletModel>,
+    /// Aliases of the outlets reachable through the relay of the project
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) relayed_outlets: Vec<String>,
 }
 
 impl Default for ModelState {
@@ -25,7 +45,12 @@ impl ModelState {
     pub fn new(user_info: Option<UserInfo>, tcp_outlets: Vec<OutletStatus>) -> Self {
         Self {
             user_info,
+            enrolled_project: None,
+            enrollment: None,
             tcp_outlets,
+            tcp_outlet_labels: BTreeMap::new(),
+            tcp_inlets: vec![],
+            relayed_outlets: vec![],
         }
     }
 
@@ -36,4 +61,81 @@ impl ModelState {
     pub fn get_user_info(&self) -> Option<UserInfo> {
         self.user_info.clone()
     }
+
+    pub fn set_enrolled_project(&mut self, project_name: impl Into<String>) {
+        self.enrolled_project = Some(project_name.into())
+    }
+
+    /// Record the enrollment of the application and its project
+    pub fn set_enrollment(&mut self, enrollment: EnrollmentSummary) {
+        self.set_enrolled_project(enrollment.project_name.clone());
+        self.enrollment = Some(enrollment)
+    }
+
+    pub fn get_enrollment(&self) -> Option<&EnrollmentSummary> {
+        self.enrollment.as_ref()
+    }
+
+    /// Replace the user information and the enrollment with the ones of `other`, keeping the
+    /// portals, for example when an enrollment is rolled back
+    pub fn restore_enrollment(&mut self, other: ModelState) {
+        self.user_info = other.user_info;
+        self.enrolled_project = other.enrolled_project;
+        self.enrollment = other.enrollment
+    }
+
+    /// Forget the enrollment of the application, for example after a reset
+    pub fn clear_enrollment(&mut self) {
+        self.enrolled_project = None;
+        self.enrollment = None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ockam_api::json_schema::validate;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn the_schema_matches_the_serialized_model_state() {
+        let mut model_state = ModelState::new(
+            Some(UserInfo {
+                sub: "sub".to_string(),
+                nickname: "nickname".to_string(),
+                name: "name".to_string(),
+                picture: "picture".to_string(),
+                updated_at: "2023-01-01".to_string(),
+                email: "a@b.c".to_string(),
+                email_verified: true,
+            }),
+            vec![OutletStatus::new("127.0.0.1:5000", "0#outlet", "db", None)],
+        );
+        model_state.set_enrollment(EnrollmentSummary {
+            project_id: "project-id".to_string(),
+            project_name: "default".to_string(),
+            authority_identifier: Some("P6c20e8142d2e28a3d395ea0585d8b44e".to_string()),
+            credential_expires_at: Some(2_000),
+            updated_at: 1_000,
+        });
+        model_state.set_tcp_outlet_label("db", Some("Database".to_string()));
+        model_state.add_tcp_inlet(TcpInletModel::new(
+            "db-inlet",
+            "127.0.0.1:15000",
+            "/project/default/service/db",
+            Some("db".to_string()),
+        ));
+        model_state.add_relayed_outlet("db");
+        let value = serde_json::to_value(&model_state).unwrap();
+        assert_eq!(validate::<ModelState>(&value), Ok(()));
+    }
+
+    #[test]
+    fn the_schema_rejects_a_malformed_model_state() {
+        assert_eq!(validate::<ModelState>(&json!({})), Ok(()));
+        let invalid = json!({ "tcp_inlets": [{ "alias": "db-inlet", "bind_addr": 15000 }] });
+        assert!(validate::<ModelState>(&invalid).is_err());
+        assert!(serde_json::from_value::<ModelState>(invalid).is_err());
+    }
 }
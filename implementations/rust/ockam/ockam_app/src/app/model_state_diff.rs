@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+
+use ockam_api::nodes::models::portal::OutletStatus;
+
+use crate::app::ModelState;
+use crate::shared_service::tcp::inlet::model_state::TcpInletModel;
+
+/// Portals added, removed and modified between two ModelStates.
+/// Outlets and inlets are identified by their alias.
+/// The state maintained by the running node, like the number of active connections of an
+/// outlet or the pending status of an inlet, is not part of the diff
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ModelStateDiff {
+    pub added_outlets: Vec<OutletStatus>,
+    pub removed_outlets: Vec<OutletStatus>,
+    pub modified_outlets: Vec<Modification<OutletStatus>>,
+    pub added_inlets: Vec<TcpInletModel>,
+    pub removed_inlets: Vec<TcpInletModel>,
+    pub modified_inlets: Vec<Modification<TcpInletModel>>,
+}
+
+/// A portal before and after its modification
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Modification<T> {
+    pub before: T,
+    pub after: T,
+}
+
+impl ModelStateDiff {
+    pub fn is_empty(&self) -> bool {
+        self == &ModelStateDiff::default()
+    }
+
+    /// Return the diff undoing this diff
+    pub fn reverse(&self) -> ModelStateDiff {
+        ModelStateDiff {
+            added_outlets: self.removed_outlets.clone(),
+            removed_outlets: self.added_outlets.clone(),
+            modified_outlets: self.modified_outlets.iter().map(reverse).collect(),
+            added_inlets: self.removed_inlets.clone(),
+            removed_inlets: self.added_inlets.clone(),
+            modified_inlets: self.modified_inlets.iter().map(reverse).collect(),
+        }
+    }
+}
+
+fn reverse<T: Clone>(modification: &Modification<T>) -> Modification<T> {
+    Modification {
+        before: modification.after.clone(),
+        after: modification.before.clone(),
+    }
+}
+
+impl ModelState {
+    /// Return the changes turning the portals of this ModelState into the portals of `other`
+    pub fn diff(&self, other: &ModelState) -> ModelStateDiff {
+        let (added_outlets, removed_outlets, modified_outlets) = diff_portals(
+            &self.tcp_outlets,
+            &other.tcp_outlets,
+            |o| &o.alias,
+            same_outlet,
+        );
+        let (added_inlets, removed_inlets, modified_inlets) = diff_portals(
+            &self.tcp_inlets,
+            &other.tcp_inlets,
+            |i| &i.alias,
+            same_inlet,
+        );
+        ModelStateDiff {
+            added_outlets,
+            removed_outlets,
+            modified_outlets,
+            added_inlets,
+            removed_inlets,
+            modified_inlets,
+        }
+    }
+
+    /// Apply the changes of a diff.
+    /// Modified portals keep their position, added portals are appended.
+    /// Applying a diff twice has the same effect as applying it once
+    pub fn apply_diff(&mut self, diff: &ModelStateDiff) {
+        apply_portals(
+            &mut self.tcp_outlets,
+            &diff.added_outlets,
+            &diff.removed_outlets,
+            &diff.modified_outlets,
+            |o| &o.alias,
+        );
+        apply_portals(
+            &mut self.tcp_inlets,
+            &diff.added_inlets,
+            &diff.removed_inlets,
+            &diff.modified_inlets,
+            |i| &i.alias,
+        );
+    }
+}
+
+fn same_outlet(a: &OutletStatus, b: &OutletStatus) -> bool {
+    a.tcp_addr == b.tcp_addr
+        && a.worker_addr == b.worker_addr
+        && a.payload == b.payload
+        && a.idle_timeout_secs == b.idle_timeout_secs
+        && a.max_connections == b.max_connections
+        && a.tags == b.tags
+}
+
+fn same_inlet(a: &TcpInletModel, b: &TcpInletModel) -> bool {
+    a.bind_addr == b.bind_addr && a.outlet_addr == b.outlet_addr && a.outlet_alias == b.outlet_alias
+}
+
+/// Return the added, removed and modified portals
+type PortalsDiff<T> = (Vec<T>, Vec<T>, Vec<Modification<T>>);
+
+fn diff_portals<T: Clone>(
+    previous: &[T],
+    current: &[T],
+    alias: impl Fn(&T) -> &String,
+    same: impl Fn(&T, &T) -> bool,
+) -> PortalsDiff<T> {
+    let find = |portals: &[T], a: &String| portals.iter().find(|p| alias(p) == a).cloned();
+    let mut added = vec![];
+    let mut modified = vec![];
+    for portal in current {
+        match find(previous, alias(portal)) {
+            None => added.push(portal.clone()),
+            Some(before) if !same(&before, portal) => modified.push(Modification {
+                before,
+                after: portal.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    let removed = previous
+        .iter()
+        .filter(|p| find(current, alias(p)).is_none())
+        .cloned()
+        .collect();
+    (added, removed, modified)
+}
+
+fn apply_portals<T: Clone>(
+    portals: &mut Vec<T>,
+    added: &[T],
+    removed: &[T],
+    modified: &[Modification<T>],
+    alias: impl Fn(&T) -> &String,
+) {
+    portals.retain(|p| !removed.iter().any(|r| alias(r) == alias(p)));
+    let changed = modified.iter().map(|m| &m.after).chain(added.iter());
+    for portal in changed {
+        match portals.iter_mut().find(|p| alias(p) == alias(portal)) {
+            Some(existing) => *existing = portal.clone(),
+            None => portals.push(portal.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outlet(alias: &str, tcp_addr: &str) -> OutletStatus {
+        OutletStatus::new(tcp_addr, alias, alias, None)
+    }
+
+    fn inlet(alias: &str, bind_addr: &str) -> TcpInletModel {
+        TcpInletModel::new(alias, bind_addr, "/service/outlet", None)
+    }
+
+    fn model_state(outlets: Vec<OutletStatus>, inlets: Vec<TcpInletModel>) -> ModelState {
+        let mut model_state = ModelState::new(None, outlets);
+        for inlet in inlets {
+            model_state.add_tcp_inlet(inlet);
+        }
+        model_state
+    }
+
+    /// Check that applying the diff to `before` gives `after`, and that its reverse undoes it
+    fn check_apply(before: &ModelState, after: &ModelState, diff: &ModelStateDiff) {
+        let mut applied = before.clone();
+        applied.apply_diff(diff);
+        assert_eq!(applied.get_tcp_outlets(), after.get_tcp_outlets());
+        assert_eq!(applied.get_tcp_inlets(), after.get_tcp_inlets());
+        assert!(applied.diff(after).is_empty());
+
+        applied.apply_diff(&diff.reverse());
+        assert!(applied.diff(before).is_empty());
+    }
+
+    #[test]
+    fn added_portals() {
+        let before = model_state(vec![outlet("db", "127.0.0.1:5432")], vec![]);
+        let after = model_state(
+            vec![
+                outlet("db", "127.0.0.1:5432"),
+                outlet("web", "127.0.0.1:80"),
+            ],
+            vec![inlet("db-inlet", "127.0.0.1:15432")],
+        );
+        let diff = before.diff(&after);
+        assert_eq!(
+            diff,
+            ModelStateDiff {
+                added_outlets: vec![outlet("web", "127.0.0.1:80")],
+                added_inlets: vec![inlet("db-inlet", "127.0.0.1:15432")],
+                ..Default::default()
+            }
+        );
+        check_apply(&before, &after, &diff);
+    }
+
+    #[test]
+    fn removed_portals() {
+        let before = model_state(
+            vec![
+                outlet("db", "127.0.0.1:5432"),
+                outlet("web", "127.0.0.1:80"),
+            ],
+            vec![inlet("db-inlet", "127.0.0.1:15432")],
+        );
+        let after = model_state(vec![outlet("web", "127.0.0.1:80")], vec![]);
+        let diff = before.diff(&after);
+        assert_eq!(
+            diff,
+            ModelStateDiff {
+                removed_outlets: vec![outlet("db", "127.0.0.1:5432")],
+                removed_inlets: vec![inlet("db-inlet", "127.0.0.1:15432")],
+                ..Default::default()
+            }
+        );
+        check_apply(&before, &after, &diff);
+    }
+
+    #[test]
+    fn modified_portals() {
+        let before = model_state(
+            vec![
+                outlet("db", "127.0.0.1:5432"),
+                outlet("web", "127.0.0.1:80"),
+            ],
+            vec![inlet("db-inlet", "127.0.0.1:15432")],
+        );
+        let after = model_state(
+            vec![
+                outlet("db", "127.0.0.1:5433"),
+                outlet("web", "127.0.0.1:80"),
+            ],
+            vec![inlet("db-inlet", "127.0.0.1:15433")],
+        );
+        let diff = before.diff(&after);
+        assert_eq!(
+            diff,
+            ModelStateDiff {
+                modified_outlets: vec![Modification {
+                    before: outlet("db", "127.0.0.1:5432"),
+                    after: outlet("db", "127.0.0.1:5433"),
+                }],
+                modified_inlets: vec![Modification {
+                    before: inlet("db-inlet", "127.0.0.1:15432"),
+                    after: inlet("db-inlet", "127.0.0.1:15433"),
+                }],
+                ..Default::default()
+            }
+        );
+        check_apply(&before, &after, &diff);
+    }
+
+    #[test]
+    fn the_runtime_state_of_portals_is_not_a_modification() {
+        let before = model_state(
+            vec![outlet("db", "127.0.0.1:5432")],
+            vec![inlet("db-inlet", "127.0.0.1:15432")],
+        );
+        let mut after = model_state(
+            vec![outlet("db", "127.0.0.1:5432").with_active_connections(3)],
+            vec![inlet("db-inlet", "127.0.0.1:15432")],
+        );
+        after.tcp_inlets[0].pending = true;
+        assert!(before.diff(&after).is_empty());
+    }
+
+    #[test]
+    fn a_diff_can_be_serialized() {
+        let before = model_state(vec![outlet("db", "127.0.0.1:5432")], vec![]);
+        let after = model_state(vec![], vec![inlet("db-inlet", "127.0.0.1:15432")]);
+        let diff = before.diff(&after);
+        let json = serde_json::to_string(&diff).unwrap();
+        assert_eq!(serde_json::from_str::<ModelStateDiff>(&json).unwrap(), diff);
+    }
+}
@@ -1,17 +1,39 @@
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use miette::miette;
+use sha2::{Digest, Sha256};
 
 use ockam::identity::Storage;
 use ockam::LmdbStorage;
+use ockam_api::cli_state::CliState;
 use ockam_core::async_trait;
+use ockam_core::env::get_env;
 
 use crate::app::model_state::ModelState;
+use crate::app::DEFAULT_NODE_NAME;
+use crate::error::Error;
 use crate::Result;
 
 const MODEL_STATE_ID: &str = "model_state";
 const MODEL_STATE_KEY: &str = "model_state_key";
 
+/// Prefix of the stored model states which are followed by their checksum.
+///
+/// The model states stored before the checksums were introduced are plain JSON objects, starting
+/// with `{`, so they can't be mistaken for a checksummed state and are still decoded, see
+/// `decode_model_state`. The versions of the application which predate the checksums can't
+/// decode a checksummed state though: they fail to start until the state is reset
+const CHECKSUM_MAGIC: &[u8] = b"OCKAM_MS1";
+/// Length of the SHA-256 checksum of a serialized model state
+const CHECKSUM_LENGTH: usize = 32;
+
+/// Initial map size of the LMDB environment storing the model state.
+/// The environment is shared with the command line so its map size is set large enough
+/// to rarely be exhausted by either process. When it is, the map is grown.
+const MODEL_STATE_MAP_SIZE: usize = 256 * 1024 * 1024;
+
 /// The ModelStateRepository is responsible for storing and loading
 /// ModelState data (user information, shared services etc...)
 /// The state must be stored everytime it is modified (see set_user_info in AppState for example)
@@ -19,34 +41,144 @@ const MODEL_STATE_KEY: &str = "model_state_key";
 #[async_trait]
 pub trait ModelStateRepository: Send + Sync + 'static {
     async fn store(&self, model_state: &ModelState) -> Result<()>;
+    /// Load the stored model state.
+    /// An `Error::CorruptedModelState` is returned if the stored bytes can't be decoded. They are
+    /// then backed up first, so that they are not lost when a new model state is stored
     async fn load(&self) -> Result<Option<ModelState>>;
 }
 
+/// Serialize a model state as JSON, prefixed with a SHA-256 checksum of the JSON bytes
+/// so that a corruption is detected when it is loaded, see `decode_model_state`
+pub(crate) fn encode_model_state(model_state: &ModelState) -> Result<Vec<u8>> {
+    let json = serde_json::to_vec(model_state)?;
+    let mut bytes = Vec::with_capacity(CHECKSUM_MAGIC.len() + CHECKSUM_LENGTH + json.len());
+    bytes.extend_from_slice(CHECKSUM_MAGIC);
+    bytes.extend_from_slice(&Sha256::digest(&json));
+    bytes.extend_from_slice(&json);
+    Ok(bytes)
+}
+
+/// Deserialize a model state stored with `encode_model_state`, after checking its checksum.
+/// The model states stored before the checksums were introduced are plain JSON
+pub(crate) fn decode_model_state(bytes: &[u8]) -> Result<ModelState> {
+    let json = match bytes.strip_prefix(CHECKSUM_MAGIC) {
+        Some(rest) if rest.len() >= CHECKSUM_LENGTH => {
+            let (checksum, json) = rest.split_at(CHECKSUM_LENGTH);
+            if Sha256::digest(json).as_slice() != checksum {
+                return Err(Error::CorruptedModelState(
+                    "the checksum doesn't match".to_string(),
+                ));
+            }
+            json
+        }
+        Some(_) => {
+            return Err(Error::CorruptedModelState(
+                "the checksum is truncated".to_string(),
+            ))
+        }
+        None => bytes,
+    };
+    serde_json::from_slice(json).map_err(|e| Error::CorruptedModelState(e.to_string()))
+}
+
 /// This implementation of the ModelStateRepository piggy-backs for now on the LMDB storage
 /// which is used to store all the data related to identities.
 /// We will possibly store all data eventually using SQLite and in that case the ModelData
 /// can be a set of tables dedicated to the desktop application
+/// The ModelState of each node is stored under its own key so that applications using different
+/// node names don't overwrite each other's state.
 pub struct LmdbModelStateRepository {
     storage: LmdbStorage,
+    key: String,
 }
 
 impl LmdbModelStateRepository {
-    pub async fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+    pub async fn new<P: AsRef<Path>>(path: P, node_name: &str) -> Result<Self> {
+        Self::with_map_size(path, node_name, MODEL_STATE_MAP_SIZE).await
+    }
+
+    async fn with_map_size<P: AsRef<Path>>(
+        path: P,
+        node_name: &str,
+        map_size: usize,
+    ) -> Result<Self> {
         Ok(Self {
-            storage: LmdbStorage::new(path).await.map_err(|e| miette!(e))?,
+            storage: LmdbStorage::with_map_size(path, map_size)
+                .await
+                .map_err(|e| miette!(e))?,
+            key: model_state_key(node_name),
         })
     }
 }
 
-/// The implementation simply serializes / deserializes the ModelState as JSON
+/// Environment variable used to override the path of the database storing the ModelState
+const OCKAM_MODEL_STATE_PATH: &str = "OCKAM_MODEL_STATE_PATH";
+
+/// Return the path of the database storing the ModelState.
+///
+/// The path is, by order of precedence:
+///  1. the value of the `OCKAM_MODEL_STATE_PATH` environment variable, if it is set and not empty
+///  2. the path of the identities repository of the CLI state
+///
+/// An error is returned if the parent directory of the path cannot be written to
+pub(crate) fn model_state_repository_path(state: &CliState) -> Result<PathBuf> {
+    let path_override = get_env::<PathBuf>(OCKAM_MODEL_STATE_PATH)
+        .map_err(|e| Error::Generic(format!("invalid {OCKAM_MODEL_STATE_PATH}: {e}")))?;
+    let default_path = state.identities.identities_repository_path()?;
+    resolve_model_state_path(path_override, default_path)
+}
+
+fn resolve_model_state_path(
+    path_override: Option<PathBuf>,
+    default_path: PathBuf,
+) -> Result<PathBuf> {
+    let path = match path_override {
+        Some(path) if !path.as_os_str().is_empty() => path,
+        _ => default_path,
+    };
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        check_writable_directory(parent).map_err(|e| {
+            Error::Generic(format!(
+                "cannot store the model state at {}: the directory {} is not writable ({e})",
+                path.display(),
+                parent.display()
+            ))
+        })?;
+    }
+    Ok(path)
+}
+
+/// Create the directory if it doesn't exist yet and check that files can be created in it
+fn check_writable_directory(dir: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let probe = dir.join(format!(".model_state_write_check_{}", std::process::id()));
+    fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&probe)?;
+    fs::remove_file(probe)
+}
+
+/// Return the key used to store the ModelState of a given node.
+/// The default node keeps using the original key so that its existing state is still loaded
+fn model_state_key(node_name: &str) -> String {
+    if node_name == DEFAULT_NODE_NAME {
+        MODEL_STATE_KEY.to_string()
+    } else {
+        format!("{MODEL_STATE_KEY}_{node_name}")
+    }
+}
+
+/// The implementation serializes / deserializes the ModelState as JSON with a checksum
 #[async_trait]
 impl ModelStateRepository for LmdbModelStateRepository {
     async fn store(&self, model_state: &ModelState) -> Result<()> {
         self.storage
             .set(
                 MODEL_STATE_ID,
-                MODEL_STATE_KEY.to_string(),
-                serde_json::to_vec(model_state)?,
+                self.key.clone(),
+                encode_model_state(model_state)?,
             )
             .await
             .map_err(|e| miette!(e))?;
@@ -54,12 +186,181 @@ impl ModelStateRepository for LmdbModelStateRepository {
     }
 
     async fn load(&self) -> Result<Option<ModelState>> {
-        match self.storage.get(MODEL_STATE_ID, MODEL_STATE_KEY).await {
+        match self.storage.get(MODEL_STATE_ID, &self.key).await {
             Err(e) => Err(miette!(e).into()),
             Ok(None) => Ok(None),
-            Ok(Some(bytes)) => {
-                Ok(serde_json::from_slice(bytes.as_slice()).map_err(|e| miette!(e))?)
-            }
+            Ok(Some(bytes)) => match decode_model_state(&bytes) {
+                Err(Error::CorruptedModelState(e)) => {
+                    let backup_key = self.back_up(bytes).await?;
+                    Err(Error::CorruptedModelState(format!(
+                        "{e}, it has been backed up under the key {backup_key}"
+                    )))
+                }
+                result => result.map(Some),
+            },
+        }
+    }
+}
+
+impl LmdbModelStateRepository {
+    /// Store the bytes of a corrupted model state under a new key and return this key
+    async fn back_up(&self, bytes: Vec<u8>) -> Result<String> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let backup_key = format!("{}_corrupted_{now}", self.key);
+        self.storage
+            .set(MODEL_STATE_ID, backup_key.clone(), bytes)
+            .await
+            .map_err(|e| miette!(e))?;
+        Ok(backup_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ockam_api::nodes::models::portal::OutletStatus;
+
+    use super::*;
+
+    /// Each writer stores states with the outlets `outlet-<writer>-0` to `outlet-<writer>-<n>`
+    fn model_state(writer: usize, n: usize) -> ModelState {
+        let outlets = (0..=n)
+            .map(|i| {
+                OutletStatus::new(
+                    "127.0.0.1:6000",
+                    format!("outlet-{writer}-{i}"),
+                    format!("outlet-{writer}-{i}"),
+                    None,
+                )
+            })
+            .collect();
+        ModelState::new(None, outlets)
+    }
+
+    #[test]
+    fn model_state_path_override() {
+        let dir = tempfile::tempdir().unwrap();
+        let default_path = dir.path().join("identities-storage.lmdb");
+
+        // without an override, or with an empty one, the default path is used
+        let path = resolve_model_state_path(None, default_path.clone()).unwrap();
+        assert_eq!(path, default_path);
+        let path = resolve_model_state_path(Some(PathBuf::new()), default_path.clone()).unwrap();
+        assert_eq!(path, default_path);
+
+        // the override takes precedence and its parent directory is created
+        let override_path = dir.path().join("app").join("model-state.lmdb");
+        let path = resolve_model_state_path(Some(override_path.clone()), default_path.clone());
+        assert_eq!(path.unwrap(), override_path);
+        assert!(dir.path().join("app").is_dir());
+
+        // a parent which is not a directory is rejected
+        let file = dir.path().join("file");
+        fs::write(&file, b"").unwrap();
+        let result = resolve_model_state_path(Some(file.join("model-state.lmdb")), default_path);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn a_corrupted_model_state_is_detected() {
+        let model_state = model_state(0, 2);
+        let bytes = encode_model_state(&model_state).unwrap();
+        let decoded = decode_model_state(&bytes).unwrap();
+        assert_eq!(decoded.get_tcp_outlets(), model_state.get_tcp_outlets());
+
+        // flipping any byte is detected, including in the checksum itself
+        for i in [0, CHECKSUM_MAGIC.len(), bytes.len() - 2] {
+            let mut corrupted = bytes.clone();
+            corrupted[i] ^= 0x01;
+            let result = decode_model_state(&corrupted);
+            assert!(
+                matches!(result, Err(Error::CorruptedModelState(_))),
+                "byte {i}"
+            );
         }
+        assert!(matches!(
+            decode_model_state(&bytes[..CHECKSUM_MAGIC.len() + 4]),
+            Err(Error::CorruptedModelState(_))
+        ));
+
+        // the model states stored without checksum can still be loaded
+        let legacy = serde_json::to_vec(&model_state).unwrap();
+        let decoded = decode_model_state(&legacy).unwrap();
+        assert_eq!(decoded.get_tcp_outlets(), model_state.get_tcp_outlets());
+    }
+
+    #[test]
+    fn a_corrupted_model_state_is_not_loaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("identities-storage.lmdb");
+        crate::app::test_block_on(async move {
+            let repository = LmdbModelStateRepository::new(&path, DEFAULT_NODE_NAME)
+                .await
+                .unwrap();
+            repository.store(&model_state(0, 1)).await.unwrap();
+            let mut bytes = repository
+                .storage
+                .get(MODEL_STATE_ID, MODEL_STATE_KEY)
+                .await
+                .unwrap()
+                .unwrap();
+            let last = bytes.len() - 1;
+            bytes[last] ^= 0x01;
+            repository
+                .storage
+                .set(MODEL_STATE_ID, MODEL_STATE_KEY.to_string(), bytes.clone())
+                .await
+                .unwrap();
+            let backup_key = match repository.load().await {
+                Err(Error::CorruptedModelState(e)) => e.rsplit_once(' ').unwrap().1.to_string(),
+                _ => panic!("the model state is corrupted"),
+            };
+            // the corrupted bytes are kept when a new model state is stored
+            repository.store(&model_state(0, 2)).await.unwrap();
+            let backup = repository.storage.get(MODEL_STATE_ID, &backup_key).await;
+            assert_eq!(backup.unwrap(), Some(bytes));
+        });
+    }
+
+    #[test]
+    fn concurrent_stores_and_loads() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("identities-storage.lmdb");
+        // the largest states don't fit in the initial map which must be grown
+        // while the other writers and readers are running
+        let initial_map_size = 64 * 1024;
+        crate::app::test_block_on(async move {
+            let repository = Arc::new(
+                LmdbModelStateRepository::with_map_size(&path, DEFAULT_NODE_NAME, initial_map_size)
+                    .await
+                    .unwrap(),
+            );
+            let mut handles = vec![];
+            for writer in 0..8 {
+                let repository = repository.clone();
+                handles.push(tauri::async_runtime::spawn(async move {
+                    for n in 0..25 {
+                        let state = model_state(writer, 20 * n);
+                        repository.store(&state).await.unwrap();
+                        let loaded = repository.load().await.unwrap().unwrap();
+                        // the loaded state must be one of the states stored by a single writer
+                        let outlets = loaded.get_tcp_outlets();
+                        let prefix = outlets[0].alias.rsplit_once('-').unwrap().0.to_string();
+                        for (i, outlet) in outlets.iter().enumerate() {
+                            assert_eq!(outlet.alias, format!("{prefix}-{i}"));
+                        }
+                    }
+                }));
+            }
+            for handle in handles {
+                handle.await.unwrap();
+            }
+            let map_size = repository.storage.env.info().unwrap().map_size();
+            assert!(map_size > initial_map_size);
+        });
     }
 }
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use miette::miette;
+
+use ockam::identity::IdentitiesVault;
+use ockam::vault::storage::PersistentStorage;
+use ockam::vault::{Vault, VaultStorage};
+use ockam_api::cli_state::{StateItemTrait, VaultState};
+use ockam_core::async_trait;
+
+use crate::Result;
+
+/// A SecretStore persists the secrets of the vaults used by the application.
+///
+/// The default implementation, `FileSecretStore`, stores the secrets in the vault files of the cli
+/// state, like the command line does. Other implementations can keep the secrets in an
+/// OS-native store, like the macOS Keychain or the Windows Credential Manager.
+/// Such an implementation must give access to the secrets of the identities already created in the vault.
+#[async_trait]
+pub trait SecretStore: Send + Sync + 'static {
+    /// Return the storage used to persist the secrets of a vault.
+    /// If the storage can't be opened while it is locked, for example a locked keychain, the
+    /// error must be `Error::vault_locked` so that the user is asked to unlock it
+    async fn vault_storage(&self, vault_state: &VaultState) -> Result<VaultStorage>;
+
+    /// Return the vault for a given vault state.
+    /// Vaults backed by AWS KMS keep their secrets in KMS and are not affected by the secret store
+    async fn vault(&self, vault_state: &VaultState) -> Result<Arc<dyn IdentitiesVault>> {
+        if vault_state.config().is_aws() {
+            return Ok(vault_state.get().await?);
+        }
+        let storage = self.vault_storage(vault_state).await?;
+        Ok(Vault::create_with_persistent_storage(storage))
+    }
+}
+
+/// This SecretStore stores the secrets of each vault in its file in the cli state directory
+#[derive(Debug, Clone, Default)]
+pub struct FileSecretStore;
+
+#[async_trait]
+impl SecretStore for FileSecretStore {
+    async fn vault_storage(&self, vault_state: &VaultState) -> Result<VaultStorage> {
+        Ok(PersistentStorage::create(vault_state.vault_file_path())
+            .await
+            .map_err(|e| miette!(e))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use ockam::vault::{PersistentSecretsStore, SecretAttributes, SecretsStoreReader};
+    use ockam_api::cli_state::VaultConfig;
+
+    use super::*;
+
+    /// Secret store counting the vault storages it returns
+    #[derive(Default)]
+    struct CountingSecretStore {
+        count: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl SecretStore for CountingSecretStore {
+        async fn vault_storage(&self, vault_state: &VaultState) -> Result<VaultStorage> {
+            self.count.fetch_add(1, Ordering::SeqCst);
+            FileSecretStore.vault_storage(vault_state).await
+        }
+    }
+
+    fn vault_state(dir: &Path) -> VaultState {
+        let vault_state = VaultState::new(
+            dir.join("vaults").join("vault.json"),
+            VaultConfig::default(),
+        )
+        .unwrap();
+        std::fs::create_dir_all(vault_state.vault_file_path().parent().unwrap()).unwrap();
+        vault_state
+    }
+
+    #[test]
+    fn the_file_secret_store_persists_the_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_state = vault_state(dir.path());
+        crate::app::test_block_on(async move {
+            let vault = FileSecretStore.vault(&vault_state).await.unwrap();
+            let key_id = vault
+                .create_persistent_secret(SecretAttributes::Ed25519)
+                .await
+                .unwrap();
+            assert!(vault_state.vault_file_path().exists());
+
+            // the secret is found by another vault using the same files
+            let vault = FileSecretStore.vault(&vault_state).await.unwrap();
+            let attributes = vault.get_secret_attributes(&key_id).await.unwrap();
+            assert_eq!(attributes, SecretAttributes::Ed25519);
+        });
+    }
+
+    #[test]
+    fn the_vaults_use_the_storage_of_the_secret_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_state = vault_state(dir.path());
+        let secret_store = CountingSecretStore::default();
+        crate::app::test_block_on(async {
+            secret_store.vault(&vault_state).await.unwrap();
+            secret_store.vault(&vault_state).await.unwrap();
+        });
+        assert_eq!(secret_store.count.load(Ordering::SeqCst), 2);
+    }
+}
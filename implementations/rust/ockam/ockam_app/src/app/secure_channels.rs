@@ -0,0 +1,31 @@
+use tauri::{AppHandle, Manager, Wry};
+use tracing::error;
+
+use ockam::Address;
+use ockam_api::nodes::models::secure_channel::SecureChannelStatus;
+
+use crate::app::AppState;
+
+/// List the secure channels of the node, with the identity of their peer.
+#[tauri::command]
+pub async fn secure_channel_list(app: AppHandle<Wry>) -> Result<Vec<SecureChannelStatus>, String> {
+    let app_state = app.state::<AppState>();
+    app_state.secure_channels().await.map_err(|e| {
+        error!("{:?}", e);
+        e.to_string()
+    })
+}
+
+/// Close a secure channel given its address.
+/// Return false if there is no such secure channel.
+#[tauri::command]
+pub async fn secure_channel_close(app: AppHandle<Wry>, address: String) -> Result<bool, String> {
+    let app_state = app.state::<AppState>();
+    app_state
+        .close_secure_channel(&Address::from_string(address))
+        .await
+        .map_err(|e| {
+            error!("{:?}", e);
+            e.to_string()
+        })
+}
@@ -0,0 +1,114 @@
+use std::path::PathBuf;
+
+use ockam_core::env::{get_env, get_env_with_default};
+
+use crate::app::{ListenerAddress, ListenerIp, DEFAULT_NODE_NAME};
+use crate::error::Error;
+use crate::Result;
+
+/// Environment variable selecting the loopback address the node of the application listens on,
+/// `ipv4` or `ipv6`
+const OCKAM_APP_LISTENER_IP: &str = "OCKAM_APP_LISTENER_IP";
+
+/// Environment variable setting the port the node of the application listens on.
+/// A port chosen by the operating system is used if it is not set
+const OCKAM_APP_LISTENER_PORT: &str = "OCKAM_APP_LISTENER_PORT";
+
+/// Environment variable allowing the node to listen on a port chosen by the operating system
+/// when the port set with `OCKAM_APP_LISTENER_PORT` is already in use, `true` by default
+const OCKAM_APP_LISTENER_PORT_FALLBACK: &str = "OCKAM_APP_LISTENER_PORT_FALLBACK";
+
+/// Environment variable enabling the local-only mode, where the application is never enrolled
+/// and only runs local portals, see `AppState::is_local_only`
+const OCKAM_APP_LOCAL_ONLY: &str = "OCKAM_APP_LOCAL_ONLY";
+
+/// Settings used to create the state of the application, see `AppState::try_with_settings`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppSettings {
+    /// Name of the node managed by the application
+    pub node_name: String,
+    /// Number of worker threads of the node runtime, one per CPU core if not set
+    pub worker_threads: Option<usize>,
+    /// Directory of the cli state. The `OCKAM_HOME` directory is used if not set
+    pub state_dir: Option<PathBuf>,
+    /// Run the application without any project, see `AppState::is_local_only`
+    pub local_only: bool,
+    /// Address the TCP listener of the node is bound to, when the node is created and reset
+    pub listener_address: ListenerAddress,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self::new(DEFAULT_NODE_NAME)
+    }
+}
+
+impl AppSettings {
+    pub fn new(node_name: impl Into<String>) -> Self {
+        Self {
+            node_name: node_name.into(),
+            worker_threads: None,
+            state_dir: None,
+            local_only: false,
+            listener_address: ListenerAddress::default(),
+        }
+    }
+
+    /// Return the settings of the node `node_name`, where the listener address is set with the
+    /// `OCKAM_APP_LISTENER_IP`, `OCKAM_APP_LISTENER_PORT` and `OCKAM_APP_LISTENER_PORT_FALLBACK`
+    /// environment variables and the local-only mode with `OCKAM_APP_LOCAL_ONLY`.
+    /// This is the only place where the application reads its settings from the environment
+    pub fn from_env(node_name: impl Into<String>) -> Result<Self> {
+        let local_only = get_env_with_default(OCKAM_APP_LOCAL_ONLY, false)
+            .map_err(|e| invalid(OCKAM_APP_LOCAL_ONLY, e))?;
+        Ok(Self::new(node_name)
+            .with_local_only(local_only)
+            .with_listener_address(listener_address_from_env()?))
+    }
+
+    pub fn with_worker_threads(mut self, worker_threads: Option<usize>) -> Self {
+        self.worker_threads = worker_threads;
+        self
+    }
+
+    pub fn with_state_dir(mut self, state_dir: impl Into<PathBuf>) -> Self {
+        self.state_dir = Some(state_dir.into());
+        self
+    }
+
+    pub fn with_local_only(mut self, local_only: bool) -> Self {
+        self.local_only = local_only;
+        self
+    }
+
+    pub fn with_listener_address(mut self, listener_address: ListenerAddress) -> Self {
+        self.listener_address = listener_address;
+        self
+    }
+}
+
+/// Return the listener address set with the environment variables, see `AppSettings::from_env`.
+/// The default values are used for the variables which are not set or are empty
+fn listener_address_from_env() -> Result<ListenerAddress> {
+    let ip = match get_env::<String>(OCKAM_APP_LISTENER_IP)
+        .map_err(|e| invalid(OCKAM_APP_LISTENER_IP, e))?
+    {
+        Some(ip) if !ip.is_empty() => ip
+            .parse::<ListenerIp>()
+            .map_err(|e| invalid(OCKAM_APP_LISTENER_IP, e))?,
+        _ => ListenerIp::default(),
+    };
+    let port =
+        get_env::<u16>(OCKAM_APP_LISTENER_PORT).map_err(|e| invalid(OCKAM_APP_LISTENER_PORT, e))?;
+    let ephemeral_port_fallback = get_env_with_default(OCKAM_APP_LISTENER_PORT_FALLBACK, true)
+        .map_err(|e| invalid(OCKAM_APP_LISTENER_PORT_FALLBACK, e))?;
+    Ok(ListenerAddress {
+        ip,
+        port,
+        ephemeral_port_fallback,
+    })
+}
+
+fn invalid(variable: &str, error: impl std::fmt::Display) -> Error {
+    Error::Generic(format!("invalid {variable}: {error}"))
+}
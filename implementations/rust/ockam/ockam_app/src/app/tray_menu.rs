@@ -1,7 +1,7 @@
 use tauri::{AppHandle, SystemTrayEvent, SystemTrayMenu, SystemTrayMenuItem, Wry};
 use tracing::error;
 
-use crate::app::AppState;
+use crate::app::{on_vault_retry, AppState, VAULT_RETRY_MENU_ID};
 use crate::enroll::build_enroll_section;
 use crate::options::build_options_section;
 use crate::shared_service::build_shared_services_section;
@@ -22,8 +22,10 @@ pub fn process_system_tray_event(app: &AppHandle<Wry>, event: SystemTrayEvent) {
         let result = match id.as_str() {
             enroll::ENROLL_MENU_ID => enroll::on_enroll(app),
             shared_service::SHARED_SERVICE_CREATE_MENU_ID => shared_service::on_create(app),
+            options::PAUSE_MENU_ID => options::on_pause(app),
             options::RESET_MENU_ID => options::on_reset(app),
-            options::QUIT_MENU_ID => options::on_quit(),
+            options::QUIT_MENU_ID => options::on_quit(app),
+            VAULT_RETRY_MENU_ID => on_vault_retry(app),
             _ => Ok(()),
         };
         if let Err(e) = result {
@@ -0,0 +1,74 @@
+use std::sync::Mutex;
+
+use tauri::{AppHandle, CustomMenuItem, Manager, SystemTrayMenu, Wry};
+use tracing::{error, info, warn};
+
+use crate::app::{build_tray_menu, start_app, AppState, AppStateRetry};
+use crate::error::Error;
+use crate::options::QUIT_MENU_ID;
+
+pub const VAULT_LOCKED_MENU_ID: &str = "vault-locked";
+pub const VAULT_RETRY_MENU_ID: &str = "vault-retry";
+
+/// State of the application while the vault of its node is locked.
+/// It keeps the creation of the application state to retry once the user unlocks the vault
+pub struct VaultLockedState {
+    retry: Mutex<Option<AppStateRetry>>,
+}
+
+impl VaultLockedState {
+    pub fn new(retry: Option<AppStateRetry>) -> Self {
+        Self {
+            retry: Mutex::new(retry),
+        }
+    }
+}
+
+/// Build the tray menu displayed while the vault of the node is locked
+pub fn build_vault_locked_menu() -> SystemTrayMenu {
+    SystemTrayMenu::new()
+        .add_item(
+            CustomMenuItem::new(
+                VAULT_LOCKED_MENU_ID,
+                "The vault is locked, unlock it then retry",
+            )
+            .disabled(),
+        )
+        .add_item(CustomMenuItem::new(VAULT_RETRY_MENU_ID, "Retry"))
+        .add_item(CustomMenuItem::new(QUIT_MENU_ID, "Quit").accelerator("cmd+q"))
+}
+
+/// Event listener for the "Retry" menu item
+/// Create the application state again and start the application if the vault is now unlocked.
+/// The other errors are fatal, like at startup
+pub fn on_vault_retry(app: &AppHandle<Wry>) -> tauri::Result<()> {
+    let vault_locked = app.state::<VaultLockedState>();
+    let retry = vault_locked
+        .retry
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take();
+    let result = match retry {
+        Some(retry) => retry.retry(),
+        None => AppState::try_new(),
+    };
+    match result {
+        Ok(app_state) => {
+            info!("the vault has been unlocked, the application is started");
+            app.manage(app_state);
+            let tray_menu =
+                tauri::async_runtime::block_on(build_tray_menu(&app.state::<AppState>()));
+            app.tray_handle().set_menu(tray_menu)?;
+            start_app(app);
+        }
+        Err(Error::VaultLocked { reason, retry }) => {
+            warn!(%reason, "the vault of the node is still locked");
+            *vault_locked.retry.lock().unwrap_or_else(|e| e.into_inner()) = retry.map(|r| *r);
+        }
+        Err(e) => {
+            error!(%e, "cannot create the application state");
+            panic!("{e}")
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use tauri::{AppHandle, Manager, Wry};
+use tracing::{error, info};
+
+use ockam::identity::IdentityIdentifier;
+use ockam_command::enroll::{record_ticket_enrollment, validate_enrollment_ticket};
+use ockam_command::project::parse_enroll_ticket;
+use ockam_core::errcode::{Kind, Origin};
+
+use crate::app::AppState;
+use crate::enroll::EnrollmentFlow;
+use crate::error::Error;
+
+/// Enroll the application with an enrollment ticket, given as a hex-encoded string.
+#[tauri::command]
+pub async fn enroll_with_ticket(app: AppHandle<Wry>, ticket: String) -> Result<(), String> {
+    let app_state = app.state::<AppState>();
+    app_state
+        .enroll(&app, EnrollmentFlow::Ticket(ticket))
+        .await
+        .map(|i| info!("Enrolled with a ticket, the identifier is {}", i))
+        .map_err(|e| {
+            error!("{:?}", e);
+            e.to_string()
+        })?;
+    app.trigger_global(crate::app::events::SYSTEM_TRAY_ON_UPDATE, None);
+    Ok(())
+}
+
+/// Cancel the enrollment currently in progress, if any.
+#[tauri::command]
+pub async fn enroll_cancel(app: AppHandle<Wry>) -> bool {
+    app.state::<AppState>().cancel_enrollment().await
+}
+
+/// Enroll with an enrollment ticket.
+///
+/// This function runs the enrollment flow of `ockam enroll --ticket`, see
+/// [`ockam_command::enroll::enroll_with_ticket`], then records the enrollment of the application.
+///
+/// `Error::InvalidEnrollmentTicket` is only returned for a ticket which can't be decoded, has
+/// expired or is refused by the project authority. `Error::AuthorityUnreachable` is returned if
+/// the ticket couldn't be presented to the authority, and the other failures keep their own error
+pub(crate) async fn enroll_with_ticket_impl(
+    app_state: &AppState,
+    ticket: &str,
+) -> crate::Result<IdentityIdentifier> {
+    let ticket =
+        parse_enroll_ticket(ticket).map_err(|e| Error::InvalidEnrollmentTicket(e.to_string()))?;
+    // an invalid or expired ticket is reported before any state is written
+    let (project, trust_context) = validate_enrollment_ticket(&ticket)
+        .map_err(|e| Error::InvalidEnrollmentTicket(e.to_string()))?;
+
+    app_state
+        .node_manager
+        .present_enrollment_ticket(&app_state.context(), &ticket)
+        .await
+        .map_err(ticket_presentation_error)?;
+    info!("the enrollment ticket was accepted by the project authority");
+
+    let identifier = record_ticket_enrollment(
+        &app_state.options().await,
+        &app_state.node_manager,
+        &app_state.node_name(),
+        &project,
+        &trust_context,
+    )
+    .await?;
+    app_state.record_enrollment(&project).await?;
+    Ok(identifier)
+}
+
+/// Tell a ticket refused by the project authority, which answered with an error response,
+/// from a ticket which couldn't be presented to it
+fn ticket_presentation_error(e: ockam_core::Error) -> Error {
+    let code = e.code();
+    match (code.origin, code.kind) {
+        (Origin::Application, Kind::Protocol) => Error::InvalidEnrollmentTicket(e.to_string()),
+        (_, Kind::Io | Kind::Timeout | Kind::Cancelled | Kind::NotFound) => {
+            Error::AuthorityUnreachable(e.to_string())
+        }
+        _ => Error::Generic(format!("cannot present the enrollment ticket: {e}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use crate::app::test_block_on as block_on;
+
+    use crate::app::{AppSettings, FileSecretStore};
+
+    use super::*;
+
+    #[test]
+    fn an_authority_failure_is_not_an_invalid_ticket() {
+        let refused = ockam_core::Error::new(Origin::Application, Kind::Protocol, "unknown code");
+        assert!(matches!(
+            ticket_presentation_error(refused),
+            Error::InvalidEnrollmentTicket(_)
+        ));
+
+        let unreachable = ockam_core::Error::new(Origin::Transport, Kind::Io, "connection refused");
+        assert!(matches!(
+            ticket_presentation_error(unreachable),
+            Error::AuthorityUnreachable(_)
+        ));
+
+        let timeout = ockam_core::Error::new(Origin::Node, Kind::Timeout, "no response");
+        assert!(matches!(
+            ticket_presentation_error(timeout),
+            Error::AuthorityUnreachable(_)
+        ));
+
+        let other = ockam_core::Error::new(Origin::Vault, Kind::Internal, "cannot sign");
+        assert!(matches!(
+            ticket_presentation_error(other),
+            Error::Generic(_)
+        ));
+    }
+
+    #[test]
+    fn an_invalid_or_expired_ticket_is_rejected_before_enrolling() {
+        let ockam_home = tempfile::tempdir().unwrap();
+        let app_state = AppState::try_with_settings(
+            AppSettings::new("invalid-ticket")
+                .with_worker_threads(Some(1))
+                .with_state_dir(ockam_home.path()),
+            Arc::new(FileSecretStore),
+        )
+        .unwrap();
+        let expired = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../ockam_command/tests/fixtures/expired.enrollment.ticket");
+
+        block_on(async {
+            for ticket in ["not a ticket", expired.to_str().unwrap()] {
+                let error = enroll_with_ticket_impl(&app_state, ticket)
+                    .await
+                    .unwrap_err();
+                assert!(
+                    matches!(error, Error::InvalidEnrollmentTicket(_)),
+                    "{error:?}"
+                );
+            }
+            assert!(!app_state.is_enrolled().await);
+        });
+    }
+}
@@ -12,7 +12,8 @@ use ockam_api::cloud::space::{CreateSpace, Space};
 use ockam_command::enroll::{update_enrolled_identity, OidcService};
 use ockam_command::util::api::CloudOpts;
 
-use crate::app::{AppState, NODE_NAME, PROJECT_NAME};
+use crate::app::{AppState, PROJECT_NAME};
+use crate::enroll::EnrollmentFlow;
 use crate::shared_service::relay::create::create_relay;
 use crate::Result;
 
@@ -24,7 +25,8 @@ use crate::Result;
 ///  - connects to the Orchestrator with the retrieved token to create a project
 pub async fn enroll_user(app: &AppHandle<Wry>) -> Result<()> {
     let app_state: State<AppState> = app.state::<AppState>();
-    enroll_with_token(&app_state)
+    app_state
+        .enroll(app, EnrollmentFlow::Auth0)
         .await
         .map(|i| info!("Enrolled a new user with identifier {}", i))
         .unwrap_or_else(|e| error!("{:?}", e));
@@ -33,7 +35,7 @@ pub async fn enroll_user(app: &AppHandle<Wry>) -> Result<()> {
     Ok(())
 }
 
-async fn enroll_with_token(app_state: &AppState) -> Result<IdentityIdentifier> {
+pub(crate) async fn enroll_with_token(app_state: &AppState) -> Result<IdentityIdentifier> {
     // get an Auth0 token
     let auth0_service = OidcService::default();
     let token = auth0_service.get_token_with_pkce().await?;
@@ -44,17 +46,20 @@ async fn enroll_with_token(app_state: &AppState) -> Result<IdentityIdentifier> {
     app_state.model_mut(|m| m.set_user_info(user_info)).await?;
 
     // enroll the current user using that token on the controller
-    let node_manager = app_state.node_manager.get().write().await;
-    node_manager
-        .enroll_auth0(&app_state.context(), &CloudOpts::route(), token)
-        .await
-        .into_diagnostic()?;
+    {
+        let node_manager = app_state.node_manager.get().write().await;
+        node_manager
+            .enroll_auth0(&app_state.context(), &CloudOpts::route(), token)
+            .await
+            .into_diagnostic()?;
+    }
 
     let space = retrieve_space(app_state).await?;
-    let _ = retrieve_project(app_state, &space).await?;
-    let identifier = update_enrolled_identity(&app_state.options().await, NODE_NAME)
+    let project = retrieve_project(app_state, &space).await?;
+    let identifier = update_enrolled_identity(&app_state.options().await, &app_state.node_name())
         .await
         .into_diagnostic()?;
+    app_state.record_enrollment(&project).await?;
     Ok(identifier)
 }
 
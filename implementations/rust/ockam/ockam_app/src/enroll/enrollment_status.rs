@@ -0,0 +1,182 @@
+use std::future::Future;
+use std::time::Duration;
+
+use serde::Serialize;
+use tracing::warn;
+
+use ockam::compat::tokio;
+use ockam::identity::Timestamp;
+use ockam_core::errcode::{Kind, Origin};
+
+use crate::enroll::{EnrollmentSummary, EnrollmentSummaryStatus};
+
+/// Maximum duration of the retrieval of the node credential from the project authority
+/// when the enrollment is checked
+pub const AUTHORITY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Current enrollment status of the application, as displayed by the status indicators.
+///
+/// It can be watched with `AppState::enrollment_watch`
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EnrollmentStatus {
+    NotEnrolled,
+    /// An enrollment flow is in progress
+    Enrolling,
+    Enrolled,
+    /// The application is enrolled but its credential couldn't be retrieved from the project
+    /// authority to verify it, for example because the computer is offline
+    Unverified,
+    /// The application is enrolled but the project authority refused to issue its credential,
+    /// for example because its identity is not a member of the project anymore
+    CredentialRejected,
+    /// The application is enrolled but its credential has expired
+    CredentialExpired,
+}
+
+/// Reason why the credential of the node couldn't be verified with the project authority
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationError {
+    /// The authority couldn't be reached, or didn't answer in time
+    Unreachable,
+    /// The authority answered with an error of the given kind
+    Rejected(Kind),
+}
+
+impl VerificationError {
+    /// Classify an error returned while the credential is retrieved: the transport errors,
+    /// timeouts and interruptions mean that the authority couldn't be reached
+    fn new(error: &ockam::Error) -> Self {
+        let code = error.code();
+        match code.kind {
+            _ if code.origin == Origin::Transport => VerificationError::Unreachable,
+            Kind::Io | Kind::Timeout | Kind::Cancelled | Kind::Shutdown => {
+                VerificationError::Unreachable
+            }
+            kind => VerificationError::Rejected(kind),
+        }
+    }
+}
+
+impl EnrollmentStatus {
+    /// Return the status of the application given its last known enrollment, at `now`,
+    /// in seconds since the Unix epoch. A stale enrollment is still enrolled.
+    /// `verification` is the error returned the last time the credential was verified, if any.
+    /// A credential known to be expired is expired, even if it is not verified
+    pub fn new(
+        summary: Option<&EnrollmentSummary>,
+        verification: Option<VerificationError>,
+        now: u64,
+    ) -> Self {
+        match (summary.map(|s| s.status(now)), verification) {
+            (None, _) => EnrollmentStatus::NotEnrolled,
+            (Some(EnrollmentSummaryStatus::Expired), _) => EnrollmentStatus::CredentialExpired,
+            (Some(_), Some(VerificationError::Unreachable)) => EnrollmentStatus::Unverified,
+            (Some(_), Some(VerificationError::Rejected(_))) => EnrollmentStatus::CredentialRejected,
+            (Some(_), None) => EnrollmentStatus::Enrolled,
+        }
+    }
+}
+
+/// Return the expiration of the credential, in seconds since the Unix epoch, or the reason why
+/// the credential couldn't be retrieved within `timeout`, for example because the project
+/// authority is unreachable
+pub(crate) async fn credential_expiration_within(
+    expiration: impl Future<Output = ockam::Result<Timestamp>>,
+    timeout: Duration,
+) -> Result<u64, VerificationError> {
+    match tokio::time::timeout(timeout, expiration).await {
+        Ok(Ok(expiration)) => Ok(expiration.unix_time()),
+        Ok(Err(e)) => {
+            let error = VerificationError::new(&e);
+            warn!(%e, ?error, "cannot retrieve the credential of the node");
+            Err(error)
+        }
+        Err(_) => {
+            warn!(
+                "the credential of the node couldn't be retrieved in {}s",
+                timeout.as_secs_f32()
+            );
+            Err(VerificationError::Unreachable)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_status_is_derived_from_the_enrollment_summary() {
+        assert_eq!(
+            EnrollmentStatus::new(None, None, 1_000),
+            EnrollmentStatus::NotEnrolled
+        );
+        assert_eq!(
+            EnrollmentStatus::new(None, Some(VerificationError::Unreachable), 1_000),
+            EnrollmentStatus::NotEnrolled
+        );
+        let summary = EnrollmentSummary {
+            project_id: "id".to_string(),
+            project_name: "default".to_string(),
+            authority_identifier: None,
+            credential_expires_at: Some(2_000),
+            updated_at: 1_000,
+        };
+        assert_eq!(
+            EnrollmentStatus::new(Some(&summary), None, 1_999),
+            EnrollmentStatus::Enrolled
+        );
+        let unreachable = Some(VerificationError::Unreachable);
+        assert_eq!(
+            EnrollmentStatus::new(Some(&summary), unreachable, 1_999),
+            EnrollmentStatus::Unverified
+        );
+        let rejected = Some(VerificationError::Rejected(Kind::NotFound));
+        assert_eq!(
+            EnrollmentStatus::new(Some(&summary), rejected, 1_999),
+            EnrollmentStatus::CredentialRejected
+        );
+        assert_eq!(
+            EnrollmentStatus::new(Some(&summary), unreachable, 2_000),
+            EnrollmentStatus::CredentialExpired
+        );
+    }
+
+    #[test]
+    fn an_unreachable_authority_does_not_block_the_enrollment_check() {
+        crate::app::test_block_on(async {
+            // an authority which never answers
+            let expiration =
+                credential_expiration_within(std::future::pending(), Duration::from_millis(10))
+                    .await;
+            assert_eq!(expiration, Err(VerificationError::Unreachable));
+
+            // an authority which can't be connected to
+            let unreachable = async {
+                Err(ockam::Error::new(
+                    Origin::Transport,
+                    Kind::NotFound,
+                    "connection refused",
+                ))
+            };
+            let expiration = credential_expiration_within(unreachable, AUTHORITY_TIMEOUT).await;
+            assert_eq!(expiration, Err(VerificationError::Unreachable));
+
+            // an authority which refuses to issue a credential
+            let refusing = async {
+                Err(ockam::Error::new(
+                    Origin::Api,
+                    Kind::NotFound,
+                    "not a member",
+                ))
+            };
+            let expiration = credential_expiration_within(refusing, AUTHORITY_TIMEOUT).await;
+            assert_eq!(expiration, Err(VerificationError::Rejected(Kind::NotFound)));
+
+            let reachable = async { Ok(Timestamp::now().unwrap()) };
+            let expiration = credential_expiration_within(reachable, AUTHORITY_TIMEOUT).await;
+            assert!(expiration.is_ok());
+        });
+    }
+}
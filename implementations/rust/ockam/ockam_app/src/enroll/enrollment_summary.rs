@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Wry};
+
+use ockam::identity::Timestamp;
+use ockam_api::cloud::project::Project;
+use ockam_api::config::lookup::ProjectAuthority;
+
+use crate::app::AppState;
+
+/// A summary which was not reconciled with the cli state and the project authority
+/// for this duration is stale
+pub const ENROLLMENT_SUMMARY_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Summary of the last known enrollment of the application.
+///
+/// It is persisted in the `ModelState` so that the enrollment status can be displayed as soon as
+/// the application starts, before it is reconciled with the cli state and the project authority
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct EnrollmentSummary {
+    pub project_id: String,
+    pub project_name: String,
+    /// Identifier of the project authority, if the project has one
+    pub authority_identifier: Option<String>,
+    /// Expiration of the credential issued by the project authority, in seconds since
+    /// the Unix epoch, if the credential was retrieved
+    pub credential_expires_at: Option<u64>,
+    /// Time of the last update of the summary, in seconds since the Unix epoch
+    pub updated_at: u64,
+}
+
+/// Status of an `EnrollmentSummary` at a given time
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EnrollmentSummaryStatus {
+    Valid,
+    /// The summary was not updated for more than `ENROLLMENT_SUMMARY_MAX_AGE`
+    Stale,
+    /// The credential of the project authority has expired
+    Expired,
+}
+
+impl EnrollmentSummary {
+    /// Create a summary for a project. The project authority identity is decoded to get
+    /// its identifier, a project without a valid authority has no authority identifier
+    pub async fn new(project: &Project) -> Self {
+        let authority = ProjectAuthority::from_raw(
+            &project.authority_access_route,
+            &project.authority_identity,
+        )
+        .await
+        .ok()
+        .flatten();
+        Self {
+            project_id: project.id.clone(),
+            project_name: project.name.clone(),
+            authority_identifier: authority.map(|a| a.identity_id().to_string()),
+            credential_expires_at: None,
+            updated_at: now(),
+        }
+    }
+
+    pub fn with_credential_expiration(mut self, expires_at: Option<u64>) -> Self {
+        self.credential_expires_at = expires_at;
+        self
+    }
+
+    /// Return the status of the summary at `now`, in seconds since the Unix epoch
+    pub fn status(&self, now: u64) -> EnrollmentSummaryStatus {
+        if self.credential_expires_at.map_or(false, |e| e <= now) {
+            EnrollmentSummaryStatus::Expired
+        } else if now.saturating_sub(self.updated_at) > ENROLLMENT_SUMMARY_MAX_AGE.as_secs() {
+            EnrollmentSummaryStatus::Stale
+        } else {
+            EnrollmentSummaryStatus::Valid
+        }
+    }
+}
+
+/// Current time in seconds since the Unix epoch
+pub(crate) fn now() -> u64 {
+    Timestamp::now().map(|t| t.unix_time()).unwrap_or_default()
+}
+
+/// Payload of the `enrollment_summary` command
+#[derive(Serialize, Debug, Clone)]
+pub struct EnrollmentSummaryView {
+    #[serde(flatten)]
+    pub summary: EnrollmentSummary,
+    pub status: EnrollmentSummaryStatus,
+}
+
+/// Return the last known enrollment of the application and its status, without checking it.
+/// It is `None` if the application is not enrolled
+#[tauri::command]
+pub async fn enrollment_summary(app: AppHandle<Wry>) -> Option<EnrollmentSummaryView> {
+    let app_state = app.state::<AppState>();
+    app_state
+        .model(|m| m.get_enrollment().cloned())
+        .await
+        .map(|summary| EnrollmentSummaryView {
+            status: summary.status(now()),
+            summary,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expired_and_stale_summaries_are_flagged() {
+        let summary = EnrollmentSummary {
+            project_id: "id".to_string(),
+            project_name: "default".to_string(),
+            authority_identifier: None,
+            credential_expires_at: None,
+            updated_at: 1_000,
+        };
+        assert_eq!(summary.status(1_000), EnrollmentSummaryStatus::Valid);
+        let max_age = ENROLLMENT_SUMMARY_MAX_AGE.as_secs();
+        assert_eq!(
+            summary.status(1_000 + max_age + 1),
+            EnrollmentSummaryStatus::Stale
+        );
+
+        let summary = summary.with_credential_expiration(Some(2_000));
+        assert_eq!(summary.status(1_999), EnrollmentSummaryStatus::Valid);
+        assert_eq!(summary.status(2_000), EnrollmentSummaryStatus::Expired);
+    }
+}
@@ -1,4 +1,18 @@
+pub(crate) mod enroll_ticket;
 pub(crate) mod enroll_user;
+mod enrollment_status;
+mod enrollment_summary;
 mod tray_menu;
 
+pub use enrollment_status::*;
+pub use enrollment_summary::*;
 pub use tray_menu::*;
+
+/// The different ways of enrolling the application
+#[derive(Clone, Debug)]
+pub enum EnrollmentFlow {
+    /// Authenticate the user with Auth0 and create a default space and project
+    Auth0,
+    /// Use a hex-encoded enrollment ticket to enroll with an existing project
+    Ticket(String),
+}
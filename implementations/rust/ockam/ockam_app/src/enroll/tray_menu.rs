@@ -13,7 +13,9 @@ pub(crate) async fn build_enroll_section(
     app_state: &AppState,
     tray_menu: SystemTrayMenu,
 ) -> SystemTrayMenu {
-    if app_state.is_enrolled().await {
+    if app_state.is_local_only() {
+        tray_menu.add_item(CustomMenuItem::new(ENROLL_MENU_HEADER_ID, "Local-only mode").disabled())
+    } else if app_state.is_enrolled().await {
         match app_state.model(|m| m.get_user_info()).await {
             Some(user_info) => {
                 let item = CustomMenuItem::new(
@@ -1,6 +1,8 @@
 use miette::Diagnostic;
 use thiserror::Error;
 
+use crate::app::AppStateRetry;
+
 pub type Result<T> = miette::Result<T, Error>;
 
 #[derive(Error, Diagnostic, Debug)]
@@ -22,6 +24,55 @@ pub enum Error {
 
     #[error(transparent)]
     JsonSerde(#[from] serde_json::Error),
+
+    #[error("The enrollment ticket is invalid or expired: {0}")]
+    InvalidEnrollmentTicket(String),
+
+    #[error("The enrollment was cancelled")]
+    EnrollmentCancelled,
+
+    #[error("Another enrollment is in progress")]
+    EnrollmentInProgress,
+
+    #[error("The project authority can't be reached: {0}")]
+    AuthorityUnreachable(String),
+
+    #[error("The operation was cancelled")]
+    Cancelled,
+
+    #[error("The stored model state is corrupted: {0}")]
+    CorruptedModelState(String),
+
+    #[error("The application runs in local-only mode, it can't be enrolled")]
+    LocalOnly,
+
+    #[error("A conflicting outlet already exists: {0}")]
+    OutletConflict(String),
+
+    /// Returned by a secret store when a vault can't be opened while it is locked, for example
+    /// while the keychain of the system is locked. The other errors of the secret stores are fatal
+    #[error("The vault of the node is locked: {reason}")]
+    VaultLocked {
+        reason: String,
+        /// Creation of the application state to retry once the vault is unlocked
+        retry: Option<Box<AppStateRetry>>,
+    },
+}
+
+impl Error {
+    /// Return the error reported by a secret store when a vault can't be opened because it is
+    /// locked
+    pub fn vault_locked(reason: impl Into<String>) -> Self {
+        Error::VaultLocked {
+            reason: reason.into(),
+            retry: None,
+        }
+    }
+
+    /// Return true if the operation was cancelled rather than failed
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Error::Cancelled | Error::EnrollmentCancelled)
+    }
 }
 
 impl From<miette::Report> for Error {
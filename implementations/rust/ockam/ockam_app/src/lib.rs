@@ -1,6 +1,17 @@
-use crate::app::{configure_tauri_plugin_log, process_application_event, setup_app, AppState};
+use crate::app::{
+    configure_tauri_plugin_log, node_listen_multiaddr, process_application_event,
+    secure_channel_close, secure_channel_list, setup_app, AppState,
+};
+use crate::enroll::enroll_ticket::{enroll_cancel, enroll_with_ticket};
+use crate::enroll::enrollment_summary;
 use crate::error::Result;
-use shared_service::tcp::outlet::tcp_outlet_create;
+use crate::options::reset_cancel;
+use shared_service::tcp::inlet::tcp_inlet_create;
+use shared_service::tcp::outlet::{
+    tcp_outlet_create, tcp_outlet_latency, tcp_outlet_list, tcp_outlet_probe, tcp_outlet_rename,
+    tcp_outlet_set_label,
+};
+use shared_service::tcp::tcp_portals_clear;
 
 mod app;
 mod enroll;
@@ -11,12 +22,30 @@ mod shared_service;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // The state is managed by the application once it is created, which is retried from the
+    // system tray while the vault of the node is locked
+    let app_state = AppState::try_new();
     // For now, the application only consists of a system tray with several menu items
     let mut app = tauri::Builder::default()
         .plugin(configure_tauri_plugin_log())
-        .setup(move |app| setup_app(app))
-        .manage(AppState::new())
-        .invoke_handler(tauri::generate_handler![tcp_outlet_create])
+        .setup(move |app| setup_app(app, app_state))
+        .invoke_handler(tauri::generate_handler![
+            enroll_cancel,
+            enroll_with_ticket,
+            enrollment_summary,
+            node_listen_multiaddr,
+            reset_cancel,
+            secure_channel_close,
+            secure_channel_list,
+            tcp_inlet_create,
+            tcp_outlet_create,
+            tcp_outlet_latency,
+            tcp_outlet_list,
+            tcp_outlet_probe,
+            tcp_outlet_rename,
+            tcp_outlet_set_label,
+            tcp_portals_clear
+        ])
         .build(tauri::generate_context!())
         .expect("Error while building the Ockam application");
 
@@ -1,17 +1,56 @@
-use miette::miette;
+use serde::Serialize;
 use tauri::{AppHandle, Manager, Wry};
 use tracing::log::info;
 
-use crate::app::AppState;
+use crate::app::events::RESET_PROGRESS;
+use crate::app::{AppState, ResetReport, ResetStage};
 use crate::Result;
 
 /// Reset the project.
 /// This function removes all persisted state
-/// So that the user must enroll again in order to be able to access a project
-pub async fn reset(app: &AppHandle<Wry>) -> Result<()> {
+/// So that the user must enroll again in order to be able to access a project.
+/// A `RESET_PROGRESS` event, with the JSON serialization of a `ResetProgress`,
+/// is emitted when each stage of the reset starts.
+/// The reset can be cancelled with `reset_cancel` until the state is reset
+pub async fn reset(app: &AppHandle<Wry>) -> Result<ResetReport> {
     let app_state = app.state::<AppState>();
-    let result = app_state.reset().await;
-    info!("Application state recreated");
+    let result = app_state
+        .reset_with_progress(|stage| {
+            if let Ok(payload) = serde_json::to_string(&ResetProgress::new(stage)) {
+                app.trigger_global(RESET_PROGRESS, Some(payload));
+            }
+        })
+        .await;
+    match &result {
+        Ok(report) => info!("{report}"),
+        Err(e) if e.is_cancelled() => info!("the reset was cancelled"),
+        Err(_) => (),
+    }
     app.trigger_global(crate::app::events::SYSTEM_TRAY_ON_UPDATE, None);
-    result.map_err(|e| miette!(e).into())
+    result
+}
+
+/// Cancel the reset currently in progress, if any.
+#[tauri::command]
+pub async fn reset_cancel(app: AppHandle<Wry>) -> bool {
+    app.state::<AppState>().cancel_reset().await
+}
+
+/// Payload of the `RESET_PROGRESS` event
+#[derive(Serialize, Debug, Clone)]
+pub struct ResetProgress {
+    pub stage: ResetStage,
+    /// Position of the stage, starting at 1
+    pub step: usize,
+    pub total: usize,
+}
+
+impl ResetProgress {
+    fn new(stage: ResetStage) -> Self {
+        Self {
+            stage,
+            step: stage.step(),
+            total: ResetStage::COUNT,
+        }
+    }
 }
@@ -1,8 +1,11 @@
-use tauri::{AppHandle, CustomMenuItem, SystemTrayMenu, Wry};
+use tauri::{AppHandle, CustomMenuItem, Manager, SystemTrayMenu, Wry};
 
+use crate::app::events::SYSTEM_TRAY_ON_UPDATE;
 use crate::app::AppState;
 use crate::options::reset;
 
+pub const MODEL_STATE_ERROR_MENU_ID: &str = "model-state-error";
+pub const PAUSE_MENU_ID: &str = "pause";
 pub const RESET_MENU_ID: &str = "reset";
 pub const QUIT_MENU_ID: &str = "quit";
 
@@ -10,6 +13,22 @@ pub(crate) async fn build_options_section(
     app_state: &AppState,
     tray_menu: SystemTrayMenu,
 ) -> SystemTrayMenu {
+    let tray_menu = match app_state.model_state_error().await {
+        Some(_) => tray_menu.add_item(
+            CustomMenuItem::new(
+                MODEL_STATE_ERROR_MENU_ID,
+                "The saved services were corrupted and have been backed up",
+            )
+            .disabled(),
+        ),
+        None => tray_menu,
+    };
+    let pause_label = if app_state.is_node_paused().await {
+        "Resume"
+    } else {
+        "Pause"
+    };
+    let tray_menu = tray_menu.add_item(CustomMenuItem::new(PAUSE_MENU_ID, pause_label));
     let tm = if app_state.is_enrolled().await {
         tray_menu.add_item(CustomMenuItem::new(RESET_MENU_ID, "Reset").accelerator("cmd+r"))
     } else {
@@ -18,6 +37,22 @@ pub(crate) async fn build_options_section(
     tm.add_item(CustomMenuItem::new(QUIT_MENU_ID, "Quit").accelerator("cmd+q"))
 }
 
+/// Event listener for the "Pause" and "Resume" menu item
+/// Pause the node if it is running, resume it otherwise
+pub fn on_pause(app: &AppHandle<Wry>) -> tauri::Result<()> {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let app_state = app.state::<AppState>();
+        if app_state.is_node_paused().await {
+            app_state.resume_node().await
+        } else {
+            app_state.pause_node(false).await
+        }
+        app.trigger_global(SYSTEM_TRAY_ON_UPDATE, None);
+    });
+    Ok(())
+}
+
 /// Event listener for the "Reset" menu item
 /// Reset the persistent state
 pub fn on_reset(app: &AppHandle<Wry>) -> tauri::Result<()> {
@@ -27,7 +62,15 @@ pub fn on_reset(app: &AppHandle<Wry>) -> tauri::Result<()> {
 }
 
 /// Event listener for the "Quit" menu item
-/// Quit the application when the user wants to
-pub fn on_quit() -> tauri::Result<()> {
-    std::process::exit(0);
+/// Shut down the node then quit the application when the user wants to.
+/// There is no node to shut down if the application was not started, while its vault is locked
+pub fn on_quit(app: &AppHandle<Wry>) -> tauri::Result<()> {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Some(app_state) = app.try_state::<AppState>() {
+            app_state.shutdown().await;
+        }
+        std::process::exit(0);
+    });
+    Ok(())
 }
@@ -1 +1,3 @@
 pub(crate) mod create;
+pub(crate) mod model_state;
+pub(crate) mod outlet;
@@ -0,0 +1,16 @@
+use crate::app::ModelState;
+
+impl ModelState {
+    /// Record that an outlet is reachable through a relay, so that its forwarder can be
+    /// registered again
+    pub fn add_relayed_outlet(&mut self, alias: &str) {
+        if !self.relayed_outlets.iter().any(|a| a == alias) {
+            self.relayed_outlets.push(alias.to_string());
+        }
+    }
+
+    /// Return the aliases of the outlets reachable through a relay
+    pub fn get_relayed_outlets(&self) -> &[String] {
+        &self.relayed_outlets
+    }
+}
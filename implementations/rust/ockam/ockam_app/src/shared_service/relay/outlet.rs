@@ -0,0 +1,71 @@
+use std::str::FromStr;
+
+use miette::IntoDiagnostic;
+use serde::Serialize;
+
+use ockam_api::nodes::models::forwarder::CreateForwarder;
+use ockam_api::nodes::models::portal::OutletStatus;
+use ockam_api::DefaultAddress;
+use ockam_multiaddr::MultiAddr;
+
+use crate::Result;
+
+/// Relay where the forwarders of the outlets are registered, so that the peers outside of the
+/// local network can reach the outlets
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Relay {
+    /// Relay of a project, given its name
+    Project(String),
+    /// Relay of a node, for example a node started for the tests
+    Node(MultiAddr),
+}
+
+impl Relay {
+    /// Return the address of the relay
+    pub(crate) fn address(&self) -> Result<MultiAddr> {
+        match self {
+            Relay::Project(name) => {
+                Ok(MultiAddr::from_str(&format!("/project/{name}")).into_diagnostic()?)
+            }
+            Relay::Node(address) => Ok(address.clone()),
+        }
+    }
+
+    /// Return the request registering a forwarder for an outlet at this relay.
+    /// The forwarder is named after the worker address of the outlet, which is kept when
+    /// the outlet is renamed
+    pub(crate) fn create_forwarder(&self, outlet: &OutletStatus) -> Result<CreateForwarder> {
+        let alias = Some(outlet.worker_addr.clone());
+        Ok(match self {
+            Relay::Project(_) => CreateForwarder::at_project(self.address()?, alias),
+            Relay::Node(address) => CreateForwarder::at_node(address.clone(), alias, true, None),
+        })
+    }
+}
+
+/// An outlet reachable through a relay, with the information to share with its peers
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct RelayedOutlet {
+    pub outlet: OutletStatus,
+    /// Address of the forwarder of the outlet at the relay
+    pub remote_address: String,
+    /// Route to the outlet through the relay, used by the peers to create an inlet.
+    /// The peers create a secure channel with the node of the application through the relay
+    pub relay_route: String,
+}
+
+impl RelayedOutlet {
+    pub(crate) fn new(relay: &Relay, outlet: OutletStatus, remote_address: &str) -> Result<Self> {
+        let relay_route = format!(
+            "{}/service/{remote_address}/secure/{}/service/{}",
+            relay.address()?,
+            DefaultAddress::SECURE_CHANNEL_LISTENER,
+            outlet.worker_addr
+        );
+        Ok(Self {
+            outlet,
+            remote_address: remote_address.to_string(),
+            relay_route,
+        })
+    }
+}
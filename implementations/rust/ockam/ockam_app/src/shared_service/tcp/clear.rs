@@ -0,0 +1,84 @@
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Wry};
+use tracing::{error, info, warn};
+
+use crate::app::AppState;
+use crate::error::Error;
+
+/// Payload of the `PORTAL_REMOVED` event
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PortalRemoved {
+    /// Either "inlet" or "outlet"
+    pub kind: &'static str,
+    pub alias: String,
+}
+
+impl PortalRemoved {
+    pub fn inlet(alias: impl Into<String>) -> Self {
+        Self {
+            kind: "inlet",
+            alias: alias.into(),
+        }
+    }
+
+    pub fn outlet(alias: impl Into<String>) -> Self {
+        Self {
+            kind: "outlet",
+            alias: alias.into(),
+        }
+    }
+
+    pub fn is_inlet(&self) -> bool {
+        self.kind == "inlet"
+    }
+}
+
+/// Outcome of the removal of all the portals, see `AppState::clear_portals`
+#[derive(Default, Debug)]
+pub(crate) struct ClearedPortals {
+    pub(crate) removed: Vec<PortalRemoved>,
+    /// The portals which couldn't be removed, with the reason
+    pub(crate) failed: Vec<(PortalRemoved, String)>,
+}
+
+impl ClearedPortals {
+    /// Record the result of the removal of a portal
+    pub(crate) fn add<T>(&mut self, portal: PortalRemoved, result: ockam_core::Result<T>) {
+        match result {
+            Ok(_) => self.removed.push(portal),
+            Err(e) => {
+                warn!(kind = portal.kind, alias = %portal.alias, "failed to stop the portal: {e:?}");
+                self.failed.push((portal, e.to_string()));
+            }
+        }
+    }
+
+    /// Return the removed portals, or an error listing the portals which couldn't be removed
+    pub(crate) fn into_result(self) -> crate::Result<Vec<PortalRemoved>> {
+        if self.failed.is_empty() {
+            return Ok(self.removed);
+        }
+        let failed: Vec<String> = self
+            .failed
+            .iter()
+            .map(|(portal, e)| format!("{} {}: {e}", portal.kind, portal.alias))
+            .collect();
+        Err(Error::Generic(format!(
+            "Some portals could not be removed: {}",
+            failed.join(", ")
+        )))
+    }
+}
+
+/// Remove all the shared services, keeping the identity and the enrollment.
+#[tauri::command]
+pub async fn tcp_portals_clear(app: AppHandle<Wry>) -> Result<(), String> {
+    let app_state = app.state::<AppState>();
+    let removed = app_state.clear_portals(&app).await.map_err(|e| {
+        error!("{:?}", e);
+        e.to_string()
+    })?;
+    info!(count = removed.len(), "Portals removed");
+    app.trigger_global(crate::app::events::SYSTEM_TRAY_ON_UPDATE, None);
+    Ok(())
+}
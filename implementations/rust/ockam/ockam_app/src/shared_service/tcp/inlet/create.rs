@@ -0,0 +1,55 @@
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+use miette::{IntoDiagnostic, WrapErr};
+use tauri::{AppHandle, Manager, Wry};
+use tracing::{debug, error, info};
+
+use ockam_multiaddr::MultiAddr;
+
+use crate::app::AppState;
+
+/// Create a TCP inlet within the default node, forwarding the connections accepted on
+/// `bind_addr` to the outlet at `outlet_addr`. The inlet is recreated when the application restarts
+#[tauri::command]
+pub async fn tcp_inlet_create(
+    app: AppHandle<Wry>,
+    alias: String,
+    bind_addr: String,
+    outlet_addr: String,
+) -> Result<(), String> {
+    tcp_inlet_create_impl(app, alias, bind_addr, outlet_addr)
+        .await
+        .map_err(|e| {
+            error!("{:?}", e);
+            e.to_string()
+        })?;
+    Ok(())
+}
+
+async fn tcp_inlet_create_impl(
+    app: AppHandle<Wry>,
+    alias: String,
+    bind_addr: String,
+    outlet_addr: String,
+) -> crate::Result<()> {
+    debug!(%alias, %bind_addr, %outlet_addr, "Creating an inlet");
+    let app_state = app.state::<AppState>();
+    let bind_addr: SocketAddr = bind_addr
+        .parse()
+        .into_diagnostic()
+        .wrap_err("Invalid bind address")?;
+    let outlet_addr = MultiAddr::from_str(&outlet_addr)
+        .into_diagnostic()
+        .wrap_err("Invalid outlet address")?;
+    let status = app_state
+        .create_inlet(&alias, bind_addr, outlet_addr, None)
+        .await?;
+    info!(
+        alias = status.alias,
+        bind_addr = status.bind_addr,
+        "Inlet created"
+    );
+    app.trigger_global(crate::app::events::SYSTEM_TRAY_ON_UPDATE, None);
+    Ok(())
+}
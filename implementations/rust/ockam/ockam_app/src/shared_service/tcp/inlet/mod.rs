@@ -0,0 +1,4 @@
+pub use create::tcp_inlet_create;
+
+mod create;
+pub(crate) mod model_state;
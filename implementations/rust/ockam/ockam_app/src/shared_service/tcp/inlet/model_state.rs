@@ -0,0 +1,52 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::app::ModelState;
+
+/// A TCP inlet persisted in the ModelState so that it can be recreated at startup
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TcpInletModel {
+    pub alias: String,
+    pub bind_addr: String,
+    /// MultiAddr of the outlet that this inlet connects to
+    pub outlet_addr: String,
+    /// Alias of the outlet, created by this node, which must be restored before this inlet
+    #[serde(default)]
+    pub outlet_alias: Option<String>,
+    /// True if the inlet could not be restored at startup
+    #[serde(default)]
+    pub pending: bool,
+}
+
+impl TcpInletModel {
+    pub fn new(
+        alias: impl Into<String>,
+        bind_addr: impl Into<String>,
+        outlet_addr: impl Into<String>,
+        outlet_alias: Option<String>,
+    ) -> Self {
+        Self {
+            alias: alias.into(),
+            bind_addr: bind_addr.into(),
+            outlet_addr: outlet_addr.into(),
+            outlet_alias,
+            pending: false,
+        }
+    }
+}
+
+impl ModelState {
+    /// Add an inlet, replacing the inlet with the same alias if there is one
+    pub fn add_tcp_inlet(&mut self, inlet: TcpInletModel) {
+        self.remove_tcp_inlet(&inlet.alias);
+        self.tcp_inlets.push(inlet);
+    }
+
+    pub fn remove_tcp_inlet(&mut self, alias: &str) {
+        self.tcp_inlets.retain(|i| i.alias != alias);
+    }
+
+    pub fn get_tcp_inlets(&self) -> &[TcpInletModel] {
+        &self.tcp_inlets
+    }
+}
@@ -1 +1,6 @@
+pub use clear::tcp_portals_clear;
+
+pub(crate) mod clear;
+pub mod inlet;
+pub(crate) mod model_state;
 pub mod outlet;
@@ -0,0 +1,213 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use miette::{miette, IntoDiagnostic};
+use tracing::{info, warn};
+
+use ockam::Context;
+use ockam_api::nodes::models::portal::{CreateInlet, OutletStatus};
+use ockam_api::nodes::service::OutletSpec;
+use ockam_api::nodes::NodeManagerWorker;
+use ockam_core::api::Id;
+use ockam_core::{async_trait, route};
+use ockam_multiaddr::MultiAddr;
+
+use crate::app::ModelState;
+use crate::shared_service::tcp::inlet::model_state::TcpInletModel;
+use crate::Result;
+
+/// Recreates the portals stored in a ModelState
+#[async_trait]
+pub(crate) trait PortalRestorer: Send {
+    async fn restore_outlet(&mut self, outlet: &OutletStatus) -> Result<()>;
+    async fn restore_inlet(&mut self, inlet: &TcpInletModel) -> Result<()>;
+}
+
+/// Restore all the portals of a ModelState.
+///
+/// Outlets are restored first since inlets can be routed to them.
+/// An inlet referencing an outlet which could not be restored is skipped and marked as pending.
+pub(crate) async fn restore_portals(
+    restorer: &mut impl PortalRestorer,
+    model_state: &mut ModelState,
+) {
+    let mut restored_outlets = HashSet::new();
+    for tcp_outlet in model_state.get_tcp_outlets() {
+        match restorer.restore_outlet(tcp_outlet).await {
+            Ok(()) => {
+                restored_outlets.insert(tcp_outlet.alias.clone());
+            }
+            Err(e) => warn!(
+                alias = %tcp_outlet.alias,
+                tcp_addr = %tcp_outlet.tcp_addr,
+                "failed to restore outlet: {e:?}"
+            ),
+        }
+    }
+
+    for tcp_inlet in model_state.tcp_inlets.iter_mut() {
+        if let Some(outlet_alias) = &tcp_inlet.outlet_alias {
+            if !restored_outlets.contains(outlet_alias) {
+                info!(
+                    alias = %tcp_inlet.alias,
+                    %outlet_alias,
+                    "the outlet of this inlet was not restored, the inlet is pending"
+                );
+                tcp_inlet.pending = true;
+                continue;
+            }
+        }
+        tcp_inlet.pending = match restorer.restore_inlet(tcp_inlet).await {
+            Ok(()) => false,
+            Err(e) => {
+                warn!(alias = %tcp_inlet.alias, "failed to restore inlet: {e:?}");
+                true
+            }
+        };
+    }
+}
+
+/// Restore portals on a running node manager
+pub(crate) struct NodeManagerPortalRestorer {
+    context: Arc<Context>,
+    node_manager: NodeManagerWorker,
+}
+
+impl NodeManagerPortalRestorer {
+    pub(crate) fn new(context: Arc<Context>, node_manager: NodeManagerWorker) -> Self {
+        Self {
+            context,
+            node_manager,
+        }
+    }
+}
+
+#[async_trait]
+impl PortalRestorer for NodeManagerPortalRestorer {
+    async fn restore_outlet(&mut self, outlet: &OutletStatus) -> Result<()> {
+        let mut node_manager = self.node_manager.get().write().await;
+        let spec = OutletSpec::new(outlet.tcp_addr.clone(), outlet.worker_addr.clone())
+            .with_alias(outlet.alias.clone())
+            .reachable_from_default_secure_channel(true)
+            .with_idle_timeout(outlet.idle_timeout())
+            .with_max_connections(outlet.max_connections)
+            .with_tags(outlet.tags.clone().unwrap_or_default());
+        node_manager
+            .create_outlet_from_spec(&self.context, spec)
+            .await
+            .map_err(|e| miette!(e))?;
+        Ok(())
+    }
+
+    async fn restore_inlet(&mut self, inlet: &TcpInletModel) -> Result<()> {
+        let listen_addr = inlet.bind_addr.parse().into_diagnostic()?;
+        let outlet_addr = MultiAddr::from_str(&inlet.outlet_addr).into_diagnostic()?;
+        let mut req = CreateInlet::to_node(listen_addr, outlet_addr, route![], route![], None);
+        req.set_alias(inlet.alias.clone());
+        // an inlet whose outlet is stuck must not block the restoration of the other portals
+        NodeManagerWorker::with_timeout(
+            self.node_manager.request_timeout(),
+            self.node_manager
+                .create_inlet_impl(Id::fresh(), req, &self.context),
+        )
+        .await
+        .map_err(|e| miette!("failed to create inlet {}: {e}", inlet.alias))?
+        .map_err(|e| {
+            let message = e
+                .into_parts()
+                .1
+                .and_then(|e| e.message().map(|m| m.to_string()))
+                .unwrap_or_else(|| "unknown error".to_string());
+            miette!("failed to create inlet {}: {message}", inlet.alias)
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// This restorer fails to restore the outlets listed in `failing`
+    /// and records the aliases of the restored portals
+    #[derive(Default)]
+    struct FakeRestorer {
+        failing: Vec<String>,
+        restored: Vec<String>,
+    }
+
+    #[async_trait]
+    impl PortalRestorer for FakeRestorer {
+        async fn restore_outlet(&mut self, outlet: &OutletStatus) -> Result<()> {
+            if self.failing.contains(&outlet.alias) {
+                return Err(miette!("cannot restore {}", outlet.alias).into());
+            }
+            self.restored.push(outlet.alias.clone());
+            Ok(())
+        }
+
+        async fn restore_inlet(&mut self, inlet: &TcpInletModel) -> Result<()> {
+            self.restored.push(inlet.alias.clone());
+            Ok(())
+        }
+    }
+
+    fn model_state() -> ModelState {
+        let mut model_state = ModelState::default();
+        // the inlets are stored before their outlets to check the restoration order
+        model_state.add_tcp_inlet(TcpInletModel::new(
+            "inlet-1",
+            "127.0.0.1:5001",
+            "/service/outlet-1",
+            Some("outlet-1".to_string()),
+        ));
+        model_state.add_tcp_inlet(TcpInletModel::new(
+            "inlet-2",
+            "127.0.0.1:5002",
+            "/service/outlet-2",
+            Some("outlet-2".to_string()),
+        ));
+        model_state.add_tcp_outlet(OutletStatus::new(
+            "127.0.0.1:6001",
+            "outlet-1",
+            "outlet-1",
+            None,
+        ));
+        model_state.add_tcp_outlet(OutletStatus::new(
+            "127.0.0.1:6002",
+            "outlet-2",
+            "outlet-2",
+            None,
+        ));
+        model_state
+    }
+
+    #[test]
+    fn inlets_are_restored_after_their_outlets() {
+        let mut model_state = model_state();
+        let mut restorer = FakeRestorer::default();
+        crate::app::test_block_on(restore_portals(&mut restorer, &mut model_state));
+
+        assert_eq!(
+            restorer.restored,
+            vec!["outlet-1", "outlet-2", "inlet-1", "inlet-2"]
+        );
+        assert!(model_state.get_tcp_inlets().iter().all(|i| !i.pending));
+    }
+
+    #[test]
+    fn an_inlet_depending_on_a_deferred_outlet_is_pending() {
+        let mut model_state = model_state();
+        let mut restorer = FakeRestorer {
+            failing: vec!["outlet-1".to_string()],
+            ..Default::default()
+        };
+        crate::app::test_block_on(restore_portals(&mut restorer, &mut model_state));
+
+        assert_eq!(restorer.restored, vec!["outlet-2", "inlet-2"]);
+        let inlets = model_state.get_tcp_inlets();
+        assert!(inlets[0].pending);
+        assert!(!inlets[1].pending);
+    }
+}
@@ -1,4 +1,9 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+
 use miette::{IntoDiagnostic, WrapErr};
+use ockam::Address;
 use ockam_command::util::extract_address_value;
 use tauri::{AppHandle, Manager, Wry};
 use tracing::{debug, error, info};
@@ -7,19 +12,30 @@ use crate::app::AppState;
 use crate::error::Error;
 use crate::shared_service::SHARED_SERVICE_WINDOW_ID;
 
-/// Create a TCP outlet within the default node.
+/// Create a TCP outlet within the default node, unless an outlet already forwards to the service.
+/// A conflict error is returned if the service name or the address is already used by another outlet.
+/// The connections of the outlet are closed after `idle_timeout` seconds without traffic, if set.
+/// The outlet can be tagged, so that it can be found by its tags
 #[tauri::command]
 pub async fn tcp_outlet_create(
     app: AppHandle<Wry>,
     service: String,
     port: String,
+    idle_timeout: Option<u64>,
+    tags: Option<BTreeMap<String, String>>,
 ) -> Result<(), String> {
-    tcp_outlet_create_impl(app, service, port)
-        .await
-        .map_err(|e| {
-            error!("{:?}", e);
-            e.to_string()
-        })?;
+    tcp_outlet_create_impl(
+        app,
+        service,
+        port,
+        idle_timeout.map(Duration::from_secs),
+        tags.unwrap_or_default(),
+    )
+    .await
+    .map_err(|e| {
+        error!("{:?}", e);
+        e.to_string()
+    })?;
     Ok(())
 }
 
@@ -27,26 +43,48 @@ async fn tcp_outlet_create_impl(
     app: AppHandle<Wry>,
     service: String,
     port: String,
+    idle_timeout: Option<Duration>,
+    tags: BTreeMap<String, String>,
 ) -> crate::Result<()> {
-    debug!(%service, %port, "Creating an outlet");
+    debug!(%service, %port, ?idle_timeout, ?tags, "Creating an outlet");
     let app_state = app.state::<AppState>();
-    let tcp_addr = format!("127.0.0.1:{port}")
+    let tcp_addr: SocketAddr = format!("127.0.0.1:{port}")
         .parse()
         .into_diagnostic()
         .wrap_err("Invalid IP address")?;
     let worker_addr = extract_address_value(&service).wrap_err("Invalid service address")?;
-    let mut node_manager = app_state.node_manager.get().write().await;
-    match node_manager
-        .create_outlet(&app_state.context(), tcp_addr, worker_addr, None, true)
-        .await
-    {
-        Ok(status) => {
+    // sharing a service which is already shared doesn't create a duplicate outlet,
+    // but the service name and its target must designate the same outlet
+    match app_state.outlet_for_target(tcp_addr).await {
+        Some(status)
+            if Address::from_string(&status.worker_addr) == Address::from_string(&worker_addr) =>
+        {
+            info!(
+                alias = status.alias,
+                tcp_addr = status.tcp_addr,
+                "An outlet already exists for this service"
+            );
+        }
+        Some(status) => {
+            return Err(Error::OutletConflict(format!(
+                "the address {tcp_addr} is already shared as '{}'",
+                status.worker_addr
+            )));
+        }
+        None => {
+            if let Some(status) = app_state.outlet_for_worker(&worker_addr).await {
+                return Err(Error::OutletConflict(format!(
+                    "the service '{service}' is already shared for the address {}",
+                    status.tcp_addr
+                )));
+            }
+            let status = app_state
+                .create_outlet_with_tags(tcp_addr.to_string(), worker_addr, idle_timeout, tags)
+                .await?;
             info!(tcp_addr = status.tcp_addr, "Outlet created");
-            app_state.model_mut(|m| m.add_tcp_outlet(status)).await?;
-            app.get_window(SHARED_SERVICE_WINDOW_ID).map(|w| w.close());
-            app.trigger_global(crate::app::events::SYSTEM_TRAY_ON_UPDATE, None);
-            Ok(())
         }
-        Err(e) => Err(Error::Generic(e.to_string())),
     }
+    app.get_window(SHARED_SERVICE_WINDOW_ID).map(|w| w.close());
+    app.trigger_global(crate::app::events::SYSTEM_TRAY_ON_UPDATE, None);
+    Ok(())
 }
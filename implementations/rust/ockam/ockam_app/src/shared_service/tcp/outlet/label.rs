@@ -0,0 +1,34 @@
+use tauri::{AppHandle, Manager, Wry};
+use tracing::{debug, error, info};
+
+use crate::app::AppState;
+
+/// Set the label displayed instead of the alias of a TCP outlet of the default node.
+/// An empty label removes the label of the outlet.
+#[tauri::command]
+pub async fn tcp_outlet_set_label(
+    app: AppHandle<Wry>,
+    alias: String,
+    label: String,
+) -> Result<(), String> {
+    tcp_outlet_set_label_impl(app, alias, label)
+        .await
+        .map_err(|e| {
+            error!("{:?}", e);
+            e.to_string()
+        })?;
+    Ok(())
+}
+
+async fn tcp_outlet_set_label_impl(
+    app: AppHandle<Wry>,
+    alias: String,
+    label: String,
+) -> crate::Result<()> {
+    debug!(%alias, %label, "Setting the label of an outlet");
+    let app_state = app.state::<AppState>();
+    app_state.set_outlet_label(&alias, &label).await?;
+    info!(%alias, "Outlet label set");
+    app.trigger_global(crate::app::events::SYSTEM_TRAY_ON_UPDATE, None);
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Wry};
+use tracing::{debug, error};
+
+use ockam::compat::tokio;
+
+use crate::app::AppState;
+
+/// Number of latency samples kept for each outlet, the older samples are discarded
+pub(crate) const LATENCY_SAMPLES: usize = 100;
+
+/// Time between two probes of the outlets by the latency sampler
+pub(crate) const LATENCY_SAMPLING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Latency statistics of an outlet, computed over its most recent successful probes
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct LatencyStats {
+    pub alias: String,
+    /// Number of samples used to compute the percentiles
+    pub samples: usize,
+    /// Median time taken to connect to the outlet target, in milliseconds
+    pub p50_ms: Option<u128>,
+    pub p95_ms: Option<u128>,
+}
+
+/// The most recent latency samples of an outlet
+#[derive(Clone, Debug, Default)]
+pub(crate) struct LatencySamples {
+    samples: VecDeque<Duration>,
+}
+
+impl LatencySamples {
+    pub(crate) fn add(&mut self, latency: Duration) {
+        if self.samples.len() == LATENCY_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(latency);
+    }
+
+    /// Return the nearest-rank percentile of the samples, `None` if there are no samples
+    pub(crate) fn percentile(&self, percentile: u8) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort();
+        let rank = (usize::from(percentile) * sorted.len() + 99) / 100;
+        Some(sorted[rank.saturating_sub(1)])
+    }
+
+    pub(crate) fn stats(&self, alias: &str) -> LatencyStats {
+        LatencyStats {
+            alias: alias.to_string(),
+            samples: self.samples.len(),
+            p50_ms: self.percentile(50).map(|d| d.as_millis()),
+            p95_ms: self.percentile(95).map(|d| d.as_millis()),
+        }
+    }
+}
+
+/// Return the latency statistics of a TCP outlet.
+#[tauri::command]
+pub async fn tcp_outlet_latency(
+    app: AppHandle<Wry>,
+    alias: String,
+) -> Result<LatencyStats, String> {
+    let app_state = app.state::<AppState>();
+    app_state.outlet_latency(&alias).await.map_err(|e| {
+        error!("{:?}", e);
+        e.to_string()
+    })
+}
+
+/// Probe all the outlets periodically to collect their latency
+pub(crate) async fn sample_outlet_latencies(app: AppHandle<Wry>) {
+    loop {
+        tokio::time::sleep(LATENCY_SAMPLING_INTERVAL).await;
+        let app_state = app.state::<AppState>();
+        let aliases: Vec<String> = app_state
+            .tcp_outlet_list()
+            .await
+            .into_iter()
+            .map(|o| o.alias)
+            .collect();
+        app_state.retain_outlet_latencies(&aliases).await;
+        for alias in aliases {
+            // the latency of a reachable outlet is recorded by the probe
+            if let Err(e) = app_state.probe_outlet(&alias).await {
+                debug!(%alias, "cannot sample the latency of the outlet: {e:?}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_of_synthetic_samples() {
+        let mut samples = LatencySamples::default();
+        assert_eq!(samples.percentile(50), None);
+        assert_eq!(
+            samples.stats("db"),
+            LatencyStats {
+                alias: "db".to_string(),
+                samples: 0,
+                p50_ms: None,
+                p95_ms: None,
+            }
+        );
+
+        // 1ms to 100ms, added in reverse order
+        for ms in (1..=100).rev() {
+            samples.add(Duration::from_millis(ms));
+        }
+        let stats = samples.stats("db");
+        assert_eq!(stats.samples, 100);
+        assert_eq!(stats.p50_ms, Some(50));
+        assert_eq!(stats.p95_ms, Some(95));
+
+        samples = LatencySamples::default();
+        samples.add(Duration::from_millis(7));
+        assert_eq!(samples.percentile(50), Some(Duration::from_millis(7)));
+        assert_eq!(samples.percentile(95), Some(Duration::from_millis(7)));
+    }
+
+    #[test]
+    fn only_the_most_recent_samples_are_kept() {
+        let mut samples = LatencySamples::default();
+        for _ in 0..LATENCY_SAMPLES {
+            samples.add(Duration::from_millis(1000));
+        }
+        for _ in 0..LATENCY_SAMPLES {
+            samples.add(Duration::from_millis(10));
+        }
+        let stats = samples.stats("db");
+        assert_eq!(stats.samples, LATENCY_SAMPLES);
+        assert_eq!(stats.p95_ms, Some(10));
+    }
+}
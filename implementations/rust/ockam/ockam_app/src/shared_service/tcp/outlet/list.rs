@@ -0,0 +1,36 @@
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Wry};
+
+use ockam_api::nodes::models::portal::OutletStatus;
+
+use crate::app::AppState;
+
+/// A running outlet with the label displayed instead of its alias, if it has one
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct LabeledOutlet {
+    #[serde(flatten)]
+    pub status: OutletStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl LabeledOutlet {
+    /// Return the label of the outlet, or its worker address if it has no label
+    pub fn display_name(&self) -> String {
+        match &self.label {
+            Some(label) => label.clone(),
+            None => self
+                .status
+                .worker_address()
+                .map(|a| a.to_string())
+                .unwrap_or_else(|_| self.status.worker_addr.clone()),
+        }
+    }
+}
+
+/// List the TCP outlets of the default node, with their labels.
+#[tauri::command]
+pub async fn tcp_outlet_list(app: AppHandle<Wry>) -> Vec<LabeledOutlet> {
+    let app_state = app.state::<AppState>();
+    app_state.labeled_outlet_list().await
+}
@@ -1,4 +1,14 @@
 pub use create::tcp_outlet_create;
+pub use label::tcp_outlet_set_label;
+pub use latency::tcp_outlet_latency;
+pub use list::tcp_outlet_list;
+pub use probe::tcp_outlet_probe;
+pub use rename::tcp_outlet_rename;
 
 mod create;
+mod label;
+pub(crate) mod latency;
+pub(crate) mod list;
 pub(crate) mod model_state;
+pub(crate) mod probe;
+mod rename;
@@ -1,8 +1,5 @@
 use crate::app::ModelState;
-use ockam::Context;
 use ockam_api::nodes::models::portal::OutletStatus;
-use ockam_api::nodes::NodeManager;
-use std::sync::Arc;
 
 impl ModelState {
     pub fn add_tcp_outlet(&mut self, status: OutletStatus) {
@@ -12,28 +9,44 @@ impl ModelState {
     pub fn get_tcp_outlets(&self) -> &[OutletStatus] {
         &self.tcp_outlets
     }
-}
 
-pub(crate) async fn load_model_state(
-    context: Arc<Context>,
-    node_manager: &mut NodeManager,
-    model_state: &ModelState,
-) {
-    for tcp_outlet in model_state.get_tcp_outlets() {
-        node_manager
-            .create_outlet(
-                &context,
-                tcp_outlet.tcp_addr.clone(),
-                tcp_outlet.worker_addr.clone(),
-                None,
-                true,
-            )
-            .await
-            .unwrap_or_else(|_| {
-                panic!(
-                    "failed to create outlet with tcp addr {}",
-                    tcp_outlet.tcp_addr
-                )
-            });
+    /// Remove a persisted outlet with its label
+    pub fn remove_tcp_outlet(&mut self, alias: &str) {
+        self.tcp_outlets.retain(|o| o.alias != alias);
+        self.tcp_outlet_labels.remove(alias);
+        self.relayed_outlets.retain(|a| a != alias);
+    }
+
+    /// Rename a persisted outlet and update its label, the inlets which depend on it and
+    /// its relay
+    pub fn rename_tcp_outlet(&mut self, old_alias: &str, new_alias: &str) {
+        for outlet in self.tcp_outlets.iter_mut().filter(|o| o.alias == old_alias) {
+            outlet.alias = new_alias.to_string();
+        }
+        if let Some(label) = self.tcp_outlet_labels.remove(old_alias) {
+            self.tcp_outlet_labels.insert(new_alias.to_string(), label);
+        }
+        for inlet in self
+            .tcp_inlets
+            .iter_mut()
+            .filter(|i| i.outlet_alias.as_deref() == Some(old_alias))
+        {
+            inlet.outlet_alias = Some(new_alias.to_string());
+        }
+        for alias in self.relayed_outlets.iter_mut().filter(|a| *a == old_alias) {
+            *alias = new_alias.to_string();
+        }
+    }
+
+    /// Set the label displayed instead of the alias of an outlet, or remove it
+    pub fn set_tcp_outlet_label(&mut self, alias: &str, label: Option<String>) {
+        match label {
+            Some(label) => self.tcp_outlet_labels.insert(alias.to_string(), label),
+            None => self.tcp_outlet_labels.remove(alias),
+        };
+    }
+
+    pub fn get_tcp_outlet_label(&self, alias: &str) -> Option<&str> {
+        self.tcp_outlet_labels.get(alias).map(|l| l.as_str())
     }
 }
@@ -0,0 +1,141 @@
+use std::io;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use miette::miette;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Wry};
+use tracing::{debug, error};
+
+use crate::app::AppState;
+
+/// Maximum amount of time spent waiting for the outlet target to accept a connection
+pub(crate) const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Result of testing the connection to the target of an outlet
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProbeResult {
+    pub alias: String,
+    pub tcp_addr: String,
+    pub reachable: bool,
+    /// Time taken to establish the connection, in milliseconds
+    pub latency_ms: Option<u128>,
+    /// Reason why the target could not be reached
+    pub error: Option<String>,
+}
+
+/// Test the connection to the target of a TCP outlet.
+#[tauri::command]
+pub async fn tcp_outlet_probe(app: AppHandle<Wry>, alias: String) -> Result<ProbeResult, String> {
+    let app_state = app.state::<AppState>();
+    app_state.probe_outlet(&alias).await.map_err(|e| {
+        error!("{:?}", e);
+        e.to_string()
+    })
+}
+
+/// Open a short-lived TCP connection to `tcp_addr` and report if it succeeded.
+///
+/// `tcp_addr` is either a socket address or a hostname with a port, like `db.local:5432`.
+/// A hostname is resolved first, and each of its addresses is tried until one accepts
+/// the connection, all within `timeout`.
+///
+/// The connection is independent of the outlet worker, so the connections forwarded by the
+/// outlet are not affected.
+pub(crate) async fn probe(
+    alias: &str,
+    tcp_addr: &str,
+    timeout: Duration,
+) -> crate::Result<ProbeResult> {
+    debug!(%alias, %tcp_addr, "Probing outlet");
+    let target = tcp_addr.to_string();
+    let (elapsed, result) = tauri::async_runtime::spawn_blocking(move || {
+        let start = Instant::now();
+        let result = connect(&target, start, timeout);
+        (start.elapsed(), result)
+    })
+    .await
+    .map_err(|e| miette!(e))?;
+
+    let probe_result = match result {
+        Ok(_) => ProbeResult {
+            alias: alias.to_string(),
+            tcp_addr: tcp_addr.to_string(),
+            reachable: true,
+            latency_ms: Some(elapsed.as_millis()),
+            error: None,
+        },
+        Err(e) => ProbeResult {
+            alias: alias.to_string(),
+            tcp_addr: tcp_addr.to_string(),
+            reachable: false,
+            latency_ms: None,
+            error: Some(e.to_string()),
+        },
+    };
+    debug!(%alias, reachable = probe_result.reachable, "Outlet probed");
+    Ok(probe_result)
+}
+
+/// Connect to the first address of `tcp_addr` accepting a connection before `start + timeout`
+fn connect(tcp_addr: &str, start: Instant, timeout: Duration) -> io::Result<TcpStream> {
+    let mut last_error = None;
+    for socket_addr in tcp_addr.to_socket_addrs()? {
+        let remaining = match timeout.checked_sub(start.elapsed()) {
+            Some(remaining) if !remaining.is_zero() => remaining,
+            _ => break,
+        };
+        match TcpStream::connect_timeout(&socket_addr, remaining) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("no address of {tcp_addr} could be reached in time"),
+        )
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use crate::app::test_block_on as block_on;
+
+    use super::*;
+
+    #[test]
+    fn probe_reachable_and_unreachable_targets() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_addr = listener.local_addr().unwrap().to_string();
+
+        let result = block_on(probe("outlet", &tcp_addr, PROBE_TIMEOUT)).unwrap();
+        assert!(result.reachable);
+        assert!(result.latency_ms.is_some());
+        assert!(result.error.is_none());
+
+        // once the listener is dropped the target can't be reached anymore
+        drop(listener);
+        let result = block_on(probe("outlet", &tcp_addr, PROBE_TIMEOUT)).unwrap();
+        assert!(!result.reachable);
+        assert!(result.latency_ms.is_none());
+        assert!(result.error.is_some());
+    }
+
+    #[test]
+    fn probe_a_target_given_by_hostname() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_addr = format!("localhost:{}", listener.local_addr().unwrap().port());
+
+        let result = block_on(probe("outlet", &tcp_addr, PROBE_TIMEOUT)).unwrap();
+        assert!(result.reachable, "{result:?}");
+        assert_eq!(result.tcp_addr, tcp_addr);
+
+        // a hostname which can't be resolved is reported as unreachable
+        let result = block_on(probe("outlet", "unknown.invalid:5432", PROBE_TIMEOUT)).unwrap();
+        assert!(!result.reachable);
+        assert!(result.error.is_some());
+    }
+}
@@ -0,0 +1,33 @@
+use tauri::{AppHandle, Manager, Wry};
+use tracing::{debug, error, info};
+
+use crate::app::AppState;
+
+/// Rename a TCP outlet of the default node without interrupting its connections.
+#[tauri::command]
+pub async fn tcp_outlet_rename(
+    app: AppHandle<Wry>,
+    alias: String,
+    new_alias: String,
+) -> Result<(), String> {
+    tcp_outlet_rename_impl(app, alias, new_alias)
+        .await
+        .map_err(|e| {
+            error!("{:?}", e);
+            e.to_string()
+        })?;
+    Ok(())
+}
+
+async fn tcp_outlet_rename_impl(
+    app: AppHandle<Wry>,
+    alias: String,
+    new_alias: String,
+) -> crate::Result<()> {
+    debug!(%alias, %new_alias, "Renaming an outlet");
+    let app_state = app.state::<AppState>();
+    let status = app_state.rename_outlet(&alias, &new_alias).await?;
+    info!(alias = status.alias, "Outlet renamed");
+    app.trigger_global(crate::app::events::SYSTEM_TRAY_ON_UPDATE, None);
+    Ok(())
+}
@@ -11,7 +11,8 @@ pub(crate) async fn build_shared_services_section(
     app_state: &AppState,
     tray_menu: SystemTrayMenu,
 ) -> SystemTrayMenu {
-    if !app_state.is_enrolled().await {
+    // the local portals are still available in local-only mode
+    if !app_state.is_enrolled().await && !app_state.is_local_only() {
         return tray_menu;
     };
 
@@ -22,13 +23,12 @@ pub(crate) async fn build_shared_services_section(
             SHARED_SERVICE_CREATE_MENU_ID,
             "Create...",
         ));
-    for outlet in app_state.tcp_outlet_list().await {
-        let outlet_info = format!(
-            "{} to {}",
-            outlet.worker_address().unwrap(),
-            outlet.tcp_addr
-        );
-        let item = CustomMenuItem::new(outlet_info.clone(), outlet_info);
+    for outlet in app_state.labeled_outlet_list().await {
+        let worker_address = outlet.status.worker_address().unwrap().to_string();
+        let id = format!("{} to {}", worker_address, outlet.status.tcp_addr);
+        // the menu item id doesn't depend on the label so that it is stable when it is renamed
+        let name = format!("{} to {}", outlet.display_name(), outlet.status.tcp_addr);
+        let item = CustomMenuItem::new(id, name);
         tm = tm.add_item(item);
     }
     tm
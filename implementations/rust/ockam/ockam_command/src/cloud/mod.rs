@@ -0,0 +1,34 @@
+use clap::{Args, Subcommand};
+
+pub use ping::PingCommand;
+
+use crate::{docs, CommandGlobalOpts};
+
+mod ping;
+
+const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
+
+/// Check the connection to Ockam Orchestrator
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    long_about = docs::about(LONG_ABOUT),
+)]
+pub struct CloudCommand {
+    #[command(subcommand)]
+    subcommand: CloudSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum CloudSubcommand {
+    Ping(PingCommand),
+}
+
+impl CloudCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            CloudSubcommand::Ping(c) => c.run(options),
+        }
+    }
+}
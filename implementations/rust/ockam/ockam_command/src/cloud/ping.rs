@@ -0,0 +1,175 @@
+use std::time::{Duration, Instant};
+
+use clap::Args;
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+use serde::Serialize;
+
+use ockam::{Context, Node, TcpConnectionOptions, TcpTransport};
+use ockam_api::cli_state::StateDirTrait;
+use ockam_api::nodes::NodeManager;
+use ockam_core::route;
+use ockam_identity::{SecureChannelOptions, TrustIdentifierPolicy};
+use ockam_multiaddr::MultiAddr;
+
+use crate::util::api::CloudOpts;
+use crate::util::node_rpc;
+use crate::{docs, fmt_err, fmt_ok, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/ping/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/ping/after_long_help.txt");
+
+/// Check that Ockam Orchestrator can be reached
+#[derive(Clone, Debug, Args)]
+#[command(
+    long_about = docs::about(LONG_ABOUT),
+    after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct PingCommand {
+    /// Address of the controller. Defaults to the Ockam Orchestrator controller
+    #[arg(long, value_name = "ROUTE")]
+    address: Option<MultiAddr>,
+
+    /// Also perform a secure channel handshake with the controller, using the default identity
+    #[arg(long)]
+    secure_channel: bool,
+
+    /// Override default timeout (in seconds)
+    #[arg(long, default_value = "10")]
+    timeout: u64,
+}
+
+impl PingCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(rpc, (options, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, PingCommand)) -> miette::Result<()> {
+    run_impl(ctx, opts, cmd).await
+}
+
+async fn run_impl(ctx: Context, opts: CommandGlobalOpts, cmd: PingCommand) -> miette::Result<()> {
+    let address = cmd.address.unwrap_or_else(CloudOpts::route);
+    let tcp_address = address.to_socket_addr().into_diagnostic()?;
+    let timeout = Duration::from_secs(cmd.timeout);
+
+    let mut result = PingResult {
+        address: address.to_string(),
+        tcp_address: tcp_address.clone(),
+        reachable: false,
+        tcp_latency_ms: None,
+        secure_channel_latency_ms: None,
+        error: None,
+    };
+    match tcp_ping(&tcp_address, timeout).await {
+        Ok(latency) => result.tcp_latency_ms = Some(latency.as_millis()),
+        Err(e) => result.error = Some(e.to_string()),
+    }
+    if cmd.secure_channel && result.error.is_none() {
+        match secure_channel_ping(ctx, &opts, &tcp_address, timeout).await {
+            Ok(latency) => result.secure_channel_latency_ms = Some(latency.as_millis()),
+            Err(e) => result.error = Some(e.to_string()),
+        }
+    }
+    result.reachable = result.error.is_none();
+
+    opts.terminal
+        .stdout()
+        .plain(result.plain())
+        .machine(result.reachable.to_string())
+        .json(serde_json::to_string_pretty(&result).into_diagnostic()?)
+        .write_line()?;
+    if !result.reachable {
+        // the command exits with the exit code of this error, once the node is stopped
+        return Err(crate::Error::Unavailable {
+            resource: "controller".to_string(),
+            resource_name: address.to_string(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Open a TCP connection to the controller and return the time it took
+async fn tcp_ping(tcp_address: &str, timeout: Duration) -> miette::Result<Duration> {
+    let start = Instant::now();
+    tokio::time::timeout(timeout, tokio::net::TcpStream::connect(tcp_address))
+        .await
+        .map_err(|_| miette!("Timed out after {}s", timeout.as_secs()))?
+        .into_diagnostic()?;
+    Ok(start.elapsed())
+}
+
+/// Establish a secure channel with the controller and return the time it took
+async fn secure_channel_ping(
+    ctx: Context,
+    opts: &CommandGlobalOpts,
+    tcp_address: &str,
+    timeout: Duration,
+) -> miette::Result<Duration> {
+    let controller_identifier = NodeManager::load_controller_identifier().into_diagnostic()?;
+    let identifier = opts.state.identities.default()?.identifier();
+    let node = {
+        let identities_vault = opts.state.vaults.default()?.get().await?;
+        let identities_repository = opts.state.identities.identities_repository().await?;
+        Node::builder()
+            .with_identities_vault(identities_vault)
+            .with_identities_repository(identities_repository)
+            .build(ctx)
+            .await
+            .into_diagnostic()?
+    };
+
+    let start = Instant::now();
+    let tcp = TcpTransport::create(node.context())
+        .await
+        .into_diagnostic()?;
+    let connection = tcp
+        .connect(tcp_address, TcpConnectionOptions::new())
+        .await
+        .into_diagnostic()?;
+    let secure_channel_options = SecureChannelOptions::new()
+        .with_trust_policy(TrustIdentifierPolicy::new(controller_identifier))
+        .with_timeout(timeout);
+    node.create_secure_channel(
+        &identifier,
+        route![connection, "api"],
+        secure_channel_options,
+    )
+    .await
+    .into_diagnostic()?;
+    Ok(start.elapsed())
+}
+
+#[derive(Serialize)]
+struct PingResult {
+    address: String,
+    tcp_address: String,
+    reachable: bool,
+    /// Time taken to open a TCP connection, in milliseconds
+    tcp_latency_ms: Option<u128>,
+    /// Time taken to establish a secure channel, in milliseconds
+    secure_channel_latency_ms: Option<u128>,
+    error: Option<String>,
+}
+
+impl PingResult {
+    fn plain(&self) -> String {
+        let address = &self.address;
+        if let Some(error) = &self.error {
+            return fmt_err!("Unable to reach {address}: {error}");
+        }
+        let mut plain = String::new();
+        if let Some(latency) = self.tcp_latency_ms {
+            plain.push_str(&fmt_ok!(
+                "TCP connection to {address} opened in {latency}ms"
+            ));
+        }
+        if let Some(latency) = self.secure_channel_latency_ms {
+            plain.push('\n');
+            plain.push_str(&fmt_ok!("Secure channel established in {latency}ms"));
+        }
+        plain
+    }
+}
@@ -1,12 +1,16 @@
 mod get;
 mod get_default_node;
 mod list;
+mod schema;
 mod set_default_node;
+mod validate;
 
 use get::GetCommand;
 use get_default_node::GetDefaultNodeCommand;
 use list::ListCommand;
+use schema::SchemaCommand;
 use set_default_node::SetDefaultNodeCommand;
+use validate::ValidateCommand;
 
 use crate::docs;
 use crate::CommandGlobalOpts;
@@ -26,7 +30,9 @@ pub enum ConfigurationSubcommand {
     Get(GetCommand),
     GetDefaultNode(GetDefaultNodeCommand),
     List(ListCommand),
+    Schema(SchemaCommand),
     SetDefaultNode(SetDefaultNodeCommand),
+    Validate(ValidateCommand),
 }
 
 impl ConfigurationCommand {
@@ -35,7 +41,9 @@ impl ConfigurationCommand {
             ConfigurationSubcommand::Get(c) => c.run(options),
             ConfigurationSubcommand::GetDefaultNode(c) => c.run(options),
             ConfigurationSubcommand::List(c) => c.run(options),
+            ConfigurationSubcommand::Schema(c) => c.run(options),
             ConfigurationSubcommand::SetDefaultNode(c) => c.run(options),
+            ConfigurationSubcommand::Validate(c) => c.run(options),
         }
     }
 }
@@ -0,0 +1,29 @@
+use crate::util::local_cmd;
+use crate::CommandGlobalOpts;
+use clap::Args;
+use miette::IntoDiagnostic;
+use ockam_api::json_schema::schema_document;
+use ockam_api::nodes::service::startup_config::NodeStartupConfig;
+
+/// Print the JSON Schema of the node startup configuration file, `startup.yaml`,
+/// so that editors can validate and autocomplete it
+#[derive(Clone, Debug, Args)]
+pub struct SchemaCommand {}
+
+impl SchemaCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        local_cmd(run_impl(options, self));
+    }
+}
+
+fn run_impl(opts: CommandGlobalOpts, _cmd: SchemaCommand) -> miette::Result<()> {
+    let schema = schema_document::<NodeStartupConfig>("Ockam node startup configuration");
+    let json = serde_json::to_string_pretty(&schema).into_diagnostic()?;
+    opts.terminal
+        .stdout()
+        .plain(&json)
+        .machine(&json)
+        .json(&json)
+        .write_line()?;
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use crate::util::{exitcode, local_cmd};
+use crate::{fmt_err, fmt_ok, CommandGlobalOpts};
+use clap::Args;
+use miette::{miette, IntoDiagnostic};
+use ockam_api::nodes::service::startup_config::NodeStartupConfig;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Check a node startup configuration file without starting a node.
+///
+/// All the errors are printed: syntax errors, invalid policy expressions, duplicated aliases,
+/// and outlets forwarding their connections to an inlet of the node
+#[derive(Clone, Debug, Args)]
+pub struct ValidateCommand {
+    /// Path of the configuration file
+    pub file: PathBuf,
+}
+
+impl ValidateCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        local_cmd(run_impl(options, self));
+    }
+}
+
+#[derive(Serialize)]
+struct Validation {
+    valid: bool,
+    errors: Vec<String>,
+}
+
+fn run_impl(opts: CommandGlobalOpts, cmd: ValidateCommand) -> miette::Result<()> {
+    let contents = std::fs::read_to_string(&cmd.file)
+        .map_err(|e| miette!("cannot read {}: {e}", cmd.file.display()))?;
+    let errors = NodeStartupConfig::validate(&contents);
+    let file = cmd.file.display();
+    let plain = if errors.is_empty() {
+        fmt_ok!("The configuration {file} is valid")
+    } else {
+        errors
+            .iter()
+            .map(|e| fmt_err!("{e}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let validation = Validation {
+        valid: errors.is_empty(),
+        errors,
+    };
+    opts.terminal
+        .stdout()
+        .plain(plain)
+        .machine(validation.valid.to_string())
+        .json(serde_json::to_string_pretty(&validation).into_diagnostic()?)
+        .write_line()?;
+    if !validation.valid {
+        std::process::exit(exitcode::DATAERR);
+    }
+    Ok(())
+}
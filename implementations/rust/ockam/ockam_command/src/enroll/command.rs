@@ -1,6 +1,8 @@
+use std::path::PathBuf;
+
 use clap::Args;
 use colorful::Colorful;
-use miette::{miette, WrapErr};
+use miette::{miette, IntoDiagnostic, WrapErr};
 use tokio::sync::Mutex;
 use tokio::try_join;
 use tracing::info;
@@ -11,19 +13,24 @@ use ockam_api::cli_state::SpaceConfig;
 use ockam_api::cloud::enroll::auth0::*;
 use ockam_api::cloud::project::Project;
 use ockam_api::cloud::space::Space;
+use ockam_api::identity::EnrollmentTicket;
 use ockam_core::api::Response;
 use ockam_core::api::Status;
 use ockam_identity::IdentityIdentifier;
 use ockam_multiaddr::MultiAddr;
 
 use crate::enroll::oidc_service::OidcService;
+use crate::enroll::{enroll_with_ticket, read_enrollment_ticket, validate_enrollment_ticket};
 use crate::identity::initialize_identity_if_default;
-use crate::node::util::{delete_embedded_node, start_embedded_node};
+use crate::node::util::{
+    delete_embedded_node, start_embedded_node, start_embedded_node_with_node_manager,
+};
 use crate::operation::util::check_for_completion;
 use crate::project::util::check_project_readiness;
 use crate::terminal::OckamColor;
 use crate::util::api::CloudOpts;
-use crate::util::{api, node_rpc, RpcBuilder};
+use crate::util::output::Output;
+use crate::util::{api, local_cmd, node_rpc, RpcBuilder};
 use crate::{display_parse_logs, docs, fmt_log, fmt_ok, fmt_para, CommandGlobalOpts, Result};
 
 const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
@@ -38,10 +45,25 @@ after_long_help = docs::after_help(AFTER_LONG_HELP)
 pub struct EnrollCommand {
     #[arg(global = true, value_name = "IDENTITY_NAME", long)]
     pub identity: Option<String>,
+
+    /// Enroll with an enrollment ticket file instead of authenticating with Ockam Orchestrator
+    #[arg(long, value_name = "ENROLLMENT_TICKET_PATH")]
+    pub ticket: Option<PathBuf>,
 }
 
 impl EnrollCommand {
     pub fn run(self, opts: CommandGlobalOpts) {
+        if let Some(path) = &self.ticket {
+            // the ticket is checked before any state is written
+            let ticket = match read_enrollment_ticket(path)
+                .and_then(|ticket| validate_enrollment_ticket(&ticket).map(|_| ticket))
+            {
+                Ok(ticket) => ticket,
+                Err(e) => return local_cmd(Err(e.into())),
+            };
+            initialize_identity_if_default(&opts, &self.identity);
+            return node_rpc(rpc_ticket, (opts, self.identity, ticket));
+        }
         initialize_identity_if_default(&opts, &self.identity);
         node_rpc(rpc, (opts, self));
     }
@@ -51,6 +73,35 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, EnrollCommand)) -> m
     run_impl(&ctx, opts, cmd).await
 }
 
+async fn rpc_ticket(
+    ctx: Context,
+    (opts, identity, ticket): (CommandGlobalOpts, Option<String>, EnrollmentTicket),
+) -> miette::Result<()> {
+    let (node_name, node_manager) =
+        start_embedded_node_with_node_manager(&ctx, &opts, identity).await?;
+    let result = enroll_with_ticket(&ctx, &opts, &node_manager, &node_name, &ticket).await;
+    delete_embedded_node(&opts, &node_name).await;
+    let (project, identifier) =
+        result.wrap_err("Failed to enroll your local identity with the enrollment ticket")?;
+
+    opts.terminal.write_line(&fmt_ok!(
+        "Enrolled {} as a member of the project {}.",
+        identifier
+            .to_string()
+            .color(OckamColor::PrimaryResource.color()),
+        project
+            .name
+            .to_string()
+            .color(OckamColor::PrimaryResource.color())
+    ))?;
+    opts.terminal
+        .stdout()
+        .plain(project.output()?)
+        .json(serde_json::to_string_pretty(&project).into_diagnostic()?)
+        .write_line()?;
+    Ok(())
+}
+
 async fn run_impl(
     ctx: &Context,
     opts: CommandGlobalOpts,
@@ -0,0 +1,150 @@
+use std::path::Path;
+
+use miette::{miette, IntoDiagnostic, WrapErr};
+
+use ockam::identity::{IdentityIdentifier, Timestamp};
+use ockam::Context;
+use ockam_api::cli_state::traits::StateDirTrait;
+use ockam_api::cloud::project::Project;
+use ockam_api::config::cli::TrustContextConfig;
+use ockam_api::identity::EnrollmentTicket;
+use ockam_api::nodes::NodeManagerWorker;
+
+use crate::enroll::update_enrolled_identity;
+use crate::project::{parse_enroll_ticket, ProjectInfo};
+use crate::{CommandGlobalOpts, Result};
+
+/// Read an enrollment ticket file, containing a hex-encoded ticket
+pub fn read_enrollment_ticket(path: &Path) -> Result<EnrollmentTicket> {
+    let contents = std::fs::read_to_string(path)
+        .into_diagnostic()
+        .wrap_err(format!(
+            "Cannot read the enrollment ticket file {}",
+            path.display()
+        ))?;
+    Ok(parse_enroll_ticket(contents.trim())
+        .wrap_err(format!("Invalid enrollment ticket file {}", path.display()))?)
+}
+
+/// Check that an enrollment ticket can be used to enroll now, without writing any state.
+///
+/// Return the project of the ticket and its trust context
+pub fn validate_enrollment_ticket(
+    ticket: &EnrollmentTicket,
+) -> Result<(Project, TrustContextConfig)> {
+    let now = Timestamp::now()
+        .ok_or_else(|| miette!("Cannot get the current time"))?
+        .unix_time();
+    let project_lookup = ticket
+        .validate(now)
+        .map_err(|e| miette!("Cannot enroll: {e}"))?;
+    let project_info: ProjectInfo = project_lookup.clone().try_into()?;
+    let project: Project = (&project_info).into();
+    let trust_context: TrustContextConfig = project.clone().try_into()?;
+    Ok((project, trust_context))
+}
+
+/// Enroll the identity of a node with an enrollment ticket.
+///
+/// This function:
+///  - checks that the ticket is valid and not expired, before writing any state
+///  - presents the ticket one-time code to the authority of the ticket project
+///  - stores the ticket project and trust context as the default ones
+///  - configures the trust context of the node manager
+///  - marks the identity of the node as enrolled
+///
+/// It is used by `ockam enroll --ticket` and by the desktop application.
+pub async fn enroll_with_ticket(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    node_manager: &NodeManagerWorker,
+    node_name: &str,
+    ticket: &EnrollmentTicket,
+) -> Result<(Project, IdentityIdentifier)> {
+    let (project, trust_context) = validate_enrollment_ticket(ticket)?;
+
+    node_manager
+        .present_enrollment_ticket(ctx, ticket)
+        .await
+        .map_err(|e| miette!("The enrollment ticket was rejected by the project authority: {e}"))?;
+
+    let identifier =
+        record_ticket_enrollment(opts, node_manager, node_name, &project, &trust_context).await?;
+    Ok((project, identifier))
+}
+
+/// Store the project and the trust context of an enrollment ticket accepted by the project
+/// authority, configure the trust context of the node manager and mark the identity of the
+/// node as enrolled.
+///
+/// This is the last step of [`enroll_with_ticket`], for the callers presenting the ticket
+/// themselves
+pub async fn record_ticket_enrollment(
+    opts: &CommandGlobalOpts,
+    node_manager: &NodeManagerWorker,
+    node_name: &str,
+    project: &Project,
+    trust_context: &TrustContextConfig,
+) -> Result<IdentityIdentifier> {
+    opts.state
+        .projects
+        .overwrite(&project.name, project.clone())?;
+    opts.state.projects.set_default(&project.name)?;
+    opts.state
+        .trust_contexts
+        .overwrite(&project.name, trust_context.clone())?;
+    opts.state.trust_contexts.set_default(&project.name)?;
+    node_manager
+        .get()
+        .write()
+        .await
+        .configure_trust_context(trust_context)
+        .await?;
+
+    update_enrolled_identity(opts, node_name).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join(name)
+    }
+
+    #[test]
+    fn a_ticket_file_is_validated_before_enrolling() {
+        let ticket = read_enrollment_ticket(&fixture("user.enrollment.ticket")).unwrap();
+        let (project, trust_context) = validate_enrollment_ticket(&ticket).unwrap();
+        assert_eq!(project.name, "default");
+        assert_eq!(project.id, "2dac6564-ee78-4bab-b982-d420b8dbad9f");
+        assert_eq!(trust_context.id(), project.id);
+    }
+
+    #[test]
+    fn an_expired_ticket_is_rejected() {
+        let ticket = read_enrollment_ticket(&fixture("expired.enrollment.ticket")).unwrap();
+        assert_eq!(ticket.expires_at(), Some(1_600_000_000));
+        let error = validate_enrollment_ticket(&ticket).unwrap_err();
+        assert!(error.to_string().contains("has expired"), "{error}");
+    }
+
+    #[test]
+    fn an_invalid_ticket_file_is_rejected() {
+        let error = read_enrollment_ticket(&fixture("missing.enrollment.ticket")).unwrap_err();
+        assert!(error.to_string().contains("Cannot read"), "{error}");
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), "not a ticket").unwrap();
+        let error = read_enrollment_ticket(file.path()).unwrap_err();
+        assert!(
+            error.to_string().contains("Invalid enrollment ticket"),
+            "{error}"
+        );
+    }
+}
@@ -1,9 +1,11 @@
 pub use command::*;
+pub use enroll_ticket::*;
 pub use ockam_oidc_provider::*;
 pub use oidc_service::*;
 pub use okta_oidc_provider::*;
 
 mod command;
+mod enroll_ticket;
 mod ockam_oidc_provider;
 mod oidc_provider;
 mod oidc_service;
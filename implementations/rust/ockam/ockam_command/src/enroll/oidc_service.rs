@@ -9,10 +9,10 @@ use reqwest::{StatusCode, Url};
 use serde::de::DeserializeOwned;
 use tiny_http::{Header, Response, Server};
 use tokio::time::{sleep, Duration};
-use tokio_retry::{strategy::ExponentialBackoff, Retry};
 use tracing::{debug, error, info};
 
 use ockam::compat::fmt::Debug;
+use ockam_api::backoff::{retry, BackoffPolicy};
 use ockam_api::cloud::enroll::auth0::*;
 use ockam_core::compat::rand::{thread_rng, RngCore};
 use ockam_node::callback::{new_callback, CallbackSender};
@@ -127,8 +127,8 @@ impl OidcService {
                 .get("https://account.ockam.io/userinfo")
                 .header("Authorization", format!("Bearer {}", access_token.clone()))
         };
-        let retry_strategy = ExponentialBackoff::from_millis(10).take(3);
-        let res = Retry::spawn(retry_strategy, move || req().send())
+        let retry_policy = BackoffPolicy::new(Duration::from_millis(10), 4).with_multiplier(10.0);
+        let res = retry(retry_policy, move || req().send())
             .await
             .into_diagnostic()?;
         res.json().await.map_err(|e| miette!(e).into())
@@ -258,8 +258,8 @@ impl OidcService {
                 .header("content-type", "application/x-www-form-urlencoded")
                 .form(&parameters)
         };
-        let retry_strategy = ExponentialBackoff::from_millis(10).take(3);
-        let res = Retry::spawn(retry_strategy, move || req().send())
+        let retry_policy = BackoffPolicy::new(Duration::from_millis(10), 4).with_multiplier(10.0);
+        let res = retry(retry_policy, move || req().send())
             .await
             .into_diagnostic()?;
 
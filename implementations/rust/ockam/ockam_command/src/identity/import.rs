@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic, WrapErr};
+use ockam::Context;
+use ockam_api::cli_state::traits::StateDirTrait;
+use ockam_identity::{IdentityChangeConstants, IdentityIdentifier, KeyAttributes};
+use ockam_vault::{decode_private_key, PersistentSecretsStore};
+use rand::prelude::random;
+
+use crate::terminal::OckamColor;
+use crate::util::node_rpc;
+use crate::{docs, fmt_log, fmt_ok, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/import/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/import/after_long_help.txt");
+
+/// Create an identity from an existing private key
+#[derive(Clone, Debug, Args)]
+#[command(
+long_about = docs::about(LONG_ABOUT),
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct ImportCommand {
+    /// Path of the private key file, an Ed25519 or NIST P-256 PKCS#8 key encoded as PEM or DER
+    #[arg(long, value_name = "FILE")]
+    key: PathBuf,
+
+    /// Name of the identity
+    #[arg(long, hide_default_value = true, default_value_t = hex::encode(& random::< [u8; 4] > ()))]
+    name: String,
+
+    /// Vault name to store the identity key
+    #[arg(long, value_name = "VAULT_NAME")]
+    vault: Option<String>,
+}
+
+impl ImportCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(Self::run_impl, (options, self))
+    }
+
+    async fn run_impl(
+        _ctx: Context,
+        (opts, cmd): (CommandGlobalOpts, ImportCommand),
+    ) -> miette::Result<()> {
+        let identifier = cmd.import_identity(&opts).await?;
+        opts.terminal
+            .stdout()
+            .plain(
+                fmt_ok!(
+                    "Identity {} \n",
+                    identifier
+                        .to_string()
+                        .color(OckamColor::PrimaryResource.color())
+                ) + &fmt_log!(
+                    "imported successfully as {}",
+                    &cmd.name
+                        .to_string()
+                        .color(OckamColor::PrimaryResource.color())
+                ),
+            )
+            .machine(identifier.clone())
+            .json(serde_json::json!({ "identity": { "identifier": &identifier } }))
+            .write_line()?;
+        Ok(())
+    }
+
+    /// Import the private key in the vault and create an identity with it.
+    /// The key is checked before any state is written
+    async fn import_identity(
+        &self,
+        opts: &CommandGlobalOpts,
+    ) -> miette::Result<IdentityIdentifier> {
+        let data = std::fs::read(&self.key)
+            .into_diagnostic()
+            .wrap_err(format!("Cannot read the key file {}", self.key.display()))?;
+        let (secret, attributes) = decode_private_key(&data)
+            .map_err(|e| miette!("Cannot import the key {}: {e}", self.key.display()))?;
+        if opts.state.identities.exists(&self.name) {
+            return Err(miette!("An identity named {} already exists", self.name));
+        }
+
+        let vault_state = opts.state.create_vault_state(self.vault.as_deref()).await?;
+        let vault = vault_state.get().await?;
+        let key_id = vault
+            .import_persistent_secret(secret, attributes)
+            .await
+            .into_diagnostic()?;
+        let identity = opts
+            .state
+            .get_identities(vault)
+            .await?
+            .identities_creation()
+            .create_identity_with_existing_key(
+                &key_id,
+                KeyAttributes::new(IdentityChangeConstants::ROOT_LABEL.to_string(), attributes),
+            )
+            .await
+            .into_diagnostic()?;
+        opts.state
+            .create_identity_state(&identity.identifier(), Some(&self.name))
+            .await?;
+        Ok(identity.identifier())
+    }
+}
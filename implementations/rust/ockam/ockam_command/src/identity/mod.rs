@@ -1,16 +1,22 @@
 mod create;
 mod default;
 mod delete;
+mod import;
 mod list;
+mod revoke;
+mod set_attribute;
 mod show;
 
 use colorful::Colorful;
 pub use create::CreateCommand;
 pub(crate) use delete::DeleteCommand;
+pub(crate) use import::ImportCommand;
 pub(crate) use list::ListCommand;
 pub(crate) use show::ShowCommand;
 
 use crate::identity::default::DefaultCommand;
+use crate::identity::revoke::RevokeCommand;
+use crate::identity::set_attribute::SetAttributeCommand;
 use crate::terminal::OckamColor;
 use crate::{docs, fmt_log, fmt_ok, CommandGlobalOpts, PARSER_LOGS};
 use clap::{Args, Subcommand};
@@ -34,20 +40,26 @@ pub struct IdentityCommand {
 #[derive(Clone, Debug, Subcommand)]
 pub enum IdentitySubcommand {
     Create(CreateCommand),
+    Import(ImportCommand),
     Show(ShowCommand),
     List(ListCommand),
     Default(DefaultCommand),
     Delete(DeleteCommand),
+    SetAttribute(SetAttributeCommand),
+    Revoke(RevokeCommand),
 }
 
 impl IdentityCommand {
     pub fn run(self, options: CommandGlobalOpts) {
         match self.subcommand {
             IdentitySubcommand::Create(c) => c.run(options),
+            IdentitySubcommand::Import(c) => c.run(options),
             IdentitySubcommand::Show(c) => c.run(options),
             IdentitySubcommand::List(c) => c.run(options),
             IdentitySubcommand::Delete(c) => c.run(options),
             IdentitySubcommand::Default(c) => c.run(options),
+            IdentitySubcommand::SetAttribute(c) => c.run(options),
+            IdentitySubcommand::Revoke(c) => c.run(options),
         }
     }
 }
@@ -0,0 +1,85 @@
+use crate::node::get_node_name;
+use crate::util::{node_rpc, parse_node_name, Rpc};
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+use clap::Args;
+use colorful::Colorful;
+use ockam::identity::IdentityIdentifier;
+use ockam::Context;
+use ockam_core::api::Request;
+
+const LONG_ABOUT: &str = include_str!("./static/revoke/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/revoke/after_long_help.txt");
+
+/// Revoke an identity on a node
+#[derive(Clone, Debug, Args)]
+#[command(
+arg_required_else_help = true,
+long_about = docs::about(LONG_ABOUT),
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct RevokeCommand {
+    /// Identifier of the revoked identity
+    identifier: IdentityIdentifier,
+
+    /// Node revoking the identity. The default node is used if it is not specified
+    #[arg(long, display_order = 900, id = "NODE_NAME")]
+    at: Option<String>,
+
+    /// Cancel a previous revocation of the identity
+    #[arg(long)]
+    cancel: bool,
+}
+
+impl RevokeCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(rpc, (options, self));
+    }
+}
+
+async fn rpc(
+    mut ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, RevokeCommand),
+) -> miette::Result<()> {
+    run_impl(&mut ctx, opts, cmd).await
+}
+
+async fn run_impl(
+    ctx: &mut Context,
+    opts: CommandGlobalOpts,
+    cmd: RevokeCommand,
+) -> miette::Result<()> {
+    let at = get_node_name(&opts.state, &cmd.at);
+    let node_name = parse_node_name(&at)?;
+    let path = format!("/node/revocations/{}", cmd.identifier);
+    let req = if cmd.cancel {
+        Request::delete(&path)
+    } else {
+        Request::put(&path)
+    };
+    let mut rpc = Rpc::background(ctx, &opts, &node_name)?;
+    rpc.request(req).await?;
+    rpc.is_ok()?;
+
+    let message = if cmd.cancel {
+        format!(
+            "The identity {} is not revoked anymore by the node {}",
+            cmd.identifier, node_name
+        )
+    } else {
+        format!(
+            "The identity {} is revoked by the node {}",
+            cmd.identifier, node_name
+        )
+    };
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!("{}", message))
+        .machine(cmd.identifier.to_string())
+        .json(serde_json::json!({
+            "identifier": cmd.identifier.to_string(),
+            "at": &node_name,
+            "revoked": !cmd.cancel,
+        }))
+        .write_line()?;
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use crate::util::local_cmd;
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+use clap::Args;
+use colorful::Colorful;
+use std::time::{Duration, SystemTime};
+
+const LONG_ABOUT: &str = include_str!("./static/set_attribute/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/set_attribute/after_long_help.txt");
+
+/// Set an attribute of an identity
+#[derive(Clone, Debug, Args)]
+#[command(
+arg_required_else_help = true,
+long_about = docs::about(LONG_ABOUT),
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct SetAttributeCommand {
+    /// Name of the attribute
+    name: String,
+
+    /// Value of the attribute
+    value: String,
+
+    /// Name of the identity. The default identity is used if it is not specified
+    #[arg(long)]
+    identity: Option<String>,
+
+    /// Number of seconds after which the attribute expires. The attribute doesn't expire if it is not specified
+    #[arg(long, value_name = "SECONDS")]
+    expires_in: Option<u64>,
+}
+
+impl SetAttributeCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        local_cmd(run_impl(options, self));
+    }
+}
+
+fn run_impl(opts: CommandGlobalOpts, cmd: SetAttributeCommand) -> miette::Result<()> {
+    let mut idt = opts
+        .state
+        .identities
+        .get_or_default(cmd.identity.as_deref())?;
+    let expires_at = cmd
+        .expires_in
+        .map(|secs| SystemTime::now() + Duration::from_secs(secs));
+    idt.set_attribute_with_expiration(&cmd.name, &cmd.value, expires_at)?;
+    let expiration = match cmd.expires_in {
+        Some(secs) => format!(", for {secs} seconds"),
+        None => String::new(),
+    };
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "The attribute '{}' of the identity '{}' is now '{}'{}",
+            &cmd.name,
+            idt.name(),
+            &cmd.value,
+            expiration
+        ))
+        .machine(&cmd.value)
+        .json(serde_json::json!({
+            "identity": idt.name(),
+            "name": &cmd.name,
+            "value": &cmd.value,
+            "expires_in": cmd.expires_in,
+        }))
+        .write_line()?;
+    Ok(())
+}
@@ -1,14 +1,17 @@
 use crate::identity::{get_identity_name, initialize_identity_if_default};
 use crate::util::output::Output;
-use crate::util::{node_rpc, println_output};
+use crate::util::{node_rpc, print_result};
 use crate::{docs, CommandGlobalOpts, EncodeFormat, Result};
 use clap::Args;
 use core::fmt::Write;
 use miette::IntoDiagnostic;
+use ockam::identity::identity::identity_change::IdentityChange;
 use ockam::identity::identity::IdentityChangeHistory;
+use ockam::identity::IdentityIdentifier;
 use ockam_api::cli_state::traits::{StateDirTrait, StateItemTrait};
-use ockam_api::nodes::models::identity::{LongIdentityResponse, ShortIdentityResponse};
+use ockam_api::nodes::models::identity::ShortIdentityResponse;
 use ockam_node::Context;
+use serde::Serialize;
 
 const LONG_ABOUT: &str = include_str!("./static/show/long_about.txt");
 const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
@@ -25,6 +28,7 @@ pub struct ShowCommand {
     #[arg()]
     name: Option<String>,
 
+    /// Show the public keys and the full change history of the identity
     #[arg(short, long)]
     full: bool,
 
@@ -58,29 +62,96 @@ impl ShowCommand {
                 .await?
                 .get_identity(&identifier)
                 .await
-                .into_diagnostic()?
-                .export()
                 .into_diagnostic()?;
 
             if Some(EncodeFormat::Hex) == cmd.encoding {
-                println_output(identity, &opts.global_args.output_format)?;
+                let identity = identity.export().into_diagnostic()?;
+                print_result(identity, &opts.output_format())?;
             } else {
-                let output = LongIdentityResponse::new(identity);
-                println_output(output, &opts.global_args.output_format)?;
+                let output = FullIdentityOutput::new(&identifier, &identity.change_history());
+                print_result(output, &opts.output_format())?;
             }
         } else {
             let output = ShortIdentityResponse::new(state.config().identifier().to_string());
-            println_output(output, &opts.global_args.output_format)?;
+            print_result(output, &opts.output_format())?;
         }
         Ok(())
     }
 }
 
-impl Output for LongIdentityResponse<'_> {
+/// Identifier and change history of an identity
+#[derive(Serialize)]
+pub struct FullIdentityOutput {
+    identifier: String,
+    changes: Vec<IdentityChangeOutput>,
+}
+
+/// A change of the key of an identity. The first change creates the key, the next ones rotate it
+#[derive(Serialize)]
+struct IdentityChangeOutput {
+    identifier: String,
+    #[serde(rename = "type")]
+    change_type: &'static str,
+    previous_change_identifier: String,
+    label: String,
+    public_key: String,
+    signatures: Vec<String>,
+}
+
+impl FullIdentityOutput {
+    pub fn new(identifier: &IdentityIdentifier, history: &IdentityChangeHistory) -> Self {
+        let changes = history
+            .as_ref()
+            .iter()
+            .map(|signed_change| {
+                let change = signed_change.change();
+                IdentityChangeOutput {
+                    identifier: signed_change.identifier().to_string(),
+                    change_type: match change {
+                        IdentityChange::CreateKey(_) => "CreateKey",
+                        IdentityChange::RotateKey(_) => "RotateKey",
+                    },
+                    previous_change_identifier: change.previous_change_identifier().to_string(),
+                    label: change.label().to_string(),
+                    public_key: change
+                        .public_key()
+                        .map(|k| k.to_string())
+                        .unwrap_or_default(),
+                    signatures: signed_change
+                        .signatures()
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                }
+            })
+            .collect();
+        Self {
+            identifier: identifier.to_string(),
+            changes,
+        }
+    }
+}
+
+impl Output for FullIdentityOutput {
     fn output(&self) -> Result<String> {
         let mut w = String::new();
-        let id: IdentityChangeHistory = serde_bare::from_slice(self.identity.0.as_ref())?;
-        write!(w, "{id}")?;
+        writeln!(w, "Identifier: {}", self.identifier)?;
+        write!(w, "Change History:")?;
+        for (i, change) in self.changes.iter().enumerate() {
+            write!(w, "\n  Change[{i}]: {}", change.change_type)?;
+            write!(w, "\n    identifier:             {}", change.identifier)?;
+            write!(
+                w,
+                "\n    prev_change_identifier: {}",
+                change.previous_change_identifier
+            )?;
+            write!(w, "\n    label:                  {}", change.label)?;
+            write!(w, "\n    public_key:             {}", change.public_key)?;
+            write!(w, "\n    signatures:")?;
+            for (j, signature) in change.signatures.iter().enumerate() {
+                write!(w, "\n      [{j}]: {signature}")?;
+            }
+        }
         Ok(w)
     }
 }
@@ -92,3 +163,38 @@ impl Output for ShortIdentityResponse<'_> {
         Ok(w)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    /// Change history of an identity with a single key creation and no rotation
+    const IDENTITY: &str = "01664b02ffe35a499e854acbb47d01226540afa9b28a5eeaa089864a2b09526f08000547c93239ba3d818ec26c9cdadd2a35cbdf1fa3b6d1a731e06164b1079fb7b8084f434b414d5f524b03012000000020b65e5635a1f23e5aa7f2bfc66cdb3c7d92c78a3647ef38d3161a0835081a13db03010140e581bdd8f60598437f954ecdf67475be2311f0e710ec9e56ee043cbceec694c0c4261a5709aeed842b94cd19de5b271a68a097a2c667a0d949dbf802c984c20a";
+    const IDENTIFIER: &str = "Pf19fcdbdf78636f62c8d18f09b0fb89ae6ea4f9a77d12c3a9fe69281c3935883";
+
+    #[test]
+    fn full_identity_output() {
+        let history = IdentityChangeHistory::import_hex(IDENTITY).unwrap();
+        let identifier = IdentityIdentifier::from_str(IDENTIFIER).unwrap();
+        let output = FullIdentityOutput::new(&identifier, &history);
+
+        assert_eq!(output.identifier, IDENTIFIER);
+        assert_eq!(output.changes.len(), 1);
+        let change = &output.changes[0];
+        assert_eq!(change.change_type, "CreateKey");
+        assert_eq!(change.label, "OCKAM_RK");
+        assert_eq!(
+            change.public_key,
+            "Ed25519 b65e5635a1f23e5aa7f2bfc66cdb3c7d92c78a3647ef38d3161a0835081a13db"
+        );
+        assert_eq!(change.signatures.len(), 1);
+
+        let plain = output.output().unwrap();
+        assert!(plain.starts_with(&format!("Identifier: {IDENTIFIER}\nChange History:")));
+        assert!(plain.contains("Change[0]: CreateKey"));
+
+        let json: serde_json::Value = serde_json::to_value(&output).unwrap();
+        assert_eq!(json["changes"][0]["type"], "CreateKey");
+    }
+}
@@ -7,6 +7,7 @@
 mod admin;
 mod authenticated;
 mod authority;
+mod cloud;
 mod completion;
 mod configuration;
 mod credential;
@@ -22,17 +23,20 @@ mod logs;
 mod manpages;
 mod markdown;
 mod message;
+mod multiaddr;
 pub mod node;
 mod operation;
 mod pager;
 mod policy;
-mod project;
+mod portal;
+pub mod project;
 mod relay;
 mod reset;
 mod run;
 mod secure_channel;
 mod service;
 mod space;
+mod state;
 mod status;
 mod subscription;
 pub mod tcp;
@@ -54,6 +58,7 @@ use crate::subscription::SubscriptionCommand;
 pub use crate::terminal::{OckamColor, Terminal, TerminalStream};
 use authenticated::AuthenticatedCommand;
 use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use cloud::CloudCommand;
 
 use crate::kafka::outlet::KafkaOutletCommand;
 use colorful::Colorful;
@@ -72,16 +77,19 @@ use manpages::ManpagesCommand;
 use markdown::MarkdownCommand;
 use message::MessageCommand;
 use miette::GraphicalReportHandler;
+use multiaddr::MultiAddrCommand;
 use node::NodeCommand;
 use ockam_api::cli_state::CliState;
 use once_cell::sync::Lazy;
 use policy::PolicyCommand;
+use portal::PortalCommand;
 use project::ProjectCommand;
 use relay::RelayCommand;
 use reset::ResetCommand;
 use secure_channel::{listener::SecureChannelListenerCommand, SecureChannelCommand};
 use service::ServiceCommand;
 use space::SpaceCommand;
+use state::StateCommand;
 use status::StatusCommand;
 use std::{path::PathBuf, sync::Mutex};
 use tcp::{
@@ -121,7 +129,7 @@ pub struct OckamCommand {
     global_args: GlobalArgs,
 }
 
-#[derive(Debug, Clone, Args)]
+#[derive(Debug, Clone, Default, Args)]
 pub struct GlobalArgs {
     #[arg(
     global = true,
@@ -156,15 +164,14 @@ pub struct GlobalArgs {
     #[arg(hide = docs::hide(), global = true, long)]
     no_input: bool,
 
-    /// Output format
-    #[arg(
-    hide = docs::hide(),
-    global = true,
-    long = "output",
-    value_enum,
-    default_value = "plain"
-    )]
-    output_format: OutputFormat,
+    /// Output format, plain by default. The plain output is formatted for humans if stdout is a
+    /// terminal, and for programs otherwise
+    #[arg(hide = docs::hide(), global = true, long = "output", value_enum)]
+    output_format: Option<OutputFormat>,
+
+    /// Output as JSON. Shorthand for `--output json`
+    #[arg(hide = docs::hide(), global = true, long, conflicts_with = "output_format")]
+    json: bool,
 
     // if test_argument_parser is true, command arguments are checked
     // but the command is not executed.
@@ -172,26 +179,32 @@ pub struct GlobalArgs {
     test_argument_parser: bool,
 }
 
-impl Default for GlobalArgs {
-    fn default() -> Self {
-        Self {
-            help: None,
-            quiet: false,
-            verbose: 0,
-            no_color: false,
-            no_input: false,
-            output_format: OutputFormat::Plain,
-            test_argument_parser: false,
-        }
-    }
-}
-
 impl GlobalArgs {
     pub fn set_quiet(&self) -> Self {
         let mut clone = self.clone();
         clone.quiet = true;
         clone
     }
+
+    /// Return the output format explicitly requested with `--output` or `--json`, if any
+    fn requested_output_format(&self) -> Option<OutputFormat> {
+        if self.json {
+            Some(OutputFormat::Json)
+        } else {
+            self.output_format.clone()
+        }
+    }
+
+    /// Return the terminal writing the output of a command with the requested format
+    fn terminal(&self) -> Terminal<TerminalStream<Term>> {
+        Terminal::new(
+            self.quiet,
+            self.no_color,
+            self.no_input,
+            self.requested_output_format()
+                .unwrap_or(OutputFormat::Plain),
+        )
+    }
 }
 
 #[derive(Debug, Clone, ValueEnum, PartialEq, Eq)]
@@ -216,12 +229,25 @@ pub struct CommandGlobalOpts {
 impl CommandGlobalOpts {
     pub fn new(global_args: GlobalArgs) -> Self {
         let state = CliState::initialize().expect("Failed to load the local Ockam configuration");
-        let terminal = Terminal::new(
-            global_args.quiet,
-            global_args.no_color,
-            global_args.no_input,
-            global_args.output_format.clone(),
-        );
+        let terminal = global_args.terminal();
+        Self {
+            global_args,
+            state,
+            terminal,
+        }
+    }
+
+    /// Return the options used to run commands from another application rather than from the
+    /// command line: the default global arguments, a quiet terminal, and the state stored in
+    /// the given directory, or in the default directory if none is given
+    pub fn embedded(state_dir: Option<PathBuf>) -> Self {
+        let state = match state_dir {
+            Some(dir) => CliState::initialize_in(dir),
+            None => CliState::initialize(),
+        }
+        .expect("Failed to load the local Ockam configuration");
+        let global_args = GlobalArgs::default().set_quiet();
+        let terminal = global_args.terminal();
         Self {
             global_args,
             state,
@@ -235,17 +261,19 @@ impl CommandGlobalOpts {
         clone.terminal = clone.terminal.set_quiet();
         clone
     }
+
+    /// Return the format to use for the output of a command, given the `--output` and `--json`
+    /// flags. This is the format used by the terminal, which writes the plain output for humans
+    /// when stdout is a terminal and its machine variant when stdout is piped
+    pub fn output_format(&self) -> OutputFormat {
+        self.terminal.output_format()
+    }
 }
 
 #[cfg(test)]
 impl CommandGlobalOpts {
     pub fn new_for_test(global_args: GlobalArgs, state: CliState) -> Self {
-        let terminal = Terminal::new(
-            global_args.quiet,
-            global_args.no_color,
-            global_args.no_input,
-            global_args.output_format.clone(),
-        );
+        let terminal = global_args.terminal();
         Self {
             global_args,
             state,
@@ -262,6 +290,7 @@ pub enum OckamSubcommand {
     Project(ProjectCommand),
     Admin(AdminCommand),
     Subscription(SubscriptionCommand),
+    Cloud(CloudCommand),
 
     Node(Box<NodeCommand>),
     Worker(WorkerCommand),
@@ -273,6 +302,7 @@ pub enum OckamSubcommand {
     TcpConnection(TcpConnectionCommand),
     TcpOutlet(TcpOutletCommand),
     TcpInlet(TcpInletCommand),
+    Portal(PortalCommand),
 
     KafkaOutlet(KafkaOutletCommand),
     KafkaConsumer(KafkaConsumerCommand),
@@ -291,7 +321,9 @@ pub enum OckamSubcommand {
     Run(RunCommand),
     Status(StatusCommand),
     Reset(ResetCommand),
+    State(StateCommand),
     Authenticated(AuthenticatedCommand),
+    #[command(alias = "config")]
     Configuration(ConfigurationCommand),
 
     Completion(CompletionCommand),
@@ -301,6 +333,8 @@ pub enum OckamSubcommand {
     Environment(EnvironmentCommand),
 
     FlowControl(FlowControlCommand),
+    #[command(name = "multiaddr")]
+    MultiAddr(MultiAddrCommand),
 }
 
 impl OckamSubcommand {
@@ -382,6 +416,7 @@ impl OckamCommand {
             OckamSubcommand::Project(c) => c.run(options),
             OckamSubcommand::Admin(c) => c.run(options),
             OckamSubcommand::Subscription(c) => c.run(options),
+            OckamSubcommand::Cloud(c) => c.run(options),
 
             OckamSubcommand::Node(c) => c.run(options),
             OckamSubcommand::Worker(c) => c.run(options),
@@ -394,6 +429,7 @@ impl OckamCommand {
             OckamSubcommand::TcpConnection(c) => c.run(options),
             OckamSubcommand::TcpOutlet(c) => c.run(options),
             OckamSubcommand::TcpInlet(c) => c.run(options),
+            OckamSubcommand::Portal(c) => c.run(options),
 
             OckamSubcommand::KafkaConsumer(c) => c.run(options),
             OckamSubcommand::KafkaProducer(c) => c.run(options),
@@ -411,6 +447,7 @@ impl OckamCommand {
             OckamSubcommand::Run(c) => c.run(options),
             OckamSubcommand::Status(c) => c.run(options),
             OckamSubcommand::Reset(c) => c.run(options),
+            OckamSubcommand::State(c) => c.run(options),
             OckamSubcommand::Authenticated(c) => c.run(),
             OckamSubcommand::Configuration(c) => c.run(options),
 
@@ -421,6 +458,7 @@ impl OckamCommand {
             OckamSubcommand::Environment(c) => c.run(),
 
             OckamSubcommand::FlowControl(c) => c.run(options),
+            OckamSubcommand::MultiAddr(c) => c.run(options),
         }
     }
 
@@ -489,3 +527,52 @@ pub(crate) fn replace_hyphen_with_stdin(s: String) -> String {
         s
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Return the output format used by the terminal of a command run with the given flags
+    fn output_format(args: &[&str]) -> OutputFormat {
+        let args = ["ockam"]
+            .iter()
+            .chain(args.iter())
+            .chain(["node", "list"].iter());
+        OckamCommand::try_parse_from(args)
+            .unwrap()
+            .global_args
+            .terminal()
+            .output_format()
+    }
+
+    #[test]
+    fn embedded_opts_use_the_given_state_dir() {
+        let dir = CliState::test_dir().unwrap();
+        let opts = CommandGlobalOpts::embedded(Some(dir.clone()));
+        assert_eq!(opts.state.dir, dir);
+        assert!(opts.global_args.quiet);
+        assert!(dir.join("defaults").exists());
+    }
+
+    #[test]
+    fn the_output_format_is_plain_without_explicit_flags() {
+        assert_eq!(output_format(&[]), OutputFormat::Plain);
+    }
+
+    #[test]
+    fn explicit_flags_override_the_output_format() {
+        for (args, expected) in [
+            (vec!["--output", "plain"], OutputFormat::Plain),
+            (vec!["--output", "json"], OutputFormat::Json),
+            (vec!["--json"], OutputFormat::Json),
+        ] {
+            assert_eq!(output_format(&args), expected);
+        }
+    }
+
+    #[test]
+    fn the_json_flag_conflicts_with_the_output_flag() {
+        let args = ["ockam", "--json", "--output", "plain", "node", "list"];
+        assert!(OckamCommand::try_parse_from(args).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::logs::rolling::{RollingConditionBasic, RollingFileAppender};
 
+use ockam_api::logs::LogBroadcastLayer;
 use ockam_core::env::{get_env, get_env_with_default, FromString};
 use std::io::stdout;
 use std::path::PathBuf;
@@ -98,7 +99,8 @@ pub fn setup_logging(
     };
     let subscriber = tracing_subscriber::registry()
         .with(filter)
-        .with(tracing_error::ErrorLayer::default());
+        .with(tracing_error::ErrorLayer::default())
+        .with(LogBroadcastLayer);
     let (appender, guard) = match log_path {
         // If a log path is not provided, log to stdout.
         None => {
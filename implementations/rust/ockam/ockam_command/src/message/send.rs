@@ -113,8 +113,14 @@ async fn rpc(
             cmd.message.as_bytes().to_vec()
         };
 
-        rpc.request_with_timeout(req(&to, msg_bytes), Duration::from_secs(cmd.timeout))
-            .await?;
+        // the node stops waiting for the answer after the timeout and answers with a timeout
+        // error, which is received before the rpc itself times out
+        let timeout = Duration::from_secs(cmd.timeout);
+        rpc.request_with_timeout(
+            req(&to, msg_bytes).timeout(timeout),
+            timeout + Duration::from_secs(1),
+        )
+        .await?;
         let res = {
             let res = rpc.parse_response_body::<Vec<u8>>()?;
             if cmd.hex {
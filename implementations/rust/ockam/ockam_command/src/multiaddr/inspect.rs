@@ -0,0 +1,141 @@
+use std::str::FromStr;
+
+use clap::Args;
+use miette::{miette, IntoDiagnostic};
+use serde::Serialize;
+
+use ockam_multiaddr::iter::StrIter;
+use ockam_multiaddr::MultiAddr;
+
+use crate::util::local_cmd;
+use crate::{docs, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/inspect/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/inspect/after_long_help.txt");
+
+/// Print the protocol components of a MultiAddr
+#[derive(Clone, Debug, Args)]
+#[command(
+    long_about = docs::about(LONG_ABOUT),
+    after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct InspectCommand {
+    /// The MultiAddr to inspect, for example /dnsaddr/localhost/tcp/4000/service/api
+    pub address: String,
+}
+
+impl InspectCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        local_cmd(run_impl(opts, self));
+    }
+}
+
+/// Protocol component of a MultiAddr
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct Component {
+    code: u32,
+    name: String,
+    value: String,
+}
+
+#[derive(Serialize)]
+struct Inspection {
+    components: Vec<Component>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    socket_addr: Option<String>,
+}
+
+fn run_impl(opts: CommandGlobalOpts, cmd: InspectCommand) -> miette::Result<()> {
+    let components = components(&cmd.address)?;
+    // all the components are valid so the whole address is valid
+    let socket_addr = MultiAddr::from_str(&cmd.address)
+        .into_diagnostic()?
+        .to_socket_addr()
+        .ok();
+
+    let mut plain = components
+        .iter()
+        .enumerate()
+        .map(|(i, c)| format!("{:>3}  {:<10} {:>5}  {}", i + 1, c.name, c.code, c.value))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if let Some(socket_addr) = &socket_addr {
+        plain.push_str(&format!("\nSocket address: {socket_addr}"));
+    }
+    let inspection = Inspection {
+        components,
+        socket_addr,
+    };
+    opts.terminal
+        .stdout()
+        .plain(plain)
+        .machine(&cmd.address)
+        .json(serde_json::to_string_pretty(&inspection).into_diagnostic()?)
+        .write_line()?;
+    Ok(())
+}
+
+/// Parse each component of a MultiAddr separately, so that an error names the component which
+/// can't be parsed
+fn components(address: &str) -> miette::Result<Vec<Component>> {
+    let mut components = vec![];
+    for (i, item) in StrIter::new(address).enumerate() {
+        let position = i + 1;
+        let (name, value) =
+            item.map_err(|e| miette!("The component {position} of {address} is invalid: {e}"))?;
+        let text = format!("/{name}/{}", *value);
+        let component = MultiAddr::from_str(&text).map_err(|e| {
+            miette!("The component {position} of {address}, {text}, is invalid: {e}")
+        })?;
+        let proto = component
+            .first()
+            .ok_or_else(|| miette!("The component {position} of {address} is empty"))?;
+        let normalized = component.to_string();
+        components.push(Component {
+            code: proto.code().into(),
+            name: name.to_string(),
+            value: normalized
+                .strip_prefix(&format!("/{name}/"))
+                .unwrap_or(&normalized)
+                .to_string(),
+        });
+    }
+    Ok(components)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_multiaddr::proto::{DnsAddr, Service, Tcp};
+    use ockam_multiaddr::Protocol;
+
+    #[test]
+    fn each_component_is_decoded_in_order() {
+        let components = components("/dnsaddr/localhost/tcp/4000/service/api").unwrap();
+        let expected = [
+            (DnsAddr::CODE, "dnsaddr", "localhost"),
+            (Tcp::CODE, "tcp", "4000"),
+            (Service::CODE, "service", "api"),
+        ];
+        assert_eq!(components.len(), expected.len());
+        for (component, (code, name, value)) in components.iter().zip(expected) {
+            assert_eq!(component.code, u32::from(code));
+            assert_eq!(component.name, name);
+            assert_eq!(component.value, value);
+        }
+    }
+
+    #[test]
+    fn the_invalid_component_is_reported() {
+        let error = components("/dnsaddr/localhost/tcp/not-a-port/service/api")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("component 2"), "{error}");
+        assert!(error.contains("/tcp/not-a-port"), "{error}");
+
+        let error = components("/ip4/127.0.0.1/unknown/api")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("component 2"), "{error}");
+    }
+}
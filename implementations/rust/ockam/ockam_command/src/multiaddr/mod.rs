@@ -0,0 +1,27 @@
+use crate::CommandGlobalOpts;
+use clap::{Args, Subcommand};
+pub use inspect::InspectCommand;
+
+mod inspect;
+
+/// Decode and inspect MultiAddrs
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, subcommand_required = true)]
+pub struct MultiAddrCommand {
+    #[command(subcommand)]
+    subcommand: MultiAddrSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum MultiAddrSubcommand {
+    #[command(display_order = 800)]
+    Inspect(InspectCommand),
+}
+
+impl MultiAddrCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            MultiAddrSubcommand::Inspect(c) => c.run(options),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::net::SocketAddr;
+
+use clap::Args;
+use miette::{miette, IntoDiagnostic};
+
+use ockam_api::cli_state::{StateDirTrait, StateItemTrait};
+use ockam_api::config::lookup::InternetAddress;
+use ockam_api::{replace_unspecified_ip, socket_addr_to_multiaddr};
+use ockam_multiaddr::MultiAddr;
+
+use crate::node::get_node_name;
+use crate::util::local_cmd;
+use crate::{docs, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/address/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/address/after_long_help.txt");
+
+/// Print the listen address of a node as a MultiAddr
+#[derive(Clone, Debug, Args)]
+#[command(
+    long_about = docs::about(LONG_ABOUT),
+    after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct AddressCommand {
+    /// Name of the node
+    node_name: Option<String>,
+
+    /// Append the address of a service of the node
+    #[arg(long, value_name = "SERVICE_NAME")]
+    service: Option<String>,
+
+    /// Replace an unspecified IP, 0.0.0.0 or ::, with the primary IP of the machine
+    #[arg(long)]
+    primary_ip: bool,
+}
+
+impl AddressCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        local_cmd(run_impl(opts, self));
+    }
+}
+
+fn run_impl(opts: CommandGlobalOpts, cmd: AddressCommand) -> miette::Result<()> {
+    let node_name = get_node_name(&opts.state, &cmd.node_name);
+    let node_state = opts.state.nodes.get(&node_name)?;
+    let listener = node_state
+        .config()
+        .setup()
+        .api_transport()
+        .map_err(|_| miette!("The node '{node_name}' is not listening yet"))?;
+    let ma = listen_multiaddr(&listener.addr, cmd.service.as_deref(), cmd.primary_ip)?;
+    opts.terminal
+        .stdout()
+        .plain(&ma)
+        .machine(&ma)
+        .json(serde_json::json!({ "address": ma.to_string() }))
+        .write_line()?;
+    Ok(())
+}
+
+/// Return the address of a listener as a MultiAddr
+fn listen_multiaddr(
+    addr: &InternetAddress,
+    service: Option<&str>,
+    primary_ip: bool,
+) -> miette::Result<MultiAddr> {
+    let mut addr = match addr {
+        InternetAddress::V4(v4) => SocketAddr::V4(*v4),
+        InternetAddress::V6(v6) => SocketAddr::V6(*v6),
+        InternetAddress::Dns(..) => {
+            return Err(miette!("The listen address {addr} is not an IP address"))
+        }
+    };
+    if primary_ip {
+        addr = replace_unspecified_ip(addr);
+    }
+    socket_addr_to_multiaddr(&addr, service).into_diagnostic()
+}
@@ -16,6 +16,7 @@ use ockam::{Context, TcpTransport};
 use ockam_api::cli_state::traits::{StateDirTrait, StateItemTrait};
 use ockam_api::nodes::authority_node;
 use ockam_api::nodes::models::transport::CreateTransportJson;
+use ockam_api::nodes::service::startup_config::STARTUP_CONFIG_WATCH_INTERVAL;
 use ockam_api::nodes::service::NodeManagerTrustOptions;
 use ockam_api::{
     bootstrapped_identities_store::PreTrustedIdentities,
@@ -85,6 +86,10 @@ pub struct CreateCommand {
     #[arg(long, hide = true, value_parser = parse_launch_config)]
     pub launch_config: Option<Config>,
 
+    /// Apply the changes made to the startup configuration of the node while it is running
+    #[arg(long)]
+    pub watch_startup_config: bool,
+
     #[arg(long, group = "trusted")]
     pub trusted_identities: Option<String>,
     #[arg(long, group = "trusted")]
@@ -117,6 +122,7 @@ impl Default for CreateCommand {
             foreground: false,
             child_process: false,
             launch_config: None,
+            watch_startup_config: false,
             vault: None,
             identity: None,
             trusted_identities: None,
@@ -290,6 +296,7 @@ async fn run_foreground_node(
             .config()
             .setup_mut()
             .set_verbose(opts.global_args.verbose)
+            .set_watch_startup_config(cmd.watch_startup_config)
             .set_api_transport(
                 CreateTransportJson::new(
                     TransportType::Tcp,
@@ -302,7 +309,7 @@ async fn run_foreground_node(
 
     let pre_trusted_identities = load_pre_trusted_identities(&cmd)?;
 
-    let node_man = NodeManager::create(
+    let mut node_man = NodeManager::create(
         &ctx,
         NodeManagerGeneralOptions::new(
             opts.state.clone(),
@@ -318,14 +325,29 @@ async fn run_foreground_node(
     )
     .await
     .into_diagnostic()?;
-    let node_manager_worker = NodeManagerWorker::new(node_man);
+    // the node is reported as starting until its startup configuration is loaded
+    node_man.set_starting();
+    let mut node_manager_worker = NodeManagerWorker::new(node_man);
 
     ctx.flow_controls()
         .add_consumer(NODEMANAGER_ADDR, listener.flow_control_id());
-    ctx.start_worker(NODEMANAGER_ADDR, node_manager_worker)
+    ctx.start_worker(NODEMANAGER_ADDR, node_manager_worker.clone())
         .await
         .into_diagnostic()?;
 
+    // The trust contexts, policies and portals declared in the node directory are created
+    // before the node is reported as started. The node stops if they can't be created
+    node_manager_worker
+        .load_startup_config(&ctx)
+        .await
+        .map_err(|e| miette!("Failed to load the node startup configuration: {e}"))?;
+    if cmd.watch_startup_config {
+        node_manager_worker
+            .watch_startup_config(&ctx, STARTUP_CONFIG_WATCH_INTERVAL)
+            .await
+            .into_diagnostic()?;
+    }
+
     if let Some(config) = &cmd.launch_config {
         if start_services(&ctx, config).await.is_err() {
             //TODO: Process should terminate on any error during its setup phase,
@@ -507,6 +529,7 @@ pub async fn spawn_background_node(
         cmd.credential.as_ref(),
         trust_context_path.as_ref(),
         cmd.trust_context_opts.project.as_ref(),
+        cmd.watch_startup_config,
         cmd.logging_to_file(),
     )?;
 
@@ -0,0 +1,61 @@
+use clap::Args;
+use miette::{miette, IntoDiagnostic};
+use ockam::Context;
+use ockam_api::cli_state::StateDirTrait;
+use ockam_api::nodes::models::startup_config::StartupConfigFile;
+use ockam_api::nodes::service::startup_config::NodeStartupConfig;
+
+use crate::node::get_node_name;
+use crate::util::{api, extract_address_value, node_rpc, Rpc};
+use crate::{docs, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/export_config/long_about.txt");
+const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/export_config/after_long_help.txt");
+
+/// Print the running configuration of a node in the format of its startup configuration file
+#[derive(Clone, Debug, Args)]
+#[command(
+    long_about = docs::about(LONG_ABOUT),
+    before_help = docs::before_help(PREVIEW_TAG),
+    after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct ExportConfigCommand {
+    /// Name of the node to export the configuration of.
+    node_name: Option<String>,
+
+    /// Include the credentials of the trust contexts instead of referencing them by name.
+    #[arg(long)]
+    inline_credentials: bool,
+}
+
+impl ExportConfigCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ExportConfigCommand),
+) -> miette::Result<()> {
+    let node_name = get_node_name(&opts.state, &cmd.node_name);
+    let node_name = extract_address_value(&node_name)?;
+    if !opts.state.nodes.get(&node_name)?.is_running() {
+        return Err(miette!("The node '{}' is not running", node_name));
+    }
+
+    let mut rpc = Rpc::background(&ctx, &opts, &node_name)?;
+    rpc.request(api::export_startup_config(cmd.inline_credentials))
+        .await?;
+    let file: StartupConfigFile = rpc.parse_response_body()?;
+    let config = NodeStartupConfig::parse(&file.contents).into_diagnostic()?;
+    let contents = file.contents.trim_end();
+    opts.terminal
+        .stdout()
+        .plain(contents)
+        .machine(contents)
+        .json(serde_json::to_string_pretty(&config).into_diagnostic()?)
+        .write_line()?;
+    Ok(())
+}
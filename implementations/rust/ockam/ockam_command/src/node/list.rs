@@ -8,7 +8,7 @@ use indoc::formatdoc;
 use miette::Context as _;
 use ockam::Context;
 use ockam_api::cli_state::StateDirTrait;
-use ockam_api::nodes::models::base::NodeStatus;
+use ockam_api::nodes::models::base::{NodeRunStatus, NodeStatus};
 
 use tokio::sync::Mutex;
 use tokio::try_join;
@@ -72,7 +72,12 @@ async fn run_impl(
                 }
                 resp
             } else {
-                NodeStatus::new(node_name.to_string(), "Not running".to_string(), 0, 0)
+                NodeStatus::new(
+                    node_name.to_string(),
+                    NodeRunStatus::NotRunning.as_str(),
+                    0,
+                    0,
+                )
             };
 
             *is_finished.lock().await = true;
@@ -93,7 +98,8 @@ async fn run_impl(
 
         nodes.push(NodeListOutput::new(
             node_status.node_name.to_string(),
-            node_status.status.to_string(),
+            // a node answering with a status unknown to this version is running
+            node_status.run_status().unwrap_or(NodeRunStatus::Running),
             node_status.pid,
             node_status.node_name == default,
         ));
@@ -108,13 +114,13 @@ async fn run_impl(
 
 pub struct NodeListOutput {
     pub node_name: String,
-    pub status: String,
+    pub status: NodeRunStatus,
     pub pid: i32,
     pub is_default: bool,
 }
 
 impl NodeListOutput {
-    pub fn new(node_name: String, status: String, pid: i32, is_default: bool) -> Self {
+    pub fn new(node_name: String, status: NodeRunStatus, pid: i32, is_default: bool) -> Self {
         Self {
             node_name,
             status,
@@ -126,17 +132,23 @@ impl NodeListOutput {
 
 impl Output for NodeListOutput {
     fn output(&self) -> Result<String> {
-        let (status, pid) = match self.status.as_str() {
-            "Running" => (
-                "UP".color(OckamColor::Success.color()),
-                format!(
-                    "Process id {}",
-                    self.pid
-                        .to_string()
-                        .color(OckamColor::PrimaryResource.color())
-                ),
+        let process = format!(
+            "Process id {}",
+            self.pid
+                .to_string()
+                .color(OckamColor::PrimaryResource.color())
+        );
+        let (status, pid) = match self.status {
+            NodeRunStatus::Running => ("UP".color(OckamColor::Success.color()), process),
+            NodeRunStatus::Starting => (
+                "STARTING".color(OckamColor::FmtWARNBackground.color()),
+                process,
+            ),
+            NodeRunStatus::Paused => (
+                "PAUSED".color(OckamColor::FmtWARNBackground.color()),
+                process,
             ),
-            _ => (
+            NodeRunStatus::NotRunning => (
                 "DOWN".color(OckamColor::Failure.color()),
                 "No process running".to_string(),
             ),
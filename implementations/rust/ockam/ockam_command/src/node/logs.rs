@@ -1,8 +1,17 @@
+use core::time::Duration;
+
 use crate::node::get_node_name;
-use crate::util::local_cmd;
-use crate::{docs, CommandGlobalOpts};
-use clap::Args;
+use crate::terminal::OckamColor;
+use crate::util::{
+    api, connect_to_node, extract_address_value, local_cmd, node_rpc, Rpc, RpcBuilder,
+};
+use crate::{docs, fmt_log, CommandGlobalOpts};
+use clap::{Args, ValueEnum};
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+use ockam::{Context, TcpTransport};
 use ockam_api::cli_state::StateDirTrait;
+use ockam_api::nodes::models::logs::{LogLines, LogsSubscription};
 
 const LONG_ABOUT: &str = include_str!("./static/logs/long_about.txt");
 const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
@@ -20,13 +29,49 @@ pub struct LogCommand {
     node_name: Option<String>,
 
     /// Show the standard error log file.
-    #[arg(long = "err")]
+    #[arg(long = "err", conflicts_with = "follow")]
     show_err: bool,
+
+    /// Print the log lines of the running node as they are logged, until interrupted.
+    #[arg(long, short)]
+    follow: bool,
+
+    /// Most verbose level of the lines printed with --follow.
+    #[arg(long, value_enum, default_value_t = LogLevel::Info, requires = "follow")]
+    level: LogLevel,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
 }
 
+/// How often the node is asked for new lines
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(250);
+
 impl LogCommand {
     pub fn run(self, opts: CommandGlobalOpts) {
-        local_cmd(run_impl(opts, self));
+        if self.follow {
+            node_rpc(follow, (opts, self))
+        } else {
+            local_cmd(run_impl(opts, self));
+        }
     }
 }
 
@@ -44,3 +89,71 @@ fn run_impl(opts: CommandGlobalOpts, cmd: LogCommand) -> miette::Result<()> {
         .write_line()?;
     Ok(())
 }
+
+async fn follow(ctx: Context, (opts, cmd): (CommandGlobalOpts, LogCommand)) -> miette::Result<()> {
+    let node_name = get_node_name(&opts.state, &cmd.node_name);
+    let node_name = extract_address_value(&node_name)?;
+    if !opts.state.nodes.get(&node_name)?.is_running() {
+        return Err(miette!("The node '{}' is not running", node_name));
+    }
+
+    // A single connection is used for all the requests, it is closed when the command stops
+    let tcp = TcpTransport::create(&ctx).await.into_diagnostic()?;
+    let connection = connect_to_node(&tcp, &opts, &node_name).await?;
+    let mut rpc = RpcBuilder::new(&ctx, &opts, &node_name)
+        .connection(&connection)
+        .build();
+    rpc.request(api::subscribe_to_logs(cmd.level.as_str()))
+        .await?;
+    let subscription: LogsSubscription = rpc.parse_response_body()?;
+
+    opts.terminal.write_line(&fmt_log!(
+        "Following the logs of node {}, press Ctrl-C to stop",
+        node_name
+            .to_string()
+            .color(OckamColor::PrimaryResource.color())
+    ))?;
+    let res = print_lines_until_interrupted(&mut rpc, &opts, &subscription.id).await;
+
+    let unsubscribed = rpc
+        .request(api::unsubscribe_from_logs(&subscription.id))
+        .await;
+    tcp.disconnect(connection).await.into_diagnostic()?;
+    res?;
+    unsubscribed?;
+    Ok(())
+}
+
+async fn print_lines_until_interrupted(
+    rpc: &mut Rpc<'_>,
+    opts: &CommandGlobalOpts,
+    subscription_id: &str,
+) -> crate::Result<()> {
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    loop {
+        tokio::select! {
+            _ = &mut ctrl_c => return Ok(()),
+            res = print_next_lines(rpc, opts, subscription_id) => res?,
+        }
+    }
+}
+
+async fn print_next_lines(
+    rpc: &mut Rpc<'_>,
+    opts: &CommandGlobalOpts,
+    subscription_id: &str,
+) -> crate::Result<()> {
+    tokio::time::sleep(FOLLOW_INTERVAL).await;
+    rpc.request(api::get_log_lines(subscription_id)).await?;
+    let lines: LogLines = rpc.parse_response_body()?;
+    for line in lines.lines {
+        opts.terminal
+            .clone()
+            .stdout()
+            .plain(&line)
+            .machine(&line)
+            .write_line()?;
+    }
+    Ok(())
+}
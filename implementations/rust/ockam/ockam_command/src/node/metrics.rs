@@ -0,0 +1,53 @@
+use clap::Args;
+use miette::miette;
+use ockam::Context;
+use ockam_api::cli_state::StateDirTrait;
+
+use crate::node::get_node_name;
+use crate::util::{api, extract_address_value, node_rpc, Rpc};
+use crate::{docs, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/metrics/long_about.txt");
+const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/metrics/after_long_help.txt");
+
+/// Print the metrics of a node in the Prometheus text format
+#[derive(Clone, Debug, Args)]
+#[command(
+    long_about = docs::about(LONG_ABOUT),
+    before_help = docs::before_help(PREVIEW_TAG),
+    after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct MetricsCommand {
+    /// Name of the node to print the metrics of.
+    node_name: Option<String>,
+}
+
+impl MetricsCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, MetricsCommand),
+) -> miette::Result<()> {
+    let node_name = get_node_name(&opts.state, &cmd.node_name);
+    let node_name = extract_address_value(&node_name)?;
+    if !opts.state.nodes.get(&node_name)?.is_running() {
+        return Err(miette!("The node '{}' is not running", node_name));
+    }
+
+    let mut rpc = Rpc::background(&ctx, &opts, &node_name)?;
+    rpc.request(api::get_node_metrics()).await?;
+    let metrics: String = rpc.parse_response_body()?;
+    // each line of the exposition format ends with a line feed
+    let metrics = metrics.trim_end();
+    opts.terminal
+        .stdout()
+        .plain(metrics)
+        .machine(metrics)
+        .write_line()?;
+    Ok(())
+}
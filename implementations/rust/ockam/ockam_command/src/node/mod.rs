@@ -1,27 +1,37 @@
 use clap::{Args, Subcommand};
 
+use address::AddressCommand;
 use colorful::Colorful;
 pub use create::CreateCommand;
 use default::DefaultCommand;
 use delete::DeleteCommand;
+use export_config::ExportConfigCommand;
 use list::ListCommand;
 use logs::LogCommand;
+use metrics::MetricsCommand;
 use ockam_api::cli_state::{CliState, StateDirTrait};
 use show::ShowCommand;
 use start::StartCommand;
 use stop::StopCommand;
+use wait::WaitCommand;
+use workers::WorkersCommand;
 
 use crate::{docs, fmt_log, terminal::OckamColor, CommandGlobalOpts, PARSER_LOGS};
 
+mod address;
 mod create;
 mod default;
 mod delete;
+mod export_config;
 mod list;
 mod logs;
+mod metrics;
 mod show;
 mod start;
 mod stop;
 pub mod util;
+mod wait;
+mod workers;
 pub use create::*;
 
 const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
@@ -50,6 +60,12 @@ pub enum NodeSubcommand {
     List(ListCommand),
     #[command(display_order = 800)]
     Logs(LogCommand),
+    #[command(display_order = 800)]
+    ExportConfig(ExportConfigCommand),
+    #[command(display_order = 800)]
+    Workers(WorkersCommand),
+    #[command(display_order = 800)]
+    Metrics(MetricsCommand),
     Show(ShowCommand),
     #[command(display_order = 800)]
     Start(StartCommand),
@@ -57,6 +73,10 @@ pub enum NodeSubcommand {
     Stop(StopCommand),
     #[command(display_order = 800)]
     Default(DefaultCommand),
+    #[command(display_order = 800)]
+    Address(AddressCommand),
+    #[command(display_order = 800)]
+    Wait(WaitCommand),
 }
 
 impl NodeCommand {
@@ -69,7 +89,12 @@ impl NodeCommand {
             NodeSubcommand::Start(c) => c.run(options),
             NodeSubcommand::Stop(c) => c.run(options),
             NodeSubcommand::Logs(c) => c.run(options),
+            NodeSubcommand::ExportConfig(c) => c.run(options),
+            NodeSubcommand::Workers(c) => c.run(options),
+            NodeSubcommand::Metrics(c) => c.run(options),
             NodeSubcommand::Default(c) => c.run(options),
+            NodeSubcommand::Address(c) => c.run(options),
+            NodeSubcommand::Wait(c) => c.run(options),
         }
     }
 }
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::node::get_node_name;
 use crate::node::util::check_default;
 use crate::util::{api, node_rpc, Rpc, RpcBuilder};
@@ -6,6 +8,7 @@ use clap::Args;
 use colorful::Colorful;
 use miette::IntoDiagnostic;
 use ockam::TcpTransport;
+use ockam_api::backoff::BackoffPolicy;
 use ockam_api::cli_state::{StateDirTrait, StateItemTrait};
 use ockam_api::nodes::models::portal::{InletList, OutletList};
 use ockam_api::nodes::models::secure_channel::SecureChannelListenersList;
@@ -15,14 +18,13 @@ use ockam_api::{addr_to_multiaddr, route_to_multiaddr};
 use ockam_core::Route;
 use ockam_multiaddr::proto::{DnsAddr, Node, Tcp};
 use ockam_multiaddr::MultiAddr;
-use tokio_retry::strategy::FixedInterval;
 use tracing::{info, trace, warn};
 
 const LONG_ABOUT: &str = include_str!("./static/show/long_about.txt");
 const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
 const AFTER_LONG_HELP: &str = include_str!("./static/show/after_long_help.txt");
 
-const IS_NODE_UP_TIME_BETWEEN_CHECKS_MS: usize = 50;
+const IS_NODE_UP_TIME_BETWEEN_CHECKS_MS: u64 = 50;
 const IS_NODE_UP_MAX_ATTEMPTS: usize = 60; // 3 seconds
 
 /// Show the details of a node
@@ -72,7 +74,7 @@ fn print_node_info(
     secure_channel_listeners: Option<&SecureChannelListenersList>,
     inlets_outlets: Option<(&InletList, &OutletList)>,
 ) {
-    if opts.global_args.output_format == OutputFormat::Json {
+    if opts.output_format() == OutputFormat::Json {
         opts.terminal
             .clone()
             .stdout()
@@ -273,13 +275,16 @@ pub async fn is_node_up(rpc: &mut Rpc<'_>, wait_until_ready: bool) -> Result<boo
         false => 1,
     };
 
-    let retries =
-        FixedInterval::from_millis(IS_NODE_UP_TIME_BETWEEN_CHECKS_MS as u64).take(attempts);
+    let policy = BackoffPolicy::fixed(
+        Duration::from_millis(IS_NODE_UP_TIME_BETWEEN_CHECKS_MS),
+        attempts,
+    );
 
     let cli_state = rpc.opts.state.clone();
     let node_name = rpc.node_name().to_owned();
     let now = std::time::Instant::now();
-    for timeout_duration in retries {
+    for attempt in 1..=policy.max_attempts {
+        let timeout_duration = policy.delay(attempt);
         let node_state = cli_state.nodes.get(&node_name)?;
         // The node is down if it has not stored its default tcp listener in its state file.
         if node_state.config().setup().api_transport().is_err() {
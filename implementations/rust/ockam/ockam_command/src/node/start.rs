@@ -74,6 +74,7 @@ async fn run_impl(
         None,                                          // Credential
         None,                                          // Trust Context
         None,                                          // Project Name
+        node_setup.watch_startup_config(),             // Keep watching the startup configuration
         true,                                          // Restarted nodes will log to files
     )?;
 
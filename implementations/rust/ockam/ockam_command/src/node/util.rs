@@ -6,7 +6,7 @@ use ockam_api::cli_state;
 use ockam_api::cli_state::traits::StateItemTrait;
 use ockam_api::config::lookup::ProjectLookup;
 
-use ockam_api::cli_state::{ProjectConfig, StateDirTrait};
+use ockam_api::cli_state::{IdentityState, ProjectConfig, StateDirTrait, VaultState};
 use ockam_api::nodes::service::{
     NodeManagerGeneralOptions, NodeManagerTransportOptions, NodeManagerTrustOptions,
 };
@@ -31,6 +31,17 @@ pub async fn start_embedded_node(
     start_embedded_node_with_vault_and_identity(ctx, opts, None, None, trust_opts).await
 }
 
+/// Start an embedded node with the given identity, or the default identity, and return its name
+/// and its node manager, for the commands which use the node manager directly instead of
+/// sending it requests
+pub async fn start_embedded_node_with_node_manager(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    identity: Option<String>,
+) -> Result<(String, NodeManagerWorker)> {
+    start_embedded_node_impl(ctx, opts, None, identity, None, true).await
+}
+
 pub async fn start_embedded_node_with_vault_and_identity(
     ctx: &Context,
     opts: &CommandGlobalOpts,
@@ -38,11 +49,46 @@ pub async fn start_embedded_node_with_vault_and_identity(
     identity: Option<String>,
     trust_opts: Option<&TrustContextOpts>,
 ) -> Result<String> {
+    start_embedded_node_impl(ctx, opts, vault, identity, trust_opts, true)
+        .await
+        .map(|(node_name, _)| node_name)
+}
+
+/// Start an embedded node using the default vault and identity.
+/// Contrary to [`start_embedded_node`], this function fails instead of creating them
+/// when they don't exist, so it can be used by commands which must not generate keys.
+pub async fn start_embedded_node_with_existing_identity(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+) -> Result<String> {
+    start_embedded_node_impl(ctx, opts, None, None, None, false)
+        .await
+        .map(|(node_name, _)| node_name)
+}
+
+async fn start_embedded_node_impl(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    vault: Option<String>,
+    identity: Option<String>,
+    trust_opts: Option<&TrustContextOpts>,
+    create_identity: bool,
+) -> Result<(String, NodeManagerWorker)> {
     let cmd = CreateCommand::default();
 
     // This node was initially created as a foreground node
     if !cmd.child_process {
-        init_node_state(opts, &cmd.node_name, vault.as_deref(), identity.as_deref()).await?;
+        if create_identity {
+            init_node_state(opts, &cmd.node_name, vault.as_deref(), identity.as_deref()).await?;
+        } else {
+            init_node_state_with_existing_identity(
+                opts,
+                &cmd.node_name,
+                vault.as_deref(),
+                identity.as_deref(),
+            )
+            .await?;
+        }
     }
 
     if let Some(p) = trust_opts {
@@ -80,10 +126,10 @@ pub async fn start_embedded_node_with_vault_and_identity(
     ctx.flow_controls()
         .add_consumer(NODEMANAGER_ADDR, listener.flow_control_id());
 
-    ctx.start_worker(NODEMANAGER_ADDR, node_manager_worker)
+    ctx.start_worker(NODEMANAGER_ADDR, node_manager_worker.clone())
         .await?;
 
-    Ok(cmd.node_name.clone())
+    Ok((cmd.node_name.clone(), node_manager_worker))
 }
 
 pub async fn add_project_info_to_node_state(
@@ -123,9 +169,80 @@ pub async fn init_node_state(
     identity_name: Option<&str>,
 ) -> miette::Result<()> {
     debug!(name=%node_name, "initializing node state");
+    let (vault_state, identity_state) =
+        load_or_create_identity(opts, vault_name, identity_name).await?;
+    write_node_state(opts, node_name, &vault_state, &identity_state)
+}
+
+/// Initialize the state of a node with an existing vault and identity, see [`load_identity`]
+pub async fn init_node_state_with_existing_identity(
+    opts: &CommandGlobalOpts,
+    node_name: &str,
+    vault_name: Option<&str>,
+    identity_name: Option<&str>,
+) -> miette::Result<()> {
+    debug!(name=%node_name, "initializing node state with an existing identity");
+    let (vault_state, identity_state) = load_identity(opts, vault_name, identity_name)?;
+    write_node_state(opts, node_name, &vault_state, &identity_state)
+}
+
+fn write_node_state(
+    opts: &CommandGlobalOpts,
+    node_name: &str,
+    vault_state: &VaultState,
+    identity_state: &IdentityState,
+) -> miette::Result<()> {
+    // Create the node with the given vault and identity
+    let node_config = cli_state::NodeConfigBuilder::default()
+        .vault(vault_state.path().clone())
+        .identity(identity_state.path().clone())
+        .build(&opts.state)?;
+    opts.state.nodes.overwrite(node_name, node_config)?;
+
+    info!(name=%node_name, "node state initialized");
+    Ok(())
+}
+
+/// Return the vault and the identity with the given names, or the default ones.
+/// Fail if they don't exist: nothing is created
+pub fn load_identity(
+    opts: &CommandGlobalOpts,
+    vault_name: Option<&str>,
+    identity_name: Option<&str>,
+) -> miette::Result<(VaultState, IdentityState)> {
+    let vault_state = match vault_name {
+        Some(name) => opts.state.vaults.get(name)?,
+        None => opts.state.vaults.default().map_err(|_| {
+            miette!(
+                "There is no default vault, run 'ockam enroll' or 'ockam identity create' first"
+            )
+        })?,
+    };
+    let identity_state = match identity_name {
+        Some(name) => opts.state.identities.get(name)?,
+        None => opts.state.identities.default().map_err(|_| {
+            miette!(
+                "There is no default identity, run 'ockam enroll' or 'ockam identity create' first"
+            )
+        })?,
+    };
+    Ok((vault_state, identity_state))
+}
+
+/// Return the vault and the identity with the given names, or the default ones.
+/// The vault and the identity are created if they don't exist
+pub async fn load_or_create_identity(
+    opts: &CommandGlobalOpts,
+    vault_name: Option<&str>,
+    identity_name: Option<&str>,
+) -> miette::Result<(VaultState, IdentityState)> {
     // Get vault specified in the argument, or get the default
     let vault_state = opts.state.create_vault_state(vault_name).await?;
 
+    if let Ok(identity_state) = opts.state.identities.get_or_default(identity_name) {
+        return Ok((vault_state, identity_state));
+    }
+
     // create an identity for the node
     let identity = opts
         .state
@@ -141,16 +258,7 @@ pub async fn init_node_state(
         .state
         .create_identity_state(&identity.identifier(), identity_name)
         .await?;
-
-    // Create the node with the given vault and identity
-    let node_config = cli_state::NodeConfigBuilder::default()
-        .vault(vault_state.path().clone())
-        .identity(identity_state.path().clone())
-        .build(&opts.state)?;
-    opts.state.nodes.overwrite(node_name, node_config)?;
-
-    info!(name=%node_name, "node state initialized");
-    Ok(())
+    Ok((vault_state, identity_state))
 }
 
 pub async fn delete_embedded_node(opts: &CommandGlobalOpts, name: &str) {
@@ -201,6 +309,7 @@ pub fn spawn_node(
     credential: Option<&String>,
     trust_context: Option<&PathBuf>,
     project_name: Option<&String>,
+    watch_startup_config: bool,
     logging_to_file: bool,
 ) -> miette::Result<()> {
     let mut args = vec![
@@ -277,6 +386,10 @@ pub fn spawn_node(
         args.push(project_name.to_string());
     }
 
+    if watch_startup_config {
+        args.push("--watch-startup-config".to_string());
+    }
+
     args.push(name.to_owned());
 
     run_ockam(opts, name, args, logging_to_file)
@@ -0,0 +1,130 @@
+use std::time::{Duration, Instant};
+
+use clap::Args;
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+use ockam::{Address, Context, TcpTransport};
+use ockam_api::nodes::models::base::{NodeRunStatus, NodeStatus};
+use tracing::trace;
+
+use crate::node::get_node_name;
+use crate::util::{api, connect_to_node, extract_address_value, node_rpc, RpcBuilder};
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/wait/long_about.txt");
+const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/wait/after_long_help.txt");
+
+/// Duration between two checks of the node status
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Maximum duration of each check of the node status, a node under load can take longer
+/// than the poll interval to respond
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Wait until a node is ready
+#[derive(Clone, Debug, Args)]
+#[command(
+    long_about = docs::about(LONG_ABOUT),
+    before_help = docs::before_help(PREVIEW_TAG),
+    after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct WaitCommand {
+    /// Name of the node to wait for
+    #[arg(long)]
+    name: Option<String>,
+
+    /// Maximum duration to wait for the node, in seconds
+    #[arg(long, default_value = "30")]
+    timeout: u64,
+}
+
+impl WaitCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, WaitCommand),
+) -> miette::Result<()> {
+    let node_name = get_node_name(&opts.state, &cmd.name);
+    let node_name = extract_address_value(&node_name)?;
+    let tcp = TcpTransport::create(&ctx).await.into_diagnostic()?;
+    let deadline = Instant::now() + Duration::from_secs(cmd.timeout);
+    // the same connection is used for all the checks, until it fails
+    let mut connection = None;
+    let ready = loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break false;
+        }
+        if is_node_ready(&ctx, &opts, &tcp, &node_name, &mut connection, remaining).await {
+            break true;
+        }
+        tokio::time::sleep(POLL_INTERVAL.min(remaining)).await;
+    };
+    if let Some(connection) = connection {
+        let _ = tcp.disconnect(connection).await;
+    }
+    if !ready {
+        return Err(miette!(
+            "The node '{}' is not ready after {} seconds",
+            node_name,
+            cmd.timeout
+        ));
+    }
+
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!("The node '{node_name}' is ready"))
+        .machine(&node_name)
+        .json(serde_json::json!({ "node": { "name": &node_name, "ready": true } }))
+        .write_line()?;
+    Ok(())
+}
+
+/// Return true if the node responds to its API and its startup configuration is loaded.
+/// A node which doesn't exist yet, or hasn't started its API yet, is not ready.
+///
+/// The connection to the node is opened if there is none, and closed if the check fails
+async fn is_node_ready(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    tcp: &TcpTransport,
+    node_name: &str,
+    connection: &mut Option<Address>,
+    remaining: Duration,
+) -> bool {
+    let sender_address = match connection {
+        Some(sender_address) => sender_address.clone(),
+        None => match connect_to_node(tcp, opts, node_name).await {
+            Ok(sender_address) => connection.insert(sender_address).clone(),
+            Err(e) => {
+                trace!(%node_name, %e, "the node doesn't accept connections yet");
+                return false;
+            }
+        },
+    };
+    let mut rpc = RpcBuilder::new(ctx, opts, node_name)
+        .connection(&sender_address)
+        .build();
+    if let Err(e) = rpc
+        .request_with_timeout(api::query_status(), REQUEST_TIMEOUT.min(remaining))
+        .await
+    {
+        trace!(%node_name, %e, "the node doesn't respond yet");
+        if let Some(connection) = connection.take() {
+            let _ = tcp.disconnect(connection).await;
+        }
+        return false;
+    }
+    match rpc.parse_response_body::<NodeStatus>() {
+        Ok(status) => {
+            trace!(%node_name, status = %status.status, "node status");
+            status.run_status() != Some(NodeRunStatus::Starting)
+        }
+        Err(_) => false,
+    }
+}
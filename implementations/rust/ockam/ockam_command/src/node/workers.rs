@@ -0,0 +1,87 @@
+use clap::Args;
+use colorful::Colorful;
+use miette::miette;
+use ockam::Context;
+use ockam_api::cli_state::StateDirTrait;
+use ockam_api::nodes::models::workers::WorkerList;
+
+use crate::node::get_node_name;
+use crate::terminal::OckamColor;
+use crate::util::{api, extract_address_value, node_rpc, Rpc};
+use crate::{docs, fmt_log, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/workers/long_about.txt");
+const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/workers/after_long_help.txt");
+
+/// List the workers of a node, and optionally stop the stale ones
+#[derive(Clone, Debug, Args)]
+#[command(
+    long_about = docs::about(LONG_ABOUT),
+    before_help = docs::before_help(PREVIEW_TAG),
+    after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct WorkersCommand {
+    /// Name of the node to list the workers of.
+    node_name: Option<String>,
+
+    /// Stop the workers left running by removed outlets, inlets and secure channels.
+    #[arg(long)]
+    prune: bool,
+}
+
+impl WorkersCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, WorkersCommand),
+) -> miette::Result<()> {
+    let node_name = get_node_name(&opts.state, &cmd.node_name);
+    let node_name = extract_address_value(&node_name)?;
+    if !opts.state.nodes.get(&node_name)?.is_running() {
+        return Err(miette!("The node '{}' is not running", node_name));
+    }
+
+    let mut rpc = Rpc::background(&ctx, &opts, &node_name)?;
+    if cmd.prune {
+        rpc.request(api::prune_stale_workers()).await?;
+        let pruned: WorkerList = rpc.parse_response_body()?;
+        opts.terminal.write_line(&fmt_log!(
+            "Stopped {} stale workers on {}",
+            pruned.list.len(),
+            node_name
+                .to_string()
+                .color(OckamColor::PrimaryResource.color())
+        ))?;
+    }
+
+    rpc.request(api::list_workers()).await?;
+    let workers: WorkerList = rpc.parse_response_body()?;
+    let plain = opts.terminal.build_list(
+        &workers.list,
+        &format!("Workers on {node_name}"),
+        &format!("No workers found on {node_name}."),
+    )?;
+    let machine = workers
+        .list
+        .iter()
+        .map(|w| w.addr.clone())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let json: Vec<_> = workers
+        .list
+        .iter()
+        .map(|w| serde_json::json!({ "address": w.addr, "kind": w.kind, "stale": w.stale }))
+        .collect();
+    opts.terminal
+        .stdout()
+        .plain(plain)
+        .machine(machine)
+        .json(serde_json::json!(json))
+        .write_line()?;
+    Ok(())
+}
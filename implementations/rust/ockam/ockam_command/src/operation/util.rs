@@ -1,7 +1,8 @@
+use std::time::Duration;
+
 use miette::miette;
-use tokio_retry::strategy::FixedInterval;
-use tokio_retry::Retry;
 
+use ockam_api::backoff::{retry, BackoffPolicy};
 use ockam_api::cloud::operation::Operation;
 use ockam_api::cloud::ORCHESTRATOR_AWAIT_TIMEOUT_MS;
 
@@ -15,15 +16,17 @@ pub async fn check_for_completion<'a>(
     api_node: &str,
     operation_id: &str,
 ) -> miette::Result<()> {
-    let retry_strategy =
-        FixedInterval::from_millis(5000).take(ORCHESTRATOR_AWAIT_TIMEOUT_MS / 5000);
+    let retry_policy = BackoffPolicy::fixed(
+        Duration::from_millis(5000),
+        ORCHESTRATOR_AWAIT_TIMEOUT_MS / 5000 + 1,
+    );
 
     let spinner_option = opts.terminal.progress_spinner();
     if let Some(spinner) = spinner_option.as_ref() {
         spinner.set_message("Configuring project...");
     }
     let route = CloudOpts::route();
-    let operation = Retry::spawn(retry_strategy.clone(), || async {
+    let operation = retry(retry_policy, || async {
         let mut rpc = RpcBuilder::new(ctx, opts, api_node).build();
 
         // Handle the operation show request result
@@ -21,6 +21,10 @@ pub struct CreateCommand {
 
     #[arg(short, long)]
     expression: Expr,
+
+    /// Why the policy exists. The description is not used to evaluate the policy
+    #[arg(long)]
+    description: Option<String>,
 }
 
 impl CreateCommand {
@@ -43,7 +47,7 @@ async fn run_impl(
 ) -> miette::Result<()> {
     let at = get_node_name(&opts.state, &cmd.at);
     let node_name = parse_node_name(&at)?;
-    let bdy = Policy::new(cmd.expression);
+    let bdy = Policy::new(cmd.expression).with_description(cmd.description);
     let req = Request::post(policy_path(&cmd.resource, &cmd.action)).body(bdy);
     let mut rpc = Rpc::background(ctx, &opts, &node_name)?;
     rpc.request(req).await?;
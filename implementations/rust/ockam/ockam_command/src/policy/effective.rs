@@ -0,0 +1,94 @@
+use crate::node::get_node_name;
+use crate::policy::policy_path;
+use crate::util::{node_rpc, parse_node_name, Rpc};
+use crate::{fmt_log, CommandGlobalOpts};
+use clap::Args;
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+use ockam::Context;
+use ockam_abac::{Action, Resource};
+use ockam_api::cli_state::StateDirTrait;
+use ockam_api::nodes::models::policy::{EffectivePolicy, PolicyOrigin};
+use ockam_core::api::Request;
+use serde_json::json;
+
+use crate::terminal::OckamColor;
+
+/// Show the policy applied to a resource and action,
+/// which can be inherited from a parent of the resource
+#[derive(Clone, Debug, Args)]
+pub struct EffectiveCommand {
+    resource: Resource,
+
+    action: Action,
+
+    #[arg(long, display_order = 900, id = "NODE_NAME")]
+    at: Option<String>,
+}
+
+impl EffectiveCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(rpc, (options, self));
+    }
+}
+
+async fn rpc(
+    mut ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, EffectiveCommand),
+) -> miette::Result<()> {
+    run_impl(&mut ctx, opts, cmd).await
+}
+
+async fn run_impl(
+    ctx: &mut Context,
+    opts: CommandGlobalOpts,
+    cmd: EffectiveCommand,
+) -> miette::Result<()> {
+    let at = get_node_name(&opts.state, &cmd.at);
+    let node_name = parse_node_name(&at)?;
+    if !opts.state.nodes.get(&node_name)?.is_running() {
+        return Err(miette!("The node '{}' is not running", &node_name));
+    }
+
+    let path = format!("{}/effective", policy_path(&cmd.resource, &cmd.action));
+    let mut rpc = Rpc::background(ctx, &opts, &node_name)?;
+    rpc.request(Request::get(path)).await?;
+    let policy: EffectivePolicy = rpc.parse_response_body()?;
+
+    let json = json!({
+        "resource": cmd.resource.as_str(),
+        "action": cmd.action.as_str(),
+        "origin": policy.origin().to_string(),
+        "defined_on": policy.defined_on().map(|r| r.as_str()),
+        "expression": policy.expression().to_string(),
+    });
+    opts.terminal
+        .stdout()
+        .plain(plain_output(&cmd, &policy))
+        .machine(policy.expression().to_string())
+        .json(serde_json::to_string_pretty(&json).into_diagnostic()?)
+        .write_line()?;
+    Ok(())
+}
+
+fn plain_output(cmd: &EffectiveCommand, policy: &EffectivePolicy) -> String {
+    let origin = match (policy.origin(), policy.defined_on()) {
+        (PolicyOrigin::Exact, _) => "defined on the resource".to_string(),
+        (PolicyOrigin::Parent, Some(parent)) => format!(
+            "inherited from {}",
+            parent.as_str().color(OckamColor::PrimaryResource.color())
+        ),
+        _ => "no policy is defined, the access is denied".to_string(),
+    };
+    let mut output = fmt_log!(
+        "Policy of {} for {}: {}",
+        cmd.resource
+            .as_str()
+            .color(OckamColor::PrimaryResource.color()),
+        cmd.action.as_str(),
+        policy.expression()
+    );
+    output.push('\n');
+    output.push_str(&fmt_log!("Origin: {} ({origin})", policy.origin()));
+    output
+}
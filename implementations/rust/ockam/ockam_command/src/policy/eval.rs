@@ -0,0 +1,155 @@
+use crate::node::get_node_name;
+use crate::policy::policy_path;
+use crate::util::{node_rpc, parse_node_name, Rpc};
+use crate::{fmt_err, fmt_log, fmt_ok, CommandGlobalOpts};
+use clap::Args;
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+use ockam::identity::{identities, IdentityIdentifier};
+use ockam::Context;
+use ockam_abac::{Action, Resource};
+use ockam_api::cli_state::StateDirTrait;
+use ockam_api::nodes::models::policy::{PolicyEvaluation, PolicyEvaluationRequest};
+use ockam_core::api::Request;
+use serde_json::json;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Check if an identity would be allowed to perform an action on a resource,
+/// without performing it
+#[derive(Clone, Debug, Args)]
+pub struct EvalCommand {
+    #[arg(long, display_order = 900, id = "NODE_NAME")]
+    at: Option<String>,
+
+    /// Identifier of the identity, or path to a file containing its identifier
+    /// or its exported change history
+    #[arg(long, value_name = "FILE_OR_IDENTIFIER")]
+    identity: String,
+
+    #[arg(short, long)]
+    resource: Resource,
+
+    #[arg(short, long)]
+    action: Action,
+
+    /// Print the value of each evaluated sub-expression
+    #[arg(long)]
+    trace: bool,
+}
+
+impl EvalCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(rpc, (options, self));
+    }
+}
+
+async fn rpc(
+    mut ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, EvalCommand),
+) -> miette::Result<()> {
+    run_impl(&mut ctx, opts, cmd).await
+}
+
+async fn run_impl(
+    ctx: &mut Context,
+    opts: CommandGlobalOpts,
+    cmd: EvalCommand,
+) -> miette::Result<()> {
+    let at = get_node_name(&opts.state, &cmd.at);
+    let node_name = parse_node_name(&at)?;
+    if !opts.state.nodes.get(&node_name)?.is_running() {
+        return Err(miette!("The node '{}' is not running", &node_name));
+    }
+
+    let identifier = identity_identifier(&cmd.identity).await?;
+    let path = format!("{}/evaluate", policy_path(&cmd.resource, &cmd.action));
+    let req = Request::get(path).body(PolicyEvaluationRequest::new(identifier.clone(), cmd.trace));
+    let mut rpc = Rpc::background(ctx, &opts, &node_name)?;
+    rpc.request(req).await?;
+    let evaluation: PolicyEvaluation = rpc.parse_response_body()?;
+
+    let json = json!({
+        "identity": identifier.to_string(),
+        "resource": cmd.resource.as_str(),
+        "action": cmd.action.as_str(),
+        "allowed": evaluation.allowed(),
+        "reason": evaluation.reason(),
+        "expression": evaluation.expression().map(|e| e.to_string()),
+        "trace": evaluation.trace().iter().map(|s| json!({
+            "depth": s.depth(),
+            "expression": s.expression().to_string(),
+            "value": s.value().map(|v| v.to_string()),
+            "error": s.error(),
+        })).collect::<Vec<_>>(),
+    });
+    opts.terminal
+        .stdout()
+        .plain(plain_output(&cmd, &identifier, &evaluation))
+        .machine(evaluation.allowed().to_string())
+        .json(serde_json::to_string_pretty(&json).into_diagnostic()?)
+        .write_line()?;
+    Ok(())
+}
+
+/// Get an identity identifier, either directly from the argument or from the file it points to.
+/// The file can contain an identifier or a hex-encoded identity change history.
+async fn identity_identifier(identity: &str) -> miette::Result<IdentityIdentifier> {
+    if let Ok(identifier) = IdentityIdentifier::from_str(identity) {
+        return Ok(identifier);
+    }
+    let path = Path::new(identity);
+    if !path.is_file() {
+        return Err(miette!(
+            "{identity} is neither an identity identifier nor a file"
+        ));
+    }
+    let contents = std::fs::read_to_string(path).into_diagnostic()?;
+    let contents = contents.trim();
+    if let Ok(identifier) = IdentityIdentifier::from_str(contents) {
+        return Ok(identifier);
+    }
+    let change_history = hex::decode(contents)
+        .map_err(|_| miette!("The file {identity} does not contain a valid identity"))?;
+    let identity = identities()
+        .identities_creation()
+        .decode_identity(&change_history)
+        .await
+        .into_diagnostic()?;
+    Ok(identity.identifier())
+}
+
+fn plain_output(
+    cmd: &EvalCommand,
+    identifier: &IdentityIdentifier,
+    evaluation: &PolicyEvaluation,
+) -> String {
+    let request = format!(
+        "{identifier} to {} on {}",
+        cmd.action.as_str(),
+        cmd.resource.as_str()
+    );
+    let mut output = if evaluation.allowed() {
+        fmt_ok!("Allowed: {request}")
+    } else {
+        let reason = evaluation.reason().unwrap_or("access denied");
+        fmt_err!("Denied: {request} ({reason})")
+    };
+    if let Some(expression) = evaluation.expression() {
+        output.push('\n');
+        output.push_str(&fmt_log!("Policy: {expression}"));
+    }
+    if cmd.trace {
+        for step in evaluation.trace() {
+            let indent = "  ".repeat(step.depth());
+            let value = match (step.value(), step.error()) {
+                (Some(value), _) => value.to_string(),
+                (None, Some(error)) => format!("error: {error}"),
+                (None, None) => String::new(),
+            };
+            output.push('\n');
+            output.push_str(&fmt_log!("{indent}{} => {value}", step.expression()));
+        }
+    }
+    output
+}
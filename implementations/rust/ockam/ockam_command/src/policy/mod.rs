@@ -1,8 +1,12 @@
 mod create;
 mod delete;
+mod effective;
+mod eval;
 mod list;
 mod show;
 use crate::policy::delete::DeleteCommand;
+use crate::policy::effective::EffectiveCommand;
+use crate::policy::eval::EvalCommand;
 use crate::policy::list::ListCommand;
 use crate::policy::show::ShowCommand;
 use crate::{policy::create::CreateCommand, util::Rpc};
@@ -28,6 +32,8 @@ pub enum PolicySubcommand {
     Show(ShowCommand),
     Delete(DeleteCommand),
     List(ListCommand),
+    Eval(EvalCommand),
+    Effective(EffectiveCommand),
 }
 
 impl PolicyCommand {
@@ -37,6 +43,8 @@ impl PolicyCommand {
             PolicySubcommand::Show(c) => c.run(opts),
             PolicySubcommand::Delete(c) => c.run(opts),
             PolicySubcommand::List(c) => c.run(opts),
+            PolicySubcommand::Eval(c) => c.run(opts),
+            PolicySubcommand::Effective(c) => c.run(opts),
         }
     }
 }
@@ -43,5 +43,8 @@ async fn run_impl(
     rpc.request(req).await?;
     let pol: Policy = rpc.parse_response_body()?;
     println!("{}", pol.expression());
+    if let Some(description) = pol.description() {
+        println!("# {description}");
+    }
     Ok(())
 }
@@ -0,0 +1,405 @@
+use std::net::SocketAddr;
+
+use clap::Args;
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+use ockam::Context;
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::terminal::OckamColor;
+use crate::util::node_rpc;
+use crate::util::parsers::socket_addr_parser;
+use crate::{docs, fmt_log, fmt_ok, CommandGlobalOpts};
+
+const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/benchmark/after_long_help.txt");
+
+/// Measure the throughput and the latency of a portal, by sending messages through its TCP Inlet
+/// and reading them back once echoed by the target of its TCP Outlet
+#[derive(Clone, Debug, Args)]
+#[command(
+before_help = docs::before_help(PREVIEW_TAG),
+after_long_help = docs::after_help(AFTER_LONG_HELP))]
+pub struct BenchmarkCommand {
+    /// Address of the TCP Inlet of the portal
+    #[arg(long, value_name = "SOCKET_ADDRESS", value_parser = socket_addr_parser)]
+    inlet: SocketAddr,
+
+    /// Duration of the benchmark, in seconds
+    #[arg(long, value_name = "SECONDS", default_value_t = 10)]
+    duration: u64,
+
+    /// Address of an echo server started for the duration of the benchmark, which must be the
+    /// target of the TCP Outlet of the portal. When omitted, the target of the outlet must echo
+    /// the data it receives
+    #[arg(long, value_name = "SOCKET_ADDRESS", value_parser = socket_addr_parser)]
+    echo: Option<SocketAddr>,
+
+    /// Size of the messages sent through the portal, in bytes
+    #[arg(long, value_name = "BYTES", default_value_t = 16 * 1024, value_parser = clap::value_parser!(u64).range(1..))]
+    message_size: u64,
+
+    /// Number of connections opened through the portal, each one sending its messages
+    /// one after the other
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    connections: u64,
+}
+
+impl BenchmarkCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    _ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, BenchmarkCommand),
+) -> miette::Result<()> {
+    // The echo server is stopped when it is dropped, whatever the outcome of the benchmark
+    let _echo_server = match cmd.echo {
+        Some(address) => Some(EchoServer::start(address).await?),
+        None => None,
+    };
+    let benchmark = Benchmark {
+        inlet: cmd.inlet,
+        duration: Duration::from_secs(cmd.duration),
+        message_size: cmd.message_size as usize,
+        connections: cmd.connections as usize,
+    };
+
+    opts.terminal.write_line(&fmt_log!(
+        "Benchmarking the portal of the TCP Inlet at {} for {} seconds, press Ctrl-C to stop",
+        cmd.inlet
+            .to_string()
+            .color(OckamColor::PrimaryResource.color()),
+        cmd.duration
+    ))?;
+    let (stop, stopped) = watch::channel(false);
+    let run = benchmark.run(stopped);
+    tokio::pin!(run);
+    let report = tokio::select! {
+        report = &mut run => report,
+        _ = tokio::signal::ctrl_c() => {
+            // the connections are closed before the measures of the benchmark are reported
+            let _ = stop.send(true);
+            run.await
+        }
+    }?;
+
+    opts.terminal
+        .stdout()
+        .plain(plain_output(&report))
+        .machine(report.throughput_bytes_per_second)
+        .json(serde_json::to_string_pretty(&report).into_diagnostic()?)
+        .write_line()?;
+    Ok(())
+}
+
+fn plain_output(report: &BenchmarkReport) -> String {
+    let mut output = fmt_ok!(
+        "{} messages of {} bytes sent through the portal in {:.1} seconds, over {} connections{}\n",
+        report.round_trips,
+        report.message_size,
+        report.duration_ms as f64 / 1000.0,
+        report.connections,
+        if report.interrupted {
+            " (interrupted)"
+        } else {
+            ""
+        }
+    );
+    output.push_str(&fmt_log!(
+        "Throughput: {:.2} MB/s\n",
+        report.throughput_bytes_per_second / 1_000_000.0
+    ));
+    output.push_str(&match &report.latency {
+        Some(latency) => fmt_log!(
+            "Latency: p50 {:.3} ms, p90 {:.3} ms, p99 {:.3} ms, max {:.3} ms",
+            latency.p50_ms,
+            latency.p90_ms,
+            latency.p99_ms,
+            latency.max_ms
+        ),
+        None => fmt_log!("Latency: no message was echoed"),
+    });
+    output
+}
+
+/// Parameters of a benchmark
+#[derive(Clone, Copy, Debug)]
+struct Benchmark {
+    inlet: SocketAddr,
+    duration: Duration,
+    message_size: usize,
+    connections: usize,
+}
+
+impl Benchmark {
+    /// Send messages on each connection until the end of the benchmark, or until it is stopped,
+    /// and return the measures of all the connections.
+    /// All the connections are closed when an error occurs on one of them
+    async fn run(self, stop: watch::Receiver<bool>) -> miette::Result<BenchmarkReport> {
+        let start = Instant::now();
+        let deadline = start + self.duration;
+        let mut connections = JoinSet::new();
+        for _ in 0..self.connections {
+            let stream = TcpStream::connect(self.inlet).await.map_err(|e| {
+                miette!("Failed to connect to the TCP Inlet at {}: {e}", self.inlet)
+            })?;
+            connections.spawn(round_trips(
+                stream,
+                self.message_size,
+                deadline,
+                stop.clone(),
+            ));
+        }
+
+        let mut latencies = vec![];
+        while let Some(res) = connections.join_next().await {
+            match res {
+                Ok(Ok(connection_latencies)) => latencies.extend(connection_latencies),
+                Ok(Err(e)) => return Err(miette!("A connection through the portal failed: {e}")),
+                Err(e) => return Err(miette!("A connection through the portal stopped: {e}")),
+            }
+        }
+        let interrupted = *stop.borrow();
+        Ok(BenchmarkReport::new(
+            &self,
+            start.elapsed(),
+            latencies,
+            interrupted,
+        ))
+    }
+}
+
+/// Send messages on a connection and read them back until the deadline, or until the benchmark
+/// is stopped, and return the duration of each round trip
+async fn round_trips(
+    mut stream: TcpStream,
+    message_size: usize,
+    deadline: Instant,
+    mut stop: watch::Receiver<bool>,
+) -> std::io::Result<Vec<Duration>> {
+    stream.set_nodelay(true)?;
+    let message: Vec<u8> = (0..message_size).map(|i| i as u8).collect();
+    let mut echoed = vec![0u8; message_size];
+    let mut latencies = vec![];
+    loop {
+        tokio::select! {
+            biased;
+            // the benchmark is only ever stopped, so any change stops the connection
+            _ = stop.changed() => break,
+            _ = tokio::time::sleep_until(deadline) => break,
+            latency = round_trip(&mut stream, &message, &mut echoed) => latencies.push(latency?),
+        }
+    }
+    let _ = stream.shutdown().await;
+    Ok(latencies)
+}
+
+/// Send a message and read it back. The message is written while the echoed data is read,
+/// so that large messages don't fill the buffers of the connection
+async fn round_trip(
+    stream: &mut TcpStream,
+    message: &[u8],
+    echoed: &mut [u8],
+) -> std::io::Result<Duration> {
+    let start = Instant::now();
+    let (mut reader, mut writer) = stream.split();
+    tokio::try_join!(writer.write_all(message), reader.read_exact(echoed))?;
+    if echoed != message {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "the data read back differs from the data sent",
+        ));
+    }
+    Ok(start.elapsed())
+}
+
+/// Measures of a benchmark
+#[derive(Debug, Serialize)]
+struct BenchmarkReport {
+    inlet: SocketAddr,
+    /// Duration of the benchmark, shorter than requested if it was interrupted
+    duration_ms: u64,
+    connections: usize,
+    message_size: usize,
+    /// Number of messages sent and read back
+    round_trips: usize,
+    /// Number of bytes sent through the portal, and read back, per second
+    throughput_bytes_per_second: f64,
+    /// Percentiles of the round trip durations, if at least one message was read back
+    latency: Option<LatencyPercentiles>,
+    interrupted: bool,
+}
+
+impl BenchmarkReport {
+    fn new(
+        benchmark: &Benchmark,
+        elapsed: Duration,
+        mut latencies: Vec<Duration>,
+        interrupted: bool,
+    ) -> Self {
+        let bytes = (latencies.len() * benchmark.message_size) as f64;
+        latencies.sort();
+        Self {
+            inlet: benchmark.inlet,
+            duration_ms: elapsed.as_millis() as u64,
+            connections: benchmark.connections,
+            message_size: benchmark.message_size,
+            round_trips: latencies.len(),
+            throughput_bytes_per_second: bytes / elapsed.as_secs_f64().max(f64::EPSILON),
+            latency: LatencyPercentiles::new(&latencies),
+            interrupted,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct LatencyPercentiles {
+    p50_ms: f64,
+    p90_ms: f64,
+    p99_ms: f64,
+    max_ms: f64,
+}
+
+impl LatencyPercentiles {
+    /// Compute the percentiles of sorted durations, with the nearest-rank method
+    fn new(sorted: &[Duration]) -> Option<Self> {
+        let max = *sorted.last()?;
+        let percentile = |p: f64| {
+            let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+            as_millis(sorted[rank.clamp(1, sorted.len()) - 1])
+        };
+        Some(Self {
+            p50_ms: percentile(50.0),
+            p90_ms: percentile(90.0),
+            p99_ms: percentile(99.0),
+            max_ms: as_millis(max),
+        })
+    }
+}
+
+fn as_millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Server sending back the data it receives, started as the target of the TCP Outlet of the
+/// benchmarked portal. The server and its connections are stopped when it is dropped
+struct EchoServer {
+    address: SocketAddr,
+    handle: JoinHandle<()>,
+}
+
+impl EchoServer {
+    async fn start(address: SocketAddr) -> miette::Result<Self> {
+        let listener = TcpListener::bind(address)
+            .await
+            .map_err(|e| miette!("Failed to start the echo server at {address}: {e}"))?;
+        let address = listener.local_addr().into_diagnostic()?;
+        let handle = tokio::spawn(async move {
+            // the connections are aborted when the set is dropped, with the server
+            let mut connections = JoinSet::new();
+            loop {
+                match listener.accept().await {
+                    Ok((mut stream, _)) => {
+                        connections.spawn(async move {
+                            let (mut reader, mut writer) = stream.split();
+                            let _ = tokio::io::copy(&mut reader, &mut writer).await;
+                        });
+                    }
+                    Err(e) => warn!(%e, "the echo server failed to accept a connection"),
+                }
+            }
+        });
+        Ok(Self { address, handle })
+    }
+}
+
+impl Drop for EchoServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ockam::{TcpInletOptions, TcpOutletOptions, TcpTransport};
+    use ockam_core::route;
+
+    use super::*;
+
+    #[ockam_macros::test(crate = "ockam", timeout = 10000)]
+    async fn a_loopback_portal_is_benchmarked(ctx: &mut Context) -> ockam::Result<()> {
+        let echo_server = EchoServer::start(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .await
+            .unwrap();
+        let tcp = TcpTransport::create(ctx).await?;
+        tcp.create_outlet(
+            "outlet",
+            echo_server.address.to_string(),
+            TcpOutletOptions::new(),
+        )
+        .await?;
+        let (inlet, _) = tcp
+            .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+            .await?;
+
+        let benchmark = Benchmark {
+            inlet,
+            duration: Duration::from_millis(500),
+            message_size: 64 * 1024,
+            connections: 2,
+        };
+        let (_stop, stopped) = watch::channel(false);
+        let report = benchmark.run(stopped).await.unwrap();
+        assert!(report.round_trips > 0);
+        assert!(report.throughput_bytes_per_second > 0.0);
+        assert!(report.duration_ms >= 500);
+        assert!(!report.interrupted);
+        let latency = report.latency.unwrap();
+        assert!(latency.p50_ms > 0.0);
+        assert!(latency.p50_ms <= latency.p90_ms);
+        assert!(latency.p90_ms <= latency.p99_ms);
+        assert!(latency.p99_ms <= latency.max_ms);
+
+        // an interrupted benchmark returns the measures taken so far
+        let benchmark = Benchmark {
+            duration: Duration::from_secs(60),
+            ..benchmark
+        };
+        let (stop, stopped) = watch::channel(false);
+        let run = tokio::spawn(benchmark.run(stopped));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        stop.send(true).unwrap();
+        let report = run.await.unwrap().unwrap();
+        assert!(report.interrupted);
+        assert!(report.round_trips > 0);
+        assert!(report.duration_ms < 60_000);
+
+        drop(echo_server);
+        ctx.stop().await?;
+        Ok(())
+    }
+
+    #[test]
+    fn the_latency_percentiles_use_the_nearest_rank() {
+        let sorted: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+        let latency = LatencyPercentiles::new(&sorted).unwrap();
+        assert_eq!(latency.p50_ms, 50.0);
+        assert_eq!(latency.p90_ms, 90.0);
+        assert_eq!(latency.p99_ms, 99.0);
+        assert_eq!(latency.max_ms, 100.0);
+
+        let latency = LatencyPercentiles::new(&[Duration::from_millis(3)]).unwrap();
+        assert_eq!(latency.p50_ms, 3.0);
+        assert_eq!(latency.max_ms, 3.0);
+        assert!(LatencyPercentiles::new(&[]).is_none());
+    }
+}
@@ -0,0 +1,34 @@
+mod benchmark;
+
+use crate::{docs, CommandGlobalOpts};
+use benchmark::BenchmarkCommand;
+use clap::{Args, Subcommand};
+
+const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/after_long_help.txt");
+
+/// Diagnose Portals
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    long_about = docs::about(LONG_ABOUT),
+    after_long_help = docs::after_help(AFTER_LONG_HELP),
+)]
+pub struct PortalCommand {
+    #[command(subcommand)]
+    subcommand: PortalSubCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum PortalSubCommand {
+    Benchmark(BenchmarkCommand),
+}
+
+impl PortalCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            PortalSubCommand::Benchmark(c) => c.run(options),
+        }
+    }
+}
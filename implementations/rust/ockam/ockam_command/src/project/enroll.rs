@@ -48,7 +48,8 @@ pub struct EnrollCommand {
     trust_opts: TrustContextOpts,
 }
 
-fn parse_enroll_ticket(input: &str) -> Result<EnrollmentTicket> {
+/// Parse an enrollment ticket given either as a file path or as a hex-encoded string
+pub fn parse_enroll_ticket(input: &str) -> Result<EnrollmentTicket> {
     let decoded = match std::fs::read_to_string(input) {
         Ok(s) => hex::decode(s)?,
         Err(_) => hex::decode(input)?,
@@ -1,5 +1,5 @@
 use clap::Args;
-use miette::miette;
+use miette::{miette, IntoDiagnostic};
 
 use ockam::identity::IdentityIdentifier;
 use ockam::Context;
@@ -17,6 +17,7 @@ use crate::util::{node_rpc, RpcBuilder};
 use crate::CommandGlobalOpts;
 use ockam_api::cli_state::{StateDirTrait, StateItemTrait};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 #[derive(Clone, Debug, Args)]
 pub struct InfoCommand {
@@ -71,6 +72,41 @@ impl TryFrom<ProjectLookup> for ProjectInfo<'_> {
     }
 }
 
+impl ProjectInfo<'_> {
+    /// Names of the fields which can be selected with [`ProjectInfo::select_fields`]
+    pub const FIELDS: [&'static str; 7] = [
+        "id",
+        "name",
+        "identity",
+        "access_route",
+        "authority_access_route",
+        "authority_identity",
+        "okta_config",
+    ];
+
+    /// Return an error listing the valid field names if one of the names is unknown
+    pub fn check_field_names(names: &[String]) -> miette::Result<()> {
+        match names.iter().find(|n| !Self::FIELDS.contains(&n.as_str())) {
+            Some(name) => Err(miette!(
+                "Unknown project field '{name}'. The valid fields are: {}",
+                Self::FIELDS.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Return the values of the given fields, in the order of the names.
+    /// The value of a field which is not set is `null`
+    pub fn select_fields(&self, names: &[String]) -> miette::Result<Vec<(String, Value)>> {
+        Self::check_field_names(names)?;
+        let value = serde_json::to_value(self).into_diagnostic()?;
+        Ok(names
+            .iter()
+            .map(|n| (n.clone(), value.get(n).cloned().unwrap_or(Value::Null)))
+            .collect())
+    }
+}
+
 impl<'a> From<Project> for ProjectInfo<'a> {
     fn from(p: Project) -> Self {
         Self {
@@ -141,3 +177,40 @@ async fn run_impl(
     delete_embedded_node(&opts, rpc.node_name()).await;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_project_fields() {
+        let info = ProjectInfo::from(Project {
+            id: "p1".to_string(),
+            name: "default".to_string(),
+            access_route: "/dnsaddr/p1.ockam.network/tcp/4000/service/api".to_string(),
+            ..Default::default()
+        });
+        let names = vec!["access_route".to_string(), "authority_identity".to_string()];
+        assert_eq!(
+            info.select_fields(&names).unwrap(),
+            vec![
+                (
+                    "access_route".to_string(),
+                    Value::from("/dnsaddr/p1.ockam.network/tcp/4000/service/api")
+                ),
+                ("authority_identity".to_string(), Value::Null)
+            ]
+        );
+
+        // every field can be selected, even the ones which are skipped when they are not set
+        let all: Vec<String> = ProjectInfo::FIELDS.iter().map(|f| f.to_string()).collect();
+        assert_eq!(info.select_fields(&all).unwrap().len(), all.len());
+
+        let error = info.select_fields(&["route".to_string()]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unknown project field 'route'. The valid fields are: id, name, identity, \
+             access_route, authority_access_route, authority_identity, okta_config"
+        );
+    }
+}
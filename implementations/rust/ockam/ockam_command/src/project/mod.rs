@@ -8,6 +8,7 @@ mod show;
 mod ticket;
 pub mod util;
 
+pub use enroll::parse_enroll_ticket;
 pub use info::ProjectInfo;
 
 use clap::{Args, Subcommand};
@@ -3,9 +3,11 @@ use clap::Args;
 use ockam::Context;
 use ockam_api::cli_state::{StateDirTrait, StateItemTrait};
 use ockam_api::cloud::project::Project;
+use serde_json::{Map, Value};
 
-use crate::node::util::{delete_embedded_node, start_embedded_node};
+use crate::node::util::{delete_embedded_node, start_embedded_node_with_existing_identity};
 use crate::project::util::refresh_projects;
+use crate::project::ProjectInfo;
 use crate::util::api::{self, CloudOpts};
 use crate::util::{node_rpc, RpcBuilder};
 use crate::{docs, CommandGlobalOpts};
@@ -26,6 +28,11 @@ pub struct ShowCommand {
     #[arg(display_order = 1001)]
     pub name: String,
 
+    /// Only print this field of the project. Can be repeated to print several fields,
+    /// one value per line, or as a JSON object with `--output json`.
+    #[arg(long = "field", value_name = "FIELD", display_order = 1002)]
+    pub fields: Vec<String>,
+
     #[command(flatten)]
     pub cloud_opts: CloudOpts,
 }
@@ -48,8 +55,10 @@ async fn run_impl(
     opts: CommandGlobalOpts,
     cmd: ShowCommand,
 ) -> miette::Result<()> {
+    ProjectInfo::check_field_names(&cmd.fields)?;
     let controller_route = &CloudOpts::route();
-    let node_name = start_embedded_node(ctx, &opts, None).await?;
+    // showing a project must not create an identity
+    let node_name = start_embedded_node_with_existing_identity(ctx, &opts).await?;
 
     // Lookup project
     let id = match &opts.state.projects.get(&cmd.name) {
@@ -64,10 +73,41 @@ async fn run_impl(
     let mut rpc = RpcBuilder::new(ctx, &opts, &node_name).build();
     rpc.request(api::project::show(&id, controller_route))
         .await?;
-    let project = rpc.parse_and_print_response::<Project>()?;
+    let project: Project = rpc.parse_response_body()?;
+    if cmd.fields.is_empty() {
+        rpc.print_response(&project)?;
+    } else {
+        print_fields(&opts, &project, &cmd.fields)?;
+    }
     opts.state
         .projects
         .overwrite(&project.name, project.clone())?;
     delete_embedded_node(&opts, rpc.node_name()).await;
     Ok(())
 }
+
+fn print_fields(
+    opts: &CommandGlobalOpts,
+    project: &Project,
+    names: &[String],
+) -> miette::Result<()> {
+    let fields = ProjectInfo::from(project.clone()).select_fields(names)?;
+    let plain = fields
+        .iter()
+        .map(|(_, value)| match value {
+            Value::String(s) => s.clone(),
+            Value::Null => String::new(),
+            other => other.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let json: Map<String, Value> = fields.into_iter().collect();
+    opts.terminal
+        .clone()
+        .stdout()
+        .plain(&plain)
+        .machine(&plain)
+        .json(Value::Object(json))
+        .write_line()?;
+    Ok(())
+}
@@ -6,9 +6,11 @@ use std::collections::HashMap;
 use std::time::Duration;
 
 use miette::{miette, IntoDiagnostic};
-use ockam::identity::IdentityIdentifier;
+use ockam::identity::{IdentityIdentifier, Timestamp};
 use ockam::Context;
-use ockam_api::authenticator::direct::{DirectAuthenticatorClient, TokenIssuerClient};
+use ockam_api::authenticator::direct::{
+    DirectAuthenticatorClient, TokenIssuerClient, MAX_TOKEN_DURATION,
+};
 use ockam_api::cli_state::{CliState, StateDirTrait, StateItemTrait};
 use ockam_api::config::lookup::{ProjectAuthority, ProjectLookup};
 use ockam_api::DefaultAddress;
@@ -198,6 +200,11 @@ impl Runner {
                 .into_diagnostic()?;
 
             let ticket = EnrollmentTicket::new(token, project, trust_context);
+            // the authority only accepts the token for a limited time
+            let ticket = match Timestamp::now() {
+                Some(now) => ticket.with_expires_at(now.unix_time() + MAX_TOKEN_DURATION.as_secs()),
+                None => ticket,
+            };
             let ticket_serialized = hex::encode(serde_json::to_vec(&ticket).into_diagnostic()?);
             print!("{}", ticket_serialized)
         }
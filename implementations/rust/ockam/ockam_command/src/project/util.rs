@@ -1,13 +1,14 @@
+use std::time::Duration;
+
 use miette::Context as _;
 use miette::{miette, IntoDiagnostic};
 
 use ockam_core::api::Request;
-use tokio_retry::strategy::FixedInterval;
-use tokio_retry::Retry;
 use tracing::debug;
 
 use ockam::identity::IdentityIdentifier;
 use ockam::TcpTransport;
+use ockam_api::backoff::{retry, BackoffPolicy};
 use ockam_api::cli_state::{StateDirTrait, StateItemTrait};
 use ockam_api::cloud::project::Project;
 use ockam_api::cloud::ORCHESTRATOR_AWAIT_TIMEOUT_MS;
@@ -174,8 +175,10 @@ pub async fn check_project_readiness(
     mut project: Project,
 ) -> Result<Project> {
     // Total of 10 Mins sleep strategy with 5 second intervals between each retry
-    let retry_strategy =
-        FixedInterval::from_millis(5000).take(ORCHESTRATOR_AWAIT_TIMEOUT_MS / 5000);
+    let retry_policy = BackoffPolicy::fixed(
+        Duration::from_millis(5000),
+        ORCHESTRATOR_AWAIT_TIMEOUT_MS / 5000 + 1,
+    );
 
     // Persist project config prior to checking readiness which might take a while
     opts.state
@@ -191,7 +194,7 @@ pub async fn check_project_readiness(
     if !project.is_ready() {
         let cloud_route = &CloudOpts::route();
         let project_id = project.id.clone();
-        project = Retry::spawn(retry_strategy.clone(), || async {
+        project = retry(retry_policy, || async {
             let mut rpc = RpcBuilder::new(ctx, opts, api_node).build();
 
             // Handle the project show request result
@@ -216,7 +219,7 @@ pub async fn check_project_readiness(
             spinner.set_message("Establishing connection to the project...");
         }
 
-        Retry::spawn(retry_strategy.clone(), || async {
+        retry(retry_policy, || async {
             // Handle the reachable result, so we can provide better errors in the case a project isn't
             if let Ok(reachable) = project.is_reachable().await {
                 if reachable {
@@ -240,7 +243,7 @@ pub async fn check_project_readiness(
             .ok_or(miette!("Project identity is not set."))?
             .to_string();
 
-        Retry::spawn(retry_strategy.clone(), || async {
+        retry(retry_policy, || async {
             if let Ok(sc_addr) = create_secure_channel_to_project(
                 ctx,
                 opts,
@@ -275,7 +278,7 @@ pub async fn check_project_readiness(
         .await?
         .ok_or(miette!("Project does not have an authority defined."))?;
 
-        Retry::spawn(retry_strategy.clone(), || async {
+        retry(retry_policy, || async {
             if let Ok(sc_addr) = create_secure_channel_to_authority(
                 ctx,
                 opts,
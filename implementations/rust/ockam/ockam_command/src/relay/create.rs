@@ -5,13 +5,13 @@ use colorful::Colorful;
 use miette::Context as _;
 use miette::{miette, IntoDiagnostic};
 use ockam::identity::IdentityIdentifier;
-use ockam_multiaddr::proto::Project;
 
 use ockam::{Context, TcpTransport};
+use ockam_api::cloud::project_route::ProjectRoute;
 use ockam_api::is_local_node;
 use ockam_api::nodes::models::forwarder::{CreateForwarder, ForwarderInfo};
 use ockam_core::api::Request;
-use ockam_multiaddr::{MultiAddr, Protocol};
+use ockam_multiaddr::MultiAddr;
 use tokio::sync::Mutex;
 use tokio::try_join;
 
@@ -92,7 +92,7 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> m
 
     let send_req = async {
         let req = {
-            let body = if cmd.at.matches(0, &[Project::CODE.into()]) {
+            let body = if ProjectRoute::detect(&cmd.at)?.is_some() {
                 if cmd.authorized.is_some() {
                     return Err(
                         miette!("--authorized can not be used with project addresses").into(),
@@ -63,7 +63,7 @@ impl DeleteCommand {
                         }
 
                         // if output format is json, write json to stdout.
-                        if options.global_args.output_format == OutputFormat::Json {
+                        if options.output_format() == OutputFormat::Json {
                             let json = json!([{ "address": multiaddr.to_string() }]);
                             println!("{json}");
                         }
@@ -72,7 +72,7 @@ impl DeleteCommand {
                         // and output format is plain then write a plain info to stderr.
                         if is_tty(std::io::stderr())
                             && !options.global_args.quiet
-                            && options.global_args.output_format == OutputFormat::Plain
+                            && options.output_format() == OutputFormat::Plain
                         {
                             if options.global_args.no_color {
                                 eprintln!("\n  Deleted Secure Channel:");
@@ -96,7 +96,7 @@ impl DeleteCommand {
                         // and output format is plain then write a plain info to stderr.
                         if is_tty(std::io::stderr())
                             && !options.global_args.quiet
-                            && options.global_args.output_format == OutputFormat::Plain
+                            && options.output_format() == OutputFormat::Plain
                         {
                             eprintln!(
                                 "Could not convert returned secure channel route {route} into a multiaddr"
@@ -114,7 +114,7 @@ impl DeleteCommand {
                 // and output format is plain then write a plain info to stderr.
                 if is_tty(std::io::stderr())
                     && !options.global_args.quiet
-                    && options.global_args.output_format == OutputFormat::Plain
+                    && options.output_format() == OutputFormat::Plain
                 {
                     eprintln!(
                         "Could not find secure channel with address {} at node {}",
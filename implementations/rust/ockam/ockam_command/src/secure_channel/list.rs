@@ -4,10 +4,10 @@ use miette::{miette, IntoDiagnostic};
 use ockam_api::cli_state::StateDirTrait;
 use std::fmt::Write;
 
-use ockam::{Context, TcpTransport};
-use ockam_api::nodes::models::secure_channel::ShowSecureChannelResponse;
+use ockam::Context;
+use ockam_api::nodes::models::secure_channel::{SecureChannelList, SecureChannelStatus};
 use ockam_api::route_to_multiaddr;
-use ockam_core::{route, Address};
+use ockam_core::route;
 
 use tokio::sync::Mutex;
 use tokio::try_join;
@@ -15,7 +15,7 @@ use tokio::try_join;
 use crate::node::get_node_name;
 use crate::terminal::OckamColor;
 use crate::util::output::Output;
-use crate::util::{parse_node_name, RpcBuilder};
+use crate::util::{parse_node_name, Rpc};
 use crate::{
     docs,
     util::{api, node_rpc},
@@ -44,50 +44,20 @@ impl ListCommand {
     pub fn run(self, opts: CommandGlobalOpts) {
         node_rpc(rpc, (opts, self));
     }
-
-    fn build_output(
-        &self,
-        node_name: &str,
-        channel_address: &str,
-        show_response: ShowSecureChannelResponse,
-    ) -> crate::Result<SecureChannelListOutput> {
-        let from = node_name.to_string();
-        let at = {
-            let channel_route = &route![channel_address];
-            let channel_multiaddr = route_to_multiaddr(channel_route).ok_or(miette!(
-                "Failed to convert route {channel_route} to multi-address"
-            ))?;
-            channel_multiaddr.to_string()
-        };
-
-        let to = {
-            let show_route = show_response.route.ok_or(miette!(
-                "Failed to retrieve route from show channel response"
-            ))?;
-            show_route
-                .split(" => ")
-                .map(|p| {
-                    let r = route![p];
-                    route_to_multiaddr(&r)
-                        .ok_or(miette!("Failed to convert route {r} to multi-address"))
-                })
-                .collect::<Result<Vec<_>, _>>()?
-                .iter()
-                .map(ToString::to_string)
-                .collect::<Vec<_>>()
-                .join("")
-        };
-
-        Ok(SecureChannelListOutput { from, to, at })
-    }
 }
 
-async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, ListCommand)) -> miette::Result<()> {
-    // We need this TCPTransport handle to ensure that we are using the same transport across
-    // multiple RPC calls. Creating a RPC instance without explicit transport results in a router
-    // instance being registered for the same transport type multiple times which is not allowed
-    let tcp = TcpTransport::create(&ctx).await.into_diagnostic()?;
+async fn rpc(
+    mut ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ListCommand),
+) -> miette::Result<()> {
+    run_impl(&mut ctx, opts, cmd).await
+}
 
+async fn run_impl(
+    ctx: &mut Context,
+    opts: CommandGlobalOpts,
+    cmd: ListCommand,
+) -> miette::Result<()> {
     let at = get_node_name(&opts.state, &cmd.at);
     let node_name = parse_node_name(&at)?;
 
@@ -96,59 +66,37 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, ListCommand)) -> mie
     }
 
     let is_finished: Mutex<bool> = Mutex::new(false);
-    let mut rpc = RpcBuilder::new(&ctx, &opts, &node_name).tcp(&tcp)?.build();
+    let mut rpc = Rpc::background(ctx, &opts, &node_name)?;
 
     let send_req = async {
         rpc.request(api::list_secure_channels()).await?;
-        let channel_identifiers = rpc.parse_response_body::<Vec<String>>()?;
+        let channels = rpc.parse_response_body::<SecureChannelList>()?;
 
         *is_finished.lock().await = true;
-        Ok(channel_identifiers)
+        Ok(channels.list)
     };
 
-    let output_messages = vec!["Retrieving secure channel identifiers...\n".to_string()];
+    let output_messages = vec!["Retrieving secure channels...\n".to_string()];
     let progress_output = opts
         .terminal
         .progress_output(&output_messages, &is_finished);
 
-    let (channel_identifiers, _) = try_join!(send_req, progress_output)?;
-
-    let mut responses = Vec::with_capacity(channel_identifiers.len());
-    for channel_addr in &channel_identifiers {
-        let is_finished: Mutex<bool> = Mutex::new(false);
-        let mut rpc = RpcBuilder::new(&ctx, &opts, &node_name).tcp(&tcp)?.build();
-        let send_req = async {
-            let request: ockam_core::api::RequestBuilder<
-                ockam_api::nodes::models::secure_channel::ShowSecureChannelRequest,
-            > = api::show_secure_channel(&Address::from(channel_addr));
-            rpc.request(request).await?;
-            let show_response = rpc.parse_response_body::<ShowSecureChannelResponse>()?;
-            let secure_channel_output =
-                cmd.build_output(&node_name, channel_addr, show_response)?;
-            *is_finished.lock().await = true;
-            Ok(secure_channel_output)
-        };
-        let output_messages = vec![format!(
-            "Retrieving secure channel {}...\n",
-            channel_addr
-                .to_string()
-                .color(OckamColor::PrimaryResource.color())
-        )];
-        let progress_output = opts
-            .terminal
-            .progress_output(&output_messages, &is_finished);
-
-        let (secure_channel_output, _) = try_join!(send_req, progress_output)?;
-
-        responses.push(secure_channel_output);
-    }
+    let (channels, _) = try_join!(send_req, progress_output)?;
 
+    let responses = channels
+        .iter()
+        .map(|channel| SecureChannelListOutput::new(&node_name, channel))
+        .collect::<Vec<_>>();
     let list = opts.terminal.build_list(
         &responses,
         &format!("Secure Channels on {}", node_name),
         &format!("No secure channels found on {}", node_name),
     )?;
-    opts.terminal.stdout().plain(list).write_line()?;
+    opts.terminal
+        .stdout()
+        .plain(list)
+        .json(serde_json::to_string_pretty(&channels).into_diagnostic()?)
+        .write_line()?;
 
     Ok(())
 }
@@ -157,6 +105,35 @@ pub struct SecureChannelListOutput {
     pub from: String,
     pub to: String,
     pub at: String,
+    pub peer: Option<String>,
+}
+
+impl SecureChannelListOutput {
+    fn new(node_name: &str, channel: &SecureChannelStatus) -> Self {
+        let at = to_multiaddr(&channel.local_address);
+        let to = match (&channel.route, &channel.remote_address) {
+            (Some(route), _) => route
+                .split(" => ")
+                .map(to_multiaddr)
+                .collect::<Vec<_>>()
+                .join(""),
+            (None, Some(remote_address)) => to_multiaddr(remote_address),
+            (None, None) => "unknown".to_string(),
+        };
+        Self {
+            from: node_name.to_string(),
+            to,
+            at,
+            peer: channel.peer_identifier.clone(),
+        }
+    }
+}
+
+/// Display an address as a multiaddr, or as is if it can't be converted
+fn to_multiaddr(address: &str) -> String {
+    route_to_multiaddr(&route![address])
+        .map(|m| m.to_string())
+        .unwrap_or_else(|| address.to_string())
 }
 
 impl Output for SecureChannelListOutput {
@@ -172,13 +149,21 @@ impl Output for SecureChannelListOutput {
                 .to_string()
                 .color(OckamColor::PrimaryResource.color())
         )?;
-        write!(
+        writeln!(
             output,
             "At {}",
             self.at
                 .to_string()
                 .color(OckamColor::PrimaryResource.color())
         )?;
+        write!(
+            output,
+            "Peer {}",
+            self.peer
+                .as_deref()
+                .unwrap_or("not authenticated")
+                .color(OckamColor::PrimaryResource.color())
+        )?;
 
         Ok(output)
     }
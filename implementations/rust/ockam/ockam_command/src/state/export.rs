@@ -0,0 +1,44 @@
+use crate::util::local_cmd;
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+use clap::Args;
+use colorful::Colorful;
+use std::path::PathBuf;
+
+const AFTER_LONG_HELP: &str = include_str!("./static/export/after_long_help.txt");
+
+/// Export the local configuration to a backup file
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = docs::after_help(AFTER_LONG_HELP))]
+pub struct ExportCommand {
+    /// Path of the backup file to create
+    path: PathBuf,
+
+    /// Include the secret material stored in the vaults
+    #[arg(long)]
+    include_secrets: bool,
+}
+
+impl ExportCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        local_cmd(run_impl(opts, self));
+    }
+}
+
+fn run_impl(opts: CommandGlobalOpts, cmd: ExportCommand) -> miette::Result<()> {
+    let manifest = opts.state.export(&cmd.path, cmd.include_secrets)?;
+    let path = cmd.path.to_string_lossy();
+    let secrets = if manifest.include_secrets {
+        "with"
+    } else {
+        "without"
+    };
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "Local configuration exported to {path}, {secrets} the vaults secrets"
+        ))
+        .machine(path.as_ref())
+        .json(serde_json::json!({ "export": { "path": path, "manifest": manifest } }))
+        .write_line()?;
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use crate::util::local_cmd;
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+use clap::Args;
+use colorful::Colorful;
+use std::path::PathBuf;
+
+const AFTER_LONG_HELP: &str = include_str!("./static/import/after_long_help.txt");
+
+/// Import the local configuration from a backup file
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = docs::after_help(AFTER_LONG_HELP))]
+pub struct ImportCommand {
+    /// Path of the backup file to restore
+    path: PathBuf,
+}
+
+impl ImportCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        local_cmd(run_impl(opts, self));
+    }
+}
+
+fn run_impl(opts: CommandGlobalOpts, cmd: ImportCommand) -> miette::Result<()> {
+    let manifest = opts.state.import(&cmd.path)?;
+    let path = cmd.path.to_string_lossy();
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!("Local configuration imported from {path}"))
+        .machine(path.as_ref())
+        .json(serde_json::json!({ "import": { "path": path, "manifest": manifest } }))
+        .write_line()?;
+    Ok(())
+}
@@ -0,0 +1,38 @@
+mod export;
+mod import;
+
+use clap::{Args, Subcommand};
+
+use crate::{docs, CommandGlobalOpts};
+
+use crate::state::export::ExportCommand;
+use crate::state::import::ImportCommand;
+
+const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
+
+/// Backup and restore the local configuration
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    long_about = docs::about(LONG_ABOUT),
+)]
+pub struct StateCommand {
+    #[command(subcommand)]
+    subcommand: StateSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum StateSubcommand {
+    Export(ExportCommand),
+    Import(ImportCommand),
+}
+
+impl StateCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        match self.subcommand {
+            StateSubcommand::Export(c) => c.run(opts),
+            StateSubcommand::Import(c) => c.run(opts),
+        }
+    }
+}
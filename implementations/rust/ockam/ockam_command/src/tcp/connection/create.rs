@@ -45,7 +45,7 @@ impl CreateCommand {
         response: &models::transport::TransportStatus,
     ) -> miette::Result<()> {
         // if output format is json, write json to stdout.
-        match opts.global_args.output_format {
+        match opts.output_format() {
             OutputFormat::Plain => {
                 if !is_tty(std::io::stdout()) {
                     println!("{}", response.multiaddr().into_diagnostic()?);
@@ -15,12 +15,12 @@ use ockam::identity::IdentityIdentifier;
 use ockam::{Context, TcpTransport};
 use ockam_abac::Resource;
 use ockam_api::cli_state::{StateDirTrait, StateItemTrait};
+use ockam_api::cloud::project_route::ProjectRoute;
 use ockam_api::nodes::models::portal::CreateInlet;
 use ockam_api::nodes::models::portal::InletStatus;
 use ockam_core::api::Request;
 use ockam_core::route;
-use ockam_multiaddr::proto::Project;
-use ockam_multiaddr::{MultiAddr, Protocol as _};
+use ockam_multiaddr::MultiAddr;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::str::FromStr;
 use std::thread::sleep;
@@ -119,7 +119,7 @@ async fn rpc(
             }
         }
 
-        let via_project = if cmd.to.clone().matches(0, &[Project::CODE.into()]) {
+        let via_project = if ProjectRoute::detect(&cmd.to)?.is_some() {
             if cmd.authorized.is_some() {
                 return Err(miette!("--authorized can not be used with project addresses").into());
             }
@@ -1,6 +1,6 @@
 use crate::node::{get_node_name, initialize_node_if_default};
 use crate::policy::{add_default_project_policy, has_policy};
-use crate::tcp::util::alias_parser;
+use crate::tcp::util::{alias_parser, tag_parser};
 use crate::terminal::OckamColor;
 
 use crate::util::parsers::socket_addr_parser;
@@ -17,6 +17,7 @@ use ockam_api::cli_state::{StateDirTrait, StateItemTrait};
 use ockam_api::nodes::models::portal::{CreateOutlet, OutletStatus};
 use ockam_core::api::Request;
 use std::net::SocketAddr;
+use std::num::NonZeroUsize;
 use tokio::sync::Mutex;
 use tokio::try_join;
 
@@ -41,6 +42,19 @@ pub struct CreateCommand {
     /// Assign a name to this outlet.
     #[arg(long, display_order = 900, id = "ALIAS", value_parser = alias_parser)]
     alias: Option<String>,
+
+    /// Only accept the peers presenting a valid credential issued by the trust context authority.
+    #[arg(long, display_order = 903)]
+    require_credential: bool,
+
+    /// Maximum number of connections open at the same time, at least 1. New connections wait until
+    /// one is closed, and are closed if none is closed in time.
+    #[arg(long, display_order = 904)]
+    max_connections: Option<NonZeroUsize>,
+
+    /// Tag the outlet with a `key=value` pair, so that it can be found by its tags. Can be repeated.
+    #[arg(long = "tag", display_order = 905, value_name = "KEY=VALUE", value_parser = tag_parser)]
+    tags: Vec<(String, String)>,
 }
 
 impl CreateCommand {
@@ -91,7 +105,13 @@ pub async fn run_impl(
             extract_address_value(&cmd.from)?,
             cmd.alias,
             true,
-        );
+        )
+        .with_require_credential(cmd.require_credential)
+        .with_tags(cmd.tags.into_iter().collect());
+        let payload = match cmd.max_connections {
+            Some(max_connections) => payload.with_max_connections(max_connections.get()),
+            None => payload,
+        };
         let res = send_request(&ctx, &opts, payload, node_name.clone()).await;
         *is_finished.lock().await = true;
         res
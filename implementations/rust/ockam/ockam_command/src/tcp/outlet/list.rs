@@ -1,4 +1,5 @@
 use crate::node::{get_node_name, initialize_node_if_default, NodeOpts};
+use crate::tcp::util::tag_parser;
 use crate::terminal::OckamColor;
 
 use crate::util::{extract_address_value, node_rpc, Rpc};
@@ -6,9 +7,9 @@ use crate::{docs, CommandGlobalOpts};
 
 use clap::Args;
 use colorful::Colorful;
-use miette::miette;
+use miette::{miette, IntoDiagnostic};
 use ockam_api::cli_state::StateDirTrait;
-use ockam_api::nodes::models::portal::OutletList;
+use ockam_api::nodes::models::portal::{ListOutlets, OutletList};
 
 use ockam_core::api::Request;
 use ockam_node::Context;
@@ -26,6 +27,11 @@ after_long_help = docs::after_help(AFTER_LONG_HELP))]
 pub struct ListCommand {
     #[command(flatten)]
     node_opts: NodeOpts,
+
+    /// Only list the outlets having a tag, written `key=value`. Can be repeated,
+    /// the outlets must then have all the tags
+    #[arg(long = "tag", value_name = "KEY=VALUE", value_parser = tag_parser)]
+    tags: Vec<(String, String)>,
 }
 
 impl ListCommand {
@@ -49,7 +55,7 @@ async fn run_impl(
     let is_finished: Mutex<bool> = Mutex::new(false);
 
     let send_req = async {
-        let res = send_request(&ctx, &opts, node_name.clone()).await;
+        let res = send_request(&ctx, &opts, node_name.clone(), &cmd.tags).await;
         *is_finished.lock().await = true;
         res
     };
@@ -72,7 +78,12 @@ async fn run_impl(
         &format!("Outlets on Node {node_name}"),
         &format!("No TCP Outlets found on node {node_name}."),
     )?;
-    opts.terminal.stdout().plain(list).write_line()?;
+    opts.terminal
+        .stdout()
+        .plain(list)
+        .machine(outlets.to_string().trim_end())
+        .json(serde_json::to_string_pretty(&outlets.list).into_diagnostic()?)
+        .write_line()?;
 
     Ok(())
 }
@@ -81,9 +92,16 @@ pub async fn send_request(
     ctx: &Context,
     opts: &CommandGlobalOpts,
     to_node: impl Into<Option<String>>,
+    tags: &[(String, String)],
 ) -> crate::Result<OutletList> {
     let to_node = get_node_name(&opts.state, &to_node.into());
     let mut rpc = Rpc::background(ctx, opts, &to_node)?;
-    rpc.request(Request::get("/node/outlet")).await?;
+    // the outlets are filtered by the node
+    if tags.is_empty() {
+        rpc.request(Request::get("/node/outlet")).await?;
+    } else {
+        let body = ListOutlets::new(tags.iter().cloned().collect());
+        rpc.request(Request::get("/node/outlet").body(body)).await?;
+    }
     rpc.parse_response_body::<OutletList>()
 }
@@ -54,6 +54,10 @@ pub async fn run_impl(
         .ok_or_else(|| miette!("Invalid Outlet Address"))?;
     println!("  From Outlet: {addr}");
     println!("  To TCP: {}", outlet_to_show.tcp_addr);
+    println!(
+        "  Active Connections: {}",
+        outlet_to_show.active_connections
+    );
     Ok(())
 }
 
@@ -0,0 +1,158 @@
+use clap::Args;
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+use ockam::{Context, TcpTransport};
+use ockam_api::cli_state::StateDirTrait;
+use ockam_api::nodes::models::portal::{
+    OutletEvent, OutletEventKind, OutletEventList, OutletEventsSubscription,
+};
+
+use crate::node::{get_node_name, initialize_node_if_default, NodeOpts};
+use crate::terminal::OckamColor;
+use crate::util::{api, connect_to_node, extract_address_value, node_rpc, Rpc, RpcBuilder};
+use crate::{docs, fmt_log, CommandGlobalOpts};
+
+const PREVIEW_TAG: &str = include_str!("../../static/preview_tag.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/watch/after_long_help.txt");
+
+/// Watch the changes of the TCP Outlets of a node until interrupted
+#[derive(Clone, Debug, Args)]
+#[command(
+before_help = docs::before_help(PREVIEW_TAG),
+after_long_help = docs::after_help(AFTER_LONG_HELP))]
+pub struct WatchCommand {
+    #[command(flatten)]
+    node_opts: NodeOpts,
+}
+
+impl WatchCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        initialize_node_if_default(&opts, &self.node_opts.at_node);
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, WatchCommand),
+) -> miette::Result<()> {
+    let node_name = get_node_name(&opts.state, &cmd.node_opts.at_node);
+    let node_name = extract_address_value(&node_name)?;
+    if !opts.state.nodes.get(&node_name)?.is_running() {
+        return Err(miette!("The node '{}' is not running", node_name));
+    }
+
+    // A single connection is used for all the requests, it is closed when the command stops
+    let tcp = TcpTransport::create(&ctx).await.into_diagnostic()?;
+    let connection = connect_to_node(&tcp, &opts, &node_name).await?;
+    let mut rpc = RpcBuilder::new(&ctx, &opts, &node_name)
+        .connection(&connection)
+        .build();
+    rpc.request(api::subscribe_to_outlet_events()).await?;
+    let subscription: OutletEventsSubscription = rpc.parse_response_body()?;
+
+    opts.terminal.write_line(&fmt_log!(
+        "Watching the TCP Outlets of node {}, press Ctrl-C to stop",
+        node_name
+            .to_string()
+            .color(OckamColor::PrimaryResource.color())
+    ))?;
+    let res = watch(&mut rpc, &opts, &subscription.id).await;
+
+    let unsubscribed = rpc
+        .request(api::unsubscribe_from_outlet_events(&subscription.id))
+        .await;
+    tcp.disconnect(connection).await.into_diagnostic()?;
+    res?;
+    unsubscribed?;
+    Ok(())
+}
+
+/// Print the events of the subscription until Ctrl-C is pressed
+async fn watch(
+    rpc: &mut Rpc<'_>,
+    opts: &CommandGlobalOpts,
+    subscription_id: &str,
+) -> crate::Result<()> {
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    loop {
+        tokio::select! {
+            _ = &mut ctrl_c => return Ok(()),
+            res = print_next_events(rpc, opts, subscription_id) => res?,
+        }
+    }
+}
+
+/// Print the next events of the subscription.
+/// The node only responds when the outlets change, or after a while without change
+async fn print_next_events(
+    rpc: &mut Rpc<'_>,
+    opts: &CommandGlobalOpts,
+    subscription_id: &str,
+) -> crate::Result<()> {
+    rpc.request(api::get_outlet_events(subscription_id)).await?;
+    let events: OutletEventList = rpc.parse_response_body()?;
+    for event in events.list {
+        opts.terminal
+            .clone()
+            .stdout()
+            .plain(plain_output(&event))
+            .machine(format!(
+                "{} {} {}",
+                event.kind, event.outlet.alias, event.outlet.active_connections
+            ))
+            .json(serde_json::to_string(&event).into_diagnostic()?)
+            .write_line()?;
+    }
+    Ok(())
+}
+
+fn plain_output(event: &OutletEvent) -> String {
+    let alias = event
+        .outlet
+        .alias
+        .to_string()
+        .color(OckamColor::PrimaryResource.color());
+    match event.kind {
+        OutletEventKind::Created => fmt_log!(
+            "Outlet {} created, forwarding to {}",
+            alias,
+            event.outlet.tcp_addr
+        ),
+        OutletEventKind::Removed => fmt_log!("Outlet {} removed", alias),
+        OutletEventKind::ConnectionsChanged => fmt_log!(
+            "Outlet {} has {} active connections",
+            alias,
+            event.outlet.active_connections
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ockam_api::nodes::models::portal::OutletStatus;
+
+    use super::*;
+
+    #[test]
+    fn outlet_event_json_is_a_single_line() {
+        let outlet =
+            OutletStatus::new("127.0.0.1:5000", "0#outlet", "db", None).with_active_connections(2);
+        let event = OutletEvent::new(OutletEventKind::ConnectionsChanged, outlet);
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(!json.contains('\n'));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            serde_json::json!({
+                "kind": "connections_changed",
+                "outlet": {
+                    "tcp_addr": "127.0.0.1:5000",
+                    "worker_addr": "0#outlet",
+                    "alias": "db",
+                    "active_connections": 2
+                }
+            })
+        );
+    }
+}
@@ -8,3 +8,11 @@ pub fn alias_parser(arg: &str) -> Result<String> {
         Ok(arg.to_string())
     }
 }
+
+/// Parse a tag of an outlet, written `key=value`
+pub fn tag_parser(arg: &str) -> Result<(String, String)> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(miette!("a tag must be written 'key=value'").into()),
+    }
+}
@@ -211,6 +211,10 @@ impl<W: TerminalWriter> Terminal<W> {
         self.stderr.is_tty()
     }
 
+    pub fn output_format(&self) -> OutputFormat {
+        self.output_format.clone()
+    }
+
     pub fn quiet() -> Self {
         Self::new(true, false, false, OutputFormat::Plain)
     }
@@ -1,7 +1,9 @@
-use crate::util::local_cmd;
+use crate::node::get_node_name;
+use crate::util::{api, node_rpc, parse_node_name, Rpc};
 use crate::{docs, fmt_ok, CommandGlobalOpts};
 use clap::Args;
 use colorful::Colorful;
+use ockam::Context;
 use ockam_api::cli_state::traits::StateDirTrait;
 
 const LONG_ABOUT: &str = include_str!("./static/delete/long_about.txt");
@@ -18,6 +20,10 @@ pub struct DeleteCommand {
     /// Name of the trust context
     pub name: String,
 
+    /// Delete the trust context configured on this node instead of the one stored locally
+    #[arg(long, display_order = 900, id = "NODE_NAME")]
+    at: Option<String>,
+
     /// Confirm the deletion without prompting
     #[arg(display_order = 901, long, short)]
     yes: bool,
@@ -25,19 +31,32 @@ pub struct DeleteCommand {
 
 impl DeleteCommand {
     pub fn run(self, opts: CommandGlobalOpts) {
-        local_cmd(run_impl(opts, self));
+        node_rpc(run_impl, (opts, self));
     }
 }
 
-fn run_impl(opts: CommandGlobalOpts, cmd: DeleteCommand) -> miette::Result<()> {
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, DeleteCommand),
+) -> miette::Result<()> {
     if opts.terminal.confirmed_with_flag_or_prompt(
         cmd.yes,
         "Are you sure you want to delete this trust context?",
     )? {
         let name = cmd.name;
-        let state = opts.state.trust_contexts;
-        state.get(&name)?;
-        state.delete(&name)?;
+        match &cmd.at {
+            Some(at) => {
+                let node_name = parse_node_name(&get_node_name(&opts.state, &Some(at.clone())))?;
+                let mut rpc = Rpc::background(&ctx, &opts, &node_name)?;
+                rpc.request(api::delete_trust_context(&name)).await?;
+                rpc.is_ok()?;
+            }
+            None => {
+                let state = opts.state.trust_contexts;
+                state.get(&name)?;
+                state.delete(&name)?;
+            }
+        }
         opts.terminal
             .stdout()
             .plain(fmt_ok!(
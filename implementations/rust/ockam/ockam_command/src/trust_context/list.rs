@@ -1,8 +1,12 @@
 use clap::Args;
-use miette::miette;
+use miette::{miette, IntoDiagnostic};
+use ockam::Context;
 use ockam_api::cli_state::traits::StateDirTrait;
+use ockam_api::nodes::models::trust_context::TrustContextList;
 
-use crate::util::local_cmd;
+use crate::node::get_node_name;
+use crate::trust_context::TrustContextOutput;
+use crate::util::{api, node_rpc, parse_node_name, Rpc};
 use crate::{docs, CommandGlobalOpts};
 
 const LONG_ABOUT: &str = include_str!("./static/list/long_about.txt");
@@ -16,30 +20,59 @@ const AFTER_LONG_HELP: &str = include_str!("./static/list/after_long_help.txt");
     before_help = docs::before_help(PREVIEW_TAG),
     after_long_help = docs::after_help(AFTER_LONG_HELP)
 )]
-pub struct ListCommand;
+pub struct ListCommand {
+    /// List the trust contexts configured on this node instead of the ones stored locally
+    #[arg(long, display_order = 900, id = "NODE_NAME")]
+    at: Option<String>,
+}
 
 impl ListCommand {
     pub fn run(self, opts: CommandGlobalOpts) {
-        local_cmd(run_impl(opts));
+        node_rpc(run_impl, (opts, self));
     }
 }
 
-fn run_impl(opts: CommandGlobalOpts) -> miette::Result<()> {
-    let states = opts.state.trust_contexts.list()?;
-    if states.is_empty() {
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ListCommand),
+) -> miette::Result<()> {
+    let trust_contexts = match &cmd.at {
+        Some(at) => {
+            let node_name = parse_node_name(&get_node_name(&opts.state, &Some(at.clone())))?;
+            let mut rpc = Rpc::background(&ctx, &opts, &node_name)?;
+            rpc.request(api::list_trust_contexts()).await?;
+            let list: TrustContextList = rpc.parse_response_body()?;
+            list.list
+                .into_iter()
+                .map(TrustContextOutput::from)
+                .collect()
+        }
+        None => {
+            let mut trust_contexts = vec![];
+            for state in opts.state.trust_contexts.list()? {
+                trust_contexts.push(TrustContextOutput::from_state(&state).await?);
+            }
+            trust_contexts
+        }
+    };
+    if trust_contexts.is_empty() {
         return Err(miette!("No trust contexts registered on this system!"));
     }
     let plain_output = {
         let mut output = String::new();
-        for (idx, tc) in states.iter().enumerate() {
-            output.push_str(&format!("Trust context[{idx}]:"));
-            for line in tc.to_string().lines() {
+        for (idx, tc) in trust_contexts.iter().enumerate() {
+            output.push_str(&format!("Trust context[{idx}]:\n"));
+            for line in tc.plain().lines() {
                 output.push_str(&format!("{:2}{}\n", "", line));
             }
             output.push('\n');
         }
         output
     };
-    opts.terminal.stdout().plain(plain_output).write_line()?;
+    opts.terminal
+        .stdout()
+        .plain(plain_output)
+        .json(serde_json::to_string_pretty(&trust_contexts).into_diagnostic()?)
+        .write_line()?;
     Ok(())
 }
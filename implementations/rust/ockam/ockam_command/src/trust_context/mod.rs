@@ -3,8 +3,15 @@ mod default;
 mod delete;
 mod list;
 mod show;
+mod update_credential;
 
 use clap::{Args, Subcommand};
+use miette::IntoDiagnostic;
+use serde::Serialize;
+
+use ockam_api::cli_state::{StateItemTrait, TrustContextState};
+use ockam_api::config::cli::CredentialRetrieverConfig;
+use ockam_api::nodes::models::trust_context::TrustContextStatus;
 
 use crate::{docs, CommandGlobalOpts};
 
@@ -12,6 +19,7 @@ use crate::trust_context::default::DefaultCommand;
 use crate::trust_context::delete::DeleteCommand;
 use crate::trust_context::list::ListCommand;
 use crate::trust_context::show::ShowCommand;
+use crate::trust_context::update_credential::UpdateCredentialCommand;
 pub use create::CreateCommand;
 
 const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
@@ -35,6 +43,7 @@ pub enum TrustContextSubcommand {
     Delete(DeleteCommand),
     List(ListCommand),
     Default(DefaultCommand),
+    UpdateCredential(UpdateCredentialCommand),
 }
 
 impl TrustContextCommand {
@@ -45,6 +54,77 @@ impl TrustContextCommand {
             TrustContextSubcommand::List(cmd) => cmd.run(opts),
             TrustContextSubcommand::Delete(cmd) => cmd.run(opts),
             TrustContextSubcommand::Default(cmd) => cmd.run(opts),
+            TrustContextSubcommand::UpdateCredential(cmd) => cmd.run(opts),
+        }
+    }
+}
+
+/// Details of a trust context, either stored in the local state or configured on a node
+#[derive(Serialize)]
+struct TrustContextOutput {
+    name: Option<String>,
+    id: String,
+    authority_identifier: Option<String>,
+    credential: Option<String>,
+}
+
+impl TrustContextOutput {
+    async fn from_state(state: &TrustContextState) -> miette::Result<Self> {
+        let config = state.config();
+        let (authority_identifier, credential) = match config.authority() {
+            Ok(authority) => {
+                let identifier = authority
+                    .identity()
+                    .await
+                    .into_diagnostic()?
+                    .identifier()
+                    .to_string();
+                let credential = authority.own_credential().ok().map(|c| match c {
+                    CredentialRetrieverConfig::FromMemory(_) => "in memory".to_string(),
+                    CredentialRetrieverConfig::FromPath(credential) => {
+                        credential.name().to_string()
+                    }
+                    CredentialRetrieverConfig::FromCredentialIssuer(issuer) => {
+                        format!("issued by {}", issuer.multiaddr)
+                    }
+                    CredentialRetrieverConfig::FromCredentialName(name) => name.clone(),
+                });
+                (Some(identifier), credential)
+            }
+            Err(_) => (None, None),
+        };
+        Ok(Self {
+            name: Some(state.name().to_string()),
+            id: config.id().to_string(),
+            authority_identifier,
+            credential,
+        })
+    }
+
+    fn plain(&self) -> String {
+        let mut output = format!(
+            "Name: {}\n",
+            self.name.as_deref().unwrap_or("(default trust context)")
+        );
+        output.push_str(&format!("ID: {}\n", self.id));
+        output.push_str(&format!(
+            "Authority: {}\n",
+            self.authority_identifier.as_deref().unwrap_or("none")
+        ));
+        if let Some(credential) = &self.credential {
+            output.push_str(&format!("Credential: {credential}\n"));
+        }
+        output
+    }
+}
+
+impl From<TrustContextStatus> for TrustContextOutput {
+    fn from(status: TrustContextStatus) -> Self {
+        Self {
+            name: status.name,
+            id: status.id,
+            authority_identifier: status.authority_identifier,
+            credential: None,
         }
     }
 }
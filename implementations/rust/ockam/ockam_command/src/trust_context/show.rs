@@ -1,7 +1,12 @@
 use clap::Args;
+use miette::{miette, IntoDiagnostic};
+use ockam::Context;
 use ockam_api::cli_state::traits::StateDirTrait;
+use ockam_api::nodes::models::trust_context::TrustContextList;
 
-use crate::util::local_cmd;
+use crate::node::get_node_name;
+use crate::trust_context::TrustContextOutput;
+use crate::util::{api, node_rpc, parse_node_name, Rpc};
 use crate::{docs, CommandGlobalOpts};
 
 const LONG_ABOUT: &str = include_str!("./static/show/long_about.txt");
@@ -18,26 +23,58 @@ const AFTER_LONG_HELP: &str = include_str!("./static/show/after_long_help.txt");
 pub struct ShowCommand {
     /// Name of the trust context
     pub name: Option<String>,
+
+    /// Show the trust context configured on this node instead of the one stored locally
+    #[arg(long, display_order = 900, id = "NODE_NAME")]
+    at: Option<String>,
 }
 
 impl ShowCommand {
     pub fn run(self, opts: CommandGlobalOpts) {
-        local_cmd(run_impl(opts, self));
+        node_rpc(run_impl, (opts, self));
     }
 }
 
-fn run_impl(opts: CommandGlobalOpts, cmd: ShowCommand) -> miette::Result<()> {
-    let name = cmd
-        .name
-        .unwrap_or(opts.state.trust_contexts.default()?.name().to_string());
-    let state = opts.state.trust_contexts.get(name)?;
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ShowCommand),
+) -> miette::Result<()> {
+    let trust_context = match &cmd.at {
+        Some(at) => {
+            let node_name = parse_node_name(&get_node_name(&opts.state, &Some(at.clone())))?;
+            let mut rpc = Rpc::background(&ctx, &opts, &node_name)?;
+            rpc.request(api::list_trust_contexts()).await?;
+            let list: TrustContextList = rpc.parse_response_body()?;
+            // without a name, the default trust context of the node is shown
+            let status = list
+                .list
+                .into_iter()
+                .find(|tc| tc.name == cmd.name)
+                .ok_or_else(|| match &cmd.name {
+                    Some(name) => miette!("The node {node_name} has no trust context named {name}"),
+                    None => miette!("The node {node_name} has no default trust context"),
+                })?;
+            TrustContextOutput::from(status)
+        }
+        None => {
+            let name = cmd
+                .name
+                .unwrap_or(opts.state.trust_contexts.default()?.name().to_string());
+            let state = opts.state.trust_contexts.get(name)?;
+            TrustContextOutput::from_state(&state).await?
+        }
+    };
     let plain_output = {
-        let mut output = "Trust context:".to_string();
-        for line in state.to_string().lines() {
+        let mut output = "Trust context:\n".to_string();
+        for line in trust_context.plain().lines() {
             output.push_str(&format!("{:2}{}\n", "", line));
         }
         output
     };
-    opts.terminal.stdout().plain(plain_output).write_line()?;
+    opts.terminal
+        .stdout()
+        .plain(plain_output)
+        .json(serde_json::to_string_pretty(&trust_context).into_diagnostic()?)
+        .write_line()?;
     Ok(())
 }
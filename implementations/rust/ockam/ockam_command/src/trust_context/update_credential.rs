@@ -0,0 +1,137 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+use ockam::identity::credential::{Credential, CredentialData, Unverified};
+use ockam::identity::identities;
+use ockam::Context;
+use ockam_api::cli_state::traits::{StateDirTrait, StateItemTrait};
+use ockam_api::config::cli::CredentialRetrieverConfig;
+use ockam_api::nodes::models::trust_context::{TrustContextList, UpdateTrustContextCredential};
+
+use crate::node::get_node_name;
+use crate::util::{api, node_rpc, parse_node_name, Rpc};
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/update_credential/long_about.txt");
+const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/update_credential/after_long_help.txt");
+
+/// Replace the credential of a trust context
+#[derive(Clone, Debug, Args)]
+#[command(
+    long_about = docs::about(LONG_ABOUT),
+    before_help = docs::before_help(PREVIEW_TAG),
+    after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct UpdateCredentialCommand {
+    /// Name of the trust context
+    #[arg(long)]
+    name: String,
+
+    /// Path of a file containing the new credential, hex encoded
+    #[arg(long, value_name = "CREDENTIAL_FILE")]
+    credential: PathBuf,
+
+    /// Also replace the credential of the trust context configured on this node
+    #[arg(long, display_order = 900, id = "NODE_NAME")]
+    at: Option<String>,
+}
+
+impl UpdateCredentialCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self));
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, UpdateCredentialCommand),
+) -> miette::Result<()> {
+    let contents = std::fs::read_to_string(&cmd.credential).into_diagnostic()?;
+    let bytes = hex::decode(contents.trim())
+        .map_err(|e| miette!("The credential file {:?} is invalid: {e}", cmd.credential))?;
+    let credential: Credential = minicbor::decode(&bytes).into_diagnostic()?;
+
+    let local_state = opts.state.trust_contexts.get(&cmd.name).ok();
+    if local_state.is_none() && cmd.at.is_none() {
+        return Err(miette!(
+            "Unable to find the trust context named {}",
+            cmd.name
+        ));
+    }
+
+    if let Some(state) = &local_state {
+        // the credential is verified before the state is changed
+        let authority = state.config().authority().into_diagnostic()?;
+        let authority_identity = authority.identity().await.into_diagnostic()?;
+        let credential_data: CredentialData<Unverified> =
+            minicbor::decode(credential.unverified_data()).into_diagnostic()?;
+        identities()
+            .credentials()
+            .verify_credential(
+                credential_data.unverified_subject(),
+                &[authority_identity],
+                credential.clone(),
+            )
+            .await
+            .map_err(|e| {
+                miette!(
+                    "The credential doesn't verify against the authority of the trust context {}: {e}",
+                    cmd.name
+                )
+            })?;
+    }
+
+    if let Some(at) = &cmd.at {
+        let node_name = parse_node_name(&get_node_name(&opts.state, &Some(at.clone())))?;
+        let mut rpc = Rpc::background(&ctx, &opts, &node_name)?;
+        rpc.request(api::list_trust_contexts()).await?;
+        let list: TrustContextList = rpc.parse_response_body()?;
+        // the default trust context of a node created with this trust context has no name
+        let name = if list
+            .list
+            .iter()
+            .any(|tc| tc.name.as_deref() == Some(cmd.name.as_str()))
+        {
+            Some(cmd.name.clone())
+        } else if local_state.as_ref().map_or(false, |state| {
+            list.list
+                .iter()
+                .any(|tc| tc.name.is_none() && tc.id == state.config().id())
+        }) {
+            None
+        } else {
+            return Err(miette!(
+                "The node {node_name} has no trust context named {}",
+                cmd.name
+            ));
+        };
+        rpc.request(api::update_trust_context_credential(
+            UpdateTrustContextCredential::new(name, credential.clone()),
+        ))
+        .await?;
+        rpc.is_ok()?;
+    }
+
+    if let Some(state) = local_state {
+        let config = state
+            .config()
+            .clone()
+            .with_own_credential(CredentialRetrieverConfig::FromMemory(credential))
+            .into_diagnostic()?;
+        opts.state.trust_contexts.overwrite(&cmd.name, config)?;
+    }
+
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "The credential of the trust context '{}' has been replaced",
+            cmd.name
+        ))
+        .machine(&cmd.name)
+        .json(serde_json::json!({ "trust-context": { "name": &cmd.name } }))
+        .write_line()?;
+    Ok(())
+}
@@ -16,11 +16,14 @@ use ockam_api::cloud::project::Project;
 use ockam_api::cloud::{BareCloudRequestWrapper, CloudRequestWrapper};
 use ockam_api::config::cli::TrustContextConfig;
 use ockam_api::nodes::models::flow_controls::AddConsumer;
+use ockam_api::nodes::models::logs::SubscribeToLogs;
 use ockam_api::nodes::models::services::{
     StartAuthenticatedServiceRequest, StartAuthenticatorRequest, StartCredentialsService,
     StartHopServiceRequest, StartIdentityServiceRequest, StartOktaIdentityProviderRequest,
     StartVerifierService,
 };
+use ockam_api::nodes::models::startup_config::ExportStartupConfig;
+use ockam_api::nodes::models::trust_context::UpdateTrustContextCredential;
 use ockam_api::nodes::*;
 use ockam_api::DefaultAddress;
 use ockam_core::api::RequestBuilder;
@@ -71,9 +74,66 @@ pub(crate) fn list_outlets() -> RequestBuilder<()> {
     Request::get("/node/outlet")
 }
 
-/// Construct a request builder to list all secure channels on the given node
+/// Construct a request builder to subscribe to the changes of the outlets of the given node
+pub(crate) fn subscribe_to_outlet_events() -> RequestBuilder<()> {
+    Request::post("/node/outlet_events")
+}
+
+/// Construct a request builder to get the changes of the outlets since the previous request.
+/// The node responds once the outlets change, or after a while without change
+pub(crate) fn get_outlet_events(subscription_id: &str) -> RequestBuilder<()> {
+    Request::get(format!("/node/outlet_events/{subscription_id}"))
+}
+
+/// Construct a request builder to remove a subscription to the changes of the outlets
+pub(crate) fn unsubscribe_from_outlet_events(subscription_id: &str) -> RequestBuilder<()> {
+    Request::delete(format!("/node/outlet_events/{subscription_id}"))
+}
+
+/// Construct a request builder to subscribe to the log lines of the given node
+pub(crate) fn subscribe_to_logs(level: &str) -> RequestBuilder<SubscribeToLogs> {
+    Request::post("/node/logs").body(SubscribeToLogs::new(level))
+}
+
+/// Construct a request builder to get the log lines of the given node since the previous request
+pub(crate) fn get_log_lines(subscription_id: &str) -> RequestBuilder<()> {
+    Request::get(format!("/node/logs/{subscription_id}"))
+}
+
+/// Construct a request builder to remove a subscription to the log lines of the given node
+pub(crate) fn unsubscribe_from_logs(subscription_id: &str) -> RequestBuilder<()> {
+    Request::delete(format!("/node/logs/{subscription_id}"))
+}
+
+/// Construct a request builder to export the running configuration of the given node,
+/// in the format of its startup configuration file
+pub(crate) fn export_startup_config(
+    inline_credentials: bool,
+) -> RequestBuilder<ExportStartupConfig> {
+    Request::get("/node/startup_config").body(ExportStartupConfig::new(inline_credentials))
+}
+
+/// Construct a request builder to list all secure channels on the given node,
+/// together with their authenticated peer
 pub(crate) fn list_secure_channels() -> RequestBuilder<()> {
-    Request::get("/node/secure_channel")
+    Request::get("/node/secure_channels")
+}
+
+/// Construct a request builder to list the trust contexts of the given node
+pub(crate) fn list_trust_contexts() -> RequestBuilder<()> {
+    Request::get("/node/trust_contexts")
+}
+
+/// Construct a request builder to delete a named trust context of the given node
+pub(crate) fn delete_trust_context(name: &str) -> RequestBuilder<()> {
+    Request::delete(format!("/node/trust_contexts/{name}"))
+}
+
+/// Construct a request builder to replace the credential of a trust context of the given node
+pub(crate) fn update_trust_context_credential(
+    request: UpdateTrustContextCredential,
+) -> RequestBuilder<UpdateTrustContextCredential> {
+    Request::put("/node/trust_contexts/credential").body(request)
 }
 
 /// Construct a request builder to list all workers on the given node
@@ -81,6 +141,18 @@ pub(crate) fn list_workers() -> RequestBuilder<()> {
     Request::get("/node/workers")
 }
 
+/// Construct a request builder to get the metrics of the given node, in the Prometheus text
+/// exposition format
+pub(crate) fn get_node_metrics() -> RequestBuilder<()> {
+    Request::get("/node/metrics")
+}
+
+/// Construct a request builder to stop the stale workers of the given node, which were
+/// started for outlets, inlets or secure channels which were removed
+pub(crate) fn prune_stale_workers() -> RequestBuilder<()> {
+    Request::post("/node/workers/actions/prune")
+}
+
 pub(crate) fn delete_secure_channel(
     addr: &Address,
 ) -> RequestBuilder<models::secure_channel::DeleteSecureChannelRequest> {
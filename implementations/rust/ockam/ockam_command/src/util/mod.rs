@@ -41,7 +41,13 @@ pub const DEFAULT_CONTROLLER_ADDRESS: &str = "/dnsaddr/orchestrator.ockam.io/tcp
 #[derive(Clone)]
 pub enum RpcMode<'a> {
     Embedded,
-    Background { tcp: Option<&'a TcpTransport> },
+    Background {
+        tcp: Option<&'a TcpTransport>,
+    },
+    /// The requests are sent through an established connection to a background node
+    Connected {
+        sender_address: Address,
+    },
 }
 
 pub struct RpcBuilder<'a> {
@@ -79,6 +85,15 @@ impl<'a> RpcBuilder<'a> {
         Ok(self)
     }
 
+    /// Send all the requests through an established connection to a background node,
+    /// see [`connect_to_node`], instead of creating a new connection for each request.
+    pub fn connection(mut self, sender_address: &Address) -> Self {
+        self.mode = RpcMode::Connected {
+            sender_address: sender_address.clone(),
+        };
+        self
+    }
+
     pub fn build(self) -> Rpc<'a> {
         Rpc {
             ctx: self.ctx,
@@ -188,10 +203,12 @@ impl<'a> Rpc<'a> {
         let mut to = self.to.clone();
         let route = match self.mode {
             RpcMode::Embedded => to,
+            RpcMode::Connected { ref sender_address } => {
+                to.modify().prepend(sender_address.clone());
+                to
+            }
             RpcMode::Background { ref tcp } => {
-                let node_state = self.opts.state.nodes.get(&self.node_name)?;
-                let port = node_state.config().setup().api_transport()?.addr.port();
-                let addr_str = format!("localhost:{port}");
+                let addr_str = node_api_address(self.opts, &self.node_name)?;
                 let addr = match tcp {
                     None => {
                         let tcp = TcpTransport::create(ctx).await.into_diagnostic()?;
@@ -254,11 +271,17 @@ impl<'a> Rpc<'a> {
     where
         T: Output + serde::Serialize,
     {
-        println_output(b, &self.opts.global_args.output_format)
+        print_result(b, &self.opts.output_format())
     }
 }
 
-pub fn println_output<T>(b: T, output_format: &OutputFormat) -> Result<T>
+/// Print a value to stdout with the given format:
+///  - with its [`Output`] implementation for the plain format
+///  - as pretty-printed JSON for the JSON format
+///
+/// The format of a command should be obtained with [`CommandGlobalOpts::output_format`],
+/// so that all commands are consistent when their output is piped.
+pub fn print_result<T>(b: T, output_format: &OutputFormat) -> Result<T>
 where
     T: Output + serde::Serialize,
 {
@@ -318,6 +341,26 @@ pub fn local_cmd(res: miette::Result<()>) {
     }
 }
 
+/// Open a connection to the API of a background node and return the address of its sender.
+/// The connection can be shared by several requests with [`RpcBuilder::connection`],
+/// and must be closed with `TcpTransport::disconnect` once it is not needed anymore.
+pub async fn connect_to_node(
+    tcp: &TcpTransport,
+    opts: &CommandGlobalOpts,
+    node_name: &str,
+) -> Result<Address> {
+    let addr = node_api_address(opts, node_name)?;
+    let connection = tcp.connect(addr, TcpConnectionOptions::new()).await?;
+    Ok(connection.sender_address().clone())
+}
+
+/// Return the address of the API transport of a background node
+fn node_api_address(opts: &CommandGlobalOpts, node_name: &str) -> Result<String> {
+    let node_state = opts.state.nodes.get(node_name)?;
+    let port = node_state.config().setup().api_transport()?.addr.port();
+    Ok(format!("localhost:{port}"))
+}
+
 pub fn node_rpc<A, F, Fut>(f: F, a: A)
 where
     A: Send + Sync + 'static,
@@ -330,7 +373,12 @@ where
             if let Err(e) = res {
                 error!(%e, "Failed to run command");
                 eprintln!("{:?}", e);
-                std::process::exit(exitcode::SOFTWARE);
+                // a command error carries its own exit code
+                let code = e
+                    .downcast_ref::<crate::Error>()
+                    .map(|e| e.code())
+                    .unwrap_or(exitcode::SOFTWARE);
+                std::process::exit(code);
             }
             Ok(())
         },
@@ -253,12 +253,14 @@ impl Output for OutletStatus {
         let output = format!(
             r#"
 Outlet {}:
-    TCP Address:    {}
-    Worker Address: {}
+    TCP Address:        {}
+    Worker Address:     {}
+    Active Connections: {}
 "#,
             self.alias,
             self.tcp_addr,
-            self.worker_address()?
+            self.worker_address()?,
+            self.active_connections
         );
 
         Ok(output)
@@ -82,11 +82,13 @@ async fn run_impl(
 
 impl Output for WorkerStatus {
     fn output(&self) -> crate::Result<String> {
+        let stale = if self.stale { ", stale" } else { "" };
         Ok(format!(
-            "Worker {}",
+            "Worker {} ({}{stale})",
             self.addr
                 .to_string()
-                .color(OckamColor::PrimaryResource.color())
+                .color(OckamColor::PrimaryResource.color()),
+            self.kind
         ))
     }
 }
@@ -133,3 +133,28 @@ force_kill_node() {
     fail "Log file should be empty"
   fi
 }
+
+@test "node - wait until a foreground node is ready" {
+  n="$(random_str)"
+  $OCKAM node create $n -f &
+
+  # the command returns as soon as the node is up, without waiting for the timeout
+  start=$SECONDS
+  run "$OCKAM" node wait --name "$n" --timeout 20
+  assert_success
+  assert [ $((SECONDS - start)) -lt 10 ]
+
+  run "$OCKAM" node show "$n"
+  assert_success
+}
+
+@test "node - fail to wait for a stopped node" {
+  n="$(random_str)"
+  run "$OCKAM" node create "$n"
+  assert_success
+  run "$OCKAM" node stop "$n"
+  assert_success
+
+  run "$OCKAM" node wait --name "$n" --timeout 1
+  assert_failure
+}
@@ -10,5 +10,37 @@ fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
     cmd.args(prefix_args);
     cmd.assert().success();
 
+    // enrollment ticket
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.args(prefix_args).args(["--ticket", "./member.ticket"]);
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn an_expired_ticket_is_rejected_before_writing_any_state() -> Result<(), Box<dyn std::error::Error>>
+{
+    let ockam_home = tempfile::tempdir()?;
+    let ticket = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/expired.enrollment.ticket"
+    );
+
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.env("OCKAM_HOME", ockam_home.path());
+    cmd.args(["enroll", "--ticket", ticket]);
+    let output = cmd.assert().failure().get_output().clone();
+    assert!(String::from_utf8_lossy(&output.stderr).contains("has expired"));
+
+    // neither a project nor an identity were created
+    for dir in ["projects", "identities", "identities/data"] {
+        let dir = ockam_home.path().join(dir);
+        if dir.exists() {
+            for entry in std::fs::read_dir(dir)? {
+                assert!(entry?.file_type()?.is_dir());
+            }
+        }
+    }
     Ok(())
 }
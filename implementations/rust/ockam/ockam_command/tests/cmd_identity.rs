@@ -0,0 +1,77 @@
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.args([
+        "--test-argument-parser",
+        "identity",
+        "import",
+        "--key",
+        "./identity.pem",
+        "--name",
+        "imported",
+    ]);
+    cmd.assert().success();
+
+    // the key file is required
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.args(["--test-argument-parser", "identity", "import"]);
+    cmd.assert().failure();
+
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.args([
+        "--test-argument-parser",
+        "identity",
+        "revoke",
+        "P6c20e814b56579306f55c64e8747e6c1b4a53d9a3f4ca83c252cc2fbfc72fa94",
+        "--at",
+        "n1",
+        "--cancel",
+    ]);
+    cmd.assert().success();
+
+    // the identifier must be valid
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.args([
+        "--test-argument-parser",
+        "identity",
+        "revoke",
+        "not-an-identifier",
+    ]);
+    cmd.assert().failure();
+
+    Ok(())
+}
+
+#[test]
+fn an_identity_is_imported_from_a_private_key() -> Result<(), Box<dyn std::error::Error>> {
+    let ockam_home = tempfile::tempdir()?;
+    let key = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/identity.ed25519.pem"
+    );
+    // the identifier of an identity depends only on its root key
+    let identifier = "P97178f73e8c2c082439309bd861dde56df72f20c3e8ebe4d829bb551ccdc9a59";
+
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.env("OCKAM_HOME", ockam_home.path());
+    cmd.args(["identity", "import", "--key", key, "--name", "imported"]);
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.env("OCKAM_HOME", ockam_home.path());
+    cmd.args(["identity", "show", "imported"]);
+    let output = cmd.assert().success().get_output().clone();
+    assert!(String::from_utf8_lossy(&output.stdout).contains(identifier));
+
+    // the same name can't be used twice
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.env("OCKAM_HOME", ockam_home.path());
+    cmd.args(["identity", "import", "--key", key, "--name", "imported"]);
+    let output = cmd.assert().failure().get_output().clone();
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already exists"));
+
+    Ok(())
+}
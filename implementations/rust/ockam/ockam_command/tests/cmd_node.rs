@@ -19,5 +19,16 @@ fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
         .arg("node-name");
     cmd.assert().success();
 
+    // wait for a node success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("wait")
+        .arg("--name")
+        .arg("node-name")
+        .arg("--timeout")
+        .arg("5");
+    cmd.assert().success();
+
     Ok(())
 }
@@ -1,6 +1,7 @@
 #![allow(missing_docs)]
 
 use core::fmt::{self, Display, Formatter};
+use core::time::Duration;
 
 use minicbor::data::Type;
 use minicbor::encode::{self, Encoder, Write};
@@ -98,6 +99,10 @@ pub struct Request {
     #[n(3)] method: Option<Method>,
     /// Indicator if a request body is expected after this header.
     #[n(4)] has_body: bool,
+    /// The maximum duration of the handling of the request, in milliseconds.
+    ///
+    /// If not set, the server uses its default timeout.
+    #[n(5)] timeout_ms: Option<u64>,
 }
 
 /// The response header.
@@ -299,6 +304,7 @@ pub enum Status {
     #[n(404)] NotFound,
     #[n(409)] Conflict,
     #[n(405)] MethodNotAllowed,
+    #[n(408)] RequestTimeout,
     #[n(500)] InternalServerError,
     #[n(501)] NotImplemented,
 }
@@ -313,6 +319,7 @@ impl Display for Status {
             Status::NotFound => "404 NotFound",
             Status::Conflict => "409 Conflict",
             Status::MethodNotAllowed => "405 MethodNotAllowed",
+            Status::RequestTimeout => "408 RequestTimeout",
             Status::InternalServerError => "500 InternalServerError",
             Status::NotImplemented => "501 NotImplemented",
         })
@@ -347,6 +354,7 @@ impl Request {
             method: Some(method),
             path: path.into(),
             has_body,
+            timeout_ms: None,
         }
     }
 
@@ -396,6 +404,12 @@ impl Request {
     pub fn has_body(&self) -> bool {
         self.has_body
     }
+
+    /// The maximum duration of the handling of the request, if it overrides the default
+    /// timeout of the server
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_ms.map(Duration::from_millis)
+    }
 }
 
 impl Response {
@@ -606,6 +620,12 @@ impl<T> RequestBuilder<T> {
         self
     }
 
+    /// Override the default timeout of the server for this request
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.header.timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
     pub fn header(&self) -> &Request {
         &self.header
     }
@@ -954,6 +974,7 @@ mod schema_test {
         Status::BadRequest,
         Status::NotFound,
         Status::MethodNotAllowed,
+        Status::RequestTimeout,
         Status::InternalServerError,
         Status::NotImplemented,
     ];
@@ -246,6 +246,11 @@ impl CredentialData<Unverified> {
     pub fn unverified_subject(&self) -> &IdentityIdentifier {
         &self.subject
     }
+
+    /// Return the expiration date of a credential data when unverified
+    pub fn unverified_expires_at(&self) -> Timestamp {
+        self.expires
+    }
 }
 
 impl TryFrom<&[u8]> for CredentialData<Unverified> {
@@ -15,7 +15,7 @@ pub struct AuthorityService {
     identities_reader: Arc<dyn IdentitiesReader>,
     credentials: Arc<dyn Credentials>,
     identifier: IdentityIdentifier,
-    own_credential: Option<Arc<dyn CredentialsRetriever>>,
+    own_credential: Arc<RwLock<Option<Arc<dyn CredentialsRetriever>>>>,
     inner_cache: Arc<RwLock<Option<CachedCredential>>>,
 }
 
@@ -37,11 +37,25 @@ impl AuthorityService {
             identities_reader,
             credentials,
             identifier,
-            own_credential,
+            own_credential: Arc::new(RwLock::new(own_credential)),
             inner_cache: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// Return the identifier of the Authority
+    pub fn identifier(&self) -> &IdentityIdentifier {
+        &self.identifier
+    }
+
+    /// Replace the retriever of the credential issued by this authority.
+    ///
+    /// The replacement is shared with all the clones of this authority service, so the
+    /// credentials they return from now on come from the new retriever
+    pub fn replace_own_credential(&self, own_credential: Arc<dyn CredentialsRetriever>) {
+        *self.own_credential.write().unwrap() = Some(own_credential);
+        *self.inner_cache.write().unwrap() = None;
+    }
+
     /// Return the Public Identity of the Authority
     pub async fn identity(&self) -> Result<Identity> {
         self.identities_reader.get_identity(&self.identifier).await
@@ -70,6 +84,8 @@ impl AuthorityService {
         // in order to keep the locking schema simple, we allow multiple concurrent retrievals
         let retriever = self
             .own_credential
+            .read()
+            .unwrap()
             .clone()
             .ok_or(IdentityError::UnknownAuthority)?;
         let credential = retriever.retrieve(ctx, for_identity).await?;
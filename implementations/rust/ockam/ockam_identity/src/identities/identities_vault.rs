@@ -74,6 +74,16 @@ impl PersistentSecretsStore for CoercedIdentitiesVault {
         self.vault.create_persistent_secret(attributes).await
     }
 
+    async fn import_persistent_secret(
+        &self,
+        secret: Secret,
+        attributes: SecretAttributes,
+    ) -> Result<KeyId> {
+        self.vault
+            .import_persistent_secret(secret, attributes)
+            .await
+    }
+
     async fn delete_persistent_secret(&self, key_id: KeyId) -> Result<bool> {
         self.vault.delete_persistent_secret(key_id).await
     }
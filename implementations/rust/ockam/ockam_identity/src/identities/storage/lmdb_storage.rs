@@ -1,6 +1,8 @@
 use core::str;
 use std::fmt;
 use std::path::Path;
+use std::sync::{RwLock, RwLockReadGuard};
+use std::time::Duration;
 
 use lmdb::{Cursor, Database, Environment, Transaction};
 use tokio_retry::strategy::{jitter, FixedInterval};
@@ -18,6 +20,10 @@ use ockam_node::tokio::task::{self, JoinError};
 
 use crate::Storage;
 
+/// Maximum number of attempts for a transaction failing because of another process
+/// using the same environment, see [`LmdbStorage::with_retries`]
+const MAX_TRANSACTION_ATTEMPTS: u64 = 5;
+
 /// Storage using the LMDB database
 #[derive(Clone)]
 pub struct LmdbStorage {
@@ -25,6 +31,9 @@ pub struct LmdbStorage {
     pub env: Arc<Environment>,
     /// lmdb database file
     pub map: Database,
+    /// Held for reading by the transactions of this process and for writing
+    /// when the map is resized, which requires no active transaction
+    transactions: Arc<RwLock<()>>,
 }
 
 impl fmt::Debug for LmdbStorage {
@@ -36,6 +45,17 @@ impl fmt::Debug for LmdbStorage {
 impl LmdbStorage {
     /// Constructor
     pub async fn new<P: AsRef<Path>>(p: P) -> Result<Self> {
+        Self::open(p.as_ref(), None).await
+    }
+
+    /// Create a storage with an explicit map size, in bytes.
+    /// The map size is the maximum size of the database. If it is larger than the map size
+    /// used by other processes opening the same environment, they adopt it.
+    pub async fn with_map_size<P: AsRef<Path>>(p: P, map_size: usize) -> Result<Self> {
+        Self::open(p.as_ref(), Some(map_size)).await
+    }
+
+    async fn open(path: &Path, map_size: Option<usize>) -> Result<Self> {
         // creating a new database might be failing a few times
         // if the files are currently being held by another pod which is shutting down.
         // In that case we retry a few times, between 1 and 10 seconds.
@@ -43,26 +63,32 @@ impl LmdbStorage {
             .map(jitter) // add jitter to delays
             .take(10); // limit to 10 retries
 
-        let path: &Path = p.as_ref();
-        Retry::spawn(retry_strategy, || async { Self::make(path).await }).await
+        Retry::spawn(retry_strategy, || async {
+            Self::make(path, map_size).await
+        })
+        .await
     }
 
-    async fn make(p: &Path) -> Result<Self> {
+    async fn make(p: &Path, map_size: Option<usize>) -> Result<Self> {
         debug!("create the LMDB database");
         std::fs::create_dir_all(p.parent().unwrap())
             .map_err(|e| Error::new(Origin::Node, Kind::Io, e))?;
         let p = p.to_path_buf();
-        let env = Environment::new()
+        let mut builder = Environment::new();
+        builder
             .set_flags(lmdb::EnvironmentFlags::NO_SUB_DIR | lmdb::EnvironmentFlags::NO_TLS)
-            .set_max_dbs(1)
-            .open(p.as_ref())
-            .map_err(map_lmdb_err)?;
+            .set_max_dbs(1);
+        if let Some(map_size) = map_size {
+            builder.set_map_size(map_size);
+        }
+        let env = builder.open(p.as_ref()).map_err(map_lmdb_err)?;
         let map = env
             .create_db(Some("map"), lmdb::DatabaseFlags::empty())
             .map_err(map_lmdb_err)?;
         Ok(LmdbStorage {
             env: Arc::new(env),
             map,
+            transactions: Arc::new(RwLock::new(())),
         })
     }
 
@@ -70,11 +96,11 @@ impl LmdbStorage {
     pub async fn write(&self, k: String, v: Vec<u8>) -> Result<()> {
         let d = self.clone();
         let t = move || {
-            let mut w = d.env.begin_rw_txn().map_err(map_lmdb_err)?;
-            w.put(d.map, &k, &v, lmdb::WriteFlags::empty())
-                .map_err(map_lmdb_err)?;
-            w.commit().map_err(map_lmdb_err)?;
-            Ok(())
+            d.with_retries(|env| {
+                let mut w = env.begin_rw_txn()?;
+                w.put(d.map, &k, &v, lmdb::WriteFlags::empty())?;
+                w.commit()
+            })
         };
         task::spawn_blocking(t).await.map_err(map_join_err)?
     }
@@ -83,16 +109,69 @@ impl LmdbStorage {
     pub async fn delete(&self, k: String) -> Result<()> {
         let d = self.clone();
         let t = move || {
-            let mut w = d.env.begin_rw_txn().map_err(map_lmdb_err)?;
-            match w.del(d.map, &k, None) {
-                Ok(()) | Err(lmdb::Error::NotFound) => {}
-                Err(e) => return Err(map_lmdb_err(e)),
-            }
-            w.commit().map_err(map_lmdb_err)?;
-            Ok(())
+            d.with_retries(|env| {
+                let mut w = env.begin_rw_txn()?;
+                match w.del(d.map, &k, None) {
+                    Ok(()) | Err(lmdb::Error::NotFound) => {}
+                    Err(e) => return Err(e),
+                }
+                w.commit()
+            })
         };
         task::spawn_blocking(t).await.map_err(map_join_err)?
     }
+
+    /// Return a guard which must be held while a transaction is open when the transaction
+    /// is not run with [`LmdbStorage::with_retries`], so that the map is not resized under it
+    pub fn transaction_guard(&self) -> RwLockReadGuard<'_, ()> {
+        self.transactions.read().unwrap()
+    }
+
+    /// Run a transaction and retry it when it fails because of a map resize or
+    /// a lack of reader slots:
+    ///
+    ///  - `MDB_MAP_RESIZED`: the map has been grown by another process, its new size is adopted
+    ///  - `MDB_MAP_FULL`: the map is full, it is grown to twice its size
+    ///  - `MDB_READERS_FULL`: all the reader slots are taken, some of them must be released first
+    ///
+    /// The map is only resized when no other transaction is active in this process.
+    fn with_retries<T, F>(&self, f: F) -> Result<T>
+    where
+        F: Fn(&Environment) -> core::result::Result<T, lmdb::Error>,
+    {
+        let mut attempt = 1;
+        loop {
+            let result = {
+                let _guard = self.transaction_guard();
+                f(&self.env)
+            };
+            match result {
+                Err(lmdb::Error::MapResized) if attempt < MAX_TRANSACTION_ATTEMPTS => {
+                    debug!("the LMDB map has been resized by another process");
+                    // a size of 0 adopts the size set by the other process
+                    self.resize(|_| 0)?;
+                }
+                Err(lmdb::Error::MapFull) if attempt < MAX_TRANSACTION_ATTEMPTS => {
+                    debug!("the LMDB map is full");
+                    self.resize(|size| size * 2)?;
+                }
+                Err(lmdb::Error::ReadersFull) if attempt < MAX_TRANSACTION_ATTEMPTS => {
+                    debug!("no LMDB reader slot is available");
+                    std::thread::sleep(Duration::from_millis(50 * attempt));
+                }
+                result => return result.map_err(map_lmdb_err),
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Set a new map size, computed from the current one, once all the transactions
+    /// of this process are finished
+    fn resize(&self, new_size: impl Fn(usize) -> usize) -> Result<()> {
+        let _guard = self.transactions.write().unwrap();
+        let size = self.env.info().map_err(map_lmdb_err)?.map_size();
+        self.env.set_map_size(new_size(size)).map_err(map_lmdb_err)
+    }
 }
 
 #[async_trait]
@@ -101,12 +180,14 @@ impl Storage for LmdbStorage {
         let d = self.clone();
         let k = format!("{id}:{key}");
         let t = move || {
-            let r = d.env.begin_ro_txn().map_err(map_lmdb_err)?;
-            match r.get(d.map, &k) {
-                Ok(value) => Ok(Some(Vec::from(value))),
-                Err(lmdb::Error::NotFound) => Ok(None),
-                Err(e) => Err(map_lmdb_err(e)),
-            }
+            d.with_retries(|env| {
+                let r = env.begin_ro_txn()?;
+                match r.get(d.map, &k) {
+                    Ok(value) => Ok(Some(Vec::from(value))),
+                    Err(lmdb::Error::NotFound) => Ok(None),
+                    Err(e) => Err(e),
+                }
+            })
         };
         task::spawn_blocking(t).await.map_err(map_join_err)?
     }
@@ -123,16 +204,18 @@ impl Storage for LmdbStorage {
         let d = self.clone();
         let suffix = format!(":{}", namespace);
         let t = move || {
-            let r = d.env.begin_ro_txn().map_err(map_lmdb_err)?;
-            let mut cursor = r.open_ro_cursor(d.map).map_err(map_lmdb_err)?;
-            Ok(cursor
-                .iter()
-                .filter_map(|r| {
-                    let (k, _) = r.unwrap();
-                    let key = str::from_utf8(k).unwrap();
-                    key.rsplit_once(&suffix).map(|(k, _)| k.to_string())
-                })
-                .collect())
+            d.with_retries(|env| {
+                let r = env.begin_ro_txn()?;
+                let mut cursor = r.open_ro_cursor(d.map)?;
+                Ok(cursor
+                    .iter()
+                    .filter_map(|r| {
+                        let (k, _) = r.unwrap();
+                        let key = str::from_utf8(k).unwrap();
+                        key.rsplit_once(&suffix).map(|(k, _)| k.to_string())
+                    })
+                    .collect())
+            })
         };
         task::spawn_blocking(t).await.map_err(map_join_err)?
     }
@@ -44,6 +44,11 @@ impl SqliteStorage {
     );";
     const CREATE_POLICY_INDEX_SQL: &str = "CREATE UNIQUE INDEX IF NOT EXISTS idx_policy_resource_action ON policy (resource, action);";
 
+    const CREATE_RESOURCE_PARENT_TABLE_SQL: &str = "CREATE TABLE IF NOT EXISTS resource_parent (
+        resource TEXT PRIMARY KEY,
+        parent TEXT NOT NULL
+    );";
+
     /// Constructor
     pub async fn new<P: AsRef<Path>>(p: P) -> Result<Self> {
         // Not sure we need this
@@ -69,7 +74,8 @@ impl SqliteStorage {
                     + SqliteStorage::CREATE_IDENTITY_TABLE_SQL
                     + SqliteStorage::CREATE_IDENTITY_INDEX_SQL
                     + SqliteStorage::CREATE_POLICY_TABLE_SQL
-                    + SqliteStorage::CREATE_POLICY_INDEX_SQL),
+                    + SqliteStorage::CREATE_POLICY_INDEX_SQL
+                    + SqliteStorage::CREATE_RESOURCE_PARENT_TABLE_SQL),
             )
             .map_err(map_sqlite_err)?;
         Ok(SqliteStorage {
@@ -597,12 +597,12 @@ impl MultiAddr {
             match p.code() {
                 Ip4::CODE => {
                     let ip4 = p.cast::<Ip4>().unwrap();
-                    let port = it.next().unwrap().cast::<Tcp>().unwrap();
+                    let port = next_tcp_port(&mut it)?;
                     return Ok(SocketAddrV4::new(*ip4, *port).to_string());
                 }
                 Ip6::CODE => {
                     let ip6 = p.cast::<Ip6>().unwrap();
-                    let port = it.next().unwrap().cast::<Tcp>().unwrap();
+                    let port = next_tcp_port(&mut it)?;
                     return Ok(SocketAddrV6::new(*ip6, *port, 0, 0).to_string());
                 }
                 DnsAddr::CODE => {
@@ -623,6 +623,13 @@ impl MultiAddr {
     }
 }
 
+/// Return the TCP port following an IP address, which must be the next protocol component
+fn next_tcp_port<'a>(it: &mut impl Iterator<Item = ProtoValue<'a>>) -> Result<Tcp, Error> {
+    it.next()
+        .and_then(|p| p.cast::<Tcp>())
+        .ok_or_else(|| Error::message("No TCP port after the IP address"))
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Match {
     Val(Code),
@@ -45,6 +45,11 @@ quickcheck! {
         a.0 == addr
     }
 
+    fn to_socket_addr_doesnt_panic(a: Addr) -> bool {
+        let _ = a.0.to_socket_addr();
+        true
+    }
+
     fn match_test(a: Addr) -> bool {
         let codes = a.0.iter().map(|p| Match::code(p.code())).collect::<Vec<_>>();
         a.0.matches(0, &codes)
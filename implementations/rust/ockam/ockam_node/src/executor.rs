@@ -43,6 +43,22 @@ impl Executor {
     /// Create a new Ockam node [`Executor`] instance
     pub fn new(flow_controls: &FlowControls) -> Self {
         let rt = Runtime::new().unwrap();
+        Self::with_runtime(rt, flow_controls)
+    }
+
+    /// Create a new Ockam node [`Executor`] instance using a runtime
+    /// with a fixed number of worker threads
+    #[cfg(feature = "std")]
+    pub fn with_worker_threads(flow_controls: &FlowControls, worker_threads: usize) -> Self {
+        let rt = crate::tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(worker_threads)
+            .enable_all()
+            .build()
+            .unwrap();
+        Self::with_runtime(rt, flow_controls)
+    }
+
+    fn with_runtime(rt: Runtime, flow_controls: &FlowControls) -> Self {
         let router = Router::new(flow_controls);
         #[cfg(feature = "metrics")]
         let metrics = Metrics::new(&rt, router.get_metrics_readout());
@@ -19,6 +19,7 @@ impl ockam_core::Worker for NullWorker {
 /// builder API to customise the underlying node that is created.
 pub struct NodeBuilder {
     logging: bool,
+    worker_threads: Option<usize>,
 }
 
 impl Default for NodeBuilder {
@@ -30,12 +31,34 @@ impl Default for NodeBuilder {
 impl NodeBuilder {
     /// Create a node
     pub fn new() -> Self {
-        Self { logging: true }
+        Self {
+            logging: true,
+            worker_threads: None,
+        }
     }
 
     /// Disable logging on this node
     pub fn no_logging(self) -> Self {
-        Self { logging: false }
+        Self {
+            logging: false,
+            ..self
+        }
+    }
+
+    /// Set the number of worker threads of the node runtime
+    ///
+    /// By default the runtime uses one worker thread per CPU core.
+    /// A value of 1 can be used to get a more deterministic scheduling in tests.
+    ///
+    /// # Panics
+    ///
+    /// The node creation panics if `worker_threads` is 0.
+    #[cfg(feature = "std")]
+    pub fn with_worker_threads(self, worker_threads: usize) -> Self {
+        Self {
+            worker_threads: Some(worker_threads),
+            ..self
+        }
     }
 
     /// Consume this builder and yield a new Ockam Node
@@ -50,7 +73,11 @@ impl NodeBuilder {
         // Shared instance of FlowControls
         let flow_controls = FlowControls::new();
 
-        let mut exe = Executor::new(&flow_controls);
+        let mut exe = match self.worker_threads {
+            #[cfg(feature = "std")]
+            Some(worker_threads) => Executor::with_worker_threads(&flow_controls, worker_threads),
+            _ => Executor::new(&flow_controls),
+        };
         let addr: Address = "app".into();
 
         // The root application worker needs a mailbox and relay to accept
@@ -65,6 +65,22 @@ fn start_and_shutdown_node__many_iterations__should_not_fail() {
             .unwrap()
     }
 }
+#[allow(non_snake_case)]
+#[test]
+fn start_node__single_worker_thread__should_route_messages() {
+    let (mut ctx, mut executor) = NodeBuilder::new().with_worker_threads(1).build();
+    executor
+        .execute(async move {
+            let mut child_ctx = ctx.new_detached("child", AllowAll, AllowAll).await?;
+            ctx.send(route!["child"], "Hello".to_string()).await?;
+            let m = child_ctx.receive::<String>().await?.body();
+            assert_eq!(m, "Hello");
+            ctx.stop().await
+        })
+        .unwrap()
+        .unwrap()
+}
+
 struct SimpleWorker {
     initialize_was_called: Arc<AtomicBool>,
     shutdown_was_called: Arc<AtomicBool>,
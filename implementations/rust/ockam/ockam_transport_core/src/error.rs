@@ -38,6 +38,8 @@ pub enum TransportError {
     PortalInvalidState,
     /// InvalidRouterResponseType
     InvalidRouterResponseType,
+    /// Failed to bind to a socket address already in use
+    AddressInUse,
 }
 
 impl ockam_core::compat::error::Error for TransportError {}
@@ -59,6 +61,7 @@ impl core::fmt::Display for TransportError {
             Self::GenericIo => write!(f, "generic I/O failure"),
             Self::PortalInvalidState => write!(f, "portal entered invalid state"),
             Self::InvalidRouterResponseType => write!(f, "router responded with invalid type"),
+            Self::AddressInUse => write!(f, "the socket address is already in use"),
         }
     }
 }
@@ -83,6 +86,7 @@ impl From<TransportError> for Error {
             GenericIo => Kind::Io,
             PortalInvalidState => Kind::Invalid,
             InvalidRouterResponseType => Kind::Invalid,
+            AddressInUse => Kind::Conflict,
         };
 
         Error::new(Origin::Transport, kind, err)
@@ -93,6 +97,7 @@ impl From<io::Error> for TransportError {
     fn from(e: io::Error) -> Self {
         match e.kind() {
             io::ErrorKind::ConnectionRefused => Self::PeerNotFound,
+            io::ErrorKind::AddrInUse => Self::AddressInUse,
             _ => Self::GenericIo,
         }
     }
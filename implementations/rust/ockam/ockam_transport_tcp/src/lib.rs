@@ -21,12 +21,16 @@ extern crate alloc;
 mod options;
 mod portal;
 mod registry;
+#[cfg(feature = "tls")]
+mod tls;
 mod transport;
 
 use ockam_core::TransportType;
 pub use options::*;
 pub use portal::*;
 pub use registry::*;
+#[cfg(feature = "tls")]
+pub use tls::{TcpTlsClientOptions, TcpTlsServerOptions};
 pub use transport::*;
 
 mod workers;
@@ -1,30 +1,119 @@
 use crate::workers::Addresses;
+#[cfg(feature = "tls")]
+use crate::{TcpTlsClientOptions, TcpTlsServerOptions};
+use cfg_if::cfg_if;
+use core::time::Duration;
 use ockam_core::compat::sync::Arc;
 use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl, FlowControls};
 use ockam_core::{Address, AllowAll, IncomingAccessControl, OutgoingAccessControl};
+use socket2::{SockRef, TcpKeepalive};
+use tokio::net::TcpStream;
+use tracing::warn;
 
 pub(crate) struct TcpConnectionAccessControl {
     pub sender_incoming_access_control: Arc<dyn IncomingAccessControl>,
     pub receiver_outgoing_access_control: Arc<dyn OutgoingAccessControl>,
 }
 
+/// TCP keepalive settings of a socket, used to detect the connections which were dropped
+/// without being closed, for example by a NAT timeout or when a laptop goes to sleep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TcpKeepaliveOptions {
+    pub(crate) idle: Duration,
+    pub(crate) interval: Duration,
+    pub(crate) count: u32,
+}
+
+impl Default for TcpKeepaliveOptions {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(300), Duration::from_secs(75), 2)
+    }
+}
+
+impl TcpKeepaliveOptions {
+    /// Send a probe after the connection has been `idle` for this duration, then
+    /// every `interval` until `count` probes are unanswered
+    pub fn new(idle: Duration, interval: Duration, count: u32) -> Self {
+        Self {
+            idle,
+            interval,
+            count,
+        }
+    }
+
+    /// Enable TCP keepalive on a socket.
+    /// A setting not supported by the platform is ignored, with a warning logged once
+    pub(crate) fn apply(&self, stream: &TcpStream) {
+        #[allow(unused_mut)]
+        let mut keepalive = TcpKeepalive::new()
+            .with_time(self.idle)
+            .with_interval(self.interval);
+
+        cfg_if! {
+            if #[cfg(unix)] {
+                keepalive = keepalive.with_retries(self.count);
+            } else {
+                // the options are applied to each connection, the warning is only logged once
+                static WARNED: std::sync::Once = std::sync::Once::new();
+                WARNED.call_once(|| {
+                    warn!(count = self.count, "the number of TCP keepalive probes can't be set on this platform");
+                });
+            }
+        }
+
+        if let Err(e) = SockRef::from(stream).set_tcp_keepalive(&keepalive) {
+            warn!(err = %e, "cannot set TCP keepalive on the socket");
+        }
+    }
+}
+
 /// Trust Options for a TCP connection
 #[derive(Debug)]
 pub struct TcpConnectionOptions {
     pub(super) consumer: Vec<FlowControlId>,
     pub(crate) flow_control_id: FlowControlId,
+    pub(crate) keepalive: Option<TcpKeepaliveOptions>,
+    #[cfg(feature = "tls")]
+    pub(crate) tls: Option<TcpTlsClientOptions>,
 }
 
 impl TcpConnectionOptions {
     #[allow(clippy::new_without_default)]
-    /// Mark this Tcp Receiver as a Producer with a random [`FlowControlId`]
+    /// Mark this Tcp Receiver as a Producer with a random [`FlowControlId`].
+    /// TCP keepalive is enabled with the default [`TcpKeepaliveOptions`]
     pub fn new() -> Self {
         Self {
             consumer: vec![],
             flow_control_id: FlowControls::generate_flow_control_id(),
+            keepalive: Some(TcpKeepaliveOptions::default()),
+            #[cfg(feature = "tls")]
+            tls: None,
         }
     }
 
+    /// Set the TCP keepalive settings of the connection
+    pub fn with_keepalive(mut self, keepalive: TcpKeepaliveOptions) -> Self {
+        self.keepalive = Some(keepalive);
+
+        self
+    }
+
+    /// Disable TCP keepalive for the connection
+    pub fn without_keepalive(mut self) -> Self {
+        self.keepalive = None;
+
+        self
+    }
+
+    /// Establish a TLS session over the TCP connection, before sending any message.
+    /// The listener must accept TLS connections, see [`TcpListenerOptions::with_tls`]
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, tls: TcpTlsClientOptions) -> Self {
+        self.tls = Some(tls);
+
+        self
+    }
+
     /// Mark that this Connection is a Consumer for to the given [`FlowControlId`]
     pub fn as_consumer(mut self, id: &FlowControlId) -> Self {
         self.consumer.push(id.clone());
@@ -71,6 +160,9 @@ impl TcpConnectionOptions {
 #[derive(Debug)]
 pub struct TcpListenerOptions {
     pub(crate) flow_control_id: FlowControlId,
+    pub(crate) keepalive: Option<TcpKeepaliveOptions>,
+    #[cfg(feature = "tls")]
+    pub(crate) tls: Option<TcpTlsServerOptions>,
 }
 
 impl TcpListenerOptions {
@@ -81,9 +173,28 @@ impl TcpListenerOptions {
     pub fn new() -> Self {
         Self {
             flow_control_id: FlowControls::generate_flow_control_id(),
+            keepalive: None,
+            #[cfg(feature = "tls")]
+            tls: None,
         }
     }
 
+    /// Enable TCP keepalive on the accepted connections
+    pub fn with_keepalive(mut self, keepalive: TcpKeepaliveOptions) -> Self {
+        self.keepalive = Some(keepalive);
+
+        self
+    }
+
+    /// Only accept the connections establishing a TLS session, see
+    /// [`TcpConnectionOptions::with_tls`]
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, tls: TcpTlsServerOptions) -> Self {
+        self.tls = Some(tls);
+
+        self
+    }
+
     /// Getter for freshly generated [`FlowControlId`]
     pub fn spawner_flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
@@ -131,3 +242,31 @@ impl TcpListenerOptions {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn keepalive_is_set_on_the_socket() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        assert!(!SockRef::from(&stream).keepalive().unwrap());
+
+        let keepalive =
+            TcpKeepaliveOptions::new(Duration::from_secs(30), Duration::from_secs(5), 3);
+        keepalive.apply(&stream);
+
+        let socket = SockRef::from(&stream);
+        assert!(socket.keepalive().unwrap());
+        cfg_if! {
+            if #[cfg(target_os = "linux")] {
+                assert_eq!(socket.keepalive_time().unwrap(), Duration::from_secs(30));
+                assert_eq!(socket.keepalive_interval().unwrap(), Duration::from_secs(5));
+                assert_eq!(socket.keepalive_retries().unwrap(), 3);
+            }
+        }
+    }
+}
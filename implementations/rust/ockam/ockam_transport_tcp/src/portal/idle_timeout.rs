@@ -0,0 +1,36 @@
+use core::time::Duration;
+use ockam_core::compat::sync::{Arc, Mutex};
+use tokio::time::Instant;
+
+/// Track the last activity of a single portal connection, in either direction,
+/// in order to close the connection once it has been idle for too long
+#[derive(Clone, Debug)]
+pub(crate) struct IdleTimeout {
+    timeout: Duration,
+    last_activity: Arc<Mutex<Instant>>,
+}
+
+impl IdleTimeout {
+    /// Create a new `IdleTimeout`, the connection is considered active at creation
+    pub(crate) fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            last_activity: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Record some traffic on the connection, which resets the timeout
+    pub(crate) fn touch(&self) {
+        if let Ok(mut last_activity) = self.last_activity.lock() {
+            *last_activity = Instant::now();
+        }
+    }
+
+    /// Return the time left before the connection is considered idle
+    pub(crate) fn remaining(&self) -> Duration {
+        match self.last_activity.lock() {
+            Ok(last_activity) => self.timeout.saturating_sub(last_activity.elapsed()),
+            Err(_) => Duration::ZERO,
+        }
+    }
+}
@@ -1,4 +1,5 @@
 mod addresses;
+mod idle_timeout;
 mod inlet_listener;
 pub mod options;
 mod outlet_listener;
@@ -6,6 +7,7 @@ mod portal_message;
 mod portal_receiver;
 mod portal_worker;
 
+pub(crate) use idle_timeout::*;
 pub(crate) use inlet_listener::*;
 pub(crate) use outlet_listener::*;
 pub use portal_message::*;
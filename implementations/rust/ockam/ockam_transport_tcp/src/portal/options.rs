@@ -1,4 +1,7 @@
 use crate::portal::addresses::Addresses;
+use crate::TcpConnectionPool;
+use core::num::NonZeroUsize;
+use core::time::Duration;
 use ockam_core::compat::sync::Arc;
 use ockam_core::flow_control::{FlowControlId, FlowControls};
 use ockam_core::{Address, AllowAll, IncomingAccessControl};
@@ -62,14 +65,28 @@ impl Default for TcpInletOptions {
 pub struct TcpOutletOptions {
     pub(super) consumer: Vec<FlowControlId>,
     pub(super) incoming_access_control: Arc<dyn IncomingAccessControl>,
+    pub(super) idle_timeout: Option<Duration>,
+    pub(super) connection_pool_size: usize,
+    pub(super) shared_connection_pool: Option<TcpConnectionPool>,
+    pub(super) max_connections: Option<NonZeroUsize>,
+    pub(super) max_connections_timeout: Duration,
 }
 
+/// Maximum duration a new portal connection of an Outlet waits for an open connection to be
+/// closed, when the Outlet has reached its maximum number of connections
+pub const DEFAULT_MAX_CONNECTIONS_TIMEOUT: Duration = Duration::from_secs(30);
+
 impl TcpOutletOptions {
     /// Default constructor without Incoming Access Control
     pub fn new() -> Self {
         Self {
             consumer: vec![],
             incoming_access_control: Arc::new(AllowAll),
+            idle_timeout: None,
+            connection_pool_size: 0,
+            shared_connection_pool: None,
+            max_connections: None,
+            max_connections_timeout: DEFAULT_MAX_CONNECTIONS_TIMEOUT,
         }
     }
 
@@ -91,6 +108,58 @@ impl TcpOutletOptions {
         self
     }
 
+    /// Close each connection of the Outlet once no data has been sent or received on it
+    /// for the given duration. The timeout applies to every connection separately
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// Keep a pool of `size` connections to the peer of the Outlet, established before they are
+    /// needed. A new portal connection uses a connection of the pool, if one is available,
+    /// instead of connecting to the peer. The pool is health-checked and the connections closed
+    /// by the peer are replaced, see [`TcpConnectionPool::keep_established`].
+    ///
+    /// The pool is only used by the Outlets created with
+    /// [`TcpTransport::create_outlet`](crate::TcpTransport::create_outlet)
+    pub fn with_connection_pool(mut self, size: usize) -> Self {
+        self.connection_pool_size = size;
+        self
+    }
+
+    /// Connect to the peer of the Outlet with a connection of a [`TcpConnectionPool`], which
+    /// can be shared with other Outlets. A new portal connection waits while the pool has no
+    /// connection available to the peer, which bounds the number of connections to the peer.
+    ///
+    /// When a portal connection is closed by its Inlet, its connection to the peer is returned
+    /// to the pool to be reused, unless the peer has sent data which hasn't been read yet.
+    /// The connections closed by the peer are not returned to the pool. This option takes
+    /// precedence over [`TcpOutletOptions::with_connection_pool`]
+    pub fn with_shared_connection_pool(mut self, pool: TcpConnectionPool) -> Self {
+        self.shared_connection_pool = Some(pool);
+        self
+    }
+
+    /// Limit the number of connections of the Outlet which are open at the same time to
+    /// `max_connections`. When the limit is reached, a new portal connection waits until one of
+    /// the open connections is closed before connecting to the peer. It is closed if no
+    /// connection is closed within [`DEFAULT_MAX_CONNECTIONS_TIMEOUT`], or the duration set with
+    /// [`TcpOutletOptions::with_max_connections_timeout`].
+    ///
+    /// The new portal connections wait in their own worker, so that the Outlet keeps accepting
+    /// connections, and can be stopped, while the limit is reached
+    pub fn with_max_connections(mut self, max_connections: NonZeroUsize) -> Self {
+        self.max_connections = Some(max_connections);
+        self
+    }
+
+    /// Set the maximum duration a new portal connection waits for an open connection to be
+    /// closed, see [`TcpOutletOptions::with_max_connections`]
+    pub fn with_max_connections_timeout(mut self, timeout: Duration) -> Self {
+        self.max_connections_timeout = timeout;
+        self
+    }
+
     /// Mark that this Outlet listener is a Consumer for to the given [`FlowControlId`]
     /// Also, in this case spawned Outlets will be marked as Consumers with [`FlowControlId`]
     /// of the message that was used to create the Outlet
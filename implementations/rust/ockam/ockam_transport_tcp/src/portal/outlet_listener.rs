@@ -1,10 +1,74 @@
 use crate::portal::addresses::{Addresses, PortalType};
-use crate::{PortalMessage, TcpOutletOptions, TcpPortalWorker, TcpRegistry};
-use ockam_core::{async_trait, Address, DenyAll, Result, Routed, Worker};
+use crate::{PortalMessage, TcpConnectionPool, TcpOutletOptions, TcpPortalWorker, TcpRegistry};
+use ockam_core::{async_trait, Address, DenyAll, Result, Route, Routed, Worker};
 use ockam_node::{Context, WorkerBuilder};
 use ockam_transport_core::TransportError;
-use std::net::SocketAddr;
-use tracing::debug;
+use std::net::{IpAddr, SocketAddr};
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::{debug, warn};
+
+/// The TCP service(s) an outlet connects to
+#[derive(Debug, Clone)]
+pub(crate) enum OutletTarget {
+    /// All the connections go to the same peer
+    Peer(SocketAddr),
+    /// Each connection goes to the port requested by its inlet, which must be in the range.
+    ///
+    /// The port is requested with the address following the outlet listener address in the
+    /// onward route of the first message of the connection: an inlet connecting to the port
+    /// `5001` of the outlet `db` uses the route `[..., db, 5001]`
+    PortRange {
+        ip: IpAddr,
+        ports: RangeInclusive<u16>,
+    },
+}
+
+impl OutletTarget {
+    /// Return the peer requested by a message sent to the outlet listener with `onward_route`
+    fn peer(&self, onward_route: &Route) -> Result<SocketAddr> {
+        match self {
+            OutletTarget::Peer(peer) => Ok(*peer),
+            OutletTarget::PortRange { ip, ports } => {
+                let port = onward_route
+                    .iter()
+                    .nth(1)
+                    .and_then(|port| port.address().parse::<u16>().ok())
+                    .ok_or(TransportError::InvalidAddress)?;
+                if ports.contains(&port) {
+                    Ok(SocketAddr::new(*ip, port))
+                } else {
+                    warn!(%port, "the requested port is not in the range of the outlet");
+                    Err(TransportError::InvalidAddress.into())
+                }
+            }
+        }
+    }
+}
+
+/// Slots of the connections of an outlet which can be open at the same time, when they are
+/// limited with [`TcpOutletOptions::with_max_connections`]
+#[derive(Debug, Clone)]
+pub(crate) struct ConnectionSlots {
+    permits: Arc<Semaphore>,
+    timeout: Duration,
+}
+
+impl ConnectionSlots {
+    /// Wait for a free slot, at most for the timeout of the slots.
+    /// Return `None` if no slot has been released in time
+    pub(crate) async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        if self.permits.available_permits() == 0 {
+            debug!("the outlet has reached its maximum number of connections");
+        }
+        match tokio::time::timeout(self.timeout, self.permits.clone().acquire_owned()).await {
+            Ok(Ok(permit)) => Some(permit),
+            _ => None,
+        }
+    }
+}
 
 /// A TCP Portal Outlet listen worker
 ///
@@ -13,17 +77,39 @@ use tracing::debug;
 /// [`TcpTransport::create_outlet`](crate::TcpTransport::create_outlet).
 pub(crate) struct TcpOutletListenWorker {
     registry: TcpRegistry,
-    peer: SocketAddr,
+    target: OutletTarget,
     options: TcpOutletOptions,
+    /// Pool of the connections to the peer, if one is shared with other outlets, or if
+    /// connections must be established in advance
+    pool: Option<TcpConnectionPool>,
+    /// Slots of the connections which can be open at the same time, if they are limited
+    connection_slots: Option<ConnectionSlots>,
 }
 
 impl TcpOutletListenWorker {
     /// Create a new `TcpOutletListenWorker`
-    fn new(registry: TcpRegistry, peer: SocketAddr, options: TcpOutletOptions) -> Self {
+    fn new(registry: TcpRegistry, target: OutletTarget, options: TcpOutletOptions) -> Self {
+        let pool = match (&options.shared_connection_pool, &target) {
+            (Some(pool), _) => Some(pool.clone()),
+            (None, OutletTarget::Peer(peer)) if options.connection_pool_size > 0 => {
+                let pool = TcpConnectionPool::unbounded();
+                pool.keep_established(*peer, options.connection_pool_size);
+                Some(pool)
+            }
+            _ => None,
+        };
+        let connection_slots = options
+            .max_connections
+            .map(|max_connections| ConnectionSlots {
+                permits: Arc::new(Semaphore::new(max_connections.get())),
+                timeout: options.max_connections_timeout,
+            });
         Self {
             registry,
-            peer,
+            target,
             options,
+            pool,
+            connection_slots,
         }
     }
 
@@ -31,14 +117,14 @@ impl TcpOutletListenWorker {
         ctx: &Context,
         registry: TcpRegistry,
         address: Address,
-        peer: SocketAddr,
+        target: OutletTarget,
         options: TcpOutletOptions,
     ) -> Result<()> {
         let access_control = options.incoming_access_control.clone();
 
         options.setup_flow_control_for_outlet_listener(ctx.flow_controls(), &address);
 
-        let worker = Self::new(registry, peer, options);
+        let worker = Self::new(registry, target, options);
         WorkerBuilder::new(worker)
             .with_address(address)
             .with_incoming_access_control_arc(access_control)
@@ -74,11 +160,13 @@ impl Worker for TcpOutletListenWorker {
     ) -> Result<()> {
         let return_route = msg.return_route();
         let src_addr = msg.src_addr();
+        let onward_route = msg.onward_route();
 
         if let PortalMessage::Ping = msg.body() {
         } else {
             return Err(TransportError::Protocol.into());
         }
+        let peer = self.target.peer(&onward_route)?;
 
         let addresses = Addresses::generate(PortalType::Outlet);
 
@@ -88,10 +176,14 @@ impl Worker for TcpOutletListenWorker {
         TcpPortalWorker::start_new_outlet(
             ctx,
             self.registry.clone(),
-            self.peer,
+            peer,
+            self.pool.clone(),
+            self.connection_slots.clone(),
             return_route.clone(),
             addresses.clone(),
+            ctx.address(),
             self.options.incoming_access_control.clone(),
+            self.options.idle_timeout,
         )
         .await?;
 
@@ -1,11 +1,13 @@
 use crate::portal::portal_message::MAX_PAYLOAD_SIZE;
+use crate::portal::IdleTimeout;
 use crate::{PortalInternalMessage, PortalMessage, TcpRegistry};
 use ockam_core::compat::vec::Vec;
 use ockam_core::{async_trait, Encodable, LocalMessage, Route, TransportMessage};
 use ockam_core::{route, Address, Processor, Result};
 use ockam_node::Context;
+use tokio::sync::{oneshot, watch};
 use tokio::{io::AsyncReadExt, net::tcp::OwnedReadHalf};
-use tracing::{error, warn};
+use tracing::{error, info, warn};
 
 /// A TCP Portal receiving message processor
 ///
@@ -15,9 +17,21 @@ use tracing::{error, warn};
 pub(crate) struct TcpPortalRecvProcessor {
     registry: TcpRegistry,
     buf: Vec<u8>,
-    read_half: OwnedReadHalf,
+    /// Read half of the connection, only taken when the processor is shut down
+    read_half: Option<OwnedReadHalf>,
     sender_address: Address,
     onward_route: Route,
+    idle_timeout: Option<IdleTimeout>,
+    /// Notified when the connection must be closed, see `TcpTransport::close_portal_connections`
+    closing: watch::Receiver<()>,
+    /// Receives the read half when the processor is shut down, for a connection of a pool
+    released: Option<oneshot::Sender<OwnedReadHalf>>,
+}
+
+/// Reason for closing a connection whose TCP peer is still connected
+enum Closing {
+    Idle,
+    Requested,
 }
 
 impl TcpPortalRecvProcessor {
@@ -27,13 +41,64 @@ impl TcpPortalRecvProcessor {
         read_half: OwnedReadHalf,
         sender_address: Address,
         onward_route: Route,
+        idle_timeout: Option<IdleTimeout>,
+        released: Option<oneshot::Sender<OwnedReadHalf>>,
     ) -> Self {
+        let closing = registry.watch_portal_connections_closing();
         Self {
             registry,
+            closing,
             buf: Vec::with_capacity(MAX_PAYLOAD_SIZE),
-            read_half,
+            read_half: Some(read_half),
             sender_address,
             onward_route,
+            idle_timeout,
+            released,
+        }
+    }
+
+    /// Read from the tcp stream into the buffer.
+    /// Return the reason for closing the connection if it stayed idle for longer than its idle
+    /// timeout, or if it must be closed
+    async fn read(&mut self) -> std::io::Result<Option<Closing>> {
+        let read_half = match &mut self.read_half {
+            Some(read_half) => read_half,
+            None => return Err(std::io::ErrorKind::NotConnected.into()),
+        };
+        let read = Self::read_until_idle(read_half, &mut self.buf, self.idle_timeout.as_ref());
+        tokio::select! {
+            is_active = read => Ok((!is_active?).then_some(Closing::Idle)),
+            Ok(()) = self.closing.changed() => Ok(Some(Closing::Requested)),
+        }
+    }
+
+    /// Read from the tcp stream into the buffer.
+    /// Return `Ok(false)` if the connection stayed idle for longer than its idle timeout
+    async fn read_until_idle(
+        read_half: &mut OwnedReadHalf,
+        buf: &mut Vec<u8>,
+        idle_timeout: Option<&IdleTimeout>,
+    ) -> std::io::Result<bool> {
+        let idle_timeout = match idle_timeout {
+            Some(idle_timeout) => idle_timeout,
+            None => {
+                read_half.read_buf(buf).await?;
+                return Ok(true);
+            }
+        };
+
+        loop {
+            let remaining = idle_timeout.remaining();
+            if remaining.is_zero() {
+                return Ok(false);
+            }
+            // Reading is cancel safe: when the timeout elapses no data has been read.
+            // The remaining time is checked again since the other direction may have had traffic
+            if let Ok(res) = tokio::time::timeout(remaining, read_half.read_buf(buf)).await {
+                res?;
+                idle_timeout.touch();
+                return Ok(true);
+            }
         }
     }
 }
@@ -51,6 +116,9 @@ impl Processor for TcpPortalRecvProcessor {
     async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
         self.registry
             .remove_portal_receiver_processor(&ctx.address());
+        if let (Some(released), Some(read_half)) = (self.released.take(), self.read_half.take()) {
+            let _ = released.send(read_half);
+        }
 
         Ok(())
     }
@@ -58,15 +126,26 @@ impl Processor for TcpPortalRecvProcessor {
     async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
         self.buf.clear();
 
-        let _len = match self.read_half.read_buf(&mut self.buf).await {
-            Ok(len) => len,
+        let closing = match self.read().await {
+            Ok(closing) => closing,
             Err(err) => {
                 error!("Tcp Portal connection read failed with error: {}", err);
                 return Ok(false);
             }
         };
 
-        if self.buf.is_empty() {
+        match closing {
+            Some(Closing::Idle) => info!(
+                "Tcp Portal connection at {} is closed after being idle",
+                ctx.address()
+            ),
+            Some(Closing::Requested) => {
+                info!("Tcp Portal connection at {} is closed", ctx.address())
+            }
+            None => {}
+        }
+
+        if closing.is_some() || self.buf.is_empty() {
             // Notify Sender that connection was closed
             if let Err(err) = ctx
                 .send(
@@ -1,9 +1,14 @@
 use crate::portal::addresses::{Addresses, PortalType};
-use crate::{PortalInternalMessage, PortalMessage, TcpPortalRecvProcessor, TcpRegistry};
+use crate::portal::{ConnectionSlots, IdleTimeout};
+use crate::transport::is_reusable;
+use crate::{
+    PooledTcpConnection, PortalInternalMessage, PortalMessage, TcpConnectionPool,
+    TcpPortalRecvProcessor, TcpRegistry,
+};
 use core::time::Duration;
 use ockam_core::compat::{boxed::Box, net::SocketAddr, sync::Arc};
 use ockam_core::{
-    async_trait, AllowAll, AllowOnwardAddresses, AllowSourceAddress, Decodable, DenyAll,
+    async_trait, Address, AllowAll, AllowOnwardAddresses, AllowSourceAddress, Decodable, DenyAll,
     IncomingAccessControl, Mailbox, Mailboxes,
 };
 use ockam_core::{Any, Result, Route, Routed, Worker};
@@ -12,6 +17,7 @@ use ockam_transport_core::TransportError;
 use tokio::io::AsyncWriteExt;
 use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
+use tokio::sync::{oneshot, OwnedSemaphorePermit};
 use tracing::{debug, info, trace, warn};
 
 /// Enumerate all `TcpPortalWorker` states
@@ -44,6 +50,22 @@ pub(crate) struct TcpPortalWorker {
     remote_route: Option<Route>,
     is_disconnecting: bool,
     portal_type: PortalType,
+    /// Address of the outlet listener worker which created this worker, for outlets
+    outlet_listener: Option<Address>,
+    /// Activity of the connection, when it must be closed after some idle time
+    idle_timeout: Option<IdleTimeout>,
+    /// Pool of the connections to the peer, for outlets
+    pool: Option<TcpConnectionPool>,
+    /// Connection of the pool, returned to the pool when the worker is dropped if the portal
+    /// connection was closed by the other side while the connection to the peer is still open
+    pooled_connection: Option<PooledTcpConnection>,
+    /// Read half of the pooled connection, given back by the receiver when it stops
+    released_read_half: Option<oneshot::Receiver<OwnedReadHalf>>,
+    /// Slots of the connections of an outlet with a maximum number of connections
+    connection_slots: Option<ConnectionSlots>,
+    /// Slot of the connection among the connections of an outlet with a maximum number of
+    /// connections, released when the worker is dropped
+    _connection_permit: Option<OwnedSemaphorePermit>,
 }
 
 impl TcpPortalWorker {
@@ -63,21 +85,31 @@ impl TcpPortalWorker {
             peer,
             State::SendPing { ping_route },
             Some(stream),
+            None,
+            None,
             addresses,
             PortalType::Inlet,
+            None,
             access_control,
+            None,
         )
         .await
     }
 
-    /// Start a new `TcpPortalWorker` of type [`TypeName::Outlet`]
+    /// Start a new `TcpPortalWorker` of type [`TypeName::Outlet`].
+    /// The worker connects to the peer, with a connection of the pool if one is given
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn start_new_outlet(
         ctx: &Context,
         registry: TcpRegistry,
         peer: SocketAddr,
+        pool: Option<TcpConnectionPool>,
+        connection_slots: Option<ConnectionSlots>,
         pong_route: Route,
         addresses: Addresses,
+        outlet_listener: Address,
         access_control: Arc<dyn IncomingAccessControl>,
+        idle_timeout: Option<Duration>,
     ) -> Result<()> {
         Self::start(
             ctx,
@@ -85,9 +117,13 @@ impl TcpPortalWorker {
             peer,
             State::SendPong { pong_route },
             None,
+            pool,
+            connection_slots,
             addresses,
             PortalType::Outlet,
+            Some(outlet_listener),
             access_control,
+            idle_timeout,
         )
         .await
     }
@@ -100,9 +136,13 @@ impl TcpPortalWorker {
         peer: SocketAddr,
         state: State,
         stream: Option<TcpStream>,
+        pool: Option<TcpConnectionPool>,
+        connection_slots: Option<ConnectionSlots>,
         addresses: Addresses,
         portal_type: PortalType,
+        outlet_listener: Option<Address>,
         access_control: Arc<dyn IncomingAccessControl>,
+        idle_timeout: Option<Duration>,
     ) -> Result<()> {
         info!(
             "Creating new {:?} at internal: {}, remote: {}",
@@ -129,6 +169,13 @@ impl TcpPortalWorker {
             remote_route: None,
             is_disconnecting: false,
             portal_type,
+            outlet_listener,
+            idle_timeout: idle_timeout.map(IdleTimeout::new),
+            pool,
+            pooled_connection: None,
+            released_read_half: None,
+            connection_slots,
+            _connection_permit: None,
         };
 
         let internal_mailbox = Mailbox::new(
@@ -168,11 +215,21 @@ impl TcpPortalWorker {
     async fn start_receiver(&mut self, ctx: &Context, onward_route: Route) -> Result<()> {
         if let Some(rx) = self.read_half.take() {
             let next_hop = onward_route.next()?.clone();
+            // the read half of a pooled connection is needed to return it to the pool
+            let released = if self.pooled_connection.is_some() {
+                let (released, released_read_half) = oneshot::channel();
+                self.released_read_half = Some(released_read_half);
+                Some(released)
+            } else {
+                None
+            };
             let receiver = TcpPortalRecvProcessor::new(
                 self.registry.clone(),
                 rx,
                 self.addresses.internal.clone(),
                 onward_route,
+                self.idle_timeout.clone(),
+                released,
             );
 
             ProcessorBuilder::new(receiver)
@@ -258,6 +315,7 @@ impl TcpPortalWorker {
             }
             DisconnectionReason::Remote => {
                 self.stop_receiver(ctx).await?;
+                self.release_pooled_connection().await;
             }
         }
 
@@ -272,6 +330,39 @@ impl TcpPortalWorker {
         Ok(())
     }
 
+    /// Give the stream back to the pooled connection, if there is one, so that it is returned
+    /// to the pool when the worker is dropped. The stream is closed instead if there is data
+    /// left to read, since it would be received by the next user of the connection
+    async fn release_pooled_connection(&mut self) {
+        let (connection, released_read_half) =
+            match (&mut self.pooled_connection, self.released_read_half.take()) {
+                (Some(connection), Some(released_read_half)) => (connection, released_read_half),
+                _ => return,
+            };
+        let read_half = match tokio::time::timeout(Duration::from_secs(1), released_read_half).await
+        {
+            Ok(Ok(read_half)) => read_half,
+            _ => return,
+        };
+        let write_half = match self.write_half.take() {
+            Some(write_half) => write_half,
+            None => return,
+        };
+        match read_half.reunite(write_half) {
+            Ok(stream) if is_reusable(&stream).await => {
+                connection.reattach(stream);
+                debug!(
+                    "Outlet at: {} released its connection to {} to the pool",
+                    self.addresses.internal, self.peer
+                );
+            }
+            _ => debug!(
+                "Outlet at: {} closed its connection to {} instead of releasing it to the pool",
+                self.addresses.internal, self.peer
+            ),
+        }
+    }
+
     async fn handle_send_ping(&self, ctx: &Context, ping_route: Route) -> Result<State> {
         // Force creation of Outlet on the other side
         ctx.send_from_address(
@@ -295,21 +386,48 @@ impl TcpPortalWorker {
         )
         .await?;
 
+        // Wait for an open connection of the outlet to be closed if the maximum is reached.
+        // The connection is closed if none is closed in time
+        if let Some(connection_slots) = self.connection_slots.take() {
+            match connection_slots.acquire().await {
+                Some(permit) => self._connection_permit = Some(permit),
+                None => {
+                    warn!(
+                        "Outlet at: {} closes a new connection, the outlet has reached its maximum number of connections",
+                        self.addresses.internal
+                    );
+                    self.remote_route = Some(pong_route);
+                    self.start_disconnection(ctx, DisconnectionReason::FailedTx)
+                        .await?;
+                    return Ok(State::Initialized);
+                }
+            }
+        }
+
         if self.write_half.is_none() {
-            let stream = TcpStream::connect(self.peer)
-                .await
-                .map_err(TransportError::from)?;
+            // the connection is acquired by this worker, so that the outlet listener doesn't
+            // wait for a connection of the pool to be released
+            let stream = match self.pool.take() {
+                Some(pool) => {
+                    let mut connection = pool.acquire(self.peer).await?;
+                    let stream = connection.detach();
+                    self.pooled_connection = Some(connection);
+                    stream.ok_or(TransportError::PortalInvalidState)?
+                }
+                None => TcpStream::connect(self.peer)
+                    .await
+                    .map_err(TransportError::from)?,
+            };
             let (rx, tx) = stream.into_split();
             self.write_half = Some(tx);
             self.read_half = Some(rx);
 
-            self.start_receiver(ctx, pong_route.clone()).await?;
-
             debug!(
                 "Outlet at: {} successfully connected",
                 self.addresses.internal
             );
         }
+        self.start_receiver(ctx, pong_route.clone()).await?;
 
         debug!("Outlet at: {} sent pong", self.addresses.internal);
 
@@ -339,12 +457,21 @@ impl Worker for TcpPortalWorker {
         }
 
         self.registry.add_portal_worker(&self.addresses.remote);
+        if let Some(outlet_listener) = &self.outlet_listener {
+            // a connection closed right away is not counted as a connection of the outlet
+            if !self.is_disconnecting {
+                self.registry
+                    .add_outlet_connection(outlet_listener, &self.addresses.remote);
+            }
+        }
 
         Ok(())
     }
 
     async fn shutdown(&mut self, _ctx: &mut Self::Context) -> Result<()> {
         self.registry.remove_portal_worker(&self.addresses.remote);
+        self.registry
+            .remove_outlet_connection(&self.addresses.remote);
 
         Ok(())
     }
@@ -424,7 +551,11 @@ impl Worker for TcpPortalWorker {
                         PortalMessage::Payload(payload) => {
                             if let Some(tx) = &mut self.write_half {
                                 match tx.write_all(&payload).await {
-                                    Ok(()) => {}
+                                    Ok(()) => {
+                                        if let Some(idle_timeout) = &self.idle_timeout {
+                                            idle_timeout.touch();
+                                        }
+                                    }
                                     Err(err) => {
                                         warn!(
                                             "Failed to send message to peer {} with error: {}",
@@ -1,5 +1,6 @@
 use crate::{TcpListenerInfo, TcpReceiverInfo, TcpRegistry, TcpSenderInfo};
 use ockam_core::Address;
+use tokio::sync::watch;
 
 impl TcpRegistry {
     pub(crate) fn add_portal_worker(&self, addr: &Address) {
@@ -42,6 +43,26 @@ impl TcpRegistry {
             lock.remove_outlet_listener_worker(addr);
         }
     }
+    pub(crate) fn add_outlet_connection(&self, outlet: &Address, portal_worker: &Address) {
+        if let Ok(mut lock) = self.registry.write() {
+            lock.add_outlet_connection(outlet, portal_worker);
+        }
+        self.outlet_connections_changed.send_replace(());
+    }
+    pub(crate) fn remove_outlet_connection(&self, portal_worker: &Address) {
+        if let Ok(mut lock) = self.registry.write() {
+            lock.remove_outlet_connection(portal_worker);
+        }
+        self.outlet_connections_changed.send_replace(());
+    }
+    /// Request all the portal receivers to close their connection
+    pub(crate) fn close_portal_connections(&self) {
+        self.portal_connections_closing.send_replace(());
+    }
+    /// Return a receiver notified when the portal connections must be closed
+    pub(crate) fn watch_portal_connections_closing(&self) -> watch::Receiver<()> {
+        self.portal_connections_closing.subscribe()
+    }
     pub(crate) fn add_listener_processor(&self, info: TcpListenerInfo) {
         if let Ok(mut lock) = self.registry.write() {
             lock.add_listener_processor(info);
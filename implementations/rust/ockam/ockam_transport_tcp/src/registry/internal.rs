@@ -7,6 +7,8 @@ pub(super) struct InternalRegistry {
     pub(super) portal_receiver_processors: Vec<Address>,
     pub(super) inlet_listener_processors: Vec<Address>,
     pub(super) outlet_listener_workers: Vec<Address>,
+    /// Pairs of (outlet listener worker, portal worker) for each connection going through an outlet
+    pub(super) outlet_connections: Vec<(Address, Address)>,
     pub(super) listener_processors: Vec<TcpListenerInfo>,
     pub(super) sender_workers: Vec<TcpSenderInfo>,
     pub(super) receiver_processors: Vec<TcpReceiverInfo>,
@@ -37,6 +39,13 @@ impl InternalRegistry {
     pub(super) fn remove_outlet_listener_worker(&mut self, addr: &Address) {
         self.outlet_listener_workers.retain(|x| x != addr);
     }
+    pub(super) fn add_outlet_connection(&mut self, outlet: &Address, portal_worker: &Address) {
+        self.outlet_connections
+            .push((outlet.clone(), portal_worker.clone()))
+    }
+    pub(super) fn remove_outlet_connection(&mut self, portal_worker: &Address) {
+        self.outlet_connections.retain(|(_, x)| x != portal_worker);
+    }
     pub(super) fn add_listener_processor(&mut self, info: TcpListenerInfo) {
         self.listener_processors.push(info)
     }
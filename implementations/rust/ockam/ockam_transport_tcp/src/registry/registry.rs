@@ -1,11 +1,27 @@
 use crate::registry::internal::InternalRegistry;
 use crate::{TcpListenerInfo, TcpReceiverInfo, TcpSenderInfo};
 use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::Address;
+use tokio::sync::watch;
 
 /// Registry of all active workers and processors in TCP Transport to ease their lifecycle management
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct TcpRegistry {
     pub(super) registry: Arc<RwLock<InternalRegistry>>,
+    /// Notified each time a connection through an outlet is opened or closed
+    pub(super) outlet_connections_changed: Arc<watch::Sender<()>>,
+    /// Notified each time all the portal connections must be closed
+    pub(super) portal_connections_closing: Arc<watch::Sender<()>>,
+}
+
+impl Default for TcpRegistry {
+    fn default() -> Self {
+        Self {
+            registry: Default::default(),
+            outlet_connections_changed: Arc::new(watch::channel(()).0),
+            portal_connections_closing: Arc::new(watch::channel(()).0),
+        }
+    }
 }
 
 impl TcpRegistry {
@@ -23,4 +39,21 @@ impl TcpRegistry {
     pub fn get_all_listeners(&self) -> Vec<TcpListenerInfo> {
         self.registry.read().unwrap().listener_processors.clone()
     }
+
+    /// Return the number of connections currently going through the outlet at `outlet`
+    pub fn get_outlet_connections_count(&self, outlet: &Address) -> usize {
+        self.registry
+            .read()
+            .unwrap()
+            .outlet_connections
+            .iter()
+            .filter(|(x, _)| x == outlet)
+            .count()
+    }
+
+    /// Return a receiver notified each time a connection through an outlet is opened or closed,
+    /// see [`TcpRegistry::get_outlet_connections_count`]
+    pub fn watch_outlet_connections(&self) -> watch::Receiver<()> {
+        self.outlet_connections_changed.subscribe()
+    }
 }
@@ -0,0 +1,156 @@
+use crate::workers::{split_stream, ConnectionReadHalf, ConnectionWriteHalf};
+use core::fmt;
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use ockam_transport_core::TransportError;
+use rustls::{Certificate, ClientConfig, PrivateKey, RootCertStore, ServerConfig, ServerName};
+use rustls_pemfile::Item;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use tokio::net::TcpStream;
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use tracing::debug;
+
+/// Maximum duration of a TLS handshake
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// TLS settings of an outgoing TCP connection.
+///
+/// The TLS session is established before any Ockam message is exchanged, so the messages,
+/// including the secure channels messages, are sent inside the TLS session
+#[derive(Clone)]
+pub struct TcpTlsClientOptions {
+    server_name: ServerName,
+    config: Arc<ClientConfig>,
+}
+
+impl fmt::Debug for TcpTlsClientOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpTlsClientOptions")
+            .field("server_name", &self.server_name)
+            .finish()
+    }
+}
+
+impl TcpTlsClientOptions {
+    /// Trust the server certificates issued by the certificate authorities of a PEM file.
+    /// `server_name` is the name the server certificate must be valid for
+    pub fn from_pem_file(server_name: &str, ca_certificates: impl AsRef<Path>) -> Result<Self> {
+        let server_name = ServerName::try_from(server_name)
+            .map_err(|e| tls_error(format!("invalid TLS server name {server_name}: {e}")))?;
+        let mut roots = RootCertStore::empty();
+        for certificate in read_certificates(ca_certificates.as_ref())? {
+            roots
+                .add(&certificate)
+                .map_err(|e| tls_error(format!("invalid CA certificate: {e}")))?;
+        }
+        let config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        Ok(Self {
+            server_name,
+            config: Arc::new(config),
+        })
+    }
+
+    pub(crate) async fn connect(
+        &self,
+        stream: TcpStream,
+    ) -> Result<(ConnectionReadHalf, ConnectionWriteHalf)> {
+        let connector = TlsConnector::from(self.config.clone());
+        let handshake = connector.connect(self.server_name.clone(), stream);
+        let stream = tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, handshake)
+            .await
+            .map_err(|_| TransportError::ConnectionDrop)?
+            .map_err(TransportError::from)?;
+        debug!("TLS session established");
+        Ok(split_stream(stream))
+    }
+}
+
+/// TLS settings of the connections accepted by a TCP listener
+#[derive(Clone)]
+pub struct TcpTlsServerOptions {
+    config: Arc<ServerConfig>,
+}
+
+impl fmt::Debug for TcpTlsServerOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TcpTlsServerOptions")
+    }
+}
+
+impl TcpTlsServerOptions {
+    /// Use the certificate chain and the private key of PEM files.
+    /// The first certificate of the chain is the certificate of the listener
+    pub fn from_pem_files(
+        certificate_chain: impl AsRef<Path>,
+        private_key: impl AsRef<Path>,
+    ) -> Result<Self> {
+        let certificates = read_certificates(certificate_chain.as_ref())?;
+        let private_key = read_private_key(private_key.as_ref())?;
+        let config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(certificates, private_key)
+            .map_err(|e| tls_error(format!("invalid TLS certificate or key: {e}")))?;
+        Ok(Self {
+            config: Arc::new(config),
+        })
+    }
+
+    pub(crate) async fn accept(
+        &self,
+        stream: TcpStream,
+    ) -> Result<(ConnectionReadHalf, ConnectionWriteHalf)> {
+        let acceptor = TlsAcceptor::from(self.config.clone());
+        let stream = tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream))
+            .await
+            .map_err(|_| TransportError::ConnectionDrop)?
+            .map_err(TransportError::from)?;
+        debug!("TLS session accepted");
+        Ok(split_stream(stream))
+    }
+}
+
+fn read_pem_file(path: &Path) -> Result<Vec<Item>> {
+    let file =
+        File::open(path).map_err(|e| tls_error(format!("cannot open {}: {e}", path.display())))?;
+    rustls_pemfile::read_all(&mut BufReader::new(file))
+        .map_err(|e| tls_error(format!("cannot read {}: {e}", path.display())))
+}
+
+fn read_certificates(path: &Path) -> Result<Vec<Certificate>> {
+    let certificates: Vec<Certificate> = read_pem_file(path)?
+        .into_iter()
+        .filter_map(|item| match item {
+            Item::X509Certificate(der) => Some(Certificate(der)),
+            _ => None,
+        })
+        .collect();
+    if certificates.is_empty() {
+        return Err(tls_error(format!(
+            "no certificate found in {}",
+            path.display()
+        )));
+    }
+    Ok(certificates)
+}
+
+fn read_private_key(path: &Path) -> Result<PrivateKey> {
+    read_pem_file(path)?
+        .into_iter()
+        .find_map(|item| match item {
+            Item::PKCS8Key(der) | Item::RSAKey(der) | Item::ECKey(der) => Some(PrivateKey(der)),
+            _ => None,
+        })
+        .ok_or_else(|| tls_error(format!("no private key found in {}", path.display())))
+}
+
+fn tls_error(message: String) -> Error {
+    Error::new(Origin::Transport, Kind::Invalid, message)
+}
@@ -0,0 +1,538 @@
+use core::fmt;
+use core::time::Duration;
+use ockam_core::compat::collections::{BTreeMap, VecDeque};
+use ockam_core::compat::net::SocketAddr;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use ockam_transport_core::TransportError;
+use std::time::Instant;
+use tokio::net::TcpStream;
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Interval between two health checks of the connections established in advance by a
+/// [`TcpConnectionPool`], see [`TcpConnectionPool::keep_established`]
+pub(crate) const CONNECTION_POOL_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A pool of TCP connections, keyed by the address of their peer, which can be shared by
+/// several users, for example by the Outlets connecting to the same service.
+///
+/// At most `max_size` connections are in use for each peer: when they are all in use,
+/// [`TcpConnectionPool::acquire`] waits until one of them is released. A connection is
+/// returned to the pool when its [`PooledTcpConnection`] is dropped, and it is reused by the
+/// next acquisition for the same peer unless it has been idle for longer than `idle_timeout`
+/// or it has been closed by the peer.
+///
+/// Connections can also be established before they are needed, with
+/// [`TcpConnectionPool::keep_established`], so that a new user doesn't wait for the peer to
+/// accept a connection.
+#[derive(Clone)]
+pub struct TcpConnectionPool {
+    inner: Arc<PoolInner>,
+}
+
+struct PoolInner {
+    max_size: usize,
+    idle_timeout: Duration,
+    peers: Mutex<BTreeMap<SocketAddr, Arc<PeerConnections>>>,
+    /// Tasks establishing connections in advance, stopped when the pool is dropped
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+}
+
+/// Connections to one peer of a [`TcpConnectionPool`]
+struct PeerConnections {
+    idle: Mutex<VecDeque<IdleConnection>>,
+    permits: Arc<Semaphore>,
+    /// Notified when an idle connection is taken, to establish a new one in advance
+    refill: Notify,
+}
+
+struct IdleConnection {
+    stream: TcpStream,
+    since: Instant,
+}
+
+impl TcpConnectionPool {
+    /// Create a pool keeping at most `max_size` connections to each peer, at least one, and
+    /// closing the connections left idle for longer than `idle_timeout`
+    pub fn new(max_size: usize, idle_timeout: Duration) -> Self {
+        Self {
+            inner: Arc::new(PoolInner {
+                max_size: max_size.clamp(1, Semaphore::MAX_PERMITS),
+                idle_timeout,
+                peers: Default::default(),
+                tasks: Default::default(),
+            }),
+        }
+    }
+
+    /// Create a pool which doesn't limit the number of connections to a peer and never closes
+    /// its idle connections, for example to establish connections in advance
+    pub fn unbounded() -> Self {
+        Self::new(Semaphore::MAX_PERMITS, Duration::MAX)
+    }
+
+    /// Maximum number of connections to each peer
+    pub fn max_size(&self) -> usize {
+        self.inner.max_size
+    }
+
+    /// Duration after which an idle connection is closed
+    pub fn idle_timeout(&self) -> Duration {
+        self.inner.idle_timeout
+    }
+
+    /// Return a connection to the peer, reusing an idle connection if there is a healthy one,
+    /// and waiting for a connection to be released if `max_size` connections are in use
+    pub async fn acquire(&self, peer: SocketAddr) -> Result<PooledTcpConnection> {
+        let connections = self.connections(peer);
+        let permit = connections
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| TransportError::ConnectionDrop)?;
+
+        while let Some(idle) = connections.pop_idle() {
+            if idle.since.elapsed() > self.inner.idle_timeout {
+                debug!(%peer, "evicting an idle connection of the pool");
+            } else if !is_healthy(&idle.stream).await {
+                debug!(%peer, "dropping a closed connection of the pool");
+            } else {
+                return Ok(PooledTcpConnection::new(idle.stream, connections, permit));
+            }
+        }
+
+        let stream = TcpStream::connect(peer)
+            .await
+            .map_err(TransportError::from)?;
+        Ok(PooledTcpConnection::new(stream, connections, permit))
+    }
+
+    /// Keep `count` idle connections to the peer established in advance.
+    ///
+    /// A background task replaces the connections as soon as they are acquired, and checks the
+    /// idle connections periodically to replace the ones closed by the peer. It stops when the
+    /// pool is dropped
+    pub fn keep_established(&self, peer: SocketAddr, count: usize) {
+        self.keep_established_with_interval(peer, count, CONNECTION_POOL_HEALTH_CHECK_INTERVAL)
+    }
+
+    pub(crate) fn keep_established_with_interval(
+        &self,
+        peer: SocketAddr,
+        count: usize,
+        health_check_interval: Duration,
+    ) {
+        let task = tokio::spawn(maintain(
+            peer,
+            count,
+            health_check_interval,
+            self.connections(peer),
+        ));
+        self.inner.tasks.lock().unwrap().push(task);
+    }
+
+    /// Close the connections which have been idle for longer than the idle timeout.
+    ///
+    /// Idle connections are also evicted when a connection is acquired for their peer, so
+    /// calling this function is only necessary to release the connections of peers which are
+    /// not used anymore
+    pub fn evict_idle(&self) {
+        let idle_timeout = self.inner.idle_timeout;
+        let mut peers = self.inner.peers.lock().unwrap();
+        for (peer, connections) in peers.iter() {
+            let mut idle = connections.idle.lock().unwrap();
+            let before = idle.len();
+            idle.retain(|c| c.since.elapsed() <= idle_timeout);
+            let evicted = before - idle.len();
+            if evicted > 0 {
+                debug!(%peer, %evicted, "evicting idle connections of the pool");
+            }
+        }
+        // forget the peers without any connection, the connections in use and the tasks
+        // establishing connections in advance hold a reference to their peer
+        peers.retain(|_, c| Arc::strong_count(c) > 1 || !c.idle.lock().unwrap().is_empty());
+    }
+
+    /// Return the number of idle connections to the peer
+    pub fn idle_count(&self, peer: &SocketAddr) -> usize {
+        self.inner
+            .peers
+            .lock()
+            .unwrap()
+            .get(peer)
+            .map(|c| c.idle.lock().unwrap().len())
+            .unwrap_or(0)
+    }
+
+    /// Return the connections to a peer, creating them if necessary
+    fn connections(&self, peer: SocketAddr) -> Arc<PeerConnections> {
+        self.inner
+            .peers
+            .lock()
+            .unwrap()
+            .entry(peer)
+            .or_insert_with(|| {
+                Arc::new(PeerConnections {
+                    idle: Default::default(),
+                    permits: Arc::new(Semaphore::new(self.inner.max_size)),
+                    refill: Notify::new(),
+                })
+            })
+            .clone()
+    }
+}
+
+impl Drop for PoolInner {
+    fn drop(&mut self) {
+        for task in self.tasks.lock().unwrap().drain(..) {
+            task.abort()
+        }
+    }
+}
+
+impl PeerConnections {
+    /// Take the most recently released idle connection
+    fn pop_idle(&self) -> Option<IdleConnection> {
+        let idle = self.idle.lock().unwrap().pop_back();
+        if idle.is_some() {
+            self.refill.notify_one();
+        }
+        idle
+    }
+}
+
+/// Drop the unhealthy idle connections and connect to the peer until there are `count` idle
+/// connections, after a connection is taken or at each health check
+async fn maintain(
+    peer: SocketAddr,
+    count: usize,
+    health_check_interval: Duration,
+    connections: Arc<PeerConnections>,
+) {
+    // the unavailability of the peer is only reported when it changes, since the pool
+    // keeps trying to connect to it at each health check
+    let mut reachable = true;
+    loop {
+        let idle: Vec<IdleConnection> = connections.idle.lock().unwrap().drain(..).collect();
+        let mut healthy = VecDeque::with_capacity(count);
+        for connection in idle {
+            if is_healthy(&connection.stream).await {
+                healthy.push_back(connection);
+            } else {
+                debug!(%peer, "dropping a closed connection of the pool");
+            }
+        }
+        // the connections released after being used are kept if they are the most recent ones
+        while healthy.len() > count {
+            healthy.pop_front();
+        }
+        while healthy.len() < count {
+            match TcpStream::connect(peer).await {
+                Ok(stream) => {
+                    if !reachable {
+                        info!(%peer, "the connections of the pool can be established again");
+                        reachable = true;
+                    }
+                    healthy.push_front(IdleConnection {
+                        stream,
+                        since: Instant::now(),
+                    })
+                }
+                Err(e) => {
+                    // the peer is not available, try again at the next health check
+                    if reachable {
+                        warn!(%peer, %e, "cannot add a connection to the pool");
+                        reachable = false;
+                    } else {
+                        debug!(%peer, %e, "cannot add a connection to the pool");
+                    }
+                    break;
+                }
+            }
+        }
+        {
+            // the connections released in the meantime are the most recent ones
+            let mut idle = connections.idle.lock().unwrap();
+            for connection in healthy.into_iter().rev() {
+                idle.push_front(connection);
+            }
+        }
+
+        tokio::select! {
+            _ = connections.refill.notified() => {}
+            _ = tokio::time::sleep(health_check_interval) => {}
+        }
+    }
+}
+
+/// Return true if a connection is still open.
+///
+/// The connection is checked by peeking at its incoming data without waiting, so that the data
+/// sent by the peer before the connection is used, like a greeting, is not consumed
+pub(crate) async fn is_healthy(stream: &TcpStream) -> bool {
+    let mut buffer = [0u8; 1];
+    match tokio::time::timeout(Duration::ZERO, stream.peek(&mut buffer)).await {
+        // nothing to read yet
+        Err(_) => true,
+        Ok(Ok(length)) => length > 0,
+        Ok(Err(_)) => false,
+    }
+}
+
+/// Return true if a connection which has been used can be used again: it is still open and
+/// there is no data left to read, which would be received by its next user
+pub(crate) async fn is_reusable(stream: &TcpStream) -> bool {
+    let mut buffer = [0u8; 1];
+    tokio::time::timeout(Duration::ZERO, stream.peek(&mut buffer))
+        .await
+        .is_err()
+}
+
+impl fmt::Debug for TcpConnectionPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpConnectionPool")
+            .field("max_size", &self.inner.max_size)
+            .field("idle_timeout", &self.inner.idle_timeout)
+            .finish()
+    }
+}
+
+/// A connection acquired from a [`TcpConnectionPool`].
+///
+/// The connection is returned to the pool when this value is dropped, unless its stream has
+/// been detached and not reattached, or discarded. In any case, the connection counts towards
+/// the maximum size of the pool until this value is dropped.
+pub struct PooledTcpConnection {
+    stream: Option<TcpStream>,
+    connections: Arc<PeerConnections>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl PooledTcpConnection {
+    fn new(
+        stream: TcpStream,
+        connections: Arc<PeerConnections>,
+        permit: OwnedSemaphorePermit,
+    ) -> Self {
+        Self {
+            stream: Some(stream),
+            connections,
+            _permit: permit,
+        }
+    }
+
+    /// Return the stream of the connection, if it hasn't been detached
+    pub fn stream(&self) -> Option<&TcpStream> {
+        self.stream.as_ref()
+    }
+
+    /// Return the stream of the connection, if it hasn't been detached
+    pub fn stream_mut(&mut self) -> Option<&mut TcpStream> {
+        self.stream.as_mut()
+    }
+
+    /// Take the stream out of the connection, so that it is not returned to the pool unless
+    /// it is given back with [`PooledTcpConnection::reattach`]
+    pub fn detach(&mut self) -> Option<TcpStream> {
+        self.stream.take()
+    }
+
+    /// Give back the stream taken with [`PooledTcpConnection::detach`], so that it is returned
+    /// to the pool when this value is dropped
+    pub fn reattach(&mut self, stream: TcpStream) {
+        self.stream = Some(stream)
+    }
+
+    /// Close the stream instead of returning it to the pool, for example after an error
+    pub fn discard(&mut self) {
+        self.stream = None
+    }
+}
+
+impl Drop for PooledTcpConnection {
+    fn drop(&mut self) {
+        if let Some(stream) = self.stream.take() {
+            self.connections
+                .idle
+                .lock()
+                .unwrap()
+                .push_back(IdleConnection {
+                    stream,
+                    since: Instant::now(),
+                });
+        }
+    }
+}
+
+impl fmt::Debug for PooledTcpConnection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledTcpConnection")
+            .field("stream", &self.stream)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Return true if the listener doesn't accept a new connection in time
+    async fn no_new_connection(listener: &TcpListener) -> bool {
+        tokio::time::timeout(Duration::from_millis(100), listener.accept())
+            .await
+            .is_err()
+    }
+
+    fn local_addr(connection: &PooledTcpConnection) -> SocketAddr {
+        connection.stream().unwrap().local_addr().unwrap()
+    }
+
+    #[tokio::test]
+    async fn a_released_connection_is_reused() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let peer = listener.local_addr().unwrap();
+        let pool = TcpConnectionPool::new(2, Duration::from_secs(60));
+
+        let connection = pool.acquire(peer).await.unwrap();
+        let (_accepted, _) = listener.accept().await.unwrap();
+        let first = local_addr(&connection);
+        drop(connection);
+        assert_eq!(pool.idle_count(&peer), 1);
+
+        let connection = pool.acquire(peer).await.unwrap();
+        assert_eq!(local_addr(&connection), first);
+        assert_eq!(pool.idle_count(&peer), 0);
+        assert!(no_new_connection(&listener).await);
+
+        // a detached connection is not returned to the pool, unless it is reattached
+        let mut connection = connection;
+        let stream = connection.detach().unwrap();
+        connection.reattach(stream);
+        drop(connection);
+        assert_eq!(pool.idle_count(&peer), 1);
+        let mut connection = pool.acquire(peer).await.unwrap();
+        let _stream = connection.detach();
+        drop(connection);
+        assert_eq!(pool.idle_count(&peer), 0);
+    }
+
+    async fn wait_for_idle_connections(pool: &TcpConnectionPool, peer: SocketAddr, n: usize) {
+        for _ in 0..50 {
+            if pool.idle_count(&peer) == n {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("The pool should have {n} idle connections");
+    }
+
+    #[tokio::test]
+    async fn connections_established_in_advance_are_replaced() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let peer = listener.local_addr().unwrap();
+        let pool = TcpConnectionPool::unbounded();
+        pool.keep_established_with_interval(peer, 2, Duration::from_millis(50));
+
+        let (first, _) = listener.accept().await.unwrap();
+        let (_second, _) = listener.accept().await.unwrap();
+        wait_for_idle_connections(&pool, peer, 2).await;
+
+        // a connection closed by the peer is replaced at the next health check
+        drop(first);
+        let (_third, _) = listener.accept().await.unwrap();
+        wait_for_idle_connections(&pool, peer, 2).await;
+
+        // an acquired connection is replaced right away
+        let connection = pool.acquire(peer).await.unwrap();
+        let (_fourth, _) = listener.accept().await.unwrap();
+        wait_for_idle_connections(&pool, peer, 2).await;
+
+        // a released connection is kept instead of an older one
+        let released = local_addr(&connection);
+        drop(connection);
+        wait_for_idle_connections(&pool, peer, 2).await;
+        assert_eq!(local_addr(&pool.acquire(peer).await.unwrap()), released);
+    }
+
+    #[tokio::test]
+    async fn a_closed_connection_is_not_acquired() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let peer = listener.local_addr().unwrap();
+        let pool = TcpConnectionPool::unbounded();
+        // the health check runs after the connection is closed only when it is acquired
+        pool.keep_established_with_interval(peer, 1, Duration::from_secs(3600));
+
+        let (first, _) = listener.accept().await.unwrap();
+        wait_for_idle_connections(&pool, peer, 1).await;
+        drop(first);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // a new connection is established instead, and the pool is refilled
+        let connection = pool.acquire(peer).await.unwrap();
+        let (_second, _) = listener.accept().await.unwrap();
+        let (_third, _) = listener.accept().await.unwrap();
+        wait_for_idle_connections(&pool, peer, 1).await;
+        assert!(is_healthy(connection.stream().unwrap()).await);
+    }
+
+    #[tokio::test]
+    async fn idle_and_closed_connections_are_evicted() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let peer = listener.local_addr().unwrap();
+        let pool = TcpConnectionPool::new(2, Duration::from_millis(50));
+
+        // a connection idle for too long is not reused
+        let connection = pool.acquire(peer).await.unwrap();
+        let (_accepted, _) = listener.accept().await.unwrap();
+        let first = local_addr(&connection);
+        drop(connection);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let connection = pool.acquire(peer).await.unwrap();
+        let (accepted, _) = listener.accept().await.unwrap();
+        assert_ne!(local_addr(&connection), first);
+
+        // a connection closed by the peer is not reused
+        let second = local_addr(&connection);
+        drop(connection);
+        drop(accepted);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let connection = pool.acquire(peer).await.unwrap();
+        let (_accepted, _) = listener.accept().await.unwrap();
+        assert_ne!(local_addr(&connection), second);
+
+        // the idle connections are evicted without acquiring a new connection
+        drop(connection);
+        assert_eq!(pool.idle_count(&peer), 1);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        pool.evict_idle();
+        assert_eq!(pool.idle_count(&peer), 0);
+    }
+
+    #[tokio::test]
+    async fn acquiring_waits_when_the_pool_is_full() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let peer = listener.local_addr().unwrap();
+        let pool = TcpConnectionPool::new(1, Duration::from_secs(60));
+
+        let mut connection = pool.acquire(peer).await.unwrap();
+        let (_accepted, _) = listener.accept().await.unwrap();
+        let _stream = connection.detach();
+
+        let waiting = {
+            let pool = pool.clone();
+            tokio::spawn(async move { pool.acquire(peer).await })
+        };
+        assert!(no_new_connection(&listener).await);
+        assert!(!waiting.is_finished());
+
+        // the detached connection counts until the pooled connection is dropped
+        drop(connection);
+        let (_accepted, _) = listener.accept().await.unwrap();
+        assert!(waiting.await.unwrap().is_ok());
+    }
+}
@@ -1,10 +1,12 @@
 mod common;
 mod connection;
+mod connection_pool;
 mod lifecycle;
 mod listener;
 mod portals;
 
 pub use common::*;
+pub use connection_pool::*;
 
 pub use crate::portal::options::*;
 
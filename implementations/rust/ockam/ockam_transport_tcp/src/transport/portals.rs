@@ -1,8 +1,11 @@
+use crate::portal::OutletTarget;
 use crate::portal::TcpInletListenProcessor;
 use crate::transport::common::{parse_socket_addr, resolve_peer};
 use crate::{TcpInletOptions, TcpOutletListenWorker, TcpOutletOptions, TcpTransport};
+use core::ops::RangeInclusive;
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::{Address, Result, Route};
+use ockam_transport_core::TransportError;
 
 impl TcpTransport {
     /// Create Tcp Inlet that listens on bind_addr, transforms Tcp stream into Ockam Routable
@@ -88,7 +91,50 @@ impl TcpTransport {
             &self.ctx,
             self.registry.clone(),
             address.into(),
-            peer_addr,
+            OutletTarget::Peer(peer_addr),
+            options,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Create a Tcp Outlet Listener at address, that connects each of its connections to one
+    /// of the `ports` of `host`.
+    ///
+    /// The port of a connection is requested by its inlet with the address following the
+    /// outlet address in the outlet route: an inlet created with the route
+    /// `route!["outlet", "5432"]` is connected to the port `5432` of the host. The connections
+    /// requesting another port, or no port at all, are refused.
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{TcpOutletOptions, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::{AllowAll, Result};
+    /// # async fn test(ctx: Context) -> Result<()> {
+    ///
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// tcp.create_outlet_port_range("outlet", "localhost", 9000..=9010, TcpOutletOptions::new()).await?;
+    /// # tcp.stop_outlet("outlet").await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_outlet_port_range(
+        &self,
+        address: impl Into<Address>,
+        host: impl Into<String>,
+        ports: RangeInclusive<u16>,
+        options: TcpOutletOptions,
+    ) -> Result<()> {
+        if ports.is_empty() {
+            return Err(TransportError::InvalidAddress.into());
+        }
+        // Resolve the host with the first port of the range
+        let ip = resolve_peer(format!("{}:{}", host.into(), ports.start()))?.ip();
+        TcpOutletListenWorker::start(
+            &self.ctx,
+            self.registry.clone(),
+            address.into(),
+            OutletTarget::PortRange { ip, ports },
             options,
         )
         .await?;
@@ -113,4 +159,13 @@ impl TcpTransport {
         self.ctx.stop_worker(addr).await?;
         Ok(())
     }
+
+    /// Close all the connections going through the inlets and outlets of this transport.
+    ///
+    /// Each connection is closed as if its TCP peer had disconnected: its TCP stream is shut
+    /// down and the other side of the portal is notified, which closes its own TCP stream.
+    /// The inlets and outlets keep accepting new connections
+    pub fn close_portal_connections(&self) {
+        self.registry.close_portal_connections()
+    }
 }
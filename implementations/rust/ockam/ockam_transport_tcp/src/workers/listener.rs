@@ -1,11 +1,17 @@
 use crate::workers::{Addresses, TcpRecvProcessor};
-use crate::{TcpConnectionMode, TcpListenerInfo, TcpListenerOptions, TcpRegistry, TcpSendWorker};
+use crate::{
+    split_tcp_stream, ConnectionReadHalf, ConnectionWriteHalf, TcpConnectionMode, TcpListenerInfo,
+    TcpListenerOptions, TcpRegistry, TcpSendWorker,
+};
 use ockam_core::{async_trait, compat::net::SocketAddr};
 use ockam_core::{Address, Processor, Result};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
 use tokio::net::TcpListener;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 use tracing::debug;
+#[cfg(feature = "tls")]
+use tracing::warn;
 
 /// A TCP Listen processor
 ///
@@ -17,8 +23,12 @@ pub(crate) struct TcpListenProcessor {
     inner: TcpListener,
     socket_address: SocketAddr,
     options: TcpListenerOptions,
+    /// Connections for which a TLS session was established
+    tls_sessions: (UnboundedSender<TlsSession>, UnboundedReceiver<TlsSession>),
 }
 
+type TlsSession = (ConnectionReadHalf, ConnectionWriteHalf, SocketAddr);
+
 impl TcpListenProcessor {
     pub(crate) async fn start(
         ctx: &Context,
@@ -40,6 +50,7 @@ impl TcpListenProcessor {
             inner,
             socket_address: saddr,
             options,
+            tls_sessions: unbounded_channel(),
         };
 
         ctx.start_processor(address.clone(), processor).await?;
@@ -73,10 +84,52 @@ impl Processor for TcpListenProcessor {
     async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
         debug!("Waiting for incoming TCP connection...");
 
-        // Wait for an incoming connection
-        let (stream, peer) = self.inner.accept().await.map_err(TransportError::from)?;
+        // Wait for an incoming connection, or for the TLS handshake of an accepted connection
+        let (stream, peer) = tokio::select! {
+            accepted = self.inner.accept() => accepted.map_err(TransportError::from)?,
+            Some((read_half, write_half, peer)) = self.tls_sessions.1.recv() => {
+                self.start_connection(ctx, read_half, write_half, peer).await?;
+                return Ok(true);
+            }
+        };
         debug!("TCP connection accepted");
+        if let Some(keepalive) = &self.options.keepalive {
+            keepalive.apply(&stream);
+        }
+
+        #[cfg(feature = "tls")]
+        if let Some(tls) = &self.options.tls {
+            // the handshake runs in its own task so that a slow peer doesn't prevent
+            // the other connections from being accepted
+            let tls = tls.clone();
+            let tls_sessions = self.tls_sessions.0.clone();
+            tokio::spawn(async move {
+                match tls.accept(stream).await {
+                    Ok((read_half, write_half)) => {
+                        let _ = tls_sessions.send((read_half, write_half, peer));
+                    }
+                    Err(e) => warn!(%peer, err = %e, "TLS handshake failed"),
+                }
+            });
+            return Ok(true);
+        }
+
+        let (read_half, write_half) = split_tcp_stream(stream);
+        self.start_connection(ctx, read_half, write_half, peer)
+            .await?;
+
+        Ok(true)
+    }
+}
 
+impl TcpListenProcessor {
+    async fn start_connection(
+        &self,
+        ctx: &Context,
+        read_half: ConnectionReadHalf,
+        write_half: ConnectionWriteHalf,
+        peer: SocketAddr,
+    ) -> Result<()> {
         let mode = TcpConnectionMode::Incoming;
         let addresses = Addresses::generate(mode);
 
@@ -87,8 +140,6 @@ impl Processor for TcpListenProcessor {
             .options
             .create_access_control(ctx.flow_controls(), receiver_flow_control_id.clone());
 
-        let (read_half, write_half) = stream.into_split();
-
         // Worker to receive messages from the Node and send them over the wire
         TcpSendWorker::start(
             ctx,
@@ -113,8 +164,6 @@ impl Processor for TcpListenProcessor {
             &receiver_flow_control_id,
             access_control.receiver_outgoing_access_control,
         )
-        .await?;
-
-        Ok(true)
+        .await
     }
 }
@@ -1,5 +1,7 @@
 use crate::workers::Addresses;
-use crate::{TcpConnectionMode, TcpReceiverInfo, TcpRegistry, TcpSendWorkerMsg};
+use crate::{
+    ConnectionReadHalf, TcpConnectionMode, TcpReceiverInfo, TcpRegistry, TcpSendWorkerMsg,
+};
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::compat::sync::Arc;
 use ockam_core::flow_control::FlowControlId;
@@ -9,7 +11,7 @@ use ockam_core::{
 use ockam_core::{Decodable, LocalMessage, Processor, Result, TransportMessage};
 use ockam_node::{Context, ProcessorBuilder};
 use ockam_transport_core::TransportError;
-use tokio::{io::AsyncReadExt, net::tcp::OwnedReadHalf};
+use tokio::io::AsyncReadExt;
 use tracing::{error, info, trace};
 
 /// A TCP receiving message processor
@@ -22,7 +24,7 @@ use tracing::{error, info, trace};
 /// the node message system.
 pub(crate) struct TcpRecvProcessor {
     registry: TcpRegistry,
-    read_half: OwnedReadHalf,
+    read_half: ConnectionReadHalf,
     socket_address: SocketAddr,
     addresses: Addresses,
     mode: TcpConnectionMode,
@@ -33,7 +35,7 @@ impl TcpRecvProcessor {
     /// Create a new `TcpRecvProcessor`
     fn new(
         registry: TcpRegistry,
-        read_half: OwnedReadHalf,
+        read_half: ConnectionReadHalf,
         socket_address: SocketAddr,
         addresses: Addresses,
         mode: TcpConnectionMode,
@@ -53,7 +55,7 @@ impl TcpRecvProcessor {
     pub async fn start(
         ctx: &Context,
         registry: TcpRegistry,
-        read_half: OwnedReadHalf,
+        read_half: ConnectionReadHalf,
         addresses: &Addresses,
         socket_address: SocketAddr,
         mode: TcpConnectionMode,
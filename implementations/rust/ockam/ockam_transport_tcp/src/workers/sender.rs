@@ -1,7 +1,8 @@
 use crate::workers::Addresses;
-use crate::{TcpConnectionMode, TcpRegistry, TcpSenderInfo};
-use cfg_if::cfg_if;
-use core::time::Duration;
+use crate::{
+    split_tcp_stream, ConnectionReadHalf, ConnectionWriteHalf, TcpConnectionMode,
+    TcpConnectionOptions, TcpRegistry, TcpSenderInfo,
+};
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::{
     async_trait,
@@ -15,9 +16,7 @@ use ockam_core::{
 use ockam_node::{Context, WorkerBuilder};
 use ockam_transport_core::TransportError;
 use serde::{Deserialize, Serialize};
-use socket2::{SockRef, TcpKeepalive};
 use tokio::io::AsyncWriteExt;
-use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
 use tracing::{debug, info, trace, warn};
 
@@ -36,7 +35,7 @@ pub(crate) enum TcpSendWorkerMsg {
 /// to dispatch to a remote peer.
 pub(crate) struct TcpSendWorker {
     registry: TcpRegistry,
-    write_half: OwnedWriteHalf,
+    write_half: ConnectionWriteHalf,
     socket_address: SocketAddr,
     addresses: Addresses,
     mode: TcpConnectionMode,
@@ -48,7 +47,7 @@ impl TcpSendWorker {
     /// Create a new `TcpSendWorker`
     fn new(
         registry: TcpRegistry,
-        write_half: OwnedWriteHalf,
+        write_half: ConnectionWriteHalf,
         socket_address: SocketAddr,
         addresses: Addresses,
         mode: TcpConnectionMode,
@@ -73,7 +72,7 @@ impl TcpSendWorker {
     pub(crate) async fn start(
         ctx: &Context,
         registry: TcpRegistry,
-        write_half: OwnedWriteHalf,
+        write_half: ConnectionWriteHalf,
         addresses: &Addresses,
         socket_address: SocketAddr,
         mode: TcpConnectionMode,
@@ -121,7 +120,8 @@ impl TcpSendWorker {
 
     pub(crate) async fn connect(
         socket_address: SocketAddr,
-    ) -> Result<(OwnedReadHalf, OwnedWriteHalf)> {
+        options: &TcpConnectionOptions,
+    ) -> Result<(ConnectionReadHalf, ConnectionWriteHalf)> {
         debug!(addr = %socket_address, "Connecting");
         let connection = match TcpStream::connect(socket_address).await {
             Ok(c) => {
@@ -134,20 +134,19 @@ impl TcpSendWorker {
             }
         };
 
-        let mut keepalive = TcpKeepalive::new()
-            .with_time(Duration::from_secs(300))
-            .with_interval(Duration::from_secs(75));
-
-        cfg_if! {
-            if #[cfg(unix)] {
-               keepalive = keepalive.with_retries(2);
-            }
+        if let Some(keepalive) = &options.keepalive {
+            keepalive.apply(&connection);
         }
 
-        let socket = SockRef::from(&connection);
-        socket.set_tcp_keepalive(&keepalive).unwrap();
+        #[cfg(feature = "tls")]
+        if let Some(tls) = &options.tls {
+            return tls.connect(connection).await.map_err(|e| {
+                debug!(addr = %socket_address, err = %e, "Failed to establish a TLS session");
+                e
+            });
+        }
 
-        Ok(connection.into_split())
+        Ok(split_tcp_stream(connection))
     }
 }
 
@@ -0,0 +1,24 @@
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+
+/// Half of a connection used to receive messages, either a plain TCP stream or a TLS stream
+pub(crate) type ConnectionReadHalf = Box<dyn AsyncRead + Send + Sync + Unpin>;
+
+/// Half of a connection used to send messages, either a plain TCP stream or a TLS stream
+pub(crate) type ConnectionWriteHalf = Box<dyn AsyncWrite + Send + Sync + Unpin>;
+
+/// Split a plain TCP stream
+pub(crate) fn split_tcp_stream(stream: TcpStream) -> (ConnectionReadHalf, ConnectionWriteHalf) {
+    let (read_half, write_half) = stream.into_split();
+    (Box::new(read_half), Box::new(write_half))
+}
+
+/// Split a TLS stream
+#[cfg(feature = "tls")]
+pub(crate) fn split_stream<S>(stream: S) -> (ConnectionReadHalf, ConnectionWriteHalf)
+where
+    S: AsyncRead + AsyncWrite + Send + Sync + Unpin + 'static,
+{
+    let (read_half, write_half) = tokio::io::split(stream);
+    (Box::new(read_half), Box::new(write_half))
+}
@@ -1,13 +1,15 @@
+use std::num::NonZeroUsize;
 use std::time::Duration;
 
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
 
 use ockam_core::compat::rand::random;
-use ockam_core::{route, Result};
+use ockam_core::{route, Address, Result};
 use ockam_node::Context;
 use ockam_transport_tcp::{
-    TcpConnectionOptions, TcpInletOptions, TcpListenerOptions, TcpOutletOptions, TcpTransport,
+    TcpConnectionOptions, TcpConnectionPool, TcpInletOptions, TcpListenerOptions, TcpOutletOptions,
+    TcpTransport,
 };
 
 const LENGTH: usize = 32;
@@ -238,3 +240,433 @@ async fn portal__tcp_connection_with_invalid_message_flow__should_not_succeed(
 
     Ok(())
 }
+
+async fn wait_for_outlet_connections(tcp: &TcpTransport, outlet: &Address, expected: usize) {
+    for _ in 0..50 {
+        if tcp.registry().get_outlet_connections_count(outlet) == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("The outlet should have {expected} active connections");
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 10000)]
+async fn portal__open_and_close_connections__should_count_outlet_connections(
+    ctx: &mut Context,
+) -> Result<()> {
+    let tcp = TcpTransport::create(ctx).await?;
+    let outlet: Address = "outlet".into();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let bind_address = listener.local_addr().unwrap().to_string();
+    tcp.create_outlet(outlet.clone(), bind_address, TcpOutletOptions::new())
+        .await?;
+    let (inlet_addr, _) = tcp
+        .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+        .await?;
+    assert_eq!(tcp.registry().get_outlet_connections_count(&outlet), 0);
+
+    let (close_sender, close_receiver) = tokio::sync::oneshot::channel::<()>();
+    let handle = tokio::spawn(async move {
+        let (mut target_stream1, _) = listener.accept().await.unwrap();
+        let (target_stream2, _) = listener.accept().await.unwrap();
+
+        // the target closes the second connection
+        close_receiver.await.unwrap();
+        drop(target_stream2);
+
+        // wait until the first connection is closed by the client
+        let mut payload = [0u8; LENGTH];
+        while target_stream1.read(&mut payload).await.unwrap_or(0) != 0 {}
+    });
+
+    let client_stream1 = TcpStream::connect(inlet_addr).await.unwrap();
+    let _client_stream2 = TcpStream::connect(inlet_addr).await.unwrap();
+    wait_for_outlet_connections(&tcp, &outlet, 2).await;
+
+    // a connection closed by the target is not counted anymore
+    close_sender.send(()).unwrap();
+    wait_for_outlet_connections(&tcp, &outlet, 1).await;
+
+    // a connection closed by the client is not counted anymore
+    drop(client_stream1);
+    wait_for_outlet_connections(&tcp, &outlet, 0).await;
+
+    let res = handle.await;
+    assert!(res.is_ok());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 30000)]
+async fn portal__max_connections__should_bound_concurrent_connections(
+    ctx: &mut Context,
+) -> Result<()> {
+    const MAX_CONNECTIONS: usize = 2;
+    const CLIENTS: usize = 6;
+
+    let tcp = TcpTransport::create(ctx).await?;
+    let outlet: Address = "outlet".into();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let bind_address = listener.local_addr().unwrap().to_string();
+    tcp.create_outlet(
+        outlet.clone(),
+        bind_address,
+        TcpOutletOptions::new().with_max_connections(NonZeroUsize::new(MAX_CONNECTIONS).unwrap()),
+    )
+    .await?;
+    let (inlet_addr, _) = tcp
+        .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+        .await?;
+
+    // the target greets each connection and keeps it open until the client closes it
+    let greeting = generate_binary();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                write_binary(&mut stream, greeting).await;
+                let mut payload = [0u8; LENGTH];
+                while stream.read(&mut payload).await.unwrap_or(0) != 0 {}
+            });
+        }
+    });
+
+    // a burst of clients, each of them keeping its connection open for a while once served
+    let clients: Vec<_> = (0..CLIENTS)
+        .map(|_| {
+            tokio::spawn(async move {
+                let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+                let mut payload = [0u8; LENGTH];
+                stream.read_exact(&mut payload).await.unwrap();
+                assert_eq!(payload, greeting);
+                tokio::time::sleep(Duration::from_millis(300)).await;
+            })
+        })
+        .collect();
+
+    let mut peak = 0;
+    for client in clients {
+        while !client.is_finished() {
+            peak = peak.max(tcp.registry().get_outlet_connections_count(&outlet));
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(client.await.is_ok());
+    }
+
+    // all the clients were served, but never more than the maximum at the same time
+    assert_eq!(peak, MAX_CONNECTIONS);
+    wait_for_outlet_connections(&tcp, &outlet, 0).await;
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 15000)]
+async fn portal__max_connections__should_close_a_connection_waiting_for_too_long(
+    ctx: &mut Context,
+) -> Result<()> {
+    let tcp = TcpTransport::create(ctx).await?;
+    let outlet: Address = "outlet".into();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let bind_address = listener.local_addr().unwrap().to_string();
+    tcp.create_outlet(
+        outlet.clone(),
+        bind_address,
+        TcpOutletOptions::new()
+            .with_max_connections(NonZeroUsize::new(1).unwrap())
+            .with_max_connections_timeout(Duration::from_millis(500)),
+    )
+    .await?;
+    let (inlet_addr, _) = tcp
+        .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+        .await?;
+
+    let greeting = generate_binary();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                write_binary(&mut stream, greeting).await;
+                let mut payload = [0u8; LENGTH];
+                while stream.read(&mut payload).await.unwrap_or(0) != 0 {}
+            });
+        }
+    });
+
+    let mut first = TcpStream::connect(inlet_addr).await.unwrap();
+    read_assert_binary(&mut first, greeting).await;
+
+    // the second connection is closed since the first one stays open
+    let mut second = TcpStream::connect(inlet_addr).await.unwrap();
+    let mut payload = [0u8; LENGTH];
+    assert_eq!(second.read(&mut payload).await.unwrap_or(0), 0);
+    assert_eq!(tcp.registry().get_outlet_connections_count(&outlet), 1);
+
+    // a new connection is served once the first one is closed
+    drop(first);
+    wait_for_outlet_connections(&tcp, &outlet, 0).await;
+    let mut third = TcpStream::connect(inlet_addr).await.unwrap();
+    read_assert_binary(&mut third, greeting).await;
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 15000)]
+async fn portal__idle_timeout__should_close_idle_connections_only(ctx: &mut Context) -> Result<()> {
+    let tcp = TcpTransport::create(ctx).await?;
+    let outlet: Address = "outlet".into();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let bind_address = listener.local_addr().unwrap().to_string();
+    tcp.create_outlet(
+        outlet.clone(),
+        bind_address,
+        TcpOutletOptions::new().with_idle_timeout(Duration::from_secs(1)),
+    )
+    .await?;
+    let (inlet_addr, _) = tcp
+        .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+        .await?;
+
+    let handle = tokio::spawn(async move {
+        let (mut active_stream, _) = listener.accept().await.unwrap();
+        let (mut idle_stream, _) = listener.accept().await.unwrap();
+
+        // the idle connection is closed by the outlet
+        let mut payload = [0u8; LENGTH];
+        assert_eq!(idle_stream.read(&mut payload).await.unwrap(), 0);
+
+        // the active connection keeps working
+        for _ in 0..10 {
+            let payload = generate_binary();
+            write_binary(&mut active_stream, payload).await;
+        }
+    });
+
+    let mut active_stream = TcpStream::connect(inlet_addr).await.unwrap();
+    wait_for_outlet_connections(&tcp, &outlet, 1).await;
+    let mut idle_stream = TcpStream::connect(inlet_addr).await.unwrap();
+    wait_for_outlet_connections(&tcp, &outlet, 2).await;
+
+    // keep sending data on the active connection, for more than the idle timeout
+    for _ in 0..8 {
+        write_binary(&mut active_stream, generate_binary()).await;
+        tokio::time::sleep(Duration::from_millis(300)).await;
+    }
+    wait_for_outlet_connections(&tcp, &outlet, 1).await;
+
+    // the client of the idle connection sees it closed
+    let mut payload = [0u8; LENGTH];
+    assert_eq!(idle_stream.read(&mut payload).await.unwrap_or(0), 0);
+
+    let res = handle.await;
+    assert!(res.is_ok());
+
+    // data from the target still reaches the client of the active connection
+    let mut received = 0;
+    while received < 10 * LENGTH {
+        let length = active_stream.read(&mut payload).await.unwrap();
+        assert_ne!(length, 0, "The active connection should not be closed");
+        received += length;
+    }
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__port_range__should_forward_to_the_requested_port(ctx: &mut Context) -> Result<()> {
+    let tcp = TcpTransport::create(ctx).await?;
+
+    let listener1 = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let listener2 = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port1 = listener1.local_addr().unwrap().port();
+    let port2 = listener2.local_addr().unwrap().port();
+    tcp.create_outlet_port_range(
+        "outlet",
+        "127.0.0.1",
+        port1.min(port2)..=port1.max(port2),
+        TcpOutletOptions::new(),
+    )
+    .await?;
+
+    for (listener, port) in [(listener1, port1), (listener2, port2)] {
+        let payload1 = generate_binary();
+        let payload2 = generate_binary();
+        let (inlet_addr, _) = tcp
+            .create_inlet(
+                "127.0.0.1:0",
+                route!["outlet", port.to_string()],
+                TcpInletOptions::new(),
+            )
+            .await?;
+
+        let handle = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+
+            read_assert_binary(&mut stream, payload1).await;
+            write_binary(&mut stream, payload2).await;
+        });
+
+        let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+        write_binary(&mut stream, payload1).await;
+        read_assert_binary(&mut stream, payload2).await;
+
+        assert!(handle.await.is_ok());
+    }
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+/// Return the time it takes for a new client of an inlet to receive the greeting of the target
+async fn time_to_greeting(inlet_addr: std::net::SocketAddr, greeting: [u8; LENGTH]) -> Duration {
+    let start = std::time::Instant::now();
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    read_assert_binary(&mut stream, greeting).await;
+    start.elapsed()
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 10000)]
+async fn portal__connection_pool__should_serve_the_first_client_faster(
+    ctx: &mut Context,
+) -> Result<()> {
+    let tcp = TcpTransport::create(ctx).await?;
+    let greeting = generate_binary();
+
+    // the target greets each connection after a delay, like a service with a slow handshake
+    let greeting_delay = Duration::from_millis(500);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let bind_address = listener.local_addr().unwrap().to_string();
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                tokio::time::sleep(greeting_delay).await;
+                write_binary(&mut stream, greeting).await;
+                tokio::time::sleep(Duration::from_secs(10)).await;
+            });
+        }
+    });
+
+    tcp.create_outlet("cold", bind_address.clone(), TcpOutletOptions::new())
+        .await?;
+    tcp.create_outlet(
+        "warm",
+        bind_address,
+        TcpOutletOptions::new().with_connection_pool(1),
+    )
+    .await?;
+    let (cold_inlet, _) = tcp
+        .create_inlet("127.0.0.1:0", route!["cold"], TcpInletOptions::new())
+        .await?;
+    let (warm_inlet, _) = tcp
+        .create_inlet("127.0.0.1:0", route!["warm"], TcpInletOptions::new())
+        .await?;
+
+    // let the pool establish its connection and receive the greeting
+    tokio::time::sleep(greeting_delay * 2).await;
+
+    let cold = time_to_greeting(cold_inlet, greeting).await;
+    let warm = time_to_greeting(warm_inlet, greeting).await;
+    assert!(cold >= greeting_delay, "cold: {cold:?}");
+    assert!(warm < greeting_delay, "warm: {warm:?}");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 15000)]
+async fn portal__shared_connection_pool__should_reuse_a_released_connection(
+    ctx: &mut Context,
+) -> Result<()> {
+    let tcp = TcpTransport::create(ctx).await?;
+
+    // an echo server counting the connections it accepts
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let peer = listener.local_addr().unwrap();
+    let (accepted_tx, mut accepted) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            accepted_tx.send(()).unwrap();
+            tokio::spawn(async move {
+                let mut buffer = [0u8; LENGTH];
+                while let Ok(length) = stream.read(&mut buffer).await {
+                    if length == 0 || stream.write_all(&buffer[..length]).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    let pool = TcpConnectionPool::new(1, Duration::from_secs(60));
+    tcp.create_outlet(
+        "outlet",
+        peer.to_string(),
+        TcpOutletOptions::new().with_shared_connection_pool(pool.clone()),
+    )
+    .await?;
+    let (inlet_addr, _) = tcp
+        .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+        .await?;
+
+    for _ in 0..2 {
+        let payload = generate_binary();
+        let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+        write_binary(&mut stream, payload).await;
+        read_assert_binary(&mut stream, payload).await;
+        drop(stream);
+
+        // the connection to the peer is returned to the pool once the portal connection is closed
+        let mut released = false;
+        for _ in 0..50 {
+            if pool.idle_count(&peer) == 1 {
+                released = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(released, "the connection should be returned to the pool");
+    }
+
+    // both portal connections used the same connection to the peer
+    assert!(accepted.try_recv().is_ok());
+    assert!(accepted.try_recv().is_err());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use core::time::Duration;
+use std::path::PathBuf;
+
+use ockam_core::{route, Result, Routed, Worker};
+use ockam_identity::{secure_channels, SecureChannelListenerOptions, SecureChannelOptions};
+use ockam_node::Context;
+use ockam_transport_tcp::{
+    TcpConnectionOptions, TcpListenerOptions, TcpTlsClientOptions, TcpTlsServerOptions,
+    TcpTransport,
+};
+
+pub struct Echoer;
+
+#[ockam_core::worker]
+impl Worker for Echoer {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+/// Certificates of a test certificate authority and of a server named `localhost`
+fn tls_file(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("tls")
+        .join(name)
+}
+
+fn server_options() -> TcpTlsServerOptions {
+    TcpTlsServerOptions::from_pem_files(tls_file("server.pem"), tls_file("server.key")).unwrap()
+}
+
+fn client_options(server_name: &str) -> TcpTlsClientOptions {
+    TcpTlsClientOptions::from_pem_file(server_name, tls_file("ca.pem")).unwrap()
+}
+
+#[ockam_macros::test]
+async fn secure_channel_over_tls(ctx: &mut Context) -> Result<()> {
+    let tcp = TcpTransport::create(ctx).await?;
+    let tcp_listener_options = TcpListenerOptions::new().with_tls(server_options());
+    let tcp_listener = tcp.listen("127.0.0.1:0", tcp_listener_options).await?;
+
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+    let bob = identities_creation.create_identity().await?;
+    let alice = identities_creation.create_identity().await?;
+
+    let secure_channel_listener_options =
+        SecureChannelListenerOptions::new().as_consumer(tcp_listener.flow_control_id());
+    ctx.flow_controls().add_consumer(
+        "echoer",
+        &secure_channel_listener_options.spawner_flow_control_id(),
+    );
+    ctx.start_worker("echoer", Echoer).await?;
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            &bob.identifier(),
+            "listener",
+            secure_channel_listener_options,
+        )
+        .await?;
+
+    let connection = tcp
+        .connect(
+            format!("localhost:{}", tcp_listener.socket_address().port()),
+            TcpConnectionOptions::new().with_tls(client_options("localhost")),
+        )
+        .await?;
+    let channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            &alice.identifier(),
+            route![connection, "listener"],
+            SecureChannelOptions::new(),
+        )
+        .await?;
+
+    let reply: String = ctx
+        .send_and_receive(
+            route![channel.encryptor_address().clone(), "echoer"],
+            "Hello over TLS".to_string(),
+        )
+        .await?;
+    assert_eq!(reply, "Hello over TLS");
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn tls_connections_are_verified(ctx: &mut Context) -> Result<()> {
+    let tcp = TcpTransport::create(ctx).await?;
+    let options = TcpListenerOptions::new().with_tls(server_options());
+    ctx.flow_controls()
+        .add_consumer("echoer", &options.spawner_flow_control_id());
+    ctx.start_worker("echoer", Echoer).await?;
+    let listener = tcp.listen("127.0.0.1:0", options).await?;
+
+    // the certificate of the server is not valid for this name
+    let res = tcp
+        .connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new().with_tls(client_options("ockam.io")),
+        )
+        .await;
+    assert!(res.is_err());
+
+    // a connection without TLS can't be used to send messages to the listener
+    let connection = tcp
+        .connect(listener.socket_string(), TcpConnectionOptions::new())
+        .await?;
+    let res = ctx
+        .send_and_receive_extended::<String>(
+            route![connection, "echoer"],
+            "Hello".to_string(),
+            ockam_node::MessageSendReceiveOptions::new().with_timeout(Duration::from_secs(1)),
+        )
+        .await;
+    assert!(res.is_err());
+
+    // the listener still accepts TLS connections
+    let connection = tcp
+        .connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new().with_tls(client_options("localhost")),
+        )
+        .await?;
+    let reply: String = ctx
+        .send_and_receive(route![connection, "echoer"], "Hello".to_string())
+        .await?;
+    assert_eq!(reply, "Hello");
+
+    ctx.stop().await
+}
+
+#[test]
+fn invalid_tls_files_are_rejected() {
+    let res = TcpTlsServerOptions::from_pem_files(tls_file("server.pem"), tls_file("ca.pem"));
+    assert!(res.unwrap_err().to_string().contains("no private key"));
+    let res = TcpTlsClientOptions::from_pem_file("localhost", tls_file("server.key"));
+    assert!(res.unwrap_err().to_string().contains("no certificate"));
+    let res = TcpTlsClientOptions::from_pem_file("localhost", tls_file("missing.pem"));
+    assert!(res.unwrap_err().to_string().contains("cannot open"));
+}
@@ -39,6 +39,12 @@ pub trait EphemeralSecretsStore: SecretsStoreReader + Sync + Send {
 pub trait PersistentSecretsStore: SecretsStoreReader + Sync + Send {
     /// Generate a secret and persist it to long-term memory
     async fn create_persistent_secret(&self, attributes: SecretAttributes) -> Result<KeyId>;
+    /// Import a secret and persist it to long-term memory
+    async fn import_persistent_secret(
+        &self,
+        secret: Secret,
+        attributes: SecretAttributes,
+    ) -> Result<KeyId>;
     /// Remove a persistent secret from the vault.
     async fn delete_persistent_secret(&self, key_id: KeyId) -> Result<bool>;
 }
@@ -1,6 +1,7 @@
-use crate::{KeyId, PublicKey, SecretAttributes, Signature};
+use crate::{KeyId, PublicKey, Secret, SecretAttributes, Signature};
 use ockam_core::compat::boxed::Box;
-use ockam_core::{async_trait, Result};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, Error, Result};
 
 /// A SecurityModule provides several functions related to secrets:
 ///   - create and persist secrets
@@ -18,6 +19,16 @@ pub trait SecurityModule: Sync + Send {
     /// Create a new secret and return its key id
     async fn create_secret(&self, attributes: SecretAttributes) -> Result<KeyId>;
 
+    /// Persist an existing secret and return its key id.
+    /// Security modules which don't give access to their secrets, like KMSes, can't import secrets
+    async fn import_secret(&self, _secret: Secret, _attributes: SecretAttributes) -> Result<KeyId> {
+        Err(Error::new(
+            Origin::Vault,
+            Kind::Unsupported,
+            "this security module cannot import secrets",
+        ))
+    }
+
     /// Get the public key from a secret
     async fn get_public_key(&self, key_id: &KeyId) -> Result<PublicKey>;
 
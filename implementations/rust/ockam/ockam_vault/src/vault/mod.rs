@@ -11,6 +11,8 @@
 //! [`ockam_vault`]: https://docs.rs/ockam_vault/latest
 
 mod asymmetric_impl;
+#[cfg(feature = "std")]
+mod private_key;
 mod secrets_store_impl;
 mod signer_impl;
 mod symmetric_impl;
@@ -20,6 +22,8 @@ mod vault_builder;
 mod vault_error;
 mod vault_kms;
 
+#[cfg(feature = "std")]
+pub use private_key::*;
 pub use vault::*;
 pub use vault_builder::*;
 pub use vault_error::*;
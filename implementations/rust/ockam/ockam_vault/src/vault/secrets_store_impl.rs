@@ -76,6 +76,14 @@ impl PersistentSecretsStore for VaultSecretsStore {
         self.security_module.create_secret(attributes).await
     }
 
+    async fn import_persistent_secret(
+        &self,
+        secret: Secret,
+        attributes: SecretAttributes,
+    ) -> Result<KeyId> {
+        self.security_module.import_secret(secret, attributes).await
+    }
+
     /// Remove secret from in memory storage
     async fn delete_persistent_secret(&self, key_id: KeyId) -> Result<bool> {
         self.security_module.delete_secret(key_id).await
@@ -115,6 +123,10 @@ impl SecurityModule for VaultSecretsStore {
         self.security_module.create_secret(attributes).await
     }
 
+    async fn import_secret(&self, secret: Secret, attributes: SecretAttributes) -> Result<KeyId> {
+        self.security_module.import_secret(secret, attributes).await
+    }
+
     async fn get_public_key(&self, key_id: &KeyId) -> Result<PublicKey> {
         self.security_module.get_public_key(key_id).await
     }
@@ -156,6 +156,16 @@ impl PersistentSecretsStore for Vault {
             .await
     }
 
+    async fn import_persistent_secret(
+        &self,
+        secret: Secret,
+        attributes: SecretAttributes,
+    ) -> Result<KeyId> {
+        self.secrets_store
+            .import_persistent_secret(secret, attributes)
+            .await
+    }
+
     async fn delete_persistent_secret(&self, key_id: KeyId) -> Result<bool> {
         self.secrets_store.delete_persistent_secret(key_id).await
     }
@@ -45,6 +45,10 @@ pub enum VaultError {
     StorageError,
     /// Invalid Storage data
     InvalidStorageData,
+    /// A private key can't be decoded
+    InvalidPrivateKey(String),
+    /// The algorithm or the curve of a private key is not supported
+    UnsupportedPrivateKey(String),
 }
 
 impl ockam_core::compat::error::Error for VaultError {}
@@ -73,6 +77,11 @@ impl core::fmt::Display for VaultError {
             Self::InvalidSecretAttributes => write!(f, "invalid secret attributes"),
             Self::StorageError => write!(f, "invalid storage"),
             Self::InvalidStorageData => write!(f, "invalid storage data"),
+            Self::InvalidPrivateKey(reason) => write!(f, "invalid private key: {reason}"),
+            Self::UnsupportedPrivateKey(algorithm) => write!(
+                f,
+                "unsupported private key: {algorithm} keys are not supported, only Ed25519 and NIST P-256 keys can be imported"
+            ),
         }
     }
 }
@@ -90,6 +99,7 @@ impl From<VaultError> for Error {
             | InvalidPrivateKeyLen
             | InvalidX25519SecretLength => Kind::Misuse,
             UnknownEcdhKeyType | EntryNotFound(_) | SecretNotFound => Kind::NotFound,
+            UnsupportedPrivateKey(_) => Kind::Unsupported,
             _ => Kind::Invalid,
         };
 
@@ -39,6 +39,11 @@ impl SecurityModule for VaultSecurityModule {
     /// Generate fresh secret
     async fn create_secret(&self, attributes: SecretAttributes) -> Result<KeyId> {
         let secret = Self::create_secret_from_attributes(attributes)?;
+        self.import_secret(secret, attributes).await
+    }
+
+    /// Persist an existing secret
+    async fn import_secret(&self, secret: Secret, attributes: SecretAttributes) -> Result<KeyId> {
         let stored_secret = StoredSecret::create(secret.clone(), attributes)?;
         let key_id = Self::compute_key_id(&secret, &attributes).await?;
         self.storage.put(key_id.clone(), stored_secret).await?;